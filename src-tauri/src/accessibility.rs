@@ -0,0 +1,406 @@
+//! Accessibility announcements for VoiceOver
+//!
+//! xterm.js renders to a canvas, so VoiceOver can't see what happens in the
+//! terminal. Important events (bell, command finished, session closed) are
+//! posted as `NSAccessibility` announcements and emitted to the frontend as
+//! `accessibility-announcement` for its live region. The
+//! `accessibility_verbosity` setting controls which events are announced.
+//!
+//! The system "Reduce motion" and "Increase contrast" preferences are also
+//! tracked here. The `reduce_motion` / `increase_contrast` settings override
+//! them; the effective values are what animation and theme defaults follow.
+
+use crate::events;
+use crate::settings::{AppSettings, SettingsManager};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Manager, State};
+use tracing::debug;
+
+/// Minimum time between bell announcements from the same session
+const MIN_BELL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Which events are announced
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessibilityVerbosity {
+    /// No announcements
+    Off,
+    /// Failed commands and closed sessions
+    #[default]
+    Minimal,
+    /// Every finished command and the bell as well
+    Full,
+}
+
+/// A terminal event worth announcing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessibilityEvent {
+    Bell,
+    /// Reported by shell integration (`OSC 133 ; D ; exit_code`)
+    CommandFinished {
+        exit_code: Option<i32>,
+    },
+    SessionClosed {
+        exit_code: Option<i32>,
+    },
+}
+
+impl AccessibilityEvent {
+    /// Lowest verbosity at which the event is announced
+    fn min_verbosity(&self) -> AccessibilityVerbosity {
+        match self {
+            AccessibilityEvent::CommandFinished {
+                exit_code: Some(code),
+            } if *code != 0 => AccessibilityVerbosity::Minimal,
+            AccessibilityEvent::SessionClosed { .. } => AccessibilityVerbosity::Minimal,
+            _ => AccessibilityVerbosity::Full,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            AccessibilityEvent::Bell => "Bell".to_string(),
+            AccessibilityEvent::CommandFinished {
+                exit_code: Some(code),
+            } if *code != 0 => format!("Command failed with exit code {}", code),
+            AccessibilityEvent::CommandFinished { .. } => "Command finished".to_string(),
+            AccessibilityEvent::SessionClosed {
+                exit_code: Some(code),
+            } if *code != 0 => format!("Session ended with exit code {}", code),
+            AccessibilityEvent::SessionClosed { .. } => "Session ended".to_string(),
+        }
+    }
+
+    /// High-priority announcements interrupt VoiceOver's current speech
+    fn is_high_priority(&self) -> bool {
+        self.min_verbosity() == AccessibilityVerbosity::Minimal
+    }
+}
+
+/// Payload of the `accessibility-announcement` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Announcement {
+    pub session_id: String,
+    pub message: String,
+    pub high_priority: bool,
+}
+
+/// Motion and contrast preferences
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayPreferences {
+    pub reduce_motion: bool,
+    pub increase_contrast: bool,
+}
+
+/// Result of `get_display_preferences`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DisplayPreferencesReport {
+    /// Values after applying the settings overrides
+    #[serde(flatten)]
+    pub effective: DisplayPreferences,
+    /// Values reported by macOS
+    pub system: DisplayPreferences,
+}
+
+/// Current system motion and contrast preferences
+pub fn system_display_preferences() -> DisplayPreferences {
+    #[cfg(target_os = "macos")]
+    {
+        let (reduce_motion, increase_contrast) = crate::macos::accessibility_display_options();
+        DisplayPreferences {
+            reduce_motion,
+            increase_contrast,
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        DisplayPreferences::default()
+    }
+}
+
+/// Apply the settings overrides to the system preferences
+pub fn effective_display_preferences(
+    settings: &AppSettings,
+    system: DisplayPreferences,
+) -> DisplayPreferences {
+    DisplayPreferences {
+        reduce_motion: settings.reduce_motion.unwrap_or(system.reduce_motion),
+        increase_contrast: settings
+            .increase_contrast
+            .unwrap_or(system.increase_contrast),
+    }
+}
+
+fn display_preferences_report(app: &AppHandle) -> DisplayPreferencesReport {
+    let system = system_display_preferences();
+    let settings = app
+        .try_state::<Arc<SettingsManager>>()
+        .map(|settings| settings.get())
+        .unwrap_or_default();
+    DisplayPreferencesReport {
+        effective: effective_display_preferences(&settings, system),
+        system,
+    }
+}
+
+/// Re-read the display preferences and emit `display-preferences-changed`
+/// if the effective values changed (called when the window is shown and when
+/// settings change)
+pub fn refresh_display_preferences(app: &AppHandle) {
+    let report = display_preferences_report(app);
+    let changed = app
+        .try_state::<Arc<AccessibilityManager>>()
+        .is_some_and(|manager| manager.update_display_preferences(report.effective));
+    if changed {
+        debug!(preferences = ?report.effective, "Display preferences changed");
+        let _ = events::emit(app, report);
+    }
+}
+
+/// Parse the data of an OSC 133 sequence, returning the exit code of a
+/// "command finished" mark (`D` or `D;code`)
+pub fn parse_command_finished(data: &str) -> Option<Option<i32>> {
+    let mut params = data.split(';');
+    if params.next()? != "D" {
+        return None;
+    }
+    Some(params.next().and_then(|code| code.trim().parse().ok()))
+}
+
+/// Per-session announcement rate limiting and last known display preferences
+pub struct AccessibilityManager {
+    last_bell: Mutex<HashMap<String, Instant>>,
+    display_preferences: Mutex<Option<DisplayPreferences>>,
+}
+
+impl Default for AccessibilityManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AccessibilityManager {
+    pub fn new() -> Self {
+        Self {
+            last_bell: Mutex::new(HashMap::new()),
+            display_preferences: Mutex::new(None),
+        }
+    }
+
+    /// Store the effective display preferences, returning true if they
+    /// differ from the previous values (the first call only records them)
+    pub fn update_display_preferences(&self, preferences: DisplayPreferences) -> bool {
+        let previous = self.display_preferences.lock().replace(preferences);
+        previous.is_some_and(|previous| previous != preferences)
+    }
+
+    /// Record an event, returning false if it should be dropped (bell spam)
+    pub fn check_rate_limit(&self, session_id: &str, event: &AccessibilityEvent) -> bool {
+        if *event != AccessibilityEvent::Bell {
+            return true;
+        }
+        let now = Instant::now();
+        let mut last_bell = self.last_bell.lock();
+        match last_bell.get(session_id) {
+            Some(last) if now.duration_since(*last) < MIN_BELL_INTERVAL => false,
+            _ => {
+                last_bell.insert(session_id.to_string(), now);
+                true
+            }
+        }
+    }
+
+    /// Forget a session's state (e.g. when it exits)
+    pub fn remove_session(&self, session_id: &str) {
+        self.last_bell.lock().remove(session_id);
+    }
+}
+
+/// Whether an event is announced at the given verbosity
+fn should_announce(event: &AccessibilityEvent, verbosity: AccessibilityVerbosity) -> bool {
+    verbosity != AccessibilityVerbosity::Off && verbosity >= event.min_verbosity()
+}
+
+/// Announce a terminal event, if the verbosity setting includes it
+pub fn announce(app: &AppHandle, session_id: &str, event: AccessibilityEvent) {
+    let verbosity = app
+        .try_state::<Arc<SettingsManager>>()
+        .map(|settings| settings.get().accessibility_verbosity)
+        .unwrap_or_default();
+    if !should_announce(&event, verbosity) {
+        return;
+    }
+    if let Some(manager) = app.try_state::<Arc<AccessibilityManager>>() {
+        if !manager.check_rate_limit(session_id, &event) {
+            return;
+        }
+    }
+
+    let announcement = Announcement {
+        session_id: session_id.to_string(),
+        message: event.message(),
+        high_priority: event.is_high_priority(),
+    };
+    debug!(session_id = %session_id, message = %announcement.message, "Accessibility announcement");
+
+    #[cfg(target_os = "macos")]
+    {
+        let message = announcement.message.clone();
+        let high_priority = announcement.high_priority;
+        let _ = app.run_on_main_thread(move || post_native_announcement(&message, high_priority));
+    }
+
+    let _ = events::emit(app, announcement);
+}
+
+/// Effective and system motion/contrast preferences
+#[command]
+pub fn get_display_preferences(
+    app: AppHandle,
+    accessibility_manager: State<'_, Arc<AccessibilityManager>>,
+) -> Result<DisplayPreferencesReport, String> {
+    let report = display_preferences_report(&app);
+    accessibility_manager.update_display_preferences(report.effective);
+    Ok(report)
+}
+
+/// Ask VoiceOver to speak a message. Must run on the main thread.
+#[cfg(target_os = "macos")]
+fn post_native_announcement(message: &str, high_priority: bool) {
+    use objc2::runtime::AnyObject;
+    use objc2_app_kit::{
+        NSAccessibilityAnnouncementKey, NSAccessibilityAnnouncementRequestedNotification,
+        NSAccessibilityPostNotificationWithUserInfo, NSAccessibilityPriorityKey,
+        NSAccessibilityPriorityLevel, NSApplication,
+    };
+    use objc2_foundation::{MainThreadMarker, NSDictionary, NSNumber, NSString};
+
+    let Some(mtm) = MainThreadMarker::new() else {
+        return;
+    };
+    let app = NSApplication::sharedApplication(mtm);
+    let text = NSString::from_str(message);
+    let priority = NSNumber::new_isize(if high_priority {
+        NSAccessibilityPriorityLevel::High.0
+    } else {
+        NSAccessibilityPriorityLevel::Medium.0
+    });
+
+    let element: &AnyObject = &app;
+    let text: &AnyObject = &text;
+    let priority: &AnyObject = &priority;
+
+    // SAFETY: the keys are AppKit's user info keys and both values are valid
+    // objects; this runs on the main thread as AppKit requires.
+    unsafe {
+        let user_info = NSDictionary::<NSString, AnyObject>::from_slices(
+            &[NSAccessibilityAnnouncementKey, NSAccessibilityPriorityKey],
+            &[text, priority],
+        );
+        NSAccessibilityPostNotificationWithUserInfo(
+            element,
+            NSAccessibilityAnnouncementRequestedNotification,
+            Some(&*user_info),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command_finished() {
+        assert_eq!(parse_command_finished("D;0"), Some(Some(0)));
+        assert_eq!(parse_command_finished("D;127"), Some(Some(127)));
+        assert_eq!(parse_command_finished("D"), Some(None));
+        assert_eq!(parse_command_finished("A"), None);
+        assert_eq!(parse_command_finished("C;cmdline"), None);
+    }
+
+    #[test]
+    fn test_verbosity_filtering() {
+        let bell = AccessibilityEvent::Bell;
+        let success = AccessibilityEvent::CommandFinished { exit_code: Some(0) };
+        let failure = AccessibilityEvent::CommandFinished { exit_code: Some(1) };
+        let closed = AccessibilityEvent::SessionClosed { exit_code: None };
+
+        for event in [&bell, &success, &failure, &closed] {
+            assert!(!should_announce(event, AccessibilityVerbosity::Off));
+            assert!(should_announce(event, AccessibilityVerbosity::Full));
+        }
+        assert!(!should_announce(&bell, AccessibilityVerbosity::Minimal));
+        assert!(!should_announce(&success, AccessibilityVerbosity::Minimal));
+        assert!(should_announce(&failure, AccessibilityVerbosity::Minimal));
+        assert!(should_announce(&closed, AccessibilityVerbosity::Minimal));
+    }
+
+    #[test]
+    fn test_messages() {
+        assert_eq!(
+            AccessibilityEvent::CommandFinished { exit_code: Some(2) }.message(),
+            "Command failed with exit code 2"
+        );
+        assert_eq!(
+            AccessibilityEvent::CommandFinished { exit_code: None }.message(),
+            "Command finished"
+        );
+        assert_eq!(
+            AccessibilityEvent::SessionClosed { exit_code: Some(0) }.message(),
+            "Session ended"
+        );
+    }
+
+    #[test]
+    fn test_effective_display_preferences() {
+        let system = DisplayPreferences {
+            reduce_motion: true,
+            increase_contrast: false,
+        };
+        let mut settings = AppSettings::default();
+        assert_eq!(effective_display_preferences(&settings, system), system);
+
+        settings.reduce_motion = Some(false);
+        settings.increase_contrast = Some(true);
+        assert_eq!(
+            effective_display_preferences(&settings, system),
+            DisplayPreferences {
+                reduce_motion: false,
+                increase_contrast: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_display_preferences_change_detection() {
+        let manager = AccessibilityManager::new();
+        let reduced = DisplayPreferences {
+            reduce_motion: true,
+            increase_contrast: false,
+        };
+        assert!(!manager.update_display_preferences(DisplayPreferences::default()));
+        assert!(!manager.update_display_preferences(DisplayPreferences::default()));
+        assert!(manager.update_display_preferences(reduced));
+        assert!(!manager.update_display_preferences(reduced));
+    }
+
+    #[test]
+    fn test_bell_rate_limit() {
+        let manager = AccessibilityManager::new();
+        let bell = AccessibilityEvent::Bell;
+        assert!(manager.check_rate_limit("a", &bell));
+        assert!(!manager.check_rate_limit("a", &bell));
+        assert!(manager.check_rate_limit("b", &bell));
+        // Other events are never rate limited
+        let closed = AccessibilityEvent::SessionClosed { exit_code: None };
+        assert!(manager.check_rate_limit("a", &closed));
+
+        manager.remove_session("a");
+        assert!(manager.check_rate_limit("a", &bell));
+    }
+}
@@ -0,0 +1,149 @@
+//! Session activity and idle events
+//!
+//! The tray and the session switcher highlight sessions that were busy and
+//! went quiet, such as a long build that just finished. Each session's output
+//! batcher feeds its output to an `ActivityTracker`: the first output after a
+//! quiet spell emits `pty-activity`, and `activity_idle_seconds` without output
+//! after that emits `pty-idle` with how long the session was busy. Both follow
+//! the output frames they're about. An `activity_idle_seconds` of 0 turns the
+//! events off.
+
+use crate::events;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+/// Default `activity_idle_seconds`
+pub const DEFAULT_IDLE_SECONDS: u32 = 10;
+
+/// Longest allowed `activity_idle_seconds`
+pub const MAX_IDLE_SECONDS: u32 = 600;
+
+/// A change between producing output and being quiet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activity {
+    Active,
+    /// Quiet after being busy for `active_for` (first to last output)
+    Idle {
+        active_for: Duration,
+    },
+}
+
+/// Payload of the `pty-activity` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PtyActivity {
+    pub session_id: String,
+}
+
+/// Payload of the `pty-idle` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PtyIdle {
+    pub session_id: String,
+    /// Milliseconds from the first to the last output before going quiet
+    pub active_ms: u64,
+}
+
+/// Per-session activity state, owned by the output batcher
+#[derive(Debug)]
+pub struct ActivityTracker {
+    idle_after: Duration,
+    /// First and last output of the current burst, while active
+    burst: Option<(Instant, Instant)>,
+}
+
+impl ActivityTracker {
+    pub fn new(idle_after: Duration) -> Self {
+        Self {
+            idle_after,
+            burst: None,
+        }
+    }
+
+    /// Record output at `now`; `Active` if the session was quiet
+    pub fn output(&mut self, now: Instant) -> Option<Activity> {
+        match &mut self.burst {
+            Some((_, last)) => {
+                *last = now;
+                None
+            }
+            None => {
+                self.burst = Some((now, now));
+                Some(Activity::Active)
+            }
+        }
+    }
+
+    /// When the session goes idle without more output; `None` while idle
+    pub fn deadline(&self) -> Option<Instant> {
+        self.burst.map(|(_, last)| last + self.idle_after)
+    }
+
+    /// `Idle` once the session has been quiet for long enough
+    pub fn check(&mut self, now: Instant) -> Option<Activity> {
+        let (first, last) = self.burst?;
+        if now.saturating_duration_since(last) < self.idle_after {
+            return None;
+        }
+        self.burst = None;
+        Some(Activity::Idle {
+            active_for: last.saturating_duration_since(first),
+        })
+    }
+}
+
+/// Quiet time before `pty-idle` for the `activity_idle_seconds` setting; `None` when off
+pub fn idle_after(seconds: u32) -> Option<Duration> {
+    (seconds > 0).then(|| Duration::from_secs(u64::from(seconds.min(MAX_IDLE_SECONDS))))
+}
+
+/// Emit a session's activity event to all windows
+pub fn emit(app: &AppHandle, session_id: String, activity: Activity) {
+    let _ = match activity {
+        Activity::Active => events::emit(app, PtyActivity { session_id }),
+        Activity::Idle { active_for } => events::emit(
+            app,
+            PtyIdle {
+                session_id,
+                active_ms: active_for.as_millis() as u64,
+            },
+        ),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_reports_transitions() {
+        let mut tracker = ActivityTracker::new(Duration::from_secs(10));
+        let start = Instant::now();
+        assert_eq!(tracker.deadline(), None);
+        assert_eq!(tracker.check(start), None);
+
+        assert_eq!(tracker.output(start), Some(Activity::Active));
+        assert_eq!(tracker.output(start + Duration::from_secs(5)), None);
+        assert_eq!(tracker.deadline(), Some(start + Duration::from_secs(15)));
+        assert_eq!(tracker.check(start + Duration::from_secs(14)), None);
+        assert_eq!(
+            tracker.check(start + Duration::from_secs(15)),
+            Some(Activity::Idle {
+                active_for: Duration::from_secs(5)
+            })
+        );
+        // Reported once per burst
+        assert_eq!(tracker.check(start + Duration::from_secs(20)), None);
+        assert_eq!(tracker.deadline(), None);
+        assert_eq!(
+            tracker.output(start + Duration::from_secs(30)),
+            Some(Activity::Active)
+        );
+    }
+
+    #[test]
+    fn test_idle_after() {
+        assert_eq!(idle_after(0), None);
+        assert_eq!(idle_after(10), Some(Duration::from_secs(10)));
+        assert_eq!(idle_after(86_400), Some(Duration::from_secs(600)));
+    }
+}
@@ -0,0 +1,95 @@
+//! Answerback and terminal identification
+//!
+//! Some legacy hosts and BBS-style services send ENQ or a Device Attributes
+//! query on connect and expect a particular reply. The backend answers these
+//! from the `answerback`, `primary_device_attributes` and
+//! `secondary_device_attributes` settings; the frontend suppresses the replies
+//! xterm.js would send, so programs see exactly one.
+
+use crate::settings::AppSettings;
+
+/// Primary DA parameters xterm.js reports (VT100 with Advanced Video Option)
+pub const DEFAULT_PRIMARY_DEVICE_ATTRIBUTES: &str = "1;2";
+
+/// Secondary DA parameters xterm.js reports (xterm patch 276)
+pub const DEFAULT_SECONDARY_DEVICE_ATTRIBUTES: &str = "0;276;0";
+
+/// Maximum length of the answerback message, in characters
+pub const MAX_ANSWERBACK_LENGTH: usize = 64;
+
+/// Whether a DA setting is a valid parameter list such as `62;22`
+pub fn is_attribute_list(attributes: &str) -> bool {
+    !attributes.is_empty()
+        && attributes
+            .split(';')
+            .all(|param| !param.is_empty() && param.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Answerback message as it may be sent: control characters other than CR
+/// are dropped and the length is capped
+pub fn sanitize_answerback(answerback: &str) -> String {
+    answerback
+        .chars()
+        .filter(|&c| c == '\r' || !c.is_control())
+        .take(MAX_ANSWERBACK_LENGTH)
+        .collect()
+}
+
+/// Reply to ENQ, if an answerback message is set
+pub fn enq_response(settings: &AppSettings) -> Option<String> {
+    (!settings.answerback.is_empty()).then(|| settings.answerback.clone())
+}
+
+/// Reply to a Primary (`CSI c`) or Secondary (`CSI > c`) Device Attributes request
+pub fn device_attributes_response(settings: &AppSettings, secondary: bool) -> String {
+    if secondary {
+        format!("\x1b[>{}c", settings.secondary_device_attributes)
+    } else {
+        format!("\x1b[?{}c", settings.primary_device_attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_responses_match_xterm_js() {
+        let settings = AppSettings::default();
+        assert_eq!(enq_response(&settings), None);
+        assert_eq!(device_attributes_response(&settings, false), "\x1b[?1;2c");
+        assert_eq!(
+            device_attributes_response(&settings, true),
+            "\x1b[>0;276;0c"
+        );
+    }
+
+    #[test]
+    fn test_custom_responses() {
+        let mut settings = AppSettings::default();
+        settings.answerback = "microterm\r".to_string();
+        settings.primary_device_attributes = "62;22".to_string();
+        assert_eq!(enq_response(&settings).as_deref(), Some("microterm\r"));
+        assert_eq!(device_attributes_response(&settings, false), "\x1b[?62;22c");
+    }
+
+    #[test]
+    fn test_is_attribute_list() {
+        assert!(is_attribute_list("62;22"));
+        assert!(is_attribute_list("0"));
+        assert!(!is_attribute_list(""));
+        assert!(!is_attribute_list("62;"));
+        assert!(!is_attribute_list("?62"));
+        assert!(!is_attribute_list("1;2c\x1b"));
+    }
+
+    #[test]
+    fn test_sanitize_answerback() {
+        assert_eq!(sanitize_answerback("vt100\r"), "vt100\r");
+        assert_eq!(sanitize_answerback("a\x1b[2Jb\n"), "a[2Jb");
+        assert_eq!(
+            sanitize_answerback(&"x".repeat(100)).len(),
+            MAX_ANSWERBACK_LENGTH
+        );
+    }
+}
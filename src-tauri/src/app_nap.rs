@@ -0,0 +1,45 @@
+//! App Nap management
+//!
+//! macOS naps apps whose windows are hidden, throttling their timers and I/O.
+//! The panel is hidden most of the time, so a long build running in a session
+//! would slow to a crawl. While any session has a foreground command running
+//! the app holds an activity assertion that opts out of App Nap; once every
+//! session is back at its prompt the assertion is released and the app may
+//! nap again.
+
+use crate::pty::PtyManager;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::debug;
+
+/// How often sessions are checked for running commands
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Reason reported to the system for the activity assertion
+#[cfg(target_os = "macos")]
+const ACTIVITY_REASON: &str = "Running terminal commands";
+
+/// Start the background check that toggles App Nap
+pub fn start(app: AppHandle) {
+    thread::spawn(move || {
+        let mut nap_disabled = false;
+        loop {
+            thread::sleep(CHECK_INTERVAL);
+            let running = app
+                .try_state::<Arc<PtyManager>>()
+                .is_some_and(|pty_manager| pty_manager.has_running_command());
+            if running != nap_disabled {
+                nap_disabled = running;
+                set_app_nap_disabled(nap_disabled);
+            }
+        }
+    });
+}
+
+fn set_app_nap_disabled(disabled: bool) {
+    debug!(disabled, "App Nap opt-out changed");
+    #[cfg(target_os = "macos")]
+    crate::macos::set_app_nap_disabled(disabled, ACTIVITY_REASON);
+}
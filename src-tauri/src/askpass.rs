@@ -0,0 +1,164 @@
+//! sudo password prompts
+//!
+//! Quick commands and jobs have nobody watching their output, so a `sudo`
+//! password prompt in them would hang. At startup a small `SUDO_ASKPASS`
+//! helper is written to the app data directory and exported to every session
+//! and command; `sudo -A` (or any sudo without a terminal) runs it, and it asks
+//! for the password in a native dialog. The password goes from the dialog to
+//! sudo and never passes through the webview.
+//!
+//! In interactive sessions `enter_sudo_password` does the same for a prompt
+//! that is already showing: the dialog's result is written to the PTY, but
+//! only while terminal echo is off.
+
+use crate::lock::LockManager;
+use crate::pty::PtyManager;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use tauri::{command, State};
+use tracing::info;
+
+/// Environment variable sudo reads the helper path from
+pub const ASKPASS_ENV_VAR: &str = "SUDO_ASKPASS";
+
+/// File name of the helper in the app data directory
+const HELPER_NAME: &str = "sudo-askpass";
+
+/// AppleScript showing a password dialog; the prompt is the first argument
+const DIALOG_SCRIPT: &[&str] = &[
+    "on run argv",
+    r#"display dialog (item 1 of argv) default answer "" with hidden answer with title "microterm" with icon caution"#,
+    "text returned of result",
+    "end run",
+];
+
+/// Prompt used when sudo doesn't pass one
+const DEFAULT_PROMPT: &str = "Password:";
+
+/// Path of the installed helper, set once at startup
+static HELPER_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Contents of the helper script
+fn helper_script() -> String {
+    let mut script =
+        String::from("#!/bin/sh\n# microterm SUDO_ASKPASS helper\nexec /usr/bin/osascript");
+    for line in DIALOG_SCRIPT {
+        script.push_str(&format!(" \\\n  -e '{}'", line));
+    }
+    script.push_str(&format!(" \\\n  \"${{1:-{}}}\"\n", DEFAULT_PROMPT));
+    script
+}
+
+/// Write the helper into `dir`, replacing an outdated one
+pub fn install(dir: &Path) -> Result<PathBuf, String> {
+    let path = dir.join(HELPER_NAME);
+    let script = helper_script();
+    if std::fs::read_to_string(&path).ok().as_deref() != Some(script.as_str()) {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        std::fs::write(&path, script)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Failed to make {} executable: {}", path.display(), e))?;
+    }
+    let _ = HELPER_PATH.set(path.clone());
+    Ok(path)
+}
+
+/// The installed helper, if installing it succeeded
+pub fn helper_path() -> Option<&'static Path> {
+    HELPER_PATH.get().map(PathBuf::as_path)
+}
+
+/// Ask for a password in a native dialog; `None` when cancelled
+#[cfg(target_os = "macos")]
+async fn password_dialog(prompt: &str) -> Result<Option<String>, String> {
+    let mut cmd = tokio::process::Command::new("/usr/bin/osascript");
+    for line in DIALOG_SCRIPT {
+        cmd.args(["-e", line]);
+    }
+    let output = cmd
+        .arg(prompt)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run osascript: {}", e))?;
+    if !output.status.success() {
+        // -128 is "User canceled"
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("-128") {
+            return Ok(None);
+        }
+        return Err(format!("Password dialog failed: {}", stderr.trim()));
+    }
+    let password =
+        String::from_utf8(output.stdout).map_err(|_| "Password is not valid UTF-8".to_string())?;
+    Ok(Some(password.trim_end_matches('\n').to_string()))
+}
+
+#[cfg(not(target_os = "macos"))]
+async fn password_dialog(_prompt: &str) -> Result<Option<String>, String> {
+    Err("Password dialogs are only available on macOS".to_string())
+}
+
+/// Answer a sudo prompt in a session from a native password dialog. Returns
+/// false if the dialog was cancelled.
+#[command]
+pub async fn enter_sudo_password(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    lock_manager: State<'_, Arc<LockManager>>,
+    session_id: String,
+    prompt: Option<String>,
+) -> Result<bool, String> {
+    lock_manager.ensure_unlocked()?;
+    let prompt = prompt.unwrap_or_else(|| DEFAULT_PROMPT.to_string());
+    let Some(password) = password_dialog(&prompt).await? else {
+        info!(session_id = %session_id, "sudo password dialog cancelled");
+        return Ok(false);
+    };
+    // The app may have locked while the dialog was open
+    lock_manager.ensure_unlocked()?;
+    lock_manager.record_activity();
+    // With echo on there's no password prompt anymore (sudo timed out, or
+    // it's back at the shell), and the password would be shown and run
+    if pty_manager.echoes_input(&session_id)? != Some(false) {
+        info!(session_id = %session_id, "No password prompt showing, password not sent");
+        return Err("The session isn't waiting for a password".to_string());
+    }
+    pty_manager.write_to_session(&session_id, &format!("{}\r", password))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_helper_script() {
+        let script = helper_script();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        for line in DIALOG_SCRIPT {
+            assert!(script.contains(&format!("-e '{}'", line)));
+        }
+        assert!(script.ends_with("\"${1:-Password:}\"\n"));
+    }
+
+    #[test]
+    fn test_install() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = install(temp_dir.path()).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), helper_script());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+        // Reinstalling is a no-op
+        assert_eq!(install(temp_dir.path()).unwrap(), path);
+    }
+}
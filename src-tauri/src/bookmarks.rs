@@ -0,0 +1,169 @@
+//! Directory bookmarks
+//!
+//! A bookmark names a directory, optionally with the profile to start in
+//! instead of the one the directory resolves to. Bookmarks are kept in the
+//! session store. `open_bookmark` starts a shell there and, like launch
+//! commands, queues the session for the frontend and emits `session-opened`,
+//! so bookmarks opened from the tray menu's Bookmarks submenu or with a
+//! `microterm://bookmark/<id>` URL show up the same way.
+
+use crate::launch_command;
+use crate::pty::SessionOptions;
+use crate::recent_dirs::RecentDirsManager;
+use crate::session_store::SessionStore;
+use crate::usage_stats::UsageStatsManager;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{command, AppHandle, Manager, State};
+use tracing::{info, warn};
+
+/// URL scheme registered in `Info.plist`
+pub const URL_SCHEME: &str = "microterm";
+
+/// Prefix of the tray menu item IDs of bookmarks
+const MENU_ID_PREFIX: &str = "bookmark:";
+
+/// A bookmarked directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: i64,
+    pub name: String,
+    /// Absolute path of the directory
+    pub path: String,
+    /// Profile to start with instead of the directory's
+    pub profile: Option<String>,
+}
+
+/// Bookmark ID in a `microterm://bookmark/<id>` URL
+pub fn id_from_url(url: &str) -> Option<i64> {
+    let rest = url.strip_prefix(URL_SCHEME)?.strip_prefix("://bookmark/")?;
+    rest.trim_end_matches('/').parse().ok()
+}
+
+/// Tray menu item ID for a bookmark
+pub fn menu_id(id: i64) -> String {
+    format!("{}{}", MENU_ID_PREFIX, id)
+}
+
+/// Bookmark ID of a tray menu item, if it is one
+pub fn id_from_menu_id(menu_id: &str) -> Option<i64> {
+    menu_id.strip_prefix(MENU_ID_PREFIX)?.parse().ok()
+}
+
+/// Bookmarks for the tray menu; empty when the store isn't available
+pub fn list(app: &AppHandle) -> Vec<Bookmark> {
+    app.try_state::<Arc<SessionStore>>()
+        .and_then(|store| store.bookmarks().ok())
+        .unwrap_or_default()
+}
+
+/// Start a shell in a bookmark's directory, returning the session ID
+pub fn open(app: &AppHandle, id: i64) -> Result<String, String> {
+    let store = app
+        .try_state::<Arc<SessionStore>>()
+        .ok_or("Session store not available")?;
+    let bookmark = store.bookmark(id)?;
+    let cwd = PathBuf::from(&bookmark.path);
+    if !cwd.is_dir() {
+        return Err(format!("Not a directory: {}", bookmark.path));
+    }
+
+    let options = SessionOptions {
+        cwd: Some(cwd),
+        profile: bookmark.profile.clone(),
+        ..Default::default()
+    };
+    let session_id = launch_command::start(app, options, bookmark.name.clone())?;
+    info!(session_id = %session_id, bookmark = %bookmark.name, "Opened bookmark");
+    if let Some(recent_dirs) = app.try_state::<Arc<RecentDirsManager>>() {
+        recent_dirs.record(&bookmark.path);
+    }
+    if let Some(usage_stats) = app.try_state::<Arc<UsageStatsManager>>() {
+        usage_stats.record_feature("bookmarks")?;
+    }
+    Ok(session_id)
+}
+
+/// Rebuild the tray menu after the bookmarks changed
+fn refresh_tray(app: &AppHandle) {
+    if let Err(e) = crate::refresh_tray_menu(app) {
+        warn!("Failed to update the tray menu: {}", e);
+    }
+}
+
+#[command]
+pub fn list_bookmarks(
+    session_store: State<'_, Arc<SessionStore>>,
+) -> Result<Vec<Bookmark>, String> {
+    session_store.bookmarks()
+}
+
+/// Bookmark a directory; an empty `profile` means the directory's profile
+#[command]
+pub fn add_bookmark(
+    app: AppHandle,
+    session_store: State<'_, Arc<SessionStore>>,
+    name: String,
+    path: String,
+    profile: Option<String>,
+) -> Result<Bookmark, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Bookmark name is empty".to_string());
+    }
+    if !Path::new(&path).is_absolute() || !Path::new(&path).is_dir() {
+        return Err(format!("Not a directory: {}", path));
+    }
+    let profile = profile
+        .as_deref()
+        .map(str::trim)
+        .filter(|profile| !profile.is_empty());
+    let bookmark = session_store.add_bookmark(name, &path, profile)?;
+    refresh_tray(&app);
+    Ok(bookmark)
+}
+
+/// Remove a bookmark, returning false if there was none with the ID
+#[command]
+pub fn remove_bookmark(
+    app: AppHandle,
+    session_store: State<'_, Arc<SessionStore>>,
+    id: i64,
+) -> Result<bool, String> {
+    let removed = session_store.remove_bookmark(id)?;
+    if removed {
+        refresh_tray(&app);
+    }
+    Ok(removed)
+}
+
+/// Start a shell in a bookmark's directory; the session is shown through
+/// `session-opened` like those from the tray menu
+#[command]
+pub fn open_bookmark(app: AppHandle, id: i64) -> Result<String, String> {
+    open(&app, id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_id_from_url() {
+        assert_eq!(id_from_url("microterm://bookmark/12"), Some(12));
+        assert_eq!(id_from_url("microterm://bookmark/12/"), Some(12));
+        assert_eq!(id_from_url("microterm://bookmark/"), None);
+        assert_eq!(id_from_url("microterm://bookmark/code"), None);
+        assert_eq!(id_from_url("microterm://run/12"), None);
+        assert_eq!(id_from_url("https://bookmark/12"), None);
+    }
+
+    #[test]
+    fn test_menu_ids() {
+        assert_eq!(menu_id(3), "bookmark:3");
+        assert_eq!(id_from_menu_id(&menu_id(3)), Some(3));
+        assert_eq!(id_from_menu_id("quit"), None);
+        assert_eq!(id_from_menu_id("bookmark:x"), None);
+    }
+}
@@ -0,0 +1,120 @@
+//! Keep the Mac awake during long commands
+//!
+//! With the `caffeinate_during_commands` setting on, the app holds a power
+//! assertion preventing idle system sleep while any session (jobs included)
+//! has a foreground command matching an entry of `caffeinate_commands`, and
+//! releases it once none do. An entry matches when its first word is the
+//! program name and the remaining words are the command's leading arguments,
+//! so `cargo build` matches `cargo build --release` but not `cargo test`.
+
+use crate::pty::PtyManager;
+use crate::settings::SettingsManager;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+/// How often running commands are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default value of the `caffeinate_commands` setting
+pub const DEFAULT_CAFFEINATE_COMMANDS: &[&str] = &[
+    "rsync",
+    "scp",
+    "cargo build",
+    "cargo test",
+    "make",
+    "docker build",
+];
+
+/// Reason reported to the system for the power assertion
+#[cfg(target_os = "macos")]
+const ASSERTION_REASON: &str = "Running a long terminal command";
+
+/// Whether a command's arguments match a `caffeinate_commands` entry
+pub fn command_matches(pattern: &str, args: &[String]) -> bool {
+    let mut words = pattern.split_whitespace();
+    let (Some(program), Some(arg0)) = (words.next(), args.first()) else {
+        return false;
+    };
+    let name = Path::new(arg0)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(arg0);
+    let words: Vec<&str> = words.collect();
+    name == program
+        && args.len() > words.len()
+        && words.iter().zip(&args[1..]).all(|(word, arg)| word == arg)
+}
+
+/// Start the background check that takes and releases the power assertion
+pub fn start(app: AppHandle) {
+    thread::spawn(move || {
+        let mut preventing_sleep = false;
+        loop {
+            thread::sleep(CHECK_INTERVAL);
+            let prevent = should_prevent_sleep(&app);
+            if prevent != preventing_sleep && set_sleep_prevented(prevent) {
+                preventing_sleep = prevent;
+                info!(prevent, "Idle sleep prevention changed");
+            }
+        }
+    });
+}
+
+fn should_prevent_sleep(app: &AppHandle) -> bool {
+    let Some(settings) = app.try_state::<Arc<SettingsManager>>() else {
+        return false;
+    };
+    let settings = settings.get();
+    if !settings.caffeinate_during_commands {
+        return false;
+    }
+    let Some(pty_manager) = app.try_state::<Arc<PtyManager>>() else {
+        return false;
+    };
+    pty_manager.running_command_args().iter().any(|args| {
+        settings
+            .caffeinate_commands
+            .iter()
+            .any(|pattern| command_matches(pattern, args))
+    })
+}
+
+fn set_sleep_prevented(prevent: bool) -> bool {
+    #[cfg(target_os = "macos")]
+    if !crate::macos::set_sleep_prevented(prevent, ASSERTION_REASON) {
+        warn!("Failed to create power assertion");
+        return false;
+    }
+    #[cfg(not(target_os = "macos"))]
+    warn!(prevent, "Idle sleep prevention is only supported on macOS");
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_command_matches() {
+        let build = args(&["/Users/me/.cargo/bin/cargo", "build", "--release"]);
+        assert!(command_matches("cargo build", &build));
+        assert!(command_matches("cargo", &build));
+        assert!(!command_matches("cargo test", &build));
+        assert!(!command_matches("cargo build --release --locked", &build));
+        assert!(command_matches(
+            "rsync",
+            &args(&["rsync", "-a", "src", "dst"])
+        ));
+        assert!(!command_matches("rsync", &args(&["rsync-wrapper"])));
+        assert!(!command_matches("", &build));
+        assert!(!command_matches("cargo", &[]));
+    }
+}
@@ -0,0 +1,120 @@
+//! Terminal cell width of text
+//!
+//! How many cells a character occupies is not fully specified: East Asian
+//! "ambiguous" characters (box drawing, ①, ※) are one cell in Western locales
+//! but two in CJK ones, and emoji presentation sequences (a text-style
+//! character followed by VS16) are two cells in newer terminals only. The
+//! `unicode_width_mode` setting picks the convention; `get_settings` returns
+//! it and the frontend registers a matching xterm.js width provider
+//! (`unicodeWidth.ts`) when it creates a terminal.
+
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthChar;
+
+/// Variation selector 16: request emoji presentation for the preceding character
+const VS16: char = '\u{FE0F}';
+
+/// Width of East Asian ambiguous characters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmbiguousWidth {
+    /// One cell (Western convention, xterm.js default)
+    #[default]
+    Narrow,
+    /// Two cells (CJK convention)
+    Wide,
+}
+
+/// Cell width convention for ambiguous characters and emoji
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnicodeWidthMode {
+    #[serde(default)]
+    pub ambiguous: AmbiguousWidth,
+    /// Whether a character followed by VS16 takes two cells (e.g. `❤️`)
+    #[serde(default)]
+    pub emoji_vs16_wide: bool,
+}
+
+/// Cell width of a single character (controls and combining marks are zero)
+pub fn char_width(c: char, mode: &UnicodeWidthMode) -> usize {
+    let width = match mode.ambiguous {
+        AmbiguousWidth::Narrow => c.width(),
+        AmbiguousWidth::Wide => c.width_cjk(),
+    };
+    width.unwrap_or(0)
+}
+
+/// Cell width of a string
+pub fn str_width(text: &str, mode: &UnicodeWidthMode) -> usize {
+    let mut total = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let width = char_width(c, mode);
+        if mode.emoji_vs16_wide && width == 1 && chars.peek() == Some(&VS16) {
+            total += 2;
+        } else {
+            total += width;
+        }
+    }
+    total
+}
+
+/// Cell column of the character at `char_index` within a line
+pub fn cell_column(line: &str, char_index: usize, mode: &UnicodeWidthMode) -> usize {
+    let byte_index = line
+        .char_indices()
+        .nth(char_index)
+        .map_or(line.len(), |(i, _)| i);
+    str_width(&line[..byte_index], mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NARROW: UnicodeWidthMode = UnicodeWidthMode {
+        ambiguous: AmbiguousWidth::Narrow,
+        emoji_vs16_wide: false,
+    };
+
+    const WIDE: UnicodeWidthMode = UnicodeWidthMode {
+        ambiguous: AmbiguousWidth::Wide,
+        emoji_vs16_wide: true,
+    };
+
+    #[test]
+    fn test_ascii_and_cjk() {
+        assert_eq!(str_width("abc", &NARROW), 3);
+        assert_eq!(str_width("日本語", &NARROW), 6);
+        assert_eq!(str_width("日本語", &WIDE), 6);
+    }
+
+    #[test]
+    fn test_ambiguous_width() {
+        // Box drawing and circled digits are East Asian ambiguous
+        assert_eq!(str_width("─①", &NARROW), 2);
+        assert_eq!(str_width("─①", &WIDE), 4);
+    }
+
+    #[test]
+    fn test_emoji_vs16() {
+        let heart = "\u{2764}\u{FE0F}";
+        assert_eq!(str_width(heart, &NARROW), 1);
+        assert_eq!(str_width(heart, &WIDE), 2);
+        // Already-wide emoji are unaffected by VS16
+        assert_eq!(str_width("😀\u{FE0F}", &WIDE), 2);
+    }
+
+    #[test]
+    fn test_controls_and_combining_marks_are_zero_width() {
+        assert_eq!(str_width("e\u{301}", &NARROW), 1);
+        assert_eq!(char_width('\x07', &NARROW), 0);
+    }
+
+    #[test]
+    fn test_cell_column() {
+        assert_eq!(cell_column("日本語 error", 4, &NARROW), 7);
+        assert_eq!(cell_column("① error", 2, &WIDE), 3);
+        assert_eq!(cell_column("ab", 10, &NARROW), 2);
+    }
+}
@@ -0,0 +1,326 @@
+//! OSC 52 clipboard access
+//!
+//! Programs copy to the system clipboard with `OSC 52 ; c ; base64 ST`, which
+//! is how vim, tmux and friends copy over ssh; `OSC 52 ; c ; ? ST` asks for
+//! the clipboard's contents, answered with the same sequence. What programs
+//! may do is the `clipboard_access` setting: `allow`, `deny` or `ask`. When
+//! asking, the request is held back and `clipboard-access-requested` emitted;
+//! the frontend answers with `answer_clipboard_request`, optionally
+//! remembering the answer for the rest of the session.
+
+use crate::events;
+use crate::pty::PtyManager;
+use crate::settings::SettingsManager;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tauri::{command, AppHandle, Manager, State};
+use tracing::{debug, info, warn};
+
+/// Text copied or read at most, in bytes
+const MAX_CLIPBOARD_BYTES: usize = 1024 * 1024;
+
+/// Requests held back for an answer at most; the oldest is dropped
+const MAX_PENDING_REQUESTS: usize = 8;
+
+/// The `clipboard_access` setting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardAccess {
+    Allow,
+    Deny,
+    /// Ask the user each time (or once per session, if they choose)
+    #[default]
+    Ask,
+}
+
+/// What an OSC 52 sequence asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardRequest {
+    /// Copy text to the clipboard
+    Write(String),
+    /// Reply with the clipboard's contents
+    Read,
+}
+
+/// Kind of request, as reported to the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardOperation {
+    Write,
+    Read,
+}
+
+/// Payload of `clipboard-access-requested`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipboardAccessRequested {
+    /// ID to pass to `answer_clipboard_request`
+    pub request_id: u64,
+    pub session_id: String,
+    pub operation: ClipboardOperation,
+    /// Characters the program wants to copy; 0 for reads
+    pub length: usize,
+}
+
+/// Parse the data of an OSC 52 sequence (`selection ; payload`). Only the
+/// system clipboard exists here, so the selection is ignored.
+pub fn parse_osc52(data: &str) -> Option<ClipboardRequest> {
+    let (_selection, payload) = data.split_once(';')?;
+    if payload == "?" {
+        return Some(ClipboardRequest::Read);
+    }
+    // Base64 is 4 characters for every 3 bytes
+    if payload.is_empty() || payload.len() > MAX_CLIPBOARD_BYTES / 3 * 4 + 4 {
+        return None;
+    }
+    let bytes = BASE64.decode(payload).ok()?;
+    Some(ClipboardRequest::Write(
+        String::from_utf8_lossy(&bytes).into_owned(),
+    ))
+}
+
+/// Reply to a read: the clipboard's text as an OSC 52 sequence
+pub fn read_reply(text: &str) -> Option<String> {
+    (text.len() <= MAX_CLIPBOARD_BYTES).then(|| format!("\x1b]52;c;{}\x1b\\", BASE64.encode(text)))
+}
+
+/// A request held back until the user answers
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PendingRequest {
+    session_id: String,
+    request: ClipboardRequest,
+}
+
+/// Requests awaiting an answer and the answers remembered per session
+#[derive(Default)]
+pub struct ClipboardManager {
+    pending: Mutex<BTreeMap<u64, PendingRequest>>,
+    next_id: Mutex<u64>,
+    remembered: Mutex<HashMap<String, bool>>,
+}
+
+impl ClipboardManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Effective access for a session: `ask` follows the answer remembered
+    /// for the session, if any
+    fn access(&self, setting: ClipboardAccess, session_id: &str) -> ClipboardAccess {
+        match (setting, self.remembered.lock().get(session_id)) {
+            (ClipboardAccess::Ask, Some(true)) => ClipboardAccess::Allow,
+            (ClipboardAccess::Ask, Some(false)) => ClipboardAccess::Deny,
+            (access, _) => access,
+        }
+    }
+
+    /// Hold a request back, returning its ID
+    fn hold(&self, session_id: &str, request: ClipboardRequest) -> u64 {
+        let id = {
+            let mut next_id = self.next_id.lock();
+            *next_id += 1;
+            *next_id
+        };
+        let mut pending = self.pending.lock();
+        if pending.len() >= MAX_PENDING_REQUESTS {
+            pending.pop_first();
+        }
+        pending.insert(
+            id,
+            PendingRequest {
+                session_id: session_id.to_string(),
+                request,
+            },
+        );
+        id
+    }
+
+    fn take(&self, id: u64) -> Option<PendingRequest> {
+        self.pending.lock().remove(&id)
+    }
+
+    fn remember(&self, session_id: &str, allow: bool) {
+        self.remembered.lock().insert(session_id.to_string(), allow);
+    }
+}
+
+/// Carry out a request; returns the reply to a read
+fn perform(request: &ClipboardRequest) -> Option<String> {
+    match request {
+        ClipboardRequest::Write(text) => {
+            if !set_text(text) {
+                warn!("Failed to write the clipboard");
+            }
+            None
+        }
+        ClipboardRequest::Read => read_reply(&text()?),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn text() -> Option<String> {
+    crate::macos::clipboard_text()
+}
+
+#[cfg(target_os = "macos")]
+fn set_text(text: &str) -> bool {
+    crate::macos::set_clipboard_text(text)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn text() -> Option<String> {
+    None
+}
+
+#[cfg(not(target_os = "macos"))]
+fn set_text(_text: &str) -> bool {
+    false
+}
+
+/// React to an OSC 52 sequence from a session, following `clipboard_access`.
+/// Returns the reply to write back to the PTY, if any.
+pub fn handle_osc52(app: &AppHandle, session_id: &str, data: &str) -> Option<String> {
+    let request = parse_osc52(data)?;
+    let setting = app
+        .try_state::<Arc<SettingsManager>>()
+        .map(|settings| settings.get().clipboard_access)
+        .unwrap_or_default();
+    let manager = app.try_state::<Arc<ClipboardManager>>()?;
+    match manager.access(setting, session_id) {
+        ClipboardAccess::Allow => perform(&request),
+        ClipboardAccess::Deny => {
+            debug!(session_id = %session_id, "Clipboard access denied");
+            None
+        }
+        ClipboardAccess::Ask => {
+            let (operation, length) = match &request {
+                ClipboardRequest::Write(text) => (ClipboardOperation::Write, text.chars().count()),
+                ClipboardRequest::Read => (ClipboardOperation::Read, 0),
+            };
+            let request_id = manager.hold(session_id, request);
+            let _ = events::emit(
+                app,
+                ClipboardAccessRequested {
+                    request_id,
+                    session_id: session_id.to_string(),
+                    operation,
+                    length,
+                },
+            );
+            None
+        }
+    }
+}
+
+/// Answer a `clipboard-access-requested` event; with `remember`, the
+/// session's later requests get the same answer without asking
+#[command]
+pub fn answer_clipboard_request(
+    clipboard_manager: State<'_, Arc<ClipboardManager>>,
+    pty_manager: State<'_, Arc<PtyManager>>,
+    request_id: u64,
+    allow: bool,
+    remember: Option<bool>,
+) -> Result<(), String> {
+    let pending = clipboard_manager
+        .take(request_id)
+        .ok_or_else(|| format!("Clipboard request not found: {}", request_id))?;
+    if remember.unwrap_or(false) {
+        info!(session_id = %pending.session_id, allow, "Clipboard access remembered for the session");
+        clipboard_manager.remember(&pending.session_id, allow);
+    }
+    if !allow {
+        return Ok(());
+    }
+    if let Some(reply) = perform(&pending.request) {
+        pty_manager.write_to_session(&pending.session_id, &reply)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_osc52() {
+        assert_eq!(
+            parse_osc52("c;aGVsbG8="),
+            Some(ClipboardRequest::Write("hello".to_string()))
+        );
+        // tmux sends an empty selection
+        assert_eq!(
+            parse_osc52(";aGk="),
+            Some(ClipboardRequest::Write("hi".to_string()))
+        );
+        assert_eq!(parse_osc52("c;?"), Some(ClipboardRequest::Read));
+        assert_eq!(parse_osc52("c;"), None);
+        assert_eq!(parse_osc52("c;not base64!"), None);
+        assert_eq!(parse_osc52("aGVsbG8="), None);
+        let huge = "A".repeat(MAX_CLIPBOARD_BYTES / 3 * 4 + 8);
+        assert_eq!(parse_osc52(&format!("c;{}", huge)), None);
+    }
+
+    #[test]
+    fn test_read_reply() {
+        assert_eq!(
+            read_reply("hello").as_deref(),
+            Some("\x1b]52;c;aGVsbG8=\x1b\\")
+        );
+        assert_eq!(read_reply(&"x".repeat(MAX_CLIPBOARD_BYTES + 1)), None);
+    }
+
+    #[test]
+    fn test_remembered_answers() {
+        let manager = ClipboardManager::new();
+        assert_eq!(
+            manager.access(ClipboardAccess::Ask, "s1"),
+            ClipboardAccess::Ask
+        );
+        manager.remember("s1", true);
+        manager.remember("s2", false);
+        assert_eq!(
+            manager.access(ClipboardAccess::Ask, "s1"),
+            ClipboardAccess::Allow
+        );
+        assert_eq!(
+            manager.access(ClipboardAccess::Ask, "s2"),
+            ClipboardAccess::Deny
+        );
+        assert_eq!(
+            manager.access(ClipboardAccess::Ask, "s3"),
+            ClipboardAccess::Ask
+        );
+        // The setting wins over remembered answers
+        assert_eq!(
+            manager.access(ClipboardAccess::Deny, "s1"),
+            ClipboardAccess::Deny
+        );
+        assert_eq!(
+            manager.access(ClipboardAccess::Allow, "s2"),
+            ClipboardAccess::Allow
+        );
+    }
+
+    #[test]
+    fn test_pending_requests_are_bounded() {
+        let manager = ClipboardManager::new();
+        let first = manager.hold("s1", ClipboardRequest::Read);
+        for _ in 0..MAX_PENDING_REQUESTS {
+            manager.hold("s1", ClipboardRequest::Write("x".to_string()));
+        }
+        assert_eq!(manager.take(first), None);
+        let last = manager.hold("s2", ClipboardRequest::Read);
+        assert_eq!(
+            manager.take(last),
+            Some(PendingRequest {
+                session_id: "s2".to_string(),
+                request: ClipboardRequest::Read,
+            })
+        );
+        assert_eq!(manager.take(last), None);
+    }
+}
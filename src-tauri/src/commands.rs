@@ -1,16 +1,29 @@
 //! Command execution module for µTerm
 //!
 //! Provides synchronous and streaming command execution capabilities.
-
+//! Finished commands are recorded in the history with their invocation, so
+//! `rerun_history_entry` can run one again in the same directory and
+//! environment. Either can be sandboxed, see `sandbox.rs`.
+
+use crate::askpass;
+use crate::events::{self, CommandComplete};
+use crate::i18n;
+use crate::profiles;
+use crate::sandbox::{self, SandboxLevel};
+use crate::scripting::{HookEvent, ScriptManager};
+use crate::session_store::{CommandInvocation, SessionStore};
+use crate::usage_stats::UsageStatsManager;
+use crate::utf8::Utf8Decoder;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::process::Stdio;
-use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
 use std::time::{Duration, Instant};
-use tauri::{command, AppHandle, Emitter, Manager};
-use tokio::io::AsyncReadExt;
+use tauri::{command, AppHandle, Manager, State};
 use tokio::io::BufReader as TokioBufReader;
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::process::Command as TokioCommand;
 
 /// Buffer size for reading command output streams
@@ -25,6 +38,9 @@ const MAX_ARG_LENGTH: usize = 4096;
 /// Maximum number of arguments allowed
 const MAX_ARGS_COUNT: usize = 100;
 
+/// Environment variables kept in history entries and restored on rerun
+pub const SNAPSHOT_ENV_VARS: &[&str] = &["PATH", "LANG", "LC_ALL", "LC_CTYPE", "TZ"];
+
 /// Common shell built-in commands for tab completion
 const BUILTIN_COMMANDS: &[&str] = &[
     "alias", "cat", "cd", "clear", "cp", "echo", "exit", "export", "find", "grep", "help",
@@ -65,19 +81,29 @@ impl CompletionCache {
 static COMPLETION_CACHE: LazyLock<RwLock<CompletionCache>> =
     LazyLock::new(|| RwLock::new(CompletionCache::new()));
 
+/// Error message for a command that couldn't be started
+pub(crate) fn spawn_error(cmd: &str, e: &std::io::Error) -> String {
+    match e.kind() {
+        std::io::ErrorKind::NotFound => i18n::tr("command.not_found", &[("cmd", &cmd)]),
+        std::io::ErrorKind::PermissionDenied => {
+            i18n::tr("command.permission_denied", &[("cmd", &cmd)])
+        }
+        _ => i18n::tr("command.failed", &[("cmd", &cmd), ("error", e)]),
+    }
+}
+
 /// Validate a command string for security
-fn validate_command(cmd: &str) -> Result<(), String> {
+pub(crate) fn validate_command(cmd: &str) -> Result<(), String> {
     // Check for empty command
     if cmd.is_empty() {
-        return Err("Command cannot be empty".to_string());
+        return Err(i18n::tr("command.empty", &[]));
     }
 
     // Check command length
     if cmd.len() > MAX_COMMAND_LENGTH {
-        return Err(format!(
-            "Command too long: {} chars (max {})",
-            cmd.len(),
-            MAX_COMMAND_LENGTH
+        return Err(i18n::tr(
+            "command.too_long",
+            &[("len", &cmd.len()), ("max", &MAX_COMMAND_LENGTH)],
         ));
     }
 
@@ -90,51 +116,48 @@ fn validate_command(cmd: &str) -> Result<(), String> {
                 '\0' => "\\0".to_string(),
                 other => other.to_string(),
             };
-            return Err(format!(
-                "Command contains forbidden character '{}'. Use proper arguments instead of shell syntax.",
-                char_display
+            return Err(i18n::tr(
+                "command.forbidden_char",
+                &[("char", &char_display)],
             ));
         }
     }
 
     // Check that command doesn't start with a dash (option injection)
     if cmd.starts_with('-') {
-        return Err("Command cannot start with '-'".to_string());
+        return Err(i18n::tr("command.starts_with_dash", &[]));
     }
 
     // Check for path traversal attempts in command name
     if cmd.contains("..") {
-        return Err("Command cannot contain '..' path traversal".to_string());
+        return Err(i18n::tr("command.path_traversal", &[]));
     }
 
     Ok(())
 }
 
 /// Validate arguments for security
-fn validate_args(args: &[String]) -> Result<(), String> {
+pub(crate) fn validate_args(args: &[String]) -> Result<(), String> {
     // Check argument count
     if args.len() > MAX_ARGS_COUNT {
-        return Err(format!(
-            "Too many arguments: {} (max {})",
-            args.len(),
-            MAX_ARGS_COUNT
+        return Err(i18n::tr(
+            "args.too_many",
+            &[("count", &args.len()), ("max", &MAX_ARGS_COUNT)],
         ));
     }
 
     // Validate each argument
     for (i, arg) in args.iter().enumerate() {
         if arg.len() > MAX_ARG_LENGTH {
-            return Err(format!(
-                "Argument {} too long: {} chars (max {})",
-                i,
-                arg.len(),
-                MAX_ARG_LENGTH
+            return Err(i18n::tr(
+                "args.too_long",
+                &[("index", &i), ("len", &arg.len()), ("max", &MAX_ARG_LENGTH)],
             ));
         }
 
         // Check for null bytes which could cause truncation
         if arg.contains('\0') {
-            return Err(format!("Argument {} contains null byte", i));
+            return Err(i18n::tr("args.null_byte", &[("index", &i)]));
         }
     }
 
@@ -150,132 +173,254 @@ pub struct CommandResult {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamChunk {
+    /// ID of the `execute_command_stream` call the chunk belongs to
+    pub execution_id: String,
     pub chunk: String,
     pub is_stderr: bool,
+    /// Position among both streams' chunks in merged mode, starting at 0
+    pub seq: Option<u64>,
 }
 
-#[command]
-pub async fn execute_command(cmd: String, args: Vec<String>) -> Result<CommandResult, String> {
-    use std::process::Command;
-
-    // Validate command and arguments for security
-    validate_command(&cmd)?;
-    validate_args(&args)?;
+/// Emit one output stream of a command as chunks, returning the bytes read.
+/// With `sequence` (merged mode, shared by both streams) each chunk is
+/// numbered as it's read.
+fn spawn_stream_reader<R>(
+    app: AppHandle,
+    stream: R,
+    execution_id: String,
+    is_stderr: bool,
+    sequence: Option<Arc<AtomicU64>>,
+) -> tokio::task::JoinHandle<u64>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    // Read in chunks for real-time streaming with error logging
+    tokio::spawn(async move {
+        let mut reader = TokioBufReader::new(stream);
+        let mut buffer = vec![0u8; STREAM_BUFFER_SIZE];
+        let mut bytes = 0;
+        let mut decoder = Utf8Decoder::new();
+        let name = if is_stderr { "stderr" } else { "stdout" };
+        let emit_chunk = |chunk: String| {
+            let seq = sequence
+                .as_ref()
+                .map(|sequence| sequence.fetch_add(1, Ordering::SeqCst));
+            events::emit(
+                &app,
+                StreamChunk {
+                    execution_id: execution_id.clone(),
+                    chunk,
+                    is_stderr,
+                    seq,
+                },
+            )
+        };
 
-    // Execute command with proper error handling
-    let output = Command::new(&cmd)
-        .args(&args)
-        .output()
-        .map_err(|e| {
-            // Provide more specific error messages
-            match e.kind() {
-                std::io::ErrorKind::NotFound => {
-                    format!("Command not found: '{}'. Make sure the command is installed and in your PATH.", cmd)
+        loop {
+            match reader.read(&mut buffer).await {
+                Ok(0) => {
+                    // The stream may end in the middle of a character
+                    let rest = decoder.finish();
+                    if !rest.is_empty() {
+                        let _ = emit_chunk(rest);
+                    }
+                    break;
+                }
+                Ok(n) => {
+                    bytes += n as u64;
+                    // A character cut off at the end of the read is completed by the next
+                    let chunk = decoder.decode(&buffer[..n]);
+                    if chunk.is_empty() {
+                        continue;
+                    }
+                    if let Err(e) = emit_chunk(chunk) {
+                        eprintln!("Failed to emit {} event: {}", name, e);
+                        break;
+                    }
                 }
-                std::io::ErrorKind::PermissionDenied => {
-                    format!("Permission denied: '{}'. You may need to run this command with elevated privileges.", cmd)
+                Err(e) => {
+                    eprintln!("Error reading {}: {}", name, e);
+                    break;
                 }
-                _ => format!("Failed to execute '{}': {}", cmd, e),
             }
-        })?;
+        }
+        bytes
+    })
+}
+
+/// Notify user scripts that a command finished
+fn dispatch_command_finished_hook(app: &AppHandle, cmd: &str, args: &[String], exit_code: i32) {
+    if let Some(script_manager) = app.try_state::<Arc<ScriptManager>>() {
+        script_manager.dispatch(
+            HookEvent::CommandFinished,
+            serde_json::json!({ "cmd": cmd, "args": args, "exit_code": exit_code }),
+        );
+    }
+}
+
+/// How a command run now is invoked: the app's working directory, the
+/// `SNAPSHOT_ENV_VARS` that are set and the profile matching the directory
+fn capture_invocation(
+    app: &AppHandle,
+    cmd: &str,
+    args: &[String],
+    sandbox: SandboxLevel,
+) -> CommandInvocation {
+    let cwd = std::env::current_dir()
+        .ok()
+        .map(|dir| dir.to_string_lossy().into_owned());
+    let env = SNAPSHOT_ENV_VARS
+        .iter()
+        .filter_map(|name| Some((name.to_string(), std::env::var(name).ok()?)))
+        .collect();
+    let profile = cwd
+        .as_deref()
+        .and_then(|dir| profiles::resolve_for_dir(app, dir))
+        .map(|profile| profile.name);
+    CommandInvocation {
+        cmd: cmd.to_string(),
+        args: args.to_vec(),
+        cwd,
+        env,
+        profile,
+        sandbox,
+    }
+}
+
+/// Program and arguments to spawn for an invocation, sandboxed as requested
+fn sandboxed(invocation: &CommandInvocation) -> Result<(String, Vec<String>), String> {
+    let home = std::env::var("HOME").ok();
+    sandbox::wrap(
+        invocation.sandbox,
+        &invocation.cmd,
+        &invocation.args,
+        &invocation.env,
+        home.as_deref(),
+    )
+}
+
+/// Add a finished command to the persisted history
+fn record_command_history(app: &AppHandle, invocation: &CommandInvocation, exit_code: i32) {
+    if let Some(store) = app.try_state::<Arc<SessionStore>>() {
+        store.record_command(invocation, exit_code);
+    }
+}
+
+/// Run an invocation to completion, collecting its output
+fn run_invocation(
+    app: &AppHandle,
+    invocation: &CommandInvocation,
+) -> Result<CommandResult, String> {
+    use std::process::Command;
+
+    let CommandInvocation { cmd, args, .. } = invocation;
+    let (program, program_args) = sandboxed(invocation)?;
+    let mut command = Command::new(program);
+    command
+        .args(program_args)
+        .envs(invocation.env.iter().map(|(name, value)| (name, value)))
+        .envs(askpass::helper_path().map(|path| (askpass::ASKPASS_ENV_VAR, path)));
+    if let Some(cwd) = &invocation.cwd {
+        command.current_dir(cwd);
+    }
+
+    // Execute command with proper error handling
+    let output = command.output().map_err(|e| spawn_error(cmd, &e))?;
+
+    let exit_code = output.status.code().unwrap_or(0);
+    dispatch_command_finished_hook(app, cmd, args, exit_code);
+    record_command_history(app, invocation, exit_code);
+    if let Some(usage_stats) = app.try_state::<Arc<UsageStatsManager>>() {
+        usage_stats.record_commands(1);
+    }
 
     Ok(CommandResult {
         stdout: String::from_utf8_lossy(&output.stdout).to_string(),
         stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-        exit_code: output.status.code().unwrap_or(0),
+        exit_code,
     })
 }
 
+/// Run a command to completion; `sandbox` restricts what it can access (see
+/// `sandbox.rs`)
+#[command]
+pub async fn execute_command(
+    app: AppHandle,
+    cmd: String,
+    args: Vec<String>,
+    sandbox: Option<SandboxLevel>,
+) -> Result<CommandResult, String> {
+    // Validate command and arguments for security
+    validate_command(&cmd)?;
+    validate_args(&args)?;
+
+    let invocation = capture_invocation(&app, &cmd, &args, sandbox.unwrap_or_default());
+    run_invocation(&app, &invocation)
+}
+
+/// Run a history entry again with the arguments, working directory and
+/// environment it was first run with; the run is added to the history as a
+/// new entry. Entries whose command contained secrets can't be rerun.
+#[command]
+pub async fn rerun_history_entry(
+    app: AppHandle,
+    session_store: State<'_, Arc<SessionStore>>,
+    id: i64,
+) -> Result<CommandResult, String> {
+    let invocation = session_store.invocation(id)?;
+    validate_command(&invocation.cmd)?;
+    validate_args(&invocation.args)?;
+    if let Some(cwd) = &invocation.cwd {
+        if !std::path::Path::new(cwd).is_dir() {
+            return Err(format!("Directory no longer exists: {}", cwd));
+        }
+    }
+    run_invocation(&app, &invocation)
+}
+
+/// Run a command, emitting its output as `command-stdout` / `command-stderr`
+/// chunks and `command-complete:{execution_id}` when it exits. Callers pass
+/// `execution_id` to tell their events apart from other invocations'; one is
+/// generated otherwise. In `merged` mode chunks carry a `seq` numbering them
+/// across both streams, so the output can be shown in the order it was read.
+/// `sandbox` restricts what the command can access (see `sandbox.rs`).
 #[command]
 pub async fn execute_command_stream(
     app: AppHandle,
     cmd: String,
     args: Vec<String>,
+    execution_id: Option<String>,
+    merged: Option<bool>,
+    sandbox: Option<SandboxLevel>,
 ) -> Result<i32, String> {
     // Validate command and arguments for security
     validate_command(&cmd)?;
     validate_args(&args)?;
-
-    let mut child = TokioCommand::new(&cmd)
-        .args(&args)
+    let execution_id = execution_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let invocation = capture_invocation(&app, &cmd, &args, sandbox.unwrap_or_default());
+    let (program, program_args) = sandboxed(&invocation)?;
+    let started = Instant::now();
+
+    let mut child = TokioCommand::new(program)
+        .args(program_args)
+        .envs(askpass::helper_path().map(|path| (askpass::ASKPASS_ENV_VAR, path)))
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
-        .map_err(|e| {
-            match e.kind() {
-                std::io::ErrorKind::NotFound => {
-                    format!("Command not found: '{}'. Make sure the command is installed and in your PATH.", cmd)
-                }
-                std::io::ErrorKind::PermissionDenied => {
-                    format!("Permission denied: '{}'. You may need to run this command with elevated privileges.", cmd)
-                }
-                _ => format!("Failed to execute '{}': {}", cmd, e),
-            }
-        })?;
+        .map_err(|e| spawn_error(&cmd, &e))?;
 
     let stdout = child.stdout.take().unwrap();
     let stderr = child.stderr.take().unwrap();
-
-    let app_stdout = app.clone();
-    let app_stderr = app.clone();
-
-    // Handle stdout - read in chunks for real-time streaming with error logging
-    tokio::spawn(async move {
-        let mut reader = TokioBufReader::new(stdout);
-        let mut buffer = vec![0u8; STREAM_BUFFER_SIZE];
-
-        loop {
-            match reader.read(&mut buffer).await {
-                Ok(0) => break, // EOF
-                Ok(n) => {
-                    let chunk = String::from_utf8_lossy(&buffer[..n]).to_string();
-                    if let Err(e) = app_stdout.emit(
-                        "command-stdout",
-                        StreamChunk {
-                            chunk,
-                            is_stderr: false,
-                        },
-                    ) {
-                        eprintln!("Failed to emit stdout event: {}", e);
-                        break;
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error reading stdout: {}", e);
-                    break;
-                }
-            }
-        }
-    });
-
-    // Handle stderr - read in chunks for real-time streaming with error logging
-    tokio::spawn(async move {
-        let mut reader = TokioBufReader::new(stderr);
-        let mut buffer = vec![0u8; STREAM_BUFFER_SIZE];
-
-        loop {
-            match reader.read(&mut buffer).await {
-                Ok(0) => break, // EOF
-                Ok(n) => {
-                    let chunk = String::from_utf8_lossy(&buffer[..n]).to_string();
-                    if let Err(e) = app_stderr.emit(
-                        "command-stderr",
-                        StreamChunk {
-                            chunk,
-                            is_stderr: true,
-                        },
-                    ) {
-                        eprintln!("Failed to emit stderr event: {}", e);
-                        break;
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error reading stderr: {}", e);
-                    break;
-                }
-            }
-        }
-    });
+    let sequence = merged.unwrap_or(false).then(|| Arc::new(AtomicU64::new(0)));
+    let stdout_task = spawn_stream_reader(
+        app.clone(),
+        stdout,
+        execution_id.clone(),
+        false,
+        sequence.clone(),
+    );
+    let stderr_task =
+        spawn_stream_reader(app.clone(), stderr, execution_id.clone(), true, sequence);
 
     // Wait for command to complete
     let exit_code = child
@@ -283,10 +428,24 @@ pub async fn execute_command_stream(
         .await
         .map_err(|e| format!("Failed to wait for command: {}", e))?;
 
-    // Emit completion event
-    let _ = app.emit("command-complete", exit_code.code().unwrap_or(0));
-
-    Ok(exit_code.code().unwrap_or(0))
+    // Emit completion event once all output has been emitted
+    let exit_code = exit_code.code().unwrap_or(0);
+    let stdout_bytes = stdout_task.await.unwrap_or(0);
+    let stderr_bytes = stderr_task.await.unwrap_or(0);
+    let _ = events::emit(
+        &app,
+        CommandComplete {
+            execution_id,
+            exit_code,
+            duration_ms: started.elapsed().as_millis() as u64,
+            stdout_bytes,
+            stderr_bytes,
+        },
+    );
+    dispatch_command_finished_hook(&app, &cmd, &args, exit_code);
+    record_command_history(&app, &invocation, exit_code);
+
+    Ok(exit_code)
 }
 
 /// Refresh the completion cache by scanning PATH directories
@@ -569,22 +728,28 @@ mod tests {
     #[test]
     fn test_stream_chunk_serialization() {
         let chunk = StreamChunk {
+            execution_id: "exec-1".to_string(),
             chunk: "data".to_string(),
             is_stderr: false,
+            seq: None,
         };
 
         let json = serde_json::to_string(&chunk).unwrap();
         let deserialized: StreamChunk = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.execution_id, "exec-1");
         assert_eq!(deserialized.chunk, "data");
         assert!(!deserialized.is_stderr);
 
         let stderr_chunk = StreamChunk {
+            execution_id: "exec-1".to_string(),
             chunk: "error".to_string(),
             is_stderr: true,
+            seq: Some(3),
         };
         let json = serde_json::to_string(&stderr_chunk).unwrap();
         let deserialized: StreamChunk = serde_json::from_str(&json).unwrap();
         assert!(deserialized.is_stderr);
+        assert_eq!(deserialized.seq, Some(3));
     }
 
     // ============== Constants validation ==============
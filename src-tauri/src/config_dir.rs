@@ -0,0 +1,117 @@
+//! Config directory override
+//!
+//! Settings, screen configs and all other stored data normally live in the
+//! app data directory. `--config-dir <dir>` (or `--config-dir=<dir>`) or the
+//! `MICROTERM_CONFIG_DIR` environment variable point them somewhere else, so a
+//! second instance can run with a separate profile for testing and demos. The
+//! argument wins over the variable; relative paths are resolved against the
+//! working directory. The webview's own storage is not redirected.
+
+use std::path::{Path, PathBuf};
+use tauri::{App, Manager};
+use tracing::info;
+
+/// Environment variable overriding the config directory
+pub const CONFIG_DIR_ENV_VAR: &str = "MICROTERM_CONFIG_DIR";
+
+/// Launch argument overriding the config directory
+pub const CONFIG_DIR_ARG: &str = "--config-dir";
+
+/// Directory requested by the launch arguments (without the program name) or
+/// the environment variable's value
+fn requested_dir(args: &[String], env: Option<&str>) -> Option<String> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == CONFIG_DIR_ARG {
+            return args.next().cloned().filter(|dir| !dir.is_empty());
+        }
+        if let Some(dir) = arg
+            .strip_prefix(CONFIG_DIR_ARG)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(dir.to_string()).filter(|dir| !dir.is_empty());
+        }
+    }
+    env.filter(|dir| !dir.is_empty()).map(str::to_string)
+}
+
+fn absolute(dir: &str, cwd: &Path) -> PathBuf {
+    let path = Path::new(dir);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        cwd.join(path)
+    }
+}
+
+/// Overridden config directory, if any
+pub fn override_dir() -> Option<PathBuf> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let env = std::env::var(CONFIG_DIR_ENV_VAR).ok();
+    let dir = requested_dir(&args, env.as_deref())?;
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
+    Some(absolute(&dir, &cwd))
+}
+
+/// Directory for settings and stored data: the override, or the app data directory
+pub fn resolve(app: &App) -> tauri::Result<PathBuf> {
+    match override_dir() {
+        Some(dir) => {
+            info!(dir = %dir.display(), "Using config directory override");
+            Ok(dir)
+        }
+        None => app
+            .path()
+            .app_data_dir()
+            .map_err(|e| tauri::Error::Anyhow(e.into())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_requested_dir() {
+        assert_eq!(requested_dir(&args(&[]), None), None);
+        assert_eq!(
+            requested_dir(&args(&["--config-dir", "/tmp/demo"]), None),
+            Some("/tmp/demo".to_string())
+        );
+        assert_eq!(
+            requested_dir(&args(&["--config-dir=/tmp/demo"]), None),
+            Some("/tmp/demo".to_string())
+        );
+        assert_eq!(
+            requested_dir(&args(&[]), Some("/tmp/env")),
+            Some("/tmp/env".to_string())
+        );
+        // The argument wins over the environment
+        assert_eq!(
+            requested_dir(&args(&["--config-dir", "/tmp/arg"]), Some("/tmp/env")),
+            Some("/tmp/arg".to_string())
+        );
+    }
+
+    #[test]
+    fn test_requested_dir_ignores_empty_and_unrelated() {
+        assert_eq!(requested_dir(&args(&["--config-dir"]), None), None);
+        assert_eq!(requested_dir(&args(&["--config-dir="]), None), None);
+        assert_eq!(requested_dir(&args(&[]), Some("")), None);
+        assert_eq!(
+            requested_dir(&args(&["-psn_0_12345", "--config-directory=/x"]), None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_absolute() {
+        let cwd = Path::new("/Users/me");
+        assert_eq!(absolute("/tmp/demo", cwd), PathBuf::from("/tmp/demo"));
+        assert_eq!(absolute("demo", cwd), PathBuf::from("/Users/me/demo"));
+    }
+}
@@ -0,0 +1,167 @@
+//! Crash-safe JSON config files
+//!
+//! `write_json` writes to `<file>.tmp` next to the target, syncs it and
+//! renames it over the target, so a crash mid-write leaves either the old or
+//! the new file and never a truncated one. The version being replaced is kept
+//! as `<file>.bak`. `read_json` falls back to that backup when the file is
+//! missing or can't be parsed, moving an unparsable file aside as
+//! `<file>.corrupt` so the next save doesn't back it up over the good copy.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{debug, error, warn};
+
+/// `path` with `suffix` appended to the file name
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Path of the backup kept for `path`
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling(path, ".bak")
+}
+
+fn parse<T: DeserializeOwned>(path: &Path) -> Option<Result<T, String>> {
+    let content = fs::read_to_string(path).ok()?;
+    Some(serde_json::from_str(&content).map_err(|e| e.to_string()))
+}
+
+/// Read a JSON file, or its backup if the file is missing or unparsable;
+/// `None` if neither can be read
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    match parse(path) {
+        Some(Ok(value)) => return Some(value),
+        Some(Err(e)) => {
+            error!(path = %path.display(), error = %e, "Failed to parse config file");
+            if let Err(e) = fs::rename(path, sibling(path, ".corrupt")) {
+                warn!(path = %path.display(), error = %e, "Failed to move corrupt config file aside");
+            }
+        }
+        None => {}
+    }
+    let backup = backup_path(path);
+    match parse(&backup) {
+        Some(Ok(value)) => {
+            warn!(path = %path.display(), "Restored config from backup");
+            Some(value)
+        }
+        Some(Err(e)) => {
+            error!(path = %backup.display(), error = %e, "Failed to parse config backup");
+            None
+        }
+        None => {
+            debug!(path = %path.display(), "No config file");
+            None
+        }
+    }
+}
+
+/// Atomically replace a JSON file, keeping the previous version as a backup
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    write_atomic(path, json.as_bytes())
+}
+
+/// Atomically replace a file, keeping the previous version as a backup
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let temp = sibling(path, ".tmp");
+    let result = File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .map_err(|e| format!("Failed to write {}: {}", temp.display(), e));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+
+    if path.exists() {
+        // Replace the backup with the current file
+        let backup = backup_path(path);
+        let _ = fs::remove_file(&backup);
+        if fs::hard_link(path, &backup).is_err() {
+            if let Err(e) = fs::copy(path, &backup) {
+                warn!(path = %path.display(), error = %e, "Failed to back up config file");
+            }
+        }
+    }
+
+    fs::rename(&temp, path).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        format!("Failed to replace {}: {}", path.display(), e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    type Config = HashMap<String, u32>;
+
+    fn config(value: u32) -> Config {
+        HashMap::from([("value".to_string(), value)])
+    }
+
+    #[test]
+    fn test_write_keeps_one_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.json");
+
+        write_json(&path, &config(1)).unwrap();
+        assert!(!backup_path(&path).exists());
+        write_json(&path, &config(2)).unwrap();
+        write_json(&path, &config(3)).unwrap();
+
+        assert_eq!(read_json::<Config>(&path), Some(config(3)));
+        assert_eq!(read_json::<Config>(&backup_path(&path)), Some(config(2)));
+        assert!(!temp_dir.path().join("settings.json.tmp").exists());
+    }
+
+    #[test]
+    fn test_corrupt_file_falls_back_to_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.json");
+        write_json(&path, &config(1)).unwrap();
+        write_json(&path, &config(2)).unwrap();
+        // A truncated write from an older version
+        fs::write(&path, "{\"value\": ").unwrap();
+
+        assert_eq!(read_json::<Config>(&path), Some(config(1)));
+        assert!(temp_dir.path().join("settings.json.corrupt").exists());
+
+        // The next save must not back up the corrupt file over the good one
+        write_json(&path, &config(3)).unwrap();
+        assert_eq!(read_json::<Config>(&backup_path(&path)), Some(config(1)));
+    }
+
+    #[test]
+    fn test_missing_file_falls_back_to_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.json");
+        assert_eq!(read_json::<Config>(&path), None);
+
+        write_json(&backup_path(&path), &config(7)).unwrap();
+        assert_eq!(read_json::<Config>(&path), Some(config(7)));
+    }
+
+    #[test]
+    fn test_unreadable_file_and_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.json");
+        fs::write(&path, "invalid json").unwrap();
+        fs::write(backup_path(&path), "also invalid").unwrap();
+        assert_eq!(read_json::<Config>(&path), None);
+    }
+}
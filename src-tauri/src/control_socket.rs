@@ -0,0 +1,262 @@
+//! Control socket and single-instance enforcement
+//!
+//! The running instance listens on a Unix socket (`control.sock` in the config
+//! directory). Launching the app again with the same config directory (e.g.
+//! the binary from a script) connects to it, forwards a show request (toggle
+//! with `--toggle`, a command with `run`) and exits instead of adding a second
+//! tray icon. An instance started with another `--config-dir` gets its own
+//! socket and runs side by side.
+//!
+//! Requests are single lines (`show`, `toggle`, or `run` and the command as a
+//! JSON string); the reply is `ok` or `error: <message>`.
+
+use crate::launch_command;
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Name of the socket file in the config directory
+pub const SOCKET_FILE: &str = "control.sock";
+
+/// Launch argument making a second launch toggle the window instead of showing it
+pub const TOGGLE_ARG: &str = "--toggle";
+
+/// How long either side waits for the other
+const IO_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Longest request line accepted
+const MAX_REQUEST_LENGTH: u64 = 64 * 1024;
+
+/// What another launch asks the running instance to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlRequest {
+    /// Show the window (and focus it if it's already visible)
+    Show,
+    /// Show or hide the window, like clicking the tray icon
+    Toggle,
+    /// Start a session running the command and show the window
+    Run(String),
+}
+
+impl ControlRequest {
+    /// Name of the request, for logging
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControlRequest::Show => "show",
+            ControlRequest::Toggle => "toggle",
+            ControlRequest::Run(_) => "run",
+        }
+    }
+
+    /// The request as sent over the socket (without the newline)
+    pub fn to_line(&self) -> String {
+        match self {
+            ControlRequest::Run(command) => {
+                format!("run {}", serde_json::to_string(command).unwrap_or_default())
+            }
+            request => request.name().to_string(),
+        }
+    }
+
+    pub fn parse(line: &str) -> Option<Self> {
+        match line.trim() {
+            "show" => Some(ControlRequest::Show),
+            "toggle" => Some(ControlRequest::Toggle),
+            line => {
+                let command = line.strip_prefix("run ")?;
+                serde_json::from_str(command).ok().map(ControlRequest::Run)
+            }
+        }
+    }
+
+    /// Request for a second launch with the given arguments (without the
+    /// program name)
+    pub fn from_args(args: &[String]) -> Self {
+        if let Some(command) = launch_command::command_from_args(args) {
+            ControlRequest::Run(command)
+        } else if args.iter().any(|arg| arg == TOGGLE_ARG) {
+            ControlRequest::Toggle
+        } else {
+            ControlRequest::Show
+        }
+    }
+}
+
+/// Path of the control socket for a config directory
+pub fn socket_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(SOCKET_FILE)
+}
+
+/// Send a request to the instance listening on `path`.
+/// Returns `Ok(false)` when no instance is running.
+pub fn forward(path: &Path, request: &ControlRequest) -> Result<bool, String> {
+    let mut stream = match UnixStream::connect(path) {
+        Ok(stream) => stream,
+        Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => {
+            return Ok(false);
+        }
+        Err(e) => return Err(format!("Failed to connect to the running instance: {}", e)),
+    };
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+    writeln!(stream, "{}", request.to_line())
+        .map_err(|e| format!("Failed to send to the running instance: {}", e))?;
+
+    let mut reply = String::new();
+    BufReader::new(stream)
+        .read_line(&mut reply)
+        .map_err(|e| format!("No reply from the running instance: {}", e))?;
+    match reply.trim() {
+        "ok" => Ok(true),
+        reply => Err(format!(
+            "The running instance refused the request: {}",
+            reply
+        )),
+    }
+}
+
+/// Listen on `path` and hand requests to `handler` on a background thread.
+/// A socket file left behind by a crashed instance is replaced.
+pub fn listen<F>(path: &Path, handler: F) -> Result<(), String>
+where
+    F: Fn(ControlRequest) -> Result<(), String> + Send + 'static,
+{
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    // Only reached when `forward` found nobody listening
+    let _ = fs::remove_file(path);
+    let listener = UnixListener::bind(path)
+        .map_err(|e| format!("Failed to bind control socket {}: {}", path.display(), e))?;
+    let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o600));
+    info!(path = %path.display(), "Listening on control socket");
+
+    thread::Builder::new()
+        .name("control-socket".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => handle_connection(stream, &handler),
+                    Err(e) => warn!(error = %e, "Control socket connection failed"),
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to start control socket thread: {}", e))?;
+    Ok(())
+}
+
+fn handle_connection<F>(stream: UnixStream, handler: &F)
+where
+    F: Fn(ControlRequest) -> Result<(), String>,
+{
+    let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let mut line = String::new();
+    if let Err(e) = BufReader::new(&stream)
+        .take(MAX_REQUEST_LENGTH)
+        .read_line(&mut line)
+    {
+        debug!(error = %e, "Failed to read control request");
+        return;
+    }
+    let reply = match ControlRequest::parse(&line) {
+        Some(request) => {
+            debug!(request = request.name(), "Control request");
+            match handler(request) {
+                Ok(()) => "ok".to_string(),
+                Err(e) => format!("error: {}", e),
+            }
+        }
+        None => "error: unknown request".to_string(),
+    };
+    let _ = writeln!(writer, "{}", reply);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_request_parsing() {
+        assert_eq!(ControlRequest::parse("show\n"), Some(ControlRequest::Show));
+        assert_eq!(
+            ControlRequest::parse("toggle"),
+            Some(ControlRequest::Toggle)
+        );
+        assert_eq!(ControlRequest::parse("quit"), None);
+        assert_eq!(ControlRequest::parse("run"), None);
+        assert_eq!(ControlRequest::parse("run make"), None);
+        for request in [
+            ControlRequest::Show,
+            ControlRequest::Toggle,
+            ControlRequest::Run("echo \"hi\"\nls".to_string()),
+        ] {
+            assert_eq!(ControlRequest::parse(&request.to_line()), Some(request));
+        }
+        assert_eq!(ControlRequest::from_args(&[]), ControlRequest::Show);
+        assert_eq!(
+            ControlRequest::from_args(&["--toggle".to_string()]),
+            ControlRequest::Toggle
+        );
+        assert_eq!(
+            ControlRequest::from_args(&["run".to_string(), "make test".to_string()]),
+            ControlRequest::Run("make test".to_string())
+        );
+    }
+
+    #[test]
+    fn test_forward_without_instance() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = socket_path(temp_dir.path());
+        assert_eq!(forward(&path, &ControlRequest::Show), Ok(false));
+
+        // A socket file left by a crashed instance
+        drop(UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+        assert_eq!(forward(&path, &ControlRequest::Show), Ok(false));
+    }
+
+    #[test]
+    fn test_forward_to_running_instance() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = socket_path(temp_dir.path());
+        // Stale file from an earlier run is replaced
+        fs::write(&path, "").unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        listen(&path, move |request| {
+            let result = match request {
+                ControlRequest::Toggle => Err("window not found".to_string()),
+                _ => Ok(()),
+            };
+            sender.send(request).unwrap();
+            result
+        })
+        .unwrap();
+
+        assert_eq!(forward(&path, &ControlRequest::Show), Ok(true));
+        assert_eq!(receiver.recv().unwrap(), ControlRequest::Show);
+        let run = ControlRequest::Run("make test".to_string());
+        assert_eq!(forward(&path, &run), Ok(true));
+        assert_eq!(receiver.recv().unwrap(), run);
+        assert!(forward(&path, &ControlRequest::Toggle)
+            .unwrap_err()
+            .contains("window not found"));
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        stream.write_all(b"quit\n").unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        assert_eq!(reply, "error: unknown request\n");
+    }
+}
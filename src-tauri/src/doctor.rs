@@ -0,0 +1,381 @@
+//! Environment doctor for µTerm
+//!
+//! macOS GUI apps are launched without the user's login shell environment, so
+//! a command that works in Terminal.app can be "not found" in µTerm. This
+//! module compares the PATH µTerm gives its shells with the PATH a login shell
+//! reports and points at common tool locations that are missing.
+
+use crate::pty::{build_pty_path, resolve_shell};
+use crate::redact;
+use crate::settings::SettingsManager;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{command, State};
+use tokio::process::Command as TokioCommand;
+use tracing::warn;
+
+/// How long to wait for the login shell to report its PATH
+const LOGIN_SHELL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Markers around the PATH in login shell output, so rc-file noise is ignored
+const PATH_MARKER: &str = "__MICROTERM_PATH__";
+
+/// A well-known tool location and how it shows up in each PATH
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCheck {
+    pub name: String,
+    /// Directory the tool's binaries live in, if installed
+    pub location: Option<String>,
+    pub installed: bool,
+    pub in_app_path: bool,
+    /// `None` when the login shell PATH could not be read
+    pub in_login_path: Option<bool>,
+}
+
+/// Result of `check_environment`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvironmentReport {
+    pub shell: String,
+    /// PATH given to µTerm's shells
+    pub app_path: Vec<String>,
+    pub login_shell_path: Option<Vec<String>>,
+    pub login_shell_error: Option<String>,
+    /// Existing directories on the login shell PATH that µTerm's PATH lacks
+    pub missing_from_app_path: Vec<String>,
+    pub tools: Vec<ToolCheck>,
+    pub suggestions: Vec<String>,
+}
+
+/// Split a PATH string into its non-empty entries
+fn split_path(path: &str) -> Vec<String> {
+    path.split(':')
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Extract the PATH printed between `PATH_MARKER`s from login shell output
+fn parse_marked_path(output: &str) -> Option<String> {
+    let start = output.find(PATH_MARKER)? + PATH_MARKER.len();
+    let end = start + output[start..].find(PATH_MARKER)?;
+    Some(output[start..end].trim().to_string())
+}
+
+/// Ask an interactive login shell for its PATH
+async fn read_login_shell_path(shell: &str) -> Result<String, String> {
+    let script = format!("printf '{0}%s{0}' \"$PATH\"", PATH_MARKER);
+    let child = TokioCommand::new(shell)
+        .args(["-i", "-l", "-c", &script])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start login shell '{}': {}", shell, e))?;
+
+    let output = tokio::time::timeout(LOGIN_SHELL_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            format!(
+                "Login shell did not respond within {}s",
+                LOGIN_SHELL_TIMEOUT.as_secs()
+            )
+        })?
+        .map_err(|e| format!("Failed to read login shell output: {}", e))?;
+
+    parse_marked_path(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| "Login shell did not print a PATH".to_string())
+}
+
+/// Locate well-known tool directories under `home`
+fn check_tools(home: &Path, app_path: &[String], login_path: Option<&[String]>) -> Vec<ToolCheck> {
+    let homebrew = ["/opt/homebrew/bin", "/usr/local/bin"]
+        .into_iter()
+        .find(|dir| Path::new(dir).join("brew").is_file())
+        .map(str::to_string);
+
+    let cargo_dir = home.join(".cargo/bin");
+    let cargo = cargo_dir
+        .is_dir()
+        .then(|| cargo_dir.to_string_lossy().into_owned());
+
+    // nvm installs one bin directory per Node version; any of them on PATH counts
+    let nvm_root = home.join(".nvm");
+    let nvm_versions = nvm_root.join("versions/node");
+    let nvm = nvm_root
+        .is_dir()
+        .then(|| nvm_versions.to_string_lossy().into_owned());
+
+    let exact = |dir: &str, path: &[String]| path.iter().any(|entry| entry == dir);
+    let under = |dir: &str, path: &[String]| path.iter().any(|entry| entry.starts_with(dir));
+
+    let check = |name: &str, location: Option<String>, prefix_match: bool| {
+        let matches = |path: &[String]| match &location {
+            Some(dir) if prefix_match => under(dir, path),
+            Some(dir) => exact(dir, path),
+            None => false,
+        };
+        ToolCheck {
+            name: name.to_string(),
+            installed: location.is_some(),
+            in_app_path: matches(app_path),
+            in_login_path: login_path.map(matches),
+            location,
+        }
+    };
+
+    vec![
+        check("Homebrew", homebrew, false),
+        check("cargo", cargo, false),
+        check("nvm", nvm, true),
+    ]
+}
+
+/// Suggest fixes based on the PATH comparison and tool checks
+fn build_suggestions(
+    shell: &str,
+    missing_from_app_path: &[String],
+    tools: &[ToolCheck],
+    login_shell_error: Option<&str>,
+) -> Vec<String> {
+    let mut suggestions = Vec::new();
+    let shell_name = Path::new(shell)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(shell);
+    let rc_file = match shell_name {
+        "zsh" => "~/.zshrc",
+        "bash" => "~/.bashrc",
+        "fish" => "~/.config/fish/config.fish",
+        _ => "your shell's rc file",
+    };
+
+    if let Some(error) = login_shell_error {
+        suggestions.push(format!(
+            "Could not read your login shell PATH ({}). Check that {} starts without prompting.",
+            error, rc_file
+        ));
+    }
+
+    if !missing_from_app_path.is_empty() {
+        suggestions.push(format!(
+            "Your login shell PATH has entries missing in µTerm ({}). \
+             Move PATH exports from login-only files (e.g. ~/.zprofile) into {}.",
+            missing_from_app_path.join(", "),
+            rc_file
+        ));
+    }
+
+    for tool in tools
+        .iter()
+        .filter(|tool| tool.installed && !tool.in_app_path)
+    {
+        let fix = match tool.name.as_str() {
+            "Homebrew" => format!(
+                "add `eval \"$({}/brew shellenv)\"` to {}",
+                tool.location.as_deref().unwrap_or("/opt/homebrew/bin"),
+                rc_file
+            ),
+            "cargo" => format!("add `. \"$HOME/.cargo/env\"` to {}", rc_file),
+            "nvm" => format!(
+                "make sure {} sources nvm.sh (nvm only adds Node to PATH in interactive shells)",
+                rc_file
+            ),
+            _ => format!("add {} to PATH in {}", tool.name, rc_file),
+        };
+        suggestions.push(format!(
+            "{} is installed but not on µTerm's PATH: {}.",
+            tool.name, fix
+        ));
+    }
+
+    suggestions
+}
+
+/// Build a report from already-collected PATHs
+fn build_report(
+    shell: String,
+    home: &Path,
+    app_path: Vec<String>,
+    login_shell: Result<String, String>,
+) -> EnvironmentReport {
+    let (login_shell_path, login_shell_error) = match login_shell {
+        Ok(path) => (Some(split_path(&path)), None),
+        Err(e) => (None, Some(e)),
+    };
+
+    let app_entries: HashSet<&str> = app_path.iter().map(String::as_str).collect();
+    let mut seen = HashSet::new();
+    let missing_from_app_path: Vec<String> = login_shell_path
+        .iter()
+        .flatten()
+        .filter(|entry| !app_entries.contains(entry.as_str()))
+        .filter(|entry| Path::new(entry).is_dir())
+        .filter(|entry| seen.insert(entry.as_str()))
+        .cloned()
+        .collect();
+
+    let tools = check_tools(home, &app_path, login_shell_path.as_deref());
+    let suggestions = build_suggestions(
+        &shell,
+        &missing_from_app_path,
+        &tools,
+        login_shell_error.as_deref(),
+    );
+
+    EnvironmentReport {
+        shell,
+        app_path,
+        login_shell_path,
+        login_shell_error,
+        missing_from_app_path,
+        tools,
+        suggestions,
+    }
+}
+
+/// Compare µTerm's PATH with the PATH of the shell sessions start and suggest fixes
+#[command]
+pub async fn check_environment(
+    settings_manager: State<'_, Arc<SettingsManager>>,
+) -> Result<EnvironmentReport, String> {
+    let (shell, _) = resolve_shell(&settings_manager.get(), None);
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/".to_string());
+    let app_path = split_path(&build_pty_path(
+        &home,
+        std::env::var("PATH").ok().as_deref(),
+    ));
+
+    // Shell errors include rc-file output, which may echo secrets
+    let login_shell = read_login_shell_path(&shell)
+        .await
+        .map_err(|e| redact::redact(&e));
+    if let Err(e) = &login_shell {
+        warn!(shell = %shell, error = %e, "Failed to read login shell PATH");
+    }
+
+    Ok(build_report(shell, Path::new(&home), app_path, login_shell))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_split_path_skips_empty_entries() {
+        assert_eq!(
+            split_path("/usr/bin::/bin:"),
+            vec!["/usr/bin".to_string(), "/bin".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_marked_path_ignores_noise() {
+        let output = format!("Welcome!\n{0}/usr/bin:/bin{0}\nlogout\n", PATH_MARKER);
+        assert_eq!(parse_marked_path(&output).as_deref(), Some("/usr/bin:/bin"));
+        assert_eq!(parse_marked_path("no markers here"), None);
+    }
+
+    #[test]
+    fn test_missing_dirs_reported() {
+        let temp = TempDir::new().unwrap();
+        let extra = temp.path().join("extra/bin");
+        std::fs::create_dir_all(&extra).unwrap();
+        let extra = extra.to_string_lossy().into_owned();
+
+        let login = format!("/usr/bin:{}:/does/not/exist:{}", extra, extra);
+        let report = build_report(
+            "/bin/zsh".to_string(),
+            temp.path(),
+            vec!["/usr/bin".to_string()],
+            Ok(login),
+        );
+
+        // Duplicates and non-existent directories are dropped
+        assert_eq!(report.missing_from_app_path, vec![extra]);
+        assert!(report.suggestions.iter().any(|s| s.contains("~/.zshrc")));
+    }
+
+    #[test]
+    fn test_cargo_not_on_app_path() {
+        let temp = TempDir::new().unwrap();
+        let cargo_bin = temp.path().join(".cargo/bin");
+        std::fs::create_dir_all(&cargo_bin).unwrap();
+        let cargo_bin = cargo_bin.to_string_lossy().into_owned();
+
+        let report = build_report(
+            "/bin/bash".to_string(),
+            temp.path(),
+            vec!["/usr/bin".to_string()],
+            Ok(cargo_bin.clone()),
+        );
+
+        let cargo = report.tools.iter().find(|t| t.name == "cargo").unwrap();
+        assert!(cargo.installed);
+        assert!(!cargo.in_app_path);
+        assert_eq!(cargo.in_login_path, Some(true));
+        assert!(report
+            .suggestions
+            .iter()
+            .any(|s| s.contains(".cargo/env") && s.contains("~/.bashrc")));
+    }
+
+    #[test]
+    fn test_nvm_matches_any_version_dir() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join(".nvm/versions/node/v20.0.0/bin")).unwrap();
+        let node_bin = temp
+            .path()
+            .join(".nvm/versions/node/v20.0.0/bin")
+            .to_string_lossy()
+            .into_owned();
+
+        let report = build_report(
+            "/bin/zsh".to_string(),
+            temp.path(),
+            vec![node_bin],
+            Err("timed out".to_string()),
+        );
+
+        let nvm = report.tools.iter().find(|t| t.name == "nvm").unwrap();
+        assert!(nvm.installed);
+        assert!(nvm.in_app_path);
+        assert_eq!(nvm.in_login_path, None);
+    }
+
+    #[test]
+    fn test_login_shell_error_reported() {
+        let temp = TempDir::new().unwrap();
+        let report = build_report(
+            "/usr/local/bin/fish".to_string(),
+            temp.path(),
+            vec!["/usr/bin".to_string()],
+            Err("timed out".to_string()),
+        );
+
+        assert!(report.login_shell_path.is_none());
+        assert_eq!(report.login_shell_error.as_deref(), Some("timed out"));
+        assert!(report.missing_from_app_path.is_empty());
+        assert!(report.suggestions[0].contains("config.fish"));
+    }
+
+    #[test]
+    fn test_uninstalled_tools_not_suggested() {
+        let temp = TempDir::new().unwrap();
+        let report = build_report(
+            "/bin/zsh".to_string(),
+            temp.path(),
+            vec!["/usr/bin".to_string()],
+            Ok("/usr/bin".to_string()),
+        );
+
+        let cargo = report.tools.iter().find(|t| t.name == "cargo").unwrap();
+        assert!(!cargo.installed);
+        assert!(report.suggestions.iter().all(|s| !s.contains("cargo")));
+    }
+}
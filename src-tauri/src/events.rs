@@ -0,0 +1,796 @@
+//! Events emitted to the frontend
+//!
+//! Every event the backend emits is named here and goes through `emit`, which
+//! adds `version` (`EVENT_API_VERSION`) to the payload and counts the event in
+//! the metrics. Payloads are always JSON objects. `get_event_schema` returns the
+//! catalog of events and their fields so other clients (scripts, the status
+//! endpoint's users) can check what they're talking to; bump the version when a
+//! field is removed or changes meaning, and update `CATALOG` with any change.
+//!
+//! Events about one invocation (such as `command-complete`) are scoped: they're
+//! emitted as `name:scope`, so listeners only get their own.
+
+use crate::accessibility::{Announcement, DisplayPreferencesReport};
+use crate::activity::{PtyActivity, PtyIdle};
+use crate::clipboard::ClipboardAccessRequested;
+use crate::commands::StreamChunk;
+use crate::images::PtyImage;
+use crate::jobs::Job;
+use crate::launch_command::SessionOpened;
+use crate::lock::LockState;
+use crate::metrics;
+use crate::notifications::{PtyBell, PtyNotification};
+use crate::orphans::PtyOrphaned;
+use crate::paste::PasteConfirmationRequested;
+use crate::power::PowerStatus;
+use crate::presentation::PresentationStatus;
+use crate::profiles::SessionProfileChanged;
+use crate::progress::PtyProgress;
+use crate::pty::{
+    AlternateScreen, PtyExit, PtyOutput, PtyOutputTruncated, SessionCleared, SessionMoved,
+};
+use crate::quit_guard::CloseBlocked;
+use crate::session_labels::SessionLabelsChanged;
+use crate::shell_integration::{CommandEnd, CommandStart, PromptStart};
+use crate::ssh::{SshConnected, SshDisconnected};
+use crate::status_strip::StatusSnapshot;
+use crate::tab_title::TabTitle;
+use crate::trash::RmIntercepted;
+use crate::tray_recent::SessionFocusRequested;
+use crate::window_mode::WindowModeChanged;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use tauri::{command, Emitter, Runtime};
+
+/// Version of the event payloads, sent as `version` in each of them
+pub const EVENT_API_VERSION: u32 = 3;
+
+pub const PTY_OUTPUT: &str = "pty-output";
+pub const PTY_OUTPUT_TRUNCATED: &str = "pty-output-truncated";
+pub const PTY_EXIT: &str = "pty-exit";
+pub const PTY_IMAGE: &str = "pty-image";
+pub const PTY_NOTIFICATION: &str = "pty-notification";
+pub const PTY_BELL: &str = "pty-bell";
+pub const PTY_PROGRESS: &str = "pty-progress";
+pub const PTY_ACTIVITY: &str = "pty-activity";
+pub const PTY_IDLE: &str = "pty-idle";
+pub const PTY_ORPHANED: &str = "pty-orphaned";
+pub const PTY_ALTERNATE_SCREEN: &str = "pty-alternate-screen";
+pub const SESSION_CLEARED: &str = "session-cleared";
+pub const SESSION_LABELS_CHANGED: &str = "session-labels-changed";
+pub const SESSION_PROFILE_CHANGED: &str = "session-profile-changed";
+pub const SESSION_OPENED: &str = "session-opened";
+pub const SESSION_MOVED: &str = "session-moved";
+pub const SESSION_FOCUS_REQUESTED: &str = "session-focus-requested";
+pub const PASTE_CONFIRMATION_REQUESTED: &str = "paste-confirmation-requested";
+pub const CLIPBOARD_ACCESS_REQUESTED: &str = "clipboard-access-requested";
+pub const TAB_TITLE_CHANGED: &str = "tab-title-changed";
+pub const PROMPT_START: &str = "prompt-start";
+pub const COMMAND_START: &str = "command-start";
+pub const COMMAND_END: &str = "command-end";
+pub const COMMAND_STDOUT: &str = "command-stdout";
+pub const COMMAND_STDERR: &str = "command-stderr";
+pub const COMMAND_COMPLETE: &str = "command-complete";
+pub const JOB_STATUS: &str = "job-status";
+pub const SSH_CONNECTED: &str = "ssh-connected";
+pub const SSH_DISCONNECTED: &str = "ssh-disconnected";
+pub const RM_INTERCEPTED: &str = "rm-intercepted";
+pub const LOCK_STATE_CHANGED: &str = "lock-state-changed";
+pub const CLOSE_BLOCKED: &str = "close-blocked";
+pub const POWER_STATUS_CHANGED: &str = "power-status-changed";
+pub const PRESENTATION_MODE_CHANGED: &str = "presentation-mode-changed";
+pub const DISPLAY_PREFERENCES_CHANGED: &str = "display-preferences-changed";
+pub const ACCESSIBILITY_ANNOUNCEMENT: &str = "accessibility-announcement";
+pub const PIN_STATE_UPDATED: &str = "pin-state-updated";
+pub const WINDOW_VISIBILITY: &str = "window-visibility";
+pub const WINDOW_READY: &str = "window-ready";
+pub const WINDOW_MODE_CHANGED: &str = "window-mode-changed";
+pub const STATUS_STRIP_UPDATED: &str = "status-strip-updated";
+
+/// A payload that can be emitted to the frontend
+pub trait Event: Serialize + Clone {
+    /// Name the payload is emitted under
+    fn name(&self) -> &'static str;
+
+    /// Invocation the event belongs to, appended to the name as `name:scope`
+    fn scope(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Payload of the `command-complete:{execution_id}` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandComplete {
+    pub execution_id: String,
+    pub exit_code: i32,
+    pub duration_ms: u64,
+    /// Bytes read from the command's stdout and stderr
+    pub stdout_bytes: u64,
+    pub stderr_bytes: u64,
+}
+
+/// Payload of the `pin-state-updated` event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinStateUpdated {
+    pub pinned: bool,
+}
+
+/// Payload of the `window-visibility` event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowVisibility {
+    pub visible: bool,
+}
+
+/// Payload of the `window-ready` event, sent once the window is set up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowReady {}
+
+macro_rules! event {
+    ($payload:ty, $name:expr) => {
+        impl Event for $payload {
+            fn name(&self) -> &'static str {
+                $name
+            }
+        }
+    };
+}
+
+event!(PtyOutput, PTY_OUTPUT);
+event!(PtyOutputTruncated, PTY_OUTPUT_TRUNCATED);
+event!(PtyExit, PTY_EXIT);
+event!(PtyImage, PTY_IMAGE);
+event!(PtyNotification, PTY_NOTIFICATION);
+event!(PtyBell, PTY_BELL);
+event!(PtyProgress, PTY_PROGRESS);
+event!(PtyActivity, PTY_ACTIVITY);
+event!(PtyIdle, PTY_IDLE);
+event!(AlternateScreen, PTY_ALTERNATE_SCREEN);
+event!(PtyOrphaned, PTY_ORPHANED);
+event!(SessionCleared, SESSION_CLEARED);
+event!(SessionLabelsChanged, SESSION_LABELS_CHANGED);
+event!(SessionProfileChanged, SESSION_PROFILE_CHANGED);
+event!(SessionOpened, SESSION_OPENED);
+event!(SessionMoved, SESSION_MOVED);
+event!(SessionFocusRequested, SESSION_FOCUS_REQUESTED);
+event!(PasteConfirmationRequested, PASTE_CONFIRMATION_REQUESTED);
+event!(ClipboardAccessRequested, CLIPBOARD_ACCESS_REQUESTED);
+event!(TabTitle, TAB_TITLE_CHANGED);
+event!(PromptStart, PROMPT_START);
+event!(CommandStart, COMMAND_START);
+event!(CommandEnd, COMMAND_END);
+event!(Job, JOB_STATUS);
+event!(SshConnected, SSH_CONNECTED);
+event!(SshDisconnected, SSH_DISCONNECTED);
+event!(RmIntercepted, RM_INTERCEPTED);
+event!(LockState, LOCK_STATE_CHANGED);
+event!(CloseBlocked, CLOSE_BLOCKED);
+event!(PowerStatus, POWER_STATUS_CHANGED);
+event!(PresentationStatus, PRESENTATION_MODE_CHANGED);
+event!(DisplayPreferencesReport, DISPLAY_PREFERENCES_CHANGED);
+event!(Announcement, ACCESSIBILITY_ANNOUNCEMENT);
+event!(PinStateUpdated, PIN_STATE_UPDATED);
+event!(WindowVisibility, WINDOW_VISIBILITY);
+event!(WindowReady, WINDOW_READY);
+event!(WindowModeChanged, WINDOW_MODE_CHANGED);
+event!(StatusSnapshot, STATUS_STRIP_UPDATED);
+
+impl Event for CommandComplete {
+    fn name(&self) -> &'static str {
+        COMMAND_COMPLETE
+    }
+
+    fn scope(&self) -> Option<&str> {
+        Some(&self.execution_id)
+    }
+}
+
+impl Event for StreamChunk {
+    fn name(&self) -> &'static str {
+        if self.is_stderr {
+            COMMAND_STDERR
+        } else {
+            COMMAND_STDOUT
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct Envelope<'a, E> {
+    version: u32,
+    #[serde(flatten)]
+    payload: &'a E,
+}
+
+/// Name an event is emitted under, including its scope
+fn event_name<E: Event>(event: &E) -> Cow<'static, str> {
+    match event.scope() {
+        Some(scope) => Cow::Owned(format!("{}:{}", event.name(), scope)),
+        None => Cow::Borrowed(event.name()),
+    }
+}
+
+/// Emit an event to all webviews (or one window, when `emitter` is a window)
+pub fn emit<R: Runtime, E: Event>(emitter: &impl Emitter<R>, event: E) -> tauri::Result<()> {
+    metrics::record_event(event.name());
+    emitter.emit(
+        &event_name(&event),
+        Envelope {
+            version: EVENT_API_VERSION,
+            payload: &event,
+        },
+    )
+}
+
+/// Emit an event to the webviews of the window labelled `target` only
+pub fn emit_to<R: Runtime, E: Event>(
+    emitter: &impl Emitter<R>,
+    target: &str,
+    event: E,
+) -> tauri::Result<()> {
+    metrics::record_event(event.name());
+    emitter.emit_to(
+        target,
+        &event_name(&event),
+        Envelope {
+            version: EVENT_API_VERSION,
+            payload: &event,
+        },
+    )
+}
+
+/// Description of one event in `get_event_schema`
+#[derive(Debug, Clone, Serialize)]
+pub struct EventSchema {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Top-level payload fields, besides `version`
+    pub fields: &'static [&'static str],
+}
+
+/// Result of `get_event_schema`
+#[derive(Debug, Clone, Serialize)]
+pub struct EventCatalog {
+    pub version: u32,
+    pub events: &'static [EventSchema],
+}
+
+const CATALOG: &[EventSchema] = &[
+    EventSchema {
+        name: PTY_OUTPUT,
+        description: "Output read from a session; sent to the session's window only",
+        fields: &["session_id", "data"],
+    },
+    EventSchema {
+        name: PTY_OUTPUT_TRUNCATED,
+        description: "Output was dropped because the session exceeded output_rate_limit_kib; sent at most once a second while it lasts, to the session's window only",
+        fields: &["session_id", "dropped_bytes"],
+    },
+    EventSchema {
+        name: PTY_EXIT,
+        description: "A session's shell exited; exit_code is null when it was closed, signal is set when the shell was killed by one",
+        fields: &["session_id", "exit_code", "signal"],
+    },
+    EventSchema {
+        name: PTY_IMAGE,
+        description: "An inline image to display in a session",
+        fields: &[
+            "session_id",
+            "image_id",
+            "protocol",
+            "format",
+            "pixel_width",
+            "pixel_height",
+            "display_width",
+            "display_height",
+            "preserve_aspect_ratio",
+            "name",
+            "byte_size",
+        ],
+    },
+    EventSchema {
+        name: PTY_NOTIFICATION,
+        description: "A program in a session requested a notification",
+        fields: &["session_id", "title", "body"],
+    },
+    EventSchema {
+        name: PTY_BELL,
+        description: "A session rang the bell (reported at most once a second)",
+        fields: &["session_id"],
+    },
+    EventSchema {
+        name: PTY_PROGRESS,
+        description: "A program in a session reported progress (OSC 9;4)",
+        fields: &["session_id", "state", "progress"],
+    },
+    EventSchema {
+        name: PTY_ACTIVITY,
+        description: "A quiet session started producing output",
+        fields: &["session_id"],
+    },
+    EventSchema {
+        name: PTY_IDLE,
+        description: "A busy session has produced no output for activity_idle_seconds; active_ms is how long it was busy",
+        fields: &["session_id", "active_ms"],
+    },
+    EventSchema {
+        name: PTY_ALTERNATE_SCREEN,
+        description: "A full-screen program entered or left the alternate screen (smcup/rmcup); sent to the session's window only, after the output it was found in. A session reset also leaves it, without this event",
+        fields: &["session_id", "active"],
+    },
+    EventSchema {
+        name: PTY_ORPHANED,
+        description: "A session without a pane (after its page reloaded) has exited; it is removed a minute later unless a terminal resizes it",
+        fields: &["session_id", "window", "exit_code", "signal"],
+    },
+    EventSchema {
+        name: SESSION_CLEARED,
+        description: "A session's scrollback was cleared, or the session was reset",
+        fields: &["session_id", "reset"],
+    },
+    EventSchema {
+        name: SESSION_LABELS_CHANGED,
+        description: "A session's tags or color changed",
+        fields: &["session_id", "tags", "color"],
+    },
+    EventSchema {
+        name: SESSION_PROFILE_CHANGED,
+        description: "The profile matching a session's directory changed",
+        fields: &["session_id", "profile"],
+    },
+    EventSchema {
+        name: SESSION_OPENED,
+        description: "A launch command or bookmark started a session; take it with take_opened_sessions",
+        fields: &["session_id", "title", "command"],
+    },
+    EventSchema {
+        name: SESSION_MOVED,
+        description: "A session was handed over to another window",
+        fields: &["session_id", "from_window", "to_window"],
+    },
+    EventSchema {
+        name: SESSION_FOCUS_REQUESTED,
+        description: "A session was chosen in the tray menu; sent to the session's window only",
+        fields: &["session_id"],
+    },
+    EventSchema {
+        name: PASTE_CONFIRMATION_REQUESTED,
+        description: "A multi-line or privileged paste is held back until sent again confirmed; sent to the session's window only",
+        fields: &["session_id", "line_count", "is_multiline", "contains_sudo"],
+    },
+    EventSchema {
+        name: CLIPBOARD_ACCESS_REQUESTED,
+        description: "A program asked to copy to or read the clipboard with OSC 52 and clipboard_access is ask",
+        fields: &["request_id", "session_id", "operation", "length"],
+    },
+    EventSchema {
+        name: TAB_TITLE_CHANGED,
+        description: "A session's tab title changed",
+        fields: &["session_id", "title"],
+    },
+    EventSchema {
+        name: PROMPT_START,
+        description: "The shell started drawing a prompt (OSC 133 A); sent to the session's window only",
+        fields: &["session_id"],
+    },
+    EventSchema {
+        name: COMMAND_START,
+        description: "A command entered at the prompt started running (OSC 133 C); sent to the session's window only",
+        fields: &["session_id"],
+    },
+    EventSchema {
+        name: COMMAND_END,
+        description: "A command entered at the prompt finished (OSC 133 D); sent to the session's window only",
+        fields: &["session_id", "exit_code", "duration_ms"],
+    },
+    EventSchema {
+        name: COMMAND_STDOUT,
+        description: "Output of a command run with execute_command_stream",
+        fields: &["execution_id", "chunk", "is_stderr", "seq"],
+    },
+    EventSchema {
+        name: COMMAND_STDERR,
+        description: "Error output of a command run with execute_command_stream",
+        fields: &["execution_id", "chunk", "is_stderr", "seq"],
+    },
+    EventSchema {
+        name: COMMAND_COMPLETE,
+        description: "A command run with execute_command_stream finished; emitted as command-complete:{execution_id}",
+        fields: &[
+            "execution_id",
+            "exit_code",
+            "duration_ms",
+            "stdout_bytes",
+            "stderr_bytes",
+        ],
+    },
+    EventSchema {
+        name: SSH_CONNECTED,
+        description: "A session running ssh or mosh to a saved or configured host was started",
+        fields: &["session_id", "host", "transport"],
+    },
+    EventSchema {
+        name: SSH_DISCONNECTED,
+        description: "An ssh or mosh session ended; ssh's exit code 255 means the connection failed or dropped",
+        fields: &["session_id", "host", "transport", "exit_code"],
+    },
+    EventSchema {
+        name: RM_INTERCEPTED,
+        description: "Enter on a plain rm was held back (safe_delete); answer with resolve_rm_intercept",
+        fields: &["session_id", "command", "paths"],
+    },
+    EventSchema {
+        name: JOB_STATUS,
+        description: "A job started or finished",
+        fields: &[
+            "id",
+            "task",
+            "runner",
+            "command",
+            "cwd",
+            "status",
+            "started_at",
+            "finished_at",
+        ],
+    },
+    EventSchema {
+        name: LOCK_STATE_CHANGED,
+        description: "The terminal was locked or unlocked",
+        fields: &["locked"],
+    },
+    EventSchema {
+        name: CLOSE_BLOCKED,
+        description: "Closing the window or quitting was blocked by protected sessions",
+        fields: &["action", "protected_sessions"],
+    },
+    EventSchema {
+        name: POWER_STATUS_CHANGED,
+        description: "Battery or thermal status changed",
+        fields: &[
+            "battery_percent",
+            "on_battery",
+            "low_battery",
+            "thermal_state",
+            "thermal_pressure",
+        ],
+    },
+    EventSchema {
+        name: PRESENTATION_MODE_CHANGED,
+        description: "The window was made opaque for a screen share, or translucent again",
+        fields: &["mode", "capture", "presenting", "active"],
+    },
+    EventSchema {
+        name: DISPLAY_PREFERENCES_CHANGED,
+        description: "Reduce motion or increase contrast changed",
+        fields: &["reduce_motion", "increase_contrast", "system"],
+    },
+    EventSchema {
+        name: ACCESSIBILITY_ANNOUNCEMENT,
+        description: "Text for screen readers to announce",
+        fields: &["session_id", "message", "high_priority"],
+    },
+    EventSchema {
+        name: PIN_STATE_UPDATED,
+        description: "The window was pinned or unpinned",
+        fields: &["pinned"],
+    },
+    EventSchema {
+        name: WINDOW_VISIBILITY,
+        description: "The window was shown or hidden",
+        fields: &["visible"],
+    },
+    EventSchema {
+        name: WINDOW_READY,
+        description: "The window is set up and can be shown",
+        fields: &[],
+    },
+    EventSchema {
+        name: WINDOW_MODE_CHANGED,
+        description: "The window switched between the panel and the command bar",
+        fields: &["mode"],
+    },
+    EventSchema {
+        name: STATUS_STRIP_UPDATED,
+        description: "Running jobs, progress or bells shown in the status strip changed",
+        fields: &["running_jobs", "progress", "bells"],
+    },
+];
+
+/// Current event version and the catalog of events
+pub fn catalog() -> EventCatalog {
+    EventCatalog {
+        version: EVENT_API_VERSION,
+        events: CATALOG,
+    }
+}
+
+/// Get the event version and the names and fields of all events
+#[command]
+pub fn get_event_schema() -> Result<EventCatalog, String> {
+    Ok(catalog())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accessibility::DisplayPreferences;
+    use crate::clipboard::ClipboardOperation;
+    use crate::images::{ImageFormat, ImageProtocol};
+    use crate::jobs::JobStatus;
+    use crate::notifications::TerminalNotification;
+    use crate::presentation::PresentationMode;
+    use crate::progress::{ProgressState, ProgressUpdate};
+    use crate::project::TaskRunner;
+    use crate::quit_guard::BlockedAction;
+    use crate::session_labels::SessionLabels;
+    use crate::ssh::SshTransport;
+    use crate::window_mode::WindowMode;
+    use std::collections::HashSet;
+
+    /// Payload keys as emitted, and the name
+    fn emitted<E: Event>(event: E) -> (&'static str, Vec<String>) {
+        let value = serde_json::to_value(Envelope {
+            version: EVENT_API_VERSION,
+            payload: &event,
+        })
+        .unwrap();
+        let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        (event.name(), keys)
+    }
+
+    fn session_id() -> String {
+        "session-1".to_string()
+    }
+
+    fn samples() -> Vec<(&'static str, Vec<String>)> {
+        vec![
+            emitted(PtyOutput {
+                session_id: session_id(),
+                data: "ls\r\n".to_string(),
+            }),
+            emitted(PtyOutputTruncated {
+                session_id: session_id(),
+                dropped_bytes: 1 << 20,
+            }),
+            emitted(PtyExit {
+                session_id: session_id(),
+                exit_code: Some(0),
+                signal: None,
+            }),
+            emitted(PtyImage {
+                session_id: session_id(),
+                image_id: "1".to_string(),
+                protocol: ImageProtocol::Kitty,
+                format: ImageFormat::Png,
+                pixel_width: None,
+                pixel_height: None,
+                display_width: None,
+                display_height: None,
+                preserve_aspect_ratio: true,
+                name: None,
+                byte_size: 0,
+            }),
+            emitted(PtyNotification {
+                session_id: session_id(),
+                notification: TerminalNotification {
+                    title: "Build".to_string(),
+                    body: "Done".to_string(),
+                },
+            }),
+            emitted(PtyBell {
+                session_id: session_id(),
+            }),
+            emitted(PtyProgress {
+                session_id: session_id(),
+                update: ProgressUpdate {
+                    state: ProgressState::Normal,
+                    progress: 50,
+                },
+            }),
+            emitted(PtyActivity {
+                session_id: session_id(),
+            }),
+            emitted(PtyIdle {
+                session_id: session_id(),
+                active_ms: 95_000,
+            }),
+            emitted(AlternateScreen {
+                session_id: session_id(),
+                active: true,
+            }),
+            emitted(PtyOrphaned {
+                session_id: session_id(),
+                window: "main".to_string(),
+                exit_code: 0,
+                signal: None,
+            }),
+            emitted(SessionCleared {
+                session_id: session_id(),
+                reset: false,
+            }),
+            emitted(SessionLabelsChanged {
+                session_id: session_id(),
+                labels: SessionLabels::default(),
+            }),
+            emitted(SessionProfileChanged {
+                session_id: session_id(),
+                profile: None,
+            }),
+            emitted(SessionOpened {
+                session_id: session_id(),
+                title: "make test".to_string(),
+                command: Some("make test".to_string()),
+            }),
+            emitted(SessionMoved {
+                session_id: session_id(),
+                from_window: "main".to_string(),
+                to_window: "detached-1".to_string(),
+            }),
+            emitted(SessionFocusRequested {
+                session_id: session_id(),
+            }),
+            emitted(PasteConfirmationRequested {
+                session_id: session_id(),
+                line_count: 2,
+                is_multiline: true,
+                contains_sudo: false,
+            }),
+            emitted(ClipboardAccessRequested {
+                request_id: 1,
+                session_id: session_id(),
+                operation: ClipboardOperation::Write,
+                length: 5,
+            }),
+            emitted(TabTitle {
+                session_id: session_id(),
+                title: "~".to_string(),
+            }),
+            emitted(PromptStart {
+                session_id: session_id(),
+            }),
+            emitted(CommandStart {
+                session_id: session_id(),
+            }),
+            emitted(CommandEnd {
+                session_id: session_id(),
+                exit_code: Some(0),
+                duration_ms: 1200,
+            }),
+            emitted(StreamChunk {
+                execution_id: "exec-1".to_string(),
+                chunk: "out".to_string(),
+                is_stderr: false,
+                seq: None,
+            }),
+            emitted(StreamChunk {
+                execution_id: "exec-1".to_string(),
+                chunk: "err".to_string(),
+                is_stderr: true,
+                seq: Some(1),
+            }),
+            emitted(CommandComplete {
+                execution_id: "exec-1".to_string(),
+                exit_code: 0,
+                duration_ms: 40,
+                stdout_bytes: 3,
+                stderr_bytes: 3,
+            }),
+            emitted(Job {
+                id: session_id(),
+                task: "build".to_string(),
+                runner: Some(TaskRunner::Npm),
+                command: "npm run build".to_string(),
+                cwd: "/tmp".to_string(),
+                status: JobStatus::Running,
+                started_at: 0,
+                finished_at: None,
+            }),
+            emitted(SshConnected {
+                session_id: session_id(),
+                host: "prod".to_string(),
+                transport: SshTransport::Mosh,
+            }),
+            emitted(SshDisconnected {
+                session_id: session_id(),
+                host: "prod".to_string(),
+                transport: SshTransport::Ssh,
+                exit_code: Some(255),
+            }),
+            emitted(RmIntercepted {
+                session_id: session_id(),
+                command: "rm -rf build".to_string(),
+                paths: vec!["/tmp/build".to_string()],
+            }),
+            emitted(LockState { locked: true }),
+            emitted(CloseBlocked {
+                action: BlockedAction::Quit,
+                protected_sessions: vec![session_id()],
+            }),
+            emitted(PowerStatus::default()),
+            emitted(PresentationStatus {
+                mode: PresentationMode::Auto,
+                capture: Some("zoom.us".to_string()),
+                presenting: false,
+                active: true,
+            }),
+            emitted(DisplayPreferencesReport {
+                effective: DisplayPreferences::default(),
+                system: DisplayPreferences::default(),
+            }),
+            emitted(Announcement {
+                session_id: session_id(),
+                message: "Done".to_string(),
+                high_priority: false,
+            }),
+            emitted(PinStateUpdated { pinned: true }),
+            emitted(WindowVisibility { visible: true }),
+            emitted(WindowReady {}),
+            emitted(WindowModeChanged {
+                mode: WindowMode::CommandBar,
+            }),
+            emitted(StatusSnapshot {
+                running_jobs: vec!["build".to_string()],
+                progress: Some(ProgressUpdate {
+                    state: ProgressState::Normal,
+                    progress: 40,
+                }),
+                bells: vec![session_id()],
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_catalog_matches_payloads() {
+        let samples = samples();
+        assert_eq!(samples.len(), CATALOG.len());
+        for (name, keys) in samples {
+            let schema = CATALOG
+                .iter()
+                .find(|schema| schema.name == name)
+                .unwrap_or_else(|| panic!("{} is missing from the catalog", name));
+            let mut fields: Vec<String> = schema
+                .fields
+                .iter()
+                .chain(&["version"])
+                .map(|field| field.to_string())
+                .collect();
+            fields.sort();
+            assert_eq!(keys, fields, "fields of {}", name);
+        }
+    }
+
+    #[test]
+    fn test_catalog_names_are_unique() {
+        let names: HashSet<_> = CATALOG.iter().map(|schema| schema.name).collect();
+        assert_eq!(names.len(), CATALOG.len());
+    }
+
+    #[test]
+    fn test_envelope_adds_version() {
+        let value = serde_json::to_value(Envelope {
+            version: EVENT_API_VERSION,
+            payload: &PinStateUpdated { pinned: true },
+        })
+        .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "version": EVENT_API_VERSION, "pinned": true })
+        );
+    }
+
+    #[test]
+    fn test_scoped_event_names() {
+        assert_eq!(
+            event_name(&PinStateUpdated { pinned: true }),
+            PIN_STATE_UPDATED
+        );
+        let complete = CommandComplete {
+            execution_id: "exec-1".to_string(),
+            exit_code: 0,
+            duration_ms: 0,
+            stdout_bytes: 0,
+            stderr_bytes: 0,
+        };
+        assert_eq!(event_name(&complete), "command-complete:exec-1");
+    }
+}
@@ -0,0 +1,105 @@
+//! Files and folders dropped on a terminal
+//!
+//! The frontend passes the paths of a drop on a pane to `drop_paths`, which
+//! types them into the pane's session, single-quoted for the shell and
+//! separated by spaces. With `cd_on_folder_drop`, a single folder dropped on a
+//! shell sitting at its prompt runs `cd '<folder>'` instead. Paths with
+//! control characters are skipped, since a newline in one would run whatever
+//! follows it.
+
+use crate::lock::LockManager;
+use crate::pty::{PtyManager, WriteError};
+use crate::settings::SettingsManager;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{command, State};
+use tracing::debug;
+
+/// Quote a path for POSIX shells; `None` if it contains control characters
+pub fn shell_quote(path: &str) -> Option<String> {
+    if path.is_empty() || path.chars().any(char::is_control) {
+        return None;
+    }
+    Some(format!("'{}'", path.replace('\'', r"'\''")))
+}
+
+/// Input to type for dropped paths, or `None` if none can be typed.
+/// `cd` is set when a lone folder should be changed into.
+pub fn drop_input(paths: &[String], cd: bool) -> Option<String> {
+    if cd {
+        if let [path] = paths {
+            return shell_quote(path).map(|quoted| format!("cd {}\r", quoted));
+        }
+    }
+    let quoted: Vec<String> = paths.iter().filter_map(|path| shell_quote(path)).collect();
+    (!quoted.is_empty()).then(|| format!("{} ", quoted.join(" ")))
+}
+
+/// Type paths dropped on a pane into its session, returning what was typed
+/// (empty if nothing could be)
+#[command]
+pub async fn drop_paths(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    lock_manager: State<'_, Arc<LockManager>>,
+    session_id: String,
+    paths: Vec<String>,
+) -> Result<String, WriteError> {
+    if lock_manager.is_locked() {
+        return Err(WriteError::Locked);
+    }
+    let cd = settings_manager.get().cd_on_folder_drop
+        && paths.len() == 1
+        && Path::new(&paths[0]).is_dir()
+        && pty_manager
+            .is_at_prompt(&session_id)
+            .map_err(|_| WriteError::SessionNotFound {
+                session_id: session_id.clone(),
+            })?;
+    let Some(input) = drop_input(&paths, cd) else {
+        debug!(session_id = %session_id, "Nothing to type for dropped paths");
+        return Ok(String::new());
+    };
+    lock_manager.record_activity();
+    pty_manager.write_to_session(&session_id, &input)?;
+    Ok(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(
+            shell_quote("/Users/me/My Files").as_deref(),
+            Some("'/Users/me/My Files'")
+        );
+        assert_eq!(
+            shell_quote("/tmp/it's $HOME").as_deref(),
+            Some(r"'/tmp/it'\''s $HOME'")
+        );
+        assert_eq!(shell_quote("/tmp/a\nrm -rf ~"), None);
+        assert_eq!(shell_quote(""), None);
+    }
+
+    #[test]
+    fn test_drop_input() {
+        let paths = vec!["/tmp/a b".to_string(), "/tmp/c".to_string()];
+        assert_eq!(
+            drop_input(&paths, false).as_deref(),
+            Some("'/tmp/a b' '/tmp/c' ")
+        );
+        // Only a lone folder is changed into
+        assert_eq!(
+            drop_input(&paths, true).as_deref(),
+            Some("'/tmp/a b' '/tmp/c' ")
+        );
+        assert_eq!(
+            drop_input(&paths[..1], true).as_deref(),
+            Some("cd '/tmp/a b'\r")
+        );
+        assert_eq!(drop_input(&["/tmp/\x1b[A".to_string()], true), None);
+        assert_eq!(drop_input(&[], false), None);
+    }
+}
@@ -0,0 +1,321 @@
+//! Localized backend error messages
+//!
+//! Errors that end up in dialogs (command validation, PTY errors) are looked
+//! up by key in a small catalog instead of being formatted in English, so a
+//! German UI doesn't show English errors. The `locale` setting picks the
+//! language; `system` follows the macOS preferred languages (or `LANG`
+//! elsewhere). Keys without a translation fall back to English.
+
+use crate::settings::AppSettings;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Language of backend messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Locale {
+    /// Follow the system language
+    #[default]
+    System,
+    En,
+    De,
+    Ja,
+    ZhHans,
+}
+
+impl Locale {
+    /// Locale for a language tag such as `de-CH`, `ja_JP.UTF-8` or `zh-Hans-CN`
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let tag = tag.to_lowercase().replace('_', "-");
+        let language = tag.split(['-', '.']).next().unwrap_or_default();
+        match language {
+            "en" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            "ja" => Some(Locale::Ja),
+            // Traditional Chinese isn't translated
+            "zh" if !["-hant", "-tw", "-hk", "-mo"]
+                .iter()
+                .any(|region| tag.contains(region)) =>
+            {
+                Some(Locale::ZhHans)
+            }
+            _ => None,
+        }
+    }
+
+    /// The concrete locale for `System`
+    pub fn resolve(self) -> Self {
+        if self != Locale::System {
+            return self;
+        }
+        system_languages()
+            .iter()
+            .find_map(|tag| Locale::from_tag(tag))
+            .unwrap_or(Locale::En)
+    }
+
+    /// Column of the locale in `CATALOG`
+    fn column(self) -> usize {
+        match self {
+            Locale::System | Locale::En => 0,
+            Locale::De => 1,
+            Locale::Ja => 2,
+            Locale::ZhHans => 3,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn system_languages() -> Vec<String> {
+    crate::macos::preferred_languages()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn system_languages() -> Vec<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// Message templates by key: English, German, Japanese, Simplified Chinese.
+/// `{name}` placeholders are filled from the arguments.
+const CATALOG: &[(&str, [&str; 4])] = &[
+    (
+        "command.empty",
+        [
+            "Command cannot be empty",
+            "Der Befehl darf nicht leer sein",
+            "コマンドを空にすることはできません",
+            "命令不能为空",
+        ],
+    ),
+    (
+        "command.too_long",
+        [
+            "Command too long: {len} chars (max {max})",
+            "Befehl zu lang: {len} Zeichen (maximal {max})",
+            "コマンドが長すぎます: {len} 文字(最大 {max})",
+            "命令过长:{len} 个字符(最多 {max})",
+        ],
+    ),
+    (
+        "command.forbidden_char",
+        [
+            "Command contains forbidden character '{char}'. Use proper arguments instead of shell syntax.",
+            "Der Befehl enthält das unzulässige Zeichen '{char}'. Verwende Argumente statt Shell-Syntax.",
+            "コマンドに使用できない文字 '{char}' が含まれています。シェル構文ではなく引数を使用してください。",
+            "命令包含禁止的字符 '{char}'。请使用参数而不是 shell 语法。",
+        ],
+    ),
+    (
+        "command.starts_with_dash",
+        [
+            "Command cannot start with '-'",
+            "Der Befehl darf nicht mit '-' beginnen",
+            "コマンドを '-' で始めることはできません",
+            "命令不能以 '-' 开头",
+        ],
+    ),
+    (
+        "command.path_traversal",
+        [
+            "Command cannot contain '..' path traversal",
+            "Der Befehl darf keinen Pfad mit '..' enthalten",
+            "コマンドに '..' によるパスの遡りを含めることはできません",
+            "命令不能包含 '..' 路径穿越",
+        ],
+    ),
+    (
+        "command.not_found",
+        [
+            "Command not found: '{cmd}'. Make sure the command is installed and in your PATH.",
+            "Befehl nicht gefunden: '{cmd}'. Stelle sicher, dass er installiert ist und im PATH liegt.",
+            "コマンドが見つかりません: '{cmd}'。インストールされていて PATH に含まれていることを確認してください。",
+            "找不到命令:'{cmd}'。请确认该命令已安装并位于 PATH 中。",
+        ],
+    ),
+    (
+        "command.permission_denied",
+        [
+            "Permission denied: '{cmd}'. You may need to run this command with elevated privileges.",
+            "Zugriff verweigert: '{cmd}'. Der Befehl benötigt möglicherweise erweiterte Rechte.",
+            "アクセスが拒否されました: '{cmd}'。管理者権限での実行が必要な可能性があります。",
+            "权限被拒绝:'{cmd}'。可能需要以更高权限运行此命令。",
+        ],
+    ),
+    (
+        "command.failed",
+        [
+            "Failed to execute '{cmd}': {error}",
+            "'{cmd}' konnte nicht ausgeführt werden: {error}",
+            "'{cmd}' を実行できませんでした: {error}",
+            "无法执行 '{cmd}':{error}",
+        ],
+    ),
+    (
+        "args.too_many",
+        [
+            "Too many arguments: {count} (max {max})",
+            "Zu viele Argumente: {count} (maximal {max})",
+            "引数が多すぎます: {count} 個(最大 {max})",
+            "参数过多:{count} 个(最多 {max})",
+        ],
+    ),
+    (
+        "args.too_long",
+        [
+            "Argument {index} too long: {len} chars (max {max})",
+            "Argument {index} zu lang: {len} Zeichen (maximal {max})",
+            "引数 {index} が長すぎます: {len} 文字(最大 {max})",
+            "参数 {index} 过长:{len} 个字符(最多 {max})",
+        ],
+    ),
+    (
+        "args.null_byte",
+        [
+            "Argument {index} contains null byte",
+            "Argument {index} enthält ein Nullbyte",
+            "引数 {index} に NULL バイトが含まれています",
+            "参数 {index} 包含空字节",
+        ],
+    ),
+    (
+        "pty.invalid_cols",
+        [
+            "Invalid cols: {cols}. Must be between {min} and {max}",
+            "Ungültige Spaltenzahl: {cols}. Erlaubt sind {min} bis {max}",
+            "列数が無効です: {cols}。{min} から {max} の範囲で指定してください",
+            "列数无效:{cols}。必须介于 {min} 和 {max} 之间",
+        ],
+    ),
+    (
+        "pty.invalid_rows",
+        [
+            "Invalid rows: {rows}. Must be between {min} and {max}",
+            "Ungültige Zeilenzahl: {rows}. Erlaubt sind {min} bis {max}",
+            "行数が無効です: {rows}。{min} から {max} の範囲で指定してください",
+            "行数无效:{rows}。必须介于 {min} 和 {max} 之间",
+        ],
+    ),
+    (
+        "pty.session_not_found",
+        [
+            "Session not found: {session_id}",
+            "Sitzung nicht gefunden: {session_id}",
+            "セッションが見つかりません: {session_id}",
+            "找不到会话:{session_id}",
+        ],
+    ),
+    (
+        "pty.read_only",
+        [
+            "Session is read-only: {session_id}",
+            "Sitzung ist schreibgeschützt: {session_id}",
+            "セッションは読み取り専用です: {session_id}",
+            "会话为只读:{session_id}",
+        ],
+    ),
+    (
+        "pty.protected",
+        [
+            "Session is protected: {session_id}",
+            "Sitzung ist geschützt: {session_id}",
+            "セッションは保護されています: {session_id}",
+            "会话受保护:{session_id}",
+        ],
+    ),
+    (
+        "app.locked",
+        [
+            "The app is locked",
+            "Die App ist gesperrt",
+            "アプリはロックされています",
+            "应用已锁定",
+        ],
+    ),
+];
+
+/// Message language, set from the settings
+static LOCALE: RwLock<Locale> = RwLock::new(Locale::En);
+
+/// Apply the `locale` setting
+pub fn configure(settings: &AppSettings) {
+    *LOCALE.write() = settings.locale.resolve();
+}
+
+/// Format the message `key` in `locale`
+pub fn translate(locale: Locale, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let Some((_, templates)) = CATALOG.iter().find(|(k, _)| *k == key) else {
+        return key.to_string();
+    };
+    let mut message = templates[locale.column()].to_string();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    message
+}
+
+/// Format the message `key` in the configured locale
+pub fn tr(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    translate(*LOCALE.read(), key, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(template: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = template
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn test_translations_use_same_placeholders() {
+        for (key, templates) in CATALOG {
+            let english = placeholders(templates[0]);
+            for template in &templates[1..] {
+                assert_eq!(placeholders(template), english, "{}", key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(
+            translate(
+                Locale::En,
+                "pty.session_not_found",
+                &[("session_id", &"s1")]
+            ),
+            "Session not found: s1"
+        );
+        assert_eq!(
+            translate(
+                Locale::De,
+                "args.too_many",
+                &[("count", &300), ("max", &256)]
+            ),
+            "Zu viele Argumente: 300 (maximal 256)"
+        );
+        assert_eq!(translate(Locale::Ja, "no.such.key", &[]), "no.such.key");
+    }
+
+    #[test]
+    fn test_locale_from_tag() {
+        assert_eq!(Locale::from_tag("de-CH"), Some(Locale::De));
+        assert_eq!(Locale::from_tag("ja_JP.UTF-8"), Some(Locale::Ja));
+        assert_eq!(Locale::from_tag("zh-Hans-CN"), Some(Locale::ZhHans));
+        assert_eq!(Locale::from_tag("zh_CN.UTF-8"), Some(Locale::ZhHans));
+        assert_eq!(Locale::from_tag("zh-Hant-TW"), None);
+        assert_eq!(Locale::from_tag("fr-FR"), None);
+        assert_eq!(Locale::De.resolve(), Locale::De);
+    }
+}
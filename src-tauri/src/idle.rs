@@ -0,0 +1,186 @@
+//! Idle shell suspension
+//!
+//! A dozen idle shells with heavy prompt frameworks keep waking up (git
+//! status refreshes, theme timers) and cost battery while the panel is
+//! hidden. With the `idle_suspend` setting on, shells that have had no input
+//! or output for `idle_suspend_minutes` while the window is hidden are either
+//! stopped (`SIGSTOP` to their process group) or moved to the background QoS
+//! band. They resume when the window is shown or the session receives input.
+//!
+//! Only shells sitting at their prompt are suspended: a session whose
+//! foreground process group isn't the shell itself is running a command
+//! (perhaps a quiet build) and is left alone.
+
+use crate::pty::PtyManager;
+use crate::settings::SettingsManager;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{debug, info, warn};
+
+/// How often idle sessions are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default idle time before suspending, in minutes
+pub const DEFAULT_IDLE_SUSPEND_MINUTES: u32 = 15;
+
+/// What to do with idle shells
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdleSuspendMode {
+    /// Leave idle shells alone
+    #[default]
+    Off,
+    /// Move the shell to the background QoS band (macOS)
+    LowerPriority,
+    /// Stop the shell's process group until it's needed
+    Stop,
+}
+
+/// Suspend a shell; returns false if the OS call failed
+pub fn suspend(pid: u32, mode: IdleSuspendMode) -> bool {
+    match mode {
+        IdleSuspendMode::Off => false,
+        IdleSuspendMode::Stop => signal_process_group(pid, libc::SIGSTOP),
+        IdleSuspendMode::LowerPriority => set_background_priority(pid, true),
+    }
+}
+
+/// Undo `suspend`
+pub fn resume(pid: u32, mode: IdleSuspendMode) {
+    let resumed = match mode {
+        IdleSuspendMode::Off => true,
+        IdleSuspendMode::Stop => signal_process_group(pid, libc::SIGCONT),
+        IdleSuspendMode::LowerPriority => set_background_priority(pid, false),
+    };
+    if !resumed {
+        warn!(pid, ?mode, "Failed to resume idle shell");
+    }
+}
+
+fn signal_process_group(pgid: u32, signal: libc::c_int) -> bool {
+    // SAFETY: kill has no memory safety requirements; a negative pid
+    // addresses the process group
+    unsafe { libc::kill(-(pgid as libc::pid_t), signal) == 0 }
+}
+
+#[cfg(target_os = "macos")]
+fn set_background_priority(pid: u32, background: bool) -> bool {
+    // From sys/resource.h
+    const PRIO_DARWIN_PROCESS: libc::c_int = 4;
+    const PRIO_DARWIN_BG: libc::c_int = 0x1000;
+
+    let priority = if background { PRIO_DARWIN_BG } else { 0 };
+    // SAFETY: setpriority has no memory safety requirements
+    unsafe { libc::setpriority(PRIO_DARWIN_PROCESS, pid as libc::id_t, priority) == 0 }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn set_background_priority(_pid: u32, _background: bool) -> bool {
+    // Niceness can't be lowered back without privileges, so there's no
+    // reversible equivalent of the Darwin background band here
+    false
+}
+
+/// Tracks window visibility and runs the idle check
+pub struct IdleSuspender {
+    window_visible: AtomicBool,
+}
+
+impl Default for IdleSuspender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdleSuspender {
+    pub fn new() -> Self {
+        Self {
+            window_visible: AtomicBool::new(false),
+        }
+    }
+
+    /// Start the background idle check
+    pub fn start(app: AppHandle) {
+        thread::spawn(move || loop {
+            thread::sleep(CHECK_INTERVAL);
+            check_idle_sessions(&app);
+        });
+    }
+}
+
+fn check_idle_sessions(app: &AppHandle) {
+    let Some(suspender) = app.try_state::<Arc<IdleSuspender>>() else {
+        return;
+    };
+    if suspender.window_visible.load(Ordering::SeqCst) {
+        return;
+    }
+    let Some(settings) = app.try_state::<Arc<SettingsManager>>() else {
+        return;
+    };
+    let settings = settings.get();
+    if settings.idle_suspend == IdleSuspendMode::Off {
+        return;
+    }
+    let idle_for = Duration::from_secs(u64::from(settings.idle_suspend_minutes.max(1)) * 60);
+    if let Some(pty_manager) = app.try_state::<Arc<PtyManager>>() {
+        let suspended = pty_manager.suspend_idle_sessions(idle_for, settings.idle_suspend);
+        if suspended > 0 {
+            info!(count = suspended, mode = ?settings.idle_suspend, "Suspended idle shells");
+        }
+    }
+}
+
+/// Record window visibility; showing the window resumes every suspended shell
+pub fn window_visibility_changed(app: &AppHandle, visible: bool) {
+    if let Some(suspender) = app.try_state::<Arc<IdleSuspender>>() {
+        suspender.window_visible.store(visible, Ordering::SeqCst);
+    }
+    if visible {
+        if let Some(pty_manager) = app.try_state::<Arc<PtyManager>>() {
+            let resumed = pty_manager.resume_suspended_sessions();
+            if resumed > 0 {
+                debug!(count = resumed, "Resumed idle shells");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_mode_serialization() {
+        assert_eq!(
+            serde_json::to_string(&IdleSuspendMode::LowerPriority).unwrap(),
+            "\"lower_priority\""
+        );
+        assert_eq!(
+            serde_json::from_str::<IdleSuspendMode>("\"stop\"").unwrap(),
+            IdleSuspendMode::Stop
+        );
+    }
+
+    #[test]
+    fn test_stop_and_resume_process_group() {
+        use std::os::unix::process::CommandExt;
+
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        assert!(suspend(pid, IdleSuspendMode::Stop));
+        resume(pid, IdleSuspendMode::Stop);
+        assert!(!suspend(pid, IdleSuspendMode::Off));
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
@@ -0,0 +1,792 @@
+//! Inline image protocols (iTerm2 OSC 1337 and kitty graphics)
+//!
+//! xterm.js can't draw images, so the PTY reader decodes image payloads here,
+//! stores the bytes in an `ImageStore`, and emits a `pty-image` event that
+//! references the stored image. The frontend fetches the data with
+//! `get_inline_image` and renders it over the terminal.
+
+use crate::events;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tauri::{command, AppHandle, Manager, State};
+use tracing::debug;
+
+/// Largest decoded image accepted
+pub const MAX_IMAGE_SIZE: usize = 8 * 1024 * 1024;
+
+/// Total bytes kept in the image store before old images are evicted
+const MAX_STORE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Maximum number of pending (chunked) kitty transmissions per session
+const MAX_PENDING_KITTY_IMAGES: usize = 4;
+
+/// Maximum number of kitty image IDs remembered per session for placement
+const MAX_KITTY_IMAGE_IDS: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageProtocol {
+    Iterm2,
+    Kitty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+    Bmp,
+    /// Raw 24-bit RGB pixels (kitty `f=24`)
+    Rgb,
+    /// Raw 32-bit RGBA pixels (kitty `f=32`)
+    Rgba,
+    Unknown,
+}
+
+impl ImageFormat {
+    /// Detect an encoded image format from its magic bytes
+    fn sniff(data: &[u8]) -> Self {
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            ImageFormat::Png
+        } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+            ImageFormat::Jpeg
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            ImageFormat::Gif
+        } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            ImageFormat::Webp
+        } else if data.starts_with(b"BM") {
+            ImageFormat::Bmp
+        } else {
+            ImageFormat::Unknown
+        }
+    }
+}
+
+/// Read pixel dimensions from an encoded image header
+fn image_dimensions(format: ImageFormat, data: &[u8]) -> Option<(u32, u32)> {
+    let be32 = |at: usize| -> Option<u32> {
+        Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+    };
+    let le16 = |at: usize| -> Option<u32> {
+        Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?) as u32)
+    };
+    match format {
+        // IHDR is always the first chunk
+        ImageFormat::Png => Some((be32(16)?, be32(20)?)),
+        ImageFormat::Gif => Some((le16(6)?, le16(8)?)),
+        ImageFormat::Jpeg => jpeg_dimensions(data),
+        _ => None,
+    }
+}
+
+/// Find the dimensions in a JPEG's start-of-frame segment
+fn jpeg_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let mut pos = 2;
+    while pos + 9 < data.len() {
+        if data[pos] != 0xff {
+            return None;
+        }
+        let marker = data[pos + 1];
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        // SOF0..SOF15, excluding DHT (C4), JPG (C8) and DAC (CC)
+        if (0xc0..=0xcf).contains(&marker) && !matches!(marker, 0xc4 | 0xc8 | 0xcc) {
+            let height = u16::from_be_bytes([data[pos + 5], data[pos + 6]]) as u32;
+            let width = u16::from_be_bytes([data[pos + 7], data[pos + 8]]) as u32;
+            return Some((width, height));
+        }
+        pos += 2 + length;
+    }
+    None
+}
+
+/// Decode base64, ignoring embedded whitespace (iTerm2 payloads may be wrapped)
+fn decode_base64(data: &str) -> Result<Vec<u8>, String> {
+    let compact: String = data.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    if compact.len() / 4 * 3 > MAX_IMAGE_SIZE {
+        return Err(format!("Image exceeds {} bytes", MAX_IMAGE_SIZE));
+    }
+    BASE64
+        .decode(compact)
+        .map_err(|e| format!("Invalid base64 image data: {}", e))
+}
+
+/// A decoded image ready to be stored and displayed
+#[derive(Debug, Clone)]
+pub struct DecodedImage {
+    pub protocol: ImageProtocol,
+    pub format: ImageFormat,
+    pub data: Vec<u8>,
+    pub pixel_width: Option<u32>,
+    pub pixel_height: Option<u32>,
+    /// Requested display width (iTerm2 syntax: `N` cells, `Npx`, `N%` or `auto`)
+    pub display_width: Option<String>,
+    pub display_height: Option<String>,
+    pub preserve_aspect_ratio: bool,
+    pub name: Option<String>,
+}
+
+impl DecodedImage {
+    fn new(protocol: ImageProtocol, format: ImageFormat, data: Vec<u8>) -> Self {
+        let (pixel_width, pixel_height) = match image_dimensions(format, &data) {
+            Some((w, h)) => (Some(w), Some(h)),
+            None => (None, None),
+        };
+        Self {
+            protocol,
+            format,
+            data,
+            pixel_width,
+            pixel_height,
+            display_width: None,
+            display_height: None,
+            preserve_aspect_ratio: true,
+            name: None,
+        }
+    }
+}
+
+/// Parse the data of an OSC 1337 sequence (`File=args:base64`).
+///
+/// Returns `Ok(None)` for other OSC 1337 commands and for files sent without
+/// `inline=1` (downloads), which are not displayed.
+pub fn parse_iterm2_file(data: &str) -> Result<Option<DecodedImage>, String> {
+    let Some(rest) = data.strip_prefix("File=") else {
+        return Ok(None);
+    };
+    let (args, payload) = rest
+        .split_once(':')
+        .ok_or_else(|| "Missing image payload".to_string())?;
+
+    let args: HashMap<&str, &str> = args
+        .split(';')
+        .filter_map(|arg| arg.split_once('='))
+        .collect();
+    if args.get("inline") != Some(&"1") {
+        return Ok(None);
+    }
+
+    let bytes = decode_base64(payload)?;
+    let mut image = DecodedImage::new(ImageProtocol::Iterm2, ImageFormat::sniff(&bytes), bytes);
+    image.display_width = args.get("width").map(|w| w.to_string());
+    image.display_height = args.get("height").map(|h| h.to_string());
+    image.preserve_aspect_ratio = args.get("preserveAspectRatio") != Some(&"0");
+    image.name = args
+        .get("name")
+        .and_then(|name| BASE64.decode(name).ok())
+        .map(|name| String::from_utf8_lossy(&name).into_owned());
+    Ok(Some(image))
+}
+
+/// Outcome of a kitty graphics command
+#[derive(Debug)]
+pub enum KittyAction {
+    /// Image transmitted and should be displayed
+    Display(DecodedImage),
+    /// Previously transmitted image (by kitty image ID) should be displayed
+    Place(u32),
+    /// Image transmitted for later placement
+    Stored(DecodedImage),
+    /// Nothing to display (query, delete, or a chunk of a larger image)
+    None,
+}
+
+/// Result of handling a kitty graphics command
+#[derive(Debug)]
+pub struct KittyResult {
+    pub action: KittyAction,
+    /// Kitty image ID (`i=`), if the client set one
+    pub image_id: Option<u32>,
+    /// Response to write back to the PTY
+    pub response: Option<String>,
+}
+
+/// A chunked kitty transmission being assembled
+struct PendingKittyImage {
+    keys: HashMap<char, String>,
+    payload: String,
+}
+
+/// Per-session kitty graphics protocol state
+#[derive(Default)]
+pub struct KittyGraphics {
+    /// Chunked transmissions keyed by kitty image ID (0 when unset)
+    pending: HashMap<u32, PendingKittyImage>,
+    /// ID of the transmission that chunks without `i=` continue
+    last_pending: Option<u32>,
+}
+
+impl KittyGraphics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle the data of an APC sequence.
+    ///
+    /// Returns `None` if the APC is not a kitty graphics command.
+    pub fn handle_apc(&mut self, data: &str) -> Option<KittyResult> {
+        let body = data.strip_prefix('G')?;
+        let (control, payload) = body.split_once(';').unwrap_or((body, ""));
+        let mut keys: HashMap<char, String> = control
+            .split(',')
+            .filter_map(|kv| {
+                let (k, v) = kv.split_once('=')?;
+                let mut chars = k.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(k), None) => Some((k, v.to_string())),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        let more = keys.get(&'m').map(String::as_str) == Some("1");
+        let id_key = keys
+            .get(&'i')
+            .and_then(|i| i.parse::<u32>().ok())
+            .or(self.last_pending)
+            .unwrap_or(0);
+
+        // Continuation chunks carry only `m=` (and optionally `q=`); merge with the first chunk
+        let payload = match self.pending.remove(&id_key) {
+            Some(mut pending) => {
+                pending.payload.push_str(payload);
+                for (k, v) in keys.drain() {
+                    pending.keys.entry(k).or_insert(v);
+                }
+                pending
+                    .keys
+                    .insert('m', if more { "1" } else { "0" }.to_string());
+                keys = pending.keys;
+                pending.payload
+            }
+            None => payload.to_string(),
+        };
+
+        if more {
+            if payload.len() / 4 * 3 > MAX_IMAGE_SIZE {
+                self.last_pending = None;
+                return Some(Self::result(
+                    &keys,
+                    Err("EFBIG:image too large".to_string()),
+                ));
+            }
+            if self.pending.len() >= MAX_PENDING_KITTY_IMAGES {
+                self.pending.clear();
+            }
+            self.pending
+                .insert(id_key, PendingKittyImage { keys, payload });
+            self.last_pending = Some(id_key);
+            return Some(KittyResult {
+                action: KittyAction::None,
+                image_id: None,
+                response: None,
+            });
+        }
+        self.last_pending = None;
+
+        let action = keys.get(&'a').map(String::as_str).unwrap_or("t");
+        let result = match action {
+            "t" | "T" | "q" => Self::decode(&keys, &payload).map(|image| match action {
+                "T" => KittyAction::Display(image),
+                "t" => KittyAction::Stored(image),
+                _ => KittyAction::None,
+            }),
+            "p" => match keys.get(&'i').and_then(|i| i.parse::<u32>().ok()) {
+                Some(id) => Ok(KittyAction::Place(id)),
+                None => Err("EINVAL:placement requires an image id".to_string()),
+            },
+            // Deletion is handled by the frontend's own bookkeeping
+            "d" => Ok(KittyAction::None),
+            _ => Err(format!("EINVAL:unsupported action {}", action)),
+        };
+        Some(Self::result(&keys, result))
+    }
+
+    fn decode(keys: &HashMap<char, String>, payload: &str) -> Result<DecodedImage, String> {
+        let medium = keys.get(&'t').map(String::as_str).unwrap_or("d");
+        if medium != "d" {
+            // File and shared-memory transmission would let remote programs read local files
+            return Err("ENOTSUPPORTED:only direct transmission is supported".to_string());
+        }
+        if keys.contains_key(&'o') {
+            return Err("ENOTSUPPORTED:compression is not supported".to_string());
+        }
+
+        let data = decode_base64(payload).map_err(|e| format!("EINVAL:{}", e))?;
+        let number = |k: char| keys.get(&k).and_then(|v| v.parse::<u32>().ok());
+
+        let format = match keys.get(&'f').map(String::as_str).unwrap_or("32") {
+            "24" => ImageFormat::Rgb,
+            "32" => ImageFormat::Rgba,
+            "100" => ImageFormat::Png,
+            f => return Err(format!("EINVAL:unsupported format {}", f)),
+        };
+
+        let mut image = DecodedImage::new(ImageProtocol::Kitty, format, data);
+        if matches!(format, ImageFormat::Rgb | ImageFormat::Rgba) {
+            let (width, height) = match (number('s'), number('v')) {
+                (Some(w), Some(h)) => (w, h),
+                _ => return Err("EINVAL:raw pixel data requires s and v".to_string()),
+            };
+            let bytes_per_pixel = if format == ImageFormat::Rgb { 3 } else { 4 };
+            let expected = (width as usize)
+                .checked_mul(height as usize)
+                .and_then(|n| n.checked_mul(bytes_per_pixel));
+            if expected != Some(image.data.len()) {
+                return Err("EINVAL:pixel data size does not match dimensions".to_string());
+            }
+            image.pixel_width = Some(width);
+            image.pixel_height = Some(height);
+        }
+        // Kitty sizes placements in cells, which maps to iTerm2's bare-number syntax
+        image.display_width = number('c').map(|c| c.to_string());
+        image.display_height = number('r').map(|r| r.to_string());
+        image.preserve_aspect_ratio =
+            !(image.display_width.is_some() && image.display_height.is_some());
+        Ok(image)
+    }
+
+    /// Build the result and the response kitty clients expect
+    fn result(keys: &HashMap<char, String>, result: Result<KittyAction, String>) -> KittyResult {
+        let image_id = keys.get(&'i').and_then(|i| i.parse::<u32>().ok());
+        let quiet = keys.get(&'q').map(String::as_str).unwrap_or("0");
+        let message = match &result {
+            Ok(_) if quiet == "0" => Some("OK".to_string()),
+            Err(e) if quiet != "2" => Some(e.clone()),
+            _ => None,
+        };
+        // Responses are only sent when the client can correlate them
+        let response = match (image_id, message) {
+            (Some(id), Some(message)) => Some(format!("\x1b_Gi={};{}\x1b\\", id, message)),
+            _ => None,
+        };
+        KittyResult {
+            action: result.unwrap_or(KittyAction::None),
+            image_id,
+            response,
+        }
+    }
+}
+
+/// Metadata of a stored image, sent in the `pty-image` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PtyImage {
+    pub session_id: String,
+    pub image_id: String,
+    pub protocol: ImageProtocol,
+    pub format: ImageFormat,
+    pub pixel_width: Option<u32>,
+    pub pixel_height: Option<u32>,
+    pub display_width: Option<String>,
+    pub display_height: Option<String>,
+    pub preserve_aspect_ratio: bool,
+    pub name: Option<String>,
+    pub byte_size: usize,
+}
+
+/// Image data returned by `get_inline_image`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InlineImageData {
+    pub format: ImageFormat,
+    pub pixel_width: Option<u32>,
+    pub pixel_height: Option<u32>,
+    /// Base64-encoded image bytes
+    pub data: String,
+}
+
+struct StoredImage {
+    format: ImageFormat,
+    pixel_width: Option<u32>,
+    pixel_height: Option<u32>,
+    data: Arc<Vec<u8>>,
+}
+
+struct StoreInner {
+    images: HashMap<String, StoredImage>,
+    /// Insertion order for eviction
+    order: VecDeque<String>,
+    total_size: usize,
+}
+
+/// Bounded store of decoded inline images, shared by all sessions
+pub struct ImageStore {
+    inner: Mutex<StoreInner>,
+    next_id: AtomicU64,
+}
+
+impl Default for ImageStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImageStore {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(StoreInner {
+                images: HashMap::new(),
+                order: VecDeque::new(),
+                total_size: 0,
+            }),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Store an image and return its metadata for the `pty-image` event
+    pub fn insert(&self, session_id: &str, image: DecodedImage) -> PtyImage {
+        let image_id = format!("img-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let byte_size = image.data.len();
+
+        let mut inner = self.inner.lock();
+        while inner.total_size + byte_size > MAX_STORE_SIZE {
+            let Some(oldest) = inner.order.pop_front() else {
+                break;
+            };
+            if let Some(evicted) = inner.images.remove(&oldest) {
+                inner.total_size -= evicted.data.len();
+            }
+        }
+        inner.images.insert(
+            image_id.clone(),
+            StoredImage {
+                format: image.format,
+                pixel_width: image.pixel_width,
+                pixel_height: image.pixel_height,
+                data: Arc::new(image.data),
+            },
+        );
+        inner.order.push_back(image_id.clone());
+        inner.total_size += byte_size;
+
+        PtyImage {
+            session_id: session_id.to_string(),
+            image_id,
+            protocol: image.protocol,
+            format: image.format,
+            pixel_width: image.pixel_width,
+            pixel_height: image.pixel_height,
+            display_width: image.display_width,
+            display_height: image.display_height,
+            preserve_aspect_ratio: image.preserve_aspect_ratio,
+            name: image.name,
+            byte_size,
+        }
+    }
+
+    pub fn get(&self, image_id: &str) -> Option<InlineImageData> {
+        let (format, pixel_width, pixel_height, data) = {
+            let inner = self.inner.lock();
+            let image = inner.images.get(image_id)?;
+            (
+                image.format,
+                image.pixel_width,
+                image.pixel_height,
+                image.data.clone(),
+            )
+        };
+        // Encode outside the lock
+        Some(InlineImageData {
+            format,
+            pixel_width,
+            pixel_height,
+            data: BASE64.encode(data.as_slice()),
+        })
+    }
+
+    pub fn contains(&self, image_id: &str) -> bool {
+        self.inner.lock().images.contains_key(image_id)
+    }
+}
+
+/// Per-session image state kept by the PTY reader thread
+#[derive(Default)]
+pub struct SessionImages {
+    kitty: KittyGraphics,
+    /// Stored images by kitty image ID, for `a=p` placements
+    kitty_images: HashMap<u32, PtyImage>,
+}
+
+impl SessionImages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle an OSC 1337 sequence
+    pub fn handle_iterm2(&mut self, app: &AppHandle, session_id: &str, data: &str) {
+        match parse_iterm2_file(data) {
+            Ok(Some(image)) => self.store(app, session_id, None, image, true),
+            Ok(None) => {}
+            Err(e) => debug!(session_id = %session_id, error = %e, "Invalid iTerm2 image"),
+        }
+    }
+
+    /// Handle an APC sequence, returning a kitty response to write back to the PTY
+    pub fn handle_kitty(
+        &mut self,
+        app: &AppHandle,
+        session_id: &str,
+        data: &str,
+    ) -> Option<String> {
+        let result = self.kitty.handle_apc(data)?;
+        match result.action {
+            KittyAction::Display(image) => {
+                self.store(app, session_id, result.image_id, image, true);
+            }
+            // Images transmitted without an ID can never be placed
+            KittyAction::Stored(image) if result.image_id.is_some() => {
+                self.store(app, session_id, result.image_id, image, false);
+            }
+            KittyAction::Place(id) => match self.kitty_images.get(&id) {
+                Some(meta) => {
+                    let _ = events::emit(app, meta.clone());
+                }
+                None => {
+                    return Some(format!("\x1b_Gi={};ENOENT:image not found\x1b\\", id));
+                }
+            },
+            _ => {}
+        }
+        result.response
+    }
+
+    fn store(
+        &mut self,
+        app: &AppHandle,
+        session_id: &str,
+        kitty_id: Option<u32>,
+        image: DecodedImage,
+        display: bool,
+    ) {
+        let Some(store) = app.try_state::<Arc<ImageStore>>() else {
+            return;
+        };
+        let meta = store.insert(session_id, image);
+        if display {
+            let _ = events::emit(app, meta.clone());
+        }
+        if let Some(id) = kitty_id {
+            if self.kitty_images.len() >= MAX_KITTY_IMAGE_IDS {
+                self.kitty_images.clear();
+            }
+            self.kitty_images.insert(id, meta);
+        }
+    }
+}
+
+/// Fetch a stored inline image by the ID from a `pty-image` event
+#[command]
+pub fn get_inline_image(
+    image_store: State<'_, Arc<ImageStore>>,
+    image_id: String,
+) -> Result<InlineImageData, String> {
+    image_store
+        .get(&image_id)
+        .ok_or_else(|| format!("Image not found: {}", image_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1x1 transparent PNG
+    const PNG_1X1: &[u8] = &[
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f,
+        0x15, 0xc4, 0x89,
+    ];
+
+    // ============== iTerm2 tests ==============
+
+    #[test]
+    fn test_iterm2_inline_png() {
+        let name = BASE64.encode("dot.png");
+        let data = format!(
+            "File=name={};size={};width=10;height=auto;inline=1:{}",
+            name,
+            PNG_1X1.len(),
+            BASE64.encode(PNG_1X1)
+        );
+        let image = parse_iterm2_file(&data).unwrap().unwrap();
+        assert_eq!(image.protocol, ImageProtocol::Iterm2);
+        assert_eq!(image.format, ImageFormat::Png);
+        assert_eq!((image.pixel_width, image.pixel_height), (Some(1), Some(1)));
+        assert_eq!(image.display_width.as_deref(), Some("10"));
+        assert_eq!(image.display_height.as_deref(), Some("auto"));
+        assert_eq!(image.name.as_deref(), Some("dot.png"));
+        assert!(image.preserve_aspect_ratio);
+    }
+
+    #[test]
+    fn test_iterm2_payload_with_whitespace() {
+        let encoded = BASE64.encode(PNG_1X1);
+        let (a, b) = encoded.split_at(10);
+        let data = format!("File=inline=1:{}\n{}", a, b);
+        assert!(parse_iterm2_file(&data).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_iterm2_non_inline_and_other_commands_ignored() {
+        let data = format!("File=name=eA==:{}", BASE64.encode(PNG_1X1));
+        assert!(parse_iterm2_file(&data).unwrap().is_none());
+        assert!(parse_iterm2_file("SetMark").unwrap().is_none());
+        assert!(parse_iterm2_file("CurrentDir=/tmp").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_iterm2_invalid_payload() {
+        assert!(parse_iterm2_file("File=inline=1:!!!notbase64").is_err());
+        assert!(parse_iterm2_file("File=inline=1").is_err());
+    }
+
+    #[test]
+    fn test_gif_and_jpeg_dimensions() {
+        let gif = b"GIF89a\x40\x01\xf0\x00rest";
+        assert_eq!(ImageFormat::sniff(gif), ImageFormat::Gif);
+        assert_eq!(image_dimensions(ImageFormat::Gif, gif), Some((320, 240)));
+
+        // SOI, APP0 (length 4), SOF0 with height 2, width 3
+        let jpeg = [
+            0xff, 0xd8, 0xff, 0xe0, 0x00, 0x04, 0x00, 0x00, 0xff, 0xc0, 0x00, 0x11, 0x08, 0x00,
+            0x02, 0x00, 0x03, 0x03,
+        ];
+        assert_eq!(ImageFormat::sniff(&jpeg), ImageFormat::Jpeg);
+        assert_eq!(image_dimensions(ImageFormat::Jpeg, &jpeg), Some((3, 2)));
+    }
+
+    // ============== Kitty tests ==============
+
+    #[test]
+    fn test_kitty_transmit_and_display_png() {
+        let mut kitty = KittyGraphics::new();
+        let apc = format!("Ga=T,f=100,i=7,c=20,r=5;{}", BASE64.encode(PNG_1X1));
+        let result = kitty.handle_apc(&apc).unwrap();
+
+        assert_eq!(result.image_id, Some(7));
+        assert_eq!(result.response.as_deref(), Some("\x1b_Gi=7;OK\x1b\\"));
+        let KittyAction::Display(image) = result.action else {
+            panic!("expected display action");
+        };
+        assert_eq!(image.format, ImageFormat::Png);
+        assert_eq!(image.display_width.as_deref(), Some("20"));
+        assert_eq!(image.display_height.as_deref(), Some("5"));
+    }
+
+    #[test]
+    fn test_kitty_raw_rgba_requires_matching_size() {
+        let mut kitty = KittyGraphics::new();
+        let pixels = BASE64.encode([0u8; 2 * 2 * 4]);
+
+        let ok = kitty
+            .handle_apc(&format!("Ga=T,f=32,s=2,v=2;{}", pixels))
+            .unwrap();
+        let KittyAction::Display(image) = ok.action else {
+            panic!("expected display action");
+        };
+        assert_eq!((image.pixel_width, image.pixel_height), (Some(2), Some(2)));
+        // No image ID, so no response
+        assert!(ok.response.is_none());
+
+        let bad = kitty
+            .handle_apc(&format!("Ga=T,f=32,s=3,v=2,i=1;{}", pixels))
+            .unwrap();
+        assert!(matches!(bad.action, KittyAction::None));
+        assert!(bad.response.unwrap().contains("EINVAL"));
+    }
+
+    #[test]
+    fn test_kitty_chunked_transmission() {
+        let mut kitty = KittyGraphics::new();
+        let encoded = BASE64.encode(PNG_1X1);
+        let (first, second) = encoded.split_at(16);
+
+        let partial = kitty
+            .handle_apc(&format!("Ga=T,f=100,i=3,m=1;{}", first))
+            .unwrap();
+        assert!(matches!(partial.action, KittyAction::None));
+        assert!(partial.response.is_none());
+
+        let done = kitty.handle_apc(&format!("Gm=0;{}", second)).unwrap();
+        assert_eq!(done.image_id, Some(3));
+        assert!(matches!(done.action, KittyAction::Display(_)));
+    }
+
+    #[test]
+    fn test_kitty_query_responds_without_displaying() {
+        let mut kitty = KittyGraphics::new();
+        let result = kitty.handle_apc("Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA").unwrap();
+        assert!(matches!(result.action, KittyAction::None));
+        assert_eq!(result.response.as_deref(), Some("\x1b_Gi=31;OK\x1b\\"));
+    }
+
+    #[test]
+    fn test_kitty_rejects_file_transmission() {
+        let mut kitty = KittyGraphics::new();
+        let path = BASE64.encode("/etc/passwd");
+        let result = kitty
+            .handle_apc(&format!("Ga=T,t=f,f=100,i=2;{}", path))
+            .unwrap();
+        assert!(matches!(result.action, KittyAction::None));
+        assert!(result.response.unwrap().contains("ENOTSUPPORTED"));
+    }
+
+    #[test]
+    fn test_kitty_quiet_suppresses_responses() {
+        let mut kitty = KittyGraphics::new();
+        let ok = kitty
+            .handle_apc(&format!("Ga=t,f=100,i=4,q=1;{}", BASE64.encode(PNG_1X1)))
+            .unwrap();
+        assert!(ok.response.is_none());
+        assert!(matches!(ok.action, KittyAction::Stored(_)));
+
+        let err = kitty.handle_apc("Ga=T,t=f,i=5,q=2;eA==").unwrap();
+        assert!(err.response.is_none());
+    }
+
+    #[test]
+    fn test_kitty_place_and_non_graphics_apc() {
+        let mut kitty = KittyGraphics::new();
+        let result = kitty.handle_apc("Ga=p,i=9").unwrap();
+        assert!(matches!(result.action, KittyAction::Place(9)));
+        assert!(kitty.handle_apc("Xsomething").is_none());
+    }
+
+    // ============== ImageStore tests ==============
+
+    #[test]
+    fn test_store_insert_and_get() {
+        let store = ImageStore::new();
+        let image = DecodedImage::new(ImageProtocol::Iterm2, ImageFormat::Png, PNG_1X1.to_vec());
+        let meta = store.insert("session-1", image);
+
+        assert_eq!(meta.session_id, "session-1");
+        assert_eq!(meta.byte_size, PNG_1X1.len());
+        let fetched = store.get(&meta.image_id).unwrap();
+        assert_eq!(BASE64.decode(fetched.data).unwrap(), PNG_1X1);
+        assert!(store.get("img-missing").is_none());
+    }
+
+    #[test]
+    fn test_store_evicts_oldest() {
+        let store = ImageStore::new();
+        let big = || {
+            DecodedImage::new(
+                ImageProtocol::Kitty,
+                ImageFormat::Rgba,
+                vec![0u8; MAX_STORE_SIZE / 2],
+            )
+        };
+        let first = store.insert("s", big());
+        let second = store.insert("s", big());
+        let third = store.insert("s", big());
+
+        assert!(!store.contains(&first.image_id));
+        assert!(store.contains(&second.image_id));
+        assert!(store.contains(&third.image_id));
+    }
+}
@@ -0,0 +1,1225 @@
+//! Keyboard input encoding for PTY sessions
+//!
+//! The frontend can forward key events here instead of letting xterm.js encode
+//! them, so the backend can honor modes that programs negotiate over the PTY.
+//! `InputModes` is updated by the reader thread from private-mode CSI
+//! sequences and read when encoding keys.
+//!
+//! Supports the legacy xterm encoding and the kitty progressive keyboard
+//! enhancement protocol (<https://sw.kovidgoyal.net/kitty/keyboard-protocol/>).
+//!
+//! Mouse events are encoded the same way when a program enables xterm mouse
+//! tracking, subject to the user's `mouse_reporting` preference. Focus reports
+//! follow the window's key state rather than xterm.js focus, since the panel
+//! is hidden and shown without the webview noticing.
+
+use serde::{Deserialize, Serialize};
+
+/// Kitty flag: disambiguate escape codes
+pub const KITTY_DISAMBIGUATE: u32 = 0b1;
+/// Kitty flag: report event types (press/repeat/release)
+pub const KITTY_REPORT_EVENTS: u32 = 0b10;
+/// Kitty flag: report all keys as escape codes
+pub const KITTY_REPORT_ALL_KEYS: u32 = 0b1000;
+/// Kitty flags this implementation supports (alternate keys and associated text are not)
+const KITTY_SUPPORTED_FLAGS: u32 = KITTY_DISAMBIGUATE | KITTY_REPORT_EVENTS | KITTY_REPORT_ALL_KEYS;
+
+/// Maximum depth of the kitty keyboard flag stack
+const MAX_KITTY_STACK_DEPTH: usize = 16;
+
+/// Key event type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyEventType {
+    #[default]
+    Press,
+    Repeat,
+    Release,
+}
+
+/// A key event from the frontend, using DOM `KeyboardEvent.key` names
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyEvent {
+    pub key: String,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub alt: bool,
+    #[serde(default)]
+    pub ctrl: bool,
+    /// Command key on macOS; such events are app shortcuts and never encoded
+    #[serde(default)]
+    pub meta: bool,
+    #[serde(default)]
+    pub event_type: KeyEventType,
+}
+
+impl KeyEvent {
+    /// Kitty/xterm modifier parameter (1 + bitmask), or 1 when no modifiers.
+    /// Cmd is kitty's Super; it only gets here through `encode_shortcut`.
+    fn modifier_param(&self) -> u32 {
+        1 + u32::from(self.shift)
+            + (u32::from(self.alt) << 1)
+            + (u32::from(self.ctrl) << 2)
+            + (u32::from(self.meta) << 3)
+    }
+
+    fn has_modifiers(&self) -> bool {
+        self.modifier_param() > 1
+    }
+}
+
+/// Input-related terminal modes negotiated by the program in a session
+#[derive(Debug, Clone, Default)]
+pub struct InputModes {
+    /// Kitty keyboard flag stack of the main screen; the top is the active set
+    kitty_stack: Vec<u32>,
+    /// Kitty keyboard flag stack of the alternate screen, emptied on entering it
+    alternate_kitty_stack: Vec<u32>,
+    /// DECCKM (`CSI ? 1 h`): cursor keys send SS3 instead of CSI
+    application_cursor: bool,
+    /// Mouse events the program asked for (`CSI ? 9/1000/1002/1003 h`)
+    mouse_tracking: MouseTracking,
+    /// Mouse report format (`CSI ? 1006 h` for SGR)
+    mouse_encoding: MouseEncoding,
+    /// Alternate screen buffer (`CSI ? 47/1047/1049 h`), used by full-screen programs
+    alternate_screen: bool,
+    /// Focus reporting (`CSI ? 1004 h`): focus changes are sent as `CSI I` / `CSI O`
+    focus_reporting: bool,
+    /// Bracketed paste (`CSI ? 2004 h`): pastes are wrapped in `CSI 200~` / `CSI 201~`
+    bracketed_paste: bool,
+    /// Whether the frontend sends keys here to be encoded; until it does, keys
+    /// are encoded by xterm.js, so kitty queries get no reply
+    keys_encoded: bool,
+}
+
+/// Mouse tracking mode requested by the program
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseTracking {
+    #[default]
+    Off,
+    /// Mode 9: button presses only
+    X10,
+    /// Mode 1000: presses, releases and wheel
+    Normal,
+    /// Mode 1002: also motion while a button is held
+    ButtonEvent,
+    /// Mode 1003: all motion
+    AnyEvent,
+}
+
+/// Mouse report format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum MouseEncoding {
+    /// `CSI M` followed by three bytes offset by 32
+    #[default]
+    Default,
+    /// Mode 1006: `CSI < b ; x ; y M/m`
+    Sgr,
+}
+
+impl InputModes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget all negotiated modes, as after a full reset (RIS)
+    pub fn reset(&mut self) {
+        *self = Self {
+            keys_encoded: self.keys_encoded,
+            ..Self::default()
+        };
+    }
+
+    /// Note that the frontend sends keys through `encode_key`
+    pub fn set_keys_encoded(&mut self) {
+        self.keys_encoded = true;
+    }
+
+    /// Update a DEC private mode (`CSI ? mode h` / `CSI ? mode l`)
+    pub fn set_private_mode(&mut self, mode: u32, enabled: bool) {
+        let tracking = match mode {
+            1 => {
+                self.application_cursor = enabled;
+                return;
+            }
+            1006 => {
+                self.mouse_encoding = if enabled {
+                    MouseEncoding::Sgr
+                } else {
+                    MouseEncoding::Default
+                };
+                return;
+            }
+            1004 => {
+                self.focus_reporting = enabled;
+                return;
+            }
+            2004 => {
+                self.bracketed_paste = enabled;
+                return;
+            }
+            47 | 1047 | 1049 => {
+                if enabled && !self.alternate_screen {
+                    self.alternate_kitty_stack.clear();
+                }
+                self.alternate_screen = enabled;
+                return;
+            }
+            9 => MouseTracking::X10,
+            1000 => MouseTracking::Normal,
+            1002 => MouseTracking::ButtonEvent,
+            1003 => MouseTracking::AnyEvent,
+            _ => return,
+        };
+        // Like xterm, resetting any tracking mode turns tracking off
+        self.mouse_tracking = if enabled {
+            tracking
+        } else {
+            MouseTracking::Off
+        };
+    }
+
+    /// Mouse tracking mode currently requested by the program
+    pub fn mouse_tracking(&self) -> MouseTracking {
+        self.mouse_tracking
+    }
+
+    /// Report for a focus change, if the program asked for focus reporting
+    pub fn focus_report(&self, focused: bool) -> Option<&'static str> {
+        match (self.focus_reporting, focused) {
+            (false, _) => None,
+            (true, true) => Some("\x1b[I"),
+            (true, false) => Some("\x1b[O"),
+        }
+    }
+
+    /// Whether the program switched to the alternate screen
+    pub fn alternate_screen(&self) -> bool {
+        self.alternate_screen
+    }
+
+    /// Whether the program asked for pastes to be bracketed
+    pub fn bracketed_paste(&self) -> bool {
+        self.bracketed_paste
+    }
+
+    /// Active kitty keyboard flags
+    pub fn kitty_flags(&self) -> u32 {
+        let stack = if self.alternate_screen {
+            &self.alternate_kitty_stack
+        } else {
+            &self.kitty_stack
+        };
+        stack.last().copied().unwrap_or(0)
+    }
+
+    /// Kitty keyboard flag stack of the current screen
+    fn kitty_stack_mut(&mut self) -> &mut Vec<u32> {
+        if self.alternate_screen {
+            &mut self.alternate_kitty_stack
+        } else {
+            &mut self.kitty_stack
+        }
+    }
+
+    /// Handle a kitty keyboard CSI (`CSI > flags u`, `CSI < n u`, `CSI = flags ; mode u`,
+    /// `CSI ? u`). Returns the reply for a query.
+    ///
+    /// When the protocol is disabled, requests are ignored and queries get no
+    /// reply, so programs fall back to legacy encoding. Queries also go
+    /// unanswered while keys don't go through `encode_key`.
+    pub fn handle_kitty_csi(
+        &mut self,
+        marker: char,
+        params: &str,
+        enabled: bool,
+    ) -> Option<String> {
+        if !enabled {
+            self.kitty_stack.clear();
+            self.alternate_kitty_stack.clear();
+            return None;
+        }
+        let mut values = params.split(';').map(|p| p.parse::<u32>().ok());
+        let first = values.next().flatten();
+        match marker {
+            '?' => {
+                return self
+                    .keys_encoded
+                    .then(|| format!("\x1b[?{}u", self.kitty_flags()));
+            }
+            '>' => {
+                let stack = self.kitty_stack_mut();
+                if stack.len() >= MAX_KITTY_STACK_DEPTH {
+                    stack.remove(0);
+                }
+                stack.push(first.unwrap_or(0) & KITTY_SUPPORTED_FLAGS);
+            }
+            '<' => {
+                let count = first.unwrap_or(1).max(1) as usize;
+                let stack = self.kitty_stack_mut();
+                let keep = stack.len().saturating_sub(count);
+                stack.truncate(keep);
+            }
+            '=' => {
+                let flags = first.unwrap_or(0) & KITTY_SUPPORTED_FLAGS;
+                let mode = values.next().flatten().unwrap_or(1);
+                let current = self.kitty_flags();
+                let updated = match mode {
+                    2 => current | flags,
+                    3 => current & !flags,
+                    _ => flags,
+                };
+                let stack = self.kitty_stack_mut();
+                match stack.last_mut() {
+                    Some(top) => *top = updated,
+                    None => stack.push(updated),
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Keys with a legacy `CSI n ~` encoding
+fn tilde_code(key: &str) -> Option<u32> {
+    Some(match key {
+        "Insert" => 2,
+        "Delete" => 3,
+        "PageUp" => 5,
+        "PageDown" => 6,
+        "F5" => 15,
+        "F6" => 17,
+        "F7" => 18,
+        "F8" => 19,
+        "F9" => 20,
+        "F10" => 21,
+        "F11" => 23,
+        "F12" => 24,
+        _ => return None,
+    })
+}
+
+/// Keys with a legacy `CSI 1 ; mods X` / `SS3 X` encoding
+fn letter_code(key: &str) -> Option<char> {
+    Some(match key {
+        "ArrowUp" => 'A',
+        "ArrowDown" => 'B',
+        "ArrowRight" => 'C',
+        "ArrowLeft" => 'D',
+        "End" => 'F',
+        "Home" => 'H',
+        "F1" => 'P',
+        "F2" => 'Q',
+        "F3" => 'R',
+        "F4" => 'S',
+        _ => return None,
+    })
+}
+
+/// Unicode code point kitty uses for a key, if it has one
+fn kitty_key_code(key: &str) -> Option<u32> {
+    Some(match key {
+        "Escape" => 27,
+        "Enter" => 13,
+        "Tab" => 9,
+        "Backspace" => 127,
+        _ => {
+            let mut chars = key.chars();
+            let c = chars.next()?;
+            if chars.next().is_some() {
+                return None;
+            }
+            // Kitty reports the unshifted key
+            c.to_lowercase().next().unwrap_or(c) as u32
+        }
+    })
+}
+
+/// The text a key produces, if it is a single printable character
+fn key_text(key: &str) -> Option<&str> {
+    let mut chars = key.chars();
+    let c = chars.next()?;
+    (chars.next().is_none() && !c.is_control()).then_some(key)
+}
+
+/// Encode a key event for the PTY using the session's negotiated modes.
+///
+/// Returns `None` for keys the terminal doesn't send (e.g. Cmd shortcuts or
+/// releases the program didn't ask for).
+pub fn encode_key(event: &KeyEvent, modes: &InputModes) -> Option<String> {
+    if event.meta {
+        return None;
+    }
+    let flags = modes.kitty_flags();
+    if flags != 0 {
+        return encode_kitty(event, flags, modes.application_cursor);
+    }
+    if event.event_type == KeyEventType::Release {
+        return None;
+    }
+    encode_legacy(event, modes.application_cursor)
+}
+
+/// Encode a Cmd shortcut passed through to the program (see `passthrough.rs`).
+/// With the kitty protocol Cmd is reported as Super; otherwise it's sent like
+/// Alt, with an ESC prefix, which is how editors and tmux bind Meta keys.
+pub fn encode_shortcut(event: &KeyEvent, modes: &InputModes) -> Option<String> {
+    let flags = modes.kitty_flags();
+    if flags != 0 {
+        return encode_kitty(event, flags, modes.application_cursor);
+    }
+    if event.event_type == KeyEventType::Release {
+        return None;
+    }
+    let as_alt = KeyEvent {
+        meta: false,
+        alt: true,
+        ..event.clone()
+    };
+    encode_legacy(&as_alt, modes.application_cursor)
+}
+
+fn encode_legacy(event: &KeyEvent, application_cursor: bool) -> Option<String> {
+    let mods = event.modifier_param();
+    let key = event.key.as_str();
+
+    if let Some(code) = tilde_code(key) {
+        return Some(if mods > 1 {
+            format!("\x1b[{};{}~", code, mods)
+        } else {
+            format!("\x1b[{}~", code)
+        });
+    }
+    if let Some(letter) = letter_code(key) {
+        let is_function_key = key.starts_with('F');
+        return Some(if mods > 1 {
+            format!("\x1b[1;{}{}", mods, letter)
+        } else if is_function_key || application_cursor {
+            format!("\x1bO{}", letter)
+        } else {
+            format!("\x1b[{}", letter)
+        });
+    }
+
+    let alt_prefix = if event.alt { "\x1b" } else { "" };
+    let base = match key {
+        "Enter" => "\r".to_string(),
+        "Tab" if event.shift => return Some("\x1b[Z".to_string()),
+        "Tab" => "\t".to_string(),
+        "Backspace" if event.ctrl => "\x08".to_string(),
+        "Backspace" => "\x7f".to_string(),
+        "Escape" => "\x1b".to_string(),
+        _ => {
+            let text = key_text(key)?;
+            if event.ctrl {
+                ctrl_char(text.chars().next()?)?.to_string()
+            } else {
+                text.to_string()
+            }
+        }
+    };
+    Some(format!("{}{}", alt_prefix, base))
+}
+
+/// Control character for Ctrl+key in legacy encoding
+fn ctrl_char(c: char) -> Option<char> {
+    match c {
+        'a'..='z' => Some((c as u8 - b'a' + 1) as char),
+        'A'..='Z' => Some((c as u8 - b'A' + 1) as char),
+        ' ' | '@' | '2' => Some('\0'),
+        '[' | '3' => Some('\x1b'),
+        '\\' | '4' => Some('\x1c'),
+        ']' | '5' => Some('\x1d'),
+        '^' | '6' => Some('\x1e'),
+        '_' | '-' | '7' => Some('\x1f'),
+        '?' | '8' => Some('\x7f'),
+        _ => None,
+    }
+}
+
+fn encode_kitty(event: &KeyEvent, flags: u32, application_cursor: bool) -> Option<String> {
+    let report_events = flags & KITTY_REPORT_EVENTS != 0;
+    let report_all = flags & KITTY_REPORT_ALL_KEYS != 0;
+    if event.event_type == KeyEventType::Release && !report_events {
+        return None;
+    }
+    let key = event.key.as_str();
+    let mods = event.modifier_param();
+
+    // Modifier parameter, with the event type appended when reported
+    let event_suffix = match event.event_type {
+        KeyEventType::Press => None,
+        KeyEventType::Repeat => Some(2),
+        KeyEventType::Release => Some(3),
+    }
+    .filter(|_| report_events);
+    let mods_param = match event_suffix {
+        Some(kind) => format!("{}:{}", mods, kind),
+        None if mods > 1 => mods.to_string(),
+        None => String::new(),
+    };
+
+    if let Some(code) = tilde_code(key) {
+        return Some(if mods_param.is_empty() {
+            format!("\x1b[{}~", code)
+        } else {
+            format!("\x1b[{};{}~", code, mods_param)
+        });
+    }
+    if let Some(letter) = letter_code(key) {
+        return if mods_param.is_empty() {
+            // Unmodified arrows and F1-F4 keep their legacy (possibly SS3) form
+            encode_legacy(event, application_cursor)
+        } else {
+            Some(format!("\x1b[1;{}{}", mods_param, letter))
+        };
+    }
+
+    let code = kitty_key_code(key)?;
+    let is_text = key_text(key).is_some();
+    // Without "report all keys", unmodified text (and shifted text) is sent as-is,
+    // and Enter/Tab/Backspace keep their legacy bytes so a crashed program
+    // doesn't leave the shell unusable
+    let legacy_ok = !report_all
+        && event_suffix.is_none()
+        && match key {
+            "Enter" | "Tab" | "Backspace" => !event.has_modifiers(),
+            _ => is_text && !event.ctrl && !event.alt && !event.meta,
+        };
+    if legacy_ok {
+        return encode_legacy(event, application_cursor);
+    }
+
+    Some(if mods_param.is_empty() {
+        format!("\x1b[{}u", code)
+    } else {
+        format!("\x1b[{};{}u", code, mods_param)
+    })
+}
+
+/// User preference for reporting mouse events to programs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseReporting {
+    /// Report mouse events when the program enables tracking
+    #[default]
+    Enabled,
+    /// Never report; the mouse always does native text selection
+    ForceOff,
+}
+
+/// Mouse reporting settings applied when encoding mouse events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MousePolicy {
+    pub reporting: MouseReporting,
+    /// Holding Shift bypasses reporting so text can be selected
+    pub shift_overrides: bool,
+    /// Reports or arrow keys sent per wheel notch
+    pub scroll_multiplier: u8,
+    /// Without mouse tracking, the wheel sends arrow keys on the alternate
+    /// screen (so `less` and `vim` scroll)
+    pub alternate_scroll: bool,
+}
+
+impl Default for MousePolicy {
+    fn default() -> Self {
+        Self {
+            reporting: MouseReporting::Enabled,
+            shift_overrides: true,
+            scroll_multiplier: 1,
+            alternate_scroll: true,
+        }
+    }
+}
+
+/// Mouse button involved in an event
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    WheelUp,
+    WheelDown,
+    WheelLeft,
+    WheelRight,
+    /// Motion without a button held
+    #[default]
+    None,
+}
+
+impl MouseButton {
+    /// xterm button number (before motion and modifier bits)
+    fn code(self) -> u32 {
+        match self {
+            MouseButton::Left => 0,
+            MouseButton::Middle => 1,
+            MouseButton::Right => 2,
+            MouseButton::None => 3,
+            MouseButton::WheelUp => 64,
+            MouseButton::WheelDown => 65,
+            MouseButton::WheelLeft => 66,
+            MouseButton::WheelRight => 67,
+        }
+    }
+
+    fn is_wheel(self) -> bool {
+        self.code() >= 64
+    }
+}
+
+/// Kind of mouse event
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseEventKind {
+    #[default]
+    Press,
+    Release,
+    Move,
+}
+
+/// A mouse event from the frontend, in zero-based cell coordinates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MouseEvent {
+    pub kind: MouseEventKind,
+    #[serde(default)]
+    pub button: MouseButton,
+    pub col: u16,
+    pub row: u16,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub alt: bool,
+    #[serde(default)]
+    pub ctrl: bool,
+}
+
+/// Whether mouse events should go to the program rather than native selection
+pub fn mouse_reporting_active(shift: bool, modes: &InputModes, policy: &MousePolicy) -> bool {
+    modes.mouse_tracking != MouseTracking::Off
+        && policy.reporting == MouseReporting::Enabled
+        && !(shift && policy.shift_overrides)
+}
+
+/// Encode a mouse event for the PTY.
+///
+/// Returns `None` when the event isn't reported (tracking off, disabled by
+/// the user, Shift override, or an event the tracking mode doesn't include);
+/// the frontend then handles it natively. Wheel events are repeated
+/// `scroll_multiplier` times, and become arrow keys on the alternate screen
+/// when they aren't reported.
+pub fn encode_mouse(
+    event: &MouseEvent,
+    modes: &InputModes,
+    policy: &MousePolicy,
+) -> Option<String> {
+    let wheel = event.kind == MouseEventKind::Press && event.button.is_wheel();
+    let encoded = if mouse_reporting_active(event.shift, modes, policy) {
+        encode_mouse_report(event, modes)?
+    } else if wheel {
+        alternate_scroll_keys(event, modes, policy)?
+    } else {
+        return None;
+    };
+    if wheel {
+        Some(encoded.repeat(usize::from(policy.scroll_multiplier.max(1))))
+    } else {
+        Some(encoded)
+    }
+}
+
+/// Arrow key for a wheel event on the alternate screen
+fn alternate_scroll_keys(
+    event: &MouseEvent,
+    modes: &InputModes,
+    policy: &MousePolicy,
+) -> Option<String> {
+    if !policy.alternate_scroll || !modes.alternate_screen || event.shift {
+        return None;
+    }
+    let key = match event.button {
+        MouseButton::WheelUp => "ArrowUp",
+        MouseButton::WheelDown => "ArrowDown",
+        _ => return None,
+    };
+    let key = KeyEvent {
+        key: key.to_string(),
+        ..Default::default()
+    };
+    encode_legacy(&key, modes.application_cursor)
+}
+
+/// Encode a mouse report in the program's tracking mode and format
+fn encode_mouse_report(event: &MouseEvent, modes: &InputModes) -> Option<String> {
+    let tracking = modes.mouse_tracking;
+    let wanted = match event.kind {
+        MouseEventKind::Press => true,
+        MouseEventKind::Release => tracking != MouseTracking::X10 && !event.button.is_wheel(),
+        MouseEventKind::Move => match tracking {
+            MouseTracking::AnyEvent => true,
+            MouseTracking::ButtonEvent => event.button != MouseButton::None,
+            _ => false,
+        },
+    };
+    if !wanted {
+        return None;
+    }
+
+    let mut code = event.button.code();
+    if event.kind == MouseEventKind::Move {
+        code += 32;
+    }
+    // X10 mode doesn't report modifiers
+    if tracking != MouseTracking::X10 {
+        code += u32::from(event.shift) * 4 + u32::from(event.alt) * 8 + u32::from(event.ctrl) * 16;
+    }
+    let col = u32::from(event.col) + 1;
+    let row = u32::from(event.row) + 1;
+
+    match modes.mouse_encoding {
+        MouseEncoding::Sgr => {
+            let final_char = if event.kind == MouseEventKind::Release {
+                'm'
+            } else {
+                'M'
+            };
+            Some(format!("\x1b[<{};{};{}{}", code, col, row, final_char))
+        }
+        MouseEncoding::Default => {
+            // The default format can't say which button was released
+            if event.kind == MouseEventKind::Release {
+                code = (code & !0b11) | 3;
+            }
+            // Values are sent as single bytes offset by 32; keep them ASCII
+            // (programs that need larger coordinates enable SGR)
+            let byte = |value: u32| char::from_u32(value + 32).filter(char::is_ascii);
+            Some(format!("\x1b[M{}{}{}", byte(code)?, byte(col)?, byte(row)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> KeyEvent {
+        KeyEvent {
+            key: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn with_flags(flags: u32) -> InputModes {
+        let mut modes = InputModes::new();
+        modes.handle_kitty_csi('>', &flags.to_string(), true);
+        modes
+    }
+
+    // ============== Kitty negotiation tests ==============
+
+    #[test]
+    fn test_kitty_query_reports_flags() {
+        let mut modes = InputModes::new();
+        assert!(modes.handle_kitty_csi('?', "", true).is_none());
+        modes.set_keys_encoded();
+        modes.reset();
+        assert_eq!(
+            modes.handle_kitty_csi('?', "", true).as_deref(),
+            Some("\x1b[?0u")
+        );
+        modes.handle_kitty_csi('>', "1", true);
+        assert_eq!(
+            modes.handle_kitty_csi('?', "", true).as_deref(),
+            Some("\x1b[?1u")
+        );
+    }
+
+    #[test]
+    fn test_kitty_push_pop() {
+        let mut modes = InputModes::new();
+        modes.handle_kitty_csi('>', "1", true);
+        modes.handle_kitty_csi('>', "3", true);
+        assert_eq!(modes.kitty_flags(), 3);
+        modes.handle_kitty_csi('<', "", true);
+        assert_eq!(modes.kitty_flags(), 1);
+        modes.handle_kitty_csi('<', "5", true);
+        assert_eq!(modes.kitty_flags(), 0);
+    }
+
+    #[test]
+    fn test_kitty_unsupported_flags_masked() {
+        let modes = with_flags(31);
+        assert_eq!(modes.kitty_flags(), KITTY_SUPPORTED_FLAGS);
+    }
+
+    #[test]
+    fn test_kitty_set_modes() {
+        let mut modes = with_flags(1);
+        modes.handle_kitty_csi('=', "2;2", true);
+        assert_eq!(modes.kitty_flags(), 3);
+        modes.handle_kitty_csi('=', "1;3", true);
+        assert_eq!(modes.kitty_flags(), 2);
+        modes.handle_kitty_csi('=', "8", true);
+        assert_eq!(modes.kitty_flags(), 8);
+    }
+
+    #[test]
+    fn test_kitty_disabled_ignores_requests() {
+        let mut modes = InputModes::new();
+        modes.handle_kitty_csi('>', "1", false);
+        assert_eq!(modes.kitty_flags(), 0);
+        assert!(modes.handle_kitty_csi('?', "", false).is_none());
+    }
+
+    #[test]
+    fn test_kitty_stack_depth_bounded() {
+        let mut modes = InputModes::new();
+        for _ in 0..(MAX_KITTY_STACK_DEPTH * 2) {
+            modes.handle_kitty_csi('>', "1", true);
+        }
+        assert_eq!(modes.kitty_stack.len(), MAX_KITTY_STACK_DEPTH);
+    }
+
+    #[test]
+    fn test_kitty_stack_per_screen() {
+        let mut modes = with_flags(KITTY_DISAMBIGUATE);
+        modes.set_private_mode(1049, true);
+        assert_eq!(modes.kitty_flags(), 0);
+        modes.handle_kitty_csi('>', "3", true);
+        assert_eq!(modes.kitty_flags(), 3);
+        modes.handle_kitty_csi('<', "5", true);
+        modes.set_private_mode(1049, false);
+        assert_eq!(modes.kitty_flags(), KITTY_DISAMBIGUATE);
+
+        modes.set_private_mode(47, true);
+        modes.handle_kitty_csi('>', "3", true);
+        modes.set_private_mode(47, false);
+        modes.set_private_mode(1047, true);
+        assert_eq!(modes.kitty_flags(), 0);
+    }
+
+    // ============== Legacy encoding tests ==============
+
+    #[test]
+    fn test_legacy_text_and_control_keys() {
+        let modes = InputModes::new();
+        assert_eq!(encode_key(&key("a"), &modes).as_deref(), Some("a"));
+        assert_eq!(encode_key(&key("Enter"), &modes).as_deref(), Some("\r"));
+        assert_eq!(
+            encode_key(&key("Backspace"), &modes).as_deref(),
+            Some("\x7f")
+        );
+        assert_eq!(encode_key(&key("Escape"), &modes).as_deref(), Some("\x1b"));
+
+        let ctrl_c = KeyEvent {
+            ctrl: true,
+            ..key("c")
+        };
+        assert_eq!(encode_key(&ctrl_c, &modes).as_deref(), Some("\x03"));
+
+        let alt_b = KeyEvent {
+            alt: true,
+            ..key("b")
+        };
+        assert_eq!(encode_key(&alt_b, &modes).as_deref(), Some("\x1bb"));
+
+        let shift_tab = KeyEvent {
+            shift: true,
+            ..key("Tab")
+        };
+        assert_eq!(encode_key(&shift_tab, &modes).as_deref(), Some("\x1b[Z"));
+    }
+
+    #[test]
+    fn test_legacy_special_keys() {
+        let modes = InputModes::new();
+        assert_eq!(
+            encode_key(&key("ArrowUp"), &modes).as_deref(),
+            Some("\x1b[A")
+        );
+        assert_eq!(encode_key(&key("F1"), &modes).as_deref(), Some("\x1bOP"));
+        assert_eq!(encode_key(&key("F5"), &modes).as_deref(), Some("\x1b[15~"));
+        assert_eq!(
+            encode_key(&key("Delete"), &modes).as_deref(),
+            Some("\x1b[3~")
+        );
+
+        let ctrl_right = KeyEvent {
+            ctrl: true,
+            ..key("ArrowRight")
+        };
+        assert_eq!(
+            encode_key(&ctrl_right, &modes).as_deref(),
+            Some("\x1b[1;5C")
+        );
+    }
+
+    #[test]
+    fn test_application_cursor_mode() {
+        let mut modes = InputModes::new();
+        modes.set_private_mode(1, true);
+        assert_eq!(
+            encode_key(&key("ArrowUp"), &modes).as_deref(),
+            Some("\x1bOA")
+        );
+        assert_eq!(encode_key(&key("Home"), &modes).as_deref(), Some("\x1bOH"));
+
+        modes.set_private_mode(1, false);
+        assert_eq!(
+            encode_key(&key("ArrowUp"), &modes).as_deref(),
+            Some("\x1b[A")
+        );
+    }
+
+    #[test]
+    fn test_legacy_ignores_cmd_and_release() {
+        let modes = InputModes::new();
+        let cmd_c = KeyEvent {
+            meta: true,
+            ..key("c")
+        };
+        assert!(encode_key(&cmd_c, &modes).is_none());
+
+        let release = KeyEvent {
+            event_type: KeyEventType::Release,
+            ..key("a")
+        };
+        assert!(encode_key(&release, &modes).is_none());
+        assert!(encode_key(&key("Unidentified"), &modes).is_none());
+    }
+
+    #[test]
+    fn test_shortcut_passthrough_encoding() {
+        let cmd_k = KeyEvent {
+            meta: true,
+            ..key("k")
+        };
+        // Legacy: like Alt
+        assert_eq!(
+            encode_shortcut(&cmd_k, &InputModes::new()).as_deref(),
+            Some("\x1bk")
+        );
+        // Kitty: Super
+        let modes = with_flags(KITTY_DISAMBIGUATE);
+        assert_eq!(
+            encode_shortcut(&cmd_k, &modes).as_deref(),
+            Some("\x1b[107;9u")
+        );
+        // Still never sent as a normal key
+        assert!(encode_key(&cmd_k, &modes).is_none());
+    }
+
+    // ============== Kitty encoding tests ==============
+
+    #[test]
+    fn test_kitty_disambiguate() {
+        let modes = with_flags(KITTY_DISAMBIGUATE);
+        assert_eq!(
+            encode_key(&key("Escape"), &modes).as_deref(),
+            Some("\x1b[27u")
+        );
+        assert_eq!(encode_key(&key("a"), &modes).as_deref(), Some("a"));
+        // Enter stays legacy unless modified
+        assert_eq!(encode_key(&key("Enter"), &modes).as_deref(), Some("\r"));
+
+        let shift_enter = KeyEvent {
+            shift: true,
+            ..key("Enter")
+        };
+        assert_eq!(
+            encode_key(&shift_enter, &modes).as_deref(),
+            Some("\x1b[13;2u")
+        );
+
+        let ctrl_i = KeyEvent {
+            ctrl: true,
+            ..key("i")
+        };
+        assert_eq!(encode_key(&ctrl_i, &modes).as_deref(), Some("\x1b[105;5u"));
+
+        let ctrl_shift_a = KeyEvent {
+            ctrl: true,
+            shift: true,
+            ..key("A")
+        };
+        assert_eq!(
+            encode_key(&ctrl_shift_a, &modes).as_deref(),
+            Some("\x1b[97;6u")
+        );
+    }
+
+    #[test]
+    fn test_kitty_report_all_keys() {
+        let modes = with_flags(KITTY_DISAMBIGUATE | KITTY_REPORT_ALL_KEYS);
+        assert_eq!(encode_key(&key("a"), &modes).as_deref(), Some("\x1b[97u"));
+        assert_eq!(
+            encode_key(&key("Enter"), &modes).as_deref(),
+            Some("\x1b[13u")
+        );
+    }
+
+    #[test]
+    fn test_kitty_event_types() {
+        let modes = with_flags(KITTY_DISAMBIGUATE | KITTY_REPORT_EVENTS);
+        let release = KeyEvent {
+            event_type: KeyEventType::Release,
+            ..key("a")
+        };
+        assert_eq!(
+            encode_key(&release, &modes).as_deref(),
+            Some("\x1b[97;1:3u")
+        );
+
+        let repeat = KeyEvent {
+            event_type: KeyEventType::Repeat,
+            ..key("ArrowDown")
+        };
+        assert_eq!(encode_key(&repeat, &modes).as_deref(), Some("\x1b[1;1:2B"));
+
+        // Releases are dropped unless event types are reported
+        let modes = with_flags(KITTY_DISAMBIGUATE);
+        assert!(encode_key(&release, &modes).is_none());
+    }
+
+    #[test]
+    fn test_kitty_functional_keys() {
+        let modes = with_flags(KITTY_DISAMBIGUATE);
+        let shift_f5 = KeyEvent {
+            shift: true,
+            ..key("F5")
+        };
+        assert_eq!(encode_key(&shift_f5, &modes).as_deref(), Some("\x1b[15;2~"));
+        assert_eq!(
+            encode_key(&key("ArrowLeft"), &modes).as_deref(),
+            Some("\x1b[D")
+        );
+        assert_eq!(encode_key(&key("F1"), &modes).as_deref(), Some("\x1bOP"));
+    }
+
+    #[test]
+    fn test_focus_reporting() {
+        let mut modes = InputModes::new();
+        assert_eq!(modes.focus_report(true), None);
+        modes.set_private_mode(1004, true);
+        assert_eq!(modes.focus_report(true), Some("\x1b[I"));
+        assert_eq!(modes.focus_report(false), Some("\x1b[O"));
+        modes.set_private_mode(1004, false);
+        assert_eq!(modes.focus_report(false), None);
+    }
+
+    #[test]
+    fn test_bracketed_paste_mode() {
+        let mut modes = InputModes::new();
+        assert!(!modes.bracketed_paste());
+        modes.set_private_mode(2004, true);
+        assert!(modes.bracketed_paste());
+        modes.reset();
+        assert!(!modes.bracketed_paste());
+    }
+
+    // ============== Mouse tests ==============
+
+    fn mouse(kind: MouseEventKind, button: MouseButton, col: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            button,
+            col,
+            row,
+            ..Default::default()
+        }
+    }
+
+    fn with_mouse_modes(modes: &[u32]) -> InputModes {
+        let mut input_modes = InputModes::new();
+        for mode in modes {
+            input_modes.set_private_mode(*mode, true);
+        }
+        input_modes
+    }
+
+    #[test]
+    fn test_mouse_tracking_modes() {
+        let mut modes = with_mouse_modes(&[1002]);
+        assert_eq!(modes.mouse_tracking(), MouseTracking::ButtonEvent);
+        modes.set_private_mode(1003, true);
+        assert_eq!(modes.mouse_tracking(), MouseTracking::AnyEvent);
+        modes.set_private_mode(1000, false);
+        assert_eq!(modes.mouse_tracking(), MouseTracking::Off);
+    }
+
+    #[test]
+    fn test_mouse_not_reported_without_tracking() {
+        let press = mouse(MouseEventKind::Press, MouseButton::Left, 0, 0);
+        assert_eq!(
+            encode_mouse(&press, &InputModes::new(), &MousePolicy::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mouse_default_encoding() {
+        let modes = with_mouse_modes(&[1000]);
+        let policy = MousePolicy::default();
+        let press = mouse(MouseEventKind::Press, MouseButton::Left, 0, 0);
+        assert_eq!(
+            encode_mouse(&press, &modes, &policy).as_deref(),
+            Some("\x1b[M !!")
+        );
+        let release = mouse(MouseEventKind::Release, MouseButton::Right, 9, 4);
+        assert_eq!(
+            encode_mouse(&release, &modes, &policy).as_deref(),
+            Some("\x1b[M#*%")
+        );
+        // Coordinates that don't fit in one ASCII byte are dropped
+        let far = mouse(MouseEventKind::Press, MouseButton::Left, 200, 0);
+        assert_eq!(encode_mouse(&far, &modes, &policy), None);
+    }
+
+    #[test]
+    fn test_mouse_sgr_encoding() {
+        let modes = with_mouse_modes(&[1000, 1006]);
+        let policy = MousePolicy::default();
+        let mut press = mouse(MouseEventKind::Press, MouseButton::Left, 299, 49);
+        press.ctrl = true;
+        assert_eq!(
+            encode_mouse(&press, &modes, &policy).as_deref(),
+            Some("\x1b[<16;300;50M")
+        );
+        let release = mouse(MouseEventKind::Release, MouseButton::Middle, 0, 0);
+        assert_eq!(
+            encode_mouse(&release, &modes, &policy).as_deref(),
+            Some("\x1b[<1;1;1m")
+        );
+        let wheel = mouse(MouseEventKind::Press, MouseButton::WheelDown, 4, 2);
+        assert_eq!(
+            encode_mouse(&wheel, &modes, &policy).as_deref(),
+            Some("\x1b[<65;5;3M")
+        );
+    }
+
+    #[test]
+    fn test_mouse_motion_depends_on_tracking_mode() {
+        let policy = MousePolicy::default();
+        let drag = mouse(MouseEventKind::Move, MouseButton::Left, 1, 1);
+        let hover = mouse(MouseEventKind::Move, MouseButton::None, 1, 1);
+
+        let normal = with_mouse_modes(&[1000, 1006]);
+        assert_eq!(encode_mouse(&drag, &normal, &policy), None);
+
+        let button_event = with_mouse_modes(&[1002, 1006]);
+        assert_eq!(
+            encode_mouse(&drag, &button_event, &policy).as_deref(),
+            Some("\x1b[<32;2;2M")
+        );
+        assert_eq!(encode_mouse(&hover, &button_event, &policy), None);
+
+        let any_event = with_mouse_modes(&[1003, 1006]);
+        assert_eq!(
+            encode_mouse(&hover, &any_event, &policy).as_deref(),
+            Some("\x1b[<35;2;2M")
+        );
+    }
+
+    #[test]
+    fn test_mouse_x10_reports_presses_only() {
+        let modes = with_mouse_modes(&[9, 1006]);
+        let policy = MousePolicy::default();
+        let mut press = mouse(MouseEventKind::Press, MouseButton::Left, 0, 0);
+        press.alt = true;
+        assert_eq!(
+            encode_mouse(&press, &modes, &policy).as_deref(),
+            Some("\x1b[<0;1;1M")
+        );
+        let release = mouse(MouseEventKind::Release, MouseButton::Left, 0, 0);
+        assert_eq!(encode_mouse(&release, &modes, &policy), None);
+    }
+
+    #[test]
+    fn test_mouse_policy() {
+        let modes = with_mouse_modes(&[1000, 1006]);
+        let mut press = mouse(MouseEventKind::Press, MouseButton::Left, 0, 0);
+
+        let force_off = MousePolicy {
+            reporting: MouseReporting::ForceOff,
+            ..Default::default()
+        };
+        assert_eq!(encode_mouse(&press, &modes, &force_off), None);
+
+        press.shift = true;
+        assert_eq!(encode_mouse(&press, &modes, &MousePolicy::default()), None);
+
+        let no_override = MousePolicy {
+            reporting: MouseReporting::Enabled,
+            shift_overrides: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            encode_mouse(&press, &modes, &no_override).as_deref(),
+            Some("\x1b[<4;1;1M")
+        );
+    }
+
+    #[test]
+    fn test_wheel_scroll_multiplier() {
+        let modes = with_mouse_modes(&[1000, 1006]);
+        let policy = MousePolicy {
+            scroll_multiplier: 3,
+            ..Default::default()
+        };
+        let wheel = mouse(MouseEventKind::Press, MouseButton::WheelUp, 0, 0);
+        assert_eq!(
+            encode_mouse(&wheel, &modes, &policy).as_deref(),
+            Some("\x1b[<64;1;1M".repeat(3).as_str())
+        );
+        // Only wheel events are repeated
+        let press = mouse(MouseEventKind::Press, MouseButton::Left, 0, 0);
+        assert_eq!(
+            encode_mouse(&press, &modes, &policy).as_deref(),
+            Some("\x1b[<0;1;1M")
+        );
+    }
+
+    #[test]
+    fn test_alternate_scroll() {
+        let wheel_up = mouse(MouseEventKind::Press, MouseButton::WheelUp, 0, 0);
+        let wheel_down = mouse(MouseEventKind::Press, MouseButton::WheelDown, 0, 0);
+        let policy = MousePolicy {
+            scroll_multiplier: 2,
+            ..Default::default()
+        };
+
+        // Normal screen: the frontend scrolls its scrollback
+        let mut modes = InputModes::new();
+        assert_eq!(encode_mouse(&wheel_up, &modes, &policy), None);
+
+        modes.set_private_mode(1049, true);
+        assert_eq!(
+            encode_mouse(&wheel_up, &modes, &policy).as_deref(),
+            Some("\x1b[A\x1b[A")
+        );
+        modes.set_private_mode(1, true);
+        assert_eq!(
+            encode_mouse(&wheel_down, &modes, &policy).as_deref(),
+            Some("\x1bOB\x1bOB")
+        );
+
+        let disabled = MousePolicy {
+            alternate_scroll: false,
+            ..Default::default()
+        };
+        assert_eq!(encode_mouse(&wheel_up, &modes, &disabled), None);
+
+        // Mouse tracking takes precedence
+        modes.set_private_mode(1000, true);
+        assert_eq!(
+            encode_mouse(&wheel_up, &modes, &policy).as_deref(),
+            Some("\x1b[M`!!\x1b[M`!!")
+        );
+
+        modes.set_private_mode(1049, false);
+        assert!(!modes.alternate_screen());
+    }
+}
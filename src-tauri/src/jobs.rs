@@ -0,0 +1,405 @@
+//! Project task jobs
+//!
+//! `run_project_task` runs a task found by `project::detect` (npm script,
+//! Makefile target or justfile recipe) in its own PTY session, started in the
+//! project root. The session ID doubles as the job ID, so the frontend can
+//! attach a terminal to show the output; status changes are emitted as
+//! `job-status`.
+//!
+//! The `on_show_command` and `on_hide_command` settings are run the same way
+//! whenever the window is shown or hidden, from the home directory with the
+//! settings' and home profile's environment plus `MICROTERM_WINDOW_EVENT`
+//! (`shown` or `hidden`). A hook isn't started again while its previous run
+//! is still going.
+
+use crate::events;
+use crate::project::{self, ProjectTask, TaskRunner};
+use crate::pty::{PtyManager, SessionOptions};
+use crate::settings::SettingsManager;
+use crate::status_strip;
+use crate::unix_time::now_secs;
+use crate::usage_stats::UsageStatsManager;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use tauri::{command, AppHandle, Manager, State};
+use tracing::{info, warn};
+
+/// Default PTY size for jobs started without one
+const DEFAULT_JOB_COLS: u16 = 80;
+const DEFAULT_JOB_ROWS: u16 = 24;
+
+/// Maximum number of finished jobs kept for `list_jobs`
+const MAX_FINISHED_JOBS: usize = 50;
+
+/// Environment variable telling window hooks whether the window was shown or
+/// hidden
+pub const WINDOW_EVENT_ENV_VAR: &str = "MICROTERM_WINDOW_EVENT";
+
+/// Task names of the window hook jobs
+const SHOW_HOOK_TASK: &str = "on_show_command";
+const HIDE_HOOK_TASK: &str = "on_hide_command";
+
+/// State of a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "state")]
+pub enum JobStatus {
+    Running,
+    Succeeded,
+    Failed {
+        exit_code: Option<i32>,
+    },
+    /// The job's session was closed before the task finished
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Status for a session exit code (`None` when the session was closed)
+    fn from_exit_code(exit_code: Option<i32>) -> Self {
+        match exit_code {
+            Some(0) => JobStatus::Succeeded,
+            Some(code) => JobStatus::Failed {
+                exit_code: Some(code),
+            },
+            None => JobStatus::Cancelled,
+        }
+    }
+}
+
+/// A task running (or run) in a PTY session; payload of `job-status`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Job {
+    /// Also the ID of the job's PTY session
+    pub id: String,
+    pub task: String,
+    /// `None` for window hooks, which run a shell command
+    pub runner: Option<TaskRunner>,
+    pub command: String,
+    pub cwd: String,
+    pub status: JobStatus,
+    /// Seconds since the Unix epoch
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+}
+
+/// Running and recently finished jobs
+pub struct JobManager {
+    jobs: Mutex<HashMap<String, Job>>,
+}
+
+impl Default for JobManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn insert(&self, job: Job) {
+        self.jobs.lock().insert(job.id.clone(), job);
+    }
+
+    /// Mark a job finished, returning it if the session was a running job
+    pub fn finish(&self, id: &str, exit_code: Option<i32>) -> Option<Job> {
+        let mut jobs = self.jobs.lock();
+        let job = jobs.get_mut(id)?;
+        if job.status != JobStatus::Running {
+            return None;
+        }
+        job.status = JobStatus::from_exit_code(exit_code);
+        job.finished_at = Some(now_secs());
+        let finished = job.clone();
+
+        // Drop the oldest finished jobs beyond the limit
+        let mut done: Vec<(u64, String)> = jobs
+            .values()
+            .filter_map(|job| job.finished_at.map(|at| (at, job.id.clone())))
+            .collect();
+        if done.len() > MAX_FINISHED_JOBS {
+            done.sort();
+            for (_, id) in &done[..done.len() - MAX_FINISHED_JOBS] {
+                jobs.remove(id);
+            }
+        }
+        Some(finished)
+    }
+
+    /// Whether a job for the task is still running
+    pub fn is_running(&self, task: &str) -> bool {
+        self.jobs
+            .lock()
+            .values()
+            .any(|job| job.task == task && job.status == JobStatus::Running)
+    }
+
+    /// All known jobs, newest first
+    pub fn list(&self) -> Vec<Job> {
+        let mut jobs: Vec<Job> = self.jobs.lock().values().cloned().collect();
+        jobs.sort_by(|a, b| b.started_at.cmp(&a.started_at).then(a.id.cmp(&b.id)));
+        jobs
+    }
+}
+
+/// Record a session's exit if it was running a job (called by the reader thread)
+pub fn session_exited(app: &AppHandle, session_id: &str, exit_code: Option<i32>) {
+    let Some(manager) = app.try_state::<Arc<JobManager>>() else {
+        return;
+    };
+    if let Some(job) = manager.finish(session_id, exit_code) {
+        info!(job_id = %job.id, task = %job.task, status = ?job.status, "Job finished");
+        let _ = events::emit(app, job);
+        status_strip::refresh(app);
+    }
+}
+
+/// Find a task by name (and runner, when several sources define it)
+fn find_task(
+    tasks: Vec<ProjectTask>,
+    task: &str,
+    runner: Option<TaskRunner>,
+) -> Result<ProjectTask, String> {
+    tasks
+        .into_iter()
+        .find(|t| t.name == task && runner.is_none_or(|runner| t.runner == runner))
+        .ok_or_else(|| format!("Unknown task: {}", task))
+}
+
+/// Run a project task in a new PTY session
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn run_project_task(
+    app: AppHandle,
+    pty_manager: State<'_, Arc<PtyManager>>,
+    job_manager: State<'_, Arc<JobManager>>,
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
+    cwd: String,
+    task: String,
+    runner: Option<TaskRunner>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<Job, String> {
+    let info = project::detect_project(cwd)?.ok_or("Not in a project")?;
+    let task = find_task(info.tasks, &task, runner)?;
+
+    let job = new_job(task.name, Some(task.runner), task.command, info.root);
+    start(
+        &app,
+        &pty_manager,
+        &job_manager,
+        job.clone(),
+        cols.unwrap_or(DEFAULT_JOB_COLS),
+        rows.unwrap_or(DEFAULT_JOB_ROWS),
+        Vec::new(),
+    )?;
+    usage_stats.record_feature("project_tasks")?;
+    Ok(job)
+}
+
+fn new_job(task: String, runner: Option<TaskRunner>, command: String, cwd: String) -> Job {
+    Job {
+        id: uuid::Uuid::new_v4().to_string(),
+        task,
+        runner,
+        command,
+        cwd,
+        status: JobStatus::Running,
+        started_at: now_secs(),
+        finished_at: None,
+    }
+}
+
+/// Register a job and start its session
+fn start(
+    app: &AppHandle,
+    pty_manager: &PtyManager,
+    job_manager: &JobManager,
+    job: Job,
+    cols: u16,
+    rows: u16,
+    env: Vec<(String, String)>,
+) -> Result<(), String> {
+    // Register and announce the job first, so a task that exits immediately
+    // is still reported in order
+    job_manager.insert(job.clone());
+    let _ = events::emit(app, job.clone());
+    status_strip::refresh(app);
+
+    let options = SessionOptions {
+        cwd: Some(PathBuf::from(&job.cwd)),
+        command: Some(job.command.clone()),
+        session_id: Some(job.id.clone()),
+        env,
+        ..Default::default()
+    };
+    if let Err(e) = pty_manager.create_session(app.clone(), cols, rows, options) {
+        session_exited(app, &job.id, Some(1));
+        return Err(e);
+    }
+
+    info!(job_id = %job.id, command = %job.command, "Job started");
+    Ok(())
+}
+
+/// Run the window hook for a show or hide, if one is set and not already
+/// running
+pub fn window_visibility_changed(app: &AppHandle, visible: bool) {
+    let Some(settings) = app.try_state::<Arc<SettingsManager>>() else {
+        return;
+    };
+    let settings = settings.get();
+    let (task, command, event) = if visible {
+        (SHOW_HOOK_TASK, settings.on_show_command, "shown")
+    } else {
+        (HIDE_HOOK_TASK, settings.on_hide_command, "hidden")
+    };
+    let Some(command) = command else {
+        return;
+    };
+    let (Some(pty_manager), Some(job_manager)) = (
+        app.try_state::<Arc<PtyManager>>(),
+        app.try_state::<Arc<JobManager>>(),
+    ) else {
+        return;
+    };
+    if job_manager.is_running(task) {
+        info!(task, "Window hook still running, skipping");
+        return;
+    }
+
+    let pty_manager = pty_manager.inner().clone();
+    let job_manager = job_manager.inner().clone();
+    let app = app.clone();
+    // Spawning a login shell can take a moment; don't hold up the window
+    thread::spawn(move || {
+        let home = std::env::var("HOME").unwrap_or_else(|_| "/".to_string());
+        let job = new_job(task.to_string(), None, command, home);
+        let env = vec![(WINDOW_EVENT_ENV_VAR.to_string(), event.to_string())];
+        if let Err(e) = start(
+            &app,
+            &pty_manager,
+            &job_manager,
+            job,
+            DEFAULT_JOB_COLS,
+            DEFAULT_JOB_ROWS,
+            env,
+        ) {
+            warn!(task, "Failed to run window hook: {}", e);
+        }
+    });
+}
+
+/// Running and recently finished jobs, newest first
+#[command]
+pub fn list_jobs(job_manager: State<'_, Arc<JobManager>>) -> Result<Vec<Job>, String> {
+    Ok(job_manager.list())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str, started_at: u64) -> Job {
+        Job {
+            id: id.to_string(),
+            task: "build".to_string(),
+            runner: Some(TaskRunner::Npm),
+            command: "npm run build".to_string(),
+            cwd: "/tmp/app".to_string(),
+            status: JobStatus::Running,
+            started_at,
+            finished_at: None,
+        }
+    }
+
+    #[test]
+    fn test_finish_sets_status() {
+        let manager = JobManager::new();
+        manager.insert(job("a", 1));
+        manager.insert(job("b", 2));
+        manager.insert(job("c", 3));
+
+        assert_eq!(
+            manager.finish("a", Some(0)).unwrap().status,
+            JobStatus::Succeeded
+        );
+        assert_eq!(
+            manager.finish("b", Some(1)).unwrap().status,
+            JobStatus::Failed { exit_code: Some(1) }
+        );
+        assert_eq!(
+            manager.finish("c", None).unwrap().status,
+            JobStatus::Cancelled
+        );
+        // Already finished, or not a job
+        assert!(manager.finish("a", Some(0)).is_none());
+        assert!(manager.finish("shell", Some(0)).is_none());
+
+        let ids: Vec<String> = manager.list().into_iter().map(|job| job.id).collect();
+        assert_eq!(ids, ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_is_running() {
+        let manager = JobManager::new();
+        manager.insert(job("a", 1));
+        assert!(manager.is_running("build"));
+        assert!(!manager.is_running(SHOW_HOOK_TASK));
+        manager.finish("a", Some(0));
+        assert!(!manager.is_running("build"));
+    }
+
+    #[test]
+    fn test_finished_jobs_are_capped() {
+        let manager = JobManager::new();
+        for i in 0..MAX_FINISHED_JOBS + 5 {
+            let id = format!("job-{}", i);
+            manager.insert(job(&id, i as u64));
+            manager.finish(&id, Some(0));
+        }
+        manager.insert(job("running", 1000));
+        let jobs = manager.list();
+        assert_eq!(jobs.len(), MAX_FINISHED_JOBS + 1);
+        assert_eq!(jobs[0].status, JobStatus::Running);
+    }
+
+    #[test]
+    fn test_find_task() {
+        let tasks = vec![
+            ProjectTask {
+                name: "build".to_string(),
+                runner: TaskRunner::Npm,
+                command: "npm run build".to_string(),
+            },
+            ProjectTask {
+                name: "build".to_string(),
+                runner: TaskRunner::Make,
+                command: "make build".to_string(),
+            },
+        ];
+        assert_eq!(
+            find_task(tasks.clone(), "build", None).unwrap().runner,
+            TaskRunner::Npm
+        );
+        assert_eq!(
+            find_task(tasks.clone(), "build", Some(TaskRunner::Make))
+                .unwrap()
+                .command,
+            "make build"
+        );
+        assert!(find_task(tasks, "deploy", None).is_err());
+    }
+
+    #[test]
+    fn test_job_status_serialization() {
+        let json = serde_json::to_string(&JobStatus::Failed { exit_code: Some(2) }).unwrap();
+        assert_eq!(json, r#"{"state":"failed","exit_code":2}"#);
+    }
+}
@@ -0,0 +1,106 @@
+//! Keychain secrets as session environment variables
+//!
+//! Tokens like `GITHUB_TOKEN` shouldn't live in the settings file or in shell
+//! rc files. A `KeychainSecret` names a generic password item in the login
+//! Keychain and the environment variable it is exported as; the value is read
+//! with `security find-generic-password` when a session is created and only
+//! ends up in that session's environment.
+//!
+//! Secrets come from the `keychain_env` of the session's directory profile,
+//! or are passed to `create_pty_session` directly.
+
+use crate::profiles;
+use serde::{Deserialize, Serialize};
+
+/// A Keychain item exported into a session's environment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeychainSecret {
+    /// Environment variable name, e.g. `GITHUB_TOKEN`
+    pub env: String,
+    /// Service (the item's "Where" field in Keychain Access)
+    pub service: String,
+    /// Account name; the first item for the service when not set
+    #[serde(default)]
+    pub account: Option<String>,
+}
+
+impl KeychainSecret {
+    /// Arguments for `security` that print the item's password
+    fn security_args(&self) -> Vec<&str> {
+        let mut args = vec!["find-generic-password", "-s", &self.service];
+        if let Some(account) = &self.account {
+            args.extend(["-a", account]);
+        }
+        args.push("-w");
+        args
+    }
+}
+
+/// Check that a secret can be exported before asking the Keychain for it
+pub fn validate(secret: &KeychainSecret) -> Result<(), String> {
+    if !profiles::is_env_name(&secret.env) {
+        return Err(format!("Invalid environment variable name: {}", secret.env));
+    }
+    if secret.service.trim().is_empty() {
+        return Err(format!("No Keychain service for {}", secret.env));
+    }
+    Ok(())
+}
+
+/// Read a secret's value from the login Keychain
+#[cfg(target_os = "macos")]
+pub fn fetch(secret: &KeychainSecret) -> Result<String, String> {
+    validate(secret)?;
+    let output = std::process::Command::new("/usr/bin/security")
+        .args(secret.security_args())
+        .output()
+        .map_err(|e| format!("Failed to run security: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Keychain item not found for {} (service {:?})",
+            secret.env, secret.service
+        ));
+    }
+    let value = String::from_utf8(output.stdout)
+        .map_err(|_| format!("Keychain item for {} is not valid UTF-8", secret.env))?;
+    Ok(value.trim_end_matches('\n').to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn fetch(secret: &KeychainSecret) -> Result<String, String> {
+    validate(secret)?;
+    Err("Keychain secrets are only available on macOS".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret(env: &str, service: &str, account: Option<&str>) -> KeychainSecret {
+        KeychainSecret {
+            env: env.to_string(),
+            service: service.to_string(),
+            account: account.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_security_args() {
+        assert_eq!(
+            secret("GITHUB_TOKEN", "gh", None).security_args(),
+            ["find-generic-password", "-s", "gh", "-w"]
+        );
+        assert_eq!(
+            secret("GITHUB_TOKEN", "gh", Some("me")).security_args(),
+            ["find-generic-password", "-s", "gh", "-a", "me", "-w"]
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&secret("GITHUB_TOKEN", "gh", None)).is_ok());
+        assert!(validate(&secret("1TOKEN", "gh", None)).is_err());
+        assert!(validate(&secret("MY-TOKEN", "gh", None)).is_err());
+        assert!(validate(&secret("TOKEN", " ", None)).is_err());
+    }
+}
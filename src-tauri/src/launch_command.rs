@@ -0,0 +1,151 @@
+//! Commands run from the launch arguments
+//!
+//! `open -a µTerm --args run "make test"` (or the binary with `run <command>`)
+//! starts a session running the command and shows the window, so scripts and
+//! launchers can use the app to display ad-hoc commands. A launch while an
+//! instance is running forwards the command over the control socket.
+//!
+//! Sessions are queued until the frontend takes them with
+//! `take_opened_sessions`: on the first launch the command starts before the
+//! frontend is listening, so `session-opened` only tells it to look. Bookmarks
+//! opened from the tray menu or a URL go through the same queue.
+
+use crate::events;
+use crate::pty::{PtyManager, SessionOptions};
+use crate::usage_stats::UsageStatsManager;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::{command, AppHandle, Manager, State};
+use tracing::info;
+
+/// Launch argument followed by the command to run
+pub const RUN_ARG: &str = "run";
+
+/// PTY size until the frontend attaches a terminal and resizes it
+const DEFAULT_COLS: u16 = 80;
+const DEFAULT_ROWS: u16 = 24;
+
+/// Payload of the `session-opened` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionOpened {
+    pub session_id: String,
+    /// Tab title: the command, or the bookmark's name
+    pub title: String,
+    /// Command the session runs; `None` for shells
+    pub command: Option<String>,
+}
+
+/// Command given after `run` in the launch arguments (without the program
+/// name); the remaining arguments are joined with spaces
+pub fn command_from_args(args: &[String]) -> Option<String> {
+    let start = args.iter().position(|arg| arg == RUN_ARG)? + 1;
+    let command = args[start..].join(" ");
+    let command = command.trim();
+    (!command.is_empty()).then(|| command.to_string())
+}
+
+/// Sessions started from launch commands that the frontend hasn't shown yet
+#[derive(Default)]
+pub struct OpenedSessions {
+    pending: Mutex<Vec<SessionOpened>>,
+}
+
+impl OpenedSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&self, session: SessionOpened) {
+        self.pending.lock().push(session);
+    }
+
+    fn take(&self) -> Vec<SessionOpened> {
+        std::mem::take(&mut *self.pending.lock())
+    }
+}
+
+/// Start a session running `command` and tell the frontend about it
+pub fn open(app: &AppHandle, command: String) -> Result<String, String> {
+    let options = SessionOptions {
+        command: Some(command.clone()),
+        ..Default::default()
+    };
+    let session_id = start(app, options, command)?;
+    info!(session_id = %session_id, "Started launch command");
+    Ok(session_id)
+}
+
+/// Start a session and queue it for the frontend to show with `title`
+pub fn start(app: &AppHandle, options: SessionOptions, title: String) -> Result<String, String> {
+    let pty_manager = app
+        .try_state::<Arc<PtyManager>>()
+        .ok_or("PTY manager not available")?;
+    let opened = app
+        .try_state::<Arc<OpenedSessions>>()
+        .ok_or("Opened sessions not available")?;
+
+    let command = options.command.clone();
+    let session_id =
+        pty_manager.create_session(app.clone(), DEFAULT_COLS, DEFAULT_ROWS, options)?;
+    if let Some(usage_stats) = app.try_state::<Arc<UsageStatsManager>>() {
+        usage_stats.record_session_created();
+    }
+
+    let session = SessionOpened {
+        session_id: session_id.clone(),
+        title,
+        command,
+    };
+    opened.push(session.clone());
+    let _ = events::emit(app, session);
+    Ok(session_id)
+}
+
+/// Take the sessions started from launch commands, oldest first
+#[command]
+pub fn take_opened_sessions(
+    opened: State<'_, Arc<OpenedSessions>>,
+) -> Result<Vec<SessionOpened>, String> {
+    Ok(opened.take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_command_from_args() {
+        assert_eq!(command_from_args(&args(&[])), None);
+        assert_eq!(
+            command_from_args(&args(&["run", "make test"])),
+            Some("make test".to_string())
+        );
+        assert_eq!(
+            command_from_args(&args(&["--config-dir", "/tmp/demo", "run", "ls", "-la"])),
+            Some("ls -la".to_string())
+        );
+        assert_eq!(command_from_args(&args(&["run"])), None);
+        assert_eq!(command_from_args(&args(&["run", "  "])), None);
+        assert_eq!(command_from_args(&args(&["--toggle"])), None);
+    }
+
+    #[test]
+    fn test_opened_sessions_are_taken_once() {
+        let opened = OpenedSessions::new();
+        for id in ["a", "b"] {
+            opened.push(SessionOpened {
+                session_id: id.to_string(),
+                title: "ls".to_string(),
+                command: Some("ls".to_string()),
+            });
+        }
+        let ids: Vec<String> = opened.take().into_iter().map(|s| s.session_id).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert!(opened.take().is_empty());
+    }
+}
@@ -6,19 +6,88 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+pub mod accessibility;
+pub mod activity;
+pub mod answerback;
+pub mod app_nap;
+pub mod askpass;
+pub mod bookmarks;
+pub mod caffeinate;
+pub mod cell_width;
+pub mod clipboard;
 pub mod commands;
+pub mod config_dir;
+pub mod config_file;
+pub mod control_socket;
+pub mod doctor;
+pub mod events;
+pub mod file_drop;
+pub mod i18n;
+pub mod idle;
+pub mod images;
+pub mod input;
+pub mod jobs;
+pub mod keychain;
+pub mod launch_command;
+pub mod links;
+pub mod lock;
+pub mod macros;
+pub mod metrics;
+pub mod notifications;
+pub mod orphans;
+pub mod output_batch;
+pub mod passthrough;
+pub mod paste;
+pub mod permissions;
+pub mod power;
+pub mod presentation;
+pub mod process_tree;
+pub mod profiles;
+pub mod progress;
+pub mod project;
 pub mod pty;
+pub mod pty_backend;
 pub mod pty_commands;
+#[cfg(any(test, feature = "testing"))]
+pub mod pty_mock;
+pub mod quit_guard;
+pub mod recent_dirs;
+pub mod redact;
+pub mod release_notes;
+pub mod replay;
+pub mod sandbox;
 pub mod screen_config;
+pub mod scripting;
+pub mod scrollback;
+pub mod session_labels;
+pub mod session_name;
+pub mod session_store;
 pub mod settings;
 pub mod settings_commands;
+pub mod shell_integration;
+pub mod ssh;
+pub mod status_server;
+pub mod status_strip;
+pub mod tab_title;
+pub mod transcript;
+pub mod trash;
+pub mod tray_recent;
+pub mod unix_time;
+pub mod usage_stats;
+pub mod utf8;
+pub mod vt_parser;
+pub mod widgets;
 pub mod window_commands;
+pub mod window_mode;
+pub mod window_presets;
+pub mod workspace;
 
 use std::sync::Arc;
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::TrayIconEvent,
-    Emitter, Listener, Manager, WebviewWindow,
+    webview::PageLoadEvent,
+    AppHandle, Listener, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder,
 };
 
 #[cfg(not(target_os = "macos"))]
@@ -29,13 +98,19 @@ use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 #[cfg(target_os = "macos")]
 pub mod macos {
     use block2::RcBlock;
+    use objc2::msg_send;
     use objc2::rc::Retained;
-    use objc2::runtime::AnyObject;
+    use objc2::runtime::{AnyClass, AnyObject, Bool};
+    use objc2::runtime::{NSObjectProtocol, ProtocolObject};
     use objc2_app_kit::{
-        NSApplication, NSEvent, NSEventMask, NSWindow, NSWindowCollectionBehavior,
+        NSApplication, NSColor, NSEvent, NSEventMask, NSPasteboard, NSPasteboardTypeString,
+        NSWindow, NSWindowCollectionBehavior, NSWorkspace,
     };
-    use objc2_foundation::{MainThreadMarker, NSPoint};
-    use parking_lot::RwLock;
+    use objc2_foundation::{
+        MainThreadMarker, NSActivityOptions, NSArray, NSDictionary, NSLocale, NSNumber, NSPoint,
+        NSProcessInfo, NSString,
+    };
+    use parking_lot::{Mutex, RwLock};
     use std::ptr::NonNull;
     use std::sync::atomic::{AtomicBool, Ordering};
 
@@ -86,6 +161,324 @@ pub mod macos {
         WINDOW_STATE.write().pinned = pinned;
     }
 
+    /// System "Reduce motion" and "Increase contrast" accessibility preferences
+    pub fn accessibility_display_options() -> (bool, bool) {
+        let workspace = NSWorkspace::sharedWorkspace();
+        (
+            workspace.accessibilityDisplayShouldReduceMotion(),
+            workspace.accessibilityDisplayShouldIncreaseContrast(),
+        )
+    }
+
+    /// Activity assertion keeping the app out of App Nap
+    struct NapActivity(Retained<ProtocolObject<dyn NSObjectProtocol>>);
+
+    // SAFETY: the activity token is an opaque object that is only passed back
+    // to NSProcessInfo, which is thread-safe
+    unsafe impl Send for NapActivity {}
+
+    static NAP_ACTIVITY: Mutex<Option<NapActivity>> = Mutex::new(None);
+
+    /// Opt out of (or back into) App Nap. While disabled the app runs at
+    /// user-initiated QoS; idle system sleep is still allowed.
+    pub fn set_app_nap_disabled(disabled: bool, reason: &str) {
+        let mut activity = NAP_ACTIVITY.lock();
+        let process_info = NSProcessInfo::processInfo();
+        match (disabled, activity.take()) {
+            (true, None) => {
+                *activity = Some(NapActivity(process_info.beginActivityWithOptions_reason(
+                    NSActivityOptions::UserInitiatedAllowingIdleSystemSleep,
+                    &NSString::from_str(reason),
+                )));
+            }
+            // SAFETY: the token was returned by beginActivityWithOptions:reason:
+            (false, Some(token)) => unsafe { process_info.endActivity(&token.0) },
+            (_, token) => *activity = token,
+        }
+    }
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOPMAssertionCreateWithName(
+            assertion_type: *const std::ffi::c_void,
+            level: u32,
+            name: *const std::ffi::c_void,
+            assertion_id: *mut u32,
+        ) -> i32;
+        fn IOPMAssertionRelease(assertion_id: u32) -> i32;
+        fn IOPSCopyPowerSourcesInfo() -> *mut AnyObject;
+        fn IOPSCopyPowerSourcesList(blob: *const AnyObject) -> *mut NSArray<AnyObject>;
+        fn IOPSGetPowerSourceDescription(
+            blob: *const AnyObject,
+            source: *const AnyObject,
+        ) -> *const NSDictionary<NSString, AnyObject>;
+        fn IOHIDCheckAccess(request_type: u32) -> u32;
+        fn IOHIDRequestAccess(request_type: u32) -> u8;
+    }
+
+    /// Power assertion preventing idle system sleep, if held
+    static SLEEP_ASSERTION: Mutex<Option<u32>> = Mutex::new(None);
+
+    /// Take or release a power assertion preventing idle system sleep.
+    /// Returns false if the assertion couldn't be created.
+    pub fn set_sleep_prevented(prevented: bool, reason: &str) -> bool {
+        // From IOKit/pwr_mgt/IOPMLib.h
+        const ASSERTION_TYPE: &str = "PreventUserIdleSystemSleep";
+        const ASSERTION_LEVEL_ON: u32 = 255;
+
+        let mut assertion = SLEEP_ASSERTION.lock();
+        match (prevented, *assertion) {
+            (true, None) => {
+                // NSString is toll-free bridged with CFStringRef
+                let assertion_type = NSString::from_str(ASSERTION_TYPE);
+                let name = NSString::from_str(reason);
+                let mut id = 0;
+                // SAFETY: both strings outlive the call and `id` is a valid out pointer
+                let ret = unsafe {
+                    IOPMAssertionCreateWithName(
+                        Retained::as_ptr(&assertion_type) as *const _,
+                        ASSERTION_LEVEL_ON,
+                        Retained::as_ptr(&name) as *const _,
+                        &mut id,
+                    )
+                };
+                if ret != 0 {
+                    return false;
+                }
+                *assertion = Some(id);
+            }
+            (false, Some(id)) => {
+                // SAFETY: the ID was returned by IOPMAssertionCreateWithName
+                unsafe { IOPMAssertionRelease(id) };
+                *assertion = None;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    /// Internal battery charge in percent and whether it is powering the Mac;
+    /// `None` on Macs without a battery
+    pub fn battery_status() -> Option<(u8, bool)> {
+        // SAFETY: the Copy functions return owned (toll-free bridged) CF
+        // objects or null
+        let info = unsafe { Retained::from_raw(IOPSCopyPowerSourcesInfo())? };
+        let list =
+            unsafe { Retained::from_raw(IOPSCopyPowerSourcesList(Retained::as_ptr(&info)))? };
+        (0..list.count()).find_map(|i| {
+            let source = list.objectAtIndex(i);
+            // SAFETY: the description is owned by `info`, which outlives it
+            let description = unsafe {
+                IOPSGetPowerSourceDescription(Retained::as_ptr(&info), Retained::as_ptr(&source))
+                    .as_ref()?
+            };
+            let value = |key: &str| description.objectForKey(&NSString::from_str(key));
+            let string = |key: &str| {
+                value(key)?
+                    .downcast::<NSString>()
+                    .ok()
+                    .map(|s| s.to_string())
+            };
+            let number = |key: &str| {
+                value(key)?
+                    .downcast::<NSNumber>()
+                    .ok()
+                    .map(|n| n.integerValue())
+            };
+
+            // Keys and values from IOKit/ps/IOPSKeys.h
+            if string("Type")? != "InternalBattery" {
+                return None;
+            }
+            let current = number("Current Capacity")?;
+            let max = number("Max Capacity")?.max(1);
+            let percent = (current * 100 / max).clamp(0, 100) as u8;
+            let on_battery = string("Power Source State").as_deref() == Some("Battery Power");
+            Some((percent, on_battery))
+        })
+    }
+
+    /// System thermal state, from 0 (nominal) to 3 (critical)
+    pub fn thermal_state() -> isize {
+        NSProcessInfo::processInfo().thermalState().0
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGWindowListCopyWindowInfo(
+            option: u32,
+            relative_to_window: u32,
+        ) -> *mut NSArray<NSDictionary<NSString, AnyObject>>;
+        fn CGSessionCopyCurrentDictionary() -> *mut NSDictionary<NSString, AnyObject>;
+    }
+
+    /// Whether the screen is being watched with Screen Sharing or Remote
+    /// Desktop (an undocumented but long-standing session key)
+    pub fn screen_is_shared() -> bool {
+        // SAFETY: the Copy function returns an owned (toll-free bridged)
+        // dictionary or null
+        let Some(session) = (unsafe { Retained::from_raw(CGSessionCopyCurrentDictionary()) })
+        else {
+            return false;
+        };
+        session
+            .objectForKey(&NSString::from_str("CGSSessionScreenIsShared"))
+            .and_then(|value| value.downcast::<NSNumber>().ok())
+            .is_some_and(|shared| shared.boolValue())
+    }
+
+    /// Owners and titles of the windows on screen, for screen capture
+    /// detection; titles need the Screen Recording permission
+    pub fn on_screen_windows() -> Vec<crate::presentation::ScreenWindow> {
+        // From CGWindow.h
+        const ON_SCREEN_ONLY: u32 = 1 << 0;
+        const EXCLUDE_DESKTOP_ELEMENTS: u32 = 1 << 4;
+
+        // SAFETY: the Copy function returns an owned (toll-free bridged)
+        // array or null
+        let Some(list) = (unsafe {
+            Retained::from_raw(CGWindowListCopyWindowInfo(
+                ON_SCREEN_ONLY | EXCLUDE_DESKTOP_ELEMENTS,
+                0,
+            ))
+        }) else {
+            return Vec::new();
+        };
+        (0..list.count())
+            .filter_map(|i| {
+                let window = list.objectAtIndex(i);
+                let string = |key: &str| {
+                    window
+                        .objectForKey(&NSString::from_str(key))?
+                        .downcast::<NSString>()
+                        .ok()
+                        .map(|s| s.to_string())
+                };
+                Some(crate::presentation::ScreenWindow {
+                    owner: string("kCGWindowOwnerName")?,
+                    title: string("kCGWindowName").filter(|title| !title.is_empty()),
+                })
+            })
+            .collect()
+    }
+
+    /// Make the window opaque with a black background, so neither the
+    /// webview's translucency nor what's behind the window shows through, or
+    /// transparent again. Must run on the main thread.
+    pub fn set_window_opaque(opaque: bool) {
+        let state = WINDOW_STATE.read();
+        let Some(window) = state.window.as_ref() else {
+            return;
+        };
+        let color = if opaque {
+            NSColor::blackColor()
+        } else {
+            NSColor::clearColor()
+        };
+        window.setOpaque(opaque);
+        window.setBackgroundColor(Some(&color));
+    }
+
+    /// Text on the general pasteboard, if any
+    pub fn clipboard_text() -> Option<String> {
+        // SAFETY: a framework constant, initialized before main
+        let string_type = unsafe { NSPasteboardTypeString };
+        NSPasteboard::generalPasteboard()
+            .stringForType(string_type)
+            .map(|text| text.to_string())
+    }
+
+    /// Replace the general pasteboard's contents with text
+    pub fn set_clipboard_text(text: &str) -> bool {
+        // SAFETY: a framework constant, initialized before main
+        let string_type = unsafe { NSPasteboardTypeString };
+        let pasteboard = NSPasteboard::generalPasteboard();
+        pasteboard.clearContents();
+        pasteboard.setString_forType(&NSString::from_str(text), string_type)
+    }
+
+    /// The user's preferred languages as BCP 47 tags, most preferred first
+    pub fn preferred_languages() -> Vec<String> {
+        NSLocale::preferredLanguages()
+            .iter()
+            .map(|language| language.to_string())
+            .collect()
+    }
+
+    /// kIOHIDRequestTypeListenEvent from IOKit/hidsystem/IOHIDLib.h
+    const HID_REQUEST_LISTEN_EVENT: u32 = 1;
+
+    /// Input Monitoring access: `Some(true)` if granted, `Some(false)` if
+    /// denied, `None` if the user hasn't been asked yet
+    pub fn input_monitoring_access() -> Option<bool> {
+        // IOHIDAccessType: granted, denied, unknown
+        // SAFETY: plain C call without pointers
+        match unsafe { IOHIDCheckAccess(HID_REQUEST_LISTEN_EVENT) } {
+            0 => Some(true),
+            1 => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Ask for Input Monitoring access (macOS prompts only the first time).
+    /// Returns whether it's granted.
+    pub fn request_input_monitoring() -> bool {
+        // SAFETY: plain C call without pointers
+        unsafe { IOHIDRequestAccess(HID_REQUEST_LISTEN_EVENT) != 0 }
+    }
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrustedWithOptions(options: *const NSDictionary<NSString, AnyObject>) -> u8;
+    }
+
+    /// Whether the app may use the accessibility API; with `prompt`, macOS
+    /// asks the user to allow it in System Settings if it isn't allowed yet
+    pub fn accessibility_trusted(prompt: bool) -> bool {
+        // kAXTrustedCheckOptionPrompt from HIServices/AXUIElement.h
+        let key = NSString::from_str("AXTrustedCheckOptionPrompt");
+        let value = NSNumber::new_bool(prompt);
+        let value: &AnyObject = &value;
+        let options = NSDictionary::from_slices(&[&*key], &[value]);
+        // SAFETY: the options dictionary outlives the call
+        unsafe { AXIsProcessTrustedWithOptions(Retained::as_ptr(&options)) != 0 }
+    }
+
+    #[link(name = "LocalAuthentication", kind = "framework")]
+    extern "C" {}
+
+    /// Ask for Touch ID or the account password (LAPolicyDeviceOwnerAuthentication).
+    /// `reply` is called on a private queue with the result; returns false if
+    /// the request couldn't be made.
+    pub fn authenticate_device_owner(reason: &str, reply: impl Fn(bool) + Send + 'static) -> bool {
+        // From LocalAuthentication/LAPublicDefines.h
+        const POLICY_DEVICE_OWNER_AUTHENTICATION: isize = 2;
+
+        let Some(class) = AnyClass::get(c"LAContext") else {
+            return false;
+        };
+        // SAFETY: LAContext's `new` returns a retained instance
+        let context: Retained<AnyObject> = unsafe { msg_send![class, new] };
+        let reason = NSString::from_str(reason);
+        // The block keeps the context alive until the evaluation completes
+        let context_for_reply = context.clone();
+        let block = RcBlock::new(move |success: Bool, _error: *mut AnyObject| {
+            let _ = &context_for_reply;
+            reply(success.as_bool());
+        });
+        // SAFETY: the selector and argument types match LAContext's
+        // evaluatePolicy:localizedReason:reply:
+        unsafe {
+            let _: () = msg_send![
+                &*context,
+                evaluatePolicy: POLICY_DEVICE_OWNER_AUTHENTICATION,
+                localizedReason: &*reason,
+                reply: &*block
+            ];
+        }
+        true
+    }
+
     /// Configure the window to behave like a menubar panel.
     ///
     /// This sets up the window with floating level, proper space behavior,
@@ -430,7 +823,7 @@ fn position_window<R: tauri::Runtime>(window: &WebviewWindow<R>) {
     {
         use objc2_app_kit::NSScreen;
         use objc2_foundation::MainThreadMarker;
-        use tauri::{PhysicalPosition, Position};
+        use tauri::{LogicalPosition, Position};
 
         // Get the screen where the mouse cursor is located
         // This ensures the window appears on the currently active screen
@@ -502,20 +895,188 @@ fn position_window<R: tauri::Runtime>(window: &WebviewWindow<R>) {
         let monitor = monitor.or_else(|| window.primary_monitor().ok().flatten());
 
         if let Some(monitor) = monitor {
-            let screen_size = *monitor.size();
-            let screen_position = monitor.position();
-            let screen_width = screen_size.width as f64;
-            let window_width = 800.0;
-
-            let x = screen_position.x + ((screen_width - window_width) / 2.0) as i32;
+            // Work in logical pixels: the monitor's physical bounds use its own
+            // scale factor, which may differ from the window's
+            let screen = window_commands::LogicalRect::of_monitor(&monitor);
+            let window_width = window_commands::LogicalRect::of_window(window)
+                .map(|bounds| bounds.width)
+                .unwrap_or(800.0);
+
+            let x = screen.x + (screen.width - window_width) / 2.0;
             // Position window directly below menubar with small gap
             // macOS menubar is typically 24-25 logical pixels, but screen_position.y
             // already accounts for the menubar on the primary display
-            let gap = 4; // Small gap between menubar and window
-            let y = screen_position.y + gap;
+            let gap = 4.0; // Small gap between menubar and window
+            let y = screen.y + gap;
+
+            let _ = window.set_position(Position::Logical(LogicalPosition { x, y }));
+        }
+    }
+}
+
+/// Act on a request forwarded by another launch
+fn handle_control_request(window: &WebviewWindow, request: control_socket::ControlRequest) {
+    match request {
+        control_socket::ControlRequest::Toggle => toggle_window(window),
+        control_socket::ControlRequest::Show => show_window(window),
+        control_socket::ControlRequest::Run(command) => {
+            if let Err(e) = launch_command::open(window.app_handle(), command) {
+                error!("Failed to start launch command: {}", e);
+            }
+            show_window(window);
+        }
+    }
+}
+
+/// Start a shell in a bookmark's directory and show the window
+fn open_bookmark(app: &AppHandle, id: i64) {
+    if let Err(e) = bookmarks::open(app, id) {
+        error!("Failed to open bookmark: {}", e);
+        return;
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        show_window(&window);
+    }
+}
+
+/// Run a recent item chosen in the tray menu and show the window it's in
+fn run_recent_action(app: &AppHandle, action: tray_recent::RecentAction) {
+    let window = match action {
+        tray_recent::RecentAction::FocusSession(session_id) => {
+            tray_recent::focus_session(app, &session_id)
+        }
+        tray_recent::RecentAction::Rerun(id) => {
+            tray_recent::rerun(app, id).map(|_| pty::MAIN_WINDOW_LABEL.to_string())
+        }
+    };
+    match window {
+        Ok(window) => {
+            if let Some(window) = app.get_webview_window(&window) {
+                show_window(&window);
+            }
+        }
+        Err(e) => error!("Failed to open recent item: {}", e),
+    }
+}
+
+/// Submenu of recent items, or `None` when there are none
+fn recent_submenu(
+    app: &AppHandle,
+    title: &str,
+    items: &[tray_recent::RecentItem],
+) -> tauri::Result<Option<Submenu<tauri::Wry>>> {
+    if items.is_empty() {
+        return Ok(None);
+    }
+    let submenu = Submenu::new(app, title, true)?;
+    for item in items {
+        submenu.append(&MenuItem::with_id(
+            app,
+            &item.menu_id,
+            &item.label,
+            true,
+            None::<&str>,
+        )?)?;
+    }
+    Ok(Some(submenu))
+}
+
+/// Tray menu entry of a widget: its title, with its items in a submenu.
+/// Widgets shown in the tray title only get an entry for their items.
+fn widget_menu_item(
+    app: &AppHandle,
+    output: &widgets::WidgetOutput,
+) -> tauri::Result<Option<Box<dyn IsMenuItem<tauri::Wry>>>> {
+    let item = |text: &str, enabled: bool| {
+        MenuItem::with_id(app, widgets::MENU_ID, text, enabled, None::<&str>)
+    };
+    let in_title = output.placement == widgets::WidgetPlacement::Title;
+    if output.sections.is_empty() && output.error.is_none() {
+        return Ok((!in_title).then_some(Box::new(item(&output.title, true)?) as _));
+    }
+    let label = if in_title {
+        &output.name
+    } else {
+        &output.title
+    };
+    let submenu = Submenu::new(app, label, true)?;
+    if let Some(error) = &output.error {
+        submenu.append(&item(error, false)?)?;
+    }
+    for (i, section) in output.sections.iter().enumerate() {
+        if i > 0 {
+            submenu.append(&PredefinedMenuItem::separator(app)?)?;
+        }
+        for text in section {
+            submenu.append(&item(text, true)?)?;
+        }
+    }
+    Ok(Some(Box::new(submenu)))
+}
 
-            let _ = window.set_position(Position::Physical(PhysicalPosition { x, y }));
+/// Tray menu: the menu bar widgets, bookmarks and recent sessions and
+/// commands, when there are any, and Quit
+fn tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let menu = Menu::new(app)?;
+    let mut has_widgets = false;
+    for output in widgets::list(app) {
+        if let Some(item) = widget_menu_item(app, &output)? {
+            menu.append(item.as_ref())?;
+            has_widgets = true;
+        }
+    }
+    if has_widgets {
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+    let bookmarks = bookmarks::list(app);
+    if !bookmarks.is_empty() {
+        let submenu = Submenu::new(app, "Bookmarks", true)?;
+        for bookmark in &bookmarks {
+            submenu.append(&MenuItem::with_id(
+                app,
+                bookmarks::menu_id(bookmark.id),
+                &bookmark.name,
+                true,
+                None::<&str>,
+            )?)?;
         }
+        menu.append(&submenu)?;
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+    let sessions = recent_submenu(app, "Recent Sessions", &tray_recent::sessions(app))?;
+    let commands = recent_submenu(app, "Recent Commands", &tray_recent::commands(app))?;
+    let has_recent = sessions.is_some() || commands.is_some();
+    for submenu in sessions.iter().chain(&commands) {
+        menu.append(submenu)?;
+    }
+    if has_recent {
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+    menu.append(&MenuItem::with_id(
+        app,
+        "quit",
+        "Quit µTerm",
+        true,
+        None::<&str>,
+    )?)?;
+    Ok(menu)
+}
+
+/// Replace the tray menu after the bookmarks or widgets changed, or to
+/// update the recent sessions and commands
+pub(crate) fn refresh_tray_menu(app: &AppHandle) -> tauri::Result<()> {
+    if let Some(tray) = app.tray_by_id(progress::TRAY_ID) {
+        tray.set_menu(Some(tray_menu(app)?))?;
+    }
+    Ok(())
+}
+
+/// Show the window, or focus it if it's already visible
+fn show_window(window: &WebviewWindow) {
+    if window.is_visible().unwrap_or(false) {
+        let _ = window.set_focus();
+    } else {
+        toggle_window(window);
     }
 }
 
@@ -538,7 +1099,10 @@ fn toggle_window(window: &WebviewWindow) {
                     error!("Failed to save window config: {}", e);
                 }
                 macos::hide_window(ns_window);
-                let _ = window.emit("window-visibility", false);
+                let _ = events::emit(window, events::WindowVisibility { visible: false });
+                idle::window_visibility_changed(window.app_handle(), false);
+                jobs::window_visibility_changed(window.app_handle(), false);
+                progress::refresh_indicators(window.app_handle());
             } else {
                 // Apply window size and position BEFORE showing (atomic operation)
                 if let Err(e) = apply_window_config(window) {
@@ -552,7 +1116,13 @@ fn toggle_window(window: &WebviewWindow) {
                 // Now show window with correct size and position already applied (no flash!)
                 // Pass None to show_window_at since position was already set by apply_window_config
                 macos::show_window_at(ns_window, None);
-                let _ = window.emit("window-visibility", true);
+                let _ = events::emit(window, events::WindowVisibility { visible: true });
+                idle::window_visibility_changed(window.app_handle(), true);
+                jobs::window_visibility_changed(window.app_handle(), true);
+                progress::refresh_indicators(window.app_handle());
+                status_strip::panel_shown(window.app_handle());
+                accessibility::refresh_display_preferences(window.app_handle());
+                dispatch_window_shown_hook(window);
             }
         }
     }
@@ -561,17 +1131,92 @@ fn toggle_window(window: &WebviewWindow) {
     {
         if window.is_visible().unwrap_or(false) {
             let _ = window.hide();
-            let _ = window.emit("window-visibility", false);
+            let _ = events::emit(window, events::WindowVisibility { visible: false });
+            idle::window_visibility_changed(window.app_handle(), false);
+            jobs::window_visibility_changed(window.app_handle(), false);
+            progress::refresh_indicators(window.app_handle());
         } else {
             let _ = window.show();
             let _ = window.set_focus();
-            let _ = window.emit("window-visibility", true);
+            let _ = events::emit(window, events::WindowVisibility { visible: true });
+            idle::window_visibility_changed(window.app_handle(), true);
+            jobs::window_visibility_changed(window.app_handle(), true);
+            progress::refresh_indicators(window.app_handle());
+            status_strip::panel_shown(window.app_handle());
+            accessibility::refresh_display_preferences(window.app_handle());
+            dispatch_window_shown_hook(window);
         }
     }
 }
 
+/// Open or close the status strip window to match the settings
+pub(crate) fn configure_status_strip(app: &AppHandle, enabled: bool) {
+    let existing = app.get_webview_window(status_strip::STATUS_STRIP_LABEL);
+    match (enabled, existing) {
+        (true, None) => match create_status_strip(app) {
+            Ok(_) => info!("Opened status strip"),
+            Err(e) => warn!("{}", e),
+        },
+        (false, Some(strip)) => {
+            if let Err(e) = strip.close() {
+                warn!("Failed to close status strip: {}", e);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Create the status strip in the top-right corner of the primary screen,
+/// below the menubar
+fn create_status_strip(app: &AppHandle) -> Result<WebviewWindow, String> {
+    use status_strip::{STATUS_STRIP_HEIGHT, STATUS_STRIP_MARGIN, STATUS_STRIP_WIDTH};
+
+    let (x, y) = app
+        .get_webview_window("main")
+        .and_then(|window| window.primary_monitor().ok().flatten())
+        .map(|monitor| {
+            let bounds = window_commands::LogicalRect::of_monitor(&monitor);
+            (
+                bounds.x + bounds.width - STATUS_STRIP_WIDTH - STATUS_STRIP_MARGIN,
+                bounds.y + 25.0 + STATUS_STRIP_MARGIN,
+            )
+        })
+        .unwrap_or((STATUS_STRIP_MARGIN, STATUS_STRIP_MARGIN));
+
+    WebviewWindowBuilder::new(
+        app,
+        status_strip::STATUS_STRIP_LABEL,
+        WebviewUrl::App("index.html".into()),
+    )
+    .title("µTerm Status")
+    .inner_size(STATUS_STRIP_WIDTH, STATUS_STRIP_HEIGHT)
+    .position(x, y)
+    .decorations(false)
+    .transparent(true)
+    .always_on_top(true)
+    .visible_on_all_workspaces(true)
+    .skip_taskbar(true)
+    .resizable(false)
+    .focused(false)
+    .build()
+    .map_err(|e| format!("Failed to create status strip window: {}", e))
+}
+
+/// Notify user scripts that the window was shown
+fn dispatch_window_shown_hook(window: &WebviewWindow) {
+    if let Some(script_manager) = window
+        .app_handle()
+        .try_state::<Arc<scripting::ScriptManager>>()
+    {
+        script_manager.dispatch(
+            scripting::HookEvent::WindowShown,
+            serde_json::json!({ "window": window.label() }),
+        );
+    }
+}
+
 /// Apply window configuration for current screen (size and position)
-fn apply_window_config(window: &WebviewWindow) -> Result<(), String> {
+pub(crate) fn apply_window_config(window: &WebviewWindow) -> Result<(), String> {
     use tauri::Manager;
 
     // Get screen config manager
@@ -638,6 +1283,10 @@ fn apply_window_config(window: &WebviewWindow) -> Result<(), String> {
         (screen_width, screen_height, position, scale, screen_id)
     };
 
+    // Each window mode has its own config per screen
+    let mode = window_mode::current(window.app_handle());
+    let screen_id = mode.screen_id(screen_id);
+
     // Available space (account for menubar on macOS)
     let available_height = (screen_height - 25.0).max(screen_height * 0.9);
     let available_width = screen_width;
@@ -650,6 +1299,7 @@ fn apply_window_config(window: &WebviewWindow) -> Result<(), String> {
         available_width,
         available_height,
     );
+    mode.adjust_config(&mut config);
 
     // VALIDATION: Detect corrupted config (window size larger than screen)
     // This can happen if configs were saved with wrong screen ID
@@ -838,7 +1488,7 @@ fn apply_window_config(window: &WebviewWindow) -> Result<(), String> {
 }
 
 /// Save current window configuration for the screen it's on
-fn save_window_config(window: &WebviewWindow) -> Result<(), String> {
+pub(crate) fn save_window_config(window: &WebviewWindow) -> Result<(), String> {
     use tauri::Manager;
 
     let config_manager = window
@@ -850,7 +1500,9 @@ fn save_window_config(window: &WebviewWindow) -> Result<(), String> {
         use objc2_app_kit::NSWindow;
 
         let screen_info = window_screen_info(window)?;
-        let screen_id = screen_config::ScreenId::from_display_id(screen_info.display_id);
+        let screen_id = window_mode::current(window.app_handle()).screen_id(
+            screen_config::ScreenId::from_display_id(screen_info.display_id),
+        );
 
         let ns_window = window
             .ns_window()
@@ -932,7 +1584,9 @@ fn save_window_config(window: &WebviewWindow) -> Result<(), String> {
 
         let screen_width = size.width as f64 / scale;
         let screen_height = size.height as f64 / scale;
-        let screen_id = screen_config::ScreenId::from_dimensions(screen_width, screen_height);
+        let screen_id = window_mode::current(window.app_handle()).screen_id(
+            screen_config::ScreenId::from_dimensions(screen_width, screen_height),
+        );
 
         // Get current window size and position
         let outer_size = window
@@ -1137,7 +1791,12 @@ fn init_logging() {
     });
 
     tracing_subscriber::registry()
-        .with(fmt::layer().with_target(true).with_thread_ids(false))
+        .with(
+            fmt::layer()
+                .with_target(true)
+                .with_thread_ids(false)
+                .with_writer(redact::RedactingStdout),
+        )
         .with(filter)
         .init();
 }
@@ -1149,25 +1808,112 @@ pub fn run() {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
             None,
         ))
         .manage(Arc::new(pty::PtyManager::new()))
+        .manage(Arc::new(progress::ProgressTracker::new()))
+        .manage(Arc::new(notifications::NotificationManager::new()))
+        .manage(Arc::new(accessibility::AccessibilityManager::new()))
+        .manage(Arc::new(presentation::PresentationManager::new()))
+        .manage(Arc::new(clipboard::ClipboardManager::new()))
+        .manage(Arc::new(images::ImageStore::new()))
+        .manage(Arc::new(jobs::JobManager::new()))
+        .manage(Arc::new(ssh::SshManager::new()))
+        .manage(Arc::new(trash::SafeDelete::new()))
+        .manage(Arc::new(widgets::WidgetManager::new()))
+        .manage(Arc::new(launch_command::OpenedSessions::new()))
+        .manage(Arc::new(window_mode::WindowModeManager::new()))
+        .manage(Arc::new(status_strip::StatusStrip::new()))
+        .manage(Arc::new(idle::IdleSuspender::new()))
+        .manage(Arc::new(quit_guard::QuitGuard::new()))
+        .manage(Arc::new(lock::LockManager::new()))
         .invoke_handler(tauri::generate_handler![
             commands::execute_command,
             commands::execute_command_stream,
             commands::complete_command,
             commands::hide_window,
+            doctor::check_environment,
             pty_commands::create_pty_session,
             pty_commands::write_to_pty,
+            pty_commands::paste_to_pty,
+            clipboard::answer_clipboard_request,
+            pty_commands::send_key_event,
+            pty_commands::pass_shortcut,
+            pty_commands::send_mouse_event,
             pty_commands::resize_pty,
             pty_commands::close_pty_session,
+            pty_commands::set_session_protected,
+            pty_commands::clear_session_scrollback,
+            pty_commands::move_session_to_window,
+            pty_commands::reset_session,
+            pty_commands::set_session_read_only,
+            quit_guard::quit_app,
+            quit_guard::get_protected_sessions,
+            lock::lock_app,
+            lock::unlock_app,
+            askpass::enter_sudo_password,
+            lock::get_lock_state,
             pty_commands::get_pty_cwd,
+            pty_commands::search_all_sessions,
+            pty_commands::search_scrollback,
+            pty_commands::open_session_in_dir,
+            pty_commands::set_session_labels,
+            pty_commands::get_session_labels,
+            pty_commands::list_session_labels,
+            pty_commands::list_pty_sessions,
+            pty_commands::list_detached_sessions,
+            pty_commands::reattach_pty_session,
+            pty_commands::get_last_command_stats,
+            pty_commands::check_shell_integration,
+            pty_commands::get_foreground_process,
+            pty_commands::session_has_running_process,
+            pty_commands::start_session_log,
+            pty_commands::stop_session_log,
+            recent_dirs::list_recent_dirs,
+            session_store::list_session_records,
+            session_store::list_command_history,
+            commands::rerun_history_entry,
+            project::detect_project,
+            profiles::resolve_directory_profile,
+            profiles::list_profiles,
+            profiles::save_profile,
+            profiles::delete_profile,
+            workspace::list_workspaces,
+            workspace::save_workspace,
+            workspace::delete_workspace,
+            workspace::open_workspace,
+            pty_commands::create_pty_session_with_profile,
+            jobs::run_project_task,
+            jobs::list_jobs,
+            ssh::list_ssh_hosts,
+            ssh::add_ssh_host,
+            ssh::remove_ssh_host,
+            ssh::connect_ssh,
+            ssh::reconnect_ssh,
+            ssh::check_mosh,
+            ssh::list_ssh_connections,
+            trash::move_to_trash,
+            trash::resolve_rm_intercept,
+            launch_command::take_opened_sessions,
+            bookmarks::list_bookmarks,
+            bookmarks::add_bookmark,
+            bookmarks::remove_bookmark,
+            bookmarks::open_bookmark,
+            widgets::get_menu_widgets,
+            links::detect_links,
+            links::open_link,
+            file_drop::drop_paths,
             window_commands::get_screen_info,
             window_commands::adjust_window_size,
             window_commands::ensure_window_visible,
+            window_commands::cycle_window_size,
+            window_mode::get_window_mode,
+            window_mode::transition_window_mode,
+            status_strip::get_status_snapshot,
             settings_commands::get_settings,
             settings_commands::update_settings,
             settings_commands::set_opacity,
@@ -1175,7 +1921,49 @@ pub fn run() {
             settings_commands::set_pinned,
             settings_commands::get_pinned,
             settings_commands::set_onboarding_complete,
+            settings_commands::get_onboarding_state,
+            settings_commands::set_onboarding_step,
+            permissions::get_permission_status,
+            permissions::request_permission,
+            permissions::open_permission_settings,
+            scripting::list_scripts,
+            scripting::reload_scripts,
+            macros::start_macro_recording,
+            macros::stop_macro_recording,
+            macros::cancel_macro_recording,
+            macros::list_macros,
+            macros::delete_macro,
+            macros::play_macro,
+            paste::prepare_paste,
+            notifications::set_session_notifications,
+            notifications::get_session_notifications,
+            images::get_inline_image,
+            release_notes::get_release_notes,
+            accessibility::get_display_preferences,
+            power::get_power_status,
+            presentation::get_presentation_mode,
+            presentation::set_presentation_mode,
+            presentation::toggle_presentation_mode,
+            usage_stats::get_usage_stats,
+            usage_stats::export_usage_stats,
+            usage_stats::reset_usage_stats,
+            usage_stats::record_feature_usage,
+            metrics::get_metrics,
+            events::get_event_schema,
         ])
+        // A (re)loaded page has no panes yet; its window's sessions are orphans
+        // until a terminal picks them up
+        .on_page_load(|webview, payload| {
+            if payload.event() != PageLoadEvent::Started {
+                return;
+            }
+            if let Some(pty_manager) = webview.try_state::<Arc<pty::PtyManager>>() {
+                let detached = pty_manager.detach_window(webview.label());
+                if detached > 0 {
+                    info!(window = %webview.label(), detached, "Page loaded; sessions detached");
+                }
+            }
+        })
         .setup(|app| {
             let window = app
                 .get_webview_window("main")
@@ -1183,24 +1971,96 @@ pub fn run() {
             let window_for_tray = window.clone();
             let window_for_shortcut = window.clone();
 
+            // Settings and stored data go to the app data directory, unless overridden
+            let app_data_dir = config_dir::resolve(app)?;
+
+            // Hand the launch over to an instance already using this directory
+            let control_socket_path = control_socket::socket_path(&app_data_dir);
+            let args: Vec<String> = std::env::args().skip(1).collect();
+            let request = control_socket::ControlRequest::from_args(&args);
+            match control_socket::forward(&control_socket_path, &request) {
+                Ok(true) => {
+                    info!("Forwarded launch to the running instance, exiting");
+                    std::process::exit(0);
+                }
+                Ok(false) => {}
+                Err(e) => warn!("{}", e),
+            }
+
             // Initialize screen config manager
-            let config_path = app
-                .path()
-                .app_data_dir()
-                .map_err(|e| tauri::Error::Anyhow(e.into()))?
-                .join("screen-configs.json");
+            let config_path = app_data_dir.join("screen-configs.json");
             let screen_config_manager =
                 Arc::new(screen_config::ScreenConfigManager::new(config_path));
             app.manage(screen_config_manager.clone());
 
             // Initialize settings manager
-            let settings_path = app
-                .path()
-                .app_data_dir()
-                .map_err(|e| tauri::Error::Anyhow(e.into()))?
-                .join("settings.json");
+            let settings_path = app_data_dir.join("settings.json");
             let settings_manager = Arc::new(settings::SettingsManager::new(settings_path));
             app.manage(settings_manager.clone());
+            redact::configure(&settings_manager.get());
+            i18n::configure(&settings_manager.get());
+            links::configure(&settings_manager.get());
+
+            // Initialize local usage statistics (only counts when opted in)
+            let usage_stats_path = app_data_dir.join("usage-stats.json");
+            app.manage(Arc::new(usage_stats::UsageStatsManager::new(
+                usage_stats_path,
+                settings_manager.clone(),
+            )));
+
+            // Initialize keyboard macro store
+            let macros_path = app_data_dir.join("macros.json");
+            app.manage(Arc::new(macros::MacroManager::new(macros_path)));
+
+            // Initialize session metadata store and the recent directories list
+            let session_store = Arc::new(session_store::SessionStore::open(
+                &app_data_dir.join(session_store::DATABASE_FILE),
+            ));
+            session_store.import_legacy_files(&app_data_dir);
+            app.manage(Arc::new(recent_dirs::RecentDirsManager::new(
+                session_store.clone(),
+            )));
+            app.manage(session_store);
+
+            // Session transcripts go next to the other stored data
+            app.state::<Arc<pty::PtyManager>>()
+                .set_log_dir(app_data_dir.join(transcript::TRANSCRIPTS_DIR));
+
+            // Initialize release notes cache
+            let release_notes_dir = app_data_dir.join("release-notes");
+            app.manage(Arc::new(release_notes::ReleaseNotesManager::new(
+                release_notes_dir,
+            )));
+
+            // Initialize user scripting hooks
+            let scripts_dir = app_data_dir.join("scripts");
+            let pty_manager = app.state::<Arc<pty::PtyManager>>().inner().clone();
+            app.manage(Arc::new(scripting::ScriptManager::new(
+                scripts_dir,
+                pty_manager,
+            )));
+
+            // Install the SUDO_ASKPASS helper for sessions and commands
+            if let Err(e) = askpass::install(&app_data_dir) {
+                warn!("{}", e);
+            }
+
+            // Serve the localhost status endpoint if enabled
+            let status_server = Arc::new(status_server::StatusServer::new(&app_data_dir));
+            status_server.configure(app.handle(), &settings_manager.get());
+            app.manage(status_server);
+
+            // Open the status strip if enabled
+            configure_status_strip(app.handle(), settings_manager.get().status_strip_enabled);
+
+            // Check for idle shells to suspend while the window is hidden
+            idle::IdleSuspender::start(app.handle().clone());
+            orphans::start(app.handle().clone());
+            app_nap::start(app.handle().clone());
+            caffeinate::start(app.handle().clone());
+            power::start(app.handle().clone());
+            presentation::start(app.handle().clone());
+            lock::LockManager::start(app.handle().clone());
 
             // Note: Window size is now managed by screen_config.rs per-screen
             // It will be applied in apply_window_config() when window is first shown
@@ -1220,9 +2080,8 @@ pub fn run() {
                 }
             }
 
-            // Create quit menu for tray icon (shown on right-click)
-            let quit_item = MenuItem::with_id(app, "quit", "Quit µTerm", true, None::<&str>)?;
-            let tray_menu = Menu::with_items(app, &[&quit_item])?;
+            // Create bookmarks and quit menu for tray icon (shown on right-click)
+            let tray_menu = tray_menu(app.handle())?;
 
             // Create system tray
             // IMPORTANT: Use MouseButtonState::Up to trigger on mouse release, not press
@@ -1231,33 +2090,46 @@ pub fn run() {
                 .default_window_icon()
                 .ok_or_else(|| tauri::Error::AssetNotFound("default window icon".to_string()))?
                 .clone();
-            let _tray = tauri::tray::TrayIconBuilder::new()
+            let _tray = tauri::tray::TrayIconBuilder::with_id(progress::TRAY_ID)
                 .icon(tray_icon)
                 .tooltip("µTerm")
                 .menu(&tray_menu)
                 .show_menu_on_left_click(false)
-                .on_tray_icon_event(move |_tray, event| {
+                .on_tray_icon_event(move |tray, event| {
                     // Left click: toggle window
                     // Right click: menu is shown automatically by Tauri
-                    if let TrayIconEvent::Click {
-                        button: tauri::tray::MouseButton::Left,
-                        button_state: tauri::tray::MouseButtonState::Up,
-                        ..
-                    } = event
-                    {
-                        toggle_window(&window_for_tray);
+                    match event {
+                        TrayIconEvent::Click {
+                            button: tauri::tray::MouseButton::Left,
+                            button_state: tauri::tray::MouseButtonState::Up,
+                            ..
+                        } => toggle_window(&window_for_tray),
+                        // Rebuild before a click opens the menu, so the
+                        // recent sessions and commands are current
+                        TrayIconEvent::Enter { .. } => {
+                            if let Err(e) = refresh_tray_menu(tray.app_handle()) {
+                                warn!("Failed to update the tray menu: {}", e);
+                            }
+                        }
+                        _ => {}
                     }
                 })
                 .on_menu_event(|app, event| {
                     if event.id.as_ref() == "quit" {
-                        // Clean up before quitting
-                        #[cfg(target_os = "macos")]
-                        macos::cleanup();
-                        app.exit(0);
+                        // Cleans up before quitting, unless protected sessions block it
+                        let _ = quit_guard::quit(app, false);
+                    } else if let Some(id) = bookmarks::id_from_menu_id(event.id.as_ref()) {
+                        open_bookmark(app, id);
+                    } else if let Some(action) = tray_recent::action_from_menu_id(event.id.as_ref())
+                    {
+                        run_recent_action(app, action);
                     }
                 })
                 .build(app)?;
 
+            // Run menu bar widget scripts now that their tray exists
+            widgets::start(app.handle().clone());
+
             // Listen for toggle-window event from frontend (triggered by global shortcut)
             // IMPORTANT: Window operations must run on main thread
             let app_handle = app.handle().clone();
@@ -1271,6 +2143,22 @@ pub fn run() {
                 });
             });
 
+            // Serve requests forwarded by later launches
+            let app_handle = app.handle().clone();
+            let listening = control_socket::listen(&control_socket_path, move |request| {
+                let app_handle_clone = app_handle.clone();
+                app_handle
+                    .run_on_main_thread(move || {
+                        if let Some(window) = app_handle_clone.get_webview_window("main") {
+                            handle_control_request(&window, request);
+                        }
+                    })
+                    .map_err(|e| e.to_string())
+            });
+            if let Err(e) = listening {
+                warn!("{}", e);
+            }
+
             // Listen for pin-state-changed event from frontend
             // Define payload struct for type-safe deserialization
             #[derive(serde::Deserialize)]
@@ -1298,6 +2186,35 @@ pub fn run() {
                 },
             );
 
+            // Don't close the window over protected sessions
+            {
+                let app_handle = app.handle().clone();
+                window.on_window_event(move |event| {
+                    if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                        if quit_guard::is_blocked(
+                            &app_handle,
+                            quit_guard::BlockedAction::CloseWindow,
+                        ) {
+                            api.prevent_close();
+                        }
+                    }
+                });
+            }
+
+            // Report key window changes (including hiding the panel) to
+            // programs that enabled focus reporting
+            {
+                let app_handle = app.handle().clone();
+                let label = window.label().to_string();
+                window.on_window_event(move |event| {
+                    if let tauri::WindowEvent::Focused(focused) = event {
+                        if let Some(pty_manager) = app_handle.try_state::<Arc<pty::PtyManager>>() {
+                            pty_manager.report_focus(&label, *focused);
+                        }
+                    }
+                });
+            }
+
             // Listen for window resize events to auto-save configuration
             {
                 let window_for_resize = window.clone();
@@ -1335,7 +2252,7 @@ pub fn run() {
             }
 
             // Also emit an event when window is toggled so frontend can track state
-            let _ = app.emit("window-ready", ());
+            let _ = events::emit(app, events::WindowReady {});
 
             // Hide window initially
             #[cfg(target_os = "macos")]
@@ -1353,11 +2270,34 @@ pub fn run() {
                 let _ = window_for_shortcut.hide();
             }
 
+            // Launched to run a command: start it and show the window
+            if let control_socket::ControlRequest::Run(_) = request {
+                handle_control_request(&window_for_shortcut, request);
+            }
+
             Ok(())
         })
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|app_handle, event| {
+            // Cmd+Q and other exit requests don't kill protected sessions either
+            if let tauri::RunEvent::ExitRequested { api, .. } = &event {
+                if quit_guard::is_blocked(app_handle, quit_guard::BlockedAction::Quit) {
+                    api.prevent_exit();
+                }
+            }
+
+            // microterm://bookmark/<id> URLs open the bookmark
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = &event {
+                for url in urls {
+                    match bookmarks::id_from_url(url.as_str()) {
+                        Some(id) => open_bookmark(app_handle, id),
+                        None => warn!(url = %url, "Ignoring unsupported URL"),
+                    }
+                }
+            }
+
             // Handle Dock icon click (Reopen event)
             if let tauri::RunEvent::Reopen { .. } = event {
                 if let Some(window) = app_handle.get_webview_window("main") {
@@ -0,0 +1,592 @@
+//! Link detection
+//!
+//! `detect_links` finds URLs, file paths (with an optional `:line:column`),
+//! IPv4 addresses and git commit hashes in a piece of terminal text, so every
+//! pane highlights the same links. The `link_patterns` setting adds patterns
+//! with a URL template (e.g. `[A-Z]+-\d+` for issue keys, opening
+//! `https://jira.example.com/browse/$0`); they take precedence over the
+//! built-in ones. Where matches overlap, the one starting first wins.
+//!
+//! Offsets are in UTF-16 code units, so they index the text as a JavaScript
+//! string.
+//!
+//! `open_link` opens a clicked link by its scheme: the `link_handlers` setting
+//! maps schemes to the default app, a specific browser or the frontend's
+//! preview. Schemes without a handler other than `TRUSTED_SCHEMES` are only
+//! opened once the user has confirmed, and `BLOCKED_SCHEMES` never are.
+
+use crate::settings::{AppSettings, SettingsManager};
+use parking_lot::RwLock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, LazyLock};
+use tauri::{command, State};
+use tracing::{info, warn};
+
+/// Longest text `detect_links` scans, in bytes
+pub const MAX_TEXT_LENGTH: usize = 64 * 1024;
+
+/// Built-in patterns in order of precedence; the `link` group is the link
+const BUILTIN_PATTERNS: &[(LinkKind, &str)] = &[
+    (
+        LinkKind::Url,
+        r#"(?P<link>\b(?:https?|ftp|file)://[^\s<>"'`]+)"#,
+    ),
+    (
+        LinkKind::Path,
+        r#"(?:^|[\s"'(\[=:])(?P<link>(?P<path>(?:~|\.{1,2})?/[\w.@%+~-]+(?:/[\w.@%+~-]+)*/?|[\w.-]+(?:/[\w.@%+~-]+)*/[\w@%+~-]*\.\w+)(?::(?P<line>\d+)(?::(?P<column>\d+))?)?)"#,
+    ),
+    (
+        LinkKind::Ip,
+        r"\b(?P<link>(?:(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)(?::\d{1,5})?)\b",
+    ),
+    (LinkKind::GitSha, r"\b(?P<link>[0-9a-f]{7,40})\b"),
+];
+
+/// Schemes opened without a handler or confirmation
+const TRUSTED_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Schemes that run code in whatever opens them
+const BLOCKED_SCHEMES: &[&str] = &["javascript", "vbscript", "data"];
+
+/// What a link points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    Url,
+    Path,
+    Ip,
+    GitSha,
+    /// Matched a `link_patterns` entry
+    Custom,
+}
+
+/// A `link_patterns` entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkPattern {
+    pub pattern: String,
+    /// URL opened for a match; `$0` is the whole match, `$1` or `${name}` a group
+    pub url: String,
+}
+
+/// A link found by `detect_links`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Link {
+    pub kind: LinkKind,
+    pub text: String,
+    /// UTF-16 offset of the first character
+    pub start: usize,
+    /// UTF-16 offset after the last character
+    pub end: usize,
+    /// URL to open, for URLs and custom patterns
+    pub url: Option<String>,
+    /// Line and column after a path (`src/main.rs:10:5`)
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+struct Pattern {
+    kind: LinkKind,
+    regex: Regex,
+    /// URL template of a custom pattern
+    url: Option<String>,
+}
+
+/// A set of compiled link patterns
+pub struct LinkDetector {
+    patterns: Vec<Pattern>,
+}
+
+/// A match before overlaps are resolved, with byte offsets
+struct Candidate {
+    start: usize,
+    end: usize,
+    link: Link,
+}
+
+impl LinkDetector {
+    /// Built-in patterns plus `custom`; returns the detector and an error for
+    /// each custom pattern that doesn't compile
+    pub fn new(custom: &[LinkPattern]) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        let custom = custom
+            .iter()
+            .filter_map(|pattern| match Regex::new(&pattern.pattern) {
+                Ok(regex) => Some(Pattern {
+                    kind: LinkKind::Custom,
+                    regex,
+                    url: Some(pattern.url.clone()),
+                }),
+                Err(e) => {
+                    errors.push(format!("Invalid link pattern {:?}: {}", pattern.pattern, e));
+                    None
+                }
+            });
+        let builtin = BUILTIN_PATTERNS.iter().map(|(kind, pattern)| Pattern {
+            kind: *kind,
+            regex: Regex::new(pattern).expect("built-in link patterns are valid"),
+            url: None,
+        });
+        let patterns = custom.chain(builtin).collect();
+        (Self { patterns }, errors)
+    }
+
+    /// Links in `text`, in order
+    pub fn detect(&self, text: &str) -> Vec<Link> {
+        let mut candidates: Vec<Candidate> = Vec::new();
+        for pattern in &self.patterns {
+            for caps in pattern.regex.captures_iter(text) {
+                let Some(whole) = caps.name("link").or_else(|| caps.get(0)) else {
+                    continue;
+                };
+                let mut end = whole.end();
+                if pattern.kind == LinkKind::Url {
+                    end = whole.start() + trim_url(whole.as_str()).len();
+                }
+                let matched = &text[whole.start()..end];
+                let plausible = match pattern.kind {
+                    LinkKind::GitSha => looks_like_sha(matched),
+                    LinkKind::Ip => !is_version_part(text, whole.start(), end),
+                    _ => true,
+                };
+                if matched.is_empty() || !plausible {
+                    continue;
+                }
+                let url = match (pattern.kind, &pattern.url) {
+                    (LinkKind::Url, _) => Some(matched.to_string()),
+                    (_, Some(template)) => {
+                        let mut url = String::new();
+                        caps.expand(template, &mut url);
+                        Some(url)
+                    }
+                    _ => None,
+                };
+                let number = |name| caps.name(name).and_then(|m| m.as_str().parse().ok());
+                candidates.push(Candidate {
+                    start: whole.start(),
+                    end,
+                    link: Link {
+                        kind: pattern.kind,
+                        text: caps
+                            .name("path")
+                            .map_or(matched, |path| path.as_str())
+                            .to_string(),
+                        start: 0,
+                        end: 0,
+                        url,
+                        line: number("line"),
+                        column: number("column"),
+                    },
+                });
+            }
+        }
+
+        // Stable, so patterns earlier in the list win ties
+        candidates.sort_by_key(|candidate| candidate.start);
+        let mut links = Vec::new();
+        let mut taken = 0;
+        for Candidate { start, end, link } in candidates {
+            if start < taken {
+                continue;
+            }
+            taken = end;
+            links.push(Link {
+                start: utf16_len(&text[..start]),
+                end: utf16_len(&text[..end]),
+                ..link
+            });
+        }
+        links
+    }
+}
+
+/// Drop trailing punctuation and closing brackets that aren't part of the URL
+fn trim_url(url: &str) -> &str {
+    let mut url = url;
+    loop {
+        let trimmed = url.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        let trimmed = match trimmed.chars().last() {
+            Some(close @ (')' | ']')) => {
+                let open = if close == ')' { '(' } else { '[' };
+                if trimmed.matches(open).count() < trimmed.matches(close).count() {
+                    &trimmed[..trimmed.len() - 1]
+                } else {
+                    trimmed
+                }
+            }
+            _ => trimmed,
+        };
+        if trimmed.len() == url.len() {
+            return url;
+        }
+        url = trimmed;
+    }
+}
+
+/// Hex words (`deadbeef`) and plain numbers aren't commit hashes
+fn looks_like_sha(text: &str) -> bool {
+    text.bytes().any(|b| b.is_ascii_digit()) && text.bytes().any(|b| b.is_ascii_alphabetic())
+}
+
+/// Whether a dotted number is part of a longer one (`1.2.3.4.5`)
+fn is_version_part(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let mut after = text[end..].chars();
+    before == Some('.')
+        || (after.next() == Some('.') && after.next().is_some_and(|c| c.is_ascii_digit()))
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
+
+/// How links with a scheme are opened (a `link_handlers` value)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LinkHandler {
+    /// The app macOS opens the scheme with
+    Default,
+    /// A specific browser, by app name (e.g. `Firefox`)
+    Browser { app: String },
+    /// The frontend's preview pane
+    Preview,
+}
+
+/// Result of `open_link`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum OpenLinkResult {
+    Opened,
+    /// The frontend should show the link in its preview
+    Preview {
+        uri: String,
+    },
+    /// The scheme is unusual; call again with `confirmed` once the user agreed
+    Confirm {
+        scheme: String,
+    },
+}
+
+/// What `open_link` does with a link
+#[derive(Debug, PartialEq, Eq)]
+enum LinkAction {
+    Open { app: Option<String> },
+    Preview,
+    Confirm(String),
+}
+
+/// Whether `name` is a valid URI scheme (a letter, then letters, digits, `+`, `-` or `.`)
+pub fn is_scheme(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Scheme of a URI, lowercased
+fn scheme(uri: &str) -> Option<String> {
+    let (scheme, _) = uri.split_once(':')?;
+    is_scheme(scheme).then(|| scheme.to_ascii_lowercase())
+}
+
+fn resolve(
+    handlers: &BTreeMap<String, LinkHandler>,
+    uri: &str,
+    confirmed: bool,
+) -> Result<LinkAction, String> {
+    if uri.is_empty() || uri.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err("Invalid link".to_string());
+    }
+    let scheme = scheme(uri).ok_or("Link has no scheme")?;
+    if BLOCKED_SCHEMES.contains(&scheme.as_str()) {
+        return Err(format!("{} links can't be opened", scheme));
+    }
+    // Configuring a handler counts as trusting the scheme
+    let action = match handlers.get(&scheme) {
+        Some(LinkHandler::Default) => LinkAction::Open { app: None },
+        Some(LinkHandler::Browser { app }) => LinkAction::Open {
+            app: Some(app.clone()),
+        },
+        Some(LinkHandler::Preview) => LinkAction::Preview,
+        None if confirmed || TRUSTED_SCHEMES.contains(&scheme.as_str()) => {
+            LinkAction::Open { app: None }
+        }
+        None => LinkAction::Confirm(scheme),
+    };
+    Ok(action)
+}
+
+#[cfg(target_os = "macos")]
+fn open_with(uri: &str, app: Option<&str>) -> Result<(), String> {
+    let mut cmd = std::process::Command::new("open");
+    if let Some(app) = app {
+        cmd.arg("-a").arg(app);
+    }
+    // The URI starts with its scheme, so it can't be taken for an option
+    let status = cmd
+        .arg(uri)
+        .status()
+        .map_err(|e| format!("Failed to open link: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("Failed to open link: {}", status))
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn open_with(_uri: &str, _app: Option<&str>) -> Result<(), String> {
+    Err("Opening links is only available on macOS".to_string())
+}
+
+/// Open a link with its scheme's handler. Unusual schemes come back as
+/// `confirm` until called with `confirmed`.
+#[command]
+pub fn open_link(
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    uri: String,
+    confirmed: Option<bool>,
+) -> Result<OpenLinkResult, String> {
+    let handlers = settings_manager.get().link_handlers;
+    let result = match resolve(&handlers, &uri, confirmed.unwrap_or(false))? {
+        LinkAction::Open { app } => {
+            // Only the scheme is logged; links may carry tokens
+            info!(scheme = ?scheme(&uri), app = ?app, "Opening link");
+            open_with(&uri, app.as_deref())?;
+            OpenLinkResult::Opened
+        }
+        LinkAction::Preview => OpenLinkResult::Preview { uri },
+        LinkAction::Confirm(scheme) => OpenLinkResult::Confirm { scheme },
+    };
+    Ok(result)
+}
+
+/// Detector used by `detect_links`, replaced when settings change
+static DETECTOR: LazyLock<RwLock<Arc<LinkDetector>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(LinkDetector::new(&[]).0)));
+
+/// Rebuild the detector from the settings
+pub fn configure(settings: &AppSettings) {
+    let (detector, errors) = LinkDetector::new(&settings.link_patterns);
+    *DETECTOR.write() = Arc::new(detector);
+    for error in errors {
+        warn!("{}", error);
+    }
+}
+
+/// Links in a piece of terminal text (at most `MAX_TEXT_LENGTH` bytes)
+#[command]
+pub fn detect_links(text: String) -> Result<Vec<Link>, String> {
+    if text.len() > MAX_TEXT_LENGTH {
+        return Err(format!(
+            "Text too long for link detection: {} bytes (max {})",
+            text.len(),
+            MAX_TEXT_LENGTH
+        ));
+    }
+    let detector = DETECTOR.read().clone();
+    Ok(detector.detect(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(text: &str) -> Vec<(LinkKind, String)> {
+        LinkDetector::new(&[])
+            .0
+            .detect(text)
+            .into_iter()
+            .map(|link| (link.kind, link.text))
+            .collect()
+    }
+
+    fn one(kind: LinkKind, text: &str) -> Vec<(LinkKind, String)> {
+        vec![(kind, text.to_string())]
+    }
+
+    #[test]
+    fn test_urls() {
+        assert_eq!(
+            detect("see https://example.com/a?b=1."),
+            one(LinkKind::Url, "https://example.com/a?b=1")
+        );
+        assert_eq!(
+            detect("(docs at https://en.wikipedia.org/wiki/Rust_(language))"),
+            one(
+                LinkKind::Url,
+                "https://en.wikipedia.org/wiki/Rust_(language)"
+            )
+        );
+        assert_eq!(
+            detect("[link](https://example.com)"),
+            one(LinkKind::Url, "https://example.com")
+        );
+        // Paths and hosts inside a URL aren't reported separately
+        assert_eq!(
+            detect("http://10.0.0.1:8080/static/app.js"),
+            one(LinkKind::Url, "http://10.0.0.1:8080/static/app.js")
+        );
+    }
+
+    #[test]
+    fn test_paths() {
+        let links = LinkDetector::new(&[])
+            .0
+            .detect("error at src/main.rs:10:5: expected `;`");
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].kind, LinkKind::Path);
+        assert_eq!(links[0].text, "src/main.rs");
+        assert_eq!((links[0].line, links[0].column), (Some(10), Some(5)));
+        assert_eq!((links[0].start, links[0].end), (9, 25));
+
+        assert_eq!(
+            detect("cd ~/work/api && ls"),
+            one(LinkKind::Path, "~/work/api")
+        );
+        assert_eq!(
+            detect("open '/var/log/system.log'"),
+            one(LinkKind::Path, "/var/log/system.log")
+        );
+        assert_eq!(
+            detect("../lib/index.ts"),
+            one(LinkKind::Path, "../lib/index.ts")
+        );
+        // Fractions and bare slashes aren't paths
+        assert!(detect("1/2 done / 50% left").is_empty());
+        assert!(detect("and/or").is_empty());
+    }
+
+    #[test]
+    fn test_ips_and_shas() {
+        assert_eq!(
+            detect("listening on 127.0.0.1:3000"),
+            one(LinkKind::Ip, "127.0.0.1:3000")
+        );
+        assert!(detect("version 1.2.3.4.5").is_empty());
+        assert!(detect("999.1.1.1").is_empty());
+        assert_eq!(
+            detect("HEAD is now at 3614f32 Accept a startup command"),
+            one(LinkKind::GitSha, "3614f32")
+        );
+        assert!(detect("deadbeef 1234567 facade").is_empty());
+    }
+
+    #[test]
+    fn test_custom_patterns_come_first() {
+        let (detector, errors) = LinkDetector::new(&[
+            LinkPattern {
+                pattern: r"\b(?P<key>[A-Z]+-\d+)\b".to_string(),
+                url: "https://jira.example.com/browse/${key}".to_string(),
+            },
+            LinkPattern {
+                pattern: "(".to_string(),
+                url: String::new(),
+            },
+        ]);
+        assert_eq!(errors.len(), 1);
+        let links = detector.detect("fixes OPS-42 in abc1234");
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].kind, LinkKind::Custom);
+        assert_eq!(
+            links[0].url.as_deref(),
+            Some("https://jira.example.com/browse/OPS-42")
+        );
+        assert_eq!(links[1].kind, LinkKind::GitSha);
+    }
+
+    #[test]
+    fn test_offsets_are_utf16() {
+        let links = LinkDetector::new(&[]).0.detect("🚀 µ https://example.com");
+        assert_eq!((links[0].start, links[0].end), (5, 24));
+    }
+
+    #[test]
+    fn test_long_text_is_rejected() {
+        assert!(detect_links("x".repeat(MAX_TEXT_LENGTH + 1)).is_err());
+        assert!(detect_links("x".repeat(MAX_TEXT_LENGTH))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_is_scheme() {
+        assert!(is_scheme("https"));
+        assert!(is_scheme("x-man-page"));
+        assert!(is_scheme("svn+ssh"));
+        assert!(!is_scheme(""));
+        assert!(!is_scheme("1password"));
+        assert!(!is_scheme("my scheme"));
+    }
+
+    #[test]
+    fn test_resolve_link_handlers() {
+        let handlers = BTreeMap::from([
+            (
+                "https".to_string(),
+                LinkHandler::Browser {
+                    app: "Firefox".to_string(),
+                },
+            ),
+            ("file".to_string(), LinkHandler::Preview),
+            ("vscode".to_string(), LinkHandler::Default),
+        ]);
+        assert_eq!(
+            resolve(&handlers, "HTTPS://example.com", false),
+            Ok(LinkAction::Open {
+                app: Some("Firefox".to_string())
+            })
+        );
+        assert_eq!(
+            resolve(&handlers, "file:///tmp/report.html", false),
+            Ok(LinkAction::Preview)
+        );
+        assert_eq!(
+            resolve(&handlers, "vscode://file/src/main.rs", false),
+            Ok(LinkAction::Open { app: None })
+        );
+        assert_eq!(
+            resolve(&handlers, "mailto:me@example.com", false),
+            Ok(LinkAction::Open { app: None })
+        );
+    }
+
+    #[test]
+    fn test_unusual_schemes_need_confirmation() {
+        let handlers = BTreeMap::new();
+        assert_eq!(
+            resolve(&handlers, "ssh://prod-db", false),
+            Ok(LinkAction::Confirm("ssh".to_string()))
+        );
+        assert_eq!(
+            resolve(&handlers, "ssh://prod-db", true),
+            Ok(LinkAction::Open { app: None })
+        );
+
+        assert!(resolve(&handlers, "javascript:alert(1)", true).is_err());
+        assert!(resolve(&handlers, "Data:text/html,x", true).is_err());
+        assert!(resolve(&handlers, "/etc/hosts", true).is_err());
+        assert!(resolve(&handlers, "https://example.com\nrm -rf ~", true).is_err());
+        assert!(resolve(&handlers, "", true).is_err());
+    }
+
+    #[test]
+    fn test_open_link_result_json() {
+        let json = serde_json::to_value(OpenLinkResult::Confirm {
+            scheme: "ssh".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"action": "confirm", "scheme": "ssh"})
+        );
+        let handler: LinkHandler =
+            serde_json::from_str(r#"{"kind": "browser", "app": "Safari"}"#).unwrap();
+        assert_eq!(
+            handler,
+            LinkHandler::Browser {
+                app: "Safari".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,220 @@
+//! Inactivity lock
+//!
+//! A menubar terminal with open production shells is a liability on a shared
+//! desk. With the `inactivity_lock` setting on, the panel locks after
+//! `inactivity_lock_minutes` without input and when the Mac wakes from sleep.
+//! While locked, `lock-state-changed` tells the frontend to blank the panel
+//! and input to sessions is rejected; `unlock_app` asks for Touch ID or the
+//! account password through LocalAuthentication.
+//!
+//! Sleep is detected from the wall clock: the check thread doesn't run while
+//! the Mac sleeps, so a check that comes much later than scheduled follows a
+//! wake.
+
+use crate::events;
+use crate::i18n;
+use crate::settings::SettingsManager;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{command, AppHandle, Manager, State};
+use tracing::{info, warn};
+
+/// How often inactivity is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A check this late means the Mac slept in between
+const WAKE_THRESHOLD: Duration = Duration::from_secs(60);
+
+/// Default value of the `inactivity_lock_minutes` setting
+pub const DEFAULT_INACTIVITY_LOCK_MINUTES: u32 = 5;
+
+/// Reason shown in the Touch ID / password prompt
+#[cfg(target_os = "macos")]
+const UNLOCK_REASON: &str = "unlock your terminal sessions";
+
+/// Payload of the `lock-state-changed` event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockState {
+    pub locked: bool,
+}
+
+/// Lock state and input activity
+pub struct LockManager {
+    locked: AtomicBool,
+    last_activity: Mutex<Instant>,
+}
+
+impl Default for LockManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LockManager {
+    pub fn new() -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            last_activity: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::SeqCst)
+    }
+
+    /// Record user input
+    pub fn record_activity(&self) {
+        *self.last_activity.lock() = Instant::now();
+    }
+
+    /// Fail if the app is locked (checked before writing input to sessions)
+    pub fn ensure_unlocked(&self) -> Result<(), String> {
+        if self.is_locked() {
+            return Err(i18n::tr("app.locked", &[]));
+        }
+        Ok(())
+    }
+
+    /// Whether the app has been idle long enough to lock
+    fn idle_for(&self, timeout: Duration) -> bool {
+        self.last_activity.lock().elapsed() >= timeout
+    }
+
+    /// Change the lock state; returns true if it changed
+    fn set_locked(&self, locked: bool) -> bool {
+        self.locked.swap(locked, Ordering::SeqCst) != locked
+    }
+
+    /// Start the background inactivity and wake check
+    pub fn start(app: AppHandle) {
+        thread::spawn(move || {
+            let mut last_check = SystemTime::now();
+            loop {
+                thread::sleep(CHECK_INTERVAL);
+                let now = SystemTime::now();
+                let woke = now
+                    .duration_since(last_check)
+                    .is_ok_and(|elapsed| elapsed >= CHECK_INTERVAL + WAKE_THRESHOLD);
+                last_check = now;
+                check_lock(&app, woke);
+            }
+        });
+    }
+}
+
+fn check_lock(app: &AppHandle, woke: bool) {
+    let (Some(manager), Some(settings)) = (
+        app.try_state::<Arc<LockManager>>(),
+        app.try_state::<Arc<SettingsManager>>(),
+    ) else {
+        return;
+    };
+    let settings = settings.get();
+    if !settings.inactivity_lock || manager.is_locked() {
+        return;
+    }
+    let timeout = Duration::from_secs(u64::from(settings.inactivity_lock_minutes.max(1)) * 60);
+    if woke || manager.idle_for(timeout) {
+        info!(woke, "Locking after inactivity");
+        lock(app, &manager);
+    }
+}
+
+fn lock(app: &AppHandle, manager: &LockManager) {
+    if !cfg!(target_os = "macos") {
+        warn!("The inactivity lock requires LocalAuthentication (macOS)");
+        return;
+    }
+    if manager.set_locked(true) {
+        let _ = events::emit(app, LockState { locked: true });
+    }
+}
+
+/// Ask the user to authenticate; resolves to whether they did
+#[cfg(target_os = "macos")]
+async fn authenticate() -> Result<bool, String> {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    let sender = std::sync::Mutex::new(Some(sender));
+    let started = crate::macos::authenticate_device_owner(UNLOCK_REASON, move |success| {
+        if let Some(sender) = sender.lock().ok().and_then(|mut sender| sender.take()) {
+            let _ = sender.send(success);
+        }
+    });
+    if !started {
+        return Err("LocalAuthentication is unavailable".to_string());
+    }
+    receiver
+        .await
+        .map_err(|_| "Authentication was interrupted".to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+async fn authenticate() -> Result<bool, String> {
+    Err("Authentication requires LocalAuthentication (macOS)".to_string())
+}
+
+/// Lock the app now
+#[command]
+pub fn lock_app(app: AppHandle, lock_manager: State<'_, Arc<LockManager>>) -> Result<(), String> {
+    lock(&app, &lock_manager);
+    Ok(())
+}
+
+/// Unlock with Touch ID or the account password; returns whether the app is unlocked
+#[command]
+pub async fn unlock_app(
+    app: AppHandle,
+    lock_manager: State<'_, Arc<LockManager>>,
+) -> Result<bool, String> {
+    if !lock_manager.is_locked() {
+        return Ok(true);
+    }
+    if !authenticate().await? {
+        info!("Unlock authentication failed");
+        return Ok(false);
+    }
+    lock_manager.record_activity();
+    if lock_manager.set_locked(false) {
+        let _ = events::emit(&app, LockState { locked: false });
+    }
+    Ok(true)
+}
+
+/// Whether the app is locked
+#[command]
+pub fn get_lock_state(lock_manager: State<'_, Arc<LockManager>>) -> Result<LockState, String> {
+    Ok(LockState {
+        locked: lock_manager.is_locked(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_state() {
+        let manager = LockManager::new();
+        assert!(manager.ensure_unlocked().is_ok());
+        assert!(manager.set_locked(true));
+        assert!(!manager.set_locked(true));
+        assert!(manager.ensure_unlocked().is_err());
+        assert!(manager.set_locked(false));
+        assert!(!manager.is_locked());
+    }
+
+    #[test]
+    fn test_idle_timeout() {
+        let manager = LockManager::new();
+        assert!(!manager.idle_for(Duration::from_secs(60)));
+        assert!(manager.idle_for(Duration::ZERO));
+        *manager.last_activity.lock() = Instant::now() - Duration::from_secs(120);
+        assert!(manager.idle_for(Duration::from_secs(60)));
+        manager.record_activity();
+        assert!(!manager.idle_for(Duration::from_secs(60)));
+    }
+}
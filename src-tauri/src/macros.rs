@@ -0,0 +1,433 @@
+//! Keyboard macro recording and playback
+//!
+//! While a recording is active, every `write_to_pty` payload is appended to
+//! the macro. Finished macros are persisted to the app data directory and can
+//! be replayed into any session with a configurable inter-key delay.
+
+use crate::lock::LockManager;
+use crate::pty::PtyManager;
+use crate::unix_time;
+use crate::usage_stats::UsageStatsManager;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{command, State};
+use tracing::{debug, error, warn};
+
+/// Maximum length of a macro name
+const MAX_MACRO_NAME_LENGTH: usize = 64;
+
+/// Maximum number of recorded writes in a single macro
+const MAX_MACRO_STEPS: usize = 10_000;
+
+/// Delay between replayed writes when none is specified
+const DEFAULT_PLAYBACK_DELAY_MS: u64 = 20;
+
+/// Upper bound for the inter-key playback delay
+const MAX_PLAYBACK_DELAY_MS: u64 = 5_000;
+
+/// A named sequence of PTY writes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyboardMacro {
+    pub name: String,
+    /// Recorded writes, replayed in order
+    pub steps: Vec<String>,
+    /// Creation time in seconds since the Unix epoch
+    #[serde(default)]
+    pub created_at: u64,
+}
+
+/// In-progress recording
+struct Recording {
+    name: String,
+    steps: Vec<String>,
+}
+
+/// Validate a macro name
+fn validate_macro_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Macro name cannot be empty".to_string());
+    }
+    if name.len() > MAX_MACRO_NAME_LENGTH {
+        return Err(format!(
+            "Macro name too long: {} chars (max {})",
+            name.len(),
+            MAX_MACRO_NAME_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+/// Macro store and recorder
+pub struct MacroManager {
+    macros: Mutex<BTreeMap<String, KeyboardMacro>>,
+    recording: Mutex<Option<Recording>>,
+    macros_path: PathBuf,
+}
+
+impl MacroManager {
+    /// Create a new macro manager backed by the given file
+    pub fn new(macros_path: PathBuf) -> Self {
+        let macros = Self::load_macros(&macros_path);
+        Self {
+            macros: Mutex::new(macros),
+            recording: Mutex::new(None),
+            macros_path,
+        }
+    }
+
+    /// Load macros from disk
+    fn load_macros(path: &PathBuf) -> BTreeMap<String, KeyboardMacro> {
+        match fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(macros) => macros,
+                Err(e) => {
+                    error!("Failed to parse macros: {}", e);
+                    BTreeMap::new()
+                }
+            },
+            Err(_) => {
+                debug!("No existing macros file, starting fresh");
+                BTreeMap::new()
+            }
+        }
+    }
+
+    /// Save macros to disk
+    fn save_macros(&self) {
+        let macros = self.macros.lock();
+        match serde_json::to_string_pretty(&*macros) {
+            Ok(json) => {
+                if let Some(parent) = self.macros_path.parent() {
+                    let _ = fs::create_dir_all(parent);
+                }
+                match fs::write(&self.macros_path, json) {
+                    Ok(_) => debug!("Saved {} macros", macros.len()),
+                    Err(e) => error!("Failed to write macros: {}", e),
+                }
+            }
+            Err(e) => error!("Failed to serialize macros: {}", e),
+        }
+    }
+
+    /// Begin recording writes into a macro with the given name
+    pub fn start_recording(&self, name: &str) -> Result<(), String> {
+        validate_macro_name(name)?;
+        let mut recording = self.recording.lock();
+        if let Some(active) = recording.as_ref() {
+            return Err(format!("Already recording macro '{}'", active.name));
+        }
+        debug!(name = %name, "Started macro recording");
+        *recording = Some(Recording {
+            name: name.to_string(),
+            steps: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Append a write to the active recording (no-op when not recording)
+    pub fn record(&self, data: &str) {
+        let mut recording = self.recording.lock();
+        if let Some(active) = recording.as_mut() {
+            if active.steps.len() < MAX_MACRO_STEPS {
+                active.steps.push(data.to_string());
+            } else {
+                warn!(name = %active.name, "Macro step limit reached, ignoring input");
+            }
+        }
+    }
+
+    /// Whether a recording is in progress
+    pub fn is_recording(&self) -> bool {
+        self.recording.lock().is_some()
+    }
+
+    /// Finish the active recording and persist it, replacing any macro with the same name
+    pub fn stop_recording(&self) -> Result<KeyboardMacro, String> {
+        let recording = self
+            .recording
+            .lock()
+            .take()
+            .ok_or("No macro recording in progress")?;
+
+        let created_at = unix_time::now_secs();
+        let keyboard_macro = KeyboardMacro {
+            name: recording.name,
+            steps: recording.steps,
+            created_at,
+        };
+
+        debug!(
+            name = %keyboard_macro.name,
+            steps = keyboard_macro.steps.len(),
+            "Saved macro recording"
+        );
+        self.macros
+            .lock()
+            .insert(keyboard_macro.name.clone(), keyboard_macro.clone());
+        self.save_macros();
+        Ok(keyboard_macro)
+    }
+
+    /// Discard the active recording
+    pub fn cancel_recording(&self) {
+        self.recording.lock().take();
+    }
+
+    /// Get a macro by name
+    pub fn get(&self, name: &str) -> Option<KeyboardMacro> {
+        self.macros.lock().get(name).cloned()
+    }
+
+    /// All saved macros, sorted by name
+    pub fn list(&self) -> Vec<KeyboardMacro> {
+        self.macros.lock().values().cloned().collect()
+    }
+
+    /// Delete a macro. Returns true if it existed.
+    pub fn delete(&self, name: &str) -> bool {
+        let removed = self.macros.lock().remove(name).is_some();
+        if removed {
+            self.save_macros();
+        }
+        removed
+    }
+
+    /// Replay a macro into a session on a background thread.
+    ///
+    /// `delay_ms` is the pause between recorded writes; playback stops at the
+    /// first write error (e.g. the session was closed).
+    pub fn play(
+        &self,
+        name: &str,
+        pty_manager: Arc<PtyManager>,
+        session_id: String,
+        delay_ms: Option<u64>,
+    ) -> Result<(), String> {
+        let keyboard_macro = self
+            .get(name)
+            .ok_or_else(|| format!("Macro not found: {}", name))?;
+        let delay = Duration::from_millis(
+            delay_ms
+                .unwrap_or(DEFAULT_PLAYBACK_DELAY_MS)
+                .min(MAX_PLAYBACK_DELAY_MS),
+        );
+
+        thread::spawn(move || {
+            for (i, step) in keyboard_macro.steps.iter().enumerate() {
+                if i > 0 && !delay.is_zero() {
+                    thread::sleep(delay);
+                }
+                if let Err(e) = pty_manager.write_to_session(&session_id, step) {
+                    warn!(
+                        name = %keyboard_macro.name,
+                        session_id = %session_id,
+                        error = %e,
+                        "Stopping macro playback"
+                    );
+                    break;
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
+
+/// Start recording PTY input into a named macro
+#[command]
+pub fn start_macro_recording(
+    macro_manager: State<'_, Arc<MacroManager>>,
+    name: String,
+) -> Result<(), String> {
+    macro_manager.start_recording(&name)
+}
+
+/// Stop recording and save the macro
+#[command]
+pub fn stop_macro_recording(
+    macro_manager: State<'_, Arc<MacroManager>>,
+) -> Result<KeyboardMacro, String> {
+    macro_manager.stop_recording()
+}
+
+/// Discard the macro being recorded
+#[command]
+pub fn cancel_macro_recording(macro_manager: State<'_, Arc<MacroManager>>) -> Result<(), String> {
+    macro_manager.cancel_recording();
+    Ok(())
+}
+
+/// List saved macros
+#[command]
+pub fn list_macros(
+    macro_manager: State<'_, Arc<MacroManager>>,
+) -> Result<Vec<KeyboardMacro>, String> {
+    Ok(macro_manager.list())
+}
+
+/// Delete a saved macro
+#[command]
+pub fn delete_macro(
+    macro_manager: State<'_, Arc<MacroManager>>,
+    name: String,
+) -> Result<bool, String> {
+    Ok(macro_manager.delete(&name))
+}
+
+/// Replay a saved macro into a session
+#[command]
+pub fn play_macro(
+    macro_manager: State<'_, Arc<MacroManager>>,
+    pty_manager: State<'_, Arc<PtyManager>>,
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
+    lock_manager: State<'_, Arc<LockManager>>,
+    name: String,
+    session_id: String,
+    delay_ms: Option<u64>,
+) -> Result<(), String> {
+    lock_manager.ensure_unlocked()?;
+    macro_manager.play(&name, pty_manager.inner().clone(), session_id, delay_ms)?;
+    usage_stats.record_feature("macro_playback")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_temp_manager() -> (MacroManager, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = MacroManager::new(temp_dir.path().join("macros.json"));
+        (manager, temp_dir)
+    }
+
+    #[test]
+    fn test_validate_macro_name() {
+        assert!(validate_macro_name("deploy").is_ok());
+        assert!(validate_macro_name("").is_err());
+        assert!(validate_macro_name("   ").is_err());
+        let long_name = "a".repeat(MAX_MACRO_NAME_LENGTH + 1);
+        assert!(validate_macro_name(&long_name)
+            .unwrap_err()
+            .contains("too long"));
+    }
+
+    #[test]
+    fn test_record_and_stop() {
+        let (manager, _temp_dir) = create_temp_manager();
+
+        manager.start_recording("greet").unwrap();
+        assert!(manager.is_recording());
+        manager.record("echo ");
+        manager.record("hi\r");
+
+        let keyboard_macro = manager.stop_recording().unwrap();
+        assert!(!manager.is_recording());
+        assert_eq!(keyboard_macro.name, "greet");
+        assert_eq!(keyboard_macro.steps, vec!["echo ", "hi\r"]);
+        assert_eq!(manager.get("greet").unwrap().steps.len(), 2);
+    }
+
+    #[test]
+    fn test_record_without_recording_is_ignored() {
+        let (manager, _temp_dir) = create_temp_manager();
+        manager.record("ls\r");
+        assert!(manager.list().is_empty());
+    }
+
+    #[test]
+    fn test_start_recording_twice_fails() {
+        let (manager, _temp_dir) = create_temp_manager();
+        manager.start_recording("one").unwrap();
+        let err = manager.start_recording("two").unwrap_err();
+        assert!(err.contains("Already recording"));
+    }
+
+    #[test]
+    fn test_stop_without_recording_fails() {
+        let (manager, _temp_dir) = create_temp_manager();
+        assert!(manager.stop_recording().is_err());
+    }
+
+    #[test]
+    fn test_cancel_recording() {
+        let (manager, _temp_dir) = create_temp_manager();
+        manager.start_recording("scratch").unwrap();
+        manager.record("x");
+        manager.cancel_recording();
+        assert!(!manager.is_recording());
+        assert!(manager.get("scratch").is_none());
+    }
+
+    #[test]
+    fn test_step_limit() {
+        let (manager, _temp_dir) = create_temp_manager();
+        manager.start_recording("big").unwrap();
+        for _ in 0..MAX_MACRO_STEPS + 10 {
+            manager.record("a");
+        }
+        let keyboard_macro = manager.stop_recording().unwrap();
+        assert_eq!(keyboard_macro.steps.len(), MAX_MACRO_STEPS);
+    }
+
+    #[test]
+    fn test_list_sorted_and_delete() {
+        let (manager, _temp_dir) = create_temp_manager();
+        for name in ["zeta", "alpha"] {
+            manager.start_recording(name).unwrap();
+            manager.stop_recording().unwrap();
+        }
+
+        let names: Vec<String> = manager.list().into_iter().map(|m| m.name).collect();
+        assert_eq!(names, vec!["alpha", "zeta"]);
+
+        assert!(manager.delete("alpha"));
+        assert!(!manager.delete("alpha"));
+        assert_eq!(manager.list().len(), 1);
+    }
+
+    #[test]
+    fn test_persistence() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("macros.json");
+
+        {
+            let manager = MacroManager::new(path.clone());
+            manager.start_recording("build").unwrap();
+            manager.record("cargo build\r");
+            manager.stop_recording().unwrap();
+        }
+
+        let manager = MacroManager::new(path);
+        let keyboard_macro = manager.get("build").unwrap();
+        assert_eq!(keyboard_macro.steps, vec!["cargo build\r"]);
+    }
+
+    #[test]
+    fn test_load_invalid_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("macros.json");
+        fs::write(&path, "invalid json").unwrap();
+
+        let manager = MacroManager::new(path);
+        assert!(manager.list().is_empty());
+    }
+
+    #[test]
+    fn test_play_unknown_macro() {
+        let (manager, _temp_dir) = create_temp_manager();
+        let result = manager.play(
+            "missing",
+            Arc::new(PtyManager::new()),
+            "session".to_string(),
+            None,
+        );
+        assert!(result.unwrap_err().contains("Macro not found"));
+    }
+}
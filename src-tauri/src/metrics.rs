@@ -0,0 +1,352 @@
+//! Internal metrics
+//!
+//! Counters for debugging performance reports from different hardware:
+//! sessions, PTY bytes, events emitted to the frontend (counted by
+//! `events::emit`) and how long IPC commands take to handle. `get_metrics`
+//! returns a snapshot, and the status endpoint (see `status_server.rs`) serves
+//! it at `/metrics` in the Prometheus text format.
+
+use crate::pty::PtyManager;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::{command, State};
+
+/// Upper bounds of the command duration buckets, in seconds
+const DURATION_BUCKETS: [f64; 12] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+/// Durations of one command
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    /// Observations per bucket (not cumulative); the last is `+Inf`
+    counts: [u64; DURATION_BUCKETS.len() + 1],
+    sum_secs: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, secs: f64) {
+        let bucket = DURATION_BUCKETS
+            .iter()
+            .position(|bound| secs <= *bound)
+            .unwrap_or(DURATION_BUCKETS.len());
+        self.counts[bucket] += 1;
+        self.sum_secs += secs;
+    }
+
+    fn snapshot(&self, command: &str) -> CommandDurations {
+        let mut cumulative = 0;
+        let buckets = DURATION_BUCKETS
+            .iter()
+            .zip(&self.counts)
+            .map(|(le, count)| {
+                cumulative += count;
+                DurationBucket {
+                    le: *le,
+                    count: cumulative,
+                }
+            })
+            .collect();
+        CommandDurations {
+            command: command.to_string(),
+            buckets,
+            count: self.counts.iter().sum(),
+            sum_secs: self.sum_secs,
+        }
+    }
+}
+
+/// Cumulative count of observations up to `le` seconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DurationBucket {
+    pub le: f64,
+    pub count: u64,
+}
+
+/// Handling time histogram of one IPC command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandDurations {
+    pub command: String,
+    pub buckets: Vec<DurationBucket>,
+    pub count: u64,
+    pub sum_secs: f64,
+}
+
+/// Values of all metrics at one point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    /// Live sessions
+    pub sessions: usize,
+    pub sessions_created: u64,
+    pub pty_bytes_read: u64,
+    pub pty_bytes_written: u64,
+    /// Events emitted to the frontend, by event name
+    pub events_emitted: BTreeMap<String, u64>,
+    pub command_durations: Vec<CommandDurations>,
+}
+
+impl MetricsSnapshot {
+    /// Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            out.push_str(&value);
+        };
+        metric(
+            "microterm_sessions",
+            "gauge",
+            "Live terminal sessions",
+            format!("microterm_sessions {}\n", self.sessions),
+        );
+        metric(
+            "microterm_sessions_created_total",
+            "counter",
+            "Terminal sessions created",
+            format!(
+                "microterm_sessions_created_total {}\n",
+                self.sessions_created
+            ),
+        );
+        metric(
+            "microterm_pty_bytes_read_total",
+            "counter",
+            "Bytes read from PTYs",
+            format!("microterm_pty_bytes_read_total {}\n", self.pty_bytes_read),
+        );
+        metric(
+            "microterm_pty_bytes_written_total",
+            "counter",
+            "Bytes written to PTYs",
+            format!(
+                "microterm_pty_bytes_written_total {}\n",
+                self.pty_bytes_written
+            ),
+        );
+        metric(
+            "microterm_events_emitted_total",
+            "counter",
+            "Events emitted to the frontend",
+            self.events_emitted
+                .iter()
+                .map(|(event, count)| {
+                    format!(
+                        "microterm_events_emitted_total{{event=\"{}\"}} {}\n",
+                        event, count
+                    )
+                })
+                .collect(),
+        );
+        let mut durations = String::new();
+        for command in &self.command_durations {
+            let name = "microterm_command_duration_seconds";
+            for bucket in &command.buckets {
+                let _ = writeln!(
+                    durations,
+                    "{}_bucket{{command=\"{}\",le=\"{}\"}} {}",
+                    name, command.command, bucket.le, bucket.count
+                );
+            }
+            let _ = writeln!(
+                durations,
+                "{}_bucket{{command=\"{}\",le=\"+Inf\"}} {}",
+                name, command.command, command.count
+            );
+            let _ = writeln!(
+                durations,
+                "{}_sum{{command=\"{}\"}} {}",
+                name, command.command, command.sum_secs
+            );
+            let _ = writeln!(
+                durations,
+                "{}_count{{command=\"{}\"}} {}",
+                name, command.command, command.count
+            );
+        }
+        metric(
+            "microterm_command_duration_seconds",
+            "histogram",
+            "Time spent handling IPC commands",
+            durations,
+        );
+        out
+    }
+}
+
+/// Counters and histograms, updated from anywhere in the backend
+struct Metrics {
+    sessions_created: AtomicU64,
+    pty_bytes_read: AtomicU64,
+    pty_bytes_written: AtomicU64,
+    events_emitted: Mutex<BTreeMap<&'static str, u64>>,
+    command_durations: Mutex<BTreeMap<&'static str, Histogram>>,
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Self {
+            sessions_created: AtomicU64::new(0),
+            pty_bytes_read: AtomicU64::new(0),
+            pty_bytes_written: AtomicU64::new(0),
+            events_emitted: Mutex::new(BTreeMap::new()),
+            command_durations: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn record_event(&self, event: &'static str) {
+        *self.events_emitted.lock().entry(event).or_default() += 1;
+    }
+
+    fn observe_command(&self, command: &'static str, secs: f64) {
+        self.command_durations
+            .lock()
+            .entry(command)
+            .or_default()
+            .observe(secs);
+    }
+
+    fn snapshot(&self, sessions: usize) -> MetricsSnapshot {
+        MetricsSnapshot {
+            sessions,
+            sessions_created: self.sessions_created.load(Ordering::Relaxed),
+            pty_bytes_read: self.pty_bytes_read.load(Ordering::Relaxed),
+            pty_bytes_written: self.pty_bytes_written.load(Ordering::Relaxed),
+            events_emitted: self
+                .events_emitted
+                .lock()
+                .iter()
+                .map(|(event, count)| (event.to_string(), *count))
+                .collect(),
+            command_durations: self
+                .command_durations
+                .lock()
+                .iter()
+                .map(|(command, histogram)| histogram.snapshot(command))
+                .collect(),
+        }
+    }
+}
+
+static METRICS: Metrics = Metrics::new();
+
+pub fn record_session_created() {
+    METRICS.sessions_created.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_bytes_read(bytes: usize) {
+    METRICS
+        .pty_bytes_read
+        .fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+pub fn record_bytes_written(bytes: usize) {
+    METRICS
+        .pty_bytes_written
+        .fetch_add(bytes as u64, Ordering::Relaxed);
+}
+
+/// Count an event emitted to the frontend
+pub fn record_event(event: &'static str) {
+    METRICS.record_event(event);
+}
+
+/// Records how long a command took when dropped
+pub struct CommandTimer {
+    command: &'static str,
+    started: Instant,
+}
+
+impl Drop for CommandTimer {
+    fn drop(&mut self) {
+        METRICS.observe_command(self.command, self.started.elapsed().as_secs_f64());
+    }
+}
+
+/// Time a command handler: `let _timer = metrics::time_command("write_to_pty");`
+pub fn time_command(command: &'static str) -> CommandTimer {
+    CommandTimer {
+        command,
+        started: Instant::now(),
+    }
+}
+
+/// Current values of all metrics
+pub fn snapshot(sessions: usize) -> MetricsSnapshot {
+    METRICS.snapshot(sessions)
+}
+
+/// Get a snapshot of the internal metrics
+#[command]
+pub fn get_metrics(pty_manager: State<'_, Arc<PtyManager>>) -> Result<MetricsSnapshot, String> {
+    Ok(snapshot(pty_manager.session_count()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let mut histogram = Histogram::default();
+        histogram.observe(0.0002);
+        histogram.observe(0.003);
+        histogram.observe(0.003);
+        histogram.observe(10.0);
+        let snapshot = histogram.snapshot("write_to_pty");
+        assert_eq!(snapshot.count, 4);
+        assert_eq!(snapshot.buckets[0].count, 1);
+        assert_eq!(snapshot.buckets[2].count, 1);
+        assert_eq!(snapshot.buckets[3].count, 3);
+        assert_eq!(snapshot.buckets.last().unwrap().count, 3);
+        assert!((snapshot.sum_secs - 10.0062).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let metrics = Metrics::new();
+        metrics.sessions_created.fetch_add(2, Ordering::Relaxed);
+        metrics.record_event("pty-output");
+        metrics.record_event("pty-output");
+        metrics.record_event("pty-exit");
+        metrics.observe_command("resize_pty", 0.01);
+
+        let snapshot = metrics.snapshot(1);
+        assert_eq!(snapshot.sessions, 1);
+        assert_eq!(snapshot.sessions_created, 2);
+        assert_eq!(snapshot.events_emitted["pty-output"], 2);
+        assert_eq!(snapshot.events_emitted["pty-exit"], 1);
+        assert_eq!(snapshot.command_durations.len(), 1);
+        assert_eq!(snapshot.command_durations[0].command, "resize_pty");
+    }
+
+    #[test]
+    fn test_to_prometheus() {
+        let metrics = Metrics::new();
+        metrics.pty_bytes_read.fetch_add(4096, Ordering::Relaxed);
+        metrics.record_event("pty-output");
+        metrics.observe_command("write_to_pty", 0.002);
+        let text = metrics.snapshot(3).to_prometheus();
+
+        assert!(text.contains("# TYPE microterm_sessions gauge\nmicroterm_sessions 3\n"));
+        assert!(text.contains("microterm_pty_bytes_read_total 4096\n"));
+        assert!(text.contains("microterm_events_emitted_total{event=\"pty-output\"} 1\n"));
+        assert!(text.contains(
+            "microterm_command_duration_seconds_bucket{command=\"write_to_pty\",le=\"0.001\"} 0\n"
+        ));
+        assert!(text.contains(
+            "microterm_command_duration_seconds_bucket{command=\"write_to_pty\",le=\"0.0025\"} 1\n"
+        ));
+        assert!(text.contains(
+            "microterm_command_duration_seconds_bucket{command=\"write_to_pty\",le=\"+Inf\"} 1\n"
+        ));
+        assert!(
+            text.contains("microterm_command_duration_seconds_count{command=\"write_to_pty\"} 1\n")
+        );
+    }
+}
@@ -0,0 +1,494 @@
+//! Terminal-initiated desktop notifications
+//!
+//! Programs can post a notification with OSC 9 or OSC 777, which unlike
+//! `osascript` or `notify-send` works over ssh; the bell can notify too, and
+//! `quiet_hours` silence both.
+
+use crate::events;
+use crate::settings::SettingsManager;
+use crate::status_strip;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+use tracing::{debug, warn};
+
+/// Title used when the sequence doesn't provide one
+const DEFAULT_TITLE: &str = "µTerm";
+
+/// Maximum title length in characters
+const MAX_TITLE_LENGTH: usize = 256;
+
+/// Maximum body length in characters
+const MAX_BODY_LENGTH: usize = 1024;
+
+/// Minimum time between notifications from the same session
+const MIN_NOTIFICATION_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Minimum time between bells reported for the same session
+const MIN_BELL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A notification requested by a program running in a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TerminalNotification {
+    pub title: String,
+    pub body: String,
+}
+
+/// Payload of the `pty-notification` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PtyNotification {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub notification: TerminalNotification,
+}
+
+/// Payload of the `pty-bell` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PtyBell {
+    pub session_id: String,
+}
+
+/// A daily window during which bells and notifications are silenced. Times
+/// are local `HH:MM`; an `end` before `start` spans midnight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+/// Minutes after midnight of an `HH:MM` time
+pub fn parse_time(time: &str) -> Option<u16> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    if hours.is_empty() || minutes.len() != 2 {
+        return None;
+    }
+    let hours: u16 = hours.parse().ok()?;
+    let minutes: u16 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+impl QuietHours {
+    pub fn is_valid(&self) -> bool {
+        parse_time(&self.start).is_some() && parse_time(&self.end).is_some()
+    }
+
+    /// Whether a time, in minutes after midnight, is in the window. A window
+    /// starting and ending at the same time is empty.
+    pub fn contains(&self, minutes: u16) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        if start <= end {
+            (start..end).contains(&minutes)
+        } else {
+            minutes >= start || minutes < end
+        }
+    }
+}
+
+/// Minutes after midnight, local time
+fn local_minutes() -> Option<u16> {
+    // SAFETY: localtime_r only writes to the tm it's given
+    unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&now, &mut tm).is_null() {
+            return None;
+        }
+        Some((tm.tm_hour * 60 + tm.tm_min) as u16)
+    }
+}
+
+/// Whether `quiet_hours` are set and it's within them now
+pub fn in_quiet_hours(app: &AppHandle) -> bool {
+    let Some(quiet_hours) = app
+        .try_state::<Arc<SettingsManager>>()
+        .and_then(|settings| settings.get().quiet_hours)
+    else {
+        return false;
+    };
+    local_minutes().is_some_and(|minutes| quiet_hours.contains(minutes))
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
+}
+
+fn notification(title: &str, body: &str) -> Option<TerminalNotification> {
+    let title = title.trim();
+    let body = body.trim();
+    if title.is_empty() && body.is_empty() {
+        return None;
+    }
+    Some(TerminalNotification {
+        title: truncate(
+            if title.is_empty() {
+                DEFAULT_TITLE
+            } else {
+                title
+            },
+            MAX_TITLE_LENGTH,
+        ),
+        body: truncate(body, MAX_BODY_LENGTH),
+    })
+}
+
+/// Parse the data of an OSC 9 sequence (iTerm2 style: `OSC 9 ; body ST`) as
+/// a notification.
+///
+/// ConEmu uses numeric sub-commands (`9;4;...` is progress), so data whose
+/// first field is a number is not treated as a notification.
+pub fn parse_osc9(data: &str) -> Option<TerminalNotification> {
+    let first = data.split(';').next().unwrap_or_default();
+    if !first.is_empty() && first.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    notification("", data)
+}
+
+/// Parse the data of an OSC 777 sequence (rxvt style: `notify;title;body`)
+pub fn parse_osc777(data: &str) -> Option<TerminalNotification> {
+    let mut parts = data.splitn(3, ';');
+    if parts.next()? != "notify" {
+        return None;
+    }
+    let title = parts.next().unwrap_or_default();
+    let body = parts.next().unwrap_or_default();
+    notification(title, body)
+}
+
+/// Per-session notification permissions and rate limiting
+pub struct NotificationManager {
+    /// Sessions whose permission differs from the global setting
+    overrides: Mutex<HashMap<String, bool>>,
+    last_posted: Mutex<HashMap<String, Instant>>,
+    last_bell: Mutex<HashMap<String, Instant>>,
+}
+
+impl Default for NotificationManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NotificationManager {
+    pub fn new() -> Self {
+        Self {
+            overrides: Mutex::new(HashMap::new()),
+            last_posted: Mutex::new(HashMap::new()),
+            last_bell: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set a session's permission; `None` falls back to the global setting
+    pub fn set_session_permission(&self, session_id: &str, allowed: Option<bool>) {
+        let mut overrides = self.overrides.lock();
+        match allowed {
+            Some(allowed) => {
+                overrides.insert(session_id.to_string(), allowed);
+            }
+            None => {
+                overrides.remove(session_id);
+            }
+        }
+    }
+
+    /// Whether a session may post notifications
+    pub fn is_allowed(&self, session_id: &str, default_allowed: bool) -> bool {
+        self.overrides
+            .lock()
+            .get(session_id)
+            .copied()
+            .unwrap_or(default_allowed)
+    }
+
+    /// Record a notification attempt, returning false if it is rate limited
+    pub fn check_rate_limit(&self, session_id: &str) -> bool {
+        check_interval(&self.last_posted, session_id, MIN_NOTIFICATION_INTERVAL)
+    }
+
+    /// Record a bell, returning false if it is rate limited
+    pub fn check_bell_rate_limit(&self, session_id: &str) -> bool {
+        check_interval(&self.last_bell, session_id, MIN_BELL_INTERVAL)
+    }
+
+    /// Forget a session's state (e.g. when it exits)
+    pub fn remove_session(&self, session_id: &str) {
+        self.overrides.lock().remove(session_id);
+        self.last_posted.lock().remove(session_id);
+        self.last_bell.lock().remove(session_id);
+    }
+}
+
+/// Record an occurrence for a session, returning false if the previous one
+/// was less than `interval` ago
+fn check_interval(
+    last: &Mutex<HashMap<String, Instant>>,
+    session_id: &str,
+    interval: Duration,
+) -> bool {
+    let now = Instant::now();
+    let mut last = last.lock();
+    match last.get(session_id) {
+        Some(previous) if now.duration_since(*previous) < interval => false,
+        _ => {
+            last.insert(session_id.to_string(), now);
+            true
+        }
+    }
+}
+
+/// Post a system notification
+fn show(app: &AppHandle, session_id: &str, notification: &TerminalNotification) {
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title(&notification.title)
+        .body(&notification.body)
+        .show()
+    {
+        warn!(session_id = %session_id, error = %e, "Failed to post notification");
+    }
+}
+
+/// Post a notification requested by a session, if permitted by
+/// `terminal_notifications` or the session's override
+///
+/// In quiet hours it only reaches the frontend as `pty-notification`, without
+/// a system notification.
+pub fn post_notification(app: &AppHandle, session_id: &str, notification: TerminalNotification) {
+    let default_allowed = app
+        .try_state::<Arc<SettingsManager>>()
+        .is_some_and(|settings| settings.get().terminal_notifications);
+    let Some(manager) = app.try_state::<Arc<NotificationManager>>() else {
+        return;
+    };
+    if !manager.is_allowed(session_id, default_allowed) {
+        debug!(session_id = %session_id, "Terminal notification blocked by permission");
+        return;
+    }
+    if !manager.check_rate_limit(session_id) {
+        debug!(session_id = %session_id, "Terminal notification rate limited");
+        return;
+    }
+
+    // Only post a system notification when the user isn't looking at the terminal
+    let window_focused = app
+        .get_webview_window("main")
+        .and_then(|w| w.is_focused().ok())
+        .unwrap_or(false);
+    if in_quiet_hours(app) {
+        debug!(session_id = %session_id, "Terminal notification not posted in quiet hours");
+    } else if !window_focused {
+        show(app, session_id, &notification);
+    }
+
+    let _ = events::emit(
+        app,
+        PtyNotification {
+            session_id: session_id.to_string(),
+            notification,
+        },
+    );
+}
+
+/// Handle a bell from a session whose title is `title`, if known
+///
+/// Emits `pty-bell`, at most once a second per session, and with
+/// `bell_notifications` also posts a notification while the panel is hidden, so
+/// a ringing IRC client or finished build isn't missed. In quiet hours the bell
+/// is dropped entirely, status strip badge included.
+pub fn ring_bell(app: &AppHandle, session_id: &str, title: Option<&str>) {
+    if in_quiet_hours(app) {
+        return;
+    }
+    status_strip::bell(app, session_id);
+    let Some(manager) = app.try_state::<Arc<NotificationManager>>() else {
+        return;
+    };
+    if !manager.check_bell_rate_limit(session_id) {
+        return;
+    }
+
+    let bell_notifications = app
+        .try_state::<Arc<SettingsManager>>()
+        .is_some_and(|settings| settings.get().bell_notifications);
+    let panel_visible = app
+        .get_webview_window("main")
+        .and_then(|w| w.is_visible().ok())
+        .unwrap_or(false);
+    if bell_notifications && !panel_visible {
+        let body = match title {
+            Some(title) => format!("Bell in {}", title),
+            None => "Bell".to_string(),
+        };
+        if let Some(notification) = notification("", &body) {
+            show(app, session_id, &notification);
+        }
+    }
+
+    let _ = events::emit(
+        app,
+        PtyBell {
+            session_id: session_id.to_string(),
+        },
+    );
+}
+
+/// Allow or block notifications from a session (`None` uses the global setting)
+#[command]
+pub fn set_session_notifications(
+    notification_manager: State<'_, Arc<NotificationManager>>,
+    session_id: String,
+    allowed: Option<bool>,
+) -> Result<(), String> {
+    notification_manager.set_session_permission(&session_id, allowed);
+    Ok(())
+}
+
+/// Whether a session may post notifications
+#[command]
+pub fn get_session_notifications(
+    notification_manager: State<'_, Arc<NotificationManager>>,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    session_id: String,
+) -> Result<bool, String> {
+    Ok(notification_manager.is_allowed(&session_id, settings_manager.get().terminal_notifications))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_osc9_body() {
+        assert_eq!(
+            parse_osc9("Build finished"),
+            Some(TerminalNotification {
+                title: DEFAULT_TITLE.to_string(),
+                body: "Build finished".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_osc9_keeps_semicolons_in_body() {
+        let parsed = parse_osc9("done; 3 warnings").unwrap();
+        assert_eq!(parsed.body, "done; 3 warnings");
+    }
+
+    #[test]
+    fn test_parse_osc9_ignores_conemu_commands() {
+        assert_eq!(parse_osc9("4;1;50"), None);
+        assert_eq!(parse_osc9("9"), None);
+        assert_eq!(parse_osc9(""), None);
+    }
+
+    #[test]
+    fn test_parse_osc777() {
+        assert_eq!(
+            parse_osc777("notify;Deploy;Finished in 3m; all green"),
+            Some(TerminalNotification {
+                title: "Deploy".to_string(),
+                body: "Finished in 3m; all green".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_osc777("notify;;body only").unwrap().title,
+            DEFAULT_TITLE
+        );
+        assert_eq!(parse_osc777("preexec;x"), None);
+        assert_eq!(parse_osc777("notify;;"), None);
+    }
+
+    #[test]
+    fn test_long_text_truncated() {
+        let parsed = parse_osc9(&"é".repeat(MAX_BODY_LENGTH + 10)).unwrap();
+        assert_eq!(parsed.body.chars().count(), MAX_BODY_LENGTH);
+    }
+
+    #[test]
+    fn test_session_permission_override() {
+        let manager = NotificationManager::new();
+        assert!(manager.is_allowed("a", true));
+        assert!(!manager.is_allowed("a", false));
+
+        manager.set_session_permission("a", Some(false));
+        assert!(!manager.is_allowed("a", true));
+        assert!(manager.is_allowed("b", true));
+
+        manager.set_session_permission("a", None);
+        assert!(manager.is_allowed("a", true));
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let manager = NotificationManager::new();
+        assert!(manager.check_rate_limit("a"));
+        assert!(!manager.check_rate_limit("a"));
+        assert!(manager.check_rate_limit("b"));
+
+        manager.remove_session("a");
+        assert!(manager.check_rate_limit("a"));
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("22:00"), Some(22 * 60));
+        assert_eq!(parse_time("8:05"), Some(8 * 60 + 5));
+        assert_eq!(parse_time(" 00:00 "), Some(0));
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("12:60"), None);
+        assert_eq!(parse_time("12:5"), None);
+        assert_eq!(parse_time("noon"), None);
+    }
+
+    #[test]
+    fn test_quiet_hours_contains() {
+        let night = QuietHours {
+            start: "22:00".to_string(),
+            end: "08:00".to_string(),
+        };
+        assert!(night.contains(23 * 60));
+        assert!(night.contains(0));
+        assert!(night.contains(7 * 60 + 59));
+        assert!(!night.contains(8 * 60));
+        assert!(!night.contains(12 * 60));
+
+        let lunch = QuietHours {
+            start: "12:00".to_string(),
+            end: "13:00".to_string(),
+        };
+        assert!(lunch.contains(12 * 60 + 30));
+        assert!(!lunch.contains(13 * 60));
+
+        let empty = QuietHours {
+            start: "09:00".to_string(),
+            end: "09:00".to_string(),
+        };
+        assert!(!empty.contains(9 * 60));
+
+        let invalid = QuietHours {
+            start: "late".to_string(),
+            end: "08:00".to_string(),
+        };
+        assert!(!invalid.is_valid());
+        assert!(!invalid.contains(0));
+    }
+
+    #[test]
+    fn test_bell_rate_limit_is_separate() {
+        let manager = NotificationManager::new();
+        assert!(manager.check_rate_limit("a"));
+        assert!(manager.check_bell_rate_limit("a"));
+        assert!(!manager.check_bell_rate_limit("a"));
+        assert!(manager.check_bell_rate_limit("b"));
+
+        manager.remove_session("a");
+        assert!(manager.check_bell_rate_limit("a"));
+    }
+}
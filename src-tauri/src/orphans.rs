@@ -0,0 +1,69 @@
+//! Orphaned session cleanup
+//!
+//! A session is attached while a terminal pane shows it. When its window's
+//! page (re)loads, as after a webview reload, the window's sessions lose their
+//! panes until a terminal resizes them again. A detached session whose shell
+//! has exited would otherwise stay in the sessions map for good: nothing
+//! closes it, and a terminal held open by a background process never reads
+//! EOF. The reaper checks detached sessions every `CHECK_INTERVAL`, emits
+//! `pty-orphaned` when it finds an exited one, and removes it `ORPHAN_GRACE`
+//! later unless a pane picked it up in the meantime.
+
+use crate::events;
+use crate::pty::PtyManager;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+/// How often detached sessions are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long an exited orphan is kept for a pane to pick it up
+pub const ORPHAN_GRACE: Duration = Duration::from_secs(60);
+
+/// Payload of the `pty-orphaned` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PtyOrphaned {
+    pub session_id: String,
+    /// Label of the window the session belonged to
+    pub window: String,
+    pub exit_code: i32,
+    /// Signal that killed the shell, such as `SIGKILL`
+    pub signal: Option<String>,
+}
+
+/// Result of one `PtyManager::sweep_orphans` pass
+#[derive(Debug, Default)]
+pub struct OrphanSweep {
+    /// Exited orphans found in this pass
+    pub found: Vec<PtyOrphaned>,
+    /// Orphans past the grace period
+    pub expired: Vec<String>,
+}
+
+/// Start the background reaper
+pub fn start(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(CHECK_INTERVAL);
+        reap(&app);
+    });
+}
+
+fn reap(app: &AppHandle) {
+    let Some(pty_manager) = app.try_state::<Arc<PtyManager>>() else {
+        return;
+    };
+    let sweep = pty_manager.sweep_orphans(ORPHAN_GRACE);
+    for orphan in sweep.found {
+        info!(session_id = %orphan.session_id, exit_code = orphan.exit_code, "Session orphaned");
+        let _ = events::emit(app, orphan);
+    }
+    for session_id in sweep.expired {
+        if pty_manager.remove_exited_session(&session_id) {
+            info!(session_id = %session_id, "Removed orphaned session");
+        }
+    }
+}
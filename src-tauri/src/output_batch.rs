@@ -0,0 +1,802 @@
+//! PTY output batching
+//!
+//! Joins the reader thread's chunks into frames, so a flood such as
+//! `cat largefile` isn't one `pty-output` event per read, and optionally
+//! rate-limits what reaches the webview.
+
+use crate::activity::{Activity, ActivityTracker};
+use crate::vt_parser::VtScanner;
+use std::collections::BTreeMap;
+use std::iter;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Default `output_frame_interval_ms`
+pub const DEFAULT_FRAME_INTERVAL_MS: u64 = 8;
+
+/// Longest allowed `output_frame_interval_ms`; 0 emits every read on its own
+pub const MAX_FRAME_INTERVAL_MS: u64 = 50;
+
+/// A pause this long ends the frame, so interactive output isn't delayed
+const IDLE_FLUSH: Duration = Duration::from_millis(2);
+
+/// Frames are emitted once they reach this size
+const MAX_FRAME_BYTES: usize = 256 * 1024;
+
+/// Chunks queued before the reader blocks while frames are being built
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Default `output_rate_limit_kib`
+pub const DEFAULT_RATE_LIMIT_KIB: u64 = 4096;
+
+/// Lowest `output_rate_limit_kib` other than 0 (no limit)
+pub const MIN_RATE_LIMIT_KIB: u64 = 64;
+
+/// Period the rate limit applies to
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// How much of the dropped output is emitted once the rate window ends
+const TAIL_BYTES: usize = 4 * 1024;
+
+/// Longer sequences aren't checked for mode changes
+const MAX_MODE_SEQUENCE: usize = 64;
+
+/// Private modes tracked; further ones are ignored
+const MAX_PRIVATE_MODES: usize = 64;
+
+/// SGR sequences kept since the last reset
+const MAX_SGR_SEQUENCES: usize = 16;
+
+/// Private modes switching to the alternate screen
+const ALT_SCREEN_MODES: [u16; 3] = [47, 1047, 1049];
+
+/// What the reader hands to the batcher
+enum Item {
+    Output(String),
+    /// Runs once the output queued before it has been emitted
+    Then(Box<dyn FnOnce() + Send>),
+}
+
+/// Activity tracking of a batcher, with what to call on each change
+struct ActivityWatch {
+    tracker: ActivityTracker,
+    notify: Box<dyn FnMut(Activity) + Send>,
+}
+
+/// Rate limiting of a batcher, with what to call when output was dropped
+struct RateLimit {
+    limiter: RateLimiter,
+    notify: Box<dyn FnMut(usize) + Send>,
+}
+
+/// Optional extras of an `OutputBatcher`
+#[derive(Default)]
+pub struct BatcherHooks {
+    activity: Option<ActivityWatch>,
+    rate_limit: Option<RateLimit>,
+}
+
+impl BatcherHooks {
+    /// Call `notify` when output starts after a quiet spell and when it stops
+    /// for the tracker's idle time
+    pub fn activity<A>(mut self, tracker: ActivityTracker, notify: A) -> Self
+    where
+        A: FnMut(Activity) + Send + 'static,
+    {
+        self.activity = Some(ActivityWatch {
+            tracker,
+            notify: Box::new(notify),
+        });
+        self
+    }
+
+    /// Emit at most `bytes_per_second`, calling `notify` with the number of
+    /// bytes dropped each second output was over the limit
+    pub fn rate_limit<T>(mut self, bytes_per_second: usize, notify: T) -> Self
+    where
+        T: FnMut(usize) + Send + 'static,
+    {
+        self.rate_limit = Some(RateLimit {
+            limiter: RateLimiter::new(bytes_per_second, Instant::now()),
+            notify: Box::new(notify),
+        });
+        self
+    }
+}
+
+/// Output dropped during a rate window
+#[derive(Debug, PartialEq, Eq)]
+struct Dropped {
+    bytes: usize,
+    /// End of the dropped output, starting at a line when there is one, after
+    /// the sequences restoring the modes the rest of it changed
+    tail: String,
+}
+
+/// Terminal modes set by escape sequences
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Modes {
+    /// Mode the alternate screen was entered with
+    alt_screen: Option<u16>,
+    /// Other DECSET/DECRST modes
+    private: BTreeMap<u16, bool>,
+    /// SGR parameters since the last reset
+    sgr: Vec<String>,
+}
+
+impl Modes {
+    /// Apply a complete escape sequence
+    fn apply(&mut self, sequence: &str) {
+        let Some(body) = sequence
+            .strip_prefix("\x1b[")
+            .or_else(|| sequence.strip_prefix('\u{9b}'))
+        else {
+            return;
+        };
+        let Some(final_char) = body.chars().last() else {
+            return;
+        };
+        let params = &body[..body.len() - final_char.len_utf8()];
+        match final_char {
+            'm' if params
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == ';' || c == ':') =>
+            {
+                self.set_sgr(params)
+            }
+            'h' | 'l' => {
+                let Some(modes) = params.strip_prefix('?') else {
+                    return;
+                };
+                for mode in modes.split(';').filter_map(|mode| mode.parse().ok()) {
+                    self.set_private(mode, final_char == 'h');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn set_private(&mut self, mode: u16, set: bool) {
+        if ALT_SCREEN_MODES.contains(&mode) {
+            self.alt_screen = set.then_some(mode);
+        } else if self.private.len() < MAX_PRIVATE_MODES || self.private.contains_key(&mode) {
+            self.private.insert(mode, set);
+        }
+    }
+
+    fn set_sgr(&mut self, params: &str) {
+        let first = params.split([';', ':']).next().unwrap_or_default();
+        if first.is_empty() || first == "0" {
+            self.sgr.clear();
+            if params.len() <= 1 {
+                return;
+            }
+        }
+        if self.sgr.len() == MAX_SGR_SEQUENCES {
+            self.sgr.remove(0);
+        }
+        self.sgr.push(params.to_string());
+    }
+
+    /// Sequences taking a terminal in the `shown` modes to these ones
+    fn restore(&self, shown: &Modes) -> String {
+        let mut sequences = String::new();
+        match (shown.alt_screen, self.alt_screen) {
+            (None, Some(mode)) => sequences.push_str(&format!("\x1b[?{}h", mode)),
+            (Some(mode), None) => sequences.push_str(&format!("\x1b[?{}l", mode)),
+            _ => {}
+        }
+        for (mode, &set) in &self.private {
+            if shown.private.get(mode) != Some(&set) {
+                let final_char = if set { 'h' } else { 'l' };
+                sequences.push_str(&format!("\x1b[?{}{}", mode, final_char));
+            }
+        }
+        if self.sgr != shown.sgr {
+            sequences.push_str("\x1b[m");
+            for params in &self.sgr {
+                sequences.push_str(&format!("\x1b[{}m", params));
+            }
+        }
+        sequences
+    }
+}
+
+/// Follows escape sequences through a stream of output, keeping the modes
+/// they set
+struct ModeTracker {
+    scanner: VtScanner,
+    /// Start of the sequence being scanned, up to `MAX_MODE_SEQUENCE`
+    sequence: String,
+    modes: Modes,
+}
+
+impl ModeTracker {
+    fn new() -> Self {
+        Self {
+            scanner: VtScanner::boundaries_only(),
+            sequence: String::new(),
+            modes: Modes::default(),
+        }
+    }
+
+    /// Continue the stream with `text`
+    fn scan(&mut self, text: &str) {
+        for c in text.chars() {
+            let in_sequence = !self.scanner.at_ground();
+            self.scanner.advance(c);
+            if !in_sequence && self.scanner.at_ground() {
+                continue;
+            }
+            if !in_sequence || c == '\x1b' {
+                self.sequence.clear();
+            }
+            if self.sequence.len() < MAX_MODE_SEQUENCE {
+                self.sequence.push(c);
+            }
+            if self.scanner.at_ground() {
+                self.modes.apply(&self.sequence);
+            }
+        }
+    }
+
+    /// Offsets in `text` (including its end) that aren't inside an escape
+    /// sequence, if the stream continued with it
+    fn boundaries<'a>(&self, text: &'a str) -> impl Iterator<Item = usize> + 'a {
+        let mut scanner = self.scanner.clone();
+        text.char_indices()
+            .map(|(i, c)| (i, Some(c)))
+            .chain(iter::once((text.len(), None)))
+            .filter_map(move |(i, c)| {
+                let boundary = scanner.at_ground().then_some(i);
+                if let Some(c) = c {
+                    scanner.advance(c);
+                }
+                boundary
+            })
+    }
+}
+
+/// Budget of output bytes per `RATE_WINDOW`
+///
+/// Emitting never blocks, so without a limit a program like `yes` fills the
+/// webview's event queue faster than it renders. Output over the budget is
+/// dropped; when the window ends, the last `TAIL_BYTES` of it (from a line
+/// start) are emitted so the screen shows the latest output. Output is only
+/// cut between escape sequences, and the modes the dropped part changed
+/// (alternate screen, DECSET, SGR) are set again before the tail.
+struct RateLimiter {
+    limit: usize,
+    window_start: Instant,
+    sent: usize,
+    dropped: usize,
+    /// Dropped output not yet scanned by `stream`
+    tail: String,
+    /// Output up to `tail`
+    stream: ModeTracker,
+    /// Modes of the emitted output, while output is being dropped
+    shown: Option<Modes>,
+}
+
+impl RateLimiter {
+    fn new(limit: usize, now: Instant) -> Self {
+        Self {
+            limit,
+            window_start: now,
+            sent: 0,
+            dropped: 0,
+            tail: String::new(),
+            stream: ModeTracker::new(),
+            shown: None,
+        }
+    }
+
+    /// The part of `frame` within this window's budget; the rest is dropped
+    fn admit(&mut self, mut frame: String) -> String {
+        let budget = self.limit - self.sent;
+        if frame.len() <= budget {
+            self.sent += frame.len();
+            self.stream.scan(&frame);
+            return frame;
+        }
+        let cut = if budget == 0 {
+            0
+        } else {
+            let cut = self
+                .stream
+                .boundaries(&frame)
+                .take_while(|&i| i <= budget)
+                .last()
+                .unwrap_or(0);
+            self.stream.scan(&frame[..cut]);
+            cut
+        };
+        if self.shown.is_none() {
+            self.shown = Some(self.stream.modes.clone());
+        }
+        self.tail.push_str(&frame[cut..]);
+        if let Some(excess) = self.tail.len().checked_sub(TAIL_BYTES) {
+            let start = self
+                .stream
+                .boundaries(&self.tail)
+                .find(|&i| i >= excess)
+                .unwrap_or(self.tail.len());
+            self.stream.scan(&self.tail[..start]);
+            self.tail.drain(..start);
+        }
+        self.dropped += frame.len() - cut;
+        self.sent = self.limit;
+        frame.truncate(cut);
+        frame
+    }
+
+    /// When the window output was dropped in ends
+    fn deadline(&self) -> Option<Instant> {
+        (self.dropped > 0).then(|| self.window_start + RATE_WINDOW)
+    }
+
+    /// Start a new window once the current one is over, returning what was
+    /// dropped in it
+    fn roll(&mut self, now: Instant) -> Option<Dropped> {
+        if now.saturating_duration_since(self.window_start) < RATE_WINDOW {
+            return None;
+        }
+        self.window_start = now;
+        self.sent = 0;
+        self.take()
+    }
+
+    /// What was dropped in the current window
+    fn take(&mut self) -> Option<Dropped> {
+        if self.dropped == 0 {
+            return None;
+        }
+        let mut tail = std::mem::take(&mut self.tail);
+        let start = self
+            .stream
+            .boundaries(&tail)
+            .find(|&i| i < tail.len() && tail[..i].ends_with('\n'))
+            .or_else(|| self.stream.boundaries(&tail).next())
+            .unwrap_or(tail.len());
+        self.stream.scan(&tail[..start]);
+        let shown = self.shown.take().unwrap_or_default();
+        let restore = self.stream.modes.restore(&shown);
+        self.stream.scan(&tail[start..]);
+        tail.replace_range(..start, &restore);
+        Some(Dropped {
+            bytes: std::mem::take(&mut self.dropped),
+            tail,
+        })
+    }
+}
+
+/// Joins a session's output chunks into frames on a background thread
+///
+/// A frame is emitted once output pauses for `IDLE_FLUSH`, once the frame
+/// interval has passed since its first chunk, or when it reaches
+/// `MAX_FRAME_BYTES`.
+pub struct OutputBatcher {
+    sender: Option<SyncSender<Item>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl OutputBatcher {
+    /// Start a batcher calling `emit` with each frame
+    pub fn spawn<F>(interval: Duration, emit: F) -> Result<Self, String>
+    where
+        F: FnMut(String) + Send + 'static,
+    {
+        Self::spawn_with(interval, emit, BatcherHooks::default())
+    }
+
+    /// Start a batcher with activity reporting or rate limiting
+    pub fn spawn_with<F>(interval: Duration, emit: F, hooks: BatcherHooks) -> Result<Self, String>
+    where
+        F: FnMut(String) + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+        let thread = thread::Builder::new()
+            .name("pty-output".to_string())
+            .spawn(move || run(receiver, interval, emit, hooks))
+            .map_err(|e| format!("Failed to start output thread: {}", e))?;
+        Ok(Self {
+            sender: Some(sender),
+            thread: Some(thread),
+        })
+    }
+
+    /// Queue a chunk of output
+    pub fn push(&self, data: String) {
+        if data.is_empty() {
+            return;
+        }
+        self.send(Item::Output(data));
+    }
+
+    /// Run `f` on the batcher thread after the output queued so far has been
+    /// emitted, for events that must follow it (prompt marks)
+    pub fn then<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.send(Item::Then(Box::new(f)));
+    }
+
+    fn send(&self, item: Item) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(item);
+        }
+    }
+
+    /// Flush pending output and wait for the last frame to be emitted, so it
+    /// still comes before `pty-exit`
+    pub fn close(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for OutputBatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn run<F>(receiver: Receiver<Item>, interval: Duration, mut emit: F, hooks: BatcherHooks)
+where
+    F: FnMut(String),
+{
+    let BatcherHooks {
+        activity: mut watch,
+        mut rate_limit,
+    } = hooks;
+    let mut next = None;
+    while let Some(item) = next
+        .take()
+        .or_else(|| next_item(&receiver, &mut watch, &mut rate_limit, &mut emit))
+    {
+        let mut frame = match item {
+            Item::Output(data) => data,
+            Item::Then(f) => {
+                f();
+                continue;
+            }
+        };
+        let deadline = Instant::now() + interval;
+        while frame.len() < MAX_FRAME_BYTES {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            match receiver.recv_timeout(remaining.min(IDLE_FLUSH)) {
+                Ok(Item::Output(data)) => frame.push_str(&data),
+                // Ends the frame; runs after it's emitted
+                Ok(item) => {
+                    next = Some(item);
+                    break;
+                }
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        let now = Instant::now();
+        if let Some(watch) = &mut watch {
+            if let Some(activity) = watch.tracker.output(now) {
+                (watch.notify)(activity);
+            }
+        }
+        if let Some(rate_limit) = &mut rate_limit {
+            if let Some(dropped) = rate_limit.limiter.roll(now) {
+                report_dropped(rate_limit, dropped, &mut emit);
+            }
+            frame = rate_limit.limiter.admit(frame);
+            if frame.is_empty() {
+                continue;
+            }
+        }
+        emit(frame);
+    }
+    // The end of the output matters most; don't wait for the window to end
+    if let Some(rate_limit) = &mut rate_limit {
+        if let Some(dropped) = rate_limit.limiter.take() {
+            report_dropped(rate_limit, dropped, &mut emit);
+        }
+    }
+}
+
+fn report_dropped<F>(rate_limit: &mut RateLimit, dropped: Dropped, emit: &mut F)
+where
+    F: FnMut(String),
+{
+    (rate_limit.notify)(dropped.bytes);
+    if !dropped.tail.is_empty() {
+        emit(dropped.tail);
+    }
+}
+
+/// Wait for the next item, reporting the session idle if it's quiet for long
+/// enough and dropped output once its rate window is over
+fn next_item<F>(
+    receiver: &Receiver<Item>,
+    watch: &mut Option<ActivityWatch>,
+    rate_limit: &mut Option<RateLimit>,
+    emit: &mut F,
+) -> Option<Item>
+where
+    F: FnMut(String),
+{
+    loop {
+        let idle_deadline = watch.as_ref().and_then(|watch| watch.tracker.deadline());
+        let rate_deadline = rate_limit
+            .as_ref()
+            .and_then(|rate_limit| rate_limit.limiter.deadline());
+        let Some(deadline) = idle_deadline.into_iter().chain(rate_deadline).min() else {
+            return receiver.recv().ok();
+        };
+        match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(item) => return Some(item),
+            Err(RecvTimeoutError::Timeout) => {
+                let now = Instant::now();
+                if let Some(watch) = watch {
+                    if let Some(activity) = watch.tracker.check(now) {
+                        (watch.notify)(activity);
+                    }
+                }
+                if let Some(rate_limit) = rate_limit {
+                    if let Some(dropped) = rate_limit.limiter.roll(now) {
+                        report_dropped(rate_limit, dropped, emit);
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => return None,
+        }
+    }
+}
+
+/// Frame interval for the `output_frame_interval_ms` setting
+pub fn frame_interval(millis: u64) -> Duration {
+    Duration::from_millis(millis.min(MAX_FRAME_INTERVAL_MS))
+}
+
+/// Bytes per second for the `output_rate_limit_kib` setting; `None` when off
+pub fn rate_limit(kib: u64) -> Option<usize> {
+    (kib > 0).then(|| (kib as usize).saturating_mul(1024))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn collecting(interval: Duration) -> (OutputBatcher, Arc<Mutex<Vec<String>>>) {
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        let frames_for_emit = frames.clone();
+        let batcher = OutputBatcher::spawn(interval, move |frame| {
+            frames_for_emit.lock().unwrap().push(frame);
+        })
+        .unwrap();
+        (batcher, frames)
+    }
+
+    #[test]
+    fn test_flood_is_coalesced() {
+        let (batcher, frames) = collecting(Duration::from_millis(50));
+        let mut expected = String::new();
+        for i in 0..200 {
+            let chunk = format!("line {}\n", i);
+            expected.push_str(&chunk);
+            batcher.push(chunk);
+        }
+        batcher.close();
+
+        let frames = frames.lock().unwrap();
+        assert!(frames.len() < 200, "{} frames", frames.len());
+        assert_eq!(frames.concat(), expected);
+    }
+
+    #[test]
+    fn test_idle_output_is_flushed() {
+        let (batcher, frames) = collecting(Duration::from_millis(50));
+        batcher.push("$ ".to_string());
+        thread::sleep(Duration::from_millis(30));
+        assert_eq!(*frames.lock().unwrap(), ["$ "]);
+
+        batcher.push(String::new());
+        batcher.push("ls\r\n".to_string());
+        batcher.close();
+        assert_eq!(*frames.lock().unwrap(), ["$ ", "ls\r\n"]);
+    }
+
+    #[test]
+    fn test_large_frames_are_split() {
+        let (batcher, frames) = collecting(Duration::from_millis(50));
+        let chunk = "x".repeat(64 * 1024);
+        for _ in 0..8 {
+            batcher.push(chunk.clone());
+        }
+        batcher.close();
+
+        let frames = frames.lock().unwrap();
+        assert!(frames.len() >= 2);
+        assert!(frames.iter().all(|frame| frame.len() <= MAX_FRAME_BYTES));
+        assert_eq!(frames.concat().len(), 8 * chunk.len());
+    }
+
+    #[test]
+    fn test_then_runs_after_earlier_output() {
+        let (batcher, frames) = collecting(Duration::from_millis(50));
+        batcher.push("output\n".to_string());
+        let frames_for_then = frames.clone();
+        batcher.then(move || frames_for_then.lock().unwrap().push("<mark>".to_string()));
+        batcher.push("$ ".to_string());
+        batcher.close();
+
+        assert_eq!(*frames.lock().unwrap(), ["output\n", "<mark>", "$ "]);
+    }
+
+    #[test]
+    fn test_activity_is_reported_around_output() {
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        let frames_for_emit = frames.clone();
+        let frames_for_notify = frames.clone();
+        let hooks = BatcherHooks::default().activity(
+            ActivityTracker::new(Duration::from_millis(20)),
+            move |activity| {
+                let mark = match activity {
+                    Activity::Active => "<active>",
+                    Activity::Idle { .. } => "<idle>",
+                };
+                frames_for_notify.lock().unwrap().push(mark.to_string());
+            },
+        );
+        let batcher = OutputBatcher::spawn_with(
+            Duration::ZERO,
+            move |frame| frames_for_emit.lock().unwrap().push(frame),
+            hooks,
+        )
+        .unwrap();
+        batcher.push("make\r\n".to_string());
+        batcher.push("done\r\n".to_string());
+        thread::sleep(Duration::from_millis(100));
+        batcher.push("$ ".to_string());
+        batcher.close();
+
+        let frames = frames.lock().unwrap().concat();
+        assert_eq!(frames, "<active>make\r\ndone\r\n<idle><active>$ ");
+    }
+
+    #[test]
+    fn test_limiter_clips_and_keeps_tail() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(10, start);
+        assert_eq!(limiter.admit("12345".to_string()), "12345");
+        assert_eq!(limiter.admit("67890abc".to_string()), "67890");
+        assert_eq!(limiter.admit("lost\n$ ".to_string()), "");
+        assert_eq!(limiter.deadline(), Some(start + RATE_WINDOW));
+        assert_eq!(limiter.roll(start + Duration::from_millis(500)), None);
+
+        let dropped = limiter.roll(start + RATE_WINDOW).unwrap();
+        assert_eq!(
+            dropped,
+            Dropped {
+                bytes: 10,
+                tail: "$ ".to_string()
+            }
+        );
+        assert_eq!(limiter.deadline(), None);
+        assert_eq!(limiter.admit("next".to_string()), "next");
+    }
+
+    #[test]
+    fn test_limiter_tail_is_bounded() {
+        let mut limiter = RateLimiter::new(1, Instant::now());
+        limiter.admit("é".repeat(TAIL_BYTES));
+        let dropped = limiter.take().unwrap();
+        assert_eq!(dropped.bytes, 2 * TAIL_BYTES);
+        assert_eq!(dropped.tail.len(), TAIL_BYTES);
+        assert!(limiter.take().is_none());
+    }
+
+    #[test]
+    fn test_limiter_cuts_between_sequences() {
+        let mut limiter = RateLimiter::new(10, Instant::now());
+        assert_eq!(limiter.admit("abcdefg\x1b[31mred".to_string()), "abcdefg");
+        assert_eq!(limiter.take().unwrap().tail, "\x1b[31mred");
+
+        let mut limiter = RateLimiter::new(1, Instant::now());
+        let image = format!("\x1b_Gf=100;{}\x1b\\", "A".repeat(2 * TAIL_BYTES));
+        assert_eq!(limiter.admit(format!("${}after", image)), "$");
+        assert_eq!(limiter.take().unwrap().tail, "after");
+    }
+
+    #[test]
+    fn test_limiter_restores_dropped_modes() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(4, start);
+        assert_eq!(limiter.admit("ok\r\n".to_string()), "ok\r\n");
+        limiter.admit("\x1b[?1049h\x1b[?25;2004l\x1b[0;32mtop\r\n".to_string());
+        limiter.admit("\x1b[?1h\x1b[1mbottom".to_string());
+        assert_eq!(
+            limiter.roll(start + RATE_WINDOW).unwrap().tail,
+            "\x1b[?1049h\x1b[?25l\x1b[?2004l\x1b[m\x1b[0;32m\x1b[?1h\x1b[1mbottom"
+        );
+
+        // Modes changed and changed back while dropping aren't sent
+        assert_eq!(limiter.admit("\r\n$ ".to_string()), "\r\n$ ");
+        limiter.admit("\x1b[?1049l\x1b[?2004hvim\x1b[?1049h\x1b[?2004l\r\n~".to_string());
+        assert_eq!(limiter.take().unwrap().tail, "~");
+    }
+
+    #[test]
+    fn test_flood_is_rate_limited() {
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        let frames_for_emit = frames.clone();
+        let frames_for_notify = frames.clone();
+        let hooks = BatcherHooks::default().rate_limit(1000, move |bytes| {
+            frames_for_notify
+                .lock()
+                .unwrap()
+                .push(format!("<dropped {}>", bytes));
+        });
+        let batcher = OutputBatcher::spawn_with(
+            Duration::ZERO,
+            move |frame| frames_for_emit.lock().unwrap().push(frame),
+            hooks,
+        )
+        .unwrap();
+        for _ in 0..100 {
+            batcher.push("y\n".repeat(50));
+        }
+        batcher.push("^C\n$ ".to_string());
+        batcher.close();
+
+        let frames = frames.lock().unwrap();
+        let emitted: usize = frames[..frames.len() - 2].iter().map(String::len).sum();
+        assert_eq!(emitted, 1000);
+        assert_eq!(frames[frames.len() - 2], "<dropped 9005>");
+        assert!(frames[frames.len() - 1].ends_with("y\n^C\n$ "));
+    }
+
+    #[test]
+    fn test_dropped_output_is_reported_when_window_ends() {
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        let frames_for_emit = frames.clone();
+        let frames_for_notify = frames.clone();
+        let hooks = BatcherHooks::default().rate_limit(4, move |bytes| {
+            frames_for_notify
+                .lock()
+                .unwrap()
+                .push(format!("<dropped {}>", bytes));
+        });
+        let batcher = OutputBatcher::spawn_with(
+            Duration::ZERO,
+            move |frame| frames_for_emit.lock().unwrap().push(frame),
+            hooks,
+        )
+        .unwrap();
+        batcher.push("abcdef\n$ ".to_string());
+        thread::sleep(RATE_WINDOW + Duration::from_millis(200));
+        assert_eq!(*frames.lock().unwrap(), ["abcd", "<dropped 5>", "$ "]);
+        batcher.close();
+    }
+
+    #[test]
+    fn test_rate_limit_setting() {
+        assert_eq!(rate_limit(0), None);
+        assert_eq!(rate_limit(64), Some(64 * 1024));
+        assert_eq!(rate_limit(4096), Some(4096 * 1024));
+    }
+
+    #[test]
+    fn test_frame_interval_is_clamped() {
+        assert_eq!(frame_interval(0), Duration::ZERO);
+        assert_eq!(frame_interval(16), Duration::from_millis(16));
+        assert_eq!(frame_interval(1000), Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,158 @@
+//! Shortcut passthrough to full-screen programs
+//!
+//! Some app shortcuts mean something to the program in the terminal too:
+//! Cmd+K clears the terminal but is also a vim or tmux binding for many.
+//! `shortcut_passthrough` maps a shortcut (`cmd+k`) to the programs that get
+//! it instead of the app. Before acting on a Cmd shortcut the frontend calls
+//! `pass_shortcut`; when the session's foreground process is listed, the key
+//! is written to the PTY (see `input::encode_shortcut`) and the frontend skips
+//! its own action.
+
+use crate::input::KeyEvent;
+use std::collections::BTreeMap;
+
+/// Programs the default passthrough shortcuts go to
+pub const DEFAULT_PROGRAMS: &[&str] = &["vim", "nvim", "tmux", "emacs"];
+
+/// Default value of the `shortcut_passthrough` setting: clear and close
+/// pane go to editors and tmux
+pub fn default_shortcut_passthrough() -> BTreeMap<String, Vec<String>> {
+    let programs: Vec<String> = DEFAULT_PROGRAMS.iter().map(|p| p.to_string()).collect();
+    BTreeMap::from([
+        ("cmd+k".to_string(), programs.clone()),
+        ("cmd+w".to_string(), programs),
+    ])
+}
+
+/// Modifiers in the order shortcuts are written
+const MODIFIERS: [&str; 4] = ["cmd", "ctrl", "alt", "shift"];
+
+/// Canonical form of a shortcut such as `Cmd+Shift+K`: lowercase, modifiers
+/// in a fixed order. `None` without a Cmd modifier, since only Cmd shortcuts
+/// belong to the app, or with anything unknown.
+pub fn normalize(shortcut: &str) -> Option<String> {
+    let mut held = [false; 4];
+    let mut key = None;
+    for part in shortcut.split('+').map(|p| p.trim().to_lowercase()) {
+        let modifier = match part.as_str() {
+            "cmd" | "command" | "meta" | "super" => 0,
+            "ctrl" | "control" => 1,
+            "alt" | "option" | "opt" => 2,
+            "shift" => 3,
+            "" => return None,
+            _ => {
+                if key.replace(part).is_some() {
+                    return None;
+                }
+                continue;
+            }
+        };
+        held[modifier] = true;
+    }
+    if !held[0] {
+        return None;
+    }
+    Some(combine(held, &key?))
+}
+
+fn combine(held: [bool; 4], key: &str) -> String {
+    let mut parts: Vec<&str> = MODIFIERS
+        .iter()
+        .zip(held)
+        .filter_map(|(name, held)| held.then_some(*name))
+        .collect();
+    parts.push(key);
+    parts.join("+")
+}
+
+/// The shortcut a key event is, in `normalize`'s form
+pub fn shortcut_of(event: &KeyEvent) -> String {
+    combine(
+        [event.meta, event.ctrl, event.alt, event.shift],
+        &event.key.to_lowercase(),
+    )
+}
+
+/// Whether `shortcut` goes to the program `process` instead of the app
+pub fn passes_through(
+    passthrough: &BTreeMap<String, Vec<String>>,
+    shortcut: &str,
+    process: &str,
+) -> bool {
+    passthrough
+        .get(shortcut)
+        .is_some_and(|programs| programs.iter().any(|program| program == process))
+}
+
+/// Normalize the setting's shortcuts, dropping invalid ones and empty program
+/// names
+pub fn sanitize(passthrough: &mut BTreeMap<String, Vec<String>>) {
+    *passthrough = std::mem::take(passthrough)
+        .into_iter()
+        .filter_map(|(shortcut, programs)| {
+            let programs: Vec<String> = programs
+                .iter()
+                .map(|program| program.trim().to_string())
+                .filter(|program| !program.is_empty())
+                .collect();
+            Some((normalize(&shortcut)?, programs)).filter(|(_, programs)| !programs.is_empty())
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Cmd+K").as_deref(), Some("cmd+k"));
+        assert_eq!(
+            normalize("Shift + Command + K").as_deref(),
+            Some("cmd+shift+k")
+        );
+        assert_eq!(
+            normalize("option+meta+ArrowUp").as_deref(),
+            Some("cmd+alt+arrowup")
+        );
+        assert_eq!(normalize("Ctrl+K"), None);
+        assert_eq!(normalize("Cmd+K+L"), None);
+        assert_eq!(normalize("Cmd+"), None);
+        assert_eq!(normalize("Cmd"), None);
+    }
+
+    #[test]
+    fn test_shortcut_of_event() {
+        let event = KeyEvent {
+            key: "K".to_string(),
+            meta: true,
+            shift: true,
+            ..Default::default()
+        };
+        assert_eq!(shortcut_of(&event), "cmd+shift+k");
+        assert_eq!(normalize(&shortcut_of(&event)), Some(shortcut_of(&event)));
+    }
+
+    #[test]
+    fn test_passes_through() {
+        let passthrough = default_shortcut_passthrough();
+        assert!(passes_through(&passthrough, "cmd+k", "nvim"));
+        assert!(passes_through(&passthrough, "cmd+w", "tmux"));
+        assert!(!passes_through(&passthrough, "cmd+k", "zsh"));
+        assert!(!passes_through(&passthrough, "cmd+t", "vim"));
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let mut passthrough = BTreeMap::from([
+            ("Cmd+Shift+P".to_string(), vec![" hx ".to_string()]),
+            ("ctrl+k".to_string(), vec!["vim".to_string()]),
+            ("cmd+k".to_string(), vec!["  ".to_string()]),
+        ]);
+        sanitize(&mut passthrough);
+        assert_eq!(
+            passthrough,
+            BTreeMap::from([("cmd+shift+p".to_string(), vec!["hx".to_string()])])
+        );
+    }
+}
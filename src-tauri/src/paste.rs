@@ -0,0 +1,255 @@
+//! Paste sanitization for µTerm
+//!
+//! Clipboard text is cleaned up before it reaches the PTY: control characters
+//! (including ESC, which could break out of bracketed paste) are removed and
+//! line endings are normalized. Content that would run more than one command
+//! or escalate privileges is flagged so the UI can ask for confirmation.
+//!
+//! `paste_to_pty` does all of it in the backend: the text is sanitized, held
+//! back with `paste-confirmation-requested` when it needs confirming (until
+//! it's sent again with `confirmed`), and written with CR line endings, in
+//! bracketed-paste markers when the program enabled mode 2004.
+
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+/// Maximum paste size accepted (1 MB)
+pub const MAX_PASTE_LENGTH: usize = 1024 * 1024;
+
+/// Start and end markers of a bracketed paste
+const PASTE_START: &str = "\x1b[200~";
+const PASTE_END: &str = "\x1b[201~";
+
+/// Commands that run their arguments with elevated privileges
+const PRIVILEGED_COMMANDS: &[&str] = &["sudo", "doas", "su"];
+
+/// Result of preparing clipboard text for the PTY
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreparedPaste {
+    /// Sanitized text, with `\n` line endings
+    pub text: String,
+    pub line_count: usize,
+    pub is_multiline: bool,
+    pub contains_sudo: bool,
+    /// Number of control characters removed
+    pub removed_control_chars: usize,
+    /// True when the UI should confirm before writing to the PTY
+    pub needs_confirmation: bool,
+}
+
+/// Payload of `paste-confirmation-requested`: a paste was held back until the
+/// user confirms it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PasteConfirmationRequested {
+    pub session_id: String,
+    pub line_count: usize,
+    pub is_multiline: bool,
+    pub contains_sudo: bool,
+}
+
+/// Result of `paste_to_pty`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PasteResult {
+    /// False when the paste awaits confirmation
+    pub written: bool,
+    /// Whether it was sent in bracketed-paste markers
+    pub bracketed: bool,
+    /// Number of control characters removed
+    pub removed_control_chars: usize,
+}
+
+/// Sanitize `text` for pasting.
+///
+/// CRLF and lone CR become LF, control characters other than tab and newline
+/// are dropped, and a single trailing newline is removed when
+/// `trim_trailing_newline` is set.
+pub fn sanitize_paste(text: &str, trim_trailing_newline: bool) -> PreparedPaste {
+    let mut cleaned = String::with_capacity(text.len());
+    let mut removed_control_chars = 0;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                cleaned.push('\n');
+            }
+            '\n' | '\t' => cleaned.push(c),
+            c if c.is_control() => removed_control_chars += 1,
+            c => cleaned.push(c),
+        }
+    }
+
+    if trim_trailing_newline && cleaned.ends_with('\n') {
+        cleaned.pop();
+    }
+
+    let line_count = if cleaned.is_empty() {
+        0
+    } else {
+        cleaned.lines().count()
+    };
+    // A trailing newline still executes the (single) command on paste
+    let is_multiline = cleaned.trim_end_matches('\n').contains('\n');
+    let contains_sudo = contains_privileged_command(&cleaned);
+
+    PreparedPaste {
+        needs_confirmation: is_multiline || contains_sudo,
+        text: cleaned,
+        line_count,
+        is_multiline,
+        contains_sudo,
+        removed_control_chars,
+    }
+}
+
+/// Terminal input for sanitized paste text: newlines become CR, as typed, and
+/// the text is wrapped in markers when the program enabled bracketed paste
+pub fn encode_paste(text: &str, bracketed: bool) -> String {
+    let text = text.replace('\n', "\r");
+    if bracketed {
+        format!("{}{}{}", PASTE_START, text, PASTE_END)
+    } else {
+        text
+    }
+}
+
+/// Check whether any command in `text` starts with a privilege-escalation tool
+fn contains_privileged_command(text: &str) -> bool {
+    text.split(['\n', ';', '|', '&', '(', ')', '`'])
+        .filter_map(|segment| segment.split_whitespace().next())
+        .any(|word| {
+            // Strip a leading path (e.g. /usr/bin/sudo)
+            let name = word.rsplit('/').next().unwrap_or(word);
+            PRIVILEGED_COMMANDS.contains(&name)
+        })
+}
+
+/// Prepare clipboard text before it is written to the PTY
+#[command]
+pub fn prepare_paste(
+    text: String,
+    trim_trailing_newline: Option<bool>,
+) -> Result<PreparedPaste, String> {
+    if text.len() > MAX_PASTE_LENGTH {
+        return Err(format!(
+            "Paste too large: {} bytes (max {})",
+            text.len(),
+            MAX_PASTE_LENGTH
+        ));
+    }
+    Ok(sanitize_paste(
+        &text,
+        trim_trailing_newline.unwrap_or(false),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text_unchanged() {
+        let result = sanitize_paste("echo hello", false);
+        assert_eq!(result.text, "echo hello");
+        assert_eq!(result.line_count, 1);
+        assert!(!result.is_multiline);
+        assert!(!result.needs_confirmation);
+        assert_eq!(result.removed_control_chars, 0);
+    }
+
+    #[test]
+    fn test_strips_control_characters() {
+        let result = sanitize_paste("ls\x1b[201~; rm\x07 -rf\x00", false);
+        assert_eq!(result.text, "ls[201~; rm -rf");
+        assert_eq!(result.removed_control_chars, 3);
+    }
+
+    #[test]
+    fn test_keeps_tabs() {
+        let result = sanitize_paste("a\tb", false);
+        assert_eq!(result.text, "a\tb");
+    }
+
+    #[test]
+    fn test_collapses_windows_line_endings() {
+        let result = sanitize_paste("one\r\ntwo\rthree\n", false);
+        assert_eq!(result.text, "one\ntwo\nthree\n");
+        assert_eq!(result.line_count, 3);
+        assert!(result.is_multiline);
+    }
+
+    #[test]
+    fn test_trim_trailing_newline() {
+        let result = sanitize_paste("make build\r\n", true);
+        assert_eq!(result.text, "make build");
+
+        let result = sanitize_paste("make build\n", false);
+        assert_eq!(result.text, "make build\n");
+    }
+
+    #[test]
+    fn test_single_line_with_trailing_newline_not_multiline() {
+        let result = sanitize_paste("cargo test\n", false);
+        assert!(!result.is_multiline);
+        assert!(!result.needs_confirmation);
+    }
+
+    #[test]
+    fn test_multiline_needs_confirmation() {
+        let result = sanitize_paste("cd /tmp\nls", false);
+        assert!(result.is_multiline);
+        assert!(result.needs_confirmation);
+    }
+
+    #[test]
+    fn test_detects_sudo() {
+        assert!(sanitize_paste("sudo rm -rf /", false).contains_sudo);
+        assert!(sanitize_paste("  sudo ls", false).contains_sudo);
+        assert!(sanitize_paste("cd /x && sudo make install", false).contains_sudo);
+        assert!(sanitize_paste("curl x | /usr/bin/sudo sh", false).contains_sudo);
+        assert!(sanitize_paste("doas reboot", false).contains_sudo);
+        assert!(sanitize_paste("su -", false).contains_sudo);
+        assert!(sanitize_paste("sudo ls", false).needs_confirmation);
+    }
+
+    #[test]
+    fn test_sudo_as_argument_not_flagged() {
+        assert!(!sanitize_paste("man sudo", false).contains_sudo);
+        assert!(!sanitize_paste("echo pseudo", false).contains_sudo);
+        assert!(!sanitize_paste("sudoku", false).contains_sudo);
+    }
+
+    #[test]
+    fn test_empty_paste() {
+        let result = sanitize_paste("", true);
+        assert_eq!(result.text, "");
+        assert_eq!(result.line_count, 0);
+        assert!(!result.needs_confirmation);
+    }
+
+    #[test]
+    fn test_encode_paste() {
+        assert_eq!(encode_paste("cd /tmp\nls", false), "cd /tmp\rls");
+        assert_eq!(
+            encode_paste("cd /tmp\nls", true),
+            "\x1b[200~cd /tmp\rls\x1b[201~"
+        );
+        // Sanitized text can't end the bracket early
+        let prepared = sanitize_paste("x\x1b[201~; rm -rf ~", false);
+        assert_eq!(
+            encode_paste(&prepared.text, true)
+                .matches(PASTE_END)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_prepare_paste_rejects_oversized() {
+        let text = "a".repeat(MAX_PASTE_LENGTH + 1);
+        assert!(prepare_paste(text, None).is_err());
+    }
+}
@@ -0,0 +1,318 @@
+//! macOS privacy permissions for onboarding
+//!
+//! Some features silently stop working without a privacy permission: hiding
+//! the window on outside clicks needs Input Monitoring, global shortcuts need
+//! Accessibility, and shells listing protected folders need Full Disk Access.
+//! `get_permission_status` reports each of them, plus whether the app runs
+//! quarantined or translocated (started straight from a download, where
+//! grants don't stick to the app). `request_permission` shows the system
+//! prompt where there is one and `open_permission_settings` opens the matching
+//! System Settings pane.
+//!
+//! Full Disk Access has no API; it's probed by reading files only readable
+//! with it. Other platforms report every permission as `unknown`.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::command;
+use tracing::info;
+
+/// Files and folders only readable with Full Disk Access, relative to the home directory
+const FULL_DISK_ACCESS_PROBES: [&str; 3] = [
+    "Library/Safari/Bookmarks.plist",
+    "Library/Application Support/com.apple.TCC/TCC.db",
+    "Library/Mail",
+];
+
+/// Path component of apps run from a randomized read-only copy (App Translocation)
+const TRANSLOCATION_MARKER: &str = "/AppTranslocation/";
+
+/// A privacy permission µTerm may need
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    InputMonitoring,
+    Accessibility,
+    FullDiskAccess,
+}
+
+impl Permission {
+    /// All permissions, in the order onboarding asks for them
+    pub const ALL: [Permission; 3] = [
+        Permission::InputMonitoring,
+        Permission::Accessibility,
+        Permission::FullDiskAccess,
+    ];
+
+    /// System Settings pane listing apps with the permission
+    pub fn settings_url(self) -> &'static str {
+        match self {
+            Permission::InputMonitoring => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_ListenEvent"
+            }
+            Permission::Accessibility => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility"
+            }
+            Permission::FullDiskAccess => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_AllFiles"
+            }
+        }
+    }
+
+    /// What stops working without the permission
+    pub fn purpose(self) -> &'static str {
+        match self {
+            Permission::InputMonitoring => "Hiding the window when clicking outside it",
+            Permission::Accessibility => "Global shortcuts while other apps are focused",
+            Permission::FullDiskAccess => {
+                "Shells reading protected folders such as Mail, Safari and other apps' data"
+            }
+        }
+    }
+}
+
+/// Whether a permission is granted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionStatus {
+    Granted,
+    Denied,
+    /// The user hasn't been asked yet
+    NotDetermined,
+    /// Can't be told (no probe file, or not macOS)
+    Unknown,
+}
+
+/// One permission in `get_permission_status`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionInfo {
+    pub permission: Permission,
+    pub status: PermissionStatus,
+    pub purpose: String,
+    pub settings_url: String,
+}
+
+/// Whether the app runs from a download instead of an installed copy
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantineStatus {
+    /// The app bundle still has the quarantine attribute
+    pub quarantined: bool,
+    /// macOS runs the app from a randomized copy; permissions granted to it
+    /// are lost, so the app should be moved to Applications first
+    pub translocated: bool,
+    pub bundle_path: Option<String>,
+}
+
+/// Result of `get_permission_status`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionReport {
+    pub permissions: Vec<PermissionInfo>,
+    pub quarantine: QuarantineStatus,
+}
+
+/// Full Disk Access as seen through the probe files under `home`
+fn probe_full_disk_access(home: &Path) -> PermissionStatus {
+    for probe in FULL_DISK_ACCESS_PROBES {
+        let path = home.join(probe);
+        let result = if path.is_dir() {
+            fs::read_dir(&path).map(|_| ())
+        } else {
+            fs::File::open(&path).map(|_| ())
+        };
+        match result {
+            Ok(()) => return PermissionStatus::Granted,
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                return PermissionStatus::Denied;
+            }
+            Err(_) => {}
+        }
+    }
+    PermissionStatus::Unknown
+}
+
+/// The `.app` bundle containing `exe`, if any
+fn bundle_path(exe: &Path) -> Option<PathBuf> {
+    exe.ancestors()
+        .find(|path| path.extension().is_some_and(|ext| ext == "app"))
+        .map(Path::to_path_buf)
+}
+
+fn quarantine_status(exe: &Path) -> QuarantineStatus {
+    let bundle = bundle_path(exe);
+    QuarantineStatus {
+        quarantined: bundle.as_deref().is_some_and(has_quarantine_attribute),
+        translocated: exe.to_string_lossy().contains(TRANSLOCATION_MARKER),
+        bundle_path: bundle.map(|path| path.to_string_lossy().into_owned()),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn has_quarantine_attribute(path: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    // Extended attribute macOS sets on downloaded files
+    let name = c"com.apple.quarantine";
+    let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    // SAFETY: both strings are NUL-terminated; a null buffer only asks for the size
+    let size =
+        unsafe { libc::getxattr(path.as_ptr(), name.as_ptr(), std::ptr::null_mut(), 0, 0, 0) };
+    size >= 0
+}
+
+#[cfg(not(target_os = "macos"))]
+fn has_quarantine_attribute(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(target_os = "macos")]
+fn check(permission: Permission) -> PermissionStatus {
+    match permission {
+        Permission::InputMonitoring => match crate::macos::input_monitoring_access() {
+            Some(true) => PermissionStatus::Granted,
+            Some(false) => PermissionStatus::Denied,
+            None => PermissionStatus::NotDetermined,
+        },
+        Permission::Accessibility => {
+            if crate::macos::accessibility_trusted(false) {
+                PermissionStatus::Granted
+            } else {
+                PermissionStatus::Denied
+            }
+        }
+        Permission::FullDiskAccess => match std::env::var_os("HOME") {
+            Some(home) => probe_full_disk_access(Path::new(&home)),
+            None => PermissionStatus::Unknown,
+        },
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn check(_permission: Permission) -> PermissionStatus {
+    PermissionStatus::Unknown
+}
+
+/// Status of each permission and the app's quarantine state
+pub fn report() -> PermissionReport {
+    let permissions = Permission::ALL
+        .iter()
+        .map(|&permission| PermissionInfo {
+            permission,
+            status: check(permission),
+            purpose: permission.purpose().to_string(),
+            settings_url: permission.settings_url().to_string(),
+        })
+        .collect();
+    let quarantine = std::env::current_exe()
+        .map(|exe| quarantine_status(&exe))
+        .unwrap_or_default();
+    PermissionReport {
+        permissions,
+        quarantine,
+    }
+}
+
+#[command]
+pub fn get_permission_status() -> Result<PermissionReport, String> {
+    Ok(report())
+}
+
+/// Ask for a permission: Input Monitoring and Accessibility show the system
+/// prompt (only the first time); Full Disk Access has none, so its System
+/// Settings pane is opened. Returns the status afterwards.
+#[command]
+pub fn request_permission(permission: Permission) -> Result<PermissionStatus, String> {
+    info!(?permission, "Requesting permission");
+    #[cfg(target_os = "macos")]
+    match permission {
+        Permission::InputMonitoring => {
+            crate::macos::request_input_monitoring();
+        }
+        Permission::Accessibility => {
+            crate::macos::accessibility_trusted(true);
+        }
+        Permission::FullDiskAccess => open_settings(permission)?,
+    }
+    Ok(check(permission))
+}
+
+/// Open the System Settings pane where the permission is granted
+#[command]
+pub fn open_permission_settings(permission: Permission) -> Result<(), String> {
+    open_settings(permission)
+}
+
+#[cfg(target_os = "macos")]
+fn open_settings(permission: Permission) -> Result<(), String> {
+    let status = std::process::Command::new("open")
+        .arg(permission.settings_url())
+        .status()
+        .map_err(|e| format!("Failed to open System Settings: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("Failed to open System Settings: {}", status))
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn open_settings(_permission: Permission) -> Result<(), String> {
+    Err("Permission settings are only available on macOS".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_probe_full_disk_access() {
+        let home = TempDir::new().unwrap();
+        assert_eq!(
+            probe_full_disk_access(home.path()),
+            PermissionStatus::Unknown
+        );
+
+        fs::create_dir_all(home.path().join("Library/Mail")).unwrap();
+        assert_eq!(
+            probe_full_disk_access(home.path()),
+            PermissionStatus::Granted
+        );
+    }
+
+    #[test]
+    fn test_quarantine_status_paths() {
+        let exe = Path::new(
+            "/private/var/folders/xy/T/AppTranslocation/1234/d/µTerm.app/Contents/MacOS/microterm",
+        );
+        let status = quarantine_status(exe);
+        assert!(status.translocated);
+        assert_eq!(
+            status.bundle_path.as_deref(),
+            Some("/private/var/folders/xy/T/AppTranslocation/1234/d/µTerm.app")
+        );
+
+        let status = quarantine_status(Path::new("/usr/local/bin/microterm"));
+        assert_eq!(status, QuarantineStatus::default());
+    }
+
+    #[test]
+    fn test_permission_json() {
+        assert_eq!(
+            serde_json::to_string(&Permission::FullDiskAccess).unwrap(),
+            "\"full_disk_access\""
+        );
+        assert_eq!(
+            serde_json::to_string(&PermissionStatus::NotDetermined).unwrap(),
+            "\"not_determined\""
+        );
+        for permission in Permission::ALL {
+            assert!(permission
+                .settings_url()
+                .starts_with("x-apple.systempreferences:"));
+        }
+    }
+}
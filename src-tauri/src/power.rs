@@ -0,0 +1,171 @@
+//! Battery and thermal monitoring
+//!
+//! Long builds slow to a crawl on a low battery or once the Mac starts
+//! throttling. The power status is polled once a minute and
+//! `power-status-changed` is emitted when the power source, the low-battery
+//! warning or the thermal state changes; `get_power_status` returns the
+//! current status for the status bar.
+
+use crate::events;
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::Duration;
+use tauri::{command, AppHandle};
+use tracing::info;
+
+/// How often the power status is polled
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Battery charge, in percent, below which `low_battery` is set while on battery
+pub const LOW_BATTERY_PERCENT: u8 = 20;
+
+/// System thermal state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThermalState {
+    #[default]
+    Nominal,
+    Fair,
+    /// The system is throttling
+    Serious,
+    /// The system is throttling heavily
+    Critical,
+}
+
+impl ThermalState {
+    /// State for a level from 0 (nominal) to 3 (critical)
+    fn from_level(level: isize) -> Self {
+        match level {
+            ..=0 => ThermalState::Nominal,
+            1 => ThermalState::Fair,
+            2 => ThermalState::Serious,
+            _ => ThermalState::Critical,
+        }
+    }
+}
+
+/// Battery and thermal status; payload of `power-status-changed`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerStatus {
+    /// Battery charge in percent; `None` without a battery
+    pub battery_percent: Option<u8>,
+    /// Whether the Mac is running on battery power
+    pub on_battery: bool,
+    /// On battery with less than `LOW_BATTERY_PERCENT` left
+    pub low_battery: bool,
+    pub thermal_state: ThermalState,
+    /// Thermal state is serious or critical
+    pub thermal_pressure: bool,
+}
+
+impl PowerStatus {
+    fn new(battery: Option<(u8, bool)>, thermal_state: ThermalState) -> Self {
+        let on_battery = battery.is_some_and(|(_, on_battery)| on_battery);
+        let battery_percent = battery.map(|(percent, _)| percent);
+        Self {
+            battery_percent,
+            on_battery,
+            low_battery: on_battery && battery_percent.is_some_and(|p| p < LOW_BATTERY_PERCENT),
+            thermal_state,
+            thermal_pressure: matches!(
+                thermal_state,
+                ThermalState::Serious | ThermalState::Critical
+            ),
+        }
+    }
+
+    /// Whether the change from `previous` is worth an event (not every
+    /// percent of battery drain is)
+    fn differs_notably(&self, previous: &PowerStatus) -> bool {
+        self.on_battery != previous.on_battery
+            || self.low_battery != previous.low_battery
+            || self.thermal_state != previous.thermal_state
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn read_power_status() -> PowerStatus {
+    PowerStatus::new(
+        crate::macos::battery_status(),
+        ThermalState::from_level(crate::macos::thermal_state()),
+    )
+}
+
+#[cfg(not(target_os = "macos"))]
+fn read_power_status() -> PowerStatus {
+    // Read the first battery from sysfs; there is no portable thermal state
+    let battery = std::fs::read_dir("/sys/class/power_supply")
+        .ok()
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("BAT"))
+        .find_map(|entry| {
+            let read = |name: &str| std::fs::read_to_string(entry.path().join(name)).ok();
+            let percent = read("capacity")?.trim().parse::<u8>().ok()?;
+            let discharging = read("status").is_some_and(|s| s.trim() == "Discharging");
+            Some((percent.min(100), discharging))
+        });
+    PowerStatus::new(battery, ThermalState::Nominal)
+}
+
+/// Start polling the power status
+pub fn start(app: AppHandle) {
+    thread::spawn(move || {
+        let mut previous = read_power_status();
+        loop {
+            thread::sleep(CHECK_INTERVAL);
+            let status = read_power_status();
+            if status.differs_notably(&previous) {
+                info!(?status, "Power status changed");
+                let _ = events::emit(&app, status);
+            }
+            previous = status;
+        }
+    });
+}
+
+/// Current battery and thermal status
+#[command]
+pub fn get_power_status() -> Result<PowerStatus, String> {
+    Ok(read_power_status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_power_status_warnings() {
+        let status = PowerStatus::new(Some((15, true)), ThermalState::Nominal);
+        assert!(status.low_battery);
+        assert!(!status.thermal_pressure);
+
+        // A low charge while plugged in isn't a warning
+        assert!(!PowerStatus::new(Some((15, false)), ThermalState::Nominal).low_battery);
+
+        let status = PowerStatus::new(None, ThermalState::from_level(2));
+        assert_eq!(status.battery_percent, None);
+        assert!(!status.on_battery);
+        assert!(status.thermal_pressure);
+    }
+
+    #[test]
+    fn test_differs_notably() {
+        let status = PowerStatus::new(Some((80, true)), ThermalState::Nominal);
+        let drained = PowerStatus::new(Some((79, true)), ThermalState::Nominal);
+        assert!(!drained.differs_notably(&status));
+
+        let low = PowerStatus::new(Some((19, true)), ThermalState::Nominal);
+        assert!(low.differs_notably(&drained));
+        let hot = PowerStatus::new(Some((80, true)), ThermalState::Fair);
+        assert!(hot.differs_notably(&status));
+    }
+
+    #[test]
+    fn test_thermal_state_from_level() {
+        assert_eq!(ThermalState::from_level(0), ThermalState::Nominal);
+        assert_eq!(ThermalState::from_level(3), ThermalState::Critical);
+        assert_eq!(ThermalState::from_level(7), ThermalState::Critical);
+    }
+}
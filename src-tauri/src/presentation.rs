@@ -0,0 +1,382 @@
+//! Presentation mode: an opaque window while the screen is shared
+//!
+//! A translucent terminal is unreadable in a screen share or recording, so the
+//! window is made opaque while a capture is detected (or always), and
+//! `toggle_presentation_mode` sets up the app for a talk or a demo.
+
+use crate::events::{self, PinStateUpdated};
+use crate::settings::{AppSettings, SettingsManager};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{command, AppHandle, Manager, State};
+use tracing::info;
+
+/// How often the screen is checked for a capture in `auto` mode
+const CHECK_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Points added to the font size while presenting
+const FONT_SIZE_BUMP: u8 = 4;
+
+/// Processes whose on-screen windows mean the screen is being captured
+const CAPTURE_OWNERS: &[&str] = &[
+    // Controls of a Cmd+Shift+5 recording
+    "screencaptureui",
+];
+
+/// Window title fragments (lowercase) of sharing indicators
+const CAPTURE_TITLES: &[&str] = &[
+    // Zoom's "share statusbar" and "share toolbar" windows
+    "zoom share",
+    // Chrome and Edge sharing bars, e.g. "meet.google.com is sharing your screen."
+    "is sharing your screen",
+    "is sharing a window",
+    "is sharing this tab",
+];
+
+/// The `presentation_mode` setting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresentationMode {
+    /// Opacity always follows the settings
+    #[default]
+    Off,
+    /// Opaque while a screen capture is detected
+    Auto,
+    /// Always opaque
+    On,
+}
+
+/// An on-screen window, as far as capture detection cares
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenWindow {
+    pub owner: String,
+    /// Only available with the Screen Recording permission
+    pub title: Option<String>,
+}
+
+/// Settings replaced by `toggle_presentation_mode`, restored when it's
+/// toggled off; kept in the `presenting` setting so that works after a restart
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresentationBackup {
+    pub font_size: u8,
+    pub increase_contrast: Option<bool>,
+    pub terminal_notifications: bool,
+    pub bell_notifications: bool,
+    pub pinned: bool,
+}
+
+impl PresentationBackup {
+    /// Switch `settings` to presenting, returning what they were
+    fn apply(settings: &mut AppSettings) -> Self {
+        let backup = Self {
+            font_size: settings.font_size,
+            increase_contrast: settings.increase_contrast,
+            terminal_notifications: settings.terminal_notifications,
+            bell_notifications: settings.bell_notifications,
+            pinned: settings.pinned,
+        };
+        settings.font_size = settings.font_size.saturating_add(FONT_SIZE_BUMP);
+        settings.increase_contrast = Some(true);
+        settings.terminal_notifications = false;
+        settings.bell_notifications = false;
+        settings.pinned = true;
+        backup
+    }
+
+    /// Put back the values `apply` replaced
+    fn restore(self, settings: &mut AppSettings) {
+        settings.font_size = self.font_size;
+        settings.increase_contrast = self.increase_contrast;
+        settings.terminal_notifications = self.terminal_notifications;
+        settings.bell_notifications = self.bell_notifications;
+        settings.pinned = self.pinned;
+    }
+}
+
+/// Start or stop presenting; returns whether presenting now
+fn toggle(settings: &mut AppSettings) -> bool {
+    match settings.presenting.take() {
+        Some(backup) => {
+            backup.restore(settings);
+            false
+        }
+        None => {
+            settings.presenting = Some(PresentationBackup::apply(settings));
+            true
+        }
+    }
+}
+
+/// Payload of `presentation-mode-changed` and result of `get_presentation_mode`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresentationStatus {
+    pub mode: PresentationMode,
+    /// What gave a capture away (`Screen Sharing`, an app name), if one was
+    /// detected; only checked in `auto` mode
+    pub capture: Option<String>,
+    /// Whether `toggle_presentation_mode` is on
+    pub presenting: bool,
+    /// Whether the window is forced opaque (always while presenting); the
+    /// frontend then renders at full opacity without blur effects
+    pub active: bool,
+}
+
+impl PresentationStatus {
+    fn new(mode: PresentationMode, capture: Option<String>, presenting: bool) -> Self {
+        let active = presenting
+            || match mode {
+                PresentationMode::Off => false,
+                PresentationMode::Auto => capture.is_some(),
+                PresentationMode::On => true,
+            };
+        Self {
+            mode,
+            capture,
+            presenting,
+            active,
+        }
+    }
+}
+
+/// The window giving a capture away, if any
+fn capture_in(windows: &[ScreenWindow]) -> Option<String> {
+    windows
+        .iter()
+        .find(|window| {
+            CAPTURE_OWNERS.contains(&window.owner.as_str())
+                || window.title.as_deref().is_some_and(|title| {
+                    let title = title.to_lowercase();
+                    CAPTURE_TITLES
+                        .iter()
+                        .any(|fragment| title.contains(fragment))
+                })
+        })
+        .map(|window| window.owner.clone())
+}
+
+/// What gives a running screen capture away, if anything: macOS Screen
+/// Sharing, the screenshot toolbar's recording, or the sharing bars of Zoom
+/// and browser-based meetings
+///
+/// A heuristic: window titles are only visible with the Screen Recording
+/// permission, so without it only the owner-based signs work.
+#[cfg(target_os = "macos")]
+fn detect_capture() -> Option<String> {
+    if crate::macos::screen_is_shared() {
+        return Some("Screen Sharing".to_string());
+    }
+    capture_in(&crate::macos::on_screen_windows())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn detect_capture() -> Option<String> {
+    None
+}
+
+/// Current presentation status, shared by the polling thread and commands
+#[derive(Default)]
+pub struct PresentationManager {
+    status: Mutex<PresentationStatus>,
+}
+
+impl PresentationManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a new status; returns true if it differs from the last one
+    fn update(&self, status: PresentationStatus) -> bool {
+        let mut current = self.status.lock();
+        if *current == status {
+            return false;
+        }
+        *current = status;
+        true
+    }
+}
+
+/// Make the window opaque, or translucent again, so neither `opacity` nor
+/// anything behind the window shows through. Must run on the main thread.
+fn apply(active: bool) {
+    #[cfg(target_os = "macos")]
+    crate::macos::set_window_opaque(active);
+    #[cfg(not(target_os = "macos"))]
+    let _ = active;
+}
+
+/// Re-check the mode and the screen; applies and emits
+/// `presentation-mode-changed` when the status changed
+pub fn refresh(app: &AppHandle) -> PresentationStatus {
+    let (mode, presenting) = app
+        .try_state::<Arc<SettingsManager>>()
+        .map(|settings| {
+            let settings = settings.get();
+            (settings.presentation_mode, settings.presenting.is_some())
+        })
+        .unwrap_or_default();
+    let capture = match mode {
+        PresentationMode::Auto => detect_capture(),
+        _ => None,
+    };
+    let status = PresentationStatus::new(mode, capture, presenting);
+    let changed = app
+        .try_state::<Arc<PresentationManager>>()
+        .is_some_and(|manager| manager.update(status.clone()));
+    if changed {
+        info!(?status, "Presentation mode changed");
+        let active = status.active;
+        let _ = app.run_on_main_thread(move || apply(active));
+        let _ = events::emit(app, status.clone());
+    }
+    status
+}
+
+/// Start checking for screen captures
+pub fn start(app: AppHandle) {
+    thread::spawn(move || loop {
+        refresh(&app);
+        thread::sleep(CHECK_INTERVAL);
+    });
+}
+
+/// Current presentation mode and whether it applies
+#[command]
+pub fn get_presentation_mode(app: AppHandle) -> Result<PresentationStatus, String> {
+    Ok(refresh(&app))
+}
+
+/// Change the `presentation_mode` setting, returning the resulting status
+#[command]
+pub fn set_presentation_mode(
+    app: AppHandle,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    mode: PresentationMode,
+) -> Result<PresentationStatus, String> {
+    let mut settings = settings_manager.get();
+    settings.presentation_mode = mode;
+    settings_manager.update(settings);
+    Ok(refresh(&app))
+}
+
+/// Start presenting (larger font, high-contrast theme, no notifications, a
+/// pinned window that doesn't auto-hide), or restore the settings from before;
+/// returns the new settings
+#[command]
+pub fn toggle_presentation_mode(
+    app: AppHandle,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+) -> Result<AppSettings, String> {
+    let mut settings = settings_manager.get();
+    let presenting = toggle(&mut settings);
+    settings_manager.update(settings);
+    let settings = settings_manager.get();
+    info!(
+        presenting,
+        font_size = settings.font_size,
+        "Presenting toggled"
+    );
+
+    #[cfg(target_os = "macos")]
+    crate::macos::set_window_pinned(settings.pinned);
+    events::emit(
+        &app,
+        PinStateUpdated {
+            pinned: settings.pinned,
+        },
+    )
+    .map_err(|e| format!("Failed to emit pin-state-updated: {}", e))?;
+    crate::accessibility::refresh_display_preferences(&app);
+    refresh(&app);
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(owner: &str, title: Option<&str>) -> ScreenWindow {
+        ScreenWindow {
+            owner: owner.to_string(),
+            title: title.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_capture_in() {
+        assert_eq!(capture_in(&[]), None);
+        assert_eq!(
+            capture_in(&[window("Finder", None), window("Dock", Some("Dock"))]),
+            None
+        );
+        assert_eq!(
+            capture_in(&[window("screencaptureui", None)]).as_deref(),
+            Some("screencaptureui")
+        );
+        assert_eq!(
+            capture_in(&[window("zoom.us", Some("zoom share statusbar window"))]).as_deref(),
+            Some("zoom.us")
+        );
+        assert_eq!(
+            capture_in(&[window(
+                "Google Chrome",
+                Some("meet.google.com is sharing your screen.")
+            )])
+            .as_deref(),
+            Some("Google Chrome")
+        );
+        // Zoom without a sharing window is just a meeting
+        assert_eq!(capture_in(&[window("zoom.us", Some("Zoom Meeting"))]), None);
+    }
+
+    #[test]
+    fn test_status_follows_mode() {
+        let capture = || Some("zoom.us".to_string());
+        assert!(!PresentationStatus::new(PresentationMode::Off, None, false).active);
+        assert!(!PresentationStatus::new(PresentationMode::Auto, None, false).active);
+        assert!(PresentationStatus::new(PresentationMode::Auto, capture(), false).active);
+        assert!(PresentationStatus::new(PresentationMode::On, None, false).active);
+        assert!(PresentationStatus::new(PresentationMode::Off, None, true).active);
+    }
+
+    #[test]
+    fn test_toggle_restores_settings() {
+        let mut settings = AppSettings {
+            font_size: 14,
+            increase_contrast: Some(false),
+            bell_notifications: true,
+            ..AppSettings::default()
+        };
+        let before = settings.clone();
+
+        assert!(toggle(&mut settings));
+        assert_eq!(settings.font_size, 18);
+        assert_eq!(settings.increase_contrast, Some(true));
+        assert!(!settings.terminal_notifications);
+        assert!(!settings.bell_notifications);
+        assert!(settings.pinned);
+        assert!(settings.presenting.is_some());
+
+        assert!(!toggle(&mut settings));
+        assert_eq!(settings.font_size, before.font_size);
+        assert_eq!(settings.increase_contrast, before.increase_contrast);
+        assert_eq!(
+            settings.terminal_notifications,
+            before.terminal_notifications
+        );
+        assert_eq!(settings.bell_notifications, before.bell_notifications);
+        assert_eq!(settings.pinned, before.pinned);
+        assert_eq!(settings.presenting, None);
+    }
+
+    #[test]
+    fn test_manager_reports_changes() {
+        let manager = PresentationManager::new();
+        assert!(!manager.update(PresentationStatus::default()));
+        let on = PresentationStatus::new(PresentationMode::On, None, false);
+        assert!(manager.update(on.clone()));
+        assert!(!manager.update(on));
+    }
+}
@@ -0,0 +1,152 @@
+//! Processes started from a session's shell
+//!
+//! Closing a session hangs up its whole process tree, so an `rsync` or a
+//! background job dies with the pane. `descendants` lists what a shell has
+//! started (children, their children and so on) for
+//! `session_has_running_process`, letting the frontend ask before closing.
+//! Helpers that prompt themes keep running for the shell's lifetime (such as
+//! powerlevel10k's `gitstatusd`) don't count.
+
+/// Processes listed at most, should the tree be huge
+const MAX_PROCESSES: usize = 256;
+
+/// Name prefixes of helpers shells keep running alongside the prompt
+const SHELL_HELPERS: &[&str] = &["gitstatusd"];
+
+/// Whether a process is a prompt helper rather than something the user ran
+pub fn is_shell_helper(name: &str) -> bool {
+    SHELL_HELPERS.iter().any(|helper| name.starts_with(helper))
+}
+
+/// Walk a process tree breadth first, returning every process below `root`
+fn walk(root: u32, mut children: impl FnMut(u32) -> Vec<u32>) -> Vec<u32> {
+    let mut found: Vec<u32> = Vec::new();
+    let mut next = 0;
+    let mut parent = root;
+    loop {
+        for child in children(parent) {
+            // PIDs can be reused while the table is read
+            if child != root && !found.contains(&child) && found.len() < MAX_PROCESSES {
+                found.push(child);
+            }
+        }
+        match found.get(next) {
+            Some(&pid) => parent = pid,
+            None => return found,
+        }
+        next += 1;
+    }
+}
+
+/// Processes descended from `pid`, closest first
+#[cfg(target_os = "macos")]
+pub fn descendants(pid: u32) -> Vec<u32> {
+    // From sys/proc_info.h
+    const PROC_PPID_ONLY: u32 = 6;
+
+    walk(pid, |parent| {
+        let mut pids = vec![0 as libc::c_int; MAX_PROCESSES];
+        // SAFETY: the buffer is as long as the size passed
+        let bytes = unsafe {
+            libc::proc_listpids(
+                PROC_PPID_ONLY,
+                parent,
+                pids.as_mut_ptr() as *mut libc::c_void,
+                (pids.len() * std::mem::size_of::<libc::c_int>()) as libc::c_int,
+            )
+        };
+        let count = (bytes.max(0) as usize / std::mem::size_of::<libc::c_int>()).min(pids.len());
+        pids[..count]
+            .iter()
+            .filter(|&&pid| pid > 0)
+            .map(|&pid| pid as u32)
+            .collect()
+    })
+}
+
+/// Processes descended from `pid`, closest first
+#[cfg(not(target_os = "macos"))]
+pub fn descendants(pid: u32) -> Vec<u32> {
+    let table: Vec<(u32, u32)> = std::fs::read_dir("/proc")
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+                .filter_map(|pid| {
+                    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+                    Some((pid, parent_from_stat(&stat)?))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    walk(pid, |parent| {
+        table
+            .iter()
+            .filter(|(_, ppid)| *ppid == parent)
+            .map(|(pid, _)| *pid)
+            .collect()
+    })
+}
+
+/// Parent PID in the contents of `/proc/<pid>/stat`; the name before it is
+/// in parentheses and can contain anything
+#[cfg(not(target_os = "macos"))]
+fn parent_from_stat(stat: &str) -> Option<u32> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_walk_finds_grandchildren() {
+        let tree = HashMap::from([(1, vec![2, 3]), (2, vec![4]), (4, vec![5])]);
+        let found = walk(1, |pid| tree.get(&pid).cloned().unwrap_or_default());
+        assert_eq!(found, [2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_walk_survives_cycles() {
+        let tree = HashMap::from([(1, vec![2]), (2, vec![3, 1]), (3, vec![2])]);
+        let found = walk(1, |pid| tree.get(&pid).cloned().unwrap_or_default());
+        assert_eq!(found, [2, 3]);
+    }
+
+    #[test]
+    fn test_walk_is_bounded() {
+        let found = walk(0, |pid| vec![pid + 1, pid + 100_000]);
+        assert_eq!(found.len(), MAX_PROCESSES);
+    }
+
+    #[test]
+    fn test_shell_helpers() {
+        assert!(is_shell_helper("gitstatusd-darwin-arm64"));
+        assert!(!is_shell_helper("rsync"));
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn test_parent_from_stat() {
+        assert_eq!(
+            parent_from_stat("42 (my (odd) name) S 7 42 42 0 -1"),
+            Some(7)
+        );
+        assert_eq!(parent_from_stat("42 (x"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_descendants_include_children() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        let found = descendants(std::process::id());
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(found.contains(&child.id()));
+    }
+}
@@ -0,0 +1,377 @@
+//! Per-directory profiles
+//!
+//! A profile bundles a theme, a tab color, a shell, a starting directory,
+//! extra environment variables and a startup command. Profiles are kept in
+//! the settings and edited with `save_profile` / `delete_profile`;
+//! `create_pty_session_with_profile` starts a session with one (e.g. separate
+//! "work", "personal" and "docker" terminals). The `directory_profiles`
+//! setting maps directory globs (e.g. `~/work/**`) to profiles; the first
+//! matching rule wins. The profile is resolved when a session is created, and
+//! again whenever the session's working directory changes, in which case
+//! `session-profile-changed` is emitted so the frontend can switch themes.
+//! The shell, directory, environment variables and startup command only
+//! apply to new sessions.
+
+use crate::events;
+use crate::keychain::KeychainSecret;
+use crate::session_labels::LabelColor;
+use crate::settings::{AppSettings, SettingsManager, ShellConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{command, AppHandle, Manager, State};
+use tracing::{debug, warn};
+
+/// Environment variable naming the active profile in new sessions
+pub const PROFILE_ENV_VAR: &str = "MICROTERM_PROFILE";
+
+/// Named set of session options
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    /// Theme name for the frontend
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// Extra environment variables for new sessions
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Keychain items exported as environment variables in new sessions
+    #[serde(default)]
+    pub keychain_env: Vec<KeychainSecret>,
+    /// Command typed into new sessions once the shell starts
+    #[serde(default)]
+    pub startup_command: Option<String>,
+    /// Shell started instead of the `shell` setting
+    #[serde(default)]
+    pub shell: Option<ShellConfig>,
+    /// Starting directory for sessions started with the profile (a leading
+    /// `~` is the home directory); directory-matched sessions keep theirs
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Tab color
+    #[serde(default)]
+    pub color: Option<LabelColor>,
+}
+
+impl Profile {
+    /// Starting directory with `~` expanded, if it exists
+    pub fn start_dir(&self, home: &str) -> Option<PathBuf> {
+        let cwd = self.cwd.as_deref()?;
+        let dir = expand_home(cwd, home);
+        if dir.is_dir() {
+            Some(dir)
+        } else {
+            warn!(profile = %self.name, cwd = %cwd, "Profile directory not found");
+            None
+        }
+    }
+}
+
+/// A directory with a leading `~` replaced by the home directory
+pub fn expand_home(dir: &str, home: &str) -> PathBuf {
+    match dir.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            PathBuf::from(format!("{}{}", home, rest))
+        }
+        _ => PathBuf::from(dir),
+    }
+}
+
+/// Maps a directory glob to a profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectoryProfile {
+    /// Directory glob: `*` and `?` match within a path component, `**`
+    /// matches any number of components, and a leading `~` is the home directory
+    pub pattern: String,
+    /// Name of the profile to apply
+    pub profile: String,
+}
+
+/// Payload of the `session-profile-changed` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionProfileChanged {
+    pub session_id: String,
+    /// `None` when no rule matches the new directory
+    pub profile: Option<Profile>,
+}
+
+/// Whether a name is a valid environment variable name
+pub fn is_env_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Match one path component against a glob component (`*` and `?`)
+fn component_matches(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|skip| component_matches(rest, &text[skip..])),
+        Some(('?', rest)) => !text.is_empty() && component_matches(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && component_matches(rest, &text[1..]),
+    }
+}
+
+fn components_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| components_match(rest, &path[skip..])),
+        Some((component, rest)) => match path.split_first() {
+            Some((first, path_rest)) => {
+                let pattern: Vec<char> = component.chars().collect();
+                let text: Vec<char> = first.chars().collect();
+                component_matches(&pattern, &text) && components_match(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Whether a directory matches a glob
+pub fn glob_matches(pattern: &str, dir: &str, home: Option<&str>) -> bool {
+    let pattern = match (pattern.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{}", home.trim_end_matches('/'), rest)
+        }
+        _ => pattern.to_string(),
+    };
+    let pattern: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+    let dir: Vec<&str> = dir.split('/').filter(|c| !c.is_empty()).collect();
+    components_match(&pattern, &dir)
+}
+
+/// Profile for a directory: the first matching rule naming a known profile
+pub fn resolve<'a>(
+    settings: &'a AppSettings,
+    dir: &str,
+    home: Option<&str>,
+) -> Option<&'a Profile> {
+    settings
+        .directory_profiles
+        .iter()
+        .filter(|rule| glob_matches(&rule.pattern, dir, home))
+        .find_map(|rule| find(settings, &rule.profile))
+}
+
+/// Profile with the given name
+pub fn find<'a>(settings: &'a AppSettings, name: &str) -> Option<&'a Profile> {
+    settings.profiles.iter().find(|p| p.name == name)
+}
+
+/// Resolve the profile for a directory with the current settings
+pub fn resolve_for_dir(app: &AppHandle, dir: &str) -> Option<Profile> {
+    let settings = app.try_state::<Arc<SettingsManager>>()?.get();
+    let home = std::env::var("HOME").ok();
+    resolve(&settings, dir, home.as_deref()).cloned()
+}
+
+/// Session-side profile tracking, owned by the reader thread
+#[derive(Debug, Default)]
+pub struct SessionProfile {
+    /// Directory the profile was last resolved for
+    dir: Option<String>,
+    /// Name of the active profile
+    active: Option<String>,
+}
+
+impl SessionProfile {
+    /// Name of the active profile; `initial` until a directory was resolved
+    pub fn name_or<'a>(&'a self, initial: Option<&'a str>) -> Option<&'a str> {
+        if self.dir.is_some() {
+            self.active.as_deref()
+        } else {
+            initial
+        }
+    }
+
+    /// Re-resolve when the directory changed; emits `session-profile-changed`
+    /// if a different profile applies
+    pub fn update_dir(&mut self, app: &AppHandle, session_id: &str, dir: &str) {
+        if self.dir.as_deref() == Some(dir) {
+            return;
+        }
+        self.dir = Some(dir.to_string());
+        let profile = resolve_for_dir(app, dir);
+        let name = profile.as_ref().map(|p| p.name.clone());
+        if name == self.active {
+            return;
+        }
+        debug!(session_id = %session_id, profile = ?name, "Session profile changed");
+        self.active = name;
+        let _ = events::emit(
+            app,
+            SessionProfileChanged {
+                session_id: session_id.to_string(),
+                profile,
+            },
+        );
+    }
+}
+
+/// Profile that applies to a directory, if any
+#[command]
+pub fn resolve_directory_profile(
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    cwd: String,
+) -> Result<Option<Profile>, String> {
+    let settings = settings_manager.get();
+    let home = std::env::var("HOME").ok();
+    Ok(resolve(&settings, &cwd, home.as_deref()).cloned())
+}
+
+#[command]
+pub fn list_profiles(
+    settings_manager: State<'_, Arc<SettingsManager>>,
+) -> Result<Vec<Profile>, String> {
+    Ok(settings_manager.get().profiles)
+}
+
+/// Add a profile, or replace the one with the same name
+#[command]
+pub fn save_profile(
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    profile: Profile,
+) -> Result<(), String> {
+    let name = profile.name.trim();
+    if name.is_empty() {
+        return Err("Profile name is empty".to_string());
+    }
+    let profile = Profile {
+        name: name.to_string(),
+        ..profile
+    };
+    let mut settings = settings_manager.get();
+    match settings
+        .profiles
+        .iter_mut()
+        .find(|p| p.name == profile.name)
+    {
+        Some(existing) => *existing = profile,
+        None => settings.profiles.push(profile),
+    }
+    settings_manager.update(settings);
+    Ok(())
+}
+
+/// Remove a profile, returning false if there was none with the name;
+/// `directory_profiles` rules naming it are skipped from then on
+#[command]
+pub fn delete_profile(
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    name: String,
+) -> Result<bool, String> {
+    let mut settings = settings_manager.get();
+    let len = settings.profiles.len();
+    settings.profiles.retain(|p| p.name != name);
+    let removed = settings.profiles.len() != len;
+    if removed {
+        settings_manager.update(settings);
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_glob_matches() {
+        let home = Some("/Users/me");
+        assert!(glob_matches("~/work/**", "/Users/me/work", home));
+        assert!(glob_matches("~/work/**", "/Users/me/work/api/src", home));
+        assert!(!glob_matches("~/work/**", "/Users/me/workshop", home));
+        assert!(glob_matches("~/work/*", "/Users/me/work/api", home));
+        assert!(!glob_matches("~/work/*", "/Users/me/work/api/src", home));
+        assert!(glob_matches("/srv/app-?/**", "/srv/app-1/logs", home));
+        assert!(glob_matches("**/node_modules", "/a/b/node_modules", home));
+        assert!(glob_matches("/tmp/", "/tmp", home));
+        assert!(!glob_matches("~other/**", "/Users/me", home));
+    }
+
+    #[test]
+    fn test_resolve_first_matching_rule() {
+        let mut settings = AppSettings::default();
+        settings.profiles = vec![
+            Profile {
+                name: "work".to_string(),
+                theme: Some("solarized".to_string()),
+                ..Default::default()
+            },
+            Profile {
+                name: "prod".to_string(),
+                theme: Some("red".to_string()),
+                ..Default::default()
+            },
+        ];
+        settings.directory_profiles = vec![
+            DirectoryProfile {
+                pattern: "~/work/infra/**".to_string(),
+                profile: "prod".to_string(),
+            },
+            DirectoryProfile {
+                pattern: "~/work/**".to_string(),
+                profile: "work".to_string(),
+            },
+            DirectoryProfile {
+                pattern: "/**".to_string(),
+                profile: "missing".to_string(),
+            },
+        ];
+        let home = Some("/Users/me");
+
+        let name = |dir: &str| resolve(&settings, dir, home).map(|p| p.name.as_str());
+        assert_eq!(name("/Users/me/work/infra/k8s"), Some("prod"));
+        assert_eq!(name("/Users/me/work/web"), Some("work"));
+        // Rules naming unknown profiles are skipped
+        assert_eq!(name("/tmp"), None);
+    }
+
+    #[test]
+    fn test_session_profile_name() {
+        let mut profile = SessionProfile::default();
+        assert_eq!(profile.name_or(Some("work")), Some("work"));
+        profile.dir = Some("/tmp".to_string());
+        assert_eq!(profile.name_or(Some("work")), None);
+        profile.active = Some("scratch".to_string());
+        assert_eq!(profile.name_or(Some("work")), Some("scratch"));
+    }
+
+    #[test]
+    fn test_profile_start_dir() {
+        let home = TempDir::new().unwrap();
+        std::fs::create_dir(home.path().join("work")).unwrap();
+        let home_str = home.path().to_string_lossy();
+        let profile = |cwd: &str| Profile {
+            name: "work".to_string(),
+            cwd: Some(cwd.to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            profile("~/work").start_dir(&home_str),
+            Some(home.path().join("work"))
+        );
+        assert_eq!(
+            profile("~").start_dir(&home_str),
+            Some(home.path().to_path_buf())
+        );
+        let absolute = home.path().join("work").to_string_lossy().into_owned();
+        assert_eq!(
+            profile(&absolute).start_dir(&home_str),
+            Some(home.path().join("work"))
+        );
+        assert_eq!(profile("~/missing").start_dir(&home_str), None);
+        assert_eq!(Profile::default().start_dir(&home_str), None);
+    }
+
+    #[test]
+    fn test_is_env_name() {
+        assert!(is_env_name("AWS_PROFILE"));
+        assert!(is_env_name("_x1"));
+        assert!(!is_env_name("1X"));
+        assert!(!is_env_name("A=B"));
+        assert!(!is_env_name(""));
+    }
+}
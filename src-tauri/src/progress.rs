@@ -0,0 +1,302 @@
+//! Progress reporting via OSC 9;4
+//!
+//! Tools such as winget and cargo wrappers report progress with the
+//! ConEmu/Windows Terminal sequence `OSC 9 ; 4 ; state ; progress ST`. Each
+//! session's progress is tracked here and, while the window is hidden, shown
+//! as a tray title and a Dock progress bar.
+
+use crate::events;
+use crate::status_strip;
+use crate::widgets;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::window::{ProgressBarState, ProgressBarStatus};
+use tauri::{AppHandle, Manager};
+use tracing::debug;
+
+/// Tray icon ID, used to look up the tray when updating its title
+pub const TRAY_ID: &str = "main";
+
+/// Progress state from the `state` parameter of OSC 9;4
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressState {
+    /// 0: remove progress
+    Hidden,
+    /// 1: normal progress
+    Normal,
+    /// 2: error
+    Error,
+    /// 3: indeterminate
+    Indeterminate,
+    /// 4: paused / warning
+    Paused,
+}
+
+impl ProgressState {
+    /// Priority when several sessions report progress at once
+    fn priority(self) -> u8 {
+        match self {
+            ProgressState::Hidden => 0,
+            ProgressState::Indeterminate => 1,
+            ProgressState::Normal => 2,
+            ProgressState::Paused => 3,
+            ProgressState::Error => 4,
+        }
+    }
+}
+
+/// A progress report from one session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgressUpdate {
+    pub state: ProgressState,
+    /// Percentage (0-100); meaningless for `Hidden` and `Indeterminate`
+    pub progress: u8,
+}
+
+/// Payload of the `pty-progress` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PtyProgress {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub update: ProgressUpdate,
+}
+
+/// Parse the data of an OSC 9 sequence (`4;state;progress`)
+///
+/// Returns `None` for other OSC 9 uses (such as notifications).
+pub fn parse_progress(data: &str) -> Option<ProgressUpdate> {
+    let mut params = data.split(';');
+    if params.next()? != "4" {
+        return None;
+    }
+    let state = match params.next().unwrap_or("0") {
+        "" | "0" => ProgressState::Hidden,
+        "1" => ProgressState::Normal,
+        "2" => ProgressState::Error,
+        "3" => ProgressState::Indeterminate,
+        "4" => ProgressState::Paused,
+        _ => return None,
+    };
+    let progress = params
+        .next()
+        .and_then(|p| p.parse::<u32>().ok())
+        .map_or(0, |p| p.min(100) as u8);
+    Some(ProgressUpdate { state, progress })
+}
+
+/// Tracks the latest progress report of each session
+pub struct ProgressTracker {
+    sessions: Mutex<HashMap<String, ProgressUpdate>>,
+}
+
+impl Default for ProgressTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressTracker {
+    pub fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record a session's progress; `Hidden` removes it
+    pub fn update(&self, session_id: &str, update: ProgressUpdate) {
+        let mut sessions = self.sessions.lock();
+        if update.state == ProgressState::Hidden {
+            sessions.remove(session_id);
+        } else {
+            sessions.insert(session_id.to_string(), update);
+        }
+    }
+
+    /// Forget a session (e.g. when it exits)
+    pub fn remove(&self, session_id: &str) -> bool {
+        self.sessions.lock().remove(session_id).is_some()
+    }
+
+    /// Combined progress of all sessions.
+    ///
+    /// The most severe state wins; among equal states the least progress is shown.
+    pub fn aggregate(&self) -> Option<ProgressUpdate> {
+        self.sessions.lock().values().copied().max_by(|a, b| {
+            a.state
+                .priority()
+                .cmp(&b.state.priority())
+                .then(b.progress.cmp(&a.progress))
+        })
+    }
+}
+
+/// Handle an OSC 9;4 report from a session
+pub fn apply_progress(app: &AppHandle, session_id: &str, update: ProgressUpdate) {
+    debug!(session_id = %session_id, ?update, "Progress update");
+    if let Some(tracker) = app.try_state::<Arc<ProgressTracker>>() {
+        tracker.update(session_id, update);
+    }
+    let _ = events::emit(
+        app,
+        PtyProgress {
+            session_id: session_id.to_string(),
+            update,
+        },
+    );
+    refresh_indicators(app);
+    status_strip::refresh(app);
+}
+
+/// Clear a session's progress when it exits
+pub fn clear_session(app: &AppHandle, session_id: &str) {
+    let removed = app
+        .try_state::<Arc<ProgressTracker>>()
+        .is_some_and(|tracker| tracker.remove(session_id));
+    if removed {
+        refresh_indicators(app);
+        status_strip::refresh(app);
+    }
+}
+
+/// Tray title for a progress value
+fn tray_title(update: &ProgressUpdate) -> String {
+    match update.state {
+        ProgressState::Hidden => String::new(),
+        ProgressState::Indeterminate => "…".to_string(),
+        ProgressState::Error => format!("! {}%", update.progress),
+        _ => format!("{}%", update.progress),
+    }
+}
+
+/// Update the tray title and Dock progress bar.
+///
+/// Indicators are only shown while the window is hidden; the terminal itself
+/// shows progress when visible.
+pub fn refresh_indicators(app: &AppHandle) {
+    let window = app.get_webview_window("main");
+    let window_visible = window
+        .as_ref()
+        .and_then(|w| w.is_visible().ok())
+        .unwrap_or(false);
+
+    let current = app
+        .try_state::<Arc<ProgressTracker>>()
+        .and_then(|tracker| tracker.aggregate())
+        .filter(|_| !window_visible);
+
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        // Progress takes the place of widget titles while it's shown
+        let title = current
+            .as_ref()
+            .map(tray_title)
+            .or_else(|| widgets::tray_title(app));
+        let _ = tray.set_title(title);
+    }
+
+    if let Some(window) = window {
+        let state = match current {
+            Some(update) => ProgressBarState {
+                status: Some(match update.state {
+                    ProgressState::Hidden => ProgressBarStatus::None,
+                    ProgressState::Normal => ProgressBarStatus::Normal,
+                    ProgressState::Error => ProgressBarStatus::Error,
+                    ProgressState::Indeterminate => ProgressBarStatus::Indeterminate,
+                    ProgressState::Paused => ProgressBarStatus::Paused,
+                }),
+                progress: Some(u64::from(update.progress)),
+            },
+            None => ProgressBarState {
+                status: Some(ProgressBarStatus::None),
+                progress: None,
+            },
+        };
+        let _ = window.set_progress_bar(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(state: ProgressState, progress: u8) -> ProgressUpdate {
+        ProgressUpdate { state, progress }
+    }
+
+    #[test]
+    fn test_parse_progress_states() {
+        assert_eq!(
+            parse_progress("4;1;42"),
+            Some(update(ProgressState::Normal, 42))
+        );
+        assert_eq!(
+            parse_progress("4;0"),
+            Some(update(ProgressState::Hidden, 0))
+        );
+        assert_eq!(
+            parse_progress("4;2;10"),
+            Some(update(ProgressState::Error, 10))
+        );
+        assert_eq!(
+            parse_progress("4;3"),
+            Some(update(ProgressState::Indeterminate, 0))
+        );
+        assert_eq!(
+            parse_progress("4;4;80"),
+            Some(update(ProgressState::Paused, 80))
+        );
+    }
+
+    #[test]
+    fn test_parse_progress_clamps_value() {
+        assert_eq!(
+            parse_progress("4;1;250"),
+            Some(update(ProgressState::Normal, 100))
+        );
+        assert_eq!(
+            parse_progress("4;1;abc"),
+            Some(update(ProgressState::Normal, 0))
+        );
+    }
+
+    #[test]
+    fn test_parse_progress_ignores_other_osc9() {
+        assert_eq!(parse_progress("Build finished"), None);
+        assert_eq!(parse_progress("4;9;10"), None);
+        assert_eq!(parse_progress(""), None);
+    }
+
+    #[test]
+    fn test_tracker_hidden_removes_session() {
+        let tracker = ProgressTracker::new();
+        tracker.update("a", update(ProgressState::Normal, 50));
+        assert!(tracker.aggregate().is_some());
+
+        tracker.update("a", update(ProgressState::Hidden, 0));
+        assert!(tracker.aggregate().is_none());
+    }
+
+    #[test]
+    fn test_tracker_aggregate_prefers_severity_then_least_progress() {
+        let tracker = ProgressTracker::new();
+        tracker.update("a", update(ProgressState::Normal, 80));
+        tracker.update("b", update(ProgressState::Normal, 20));
+        assert_eq!(tracker.aggregate(), Some(update(ProgressState::Normal, 20)));
+
+        tracker.update("c", update(ProgressState::Error, 90));
+        assert_eq!(tracker.aggregate(), Some(update(ProgressState::Error, 90)));
+
+        assert!(tracker.remove("c"));
+        assert!(!tracker.remove("c"));
+        assert_eq!(tracker.aggregate(), Some(update(ProgressState::Normal, 20)));
+    }
+
+    #[test]
+    fn test_tray_title() {
+        assert_eq!(tray_title(&update(ProgressState::Normal, 5)), "5%");
+        assert_eq!(tray_title(&update(ProgressState::Indeterminate, 0)), "…");
+        assert_eq!(tray_title(&update(ProgressState::Error, 30)), "! 30%");
+    }
+}
@@ -0,0 +1,418 @@
+//! Project detection for a working directory
+//!
+//! `detect_project` walks up from a directory to the nearest project root,
+//! identifies the project types from their manifests (Cargo, npm, Go,
+//! Python) and lists the tasks defined in `package.json` scripts, Makefile
+//! targets and justfile recipes, so the UI can offer "run task" buttons.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::command;
+use tracing::debug;
+
+/// Task files larger than this are ignored
+const MAX_TASK_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Maximum number of tasks listed per source
+const MAX_TASKS_PER_SOURCE: usize = 100;
+
+/// Kind of project, identified by its manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectKind {
+    Cargo,
+    Npm,
+    Go,
+    Python,
+}
+
+impl ProjectKind {
+    const ALL: [ProjectKind; 4] = [
+        ProjectKind::Cargo,
+        ProjectKind::Npm,
+        ProjectKind::Go,
+        ProjectKind::Python,
+    ];
+
+    /// Files whose presence identifies the project kind
+    fn markers(self) -> &'static [&'static str] {
+        match self {
+            ProjectKind::Cargo => &["Cargo.toml"],
+            ProjectKind::Npm => &["package.json"],
+            ProjectKind::Go => &["go.mod"],
+            ProjectKind::Python => &["pyproject.toml", "setup.py", "requirements.txt"],
+        }
+    }
+}
+
+/// Tool that runs a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskRunner {
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+    Make,
+    Just,
+}
+
+impl TaskRunner {
+    /// Executable name
+    pub fn program(self) -> &'static str {
+        match self {
+            TaskRunner::Npm => "npm",
+            TaskRunner::Pnpm => "pnpm",
+            TaskRunner::Yarn => "yarn",
+            TaskRunner::Bun => "bun",
+            TaskRunner::Make => "make",
+            TaskRunner::Just => "just",
+        }
+    }
+
+    /// Arguments that run the named task
+    pub fn args(self, task: &str) -> Vec<String> {
+        match self {
+            TaskRunner::Npm | TaskRunner::Pnpm | TaskRunner::Yarn | TaskRunner::Bun => {
+                vec!["run".to_string(), task.to_string()]
+            }
+            TaskRunner::Make | TaskRunner::Just => vec![task.to_string()],
+        }
+    }
+}
+
+/// A runnable task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectTask {
+    pub name: String,
+    pub runner: TaskRunner,
+    /// Command line shown in the UI, e.g. `npm run build`
+    pub command: String,
+}
+
+impl ProjectTask {
+    fn new(name: &str, runner: TaskRunner) -> Self {
+        let mut command = vec![runner.program().to_string()];
+        command.extend(runner.args(name));
+        Self {
+            name: name.to_string(),
+            runner,
+            command: command.join(" "),
+        }
+    }
+}
+
+/// Result of `detect_project`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectInfo {
+    /// Project root directory
+    pub root: String,
+    pub kinds: Vec<ProjectKind>,
+    pub tasks: Vec<ProjectTask>,
+}
+
+/// Read a task file, skipping missing and oversized files
+fn read_task_file(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_TASK_FILE_SIZE {
+        return None;
+    }
+    fs::read_to_string(path).ok()
+}
+
+/// First of the given files that exists in `dir`
+fn find_file(dir: &Path, names: &[&str]) -> Option<PathBuf> {
+    names
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Script names from a `package.json`
+fn parse_package_scripts(content: &str) -> Vec<String> {
+    let Ok(package) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    package
+        .get("scripts")
+        .and_then(|scripts| scripts.as_object())
+        .map(|scripts| {
+            scripts
+                .iter()
+                .filter(|(_, value)| value.is_string())
+                .map(|(name, _)| name.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether a name is a plain task name (safe to pass as an argument)
+fn is_task_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':' | '/'))
+}
+
+/// Explicit targets of a Makefile (special, pattern and variable lines skipped)
+fn parse_makefile_targets(content: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    for line in content.lines() {
+        if line.starts_with(|c: char| c.is_whitespace() || c == '#') {
+            continue;
+        }
+        let Some(colon) = line.find(':') else {
+            continue;
+        };
+        // `VAR := value` and `VAR ::= value` are assignments
+        if line[colon..].starts_with(":=") || line[colon..].starts_with("::=") {
+            continue;
+        }
+        if line[..colon].contains('=') {
+            continue;
+        }
+        for target in line[..colon].split_whitespace() {
+            if target.starts_with('.') || target.contains('%') || target.contains('$') {
+                continue;
+            }
+            if is_task_name(target) && !targets.iter().any(|t| t == target) {
+                targets.push(target.to_string());
+            }
+        }
+    }
+    targets
+}
+
+/// Public recipe names of a justfile
+fn parse_justfile_recipes(content: &str) -> Vec<String> {
+    const KEYWORDS: [&str; 6] = ["set", "alias", "export", "import", "mod", "unexport"];
+
+    let mut recipes = Vec::new();
+    for line in content.lines() {
+        if line.starts_with(|c: char| c.is_whitespace() || matches!(c, '#' | '[')) {
+            continue;
+        }
+        let Some(colon) = line.find(':') else {
+            continue;
+        };
+        if line[colon..].starts_with(":=") {
+            continue;
+        }
+        let header = line[..colon].trim_start_matches('@');
+        let Some(name) = header.split_whitespace().next() else {
+            continue;
+        };
+        if KEYWORDS.contains(&name) || name.starts_with('_') {
+            continue;
+        }
+        if is_task_name(name) && !recipes.iter().any(|r| r == name) {
+            recipes.push(name.to_string());
+        }
+    }
+    recipes
+}
+
+/// Package manager used by an npm project, from its lockfile
+fn node_runner(root: &Path) -> TaskRunner {
+    if root.join("pnpm-lock.yaml").is_file() {
+        TaskRunner::Pnpm
+    } else if root.join("yarn.lock").is_file() {
+        TaskRunner::Yarn
+    } else if root.join("bun.lockb").is_file() || root.join("bun.lock").is_file() {
+        TaskRunner::Bun
+    } else {
+        TaskRunner::Npm
+    }
+}
+
+/// Project kinds whose manifests are in `dir`
+fn project_kinds(dir: &Path) -> Vec<ProjectKind> {
+    ProjectKind::ALL
+        .into_iter()
+        .filter(|kind| find_file(dir, kind.markers()).is_some())
+        .collect()
+}
+
+/// Tasks defined in the project root
+fn project_tasks(root: &Path) -> Vec<ProjectTask> {
+    let mut tasks = Vec::new();
+    let mut add = |names: Vec<String>, runner: TaskRunner| {
+        tasks.extend(
+            names
+                .iter()
+                .filter(|name| is_task_name(name))
+                .take(MAX_TASKS_PER_SOURCE)
+                .map(|name| ProjectTask::new(name, runner)),
+        );
+    };
+
+    if let Some(content) = read_task_file(&root.join("package.json")) {
+        add(parse_package_scripts(&content), node_runner(root));
+    }
+    if let Some(content) = find_file(root, &["GNUmakefile", "makefile", "Makefile"])
+        .and_then(|path| read_task_file(&path))
+    {
+        add(parse_makefile_targets(&content), TaskRunner::Make);
+    }
+    if let Some(content) = find_file(root, &["justfile", "Justfile", ".justfile"])
+        .and_then(|path| read_task_file(&path))
+    {
+        add(parse_justfile_recipes(&content), TaskRunner::Just);
+    }
+    tasks
+}
+
+/// Whether a directory has anything that makes it a project root
+fn is_project_root(dir: &Path) -> bool {
+    !project_kinds(dir).is_empty()
+        || find_file(
+            dir,
+            &[
+                "GNUmakefile",
+                "makefile",
+                "Makefile",
+                "justfile",
+                "Justfile",
+                ".justfile",
+            ],
+        )
+        .is_some()
+}
+
+/// Detect the project containing `cwd`, stopping at the home directory
+pub fn detect(cwd: &Path, home: Option<&Path>) -> Option<ProjectInfo> {
+    let root = cwd
+        .ancestors()
+        .take_while(|dir| home.is_none_or(|home| *dir != home || dir == &cwd))
+        .find(|dir| is_project_root(dir))?;
+    debug!(root = %root.display(), "Detected project");
+    Some(ProjectInfo {
+        root: root.to_string_lossy().to_string(),
+        kinds: project_kinds(root),
+        tasks: project_tasks(root),
+    })
+}
+
+/// Project type and tasks for a directory (`None` outside a project)
+#[command]
+pub fn detect_project(cwd: String) -> Result<Option<ProjectInfo>, String> {
+    let cwd = PathBuf::from(&cwd);
+    if !cwd.is_absolute() || !cwd.is_dir() {
+        return Err(format!("Not a directory: {}", cwd.display()));
+    }
+    let home = std::env::var("HOME").ok().map(PathBuf::from);
+    Ok(detect(&cwd, home.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn names(tasks: &[ProjectTask]) -> Vec<&str> {
+        tasks.iter().map(|task| task.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_package_scripts() {
+        let json =
+            r#"{"name": "x", "scripts": {"build": "vite build", "test": "vitest", "bad": 1}}"#;
+        let mut scripts = parse_package_scripts(json);
+        scripts.sort();
+        assert_eq!(scripts, ["build", "test"]);
+        assert!(parse_package_scripts("not json").is_empty());
+    }
+
+    #[test]
+    fn test_parse_makefile_targets() {
+        let makefile = "\
+CC := clang
+PREFIX = /usr/local
+.PHONY: build test
+build: src/main.c
+\t$(CC) -o app src/main.c
+test lint: build
+%.o: %.c
+$(OUT): build
+# comment: not a target
+install:
+";
+        assert_eq!(
+            parse_makefile_targets(makefile),
+            ["build", "test", "lint", "install"]
+        );
+    }
+
+    #[test]
+    fn test_parse_justfile_recipes() {
+        let justfile = "\
+set shell := [\"zsh\", \"-c\"]
+version := \"1.0\"
+alias b := build
+
+# Build everything
+build:
+    cargo build
+@test filter=\"\":
+    cargo test {{filter}}
+_helper:
+    echo hidden
+[private]
+deploy env: build
+    ./deploy.sh {{env}}
+";
+        assert_eq!(
+            parse_justfile_recipes(justfile),
+            ["build", "test", "deploy"]
+        );
+    }
+
+    #[test]
+    fn test_task_commands() {
+        assert_eq!(
+            ProjectTask::new("dev", TaskRunner::Pnpm).command,
+            "pnpm run dev"
+        );
+        assert_eq!(
+            ProjectTask::new("all", TaskRunner::Make).command,
+            "make all"
+        );
+        assert!(!is_task_name("--version"));
+        assert!(!is_task_name("a b"));
+        assert!(is_task_name("test:unit"));
+    }
+
+    #[test]
+    fn test_detect_walks_up_to_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("app");
+        let nested = root.join("src").join("components");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+        fs::write(root.join("package.json"), r#"{"scripts": {"dev": "vite"}}"#).unwrap();
+        fs::write(root.join("pnpm-lock.yaml"), "").unwrap();
+        fs::write(root.join("justfile"), "check:\n    cargo clippy\n").unwrap();
+
+        let info = detect(&nested, None).unwrap();
+        assert_eq!(info.root, root.to_string_lossy());
+        assert_eq!(info.kinds, [ProjectKind::Cargo, ProjectKind::Npm]);
+        assert_eq!(names(&info.tasks), ["dev", "check"]);
+        assert_eq!(info.tasks[0].runner, TaskRunner::Pnpm);
+    }
+
+    #[test]
+    fn test_detect_stops_at_home() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path();
+        let dir = home.join("notes");
+        fs::create_dir_all(&dir).unwrap();
+        // A Makefile in home shouldn't make every folder under it a project
+        fs::write(home.join("Makefile"), "all:\n").unwrap();
+
+        assert_eq!(detect(&dir, Some(home)), None);
+        // ...but home itself can still be a project
+        assert!(detect(home, Some(home)).is_some());
+    }
+}
@@ -1,12 +1,56 @@
+use crate::accessibility::{self, AccessibilityEvent, AccessibilityManager};
+use crate::activity::{self, ActivityTracker};
+use crate::answerback;
+use crate::askpass;
+use crate::clipboard;
+use crate::events;
+use crate::i18n;
+use crate::idle::{self, IdleSuspendMode};
+use crate::images::SessionImages;
+use crate::input::{self, InputModes, KeyEvent, MouseEvent, MousePolicy};
+use crate::jobs;
+use crate::keychain::{self, KeychainSecret};
+use crate::metrics;
+use crate::notifications::{self, NotificationManager};
+use crate::orphans::{OrphanSweep, PtyOrphaned};
+use crate::output_batch::{self, BatcherHooks, OutputBatcher};
+use crate::passthrough;
+use crate::paste;
+use crate::process_tree;
+use crate::profiles::{self, Profile, SessionProfile};
+use crate::progress;
+use crate::pty_backend::{
+    ExitStatus, LocalPtyBackend, SessionBackend, SessionProcess, SpawnRequest,
+};
+use crate::recent_dirs::RecentDirsManager;
+use crate::redact;
+use crate::replay::ReplayBuffer;
+use crate::scrollback::{self, ScrollbackBuffer, ScrollbackHit, SearchOptions};
+use crate::session_labels::SessionLabels;
+use crate::session_name;
+use crate::session_store::SessionStore;
+use crate::settings::{AppSettings, SettingsManager};
+use crate::shell_integration::{
+    self, CommandStats, IntegrationMarks, PromptEvent, PromptMark, PromptTracker,
+};
+use crate::ssh;
+use crate::status_strip;
+use crate::tab_title::{self, SessionTitle};
+use crate::transcript::{TranscriptFormat, TranscriptLog};
+use crate::trash::SafeDelete;
+use crate::utf8::Utf8Decoder;
+use crate::vt_parser::{VtEvent, VtScanner};
 use parking_lot::Mutex;
-use portable_pty::{native_pty_system, Child, CommandBuilder, PtyPair, PtySize};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
-use tauri::{AppHandle, Emitter};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
 use tracing::{debug, error, info, trace, warn};
 
 /// Minimum allowed PTY columns
@@ -20,165 +64,1188 @@ const MAX_PTY_ROWS: u16 = 200;
 /// PTY read buffer size (8KB for better throughput)
 const PTY_READ_BUFFER_SIZE: usize = 8192;
 
+/// Longest accepted startup command
+const MAX_STARTUP_COMMAND_LENGTH: usize = 4096;
+
+/// Check a startup command for a new session: one line without control
+/// characters, so it can't type anything but the command itself
+pub fn validate_startup_command(command: &str) -> Result<String, String> {
+    let command = command.trim();
+    if command.is_empty() {
+        return Err("Startup command is empty".to_string());
+    }
+    if command.len() > MAX_STARTUP_COMMAND_LENGTH {
+        return Err(format!(
+            "Startup command too long: {} bytes (max {})",
+            command.len(),
+            MAX_STARTUP_COMMAND_LENGTH
+        ));
+    }
+    if command.chars().any(|c| c.is_control() && c != '\t') {
+        return Err("Startup command must be a single line without control characters".to_string());
+    }
+    Ok(command.to_string())
+}
+
 /// Validate PTY dimensions
 fn validate_pty_size(cols: u16, rows: u16) -> Result<(), String> {
     if !(MIN_PTY_COLS..=MAX_PTY_COLS).contains(&cols) {
-        return Err(format!(
-            "Invalid cols: {}. Must be between {} and {}",
-            cols, MIN_PTY_COLS, MAX_PTY_COLS
+        return Err(i18n::tr(
+            "pty.invalid_cols",
+            &[
+                ("cols", &cols),
+                ("min", &MIN_PTY_COLS),
+                ("max", &MAX_PTY_COLS),
+            ],
         ));
     }
     if !(MIN_PTY_ROWS..=MAX_PTY_ROWS).contains(&rows) {
-        return Err(format!(
-            "Invalid rows: {}. Must be between {} and {}",
-            rows, MIN_PTY_ROWS, MAX_PTY_ROWS
+        return Err(i18n::tr(
+            "pty.invalid_rows",
+            &[
+                ("rows", &rows),
+                ("min", &MIN_PTY_ROWS),
+                ("max", &MAX_PTY_ROWS),
+            ],
         ));
     }
     Ok(())
 }
 
+fn session_not_found(session_id: &str) -> String {
+    i18n::tr("pty.session_not_found", &[("session_id", &session_id)])
+}
+
+/// Build the PATH given to PTY shells.
+///
+/// macOS GUI apps don't inherit the shell PATH, so common tool locations are
+/// prepended to whatever PATH the app was launched with.
+pub fn build_pty_path(home: &str, existing_path: Option<&str>) -> String {
+    let mut path_dirs: Vec<String> = Vec::new();
+
+    // Add user's local bin directories first (highest priority)
+    if !home.is_empty() {
+        path_dirs.push(format!("{}/bin", home));
+        path_dirs.push(format!("{}/.local/bin", home));
+    }
+
+    // Add common system paths
+    path_dirs.extend([
+        "/opt/homebrew/bin".to_string(), // Homebrew on Apple Silicon
+        "/opt/homebrew/sbin".to_string(),
+        "/usr/local/bin".to_string(), // Homebrew on Intel Mac
+        "/usr/local/sbin".to_string(),
+        "/usr/bin".to_string(),
+        "/bin".to_string(),
+        "/usr/sbin".to_string(),
+        "/sbin".to_string(),
+    ]);
+
+    // Append any existing PATH from the environment
+    let base_path = path_dirs.join(":");
+    match existing_path {
+        Some(existing_path) => format!("{}:{}", base_path, existing_path),
+        None => base_path,
+    }
+}
+
+/// Shell program and interactive arguments for new sessions: the profile's
+/// shell, else the `shell` setting, when its path exists, else the login
+/// shell (`$SHELL`)
+pub fn resolve_shell(settings: &AppSettings, profile: Option<&Profile>) -> (String, Vec<String>) {
+    let configured = profile
+        .and_then(|profile| profile.shell.as_ref())
+        .into_iter()
+        .chain(&settings.shell);
+    for shell in configured {
+        if Path::new(&shell.path).is_file() {
+            return (shell.path.clone(), shell.args.clone());
+        }
+        warn!(path = %shell.path, "Configured shell not found");
+    }
+    let login_shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
+    (login_shell, Vec::new())
+}
+
+/// Current settings, or the defaults before settings are loaded
+fn session_settings(app: &AppHandle) -> AppSettings {
+    app.try_state::<Arc<SettingsManager>>()
+        .map(|settings| settings.get())
+        .unwrap_or_default()
+}
+
+/// An event emitted after the output it was found in
+enum DeferredEvent {
+    Prompt(PromptEvent),
+    /// The program entered (`true`) or left the alternate screen
+    AlternateScreen(bool),
+}
+
+impl DeferredEvent {
+    fn emit(self, app: &AppHandle, window: &str, session_id: String) {
+        match self {
+            DeferredEvent::Prompt(event) => shell_integration::emit(app, window, session_id, event),
+            DeferredEvent::AlternateScreen(active) => {
+                let _ = events::emit_to(app, window, AlternateScreen { session_id, active });
+            }
+        }
+    }
+}
+
+/// Per-session state for reacting to escape sequences, owned by the reader thread
+struct OutputHandler {
+    images: SessionImages,
+    input_modes: Arc<Mutex<InputModes>>,
+    title: Arc<Mutex<SessionTitle>>,
+    profile: Arc<Mutex<SessionProfile>>,
+    prompts: PromptTracker,
+    /// Events found in the current chunk, emitted after its output
+    deferred_events: Vec<DeferredEvent>,
+    /// Command line of the running command, from its `C` mark
+    command_line: Option<String>,
+    last_command: Arc<Mutex<Option<CommandStats>>>,
+    integration: Arc<Mutex<IntegrationMarks>>,
+}
+
+impl OutputHandler {
+    fn new(
+        input_modes: Arc<Mutex<InputModes>>,
+        title: Arc<Mutex<SessionTitle>>,
+        profile: Arc<Mutex<SessionProfile>>,
+        last_command: Arc<Mutex<Option<CommandStats>>>,
+        integration: Arc<Mutex<IntegrationMarks>>,
+    ) -> Self {
+        Self {
+            images: SessionImages::new(),
+            input_modes,
+            title,
+            profile,
+            prompts: PromptTracker::new(),
+            deferred_events: Vec::new(),
+            command_line: None,
+            last_command,
+            integration,
+        }
+    }
+
+    /// Keep a finished command's stats for `get_last_command_stats`
+    fn record_command(&mut self, event: &PromptEvent) {
+        if let PromptEvent::CommandEnd {
+            exit_code,
+            duration,
+        } = *event
+        {
+            *self.last_command.lock() = Some(CommandStats {
+                command: self
+                    .command_line
+                    .take()
+                    .map(|command| redact::redact(&command)),
+                exit_code,
+                duration_ms: duration.as_millis() as u64,
+                finished_at: unix_secs(Instant::now()),
+            });
+        }
+    }
+
+    /// Take the events found since the last call
+    fn take_deferred_events(&mut self) -> Vec<DeferredEvent> {
+        std::mem::take(&mut self.deferred_events)
+    }
+
+    /// Re-resolve the directory profile if the session's cwd changed
+    fn update_profile(&mut self, app: &AppHandle, session_id: &str) {
+        let cwd = self.title.lock().cwd().map(String::from);
+        if let Some(cwd) = cwd {
+            self.profile.lock().update_dir(app, session_id, &cwd);
+        }
+    }
+
+    /// React to an escape sequence found in a session's output.
+    /// Returns a response to write back to the PTY, if the sequence expects one.
+    fn handle(&mut self, app: &AppHandle, session_id: &str, event: VtEvent) -> Option<String> {
+        match event {
+            VtEvent::Osc { command, data } => match command.as_str() {
+                "0" | "2" => self.title.lock().set_program_title(&data),
+                "7" => {
+                    if let Some(cwd) = tab_title::parse_osc7(&data) {
+                        self.integration.lock().cwd = true;
+                        let changed = self.title.lock().set_reported_cwd(cwd.clone());
+                        if changed {
+                            if let Some(recent_dirs) = app.try_state::<Arc<RecentDirsManager>>() {
+                                recent_dirs.record(&cwd);
+                            }
+                            if let Some(store) = app.try_state::<Arc<SessionStore>>() {
+                                store.session_cwd_changed(session_id, &cwd);
+                            }
+                        }
+                    }
+                }
+                "9" => {
+                    if let Some(update) = progress::parse_progress(&data) {
+                        progress::apply_progress(app, session_id, update);
+                    } else if let Some(notification) = notifications::parse_osc9(&data) {
+                        notifications::post_notification(app, session_id, notification);
+                    }
+                }
+                "777" => {
+                    if let Some(notification) = notifications::parse_osc777(&data) {
+                        notifications::post_notification(app, session_id, notification);
+                    }
+                }
+                "133" => {
+                    if let Some(mark) = shell_integration::parse_mark(&data) {
+                        let mut integration = self.integration.lock();
+                        integration.record(mark);
+                        if mark == PromptMark::CommandExecuted {
+                            self.command_line = shell_integration::parse_command_line(&data);
+                            integration.command_line |= self.command_line.is_some();
+                        }
+                        drop(integration);
+                        if let Some(event) = self.prompts.handle(mark, Instant::now()) {
+                            self.record_command(&event);
+                            self.deferred_events.push(DeferredEvent::Prompt(event));
+                        }
+                    }
+                    if let Some(exit_code) = accessibility::parse_command_finished(&data) {
+                        accessibility::announce(
+                            app,
+                            session_id,
+                            AccessibilityEvent::CommandFinished { exit_code },
+                        );
+                    }
+                }
+                "52" => return clipboard::handle_osc52(app, session_id, &data),
+                "1337" => self.images.handle_iterm2(app, session_id, &data),
+                _ => {}
+            },
+            VtEvent::Bell => {
+                accessibility::announce(app, session_id, AccessibilityEvent::Bell);
+                let title = self.title.lock().rendered().map(String::from);
+                notifications::ring_bell(app, session_id, title.as_deref());
+            }
+            VtEvent::Enq => return answerback::enq_response(&session_settings(app)),
+            VtEvent::PrimaryDeviceAttributes => {
+                return Some(answerback::device_attributes_response(
+                    &session_settings(app),
+                    false,
+                ));
+            }
+            VtEvent::Apc { data } => return self.images.handle_kitty(app, session_id, &data),
+            VtEvent::Csi {
+                marker,
+                params,
+                intermediates,
+                final_char,
+            } if intermediates.is_empty() => match (marker, final_char) {
+                (_, 'u') => {
+                    let enabled = app
+                        .try_state::<Arc<SettingsManager>>()
+                        .is_some_and(|settings| settings.get().kitty_keyboard_protocol);
+                    return self
+                        .input_modes
+                        .lock()
+                        .handle_kitty_csi(marker, &params, enabled);
+                }
+                ('>', 'c') if params.is_empty() || params == "0" => {
+                    return Some(answerback::device_attributes_response(
+                        &session_settings(app),
+                        true,
+                    ));
+                }
+                ('?', 'h' | 'l') => {
+                    let mut modes = self.input_modes.lock();
+                    for mode in params.split(';').filter_map(|p| p.parse().ok()) {
+                        let alternate_screen = modes.alternate_screen();
+                        modes.set_private_mode(mode, final_char == 'h');
+                        if modes.alternate_screen() != alternate_screen {
+                            self.deferred_events
+                                .push(DeferredEvent::AlternateScreen(!alternate_screen));
+                        }
+                    }
+                }
+                _ => {}
+            },
+            VtEvent::Csi { .. } => {}
+        }
+        None
+    }
+}
+
+/// Options for a new session
+#[derive(Debug, Clone, Default)]
+pub struct SessionOptions {
+    /// Starting directory (defaults to the home directory)
+    pub cwd: Option<PathBuf>,
+    /// Command run with `<shell> -l -c` instead of an interactive shell
+    pub command: Option<String>,
+    /// Program run directly instead of a shell, e.g. `htop`; looked up in the
+    /// session's PATH. Takes precedence over `command`.
+    pub program: Option<String>,
+    /// Arguments of `program`
+    pub args: Vec<String>,
+    /// Session ID to use instead of a generated one, so callers can register
+    /// per-session state before the session can exit
+    pub session_id: Option<String>,
+    /// Keychain items to export into the session's environment; unlike the
+    /// profile's, these must all be found
+    pub secrets: Vec<KeychainSecret>,
+    /// Name of the backend to start the session with (defaults to the
+    /// manager's default backend, normally the local PTY)
+    pub backend: Option<String>,
+    /// Label of the window the session's output goes to (defaults to the
+    /// main window)
+    pub window: Option<String>,
+    /// Command typed into the shell once it starts, after the profile's
+    pub startup_command: Option<String>,
+    /// Name of the profile to start with instead of the directory's (e.g. a
+    /// bookmark's); ignored when no such profile exists
+    pub profile: Option<String>,
+    /// Environment variables set after the settings' and profile's
+    pub env: Vec<(String, String)>,
+}
+
+impl SessionOptions {
+    /// What the session runs instead of an interactive shell, if anything
+    fn command_line(&self) -> Option<String> {
+        match &self.program {
+            Some(program) => Some(
+                std::iter::once(program)
+                    .chain(&self.args)
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            None => self.command.clone(),
+        }
+    }
+}
+
+/// Window sessions belong to unless another is given
+pub const MAIN_WINDOW_LABEL: &str = "main";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PtyOutput {
     pub session_id: String,
     pub data: String,
 }
 
+/// Payload of `pty-alternate-screen`: a full-screen program such as vim or
+/// less switched to the alternate screen buffer, or back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlternateScreen {
+    pub session_id: String,
+    pub active: bool,
+}
+
+/// Output dropped because a session exceeded `output_rate_limit_kib`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PtyOutputTruncated {
+    pub session_id: String,
+    pub dropped_bytes: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PtyExit {
     pub session_id: String,
+    /// `None` when the session was closed; 128 + the signal number when the
+    /// shell was killed by a signal
     pub exit_code: Option<i32>,
+    /// Signal that killed the shell, such as `SIGKILL`
+    pub signal: Option<String>,
+}
+
+/// Payload of the `session-cleared` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionCleared {
+    pub session_id: String,
+    /// The terminal should also do a full reset (RIS), not just clear
+    pub reset: bool,
+}
+
+/// Payload of the `session-moved` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionMoved {
+    pub session_id: String,
+    pub from_window: String,
+    pub to_window: String,
+}
+
+/// Why input couldn't be written to a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum WriteError {
+    SessionNotFound {
+        session_id: String,
+    },
+    /// The session is in read-only mode
+    ReadOnly {
+        session_id: String,
+    },
+    /// The app is locked (see `lock.rs`)
+    Locked,
+    Io {
+        message: String,
+    },
+}
+
+impl std::fmt::Display for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WriteError::SessionNotFound { session_id } => {
+                f.write_str(&session_not_found(session_id))
+            }
+            WriteError::ReadOnly { session_id } => {
+                f.write_str(&i18n::tr("pty.read_only", &[("session_id", session_id)]))
+            }
+            WriteError::Locked => f.write_str(&i18n::tr("app.locked", &[])),
+            WriteError::Io { message } => f.write_str(message),
+        }
+    }
+}
+
+impl From<WriteError> for String {
+    fn from(e: WriteError) -> Self {
+        e.to_string()
+    }
+}
+
+/// State of a live session, as reported by the status endpoint and `list_pty_sessions`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub cwd: Option<String>,
+    /// Tab title last emitted with `tab-title-changed`
+    pub title: Option<String>,
+    /// Default name from the profile, cwd and creation time, e.g.
+    /// `work ~/api 09:14` (see `session_name.rs`)
+    pub name: String,
+    /// Running foreground command (with secrets redacted), or `None` at the prompt
+    pub running_command: Option<String>,
+    /// Seconds since the last input or output
+    pub idle_secs: u64,
+    /// Last input and output, in seconds since the Unix epoch; `None` if there was none yet
+    pub last_input_at: Option<u64>,
+    pub last_output_at: Option<u64>,
+    pub suspended: bool,
+    pub protected: bool,
+    pub read_only: bool,
+    pub labels: SessionLabels,
+    /// Label of the window the session belongs to
+    pub window: String,
+    /// Whether a terminal pane shows the session
+    pub attached: bool,
+    /// Whether a full-screen program is on the alternate screen
+    pub alternate_screen: bool,
+}
+
+/// A session's foreground process, as returned by `get_foreground_process`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForegroundProcess {
+    /// Leader of the terminal's foreground process group
+    pub pid: u32,
+    /// Process name, e.g. `vim`; `None` if it already exited
+    pub name: Option<String>,
+    /// Whether it's the session's shell waiting at its prompt
+    pub at_prompt: bool,
+}
+
+/// A process started from a session's shell
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChildProcess {
+    pub pid: u32,
+    /// Process name, e.g. `rsync`; `None` if it already exited
+    pub name: Option<String>,
+}
+
+/// Result of `session_has_running_process`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunningProcesses {
+    /// Whether closing the session would kill something besides an idle shell
+    pub running: bool,
+    /// What would be killed, closest to the shell first
+    pub processes: Vec<ChildProcess>,
+}
+
+/// Scrollback hits for one session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSearchResult {
+    pub session_id: String,
+    pub hits: Vec<ScrollbackHit>,
+}
+
+/// PTY input, shared by user writes and the reply thread; never locked
+/// together with the session
+type SharedWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+
+/// Replies queued for the reply thread at most; more are dropped
+const MAX_QUEUED_REPLIES: usize = 64;
+
+/// Writes replies to the program's queries (device attributes, answerback,
+/// kitty flags, OSC 52 reads) on its own thread. The reader thread is the
+/// only one draining the program's output, so it must never wait for a write
+/// that may block on a full PTY input buffer.
+struct ReplyWriter {
+    sender: SyncSender<String>,
+}
+
+impl ReplyWriter {
+    fn spawn(writer: SharedWriter) -> Result<Self, String> {
+        let (sender, receiver) = mpsc::sync_channel::<String>(MAX_QUEUED_REPLIES);
+        thread::Builder::new()
+            .name("pty-reply".to_string())
+            .spawn(move || {
+                for reply in receiver {
+                    let mut writer = writer.lock();
+                    let _ = writer.write_all(reply.as_bytes());
+                    let _ = writer.flush();
+                }
+            })
+            .map_err(|e| format!("Failed to start reply thread: {}", e))?;
+        Ok(Self { sender })
+    }
+
+    /// Queue a reply without blocking; false if it was dropped
+    fn send(&self, reply: String) -> bool {
+        self.sender.try_send(reply).is_ok()
+    }
+}
+
+/// The session's process, shared with the reader thread for title polling;
+/// only held for the duration of a call
+type SharedProcess = Arc<Mutex<Box<dyn SessionProcess>>>;
+
+/// Poll the foreground process name and the shell's cwd for the tab title,
+/// without the session lock (the reader thread must not take it)
+fn poll_title_inputs(
+    process: &SharedProcess,
+    child_pid: Option<u32>,
+) -> (Option<String>, Option<String>) {
+    let foreground = process.lock().foreground_process();
+    (
+        foreground.and_then(process_name),
+        child_pid.and_then(process_cwd),
+    )
 }
 
 struct PtySession {
-    #[allow(dead_code)]
-    pair: PtyPair,
-    writer: Box<dyn Write + Send>,
-    child: Box<dyn Child + Send + Sync>,
+    process: SharedProcess,
+    writer: SharedWriter,
     child_pid: Option<u32>,
     reader_thread: Option<JoinHandle<()>>,
     shutdown_flag: Arc<AtomicBool>,
+    /// Plain-text output history, shared with the reader thread
+    scrollback: Arc<Mutex<ScrollbackBuffer>>,
+    /// Raw output tail for reattaching, shared with the reader thread
+    replay: Arc<Mutex<ReplayBuffer>>,
+    /// Transcript output is appended to while logging, shared with the
+    /// reader thread
+    transcript: Arc<Mutex<Option<TranscriptLog>>>,
+    /// Last command finished with OSC 133 marks, set by the reader thread
+    last_command: Arc<Mutex<Option<CommandStats>>>,
+    /// Shell integration sequences seen, updated by the reader thread
+    integration: Arc<Mutex<IntegrationMarks>>,
+    /// Input modes negotiated by the program, updated by the reader thread
+    input_modes: Arc<Mutex<InputModes>>,
+    /// Tab title inputs, updated by the reader thread
+    title: Arc<Mutex<SessionTitle>>,
+    /// Directory profile, re-resolved by the reader thread as the cwd changes
+    profile: Arc<Mutex<SessionProfile>>,
+    /// Profile the session started with
+    start_profile: Option<String>,
+    /// When the session was created
+    created_at: Instant,
+    /// Time of the last input or output
+    last_activity: Arc<Mutex<Instant>>,
+    /// Time of the last write to the session
+    last_input: Option<Instant>,
+    /// Time of the last output, updated by the reader thread
+    last_output: Arc<Mutex<Option<Instant>>>,
+    /// How the idle shell was suspended, if it is
+    suspended: Option<IdleSuspendMode>,
+    /// Command run instead of an interactive shell
+    command: Option<String>,
+    /// User-assigned tags and color
+    labels: SessionLabels,
+    /// Protected sessions are only closed when forced
+    protected: bool,
+    /// Read-only sessions reject all input
+    read_only: bool,
+    /// Label of the window output and prompt events go to, shared with the
+    /// reader thread
+    window: Arc<Mutex<String>>,
+    /// Whether a terminal pane shows the session (see `orphans.rs`)
+    attached: bool,
+    /// How the shell ended, once `try_wait` saw it exit
+    exit_status: Option<ExitStatus>,
+    /// When the session was found exited without a pane
+    orphaned_at: Option<Instant>,
+}
+
+impl PtySession {
+    /// Working directory reported with OSC 7, else the shell process's
+    fn cwd(&self) -> Option<String> {
+        let reported = self.title.lock().reported_cwd().map(String::from);
+        reported.or_else(|| self.child_pid.and_then(process_cwd))
+    }
+
+    /// Default name for the session switcher, from the profile, `cwd` and
+    /// creation time
+    fn auto_name(&self, cwd: Option<&str>) -> String {
+        let home = std::env::var("HOME").ok();
+        let created = session_name::local_hour_minute(unix_secs(self.created_at));
+        let profile = self.profile.lock();
+        session_name::auto_name(
+            profile.name_or(self.start_profile.as_deref()),
+            cwd,
+            home.as_deref(),
+            created,
+        )
+    }
+
+    /// The terminal's foreground process (`tcgetpgrp` on the master), if known
+    fn foreground(&self) -> Option<ForegroundProcess> {
+        let pid = self.process.lock().foreground_process()?;
+        Some(ForegroundProcess {
+            pid,
+            name: process_name(pid),
+            at_prompt: self.command.is_none() && Some(pid) == self.child_pid,
+        })
+    }
+
+    /// Processes that closing the session would kill, other than an idle
+    /// shell and its prompt helpers
+    fn running_processes(&mut self) -> Vec<ChildProcess> {
+        let Some(shell) = self.child_pid else {
+            return Vec::new();
+        };
+        if self.check_exit().is_some() {
+            return Vec::new();
+        }
+        let mut pids = process_tree::descendants(shell);
+        // A command session runs until its `$SHELL -c` exits
+        if self.command.is_some() {
+            pids.insert(0, shell);
+        }
+        pids.into_iter()
+            .map(|pid| ChildProcess {
+                pid,
+                name: process_name(pid),
+            })
+            .filter(|process| {
+                !process
+                    .name
+                    .as_deref()
+                    .is_some_and(process_tree::is_shell_helper)
+            })
+            .collect()
+    }
+
+    /// Foreground process group leader, if the session is running a command
+    /// rather than sitting at the shell prompt
+    fn running_command(&self) -> Option<u32> {
+        let pgid = self.process.lock().foreground_process()?;
+        (self.command.is_some() || Some(pgid) != self.child_pid).then_some(pgid)
+    }
+
+    /// Arguments of the running foreground command, if any
+    fn running_command_args(&self) -> Option<Vec<String>> {
+        let pgid = self.running_command()?;
+        match &self.command {
+            // The session's own `$SHELL -c` command
+            Some(command) if Some(pgid) == self.child_pid => {
+                Some(command.split_whitespace().map(String::from).collect())
+            }
+            _ => process_args(pgid),
+        }
+    }
+
+    /// How the shell ended, if it has; doesn't block
+    fn check_exit(&mut self) -> Option<ExitStatus> {
+        if self.exit_status.is_none() {
+            self.exit_status = self.process.lock().try_wait().ok().flatten();
+        }
+        self.exit_status.clone()
+    }
+
+    /// Suspend the shell if it has been idle long enough and is at its prompt
+    fn suspend_if_idle(&mut self, idle_for: Duration, mode: IdleSuspendMode) -> bool {
+        if self.suspended.is_some() || self.last_activity.lock().elapsed() < idle_for {
+            return false;
+        }
+        let Some(pid) = self.child_pid else {
+            return false;
+        };
+        // A foreground process group other than the shell's is a running command
+        let at_prompt = self.process.lock().foreground_process() == Some(pid);
+        if !at_prompt || !idle::suspend(pid, mode) {
+            return false;
+        }
+        self.suspended = Some(mode);
+        true
+    }
+
+    /// Resume a suspended shell and mark the session active
+    fn resume(&mut self) -> bool {
+        *self.last_activity.lock() = Instant::now();
+        match (self.suspended.take(), self.child_pid) {
+            (Some(mode), Some(pid)) => {
+                idle::resume(pid, mode);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Seconds since the Unix epoch at `instant`
+fn unix_secs(instant: Instant) -> u64 {
+    SystemTime::now()
+        .checked_sub(instant.elapsed())
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Name of a process
+#[cfg(target_os = "macos")]
+fn process_name(pid: u32) -> Option<String> {
+    use std::os::raw::{c_char, c_int, c_void};
+
+    extern "C" {
+        fn proc_name(pid: c_int, buffer: *mut c_void, buffersize: u32) -> c_int;
+    }
+
+    // MAXCOMLEN is 16; proc_name accepts up to 2 * MAXCOMLEN
+    let mut buffer = [0 as c_char; 64];
+    let len = unsafe {
+        proc_name(
+            pid as c_int,
+            buffer.as_mut_ptr() as *mut c_void,
+            buffer.len() as u32,
+        )
+    };
+    if len <= 0 {
+        return None;
+    }
+    let bytes: Vec<u8> = buffer[..len as usize].iter().map(|&c| c as u8).collect();
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn process_name(pid: u32) -> Option<String> {
+    std::fs::read_to_string(format!("/proc/{}/comm", pid))
+        .ok()
+        .map(|name| name.trim_end().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Command line arguments of a process
+#[cfg(target_os = "macos")]
+fn process_args(pid: u32) -> Option<Vec<String>> {
+    let mut mib = [libc::CTL_KERN, libc::KERN_PROCARGS2, pid as libc::c_int];
+    let mut size: libc::size_t = 0;
+    // SAFETY: a null buffer asks sysctl for the required size
+    let ret = unsafe {
+        libc::sysctl(
+            mib.as_mut_ptr(),
+            mib.len() as libc::c_uint,
+            std::ptr::null_mut(),
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if ret != 0 || size < 4 {
+        return None;
+    }
+    let mut buffer = vec![0u8; size];
+    // SAFETY: the buffer is `size` bytes long
+    let ret = unsafe {
+        libc::sysctl(
+            mib.as_mut_ptr(),
+            mib.len() as libc::c_uint,
+            buffer.as_mut_ptr() as *mut libc::c_void,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if ret != 0 || size < 4 {
+        return None;
+    }
+
+    // Layout: argc, the executable path, NUL padding, then argc NUL-terminated args
+    let argc = i32::from_ne_bytes(buffer[..4].try_into().ok()?) as usize;
+    let args: Vec<String> = buffer[4..size]
+        .split(|&b| b == 0)
+        .filter(|s| !s.is_empty())
+        .skip(1)
+        .take(argc)
+        .map(|s| String::from_utf8_lossy(s).into_owned())
+        .collect();
+    (!args.is_empty()).then_some(args)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn process_args(pid: u32) -> Option<Vec<String>> {
+    let cmdline = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let args: Vec<String> = cmdline
+        .split(|&b| b == 0)
+        .filter(|s| !s.is_empty())
+        .map(|s| String::from_utf8_lossy(s).into_owned())
+        .collect();
+    (!args.is_empty()).then_some(args)
+}
+
+/// Current working directory of a process
+#[cfg(target_os = "macos")]
+fn process_cwd(pid: u32) -> Option<String> {
+    // Use libproc to get the current working directory
+    use std::ffi::CStr;
+    use std::mem::MaybeUninit;
+    use std::os::raw::{c_char, c_int};
+
+    // Constants from sys/proc_info.h
+    const PROC_PIDVNODEPATHINFO: c_int = 9;
+
+    #[repr(C)]
+    struct vnode_info_path {
+        _vip_vi: [u8; 152],       // vnode_info structure (we don't need its contents)
+        vip_path: [c_char; 1024], // MAXPATHLEN
+    }
+
+    #[repr(C)]
+    struct proc_vnodepathinfo {
+        pvi_cdir: vnode_info_path,
+        pvi_rdir: vnode_info_path,
+    }
+
+    extern "C" {
+        fn proc_pidinfo(
+            pid: c_int,
+            flavor: c_int,
+            arg: u64,
+            buffer: *mut std::ffi::c_void,
+            buffersize: c_int,
+        ) -> c_int;
+    }
+
+    let mut info: MaybeUninit<proc_vnodepathinfo> = MaybeUninit::uninit();
+    let info_size = std::mem::size_of::<proc_vnodepathinfo>() as c_int;
+
+    let ret = unsafe {
+        proc_pidinfo(
+            pid as c_int,
+            PROC_PIDVNODEPATHINFO,
+            0,
+            info.as_mut_ptr() as *mut std::ffi::c_void,
+            info_size,
+        )
+    };
+
+    if ret <= 0 {
+        return None;
+    }
+
+    let info = unsafe { info.assume_init() };
+    let cwd = unsafe { CStr::from_ptr(info.pvi_cdir.vip_path.as_ptr()) };
+
+    match cwd.to_str() {
+        Ok(s) if !s.is_empty() => Some(s.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn process_cwd(pid: u32) -> Option<String> {
+    // On non-macOS platforms, read /proc/<pid>/cwd
+    std::fs::read_link(format!("/proc/{}/cwd", pid))
+        .ok()
+        .map(|path| path.to_string_lossy().to_string())
 }
 
 pub struct PtyManager {
     sessions: Arc<Mutex<HashMap<String, Arc<Mutex<PtySession>>>>>,
+    /// Backend used when `SessionOptions::backend` isn't set
+    default_backend: Arc<dyn SessionBackend>,
+    /// Backends by name, including the default one
+    backends: Mutex<HashMap<&'static str, Arc<dyn SessionBackend>>>,
+    /// Directory transcripts are written to, set once the app data
+    /// directory is known
+    log_dir: Mutex<Option<PathBuf>>,
 }
 
 impl PtyManager {
     pub fn new() -> Self {
+        Self::with_backend(Arc::new(LocalPtyBackend))
+    }
+
+    /// Manager starting sessions through `backend` by default (e.g. the mock
+    /// in tests)
+    pub fn with_backend(backend: Arc<dyn SessionBackend>) -> Self {
         Self {
             sessions: Arc::new(Mutex::new(HashMap::new())),
+            backends: Mutex::new(HashMap::from([(backend.name(), backend.clone())])),
+            default_backend: backend,
+            log_dir: Mutex::new(None),
+        }
+    }
+
+    /// Set the directory `start_session_log` writes transcripts to
+    pub fn set_log_dir(&self, dir: PathBuf) {
+        *self.log_dir.lock() = Some(dir);
+    }
+
+    fn open_log(
+        &self,
+        session_id: &str,
+        format: TranscriptFormat,
+    ) -> Result<TranscriptLog, String> {
+        let dir = self
+            .log_dir
+            .lock()
+            .clone()
+            .ok_or("Transcript directory is not set")?;
+        TranscriptLog::start(&dir, session_id, format)
+    }
+
+    /// Make a backend available to `SessionOptions::backend`, replacing one
+    /// with the same name
+    pub fn register_backend(&self, backend: Arc<dyn SessionBackend>) {
+        self.backends.lock().insert(backend.name(), backend);
+    }
+
+    /// The backend called `name`, or the default one
+    fn backend(&self, name: Option<&str>) -> Result<Arc<dyn SessionBackend>, String> {
+        match name {
+            None => Ok(self.default_backend.clone()),
+            Some(name) => self
+                .backends
+                .lock()
+                .get(name)
+                .cloned()
+                .ok_or_else(|| format!("Unknown session backend: {}", name)),
         }
     }
 
-    pub fn create_session(&self, app: AppHandle, cols: u16, rows: u16) -> Result<String, String> {
+    pub fn create_session(
+        &self,
+        app: AppHandle,
+        cols: u16,
+        rows: u16,
+        options: SessionOptions,
+    ) -> Result<String, String> {
         // Validate PTY dimensions before creating session
         validate_pty_size(cols, rows)?;
 
-        let session_id = uuid::Uuid::new_v4().to_string();
+        let session_id = options
+            .session_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
         debug!(session_id = %session_id, cols, rows, "Creating PTY session");
 
-        let pty_system = native_pty_system();
-        let pair = pty_system
-            .openpty(PtySize {
-                rows,
-                cols,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
-            .map_err(|e| format!("Failed to open PTY: {}", e))?;
-
-        // Get the user's default shell
-        let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
+        let settings = session_settings(&app);
         let home = std::env::var("HOME").unwrap_or_else(|_| "/".to_string());
 
-        let mut cmd = CommandBuilder::new(&shell);
-        cmd.cwd(&home);
+        // A profile given by name also picks the directory; otherwise the
+        // directory picks the profile
+        let named_profile = options
+            .profile
+            .as_deref()
+            .and_then(|name| profiles::find(&settings, name).cloned());
+        let start_dir = options
+            .cwd
+            .clone()
+            .or_else(|| named_profile.as_ref()?.start_dir(&home))
+            .unwrap_or_else(|| PathBuf::from(&home));
+        let profile =
+            named_profile.or_else(|| profiles::resolve_for_dir(&app, &start_dir.to_string_lossy()));
+        let (shell, shell_args) = resolve_shell(&settings, profile.as_ref());
+
+        let mut request = SpawnRequest {
+            program: shell.clone(),
+            args: shell_args,
+            cwd: start_dir,
+            cols,
+            rows,
+            ..Default::default()
+        };
+        if let Some(program) = &options.program {
+            request.program = program.clone();
+            request.args = options.args.clone();
+        } else if let Some(command) = &options.command {
+            request.args = vec!["-l".to_string(), "-c".to_string(), command.clone()];
+        }
 
         // Set up environment variables for proper terminal behavior
-        cmd.env("TERM", "xterm-256color");
-        cmd.env("COLORTERM", "truecolor");
+        request.env("TERM", "xterm-256color");
+        request.env("COLORTERM", "truecolor");
 
         info!(session_id = %session_id, "Setting PTY environment: TERM=xterm-256color, COLORTERM=truecolor");
 
         // Inherit important environment variables for shell compatibility
-        cmd.env("HOME", &home);
-        cmd.env("SHELL", &shell);
+        request.env("HOME", &home);
+        request.env("SHELL", &shell);
         if let Ok(user) = std::env::var("USER") {
-            cmd.env("USER", user);
+            request.env("USER", user);
         }
         if let Ok(lang) = std::env::var("LANG") {
-            cmd.env("LANG", lang);
+            request.env("LANG", lang);
         } else {
-            cmd.env("LANG", "en_US.UTF-8");
-        }
-
-        // Build PATH with common tool locations
-        // macOS GUI apps don't inherit shell PATH, so we need to include common paths
-        let mut path_dirs: Vec<String> = Vec::new();
-
-        // Add user's local bin directories first (highest priority)
-        if !home.is_empty() {
-            path_dirs.push(format!("{}/bin", home));
-            path_dirs.push(format!("{}/.local/bin", home));
-        }
-
-        // Add common system paths
-        path_dirs.extend([
-            "/opt/homebrew/bin".to_string(), // Homebrew on Apple Silicon
-            "/opt/homebrew/sbin".to_string(),
-            "/usr/local/bin".to_string(), // Homebrew on Intel Mac
-            "/usr/local/sbin".to_string(),
-            "/usr/bin".to_string(),
-            "/bin".to_string(),
-            "/usr/sbin".to_string(),
-            "/sbin".to_string(),
-        ]);
-
-        // Append any existing PATH from the environment
-        let base_path = path_dirs.join(":");
-        let full_path = if let Ok(existing_path) = std::env::var("PATH") {
-            format!("{}:{}", base_path, existing_path)
-        } else {
-            base_path
-        };
-        cmd.env("PATH", full_path);
+            request.env("LANG", "en_US.UTF-8");
+        }
+
+        let full_path = build_pty_path(&home, std::env::var("PATH").ok().as_deref());
+        request.env("PATH", full_path);
         // LC_ALL for proper locale handling
         if let Ok(lc_all) = std::env::var("LC_ALL") {
-            cmd.env("LC_ALL", lc_all);
+            request.env("LC_ALL", lc_all);
         }
 
-        // Spawn the shell process
-        let child = pair
-            .slave
-            .spawn_command(cmd)
-            .map_err(|e| format!("Failed to spawn shell: {}", e))?;
+        // Environment from settings, overridden by the directory profile's
+        for (name, value) in &settings.env {
+            if profiles::is_env_name(name) {
+                request.env(name, value);
+            }
+        }
+
+        // Directory profile environment
+        if let Some(profile) = &profile {
+            info!(session_id = %session_id, profile = %profile.name, "Applying directory profile");
+            request.env(profiles::PROFILE_ENV_VAR, &profile.name);
+            for (name, value) in &profile.env {
+                if profiles::is_env_name(name) {
+                    request.env(name, value);
+                } else {
+                    warn!(profile = %profile.name, name = %name, "Ignoring invalid environment variable name");
+                }
+            }
+        }
+        for (name, value) in &options.env {
+            request.env(name, value);
+        }
+
+        if let Some(path) = askpass::helper_path() {
+            request.env(askpass::ASKPASS_ENV_VAR, path.to_string_lossy());
+        }
+
+        // Keychain secrets: missing profile items are skipped, requested ones fail
+        if let Some(profile) = &profile {
+            for secret in &profile.keychain_env {
+                match keychain::fetch(secret) {
+                    Ok(value) => {
+                        request.env(&secret.env, value);
+                    }
+                    Err(e) => {
+                        warn!(profile = %profile.name, name = %secret.env, "Skipping Keychain secret: {}", e)
+                    }
+                }
+            }
+        }
+        for secret in &options.secrets {
+            request.env(&secret.env, keychain::fetch(secret)?);
+        }
+
+        let start_cwd = request.cwd.to_string_lossy().into_owned();
+
+        // Open the PTY and spawn the shell process
+        let backend = self.backend(options.backend.as_deref())?;
+        let mut process = backend.spawn(request)?;
 
         // Get the child process ID for CWD tracking
-        let child_pid = child.process_id();
+        let child_pid = process.pid();
 
         // Get the writer for sending input to the PTY
-        let writer = pair
-            .master
-            .take_writer()
-            .map_err(|e| format!("Failed to get PTY writer: {}", e))?;
+        let mut writer = process.take_writer()?;
+
+        let start_profile = profile.as_ref().map(|p| p.name.clone());
+        // Type the startup commands; the shell reads them once it's ready
+        let command = options.command_line();
+        if command.is_none() {
+            let startup_commands = profile
+                .and_then(|p| p.startup_command)
+                .into_iter()
+                .chain(options.startup_command.clone());
+            for startup_command in startup_commands {
+                let _ = writer.write_all(format!("{}\r", startup_command).as_bytes());
+            }
+            let _ = writer.flush();
+        }
 
         // Get the reader for receiving output from the PTY
-        let mut reader = pair
-            .master
-            .try_clone_reader()
-            .map_err(|e| format!("Failed to get PTY reader: {}", e))?;
+        let mut reader = process.take_reader()?;
 
         // Create shutdown flag for clean thread termination
         let shutdown_flag = Arc::new(AtomicBool::new(false));
         let shutdown_flag_clone = shutdown_flag.clone();
 
+        let scrollback = Arc::new(Mutex::new(ScrollbackBuffer::default()));
+        let scrollback_for_thread = scrollback.clone();
+        let replay = Arc::new(Mutex::new(ReplayBuffer::default()));
+        let replay_for_thread = replay.clone();
+        let transcript = Arc::new(Mutex::new(None));
+        if settings.session_logging {
+            match self.open_log(&session_id, settings.session_log_format) {
+                Ok(log) => *transcript.lock() = Some(log),
+                Err(e) => warn!(session_id = %session_id, "Failed to start transcript: {}", e),
+            }
+        }
+        let transcript_for_thread = transcript.clone();
+        let last_command = Arc::new(Mutex::new(None));
+        let last_command_for_thread = last_command.clone();
+        let integration = Arc::new(Mutex::new(IntegrationMarks::default()));
+        let integration_for_thread = integration.clone();
+        let input_modes = Arc::new(Mutex::new(InputModes::new()));
+        let input_modes_for_thread = input_modes.clone();
+        let title = Arc::new(Mutex::new(SessionTitle::new()));
+        let title_for_thread = title.clone();
+        let profile = Arc::new(Mutex::new(SessionProfile::default()));
+        let profile_for_thread = profile.clone();
+        let last_activity = Arc::new(Mutex::new(Instant::now()));
+        let last_activity_for_thread = last_activity.clone();
+        let last_output = Arc::new(Mutex::new(None));
+        let last_output_for_thread = last_output.clone();
+        let window = Arc::new(Mutex::new(
+            options
+                .window
+                .clone()
+                .unwrap_or_else(|| MAIN_WINDOW_LABEL.to_string()),
+        ));
+        let window_for_thread = window.clone();
+
+        let writer: SharedWriter = Arc::new(Mutex::new(writer));
+        let process: SharedProcess = Arc::new(Mutex::new(process));
+        let process_for_thread = process.clone();
+        let reply_writer = ReplyWriter::spawn(writer.clone())?;
+
         let session = PtySession {
-            pair,
+            process,
             writer,
-            child,
             child_pid,
             reader_thread: None,
             shutdown_flag,
+            scrollback,
+            replay,
+            transcript,
+            last_command,
+            integration,
+            input_modes,
+            title,
+            profile,
+            start_profile,
+            created_at: Instant::now(),
+            last_activity,
+            last_input: None,
+            last_output,
+            suspended: None,
+            command,
+            labels: SessionLabels::default(),
+            protected: false,
+            read_only: false,
+            window,
+            attached: true,
+            exit_status: None,
+            orphaned_at: None,
         };
         let session_arc = Arc::new(Mutex::new(session));
         let session_arc_for_thread = session_arc.clone();
@@ -193,11 +1260,63 @@ impl PtyManager {
         let app_clone = app.clone();
         let sessions_clone = self.sessions.clone();
 
+        // Output is coalesced into frames before it's emitted
+        let output_app = app.clone();
+        let output_session_id = session_id_arc.clone();
+        let output_window = window_for_thread.clone();
+        let frame_interval = output_batch::frame_interval(settings.output_frame_interval_ms);
+        let emit_output = move |data| {
+            let window = output_window.lock().clone();
+            let _ = events::emit_to(
+                &output_app,
+                &window,
+                PtyOutput {
+                    session_id: output_session_id.to_string(),
+                    data,
+                },
+            );
+        };
+        let mut hooks = BatcherHooks::default();
+        if let Some(idle_after) = activity::idle_after(settings.activity_idle_seconds) {
+            let activity_app = app.clone();
+            let activity_session_id = session_id_arc.clone();
+            hooks = hooks.activity(ActivityTracker::new(idle_after), move |activity| {
+                activity::emit(&activity_app, activity_session_id.to_string(), activity)
+            });
+        }
+        if let Some(limit) = output_batch::rate_limit(settings.output_rate_limit_kib) {
+            let truncated_app = app.clone();
+            let truncated_session_id = session_id_arc.clone();
+            let truncated_window = window_for_thread.clone();
+            hooks = hooks.rate_limit(limit, move |dropped| {
+                warn!(session_id = %truncated_session_id, dropped, "Output over the rate limit dropped");
+                let window = truncated_window.lock().clone();
+                let _ = events::emit_to(
+                    &truncated_app,
+                    &window,
+                    PtyOutputTruncated {
+                        session_id: truncated_session_id.to_string(),
+                        dropped_bytes: dropped as u64,
+                    },
+                );
+            });
+        }
+        let output_batcher = OutputBatcher::spawn_with(frame_interval, emit_output, hooks)?;
+
         let reader_thread = thread::spawn(move || {
             // Use larger buffer for better throughput
             let mut buffer = [0u8; PTY_READ_BUFFER_SIZE];
-            // Buffer for incomplete UTF-8 sequences at boundary
-            let mut utf8_buffer: Vec<u8> = Vec::new();
+            // Holds back multi-byte characters split across reads
+            let mut utf8_decoder = Utf8Decoder::new();
+            // Picks out escape sequences the backend reacts to
+            let mut vt_scanner = VtScanner::new();
+            let mut output_handler = OutputHandler::new(
+                input_modes_for_thread,
+                title_for_thread.clone(),
+                profile_for_thread,
+                last_command_for_thread,
+                integration_for_thread,
+            );
 
             loop {
                 // Check if shutdown was requested
@@ -211,40 +1330,17 @@ impl PtyManager {
                         break;
                     }
                     Ok(n) => {
-                        // Combine any previous incomplete UTF-8 bytes with new data
-                        let mut full_buffer = utf8_buffer.clone();
-                        full_buffer.extend_from_slice(&buffer[..n]);
-                        utf8_buffer.clear();
-
-                        // Try to convert to UTF-8
-                        let data = match std::str::from_utf8(&full_buffer) {
-                            Ok(s) => s.to_string(),
-                            Err(e) => {
-                                // UTF-8 error - likely incomplete sequence at end
-                                let valid_up_to = e.valid_up_to();
-
-                                // Save incomplete bytes for next iteration
-                                // SAFETY: UTF-8 sequences are at most 4 bytes. If buffer exceeds this,
-                                // discard it to prevent memory leaks from malformed data
-                                if valid_up_to < full_buffer.len() {
-                                    let incomplete_len = full_buffer.len() - valid_up_to;
-                                    if incomplete_len <= 4 {
-                                        utf8_buffer.extend_from_slice(&full_buffer[valid_up_to..]);
-                                    } else {
-                                        // Malformed data exceeds max UTF-8 sequence length
-                                        warn!(
-                                            session_id = %session_id_for_thread,
-                                            incomplete_len = incomplete_len,
-                                            "Discarding malformed UTF-8 data exceeding 4 bytes"
-                                        );
-                                        utf8_buffer.clear();
-                                    }
-                                }
-
-                                // Convert valid portion
-                                String::from_utf8_lossy(&full_buffer[..valid_up_to]).to_string()
-                            }
-                        };
+                        let now = Instant::now();
+                        *last_activity_for_thread.lock() = now;
+                        *last_output_for_thread.lock() = Some(now);
+                        metrics::record_bytes_read(n);
+
+                        // Characters cut off at the end of the read are completed
+                        // by the next one
+                        let data = utf8_decoder.decode(&buffer[..n]);
+                        if data.is_empty() {
+                            continue;
+                        }
 
                         // Trace: Check for potential escape sequence fragmentation
                         // This helps identify if PTY buffer boundaries split multi-byte sequences
@@ -269,13 +1365,44 @@ impl PtyManager {
                             );
                         }
 
-                        let _ = app_clone.emit(
-                            "pty-output",
-                            PtyOutput {
-                                session_id: session_id_for_thread.to_string(),
-                                data,
-                            },
+                        scrollback_for_thread.lock().push(&data);
+                        replay_for_thread.lock().push(&data);
+                        if let Some(log) = transcript_for_thread.lock().as_mut() {
+                            log.push(&data);
+                        }
+
+                        for event in vt_scanner.scan(&data) {
+                            let response =
+                                output_handler.handle(&app_clone, &session_id_for_thread, event);
+                            if let Some(response) = response {
+                                if !reply_writer.send(response) {
+                                    debug!(session_id = %session_id_for_thread, "Reply queue full, reply dropped");
+                                }
+                            }
+                        }
+
+                        let poll_due = title_for_thread.lock().poll_due();
+                        if poll_due {
+                            let (process, cwd) = poll_title_inputs(&process_for_thread, child_pid);
+                            title_for_thread.lock().set_polled(process, cwd);
+                        }
+                        tab_title::update_title(
+                            &app_clone,
+                            &session_id_for_thread,
+                            &mut title_for_thread.lock(),
                         );
+                        output_handler.update_profile(&app_clone, &session_id_for_thread);
+
+                        output_batcher.push(data);
+                        for event in output_handler.take_deferred_events() {
+                            let app = app_clone.clone();
+                            let session_id = session_id_for_thread.to_string();
+                            let window = window_for_thread.clone();
+                            output_batcher.then(move || {
+                                let window = window.lock().clone();
+                                event.emit(&app, &window, session_id);
+                            });
+                        }
                     }
                     Err(e) => {
                         // Don't log error if shutdown was requested
@@ -287,75 +1414,273 @@ impl PtyManager {
                 }
             }
 
+            // Emit the remaining output before the exit
+            output_batcher.push(utf8_decoder.finish());
+            output_batcher.close();
+
             // Wait for the child process to exit (only if not shutdown)
-            let exit_code = if !shutdown_flag_clone.load(Ordering::SeqCst) {
-                let mut session_guard = session_arc_for_thread.lock();
-                session_guard.child.wait().ok().map(|status| {
-                    if status.success() {
-                        0
-                    } else {
-                        // portable-pty ExitStatus doesn't expose exit code directly
-                        1
-                    }
-                })
+            let status = if !shutdown_flag_clone.load(Ordering::SeqCst) {
+                let session_guard = session_arc_for_thread.lock();
+                // The orphan reaper may have collected the exit already
+                match session_guard.exit_status.clone() {
+                    Some(status) => Some(status),
+                    None => session_guard.process.lock().wait().ok(),
+                }
             } else {
                 None
             };
+            let exit_code = status.as_ref().map(|status| status.code);
 
             // Emit exit event
-            let _ = app_clone.emit(
-                "pty-exit",
+            let _ = events::emit(
+                &app_clone,
                 PtyExit {
                     session_id: session_id_for_thread.to_string(),
                     exit_code,
+                    signal: status.and_then(|status| status.signal),
                 },
             );
 
+            jobs::session_exited(&app_clone, &session_id_for_thread, exit_code);
+            ssh::session_exited(&app_clone, &session_id_for_thread, exit_code);
+            progress::clear_session(&app_clone, &session_id_for_thread);
+            status_strip::clear_session(&app_clone, &session_id_for_thread);
+            if let Some(manager) = app_clone.try_state::<Arc<NotificationManager>>() {
+                manager.remove_session(&session_id_for_thread);
+            }
+            if let Some(safe_delete) = app_clone.try_state::<Arc<SafeDelete>>() {
+                safe_delete.remove_session(&session_id_for_thread);
+            }
+            if let Some(store) = app_clone.try_state::<Arc<SessionStore>>() {
+                store.session_closed(&session_id_for_thread, exit_code);
+            }
+            // Sessions closed from the UI have no exit code and aren't announced
+            if exit_code.is_some() {
+                accessibility::announce(
+                    &app_clone,
+                    &session_id_for_thread,
+                    AccessibilityEvent::SessionClosed { exit_code },
+                );
+            }
+            if let Some(manager) = app_clone.try_state::<Arc<AccessibilityManager>>() {
+                manager.remove_session(&session_id_for_thread);
+            }
+
             // Remove session from map
             let mut sessions = sessions_clone.lock();
             sessions.remove(&session_id_for_cleanup);
         });
 
-        // Store the thread handle FIRST (before inserting into HashMap)
-        {
-            let mut session_guard = session_arc.lock();
-            session_guard.reader_thread = Some(reader_thread);
+        // Store the thread handle FIRST (before inserting into HashMap)
+        {
+            let mut session_guard = session_arc.lock();
+            session_guard.reader_thread = Some(reader_thread);
+        }
+
+        // NOW insert into HashMap - thread is already running and properly attached
+        // This ensures we never have orphaned sessions in the HashMap
+        {
+            let mut sessions = self.sessions.lock();
+            sessions.insert(session_id.clone(), session_arc);
+        }
+        metrics::record_session_created();
+        if let Some(store) = app.try_state::<Arc<SessionStore>>() {
+            store.session_created(&session_id, Some(&start_cwd));
+        }
+
+        info!(session_id = %session_id, "PTY session created successfully");
+        Ok(session_id)
+    }
+
+    pub fn write_to_session(&self, session_id: &str, data: &str) -> Result<(), WriteError> {
+        // Get the Arc<Mutex<PtySession>> under lock, then release immediately
+        // This prevents blocking all sessions during I/O on one session
+        let session_arc = {
+            let sessions = self.sessions.lock();
+            sessions
+                .get(session_id)
+                .cloned() // Clone the Arc (cheap - just incrementing ref count)
+                .ok_or_else(|| WriteError::SessionNotFound {
+                    session_id: session_id.to_string(),
+                })?
+        }; // sessions lock released here
+
+        // Now only hold the individual session lock during I/O
+        let mut session_guard = session_arc.lock();
+        if session_guard.read_only {
+            return Err(WriteError::ReadOnly {
+                session_id: session_id.to_string(),
+            });
+        }
+        if session_guard.resume() {
+            debug!(session_id = %session_id, "Resumed idle shell on input");
+        }
+        session_guard.last_input = Some(Instant::now());
+        let writer = session_guard.writer.clone();
+        // A large write can block until the program reads; don't keep the
+        // session locked meanwhile
+        drop(session_guard);
+        let mut writer = writer.lock();
+        writer
+            .write_all(data.as_bytes())
+            .map_err(|e| WriteError::Io {
+                message: format!("Failed to write to PTY: {}", e),
+            })?;
+        writer.flush().map_err(|e| WriteError::Io {
+            message: format!("Failed to flush PTY: {}", e),
+        })?;
+        metrics::record_bytes_written(data.len());
+
+        Ok(())
+    }
+
+    /// Report a window focus change to the window's sessions that enabled
+    /// focus reporting
+    pub fn report_focus(&self, window: &str, focused: bool) {
+        let sessions: Vec<(String, Arc<Mutex<InputModes>>)> = self
+            .sessions
+            .lock()
+            .iter()
+            .filter_map(|(id, session)| {
+                let session = session.lock();
+                let in_window = session.window.lock().as_str() == window;
+                in_window.then(|| (id.clone(), session.input_modes.clone()))
+            })
+            .collect();
+        for (session_id, input_modes) in sessions {
+            self.send_focus_report(&session_id, &input_modes, focused);
+        }
+    }
+
+    fn send_focus_report(&self, session_id: &str, input_modes: &Mutex<InputModes>, focused: bool) {
+        let report = input_modes.lock().focus_report(focused);
+        if let Some(report) = report {
+            if let Err(e) = self.write_to_session(session_id, report) {
+                debug!(session_id = %session_id, "Focus report not sent: {}", e);
+            }
+        }
+    }
+
+    /// Hand a session over to another window without restarting it: output
+    /// and prompt events go to `window` from now on, and the program gets the
+    /// window's focus state. Returns the window the session belonged to.
+    pub fn move_session(
+        &self,
+        session_id: &str,
+        window: &str,
+        focused: bool,
+    ) -> Result<String, String> {
+        let session = self
+            .sessions
+            .lock()
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| session_not_found(session_id))?;
+        let (previous, input_modes) = {
+            let session = session.lock();
+            let previous = std::mem::replace(&mut *session.window.lock(), window.to_string());
+            (previous, session.input_modes.clone())
+        };
+        if previous != window {
+            info!(session_id = %session_id, from = %previous, to = %window, "Session moved to window");
+            self.send_focus_report(session_id, &input_modes, focused);
+        }
+        Ok(previous)
+    }
+
+    /// Label of the window a session's output goes to
+    pub fn session_window(&self, session_id: &str) -> Result<String, String> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| session_not_found(session_id))?;
+        let window = session.lock().window.lock().clone();
+        Ok(window)
+    }
+
+    fn session_input_modes(&self, session_id: &str) -> Result<Arc<Mutex<InputModes>>, String> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| session_not_found(session_id))?;
+        let input_modes = session.lock().input_modes.clone();
+        Ok(input_modes)
+    }
+
+    /// Encode a key event with the session's input modes and write it.
+    /// Returns the bytes written, or `None` if the key produces no input.
+    pub fn send_key(&self, session_id: &str, event: &KeyEvent) -> Result<Option<String>, String> {
+        let input_modes = self.session_input_modes(session_id)?;
+        let encoded = {
+            let mut input_modes = input_modes.lock();
+            input_modes.set_keys_encoded();
+            input::encode_key(event, &input_modes)
+        };
+        if let Some(data) = &encoded {
+            self.write_to_session(session_id, data)?;
         }
+        Ok(encoded)
+    }
 
-        // NOW insert into HashMap - thread is already running and properly attached
-        // This ensures we never have orphaned sessions in the HashMap
-        {
-            let mut sessions = self.sessions.lock();
-            sessions.insert(session_id.clone(), session_arc);
+    /// Write a Cmd shortcut to the session if its foreground program is one
+    /// `passthrough` sends the shortcut to. Returns the bytes written, or
+    /// `None` when the app should handle the shortcut.
+    pub fn pass_shortcut(
+        &self,
+        session_id: &str,
+        event: &KeyEvent,
+        passthrough: &BTreeMap<String, Vec<String>>,
+    ) -> Result<Option<String>, String> {
+        let process = self
+            .foreground_process(session_id)?
+            .and_then(|foreground| foreground.name);
+        let shortcut = passthrough::shortcut_of(event);
+        let Some(process) =
+            process.filter(|process| passthrough::passes_through(passthrough, &shortcut, process))
+        else {
+            return Ok(None);
+        };
+        let input_modes = self.session_input_modes(session_id)?;
+        let encoded = input::encode_shortcut(event, &input_modes.lock());
+        if let Some(data) = &encoded {
+            debug!(session_id = %session_id, shortcut = %shortcut, process = %process, "Passing shortcut through");
+            self.write_to_session(session_id, data)?;
         }
-
-        info!(session_id = %session_id, "PTY session created successfully");
-        Ok(session_id)
+        Ok(encoded)
     }
 
-    pub fn write_to_session(&self, session_id: &str, data: &str) -> Result<(), String> {
-        // Get the Arc<Mutex<PtySession>> under lock, then release immediately
-        // This prevents blocking all sessions during I/O on one session
-        let session_arc = {
-            let sessions = self.sessions.lock();
-            sessions
-                .get(session_id)
-                .cloned() // Clone the Arc (cheap - just incrementing ref count)
-                .ok_or_else(|| format!("Session not found: {}", session_id))?
-        }; // sessions lock released here
-
-        // Now only hold the individual session lock during I/O
-        let mut session_guard = session_arc.lock();
-        session_guard
-            .writer
-            .write_all(data.as_bytes())
-            .map_err(|e| format!("Failed to write to PTY: {}", e))?;
-        session_guard
-            .writer
-            .flush()
-            .map_err(|e| format!("Failed to flush PTY: {}", e))?;
+    /// Write sanitized paste text to the session, bracketed if its program
+    /// enabled bracketed paste. Returns whether it was bracketed.
+    pub fn paste(&self, session_id: &str, text: &str) -> Result<bool, WriteError> {
+        let bracketed = self
+            .session_input_modes(session_id)
+            .map_err(|_| WriteError::SessionNotFound {
+                session_id: session_id.to_string(),
+            })?
+            .lock()
+            .bracketed_paste();
+        self.write_to_session(session_id, &paste::encode_paste(text, bracketed))?;
+        Ok(bracketed)
+    }
 
-        Ok(())
+    /// Encode a mouse event if the session's program is tracking the mouse and
+    /// the policy allows it. Returns false when the event wasn't reported and
+    /// should be handled natively (e.g. text selection).
+    pub fn send_mouse(
+        &self,
+        session_id: &str,
+        event: &MouseEvent,
+        policy: &MousePolicy,
+    ) -> Result<bool, String> {
+        let input_modes = self.session_input_modes(session_id)?;
+        let encoded = input::encode_mouse(event, &input_modes.lock(), policy);
+        match encoded {
+            Some(data) => {
+                self.write_to_session(session_id, &data)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
     }
 
     pub fn resize_session(&self, session_id: &str, cols: u16, rows: u16) -> Result<(), String> {
@@ -368,120 +1693,536 @@ impl PtyManager {
             sessions
                 .get(session_id)
                 .cloned() // Clone the Arc (cheap - just incrementing ref count)
-                .ok_or_else(|| format!("Session not found: {}", session_id))?
+                .ok_or_else(|| session_not_found(session_id))?
         }; // sessions lock released here
 
         // Now only hold the individual session lock during resize
-        let session_guard = session_arc.lock();
-        session_guard
-            .pair
-            .master
-            .resize(PtySize {
-                rows,
-                cols,
-                pixel_width: 0,
-                pixel_height: 0,
+        let mut session_guard = session_arc.lock();
+        // Terminals resize the sessions they show, so this (re)attaches it
+        session_guard.attached = true;
+        session_guard.orphaned_at = None;
+        let result = session_guard.process.lock().resize(cols, rows);
+        result
+    }
+
+    /// Mark a window's sessions as shown by no pane, after its page (re)loaded;
+    /// returns how many were attached
+    pub fn detach_window(&self, window: &str) -> usize {
+        let sessions: Vec<Arc<Mutex<PtySession>>> =
+            self.sessions.lock().values().cloned().collect();
+        sessions
+            .iter()
+            .filter(|session| {
+                let mut session = session.lock();
+                let detach = session.attached && *session.window.lock() == window;
+                if detach {
+                    session.attached = false;
+                }
+                detach
             })
-            .map_err(|e| format!("Failed to resize PTY: {}", e))?;
+            .count()
+    }
+
+    /// Detach a session from its closed pane, leaving the shell running.
+    /// Returns false if it was already detached or its shell has exited,
+    /// in which case it should be closed instead.
+    pub fn detach_session(&self, session_id: &str) -> Result<bool, String> {
+        let session = self
+            .sessions
+            .lock()
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| session_not_found(session_id))?;
+        let mut session = session.lock();
+        if !session.attached || session.check_exit().is_some() {
+            return Ok(false);
+        }
+        session.attached = false;
+        info!(session_id = %session_id, "Session detached");
+        Ok(true)
+    }
+
+    /// Attach a detached session to a pane in `window`, returning its
+    /// buffered output and the window it belonged to
+    pub fn reattach_session(
+        &self,
+        session_id: &str,
+        window: &str,
+        focused: bool,
+    ) -> Result<(String, String), String> {
+        let session = self
+            .sessions
+            .lock()
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| session_not_found(session_id))?;
+        let replay = {
+            let mut session = session.lock();
+            if session.attached {
+                return Err(format!("Session is not detached: {}", session_id));
+            }
+            session.attached = true;
+            session.orphaned_at = None;
+            session.replay.clone()
+        };
+        let previous = self.move_session(session_id, window, focused)?;
+        info!(session_id = %session_id, window = %window, "Session reattached");
+        let output = replay.lock().contents().to_string();
+        Ok((output, previous))
+    }
+
+    /// Summaries of the sessions no pane shows, sorted by session ID
+    pub fn list_detached_sessions(&self) -> Vec<SessionSummary> {
+        self.list_sessions()
+            .into_iter()
+            .filter(|summary| !summary.attached)
+            .collect()
+    }
+
+    /// Find detached sessions whose shell has exited: newly found ones are
+    /// reported once, and those found more than `grace` ago are expired
+    pub fn sweep_orphans(&self, grace: Duration) -> OrphanSweep {
+        let sessions: Vec<(String, Arc<Mutex<PtySession>>)> = self
+            .sessions
+            .lock()
+            .iter()
+            .map(|(id, session)| (id.clone(), session.clone()))
+            .collect();
+        let mut sweep = OrphanSweep::default();
+        for (session_id, session) in sessions {
+            let mut session = session.lock();
+            if session.attached {
+                continue;
+            }
+            let Some(status) = session.check_exit() else {
+                continue;
+            };
+            match session.orphaned_at {
+                None => {
+                    session.orphaned_at = Some(Instant::now());
+                    sweep.found.push(PtyOrphaned {
+                        session_id,
+                        window: session.window.lock().clone(),
+                        exit_code: status.code,
+                        signal: status.signal,
+                    });
+                }
+                Some(since) if since.elapsed() >= grace => sweep.expired.push(session_id),
+                Some(_) => {}
+            }
+        }
+        sweep
+    }
+
+    /// Drop a session whose shell has exited. Unlike `close_session` nothing
+    /// is killed (the PID may have been reused) and the reader thread isn't
+    /// joined: it ends by itself once the terminal closes.
+    pub fn remove_exited_session(&self, session_id: &str) -> bool {
+        let mut sessions = self.sessions.lock();
+        let exited = sessions
+            .get(session_id)
+            .is_some_and(|session| session.lock().exit_status.is_some());
+        if !exited {
+            return false;
+        }
+        if let Some(session) = sessions.remove(session_id) {
+            session.lock().shutdown_flag.store(true, Ordering::SeqCst);
+        }
+        true
+    }
+
+    /// Purge a session's Rust-side scrollback and replay output
+    pub fn clear_scrollback(&self, session_id: &str) -> Result<(), String> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| session_not_found(session_id))?;
+        let (scrollback, replay) = {
+            let session = session.lock();
+            (session.scrollback.clone(), session.replay.clone())
+        };
+        scrollback.lock().clear();
+        replay.lock().clear();
+        Ok(())
+    }
 
+    /// Purge a session's scrollback and forget the input modes its programs
+    /// negotiated, matching a full terminal reset
+    pub fn reset_session(&self, session_id: &str) -> Result<(), String> {
+        self.clear_scrollback(session_id)?;
+        self.session_input_modes(session_id)?.lock().reset();
         Ok(())
     }
 
-    /// Get the current working directory of a PTY session's shell process
-    #[cfg(target_os = "macos")]
+    /// The last command a session finished, if its shell marks commands (OSC 133)
+    pub fn last_command_stats(&self, session_id: &str) -> Result<Option<CommandStats>, String> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| session_not_found(session_id))?;
+        let last_command = session.lock().last_command.lock().clone();
+        Ok(last_command)
+    }
+
+    /// The process in the foreground of a session's terminal; `None` when the
+    /// backend can't tell (e.g. a remote session)
+    pub fn foreground_process(
+        &self,
+        session_id: &str,
+    ) -> Result<Option<ForegroundProcess>, String> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| session_not_found(session_id))?;
+        let foreground = session.lock().foreground();
+        Ok(foreground)
+    }
+
+    /// Whether the session's terminal echoes input; `None` if the backend
+    /// can't tell
+    pub fn echoes_input(&self, session_id: &str) -> Result<Option<bool>, String> {
+        let session = self
+            .sessions
+            .lock()
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| session_not_found(session_id))?;
+        let process = session.lock().process.clone();
+        let echo = process.lock().echoes_input();
+        Ok(echo)
+    }
+
+    /// Processes a session's shell has started, which closing it would kill
+    pub fn running_processes(&self, session_id: &str) -> Result<RunningProcesses, String> {
+        let session = self
+            .sessions
+            .lock()
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| session_not_found(session_id))?;
+        let processes = session.lock().running_processes();
+        Ok(RunningProcesses {
+            running: !processes.is_empty(),
+            processes,
+        })
+    }
+
+    /// Shell integration sequences a session has sent so far
+    pub fn integration_marks(&self, session_id: &str) -> Result<IntegrationMarks, String> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| session_not_found(session_id))?;
+        let marks = *session.lock().integration.lock();
+        Ok(marks)
+    }
+
+    /// Start appending a session's output to a transcript, returning its
+    /// path. A session already logged in `format` keeps its transcript.
+    pub fn start_session_log(
+        &self,
+        session_id: &str,
+        format: TranscriptFormat,
+    ) -> Result<PathBuf, String> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| session_not_found(session_id))?;
+        let transcript = session.lock().transcript.clone();
+        drop(sessions);
+        let mut transcript = transcript.lock();
+        if let Some(log) = transcript.as_ref().filter(|log| log.format() == format) {
+            return Ok(log.path().to_path_buf());
+        }
+        let log = self.open_log(session_id, format)?;
+        let path = log.path().to_path_buf();
+        *transcript = Some(log);
+        Ok(path)
+    }
+
+    /// Stop logging a session, returning the transcript's path if it was
+    /// being logged
+    pub fn stop_session_log(&self, session_id: &str) -> Result<Option<PathBuf>, String> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| session_not_found(session_id))?;
+        let log = session.lock().transcript.lock().take();
+        if let Some(log) = &log {
+            info!(session_id = %session_id, path = %log.path().display(), "Transcript stopped");
+        }
+        Ok(log.map(|log| log.path().to_path_buf()))
+    }
+
+    /// Get a session's current working directory: the one the shell last
+    /// reported with OSC 7, or the shell process's when it doesn't report it
     pub fn get_session_cwd(&self, session_id: &str) -> Result<Option<String>, String> {
         let sessions = self.sessions.lock();
         let session = sessions
             .get(session_id)
-            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+            .ok_or_else(|| session_not_found(session_id))?;
 
         let session_guard = session.lock();
-        let pid = match session_guard.child_pid {
-            Some(pid) => pid,
-            None => return Ok(None),
-        };
+        Ok(session_guard.cwd())
+    }
 
-        // Use libproc to get the current working directory
-        use std::ffi::CStr;
-        use std::mem::MaybeUninit;
-        use std::os::raw::{c_char, c_int};
+    /// Directory a session opened next to `session_id` should start in: its
+    /// working directory, if it still exists
+    pub fn inherited_cwd(&self, session_id: &str) -> Option<PathBuf> {
+        let cwd = PathBuf::from(self.get_session_cwd(session_id).ok()??);
+        (cwd.is_absolute() && cwd.is_dir()).then_some(cwd)
+    }
 
-        // Constants from sys/proc_info.h
-        const PROC_PIDVNODEPATHINFO: c_int = 9;
+    /// Replace a session's tags and color
+    pub fn set_session_labels(
+        &self,
+        session_id: &str,
+        labels: SessionLabels,
+    ) -> Result<(), String> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| session_not_found(session_id))?;
+        session.lock().labels = labels;
+        Ok(())
+    }
 
-        #[repr(C)]
-        struct vnode_info_path {
-            _vip_vi: [u8; 152],       // vnode_info structure (we don't need its contents)
-            vip_path: [c_char; 1024], // MAXPATHLEN
-        }
+    /// Tags and color of a session
+    pub fn get_session_labels(&self, session_id: &str) -> Result<SessionLabels, String> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| session_not_found(session_id))?;
+        let labels = session.lock().labels.clone();
+        Ok(labels)
+    }
+
+    /// Tags and color of every live session, by session ID
+    pub fn list_session_labels(&self) -> HashMap<String, SessionLabels> {
+        let sessions = self.sessions.lock();
+        sessions
+            .iter()
+            .map(|(id, session)| (id.clone(), session.lock().labels.clone()))
+            .collect()
+    }
+
+    /// Number of live sessions
+    pub fn session_count(&self) -> usize {
+        self.sessions.lock().len()
+    }
+
+    /// Summary of every live session, sorted by session ID
+    pub fn list_sessions(&self) -> Vec<SessionSummary> {
+        let sessions: Vec<(String, Arc<Mutex<PtySession>>)> = {
+            let sessions = self.sessions.lock();
+            sessions
+                .iter()
+                .map(|(id, session)| (id.clone(), session.clone()))
+                .collect()
+        };
+        let mut summaries: Vec<SessionSummary> = sessions
+            .into_iter()
+            .map(|(session_id, session)| {
+                let session = session.lock();
+                let idle_secs = session.last_activity.lock().elapsed().as_secs();
+                let window = session.window.lock().clone();
+                let last_output = *session.last_output.lock();
+                let alternate_screen = session.input_modes.lock().alternate_screen();
+                let title = session.title.lock().rendered().map(String::from);
+                let cwd = session.cwd();
+                let name = session.auto_name(cwd.as_deref());
+                SessionSummary {
+                    session_id,
+                    cwd,
+                    title,
+                    name,
+                    running_command: session
+                        .running_command_args()
+                        .map(|args| redact::redact(&args.join(" "))),
+                    idle_secs,
+                    last_input_at: session.last_input.map(unix_secs),
+                    last_output_at: last_output.map(unix_secs),
+                    suspended: session.suspended.is_some(),
+                    protected: session.protected,
+                    read_only: session.read_only,
+                    labels: session.labels.clone(),
+                    window,
+                    attached: session.attached,
+                    alternate_screen,
+                }
+            })
+            .collect();
+        summaries.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        summaries
+    }
 
-        #[repr(C)]
-        struct proc_vnodepathinfo {
-            pvi_cdir: vnode_info_path,
-            pvi_rdir: vnode_info_path,
+    /// Re-render and emit every session's tab title (after the template changes)
+    pub fn refresh_titles(&self, app: &AppHandle) {
+        let titles: Vec<(String, Arc<Mutex<SessionTitle>>)> = {
+            let sessions = self.sessions.lock();
+            sessions
+                .iter()
+                .map(|(id, session)| (id.clone(), session.lock().title.clone()))
+                .collect()
+        };
+        for (session_id, title) in titles {
+            let mut title = title.lock();
+            title.invalidate();
+            tab_title::update_title(app, &session_id, &mut title);
         }
+    }
 
-        extern "C" {
-            fn proc_pidinfo(
-                pid: c_int,
-                flavor: c_int,
-                arg: u64,
-                buffer: *mut std::ffi::c_void,
-                buffersize: c_int,
-            ) -> c_int;
+    /// Search the scrollback of every live session.
+    /// Results are ordered by session ID; sessions without hits are omitted.
+    pub fn search_all_sessions(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<SessionSearchResult>, String> {
+        if query.is_empty() {
+            return Err("Search query cannot be empty".to_string());
         }
 
-        let mut info: MaybeUninit<proc_vnodepathinfo> = MaybeUninit::uninit();
-        let info_size = std::mem::size_of::<proc_vnodepathinfo>() as c_int;
+        // Collect scrollback handles first so the sessions lock isn't held while searching
+        let mut scrollbacks: Vec<(String, Arc<Mutex<ScrollbackBuffer>>)> = {
+            let sessions = self.sessions.lock();
+            sessions
+                .iter()
+                .map(|(id, session)| (id.clone(), session.lock().scrollback.clone()))
+                .collect()
+        };
+        scrollbacks.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(scrollbacks
+            .into_iter()
+            .filter_map(|(session_id, scrollback)| {
+                let hits = scrollback.lock().search(query, options);
+                (!hits.is_empty()).then_some(SessionSearchResult { session_id, hits })
+            })
+            .collect())
+    }
 
-        let ret = unsafe {
-            proc_pidinfo(
-                pid as c_int,
-                PROC_PIDVNODEPATHINFO,
-                0,
-                info.as_mut_ptr() as *mut std::ffi::c_void,
-                info_size,
-            )
+    /// Search one session's scrollback for plain text or, with `regex`, a
+    /// regular expression
+    pub fn search_session(
+        &self,
+        session_id: &str,
+        query: &str,
+        regex: bool,
+        options: &SearchOptions,
+    ) -> Result<Vec<ScrollbackHit>, String> {
+        if query.is_empty() {
+            return Err("Search query cannot be empty".to_string());
+        }
+        let buffer = {
+            let sessions = self.sessions.lock();
+            let session = sessions
+                .get(session_id)
+                .ok_or_else(|| session_not_found(session_id))?;
+            let buffer = session.lock().scrollback.clone();
+            buffer
         };
 
-        if ret <= 0 {
-            return Ok(None);
+        if regex {
+            let regex = scrollback::compile_regex(query, options.case_sensitive)?;
+            Ok(buffer.lock().search_regex(&regex, options))
+        } else {
+            Ok(buffer.lock().search(query, options))
         }
+    }
 
-        let info = unsafe { info.assume_init() };
-        let cwd = unsafe { CStr::from_ptr(info.pvi_cdir.vip_path.as_ptr()) };
+    /// Suspend shells idle for at least `idle_for`; returns how many were suspended
+    pub fn suspend_idle_sessions(&self, idle_for: Duration, mode: IdleSuspendMode) -> usize {
+        let sessions: Vec<Arc<Mutex<PtySession>>> =
+            self.sessions.lock().values().cloned().collect();
+        sessions
+            .iter()
+            .filter(|session| session.lock().suspend_if_idle(idle_for, mode))
+            .count()
+    }
 
-        match cwd.to_str() {
-            Ok(s) if !s.is_empty() => Ok(Some(s.to_string())),
-            _ => Ok(None),
-        }
+    /// Whether any session is running a foreground command
+    pub fn has_running_command(&self) -> bool {
+        let sessions: Vec<Arc<Mutex<PtySession>>> =
+            self.sessions.lock().values().cloned().collect();
+        sessions
+            .iter()
+            .any(|session| session.lock().running_command().is_some())
     }
 
-    #[cfg(not(target_os = "macos"))]
-    pub fn get_session_cwd(&self, session_id: &str) -> Result<Option<String>, String> {
-        // On non-macOS platforms, try to read /proc/<pid>/cwd
+    /// Whether a session's shell is at its prompt rather than running a command
+    pub fn is_at_prompt(&self, session_id: &str) -> Result<bool, String> {
         let sessions = self.sessions.lock();
         let session = sessions
             .get(session_id)
-            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+            .ok_or_else(|| session_not_found(session_id))?;
+        let at_prompt = session.lock().running_command().is_none();
+        Ok(at_prompt)
+    }
 
-        let session_guard = session.lock();
-        let pid = match session_guard.child_pid {
-            Some(pid) => pid,
-            None => return Ok(None),
-        };
+    /// Arguments of every session's running foreground command
+    pub fn running_command_args(&self) -> Vec<Vec<String>> {
+        let sessions: Vec<Arc<Mutex<PtySession>>> =
+            self.sessions.lock().values().cloned().collect();
+        sessions
+            .iter()
+            .filter_map(|session| session.lock().running_command_args())
+            .collect()
+    }
 
-        let cwd_path = format!("/proc/{}/cwd", pid);
-        match std::fs::read_link(&cwd_path) {
-            Ok(path) => Ok(Some(path.to_string_lossy().to_string())),
-            Err(_) => Ok(None),
-        }
+    /// Resume every suspended shell; returns how many were resumed
+    pub fn resume_suspended_sessions(&self) -> usize {
+        let sessions: Vec<Arc<Mutex<PtySession>>> =
+            self.sessions.lock().values().cloned().collect();
+        sessions
+            .iter()
+            .filter(|session| session.lock().resume())
+            .count()
+    }
+
+    /// Put a session in (or out of) read-only mode
+    pub fn set_session_read_only(&self, session_id: &str, read_only: bool) -> Result<(), String> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| session_not_found(session_id))?;
+        session.lock().read_only = read_only;
+        Ok(())
+    }
+
+    /// Mark a session as protected (or not)
+    pub fn set_session_protected(&self, session_id: &str, protected: bool) -> Result<(), String> {
+        let sessions = self.sessions.lock();
+        let session = sessions
+            .get(session_id)
+            .ok_or_else(|| session_not_found(session_id))?;
+        session.lock().protected = protected;
+        Ok(())
+    }
+
+    /// IDs of all protected sessions, sorted
+    pub fn protected_sessions(&self) -> Vec<String> {
+        let sessions = self.sessions.lock();
+        let mut protected: Vec<String> = sessions
+            .iter()
+            .filter(|(_, session)| session.lock().protected)
+            .map(|(id, _)| id.clone())
+            .collect();
+        protected.sort();
+        protected
     }
 
-    pub fn close_session(&self, session_id: &str) -> Result<(), String> {
-        debug!(session_id = %session_id, "Closing PTY session");
+    /// Close a session; protected sessions are refused unless `force` is set
+    pub fn close_session(&self, session_id: &str, force: bool) -> Result<(), String> {
+        debug!(session_id = %session_id, force, "Closing PTY session");
         let session = {
             let mut sessions = self.sessions.lock();
+            let protected = sessions
+                .get(session_id)
+                .is_some_and(|session| session.lock().protected);
+            if protected && !force {
+                return Err(i18n::tr("pty.protected", &[("session_id", &session_id)]));
+            }
             sessions.remove(session_id)
         };
 
@@ -498,8 +2239,13 @@ impl PtyManager {
             // Kill the child process to unblock the reader thread
             if child_to_kill {
                 let mut session_guard = session_arc.lock();
-                // Try to kill the child process - this will cause reader to get EOF
-                let _ = session_guard.child.kill();
+                // A stopped shell wouldn't handle the hangup
+                session_guard.resume();
+                // Try to kill the child process - this will cause reader to get EOF.
+                // A reaped shell's PID may belong to another process by now.
+                if session_guard.exit_status.is_none() {
+                    let _ = session_guard.process.lock().kill();
+                }
             }
 
             // Wait for the reader thread to finish (with timeout behavior)
@@ -526,6 +2272,79 @@ impl Default for PtyManager {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_backend_selection() {
+        use crate::pty_backend::LOCAL_BACKEND;
+        use crate::pty_mock::{MockPtyBackend, MOCK_BACKEND};
+
+        let manager = PtyManager::with_backend(Arc::new(MockPtyBackend::new()));
+        assert_eq!(manager.backend(None).unwrap().name(), MOCK_BACKEND);
+        assert_eq!(manager.backend(Some("mock")).unwrap().name(), MOCK_BACKEND);
+        assert!(manager.backend(Some(LOCAL_BACKEND)).is_err());
+
+        manager.register_backend(Arc::new(LocalPtyBackend));
+        assert_eq!(
+            manager.backend(Some(LOCAL_BACKEND)).unwrap().name(),
+            LOCAL_BACKEND
+        );
+        assert_eq!(manager.backend(None).unwrap().name(), MOCK_BACKEND);
+    }
+
+    #[test]
+    fn test_session_command_line() {
+        assert_eq!(SessionOptions::default().command_line(), None);
+        let options = SessionOptions {
+            command: Some("make test".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(options.command_line().as_deref(), Some("make test"));
+        let options = SessionOptions {
+            program: Some("htop".to_string()),
+            args: vec!["-d".to_string(), "10".to_string()],
+            ..options
+        };
+        assert_eq!(options.command_line().as_deref(), Some("htop -d 10"));
+    }
+
+    /// Writer whose bytes the test can inspect
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_replies_never_wait_for_the_writer() {
+        let buffer = SharedBuffer::default();
+        let writer: SharedWriter = Arc::new(Mutex::new(Box::new(buffer.clone())));
+        let replies = ReplyWriter::spawn(writer.clone()).unwrap();
+
+        // A user write blocked on a full PTY holds the writer
+        let held = writer.lock();
+        // Overflow is dropped rather than waited for; the thread may hold one
+        // reply of its own while it waits for the writer
+        let sent = (0..MAX_QUEUED_REPLIES + 2)
+            .filter(|_| replies.send("\x1b[?62c".to_string()))
+            .count();
+        assert!(sent == MAX_QUEUED_REPLIES || sent == MAX_QUEUED_REPLIES + 1);
+        drop(held);
+
+        let expected = "\x1b[?62c".len() * sent;
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while buffer.0.lock().len() < expected && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(buffer.0.lock().len(), expected);
+    }
+
     #[test]
     fn test_validate_pty_size_valid() {
         assert!(validate_pty_size(80, 24).is_ok());
@@ -571,6 +2390,12 @@ mod tests {
         assert!(err.contains("2"));
     }
 
+    #[test]
+    fn test_process_args() {
+        let args: Vec<String> = std::env::args().collect();
+        assert_eq!(process_args(std::process::id()), Some(args));
+    }
+
     #[test]
     fn test_pty_manager_new() {
         let manager = PtyManager::new();
@@ -590,7 +2415,113 @@ mod tests {
         let manager = PtyManager::new();
         let result = manager.write_to_session("nonexistent-session-id", "test");
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Session not found"));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Session not found"));
+        assert!(manager.set_session_read_only("nonexistent", true).is_err());
+    }
+
+    #[test]
+    fn test_write_error_serialization() {
+        let error = WriteError::ReadOnly {
+            session_id: "s1".to_string(),
+        };
+        assert_eq!(error.to_string(), "Session is read-only: s1");
+        assert_eq!(
+            serde_json::to_string(&error).unwrap(),
+            r#"{"kind":"read_only","session_id":"s1"}"#
+        );
+    }
+
+    #[test]
+    fn test_validate_startup_command() {
+        assert_eq!(
+            validate_startup_command("  ssh prod\n").as_deref(),
+            Ok("ssh prod")
+        );
+        assert_eq!(
+            validate_startup_command("htop\t-d 10").as_deref(),
+            Ok("htop\t-d 10")
+        );
+        assert!(validate_startup_command(" ").is_err());
+        assert!(validate_startup_command("ls\nrm -rf ~").is_err());
+        assert!(validate_startup_command("echo \x1b]52;c;AA==\x07").is_err());
+        assert!(validate_startup_command(&"x".repeat(MAX_STARTUP_COMMAND_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_resolve_shell() {
+        use crate::settings::ShellConfig;
+        use tempfile::TempDir;
+
+        let dir = TempDir::new().unwrap();
+        let fish = dir.path().join("fish");
+        std::fs::write(&fish, "").unwrap();
+        let mut settings = AppSettings {
+            shell: Some(ShellConfig {
+                path: fish.to_string_lossy().into_owned(),
+                args: vec!["-l".to_string()],
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_shell(&settings, None),
+            (fish.to_string_lossy().into_owned(), vec!["-l".to_string()])
+        );
+
+        // The profile's shell comes first
+        let bash = dir.path().join("bash");
+        std::fs::write(&bash, "").unwrap();
+        let profile = Profile {
+            name: "docker".to_string(),
+            shell: Some(ShellConfig {
+                path: bash.to_string_lossy().into_owned(),
+                args: Vec::new(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_shell(&settings, Some(&profile)).0,
+            bash.to_string_lossy()
+        );
+
+        // A missing shell falls back to the login shell
+        settings.shell = Some(ShellConfig {
+            path: dir.path().join("missing").to_string_lossy().into_owned(),
+            args: vec!["-l".to_string()],
+        });
+        let (shell, args) = resolve_shell(&settings, None);
+        assert_ne!(shell, settings.shell.unwrap().path);
+        assert!(args.is_empty());
+    }
+
+    #[test]
+    fn test_move_nonexistent_session() {
+        let manager = PtyManager::new();
+        assert!(manager
+            .move_session("nonexistent-session-id", "detached-1", true)
+            .is_err());
+    }
+
+    #[test]
+    fn test_detach_nonexistent_session() {
+        let manager = PtyManager::new();
+        assert!(manager.detach_session("nonexistent").is_err());
+        assert!(manager
+            .reattach_session("nonexistent", MAIN_WINDOW_LABEL, true)
+            .is_err());
+        assert!(manager.list_detached_sessions().is_empty());
+    }
+
+    #[test]
+    fn test_session_log_nonexistent_session() {
+        let manager = PtyManager::new();
+        manager.set_log_dir(std::env::temp_dir());
+        assert!(manager
+            .start_session_log("nonexistent", TranscriptFormat::Plain)
+            .is_err());
+        assert!(manager.stop_session_log("nonexistent").is_err());
     }
 
     #[test]
@@ -605,10 +2536,74 @@ mod tests {
     fn test_close_nonexistent_session() {
         let manager = PtyManager::new();
         // Closing a non-existent session should succeed (no-op)
-        let result = manager.close_session("nonexistent-session-id");
+        let result = manager.close_session("nonexistent-session-id", false);
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_protect_nonexistent_session() {
+        let manager = PtyManager::new();
+        assert!(manager.set_session_protected("nonexistent", true).is_err());
+        assert!(manager.protected_sessions().is_empty());
+    }
+
+    #[test]
+    fn test_search_all_sessions_empty_query() {
+        let manager = PtyManager::new();
+        let result = manager.search_all_sessions("", &SearchOptions::default());
+        assert!(result.unwrap_err().contains("empty"));
+    }
+
+    #[test]
+    fn test_search_all_sessions_no_sessions() {
+        let manager = PtyManager::new();
+        let results = manager
+            .search_all_sessions("error", &SearchOptions::default())
+            .unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_session_errors() {
+        let manager = PtyManager::new();
+        let options = SearchOptions::default();
+        let result = manager.search_session("nonexistent", "", false, &options);
+        assert!(result.unwrap_err().contains("empty"));
+        let result = manager.search_session("nonexistent", "error", true, &options);
+        assert!(result.unwrap_err().contains("not found"));
+    }
+
+    #[test]
+    fn test_labels_of_nonexistent_session() {
+        let manager = PtyManager::new();
+        assert!(manager
+            .set_session_labels("nonexistent", SessionLabels::default())
+            .is_err());
+        assert!(manager.get_session_labels("nonexistent").is_err());
+        assert!(manager.list_session_labels().is_empty());
+    }
+
+    #[test]
+    fn test_send_key_to_nonexistent_session() {
+        let manager = PtyManager::new();
+        let event = KeyEvent {
+            key: "a".to_string(),
+            ..Default::default()
+        };
+        assert!(manager.send_key("nonexistent", &event).is_err());
+    }
+
+    #[test]
+    fn test_send_mouse_to_nonexistent_session() {
+        let manager = PtyManager::new();
+        let result = manager.send_mouse(
+            "nonexistent",
+            &MouseEvent::default(),
+            &MousePolicy::default(),
+        );
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_resize_with_invalid_dimensions() {
         let manager = PtyManager::new();
@@ -643,6 +2638,7 @@ mod tests {
         let exit_with_code = PtyExit {
             session_id: "test-session".to_string(),
             exit_code: Some(0),
+            signal: None,
         };
 
         let json = serde_json::to_string(&exit_with_code).unwrap();
@@ -653,11 +2649,55 @@ mod tests {
         let exit_without_code = PtyExit {
             session_id: "test-session".to_string(),
             exit_code: None,
+            signal: None,
         };
 
         let json = serde_json::to_string(&exit_without_code).unwrap();
         let deserialized: PtyExit = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized.exit_code, None);
+
+        let killed = PtyExit {
+            session_id: "test-session".to_string(),
+            exit_code: Some(137),
+            signal: Some("SIGKILL".to_string()),
+        };
+        let json = serde_json::to_value(&killed).unwrap();
+        assert_eq!(json["exit_code"], 137);
+        assert_eq!(json["signal"], "SIGKILL");
+    }
+
+    #[test]
+    fn test_unix_secs() {
+        let now = unix_secs(Instant::now());
+        let expected = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        assert!(now.abs_diff(expected) <= 1);
+
+        let minute_ago = Instant::now().checked_sub(Duration::from_secs(60)).unwrap();
+        assert!(unix_secs(minute_ago).abs_diff(expected - 60) <= 1);
+    }
+
+    #[test]
+    fn test_clear_nonexistent_session() {
+        let manager = PtyManager::new();
+        assert!(manager.clear_scrollback("nonexistent").is_err());
+        assert!(manager.reset_session("nonexistent").is_err());
+        assert!(manager.last_command_stats("nonexistent").is_err());
+        assert!(manager.integration_marks("nonexistent").is_err());
+        assert!(manager.foreground_process("nonexistent").is_err());
+        assert!(manager.running_processes("nonexistent").is_err());
+        assert_eq!(manager.inherited_cwd("nonexistent"), None);
+        assert!(manager.session_window("nonexistent").is_err());
+        assert!(matches!(
+            manager.paste("nonexistent", "ls"),
+            Err(WriteError::SessionNotFound { .. })
+        ));
+        assert!(manager
+            .pass_shortcut("nonexistent", &KeyEvent::default(), &BTreeMap::new())
+            .is_err());
+        assert!(manager.is_at_prompt("nonexistent").is_err());
     }
 
     #[test]
@@ -0,0 +1,361 @@
+//! Session backends
+//!
+//! `PtyManager` starts sessions through a `SessionBackend` and talks to them
+//! through the `SessionProcess` it returns, without knowing about
+//! `portable_pty`. The local backend runs the shell in a system PTY; other
+//! backends (ssh, docker, the scripted mock in `pty_mock.rs` behind the
+//! `testing` feature) are registered with `PtyManager::register_backend` and
+//! picked by name through `SessionOptions::backend`.
+
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize, SlavePty};
+use std::ffi::CStr;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+/// Name of the backend running shells in a local PTY
+pub const LOCAL_BACKEND: &str = "local";
+
+/// What to run in a new session
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpawnRequest {
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: PathBuf,
+    /// Variables set on top of the app's environment; later entries win
+    pub env: Vec<(String, String)>,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+impl SpawnRequest {
+    pub fn env(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.env.push((name.into(), value.into()));
+    }
+
+    /// Value a variable will have, if the request sets it
+    pub fn get_env(&self, name: &str) -> Option<&str> {
+        self.env
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// How a session's process ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExitStatus {
+    /// Exit code; 128 + the signal number when killed by a signal, as shells report it
+    pub code: i32,
+    /// Signal that killed the process, such as `SIGKILL`
+    pub signal: Option<String>,
+}
+
+impl ExitStatus {
+    pub fn exited(code: i32) -> Self {
+        Self { code, signal: None }
+    }
+
+    pub fn signaled(signal: i32) -> Self {
+        Self {
+            code: 128 + signal,
+            signal: Some(signal_name(signal)),
+        }
+    }
+
+    pub fn success(&self) -> bool {
+        self.code == 0 && self.signal.is_none()
+    }
+}
+
+/// Name of a signal, such as `SIGTERM`
+pub fn signal_name(signal: i32) -> String {
+    let name = match signal {
+        libc::SIGHUP => "SIGHUP",
+        libc::SIGINT => "SIGINT",
+        libc::SIGQUIT => "SIGQUIT",
+        libc::SIGILL => "SIGILL",
+        libc::SIGTRAP => "SIGTRAP",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGUSR1 => "SIGUSR1",
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGUSR2 => "SIGUSR2",
+        libc::SIGPIPE => "SIGPIPE",
+        libc::SIGALRM => "SIGALRM",
+        libc::SIGTERM => "SIGTERM",
+        libc::SIGXCPU => "SIGXCPU",
+        libc::SIGXFSZ => "SIGXFSZ",
+        _ => return format!("SIG{}", signal),
+    };
+    name.to_string()
+}
+
+/// Signal number with the given `strsignal` description
+fn signal_from_description(description: &str) -> Option<i32> {
+    (1..32).find(|&signal| {
+        // SAFETY: strsignal returns a NUL-terminated string or null
+        let ptr = unsafe { libc::strsignal(signal) };
+        !ptr.is_null() && unsafe { CStr::from_ptr(ptr) }.to_string_lossy() == description
+    })
+}
+
+impl From<portable_pty::ExitStatus> for ExitStatus {
+    fn from(status: portable_pty::ExitStatus) -> Self {
+        // portable_pty only keeps the signal's description and reports the code as 1
+        let display = status.to_string();
+        match display.strip_prefix("Terminated by ") {
+            Some(description) => match signal_from_description(description) {
+                Some(signal) => Self::signaled(signal),
+                None => Self {
+                    code: status.exit_code() as i32,
+                    signal: Some(description.to_string()),
+                },
+            },
+            None => Self::exited(status.exit_code() as i32),
+        }
+    }
+}
+
+/// Starts sessions
+pub trait SessionBackend: Send + Sync {
+    /// Name used to pick the backend in `SessionOptions::backend`
+    fn name(&self) -> &'static str;
+
+    fn spawn(&self, request: SpawnRequest) -> Result<Box<dyn SessionProcess>, String>;
+}
+
+/// A running session: its terminal I/O and the process behind it
+pub trait SessionProcess: Send {
+    /// Output stream; reads EOF once the session has ended
+    fn take_reader(&mut self) -> Result<Box<dyn Read + Send>, String>;
+
+    /// Input stream; may only be taken once
+    fn take_writer(&mut self) -> Result<Box<dyn Write + Send>, String>;
+
+    fn resize(&self, cols: u16, rows: u16) -> Result<(), String>;
+
+    /// PID of the session's own process, if it runs on this Mac
+    fn pid(&self) -> Option<u32>;
+
+    /// PID of the foreground process group leader, if known
+    fn foreground_process(&self) -> Option<u32>;
+
+    /// Whether the terminal echoes input (termios `ECHO`), if known
+    fn echoes_input(&self) -> Option<bool>;
+
+    /// Block until the session's process exits
+    fn wait(&mut self) -> Result<ExitStatus, String>;
+
+    /// How the session's process ended, or `None` while it's running
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>, String>;
+
+    fn kill(&mut self) -> Result<(), String>;
+}
+
+/// Runs shells in a system PTY
+pub struct LocalPtyBackend;
+
+impl SessionBackend for LocalPtyBackend {
+    fn name(&self) -> &'static str {
+        LOCAL_BACKEND
+    }
+
+    fn spawn(&self, request: SpawnRequest) -> Result<Box<dyn SessionProcess>, String> {
+        let pair = native_pty_system()
+            .openpty(pty_size(request.cols, request.rows))
+            .map_err(|e| format!("Failed to open PTY: {}", e))?;
+        let mut cmd = CommandBuilder::new(&request.program);
+        cmd.args(&request.args);
+        cmd.cwd(&request.cwd);
+        for (name, value) in &request.env {
+            cmd.env(name, value);
+        }
+        let child = pair
+            .slave
+            .spawn_command(cmd)
+            .map_err(|e| format!("Failed to spawn shell: {}", e))?;
+        Ok(Box::new(LocalPty {
+            master: pair.master,
+            _slave: pair.slave,
+            child,
+        }))
+    }
+}
+
+fn pty_size(cols: u16, rows: u16) -> PtySize {
+    PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+struct LocalPty {
+    master: Box<dyn MasterPty + Send>,
+    /// Kept open for the session's lifetime
+    _slave: Box<dyn SlavePty + Send>,
+    child: Box<dyn Child + Send + Sync>,
+}
+
+impl SessionProcess for LocalPty {
+    fn take_reader(&mut self) -> Result<Box<dyn Read + Send>, String> {
+        self.master
+            .try_clone_reader()
+            .map_err(|e| format!("Failed to get PTY reader: {}", e))
+    }
+
+    fn take_writer(&mut self) -> Result<Box<dyn Write + Send>, String> {
+        self.master
+            .take_writer()
+            .map_err(|e| format!("Failed to get PTY writer: {}", e))
+    }
+
+    fn resize(&self, cols: u16, rows: u16) -> Result<(), String> {
+        self.master
+            .resize(pty_size(cols, rows))
+            .map_err(|e| format!("Failed to resize PTY: {}", e))
+    }
+
+    fn pid(&self) -> Option<u32> {
+        self.child.process_id()
+    }
+
+    fn foreground_process(&self) -> Option<u32> {
+        self.master
+            .process_group_leader()
+            .filter(|pgid| *pgid > 0)
+            .map(|pgid| pgid as u32)
+    }
+
+    fn echoes_input(&self) -> Option<bool> {
+        let fd = self.master.as_raw_fd()?;
+        let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+        // SAFETY: tcgetattr fills in the termios when it succeeds
+        if unsafe { libc::tcgetattr(fd, termios.as_mut_ptr()) } != 0 {
+            return None;
+        }
+        // SAFETY: initialized by the successful tcgetattr above
+        let termios = unsafe { termios.assume_init() };
+        Some(termios.c_lflag & libc::ECHO != 0)
+    }
+
+    fn wait(&mut self) -> Result<ExitStatus, String> {
+        self.child
+            .wait()
+            .map(ExitStatus::from)
+            .map_err(|e| format!("Failed to wait for shell: {}", e))
+    }
+
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>, String> {
+        self.child
+            .try_wait()
+            .map(|status| status.map(ExitStatus::from))
+            .map_err(|e| format!("Failed to check shell: {}", e))
+    }
+
+    fn kill(&mut self) -> Result<(), String> {
+        self.child
+            .kill()
+            .map_err(|e| format!("Failed to kill shell: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn_request_env() {
+        let mut request = SpawnRequest::default();
+        request.env("TERM", "xterm-256color");
+        request.env("LANG", "en_US.UTF-8");
+        request.env("TERM", "xterm-kitty");
+        assert_eq!(request.get_env("TERM"), Some("xterm-kitty"));
+        assert_eq!(request.get_env("LANG"), Some("en_US.UTF-8"));
+        assert_eq!(request.get_env("HOME"), None);
+    }
+
+    fn run(script: &str) -> ExitStatus {
+        let mut process = LocalPtyBackend
+            .spawn(SpawnRequest {
+                program: "/bin/sh".to_string(),
+                args: vec!["-c".to_string(), script.to_string()],
+                cwd: std::env::temp_dir(),
+                cols: 80,
+                rows: 24,
+                ..Default::default()
+            })
+            .unwrap();
+        process.wait().unwrap()
+    }
+
+    #[test]
+    fn test_exit_codes_and_signals() {
+        assert_eq!(run("exit 0"), ExitStatus::exited(0));
+        assert_eq!(run("exit 2"), ExitStatus::exited(2));
+
+        let mut process = LocalPtyBackend
+            .spawn(SpawnRequest {
+                program: "/bin/sh".to_string(),
+                args: vec!["-c".to_string(), "sleep 5".to_string()],
+                cwd: std::env::temp_dir(),
+                cols: 80,
+                rows: 24,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(process.try_wait(), Ok(None));
+        process.kill().unwrap();
+        assert_eq!(process.wait(), Ok(ExitStatus::signaled(libc::SIGHUP)));
+
+        let killed = run("kill -KILL $$");
+        assert_eq!(killed.code, 128 + libc::SIGKILL);
+        assert_eq!(killed.signal.as_deref(), Some("SIGKILL"));
+        assert!(!killed.success());
+    }
+
+    #[test]
+    fn test_signal_name() {
+        assert_eq!(signal_name(libc::SIGTERM), "SIGTERM");
+        assert_eq!(signal_name(libc::SIGHUP), "SIGHUP");
+        assert_eq!(signal_name(99), "SIG99");
+    }
+
+    #[test]
+    fn test_echoes_input() {
+        let mut process = LocalPtyBackend
+            .spawn(SpawnRequest {
+                program: "/bin/sh".to_string(),
+                args: vec![
+                    "-c".to_string(),
+                    "echo started; read _; stty -echo; echo hidden; sleep 5".to_string(),
+                ],
+                cwd: std::env::temp_dir(),
+                cols: 80,
+                rows: 24,
+                ..Default::default()
+            })
+            .unwrap();
+        let mut reader = process.take_reader().unwrap();
+        let mut output = String::new();
+        let mut read_until = |text: &str| {
+            let mut buf = [0u8; 256];
+            while !output.contains(text) {
+                let n = reader.read(&mut buf).unwrap();
+                assert!(n > 0, "EOF before {:?}", text);
+                output.push_str(&String::from_utf8_lossy(&buf[..n]));
+            }
+        };
+        read_until("started");
+        assert_eq!(process.echoes_input(), Some(true));
+
+        process.take_writer().unwrap().write_all(b"\r").unwrap();
+        read_until("hidden");
+        assert_eq!(process.echoes_input(), Some(false));
+        process.kill().unwrap();
+    }
+}
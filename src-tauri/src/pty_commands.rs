@@ -1,24 +1,309 @@
-use crate::pty::PtyManager;
+use crate::commands::{validate_args, validate_command};
+use crate::events;
+use crate::input::{KeyEvent, MouseEvent};
+use crate::keychain::KeychainSecret;
+use crate::lock::LockManager;
+use crate::macros::MacroManager;
+use crate::metrics;
+use crate::paste::{self, PasteConfirmationRequested, PasteResult};
+use crate::profiles;
+use crate::pty::{
+    self, ForegroundProcess, PtyManager, RunningProcesses, SessionCleared, SessionMoved,
+    SessionOptions, SessionSearchResult, SessionSummary, WriteError,
+};
+use crate::recent_dirs::RecentDirsManager;
+use crate::scripting::{HookEvent, ScriptManager};
+use crate::scrollback::{ScrollbackHit, SearchOptions};
+use crate::session_labels::{LabelColor, SessionLabels, SessionLabelsChanged};
+use crate::session_store::SessionStore;
+use crate::settings::SettingsManager;
+use crate::shell_integration::{CommandStats, IntegrationHealth};
+use crate::transcript::TranscriptFormat;
+use crate::trash::{self, SafeDelete};
+use crate::usage_stats::{self, UsageStatsManager};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::{command, AppHandle, State};
+use tauri::{command, AppHandle, Manager, State, WebviewWindow};
 
+/// Start a shell session; `secrets` are Keychain items exported into its
+/// environment and `startup_command` (e.g. `ssh prod`) is typed into the shell
+/// once it starts. It starts in `cwd`, else in the working directory of
+/// `cwd_from_session` (so a split opens where the focused pane is), else in
+/// the home directory. With a `program` (e.g. `htop`) that program runs in the
+/// PTY with `args` instead of a shell.
 #[command]
+#[allow(clippy::too_many_arguments)]
 pub async fn create_pty_session(
     app: AppHandle,
+    window: WebviewWindow,
     pty_manager: State<'_, Arc<PtyManager>>,
+    script_manager: State<'_, Arc<ScriptManager>>,
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
     cols: u16,
     rows: u16,
+    secrets: Option<Vec<KeychainSecret>>,
+    startup_command: Option<String>,
+    cwd: Option<String>,
+    cwd_from_session: Option<String>,
+    program: Option<String>,
+    args: Option<Vec<String>>,
 ) -> Result<String, String> {
-    pty_manager.create_session(app, cols, rows)
+    let _timer = metrics::time_command("create_pty_session");
+    let args = args.unwrap_or_default();
+    if let Some(program) = &program {
+        validate_command(program)?;
+        validate_args(&args)?;
+        if startup_command.is_some() {
+            return Err("A startup command needs a shell, not a program".to_string());
+        }
+    }
+    let cwd = match (cwd, cwd_from_session) {
+        (Some(path), _) => {
+            let cwd = PathBuf::from(&path);
+            if !cwd.is_absolute() || !cwd.is_dir() {
+                return Err(format!("Not a directory: {}", path));
+            }
+            Some(cwd)
+        }
+        // The pane may have closed or its directory been removed since
+        (None, Some(session_id)) => pty_manager.inherited_cwd(&session_id),
+        (None, None) => None,
+    };
+    let options = SessionOptions {
+        cwd,
+        secrets: secrets.unwrap_or_default(),
+        window: Some(window.label().to_string()),
+        startup_command: startup_command
+            .as_deref()
+            .map(pty::validate_startup_command)
+            .transpose()?,
+        program: program.clone(),
+        args,
+        ..Default::default()
+    };
+    let session_id = pty_manager.create_session(app, cols, rows, options)?;
+    usage_stats.record_session_created();
+    script_manager.dispatch(
+        HookEvent::SessionCreated,
+        serde_json::json!({ "session_id": session_id, "cols": cols, "rows": rows, "program": program }),
+    );
+    Ok(session_id)
 }
 
+/// Start a session with a named profile: its shell, starting directory,
+/// environment and startup command
 #[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_pty_session_with_profile(
+    app: AppHandle,
+    window: WebviewWindow,
+    pty_manager: State<'_, Arc<PtyManager>>,
+    script_manager: State<'_, Arc<ScriptManager>>,
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    profile: String,
+    cols: u16,
+    rows: u16,
+) -> Result<String, String> {
+    let _timer = metrics::time_command("create_pty_session_with_profile");
+    if profiles::find(&settings_manager.get(), &profile).is_none() {
+        return Err(format!("Profile not found: {}", profile));
+    }
+    let options = SessionOptions {
+        window: Some(window.label().to_string()),
+        profile: Some(profile.clone()),
+        ..Default::default()
+    };
+    let session_id = pty_manager.create_session(app, cols, rows, options)?;
+    usage_stats.record_session_created();
+    usage_stats.record_feature("profiles")?;
+    script_manager.dispatch(
+        HookEvent::SessionCreated,
+        serde_json::json!({ "session_id": session_id, "cols": cols, "rows": rows, "profile": profile }),
+    );
+    Ok(session_id)
+}
+
+/// Start a session in the given directory (e.g. from the recent directories list)
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn open_session_in_dir(
+    app: AppHandle,
+    window: WebviewWindow,
+    pty_manager: State<'_, Arc<PtyManager>>,
+    script_manager: State<'_, Arc<ScriptManager>>,
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
+    recent_dirs: State<'_, Arc<RecentDirsManager>>,
+    path: String,
+    cols: u16,
+    rows: u16,
+) -> Result<String, String> {
+    let cwd = PathBuf::from(&path);
+    if !cwd.is_absolute() || !cwd.is_dir() {
+        return Err(format!("Not a directory: {}", path));
+    }
+    let options = SessionOptions {
+        cwd: Some(cwd),
+        window: Some(window.label().to_string()),
+        ..Default::default()
+    };
+    let session_id = pty_manager.create_session(app, cols, rows, options)?;
+    recent_dirs.record(&path);
+    usage_stats.record_session_created();
+    usage_stats.record_feature("recent_dirs")?;
+    script_manager.dispatch(
+        HookEvent::SessionCreated,
+        serde_json::json!({ "session_id": session_id, "cols": cols, "rows": rows, "cwd": path }),
+    );
+    Ok(session_id)
+}
+
+/// Write input to a session; with `safe_delete`, Enter on a plain `rm` is
+/// held back and `rm-intercepted` emitted (see `trash.rs`)
+#[command]
+#[allow(clippy::too_many_arguments)]
 pub async fn write_to_pty(
+    app: AppHandle,
     pty_manager: State<'_, Arc<PtyManager>>,
+    macro_manager: State<'_, Arc<MacroManager>>,
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
+    lock_manager: State<'_, Arc<LockManager>>,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    safe_delete: State<'_, Arc<SafeDelete>>,
     session_id: String,
     data: String,
-) -> Result<(), String> {
-    pty_manager.write_to_session(&session_id, &data)
+) -> Result<(), WriteError> {
+    let _timer = metrics::time_command("write_to_pty");
+    if lock_manager.is_locked() {
+        return Err(WriteError::Locked);
+    }
+    lock_manager.record_activity();
+    if settings_manager.get().safe_delete {
+        let held_back = trash::intercept(&app, &pty_manager, &safe_delete, &session_id, &data);
+        if let Some(typed) = held_back {
+            return pty_manager.write_to_session(&session_id, &typed);
+        }
+    }
+    pty_manager.write_to_session(&session_id, &data)?;
+    macro_manager.record(&data);
+    usage_stats.record_commands(usage_stats::count_submitted_lines(&data));
+    Ok(())
+}
+
+/// Paste clipboard text into a session: sanitized (see `paste.rs`) and
+/// bracketed when its program enabled bracketed paste. A multi-line or
+/// privileged paste is held back and `paste-confirmation-requested` emitted
+/// unless `confirmed` is set.
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn paste_to_pty(
+    app: AppHandle,
+    pty_manager: State<'_, Arc<PtyManager>>,
+    macro_manager: State<'_, Arc<MacroManager>>,
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
+    lock_manager: State<'_, Arc<LockManager>>,
+    session_id: String,
+    text: String,
+    confirmed: Option<bool>,
+) -> Result<PasteResult, String> {
+    let _timer = metrics::time_command("paste_to_pty");
+    lock_manager.ensure_unlocked()?;
+    lock_manager.record_activity();
+    if text.len() > paste::MAX_PASTE_LENGTH {
+        return Err(format!(
+            "Paste too large: {} bytes (max {})",
+            text.len(),
+            paste::MAX_PASTE_LENGTH
+        ));
+    }
+    let prepared = paste::sanitize_paste(&text, false);
+    if prepared.needs_confirmation && !confirmed.unwrap_or(false) {
+        events::emit_to(
+            &app,
+            &pty_manager.session_window(&session_id)?,
+            PasteConfirmationRequested {
+                session_id: session_id.clone(),
+                line_count: prepared.line_count,
+                is_multiline: prepared.is_multiline,
+                contains_sudo: prepared.contains_sudo,
+            },
+        )
+        .map_err(|e| e.to_string())?;
+        return Ok(PasteResult {
+            written: false,
+            bracketed: false,
+            removed_control_chars: prepared.removed_control_chars,
+        });
+    }
+    let bracketed = pty_manager.paste(&session_id, &prepared.text)?;
+    macro_manager.record(&prepared.text);
+    usage_stats.record_commands(usage_stats::count_submitted_lines(&prepared.text));
+    Ok(PasteResult {
+        written: true,
+        bracketed,
+        removed_control_chars: prepared.removed_control_chars,
+    })
+}
+
+/// Encode a key event with the session's negotiated keyboard modes and write it.
+/// Returns false when the key produces no terminal input.
+#[command]
+pub async fn send_key_event(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    macro_manager: State<'_, Arc<MacroManager>>,
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
+    lock_manager: State<'_, Arc<LockManager>>,
+    session_id: String,
+    event: KeyEvent,
+) -> Result<bool, String> {
+    let _timer = metrics::time_command("send_key_event");
+    lock_manager.ensure_unlocked()?;
+    lock_manager.record_activity();
+    match pty_manager.send_key(&session_id, &event)? {
+        Some(data) => {
+            macro_manager.record(&data);
+            usage_stats.record_commands(usage_stats::count_submitted_lines(&data));
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Write a Cmd shortcut to the session when its foreground program is listed
+/// for it in `shortcut_passthrough`. Returns false when the frontend should
+/// run the app's action instead.
+#[command]
+pub async fn pass_shortcut(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    lock_manager: State<'_, Arc<LockManager>>,
+    session_id: String,
+    event: KeyEvent,
+) -> Result<bool, String> {
+    lock_manager.ensure_unlocked()?;
+    lock_manager.record_activity();
+    let passthrough = settings_manager.get().shortcut_passthrough;
+    Ok(pty_manager
+        .pass_shortcut(&session_id, &event, &passthrough)?
+        .is_some())
+}
+
+/// Report a mouse event to the session's program if it enabled mouse tracking.
+/// Returns false when the frontend should handle the event itself (selection).
+#[command]
+pub async fn send_mouse_event(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    lock_manager: State<'_, Arc<LockManager>>,
+    session_id: String,
+    event: MouseEvent,
+) -> Result<bool, String> {
+    let _timer = metrics::time_command("send_mouse_event");
+    lock_manager.ensure_unlocked()?;
+    lock_manager.record_activity();
+    let policy = settings_manager.get().mouse_policy();
+    pty_manager.send_mouse(&session_id, &event, &policy)
 }
 
 #[command]
@@ -28,17 +313,219 @@ pub async fn resize_pty(
     cols: u16,
     rows: u16,
 ) -> Result<(), String> {
+    let _timer = metrics::time_command("resize_pty");
     pty_manager.resize_session(&session_id, cols, rows)
 }
 
+/// Close a session; protected sessions are only closed with `force`.
+/// With `keep_sessions_alive`, closing a pane detaches its running session
+/// instead, and closing a detached session ends it.
 #[command]
 pub async fn close_pty_session(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    session_id: String,
+    force: Option<bool>,
+) -> Result<(), String> {
+    let force = force.unwrap_or(false);
+    if !force
+        && settings_manager.get().keep_sessions_alive
+        && pty_manager.detach_session(&session_id)?
+    {
+        return Ok(());
+    }
+    pty_manager.close_session(&session_id, force)
+}
+
+/// Sessions kept alive without a pane, for the session switcher
+#[command]
+pub async fn list_detached_sessions(
+    pty_manager: State<'_, Arc<PtyManager>>,
+) -> Result<Vec<SessionSummary>, String> {
+    Ok(pty_manager.list_detached_sessions())
+}
+
+/// Show a detached session in a pane of the calling window, returning its
+/// recent output (escape sequences included) to write into the terminal
+/// before resizing it
+#[command]
+pub async fn reattach_pty_session(
+    app: AppHandle,
+    window: WebviewWindow,
+    pty_manager: State<'_, Arc<PtyManager>>,
+    session_id: String,
+) -> Result<String, String> {
+    let window_label = window.label().to_string();
+    let focused = window.is_focused().unwrap_or(false);
+    let (output, from_window) =
+        pty_manager.reattach_session(&session_id, &window_label, focused)?;
+    if from_window != window_label {
+        let _ = events::emit(
+            &app,
+            SessionMoved {
+                session_id,
+                from_window,
+                to_window: window_label,
+            },
+        );
+    }
+    Ok(output)
+}
+
+/// Reject all input to a session (e.g. one tailing production logs)
+#[command]
+pub async fn set_session_read_only(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    session_id: String,
+    read_only: bool,
+) -> Result<(), String> {
+    pty_manager.set_session_read_only(&session_id, read_only)
+}
+
+/// Protect a session from being closed without confirmation
+#[command]
+pub async fn set_session_protected(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    session_id: String,
+    protected: bool,
+) -> Result<(), String> {
+    pty_manager.set_session_protected(&session_id, protected)
+}
+
+/// Clear a session (Cmd+K): purges the Rust-side scrollback and emits
+/// `session-cleared` so the frontend clears the terminal
+#[command]
+pub async fn clear_session_scrollback(
+    app: AppHandle,
     pty_manager: State<'_, Arc<PtyManager>>,
     session_id: String,
 ) -> Result<(), String> {
-    pty_manager.close_session(&session_id)
+    pty_manager.clear_scrollback(&session_id)?;
+    let _ = events::emit(
+        &app,
+        SessionCleared {
+            session_id,
+            reset: false,
+        },
+    );
+    Ok(())
 }
 
+/// Clear a session and reset its terminal state; the frontend does a full
+/// reset (RIS) on the `session-cleared` event
+#[command]
+pub async fn reset_session(
+    app: AppHandle,
+    pty_manager: State<'_, Arc<PtyManager>>,
+    session_id: String,
+) -> Result<(), String> {
+    pty_manager.reset_session(&session_id)?;
+    let _ = events::emit(
+        &app,
+        SessionCleared {
+            session_id,
+            reset: true,
+        },
+    );
+    Ok(())
+}
+
+/// Hand a session over to another window without restarting its shell. Its
+/// output and prompt events go to that window from now on; `session-moved` is
+/// emitted so the old window drops the tab and the new one opens and focuses
+/// it (its `resize_pty` makes full-screen programs redraw).
+#[command]
+pub async fn move_session_to_window(
+    app: AppHandle,
+    pty_manager: State<'_, Arc<PtyManager>>,
+    session_id: String,
+    window_label: String,
+) -> Result<(), String> {
+    let window = app
+        .get_webview_window(&window_label)
+        .ok_or_else(|| format!("Window not found: {}", window_label))?;
+    let focused = window.is_focused().unwrap_or(false);
+    let from_window = pty_manager.move_session(&session_id, &window_label, focused)?;
+    if from_window != window_label {
+        let _ = events::emit(
+            &app,
+            SessionMoved {
+                session_id,
+                from_window,
+                to_window: window_label,
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Duration, exit code and command line of the last command a session
+/// finished; `None` until one finishes or if the shell doesn't mark commands
+#[command]
+pub async fn get_last_command_stats(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    session_id: String,
+) -> Result<Option<CommandStats>, String> {
+    pty_manager.last_command_stats(&session_id)
+}
+
+/// The process in the foreground of a session's terminal, with whether it's
+/// the shell at its prompt; `None` when it can't be determined
+#[command]
+pub async fn get_foreground_process(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    session_id: String,
+) -> Result<Option<ForegroundProcess>, String> {
+    pty_manager.foreground_process(&session_id)
+}
+
+/// Whether a session's shell has started processes (a command, background
+/// jobs) that closing it would kill, so the frontend can ask first
+#[command]
+pub async fn session_has_running_process(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    session_id: String,
+) -> Result<RunningProcesses, String> {
+    pty_manager.running_processes(&session_id)
+}
+
+/// Which shell integration sequences (OSC 7, OSC 133) a session has sent,
+/// and the features that don't work without the missing ones
+#[command]
+pub async fn check_shell_integration(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    session_id: String,
+) -> Result<IntegrationHealth, String> {
+    let marks = pty_manager.integration_marks(&session_id)?;
+    Ok(marks.into())
+}
+
+/// Start appending a session's output to a transcript under the app data
+/// directory (`session_log_format` unless `format` is given), returning the
+/// file's path
+#[command]
+pub async fn start_session_log(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    session_id: String,
+    format: Option<TranscriptFormat>,
+) -> Result<String, String> {
+    let format = format.unwrap_or(settings_manager.get().session_log_format);
+    let path = pty_manager.start_session_log(&session_id, format)?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Stop logging a session, returning the transcript's path if it was logged
+#[command]
+pub async fn stop_session_log(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    session_id: String,
+) -> Result<Option<String>, String> {
+    let path = pty_manager.stop_session_log(&session_id)?;
+    Ok(path.map(|path| path.to_string_lossy().into_owned()))
+}
+
+/// A session's working directory, as reported with OSC 7 when the shell does
 #[command]
 pub async fn get_pty_cwd(
     pty_manager: State<'_, Arc<PtyManager>>,
@@ -46,3 +533,104 @@ pub async fn get_pty_cwd(
 ) -> Result<Option<String>, String> {
     pty_manager.get_session_cwd(&session_id)
 }
+
+/// Set a session's tags and color label
+#[command]
+pub async fn set_session_labels(
+    app: AppHandle,
+    pty_manager: State<'_, Arc<PtyManager>>,
+    session_store: State<'_, Arc<SessionStore>>,
+    session_id: String,
+    tags: Vec<String>,
+    color: Option<LabelColor>,
+) -> Result<SessionLabels, String> {
+    let labels = SessionLabels::new(tags, color)?;
+    pty_manager.set_session_labels(&session_id, labels.clone())?;
+    session_store.session_labels_changed(&session_id, &labels);
+    let _ = events::emit(
+        &app,
+        SessionLabelsChanged {
+            session_id,
+            labels: labels.clone(),
+        },
+    );
+    Ok(labels)
+}
+
+#[command]
+pub async fn get_session_labels(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    session_id: String,
+) -> Result<SessionLabels, String> {
+    pty_manager.get_session_labels(&session_id)
+}
+
+/// Labels of every session, optionally only those with the given tag
+#[command]
+pub async fn list_session_labels(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    tag: Option<String>,
+) -> Result<HashMap<String, SessionLabels>, String> {
+    let mut labels = pty_manager.list_session_labels();
+    if let Some(tag) = tag {
+        labels.retain(|_, labels| labels.has_tag(&tag));
+    }
+    Ok(labels)
+}
+
+/// Every live session with its directory, running command and last input
+/// and output times, for the session switcher
+#[command]
+pub async fn list_pty_sessions(
+    pty_manager: State<'_, Arc<PtyManager>>,
+) -> Result<Vec<SessionSummary>, String> {
+    Ok(pty_manager.list_sessions())
+}
+
+/// Search one session's Rust-side scrollback for plain text or, with
+/// `regex`, a regular expression
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn search_scrollback(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
+    session_id: String,
+    query: String,
+    regex: Option<bool>,
+    case_sensitive: Option<bool>,
+    context_lines: Option<usize>,
+) -> Result<Vec<ScrollbackHit>, String> {
+    let _timer = metrics::time_command("search_scrollback");
+    let defaults = SearchOptions::default();
+    let options = SearchOptions {
+        case_sensitive: case_sensitive.unwrap_or(defaults.case_sensitive),
+        context_lines: context_lines.unwrap_or(defaults.context_lines),
+        width_mode: settings_manager.get().unicode_width_mode,
+    };
+    let hits = pty_manager.search_session(&session_id, &query, regex.unwrap_or(false), &options)?;
+    usage_stats.record_feature("scrollback_search")?;
+    Ok(hits)
+}
+
+/// Search the Rust-side scrollback of every session
+#[command]
+pub async fn search_all_sessions(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
+    query: String,
+    case_sensitive: Option<bool>,
+    context_lines: Option<usize>,
+) -> Result<Vec<SessionSearchResult>, String> {
+    let _timer = metrics::time_command("search_all_sessions");
+    let defaults = SearchOptions::default();
+    let options = SearchOptions {
+        case_sensitive: case_sensitive.unwrap_or(defaults.case_sensitive),
+        context_lines: context_lines.unwrap_or(defaults.context_lines),
+        width_mode: settings_manager.get().unicode_width_mode,
+    };
+    let results = pty_manager.search_all_sessions(&query, &options)?;
+    usage_stats.record_feature("scrollback_search")?;
+    Ok(results)
+}
@@ -0,0 +1,339 @@
+//! Scripted PTY backend for tests
+//!
+//! `MockPtyBackend` stands in for the local backend (see `pty_backend.rs`):
+//! nothing is spawned, spawned "shells" write the scripted output, and the
+//! test drives the rest through the `MockPty` handle, pushing more output,
+//! reading what the app wrote and deciding when the shell exits. Build with
+//! the `testing` feature to use it from integration tests:
+//!
+//! ```ignore
+//! let backend = Arc::new(MockPtyBackend::new().with_output("$ "));
+//! let pty_manager = PtyManager::with_backend(backend.clone());
+//! // ... create a session, then:
+//! let pty = backend.last().unwrap();
+//! pty.push_output("hello\r\n");
+//! pty.exit(0); // or pty.signal(libc::SIGKILL)
+//! ```
+
+use crate::pty_backend::{ExitStatus, SessionBackend, SessionProcess, SpawnRequest};
+use parking_lot::{Condvar, Mutex};
+use std::collections::VecDeque;
+use std::io::{Read, Result as IoResult, Write};
+use std::sync::Arc;
+
+/// Name of the mock backend
+pub const MOCK_BACKEND: &str = "mock";
+
+#[derive(Debug, Default)]
+struct MockState {
+    output: VecDeque<u8>,
+    input: Vec<u8>,
+    /// How the shell ended, once it has exited
+    exit_status: Option<ExitStatus>,
+    cols: u16,
+    rows: u16,
+    foreground_process: Option<u32>,
+    /// Terminal echo turned off; a real PTY echoes until a program turns it off
+    echo_off: bool,
+}
+
+#[derive(Debug)]
+struct Shared {
+    request: SpawnRequest,
+    state: Mutex<MockState>,
+    changed: Condvar,
+}
+
+impl Shared {
+    fn update(&self, f: impl FnOnce(&mut MockState)) {
+        f(&mut self.state.lock());
+        self.changed.notify_all();
+    }
+}
+
+/// Control handle of one spawned mock shell
+#[derive(Debug, Clone)]
+pub struct MockPty {
+    shared: Arc<Shared>,
+}
+
+impl MockPty {
+    /// What the shell was spawned with
+    pub fn request(&self) -> &SpawnRequest {
+        &self.shared.request
+    }
+
+    /// Make the shell write output
+    pub fn push_output(&self, data: impl AsRef<[u8]>) {
+        self.shared
+            .update(|state| state.output.extend(data.as_ref()));
+    }
+
+    /// Make the shell exit; output already pushed is still read first
+    pub fn exit(&self, code: u32) {
+        self.end(ExitStatus::exited(code as i32));
+    }
+
+    /// Make the shell die from a signal
+    pub fn signal(&self, signal: i32) {
+        self.end(ExitStatus::signaled(signal));
+    }
+
+    fn end(&self, status: ExitStatus) {
+        self.shared.update(|state| {
+            state.exit_status.get_or_insert(status);
+        });
+    }
+
+    pub fn has_exited(&self) -> bool {
+        self.shared.state.lock().exit_status.is_some()
+    }
+
+    /// Everything written to the shell so far
+    pub fn input(&self) -> Vec<u8> {
+        self.shared.state.lock().input.clone()
+    }
+
+    /// Current terminal size as (cols, rows)
+    pub fn size(&self) -> (u16, u16) {
+        let state = self.shared.state.lock();
+        (state.cols, state.rows)
+    }
+
+    /// Pretend a command (rather than the shell) is in the foreground
+    pub fn set_foreground_process(&self, pid: Option<u32>) {
+        self.shared.update(|state| state.foreground_process = pid);
+    }
+
+    /// Pretend the program turned terminal echo off (a password prompt) or on
+    pub fn set_echo(&self, echo: bool) {
+        self.shared.update(|state| state.echo_off = !echo);
+    }
+}
+
+/// Backend spawning mock shells
+#[derive(Default)]
+pub struct MockPtyBackend {
+    script: Vec<u8>,
+    exit_code: Option<u32>,
+    spawned: Mutex<Vec<MockPty>>,
+}
+
+impl MockPtyBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Output every spawned shell writes right away
+    pub fn with_output(mut self, data: impl AsRef<[u8]>) -> Self {
+        self.script.extend_from_slice(data.as_ref());
+        self
+    }
+
+    /// Make every spawned shell exit with `code` after its scripted output
+    pub fn with_exit(mut self, code: u32) -> Self {
+        self.exit_code = Some(code);
+        self
+    }
+
+    /// Handles of all shells spawned so far, oldest first
+    pub fn spawned(&self) -> Vec<MockPty> {
+        self.spawned.lock().clone()
+    }
+
+    /// Handle of the most recently spawned shell
+    pub fn last(&self) -> Option<MockPty> {
+        self.spawned.lock().last().cloned()
+    }
+}
+
+impl SessionBackend for MockPtyBackend {
+    fn name(&self) -> &'static str {
+        MOCK_BACKEND
+    }
+
+    fn spawn(&self, request: SpawnRequest) -> Result<Box<dyn SessionProcess>, String> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(MockState {
+                output: self.script.iter().copied().collect(),
+                exit_status: self.exit_code.map(|code| ExitStatus::exited(code as i32)),
+                cols: request.cols,
+                rows: request.rows,
+                ..Default::default()
+            }),
+            request,
+            changed: Condvar::new(),
+        });
+        self.spawned.lock().push(MockPty {
+            shared: shared.clone(),
+        });
+        Ok(Box::new(MockProcess(shared)))
+    }
+}
+
+struct MockProcess(Arc<Shared>);
+
+impl SessionProcess for MockProcess {
+    fn take_reader(&mut self) -> Result<Box<dyn Read + Send>, String> {
+        Ok(Box::new(MockReader(self.0.clone())))
+    }
+
+    fn take_writer(&mut self) -> Result<Box<dyn Write + Send>, String> {
+        Ok(Box::new(MockWriter(self.0.clone())))
+    }
+
+    fn resize(&self, cols: u16, rows: u16) -> Result<(), String> {
+        self.0.update(|state| {
+            state.cols = cols;
+            state.rows = rows;
+        });
+        Ok(())
+    }
+
+    /// No real process, so nothing is looked up by PID
+    fn pid(&self) -> Option<u32> {
+        None
+    }
+
+    fn foreground_process(&self) -> Option<u32> {
+        self.0.state.lock().foreground_process
+    }
+
+    fn echoes_input(&self) -> Option<bool> {
+        Some(!self.0.state.lock().echo_off)
+    }
+
+    fn wait(&mut self) -> Result<ExitStatus, String> {
+        let mut state = self.0.state.lock();
+        loop {
+            if let Some(status) = &state.exit_status {
+                return Ok(status.clone());
+            }
+            self.0.changed.wait(&mut state);
+        }
+    }
+
+    fn try_wait(&mut self) -> Result<Option<ExitStatus>, String> {
+        Ok(self.0.state.lock().exit_status.clone())
+    }
+
+    /// Hangs up like the local backend does
+    fn kill(&mut self) -> Result<(), String> {
+        self.0.update(|state| {
+            state
+                .exit_status
+                .get_or_insert(ExitStatus::signaled(libc::SIGHUP));
+        });
+        Ok(())
+    }
+}
+
+/// Blocks until there is output, and reads EOF once the shell has exited
+struct MockReader(Arc<Shared>);
+
+impl Read for MockReader {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        let mut state = self.0.state.lock();
+        loop {
+            if !state.output.is_empty() {
+                let n = buf.len().min(state.output.len());
+                for (byte, out) in state.output.drain(..n).zip(buf.iter_mut()) {
+                    *out = byte;
+                }
+                return Ok(n);
+            }
+            if state.exit_status.is_some() {
+                return Ok(0);
+            }
+            self.0.changed.wait(&mut state);
+        }
+    }
+}
+
+struct MockWriter(Arc<Shared>);
+
+impl Write for MockWriter {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        self.0.update(|state| state.input.extend_from_slice(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn spawn(backend: &MockPtyBackend) -> Box<dyn SessionProcess> {
+        backend
+            .spawn(SpawnRequest {
+                program: "/bin/zsh".to_string(),
+                args: vec!["-l".to_string()],
+                cols: 80,
+                rows: 24,
+                ..Default::default()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_scripted_output_then_exit() {
+        let backend = MockPtyBackend::new().with_output("$ ").with_exit(3);
+        let mut process = spawn(&backend);
+        let mut output = String::new();
+        process
+            .take_reader()
+            .unwrap()
+            .read_to_string(&mut output)
+            .unwrap();
+        assert_eq!(output, "$ ");
+        assert_eq!(process.wait(), Ok(ExitStatus::exited(3)));
+        assert!(backend.last().unwrap().has_exited());
+    }
+
+    #[test]
+    fn test_controlled_session() {
+        let backend = MockPtyBackend::new();
+        let mut process = spawn(&backend);
+        let handle = backend.last().unwrap();
+        assert_eq!(handle.request().args, ["-l"]);
+
+        let mut reader = process.take_reader().unwrap();
+        let reader_thread = thread::spawn(move || {
+            let mut output = Vec::new();
+            reader.read_to_end(&mut output).unwrap();
+            output
+        });
+        handle.push_output("hello\r\n");
+        assert_eq!(process.try_wait(), Ok(None));
+        handle.exit(0);
+        assert_eq!(reader_thread.join().unwrap(), b"hello\r\n");
+        assert_eq!(process.wait(), Ok(ExitStatus::exited(0)));
+
+        let mut writer = process.take_writer().unwrap();
+        writer.write_all(b"ls\r").unwrap();
+        assert_eq!(handle.input(), b"ls\r");
+
+        process.resize(120, 40).unwrap();
+        assert_eq!(handle.size(), (120, 40));
+
+        assert_eq!(process.echoes_input(), Some(true));
+        handle.set_echo(false);
+        assert_eq!(process.echoes_input(), Some(false));
+    }
+
+    #[test]
+    fn test_kill_unblocks_reader() {
+        let backend = MockPtyBackend::new();
+        let mut process = spawn(&backend);
+        let mut reader = process.take_reader().unwrap();
+        let reader_thread = thread::spawn(move || reader.read(&mut [0u8; 16]).unwrap());
+        process.kill().unwrap();
+        assert_eq!(reader_thread.join().unwrap(), 0);
+        assert_eq!(process.wait(), Ok(ExitStatus::signaled(libc::SIGHUP)));
+    }
+}
@@ -0,0 +1,126 @@
+//! Confirmation before protected sessions are killed
+//!
+//! Sessions marked with `set_session_protected` (say, a production ssh
+//! session) are not closed by `close_pty_session` unless it is forced. The
+//! same applies to closing the window and quitting: while protected sessions
+//! exist those are prevented and `close-blocked` is emitted, so the frontend
+//! can ask for confirmation and call `quit_app` with `force`.
+
+use crate::events;
+use crate::pty::PtyManager;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{command, AppHandle, Manager, State};
+use tracing::info;
+
+/// What was blocked by protected sessions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockedAction {
+    CloseWindow,
+    Quit,
+}
+
+/// Payload of the `close-blocked` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloseBlocked {
+    pub action: BlockedAction,
+    pub protected_sessions: Vec<String>,
+}
+
+/// Remembers a confirmed quit, so the exit request it triggers isn't blocked
+pub struct QuitGuard {
+    force_quit: AtomicBool,
+}
+
+impl Default for QuitGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QuitGuard {
+    pub fn new() -> Self {
+        Self {
+            force_quit: AtomicBool::new(false),
+        }
+    }
+}
+
+/// Check whether an action would kill protected sessions. If so, emits
+/// `close-blocked` and returns true; the caller must then prevent the action.
+pub fn is_blocked(app: &AppHandle, action: BlockedAction) -> bool {
+    let forced = app
+        .try_state::<Arc<QuitGuard>>()
+        .is_some_and(|guard| guard.force_quit.load(Ordering::SeqCst));
+    if forced {
+        return false;
+    }
+    let protected_sessions = app
+        .try_state::<Arc<PtyManager>>()
+        .map(|pty_manager| pty_manager.protected_sessions())
+        .unwrap_or_default();
+    if protected_sessions.is_empty() {
+        return false;
+    }
+    info!(
+        ?action,
+        count = protected_sessions.len(),
+        "Blocked by protected sessions"
+    );
+    let _ = events::emit(
+        app,
+        CloseBlocked {
+            action,
+            protected_sessions,
+        },
+    );
+    true
+}
+
+/// Quit the app, unless protected sessions would be killed and `force` isn't set
+pub fn quit(app: &AppHandle, force: bool) -> Result<(), String> {
+    if force {
+        if let Some(guard) = app.try_state::<Arc<QuitGuard>>() {
+            guard.force_quit.store(true, Ordering::SeqCst);
+        }
+    } else if is_blocked(app, BlockedAction::Quit) {
+        return Err("Protected sessions are open".to_string());
+    }
+    #[cfg(target_os = "macos")]
+    crate::macos::cleanup();
+    app.exit(0);
+    Ok(())
+}
+
+/// Quit the app; without `force` this fails while protected sessions are open
+#[command]
+pub fn quit_app(app: AppHandle, force: Option<bool>) -> Result<(), String> {
+    quit(&app, force.unwrap_or(false))
+}
+
+/// Protected sessions that would block closing the window or quitting
+#[command]
+pub fn get_protected_sessions(
+    pty_manager: State<'_, Arc<PtyManager>>,
+) -> Result<Vec<String>, String> {
+    Ok(pty_manager.protected_sessions())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_blocked_serialization() {
+        let event = CloseBlocked {
+            action: BlockedAction::CloseWindow,
+            protected_sessions: vec!["s1".to_string()],
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"action":"close_window","protected_sessions":["s1"]}"#
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Recently visited directories, ranked by frecency
+//!
+//! Shells with OSC 7 integration report every directory change. Each new
+//! directory is recorded here (across all sessions) and persisted in the
+//! session store, so the frontend can offer a jump list of the user's usual
+//! folders via `list_recent_dirs` and start a session in one with
+//! `open_session_in_dir`.
+
+use crate::session_store::SessionStore;
+use crate::unix_time::now_secs;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{command, State};
+use tracing::error;
+
+/// Maximum number of directories remembered
+const MAX_RECENT_DIRS: usize = 200;
+
+/// Default number of directories returned by `list_recent_dirs`
+const DEFAULT_LIST_LIMIT: usize = 10;
+
+const HOUR_SECS: u64 = 60 * 60;
+const DAY_SECS: u64 = 24 * HOUR_SECS;
+const WEEK_SECS: u64 = 7 * DAY_SECS;
+
+/// A visited directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentDir {
+    pub path: String,
+    pub visits: u64,
+    /// Last visit, in seconds since the Unix epoch
+    pub last_visited: u64,
+}
+
+impl RecentDir {
+    /// Visit count weighted by how recently the directory was visited
+    pub fn frecency(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_visited);
+        let weight = if age < HOUR_SECS {
+            4.0
+        } else if age < DAY_SECS {
+            2.0
+        } else if age < WEEK_SECS {
+            0.5
+        } else {
+            0.25
+        };
+        self.visits as f64 * weight
+    }
+}
+
+/// Persisted frecency list of visited directories
+pub struct RecentDirsManager {
+    dirs: Mutex<Vec<RecentDir>>,
+    store: Arc<SessionStore>,
+}
+
+impl RecentDirsManager {
+    /// Create a new manager backed by the session store
+    pub fn new(store: Arc<SessionStore>) -> Self {
+        let dirs = store.recent_dirs().unwrap_or_else(|e| {
+            error!("Failed to load recent directories: {}", e);
+            Vec::new()
+        });
+        Self {
+            dirs: Mutex::new(dirs),
+            store,
+        }
+    }
+
+    /// Record a visit to a directory
+    pub fn record(&self, path: &str) {
+        self.record_at(path, now_secs());
+    }
+
+    fn record_at(&self, path: &str, now: u64) {
+        if !path.starts_with('/') {
+            return;
+        }
+        let path = match path.trim_end_matches('/') {
+            "" => "/",
+            trimmed => trimmed,
+        };
+
+        let mut dirs = self.dirs.lock();
+        match dirs.iter_mut().find(|dir| dir.path == path) {
+            Some(dir) => {
+                dir.visits += 1;
+                dir.last_visited = now;
+                self.store.save_recent_dir(dir);
+            }
+            None => {
+                if dirs.len() >= MAX_RECENT_DIRS {
+                    // Make room by dropping the lowest-ranked directory
+                    if let Some(index) = dirs
+                        .iter()
+                        .enumerate()
+                        .min_by(|a, b| a.1.frecency(now).total_cmp(&b.1.frecency(now)))
+                        .map(|(index, _)| index)
+                    {
+                        let evicted = dirs.remove(index);
+                        self.store.remove_recent_dir(&evicted.path);
+                    }
+                }
+                let dir = RecentDir {
+                    path: path.to_string(),
+                    visits: 1,
+                    last_visited: now,
+                };
+                self.store.save_recent_dir(&dir);
+                dirs.push(dir);
+            }
+        }
+    }
+
+    /// Directories ranked by frecency, most relevant first
+    pub fn list(&self, limit: usize) -> Vec<RecentDir> {
+        self.ranked(limit, now_secs(), |path| Path::new(path).is_dir())
+    }
+
+    fn ranked(&self, limit: usize, now: u64, exists: impl Fn(&str) -> bool) -> Vec<RecentDir> {
+        let mut dirs: Vec<RecentDir> = self
+            .dirs
+            .lock()
+            .iter()
+            .filter(|dir| exists(&dir.path))
+            .cloned()
+            .collect();
+        dirs.sort_by(|a, b| {
+            b.frecency(now)
+                .total_cmp(&a.frecency(now))
+                .then(b.last_visited.cmp(&a.last_visited))
+        });
+        dirs.truncate(limit);
+        dirs
+    }
+}
+
+/// Recently visited directories, most relevant first (missing ones are skipped)
+#[command]
+pub fn list_recent_dirs(
+    recent_dirs: State<'_, Arc<RecentDirsManager>>,
+    limit: Option<usize>,
+) -> Result<Vec<RecentDir>, String> {
+    Ok(recent_dirs.list(limit.unwrap_or(DEFAULT_LIST_LIMIT)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_store::DATABASE_FILE;
+    use tempfile::TempDir;
+
+    const NOW: u64 = 1_750_000_000;
+
+    fn manager(temp_dir: &TempDir) -> RecentDirsManager {
+        RecentDirsManager::new(Arc::new(SessionStore::open(
+            &temp_dir.path().join(DATABASE_FILE),
+        )))
+    }
+
+    fn paths(dirs: &[RecentDir]) -> Vec<&str> {
+        dirs.iter().map(|dir| dir.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_frecency_ranking() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir);
+        // Visited often, but weeks ago
+        for _ in 0..5 {
+            manager.record_at("/old/project", NOW - 3 * WEEK_SECS);
+        }
+        // Visited twice just now
+        manager.record_at("/new/project", NOW);
+        manager.record_at("/new/project/", NOW);
+        manager.record_at("/tmp", NOW - 2 * DAY_SECS);
+
+        let ranked = manager.ranked(10, NOW, |_| true);
+        assert_eq!(paths(&ranked), ["/new/project", "/old/project", "/tmp"]);
+        assert_eq!(ranked[0].visits, 2);
+        assert_eq!(paths(&manager.ranked(1, NOW, |_| true)), ["/new/project"]);
+    }
+
+    #[test]
+    fn test_ignores_relative_and_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir);
+        manager.record_at("relative/path", NOW);
+        manager.record_at("/gone", NOW);
+        manager.record_at("/", NOW);
+        let ranked = manager.ranked(10, NOW, |path| path != "/gone");
+        assert_eq!(paths(&ranked), ["/"]);
+    }
+
+    #[test]
+    fn test_evicts_lowest_ranked() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir);
+        manager.record_at("/stale", NOW - 2 * WEEK_SECS);
+        for i in 1..MAX_RECENT_DIRS {
+            manager.record_at(&format!("/dir/{}", i), NOW);
+        }
+        manager.record_at("/newest", NOW);
+
+        let ranked = manager.ranked(usize::MAX, NOW, |_| true);
+        assert_eq!(ranked.len(), MAX_RECENT_DIRS);
+        assert!(!paths(&ranked).contains(&"/stale"));
+        assert!(paths(&ranked).contains(&"/newest"));
+    }
+
+    #[test]
+    fn test_persistence() {
+        let temp_dir = TempDir::new().unwrap();
+        manager(&temp_dir).record_at("/Users/me/code", NOW);
+        let reloaded = manager(&temp_dir);
+        assert_eq!(
+            paths(&reloaded.ranked(10, NOW, |_| true)),
+            ["/Users/me/code"]
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! Release notes for the "What's new" dialog
+//!
+//! Notes are fetched from the GitHub release of the requested version, so the
+//! frontend never talks to GitHub directly. A published release doesn't
+//! change, so each version is fetched once and cached in the app data
+//! directory; the cache also keeps the dialog working offline.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{command, State};
+use tokio::process::Command as TokioCommand;
+use tracing::{debug, warn};
+
+/// GitHub API endpoint for a release, by tag
+const RELEASE_API_URL: &str = "https://api.github.com/repos/ttaatoo/microterm/releases/tags";
+
+/// How long to wait for the release feed
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum length of a version string
+const MAX_VERSION_LENGTH: usize = 32;
+
+/// Release notes larger than this are truncated
+const MAX_NOTES_LENGTH: usize = 64 * 1024;
+
+/// Changelog entry for one release
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseNotes {
+    pub version: String,
+    /// Release title
+    pub name: String,
+    /// Changelog in Markdown
+    pub body: String,
+    pub published_at: Option<String>,
+    /// Release page URL
+    pub url: String,
+}
+
+/// Fields of the GitHub release API response we use
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    published_at: Option<String>,
+    html_url: String,
+}
+
+/// Normalize and validate a version (`1.5.0` or `v1.5.0`)
+fn normalize_version(version: &str) -> Result<String, String> {
+    let version = version.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+    if version.is_empty() {
+        return Err("Version cannot be empty".to_string());
+    }
+    if version.len() > MAX_VERSION_LENGTH {
+        return Err(format!("Version too long: {}", version.len()));
+    }
+    if !version.starts_with(|c: char| c.is_ascii_digit())
+        || !version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))
+    {
+        return Err(format!("Invalid version: {}", version));
+    }
+    Ok(version.to_string())
+}
+
+/// Convert a GitHub release API response into release notes
+fn parse_release(version: &str, json: &str) -> Result<ReleaseNotes, String> {
+    let release: GithubRelease =
+        serde_json::from_str(json).map_err(|e| format!("Invalid release feed response: {}", e))?;
+    let name = release
+        .name
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| format!("v{}", version));
+    let mut body = release.body.unwrap_or_default().replace("\r\n", "\n");
+    if body.len() > MAX_NOTES_LENGTH {
+        let mut end = MAX_NOTES_LENGTH;
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        body.truncate(end);
+    }
+    Ok(ReleaseNotes {
+        version: version.to_string(),
+        name,
+        body,
+        published_at: release.published_at,
+        url: release.html_url,
+    })
+}
+
+/// Fetch the GitHub release for a version
+async fn fetch_release(version: &str) -> Result<ReleaseNotes, String> {
+    let url = format!("{}/v{}", RELEASE_API_URL, version);
+    let child = TokioCommand::new("/usr/bin/curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--location",
+            "--max-time",
+            &FETCH_TIMEOUT.as_secs().to_string(),
+            "--header",
+            "Accept: application/vnd.github+json",
+            "--user-agent",
+            "microterm",
+            &url,
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start curl: {}", e))?;
+
+    let output = tokio::time::timeout(FETCH_TIMEOUT * 2, child.wait_with_output())
+        .await
+        .map_err(|_| "Timed out fetching release notes".to_string())?
+        .map_err(|e| format!("Failed to fetch release notes: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "Failed to fetch release notes for v{}: {}",
+            version,
+            stderr.trim()
+        ));
+    }
+    parse_release(version, &String::from_utf8_lossy(&output.stdout))
+}
+
+/// Release notes cache, in memory and on disk
+pub struct ReleaseNotesManager {
+    cache: Mutex<HashMap<String, ReleaseNotes>>,
+    cache_dir: PathBuf,
+}
+
+impl ReleaseNotesManager {
+    /// Create a manager caching notes in the given directory
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self {
+            cache: Mutex::new(HashMap::new()),
+            cache_dir,
+        }
+    }
+
+    fn cache_path(&self, version: &str) -> PathBuf {
+        self.cache_dir.join(format!("{}.json", version))
+    }
+
+    /// Cached notes for a (normalized) version, if any
+    fn cached(&self, version: &str) -> Option<ReleaseNotes> {
+        if let Some(notes) = self.cache.lock().get(version) {
+            return Some(notes.clone());
+        }
+        let content = fs::read_to_string(self.cache_path(version)).ok()?;
+        match serde_json::from_str::<ReleaseNotes>(&content) {
+            Ok(notes) => {
+                self.cache.lock().insert(version.to_string(), notes.clone());
+                Some(notes)
+            }
+            Err(e) => {
+                warn!(version = %version, error = %e, "Ignoring corrupt release notes cache");
+                None
+            }
+        }
+    }
+
+    /// Store notes in memory and on disk
+    fn store(&self, notes: &ReleaseNotes) {
+        self.cache
+            .lock()
+            .insert(notes.version.clone(), notes.clone());
+        let result = serde_json::to_string_pretty(notes)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                fs::create_dir_all(&self.cache_dir).map_err(|e| e.to_string())?;
+                fs::write(self.cache_path(&notes.version), json).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            warn!(version = %notes.version, error = %e, "Failed to cache release notes");
+        }
+    }
+
+    /// Release notes for a version, fetched on first use
+    pub async fn get(&self, version: &str) -> Result<ReleaseNotes, String> {
+        let version = normalize_version(version)?;
+        if let Some(notes) = self.cached(&version) {
+            debug!(version = %version, "Release notes served from cache");
+            return Ok(notes);
+        }
+        let notes = fetch_release(&version).await?;
+        self.store(&notes);
+        Ok(notes)
+    }
+}
+
+/// Changelog entry for a version, from the release feed or cache
+#[command]
+pub async fn get_release_notes(
+    release_notes_manager: State<'_, Arc<ReleaseNotesManager>>,
+    version: String,
+) -> Result<ReleaseNotes, String> {
+    release_notes_manager.get(&version).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_normalize_version() {
+        assert_eq!(normalize_version("1.5.0").unwrap(), "1.5.0");
+        assert_eq!(
+            normalize_version(" v1.6.0-beta.1 ").unwrap(),
+            "1.6.0-beta.1"
+        );
+        assert!(normalize_version("").is_err());
+        assert!(normalize_version("latest").is_err());
+        assert!(normalize_version("1.0/../../x").is_err());
+        assert!(normalize_version(&"1".repeat(MAX_VERSION_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_parse_release() {
+        let json = r#"{
+            "tag_name": "v1.5.0",
+            "name": "µTerm 1.5.0",
+            "body": "Changes:\r\n- Split panes",
+            "published_at": "2025-06-01T12:00:00Z",
+            "html_url": "https://github.com/ttaatoo/microterm/releases/tag/v1.5.0"
+        }"#;
+        let notes = parse_release("1.5.0", json).unwrap();
+        assert_eq!(notes.name, "µTerm 1.5.0");
+        assert_eq!(notes.body, "Changes:\n- Split panes");
+        assert_eq!(notes.published_at.as_deref(), Some("2025-06-01T12:00:00Z"));
+    }
+
+    #[test]
+    fn test_parse_release_defaults_and_errors() {
+        let json = r#"{"name": "", "body": null, "html_url": "https://example.com"}"#;
+        let notes = parse_release("1.2.3", json).unwrap();
+        assert_eq!(notes.name, "v1.2.3");
+        assert_eq!(notes.body, "");
+        assert!(parse_release("1.2.3", r#"{"message": "Not Found"}"#).is_err());
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes = ReleaseNotes {
+            version: "1.5.0".to_string(),
+            name: "µTerm 1.5.0".to_string(),
+            body: "- Fixes".to_string(),
+            published_at: None,
+            url: "https://example.com".to_string(),
+        };
+
+        let manager = ReleaseNotesManager::new(temp_dir.path().join("release-notes"));
+        assert!(manager.cached("1.5.0").is_none());
+        manager.store(&notes);
+
+        // A new manager reads the disk cache
+        let reloaded = ReleaseNotesManager::new(temp_dir.path().join("release-notes"));
+        assert_eq!(reloaded.cached("1.5.0"), Some(notes));
+    }
+}
@@ -0,0 +1,97 @@
+//! Output replay for detached sessions
+//!
+//! With `keep_sessions_alive`, closing a pane detaches its session instead of
+//! killing the shell (see `PtyManager::detach_session`). The terminal that
+//! reattaches it starts empty, so each session keeps the tail of its raw
+//! output, escape sequences included, for `reattach_pty_session` to return and
+//! the frontend to write into the new terminal.
+
+/// Maximum number of output bytes kept per session
+pub const DEFAULT_REPLAY_BYTES: usize = 256 * 1024;
+
+/// How far past the byte limit a trimmed buffer may start, looking for a line
+/// break so the replay doesn't begin inside an escape sequence
+const MAX_LINE_SEARCH: usize = 4096;
+
+/// The most recent raw output of a session
+#[derive(Debug, Clone)]
+pub struct ReplayBuffer {
+    data: String,
+    max_bytes: usize,
+}
+
+impl Default for ReplayBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPLAY_BYTES)
+    }
+}
+
+impl ReplayBuffer {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            data: String::new(),
+            max_bytes,
+        }
+    }
+
+    /// Append output, dropping the oldest when over the limit
+    pub fn push(&mut self, output: &str) {
+        self.data.push_str(output);
+        if self.data.len() <= self.max_bytes {
+            return;
+        }
+        let mut start = self.data.len() - self.max_bytes;
+        while !self.data.is_char_boundary(start) {
+            start += 1;
+        }
+        if self.data.as_bytes()[start - 1] != b'\n' {
+            let search_end = (start + MAX_LINE_SEARCH).min(self.data.len());
+            if let Some(newline) = self.data[start..search_end].find('\n') {
+                start += newline + 1;
+            }
+        }
+        self.data.drain(..start);
+    }
+
+    /// The kept output, oldest first
+    pub fn contents(&self) -> &str {
+        &self.data
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_everything_under_the_limit() {
+        let mut buffer = ReplayBuffer::new(64);
+        buffer.push("\x1b[1mls\x1b[0m\r\n");
+        buffer.push("a b c\r\n");
+        assert_eq!(buffer.contents(), "\x1b[1mls\x1b[0m\r\na b c\r\n");
+    }
+
+    #[test]
+    fn test_trims_to_a_line_start() {
+        let mut buffer = ReplayBuffer::new(16);
+        buffer.push("first line\r\n");
+        buffer.push("second\r\nthird\r\n");
+        assert_eq!(buffer.contents(), "second\r\nthird\r\n");
+        buffer.push("4\r\n");
+        assert_eq!(buffer.contents(), "third\r\n4\r\n");
+
+        buffer.clear();
+        assert_eq!(buffer.contents(), "");
+    }
+
+    #[test]
+    fn test_trims_at_char_boundaries() {
+        let mut buffer = ReplayBuffer::new(5);
+        buffer.push("ééé");
+        assert_eq!(buffer.contents(), "éé");
+    }
+}
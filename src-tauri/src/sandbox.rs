@@ -0,0 +1,180 @@
+//! Sandboxed command execution
+//!
+//! A command pasted from the internet can be run with less access than the
+//! app has: `execute_command` and `execute_command_stream` take a `sandbox`
+//! level. `env` starts the command with a cleared environment (only the
+//! variables history keeps, plus `HOME`), so tokens in the app's environment
+//! don't leak. `no_network` additionally runs it under a Seatbelt profile
+//! (`sandbox-exec`) denying network access, and `strict` also denies writes
+//! outside the temporary directories. The level is kept in the history, so a
+//! rerun is sandboxed the same way. Seatbelt is macOS only; elsewhere only
+//! `env` is available.
+
+use serde::{Deserialize, Serialize};
+
+/// Runs a program with a cleared environment
+const ENV_PATH: &str = "/usr/bin/env";
+
+/// Runs a program under a Seatbelt profile
+const SANDBOX_EXEC_PATH: &str = "/usr/bin/sandbox-exec";
+
+/// Seatbelt profile denying network access; later rules win
+#[cfg(target_os = "macos")]
+const NO_NETWORK_PROFILE: &str = "(version 1)(allow default)(deny network*)";
+
+/// Seatbelt rules denying writes except to terminals, `/dev/null` and the
+/// temporary directories
+#[cfg(target_os = "macos")]
+const READ_ONLY_RULES: &str = r#"(deny file-write*)(allow file-write* (literal "/dev/null") (literal "/dev/tty") (regex #"^/dev/ttys[0-9]+$") (subpath "/private/tmp") (subpath "/private/var/folders"))"#;
+
+/// How much a command is restricted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxLevel {
+    /// Runs like any other command
+    #[default]
+    Off,
+    /// Cleared environment
+    Env,
+    /// Cleared environment, no network
+    NoNetwork,
+    /// Cleared environment, no network, no writes outside temporary directories
+    Strict,
+}
+
+impl SandboxLevel {
+    /// Seatbelt profile of the level, if it needs one
+    #[cfg(target_os = "macos")]
+    fn seatbelt_profile(self) -> Result<Option<String>, String> {
+        Ok(match self {
+            SandboxLevel::Off | SandboxLevel::Env => None,
+            SandboxLevel::NoNetwork => Some(NO_NETWORK_PROFILE.to_string()),
+            SandboxLevel::Strict => Some(format!("{}{}", NO_NETWORK_PROFILE, READ_ONLY_RULES)),
+        })
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn seatbelt_profile(self) -> Result<Option<String>, String> {
+        match self {
+            SandboxLevel::Off | SandboxLevel::Env => Ok(None),
+            _ => Err("Only the env sandbox is available on this platform".to_string()),
+        }
+    }
+}
+
+/// Program and arguments that run `cmd` at `level`. The cleared environment
+/// gets `env` (the invocation's snapshot) and `home`.
+pub fn wrap(
+    level: SandboxLevel,
+    cmd: &str,
+    args: &[String],
+    env: &[(String, String)],
+    home: Option<&str>,
+) -> Result<(String, Vec<String>), String> {
+    if level == SandboxLevel::Off {
+        return Ok((cmd.to_string(), args.to_vec()));
+    }
+    // `env` would take these for an assignment or an option
+    if cmd.contains('=') || cmd.starts_with('-') {
+        return Err(format!("Can't sandbox command: {}", cmd));
+    }
+    let mut wrapped = vec!["-i".to_string()];
+    wrapped.extend(
+        env.iter()
+            .filter(|(name, _)| name != "HOME")
+            .map(|(name, value)| format!("{}={}", name, value)),
+    );
+    if let Some(home) = home {
+        wrapped.push(format!("HOME={}", home));
+    }
+    if let Some(profile) = level.seatbelt_profile()? {
+        wrapped.extend([SANDBOX_EXEC_PATH.to_string(), "-p".to_string(), profile]);
+    }
+    wrapped.push(cmd.to_string());
+    wrapped.extend(args.iter().cloned());
+    Ok((ENV_PATH.to_string(), wrapped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env() -> Vec<(String, String)> {
+        vec![
+            ("PATH".to_string(), "/usr/bin:/bin".to_string()),
+            ("HOME".to_string(), "/elsewhere".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_off_runs_unchanged() {
+        let (program, args) =
+            wrap(SandboxLevel::Off, "ls", &["-l".to_string()], &env(), None).unwrap();
+        assert_eq!(program, "ls");
+        assert_eq!(args, ["-l"]);
+    }
+
+    #[test]
+    fn test_env_clears_environment() {
+        let (program, args) = wrap(
+            SandboxLevel::Env,
+            "ls",
+            &["-l".to_string()],
+            &env(),
+            Some("/Users/me"),
+        )
+        .unwrap();
+        assert_eq!(program, ENV_PATH);
+        assert_eq!(
+            args,
+            ["-i", "PATH=/usr/bin:/bin", "HOME=/Users/me", "ls", "-l"]
+        );
+    }
+
+    #[test]
+    fn test_rejects_commands_env_would_misread() {
+        assert!(wrap(SandboxLevel::Env, "FOO=1", &[], &[], None).is_err());
+        assert!(wrap(SandboxLevel::Env, "-u", &[], &[], None).is_err());
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_seatbelt_levels() {
+        let (_, args) = wrap(SandboxLevel::NoNetwork, "curl", &[], &[], None).unwrap();
+        assert_eq!(
+            args,
+            ["-i", SANDBOX_EXEC_PATH, "-p", NO_NETWORK_PROFILE, "curl"]
+        );
+        let (_, args) = wrap(SandboxLevel::Strict, "sh", &[], &[], None).unwrap();
+        assert!(args[3].contains("(deny file-write*)"));
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn test_seatbelt_levels_unavailable() {
+        assert!(wrap(SandboxLevel::NoNetwork, "curl", &[], &[], None).is_err());
+        assert!(wrap(SandboxLevel::Strict, "sh", &[], &[], None).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_env_sandbox_hides_variables() {
+        let (program, args) = wrap(
+            SandboxLevel::Env,
+            "sh",
+            &[
+                "-c".to_string(),
+                "echo \"[$MICROTERM_SANDBOX_TEST]\"".to_string(),
+            ],
+            &[("PATH".to_string(), "/usr/bin:/bin".to_string())],
+            None,
+        )
+        .unwrap();
+        let output = std::process::Command::new(program)
+            .args(args)
+            .env("MICROTERM_SANDBOX_TEST", "secret")
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "[]\n");
+    }
+}
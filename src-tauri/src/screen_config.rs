@@ -3,10 +3,10 @@
 //! Manages window size and position per screen. Both are persisted to disk
 //! so windows remember their placement when toggled or moved between screens.
 
+use crate::config_file;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use tracing::{debug, error};
 
@@ -45,6 +45,11 @@ impl ScreenId {
         Self(format!("display-{}", display_id))
     }
 
+    /// A separate ID for the same screen, e.g. for another window mode
+    pub fn with_suffix(&self, suffix: &str) -> Self {
+        Self(format!("{}#{}", self.0, suffix))
+    }
+
     pub fn as_str(&self) -> &str {
         &self.0
     }
@@ -67,22 +72,14 @@ impl ScreenConfigManager {
     }
 
     /// Load configurations from disk
-    fn load_configs(path: &PathBuf) -> HashMap<ScreenId, WindowConfig> {
-        match fs::read_to_string(path) {
-            Ok(content) => {
-                match serde_json::from_str::<HashMap<ScreenId, WindowConfig>>(&content) {
-                    Ok(configs) => {
-                        debug!("Loaded {} screen configurations", configs.len());
-                        configs
-                    }
-                    Err(e) => {
-                        error!("Failed to parse screen config: {}", e);
-                        HashMap::new()
-                    }
-                }
+    fn load_configs(path: &Path) -> HashMap<ScreenId, WindowConfig> {
+        match config_file::read_json::<HashMap<ScreenId, WindowConfig>>(path) {
+            Some(configs) => {
+                debug!("Loaded {} screen configurations", configs.len());
+                configs
             }
-            Err(_) => {
-                debug!("No existing screen config file, starting fresh");
+            None => {
+                debug!("No usable screen config file, starting fresh");
                 HashMap::new()
             }
         }
@@ -91,17 +88,9 @@ impl ScreenConfigManager {
     /// Save configurations to disk
     fn save_configs(&self) {
         let configs = self.configs.lock().unwrap();
-        match serde_json::to_string_pretty(&*configs) {
-            Ok(json) => {
-                if let Some(parent) = self.config_path.parent() {
-                    let _ = fs::create_dir_all(parent);
-                }
-                match fs::write(&self.config_path, json) {
-                    Ok(_) => debug!("Saved {} screen configurations", configs.len()),
-                    Err(e) => error!("Failed to write screen config: {}", e),
-                }
-            }
-            Err(e) => error!("Failed to serialize screen config: {}", e),
+        match config_file::write_json(&self.config_path, &*configs) {
+            Ok(()) => debug!("Saved {} screen configurations", configs.len()),
+            Err(e) => error!("Failed to save screen config: {}", e),
         }
     }
 
@@ -0,0 +1,520 @@
+//! User scripting hooks for app events
+//!
+//! Loads Rhai scripts from the `scripts/` directory inside the app data dir
+//! and calls their hook functions when backend events happen. A script opts
+//! into an event by defining a one-argument function named after it:
+//!
+//! ```rhai
+//! fn on_session_created(event) {
+//!     write_to_session(event.session_id, "echo hello\n");
+//! }
+//! ```
+//!
+//! Scripts run on a dedicated worker thread with operation limits, and can
+//! only reach a small, safe subset of the command API (see `register_api`).
+
+use crate::pty::PtyManager;
+use parking_lot::Mutex;
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, AST};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread;
+use tauri::{command, State};
+use tracing::{debug, error, info, warn};
+
+/// File extension of user scripts
+const SCRIPT_EXTENSION: &str = "rhai";
+
+/// Maximum number of operations a single hook invocation may perform
+const MAX_SCRIPT_OPERATIONS: u64 = 100_000;
+
+/// Maximum function call nesting depth inside scripts
+const MAX_SCRIPT_CALL_LEVELS: usize = 32;
+
+/// Maximum length of strings created by scripts
+const MAX_SCRIPT_STRING_SIZE: usize = 64 * 1024;
+
+/// Backend events that scripts can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    SessionCreated,
+    CommandFinished,
+    WindowShown,
+}
+
+impl HookEvent {
+    /// All events, in the order they are documented
+    pub const ALL: [HookEvent; 3] = [
+        HookEvent::SessionCreated,
+        HookEvent::CommandFinished,
+        HookEvent::WindowShown,
+    ];
+
+    /// Name of the script function that handles this event
+    pub fn function_name(&self) -> &'static str {
+        match self {
+            HookEvent::SessionCreated => "on_session_created",
+            HookEvent::CommandFinished => "on_command_finished",
+            HookEvent::WindowShown => "on_window_shown",
+        }
+    }
+}
+
+/// Load status of a single user script
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptStatus {
+    /// File name of the script (e.g. `startup.rhai`)
+    pub name: String,
+    /// Hook functions defined by the script
+    pub hooks: Vec<HookEvent>,
+    /// Compile error, if the script failed to load
+    pub error: Option<String>,
+}
+
+/// A compiled script ready to receive hook calls
+struct LoadedScript {
+    name: String,
+    ast: AST,
+}
+
+/// Result of calling one script's hook function
+#[derive(Debug)]
+pub struct HookOutcome {
+    pub script: String,
+    pub result: Result<Dynamic, String>,
+}
+
+/// Script engine plus the set of loaded scripts.
+///
+/// Not thread-safe on its own; `ScriptManager` owns one on a worker thread.
+pub struct ScriptRuntime {
+    engine: Engine,
+    scripts: Vec<LoadedScript>,
+}
+
+impl ScriptRuntime {
+    /// Create a runtime whose API functions operate on the given PTY manager
+    pub fn new(pty_manager: Arc<PtyManager>) -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_SCRIPT_OPERATIONS);
+        engine.set_max_call_levels(MAX_SCRIPT_CALL_LEVELS);
+        engine.set_max_string_size(MAX_SCRIPT_STRING_SIZE);
+        engine.disable_symbol("eval");
+        engine.on_print(|text| info!(target: "microterm::scripting", "{}", text));
+        engine.on_debug(|text, source, pos| {
+            debug!(target: "microterm::scripting", source = ?source, position = %pos, "{}", text)
+        });
+        register_api(&mut engine, pty_manager);
+
+        Self {
+            engine,
+            scripts: Vec::new(),
+        }
+    }
+
+    /// Compile a script from source and add it to the runtime
+    pub fn load_source(&mut self, name: &str, source: &str) -> ScriptStatus {
+        match self.engine.compile(source) {
+            Ok(ast) => {
+                let hooks = hooks_defined_in(&ast);
+                debug!(script = %name, hooks = ?hooks, "Loaded script");
+                self.scripts.push(LoadedScript {
+                    name: name.to_string(),
+                    ast,
+                });
+                ScriptStatus {
+                    name: name.to_string(),
+                    hooks,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                warn!(script = %name, error = %e, "Failed to compile script");
+                ScriptStatus {
+                    name: name.to_string(),
+                    hooks: Vec::new(),
+                    error: Some(e.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Replace all loaded scripts with the `*.rhai` files in `dir`.
+    /// Files are loaded in name order so hook order is predictable.
+    pub fn load_dir(&mut self, dir: &Path) -> Vec<ScriptStatus> {
+        self.scripts.clear();
+
+        let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.is_file()
+                        && path.extension().and_then(|e| e.to_str()) == Some(SCRIPT_EXTENSION)
+                })
+                .collect(),
+            Err(_) => {
+                debug!("No scripts directory at {}", dir.display());
+                return Vec::new();
+            }
+        };
+        paths.sort();
+
+        paths
+            .iter()
+            .map(|path| {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                match fs::read_to_string(path) {
+                    Ok(source) => self.load_source(&name, &source),
+                    Err(e) => ScriptStatus {
+                        name,
+                        hooks: Vec::new(),
+                        error: Some(format!("Failed to read script: {}", e)),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Call the hook function for `event` in every script that defines it
+    pub fn dispatch(&self, event: HookEvent, payload: &serde_json::Value) -> Vec<HookOutcome> {
+        let arg = match rhai::serde::to_dynamic(payload) {
+            Ok(arg) => arg,
+            Err(e) => {
+                error!(event = ?event, error = %e, "Failed to convert hook payload");
+                return Vec::new();
+            }
+        };
+        let function = event.function_name();
+
+        self.scripts
+            .iter()
+            .filter(|script| defines_hook(&script.ast, function))
+            .map(|script| {
+                let mut scope = Scope::new();
+                let result = self
+                    .engine
+                    .call_fn::<Dynamic>(&mut scope, &script.ast, function, (arg.clone(),))
+                    .map_err(|e| e.to_string());
+                if let Err(e) = &result {
+                    warn!(script = %script.name, hook = function, error = %e, "Script hook failed");
+                }
+                HookOutcome {
+                    script: script.name.clone(),
+                    result,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Register the functions scripts are allowed to call
+fn register_api(engine: &mut Engine, pty_manager: Arc<PtyManager>) {
+    engine.register_fn("log", |message: &str| {
+        info!(target: "microterm::scripting", "{}", message);
+    });
+
+    let pty = pty_manager.clone();
+    engine.register_fn(
+        "write_to_session",
+        move |session_id: &str, data: &str| -> Result<(), Box<EvalAltResult>> {
+            pty.write_to_session(session_id, data)
+                .map_err(|e| e.to_string().into())
+        },
+    );
+
+    let pty = pty_manager.clone();
+    engine.register_fn(
+        "close_session",
+        move |session_id: &str| -> Result<(), Box<EvalAltResult>> {
+            pty.close_session(session_id, false).map_err(|e| e.into())
+        },
+    );
+
+    let pty = pty_manager;
+    engine.register_fn(
+        "session_cwd",
+        move |session_id: &str| -> Result<Dynamic, Box<EvalAltResult>> {
+            match pty.get_session_cwd(session_id) {
+                Ok(Some(cwd)) => Ok(cwd.into()),
+                Ok(None) => Ok(Dynamic::UNIT),
+                Err(e) => Err(e.into()),
+            }
+        },
+    );
+}
+
+/// Whether the AST defines a one-argument function with the given name
+fn defines_hook(ast: &AST, function: &str) -> bool {
+    ast.iter_functions()
+        .any(|f| f.name == function && f.params.len() == 1)
+}
+
+/// Hooks defined by a compiled script
+fn hooks_defined_in(ast: &AST) -> Vec<HookEvent> {
+    HookEvent::ALL
+        .iter()
+        .copied()
+        .filter(|event| defines_hook(ast, event.function_name()))
+        .collect()
+}
+
+enum ScriptMessage {
+    Dispatch(HookEvent, serde_json::Value),
+    Reload,
+}
+
+/// Runs user scripts on a background thread so hooks never block the
+/// PTY reader, command handlers or the main thread.
+pub struct ScriptManager {
+    sender: Mutex<Sender<ScriptMessage>>,
+    statuses: Arc<Mutex<Vec<ScriptStatus>>>,
+    scripts_dir: PathBuf,
+}
+
+impl ScriptManager {
+    /// Start the script worker and load scripts from `scripts_dir`
+    pub fn new(scripts_dir: PathBuf, pty_manager: Arc<PtyManager>) -> Self {
+        let (sender, receiver) = mpsc::channel::<ScriptMessage>();
+        let statuses = Arc::new(Mutex::new(Vec::new()));
+
+        let dir = scripts_dir.clone();
+        let statuses_for_thread = statuses.clone();
+        thread::spawn(move || {
+            let mut runtime = ScriptRuntime::new(pty_manager);
+            *statuses_for_thread.lock() = runtime.load_dir(&dir);
+
+            while let Ok(message) = receiver.recv() {
+                match message {
+                    ScriptMessage::Dispatch(event, payload) => {
+                        runtime.dispatch(event, &payload);
+                    }
+                    ScriptMessage::Reload => {
+                        *statuses_for_thread.lock() = runtime.load_dir(&dir);
+                        info!("Reloaded user scripts");
+                    }
+                }
+            }
+        });
+
+        Self {
+            sender: Mutex::new(sender),
+            statuses,
+            scripts_dir,
+        }
+    }
+
+    /// Queue a hook call for all scripts that handle `event`
+    pub fn dispatch(&self, event: HookEvent, payload: serde_json::Value) {
+        if let Err(e) = self
+            .sender
+            .lock()
+            .send(ScriptMessage::Dispatch(event, payload))
+        {
+            error!("Failed to dispatch script hook: {}", e);
+        }
+    }
+
+    /// Re-read all scripts from disk
+    pub fn reload(&self) -> Result<(), String> {
+        self.sender
+            .lock()
+            .send(ScriptMessage::Reload)
+            .map_err(|e| format!("Script worker is not running: {}", e))
+    }
+
+    /// Load status of every script found at the last (re)load
+    pub fn statuses(&self) -> Vec<ScriptStatus> {
+        self.statuses.lock().clone()
+    }
+
+    /// Directory scripts are loaded from
+    pub fn scripts_dir(&self) -> &Path {
+        &self.scripts_dir
+    }
+}
+
+/// List user scripts and the hooks they define
+#[command]
+pub fn list_scripts(
+    script_manager: State<'_, Arc<ScriptManager>>,
+) -> Result<Vec<ScriptStatus>, String> {
+    Ok(script_manager.statuses())
+}
+
+/// Reload user scripts from the scripts directory
+#[command]
+pub fn reload_scripts(script_manager: State<'_, Arc<ScriptManager>>) -> Result<(), String> {
+    script_manager.reload()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn runtime() -> ScriptRuntime {
+        ScriptRuntime::new(Arc::new(PtyManager::new()))
+    }
+
+    #[test]
+    fn test_hook_function_names() {
+        assert_eq!(
+            HookEvent::SessionCreated.function_name(),
+            "on_session_created"
+        );
+        assert_eq!(
+            HookEvent::CommandFinished.function_name(),
+            "on_command_finished"
+        );
+        assert_eq!(HookEvent::WindowShown.function_name(), "on_window_shown");
+    }
+
+    #[test]
+    fn test_load_source_reports_hooks() {
+        let mut runtime = runtime();
+        let status = runtime.load_source(
+            "hooks.rhai",
+            "fn on_session_created(e) { 1 }\nfn on_window_shown(e) { 2 }\nfn helper() { 3 }",
+        );
+        assert!(status.error.is_none());
+        assert_eq!(
+            status.hooks,
+            vec![HookEvent::SessionCreated, HookEvent::WindowShown]
+        );
+    }
+
+    #[test]
+    fn test_load_source_compile_error() {
+        let mut runtime = runtime();
+        let status = runtime.load_source("broken.rhai", "fn on_window_shown(e) {");
+        assert!(status.error.is_some());
+        assert!(status.hooks.is_empty());
+        assert!(runtime.scripts.is_empty());
+    }
+
+    #[test]
+    fn test_dispatch_passes_payload() {
+        let mut runtime = runtime();
+        runtime.load_source(
+            "echo.rhai",
+            "fn on_command_finished(e) { e.cmd + \":\" + e.exit_code }",
+        );
+
+        let outcomes = runtime.dispatch(
+            HookEvent::CommandFinished,
+            &json!({ "cmd": "ls", "exit_code": 0 }),
+        );
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].script, "echo.rhai");
+        let value = outcomes[0].result.as_ref().unwrap();
+        assert_eq!(value.clone().into_string().unwrap(), "ls:0");
+    }
+
+    #[test]
+    fn test_dispatch_skips_scripts_without_hook() {
+        let mut runtime = runtime();
+        runtime.load_source("a.rhai", "fn on_window_shown(e) { 1 }");
+        runtime.load_source("b.rhai", "fn on_session_created(e) { 2 }");
+
+        let outcomes = runtime.dispatch(HookEvent::WindowShown, &json!({}));
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].script, "a.rhai");
+    }
+
+    #[test]
+    fn test_dispatch_reports_api_errors() {
+        let mut runtime = runtime();
+        runtime.load_source(
+            "write.rhai",
+            "fn on_session_created(e) { write_to_session(e.session_id, \"ls\\n\") }",
+        );
+
+        let outcomes = runtime.dispatch(
+            HookEvent::SessionCreated,
+            &json!({ "session_id": "missing" }),
+        );
+        assert_eq!(outcomes.len(), 1);
+        let err = outcomes[0].result.as_ref().unwrap_err();
+        assert!(err.contains("Session not found"));
+    }
+
+    #[test]
+    fn test_operation_limit_stops_runaway_script() {
+        let mut runtime = runtime();
+        runtime.load_source("loop.rhai", "fn on_window_shown(e) { loop { } }");
+
+        let outcomes = runtime.dispatch(HookEvent::WindowShown, &json!({}));
+        assert_eq!(outcomes.len(), 1);
+        assert!(outcomes[0].result.is_err());
+    }
+
+    #[test]
+    fn test_eval_is_disabled() {
+        let mut runtime = runtime();
+        let status = runtime.load_source("eval.rhai", "fn on_window_shown(e) { eval(\"1\") }");
+        assert!(status.error.is_some());
+    }
+
+    #[test]
+    fn test_load_dir_only_loads_rhai_files_in_order() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("b.rhai"),
+            "fn on_window_shown(e) { 2 }",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("a.rhai"),
+            "fn on_window_shown(e) { 1 }",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "not a script").unwrap();
+
+        let mut runtime = runtime();
+        let statuses = runtime.load_dir(temp_dir.path());
+        let names: Vec<&str> = statuses.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["a.rhai", "b.rhai"]);
+
+        let outcomes = runtime.dispatch(HookEvent::WindowShown, &json!({}));
+        let results: Vec<i64> = outcomes
+            .iter()
+            .map(|o| o.result.as_ref().unwrap().as_int().unwrap())
+            .collect();
+        assert_eq!(results, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_load_dir_missing_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut runtime = runtime();
+        let statuses = runtime.load_dir(&temp_dir.path().join("does-not-exist"));
+        assert!(statuses.is_empty());
+    }
+
+    #[test]
+    fn test_load_dir_replaces_previous_scripts() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut runtime = runtime();
+        runtime.load_source("old.rhai", "fn on_window_shown(e) { 1 }");
+
+        runtime.load_dir(temp_dir.path());
+        assert!(runtime
+            .dispatch(HookEvent::WindowShown, &json!({}))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_hook_event_serialization() {
+        let json = serde_json::to_string(&HookEvent::CommandFinished).unwrap();
+        assert_eq!(json, "\"command_finished\"");
+    }
+}
@@ -0,0 +1,558 @@
+//! Rust-side scrollback for PTY sessions
+//!
+//! The frontend only sees streamed output chunks, so once xterm.js drops old
+//! lines they are gone. Each session keeps a bounded buffer of plain-text lines
+//! (escape sequences stripped) that backend features can search.
+
+use crate::cell_width::{self, UnicodeWidthMode};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Maximum number of lines kept per session
+pub const DEFAULT_SCROLLBACK_LINES: usize = 10_000;
+
+/// Lines longer than this are truncated to bound memory use
+const MAX_LINE_LENGTH: usize = 4096;
+
+/// Compiled size limit of a search regex, so a pathological pattern can't
+/// use unbounded memory
+const MAX_REGEX_SIZE: usize = 1 << 20;
+
+/// Maximum number of context lines around a hit
+pub const MAX_CONTEXT_LINES: usize = 10;
+
+/// Maximum number of hits returned per session
+const MAX_HITS_PER_SESSION: usize = 200;
+
+/// Escape sequence parser state for `AnsiStripper`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StripState {
+    Ground,
+    Escape,
+    Csi,
+    /// OSC/DCS/APC/PM/SOS string, terminated by BEL or ST
+    String,
+    /// ESC seen inside a string (possible ST)
+    StringEscape,
+}
+
+/// Removes terminal escape sequences from a stream of output chunks.
+///
+/// State is kept between calls, so sequences split across PTY reads are
+/// still removed completely.
+#[derive(Debug, Clone)]
+pub struct AnsiStripper {
+    state: StripState,
+}
+
+impl Default for AnsiStripper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnsiStripper {
+    pub fn new() -> Self {
+        Self {
+            state: StripState::Ground,
+        }
+    }
+
+    /// Strip escape sequences from `input`, keeping text and C0 controls
+    pub fn strip(&mut self, input: &str) -> String {
+        let mut out = String::with_capacity(input.len());
+        for c in input.chars() {
+            match self.state {
+                StripState::Ground => {
+                    if c == '\x1b' {
+                        self.state = StripState::Escape;
+                    } else if c == '\u{9b}' {
+                        self.state = StripState::Csi;
+                    } else {
+                        out.push(c);
+                    }
+                }
+                StripState::Escape => {
+                    self.state = match c {
+                        '[' => StripState::Csi,
+                        ']' | 'P' | '_' | '^' | 'X' => StripState::String,
+                        // Intermediate bytes (e.g. ESC ( B): stay until final byte
+                        ' '..='/' => StripState::Escape,
+                        _ => StripState::Ground,
+                    };
+                }
+                StripState::Csi => {
+                    if ('@'..='~').contains(&c) {
+                        self.state = StripState::Ground;
+                    }
+                }
+                StripState::String => match c {
+                    '\x07' => self.state = StripState::Ground,
+                    '\x1b' => self.state = StripState::StringEscape,
+                    _ => {}
+                },
+                StripState::StringEscape => {
+                    self.state = if c == '\\' {
+                        StripState::Ground
+                    } else {
+                        StripState::String
+                    };
+                }
+            }
+        }
+        out
+    }
+}
+
+/// A single match in a session's scrollback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrollbackHit {
+    /// Absolute line number since the session started (stable across eviction)
+    pub line_number: u64,
+    /// Character offset of the match within the line
+    pub column: usize,
+    /// Terminal cell offset of the match (per `unicode_width_mode`)
+    pub cell_column: usize,
+    pub line: String,
+    pub context_before: Vec<String>,
+    pub context_after: Vec<String>,
+}
+
+/// Search options shared by scrollback search commands
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    pub context_lines: usize,
+    /// Convention used to compute `ScrollbackHit::cell_column`
+    pub width_mode: UnicodeWidthMode,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            case_sensitive: false,
+            context_lines: 2,
+            width_mode: UnicodeWidthMode::default(),
+        }
+    }
+}
+
+/// Bounded line buffer of a session's plain-text output
+#[derive(Debug)]
+pub struct ScrollbackBuffer {
+    lines: VecDeque<String>,
+    /// Current line that has not been terminated by a newline yet
+    partial: String,
+    /// Set after a bare `\r`: the next printable character starts the line over
+    pending_carriage_return: bool,
+    max_lines: usize,
+    /// Absolute line number of `lines[0]`
+    first_line_number: u64,
+    stripper: AnsiStripper,
+}
+
+impl Default for ScrollbackBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_SCROLLBACK_LINES)
+    }
+}
+
+impl ScrollbackBuffer {
+    pub fn new(max_lines: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            partial: String::new(),
+            pending_carriage_return: false,
+            max_lines: max_lines.max(1),
+            first_line_number: 0,
+            stripper: AnsiStripper::new(),
+        }
+    }
+
+    /// Append a chunk of raw PTY output
+    pub fn push(&mut self, data: &str) {
+        let text = self.stripper.strip(data);
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    self.pending_carriage_return = false;
+                    self.finish_line();
+                }
+                '\r' => self.pending_carriage_return = true,
+                '\x08' => {
+                    self.partial.pop();
+                }
+                '\t' => self.push_char(c),
+                c if c.is_control() => {}
+                c => self.push_char(c),
+            }
+        }
+    }
+
+    fn push_char(&mut self, c: char) {
+        if self.pending_carriage_return {
+            // Bare carriage return: the line is being redrawn (progress bars, prompts)
+            self.partial.clear();
+            self.pending_carriage_return = false;
+        }
+        if self.partial.len() < MAX_LINE_LENGTH {
+            self.partial.push(c);
+        }
+    }
+
+    fn finish_line(&mut self) {
+        let line = std::mem::take(&mut self.partial);
+        self.lines.push_back(line);
+        while self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+            self.first_line_number += 1;
+        }
+    }
+
+    /// Drop all lines; line numbers keep counting from where they were
+    pub fn clear(&mut self) {
+        self.first_line_number += self.lines.len() as u64;
+        self.lines.clear();
+        self.partial.clear();
+        self.pending_carriage_return = false;
+    }
+
+    /// Number of lines currently held (including an unterminated last line)
+    pub fn len(&self) -> usize {
+        self.lines.len() + usize::from(!self.partial.is_empty())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Line at a buffer-relative index, including the unterminated last line
+    fn line_at(&self, index: usize) -> &str {
+        if index < self.lines.len() {
+            &self.lines[index]
+        } else {
+            &self.partial
+        }
+    }
+
+    /// Search for a plain-text query, returning hits with surrounding context
+    pub fn search(&self, query: &str, options: &SearchOptions) -> Vec<ScrollbackHit> {
+        if query.is_empty() {
+            return Vec::new();
+        }
+        if options.case_sensitive {
+            return self.search_with(options, |line| {
+                line.find(query)
+                    .map(|byte_offset| line[..byte_offset].chars().count())
+            });
+        }
+        // Lowercasing can change a line's length, so match on the line itself
+        match RegexBuilder::new(&regex::escape(query))
+            .case_insensitive(true)
+            .size_limit(MAX_REGEX_SIZE)
+            .build()
+        {
+            Ok(regex) => self.search_regex(&regex, options),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Search for a regex (see `compile_regex`), returning hits with
+    /// surrounding context
+    pub fn search_regex(&self, regex: &Regex, options: &SearchOptions) -> Vec<ScrollbackHit> {
+        self.search_with(options, |line| {
+            regex
+                .find(line)
+                .map(|found| line[..found.start()].chars().count())
+        })
+    }
+
+    /// Search using a matcher that returns the character column of a match
+    pub fn search_with<F>(&self, options: &SearchOptions, matcher: F) -> Vec<ScrollbackHit>
+    where
+        F: Fn(&str) -> Option<usize>,
+    {
+        let context_lines = options.context_lines.min(MAX_CONTEXT_LINES);
+        let total = self.len();
+        let mut hits = Vec::new();
+
+        for index in 0..total {
+            let line = self.line_at(index);
+            let Some(column) = matcher(line) else {
+                continue;
+            };
+
+            let before_start = index.saturating_sub(context_lines);
+            let after_end = (index + 1 + context_lines).min(total);
+            hits.push(ScrollbackHit {
+                line_number: self.first_line_number + index as u64,
+                column,
+                cell_column: cell_width::cell_column(line, column, &options.width_mode),
+                line: line.to_string(),
+                context_before: (before_start..index)
+                    .map(|i| self.line_at(i).to_string())
+                    .collect(),
+                context_after: (index + 1..after_end)
+                    .map(|i| self.line_at(i).to_string())
+                    .collect(),
+            });
+
+            if hits.len() >= MAX_HITS_PER_SESSION {
+                break;
+            }
+        }
+
+        hits
+    }
+}
+
+/// Compile a search regex; patterns matching empty text (which would hit
+/// every line) are rejected
+pub fn compile_regex(query: &str, case_sensitive: bool) -> Result<Regex, String> {
+    let regex = RegexBuilder::new(query)
+        .case_insensitive(!case_sensitive)
+        .size_limit(MAX_REGEX_SIZE)
+        .build()
+        .map_err(|e| format!("Invalid search pattern: {}", e))?;
+    if regex.is_match("") {
+        return Err("Search pattern matches empty text".to_string());
+    }
+    Ok(regex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ============== AnsiStripper tests ==============
+
+    #[test]
+    fn test_strip_csi_sequences() {
+        let mut stripper = AnsiStripper::new();
+        assert_eq!(stripper.strip("\x1b[31merror\x1b[0m: boom"), "error: boom");
+        assert_eq!(stripper.strip("\x1b[?2004h$ "), "$ ");
+    }
+
+    #[test]
+    fn test_strip_osc_sequences() {
+        let mut stripper = AnsiStripper::new();
+        assert_eq!(stripper.strip("\x1b]0;title\x07prompt"), "prompt");
+        assert_eq!(stripper.strip("\x1b]7;file://host/tmp\x1b\\done"), "done");
+    }
+
+    #[test]
+    fn test_strip_sequence_split_across_chunks() {
+        let mut stripper = AnsiStripper::new();
+        assert_eq!(stripper.strip("abc\x1b["), "abc");
+        assert_eq!(stripper.strip("1;32"), "");
+        assert_eq!(stripper.strip("mdef"), "def");
+    }
+
+    #[test]
+    fn test_strip_charset_designation() {
+        let mut stripper = AnsiStripper::new();
+        assert_eq!(stripper.strip("\x1b(Bplain"), "plain");
+    }
+
+    // ============== ScrollbackBuffer tests ==============
+
+    #[test]
+    fn test_push_splits_lines() {
+        let mut buffer = ScrollbackBuffer::new(100);
+        buffer.push("one\r\ntwo\nthr");
+        buffer.push("ee");
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.line_at(0), "one");
+        assert_eq!(buffer.line_at(1), "two");
+        assert_eq!(buffer.line_at(2), "three");
+    }
+
+    #[test]
+    fn test_carriage_return_overwrites_line() {
+        let mut buffer = ScrollbackBuffer::new(100);
+        buffer.push("progress 10%\rprogress 100%\n");
+        assert_eq!(buffer.line_at(0), "progress 100%");
+    }
+
+    #[test]
+    fn test_backspace_removes_character() {
+        let mut buffer = ScrollbackBuffer::new(100);
+        buffer.push("lx\x08s\n");
+        assert_eq!(buffer.line_at(0), "ls");
+    }
+
+    #[test]
+    fn test_eviction_keeps_absolute_line_numbers() {
+        let mut buffer = ScrollbackBuffer::new(2);
+        buffer.push("a\nb\nc\n");
+        assert_eq!(buffer.len(), 2);
+
+        let hits = buffer.search("c", &SearchOptions::default());
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line_number, 2);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut buffer = ScrollbackBuffer::new(10);
+        buffer.push("a\nb\npartial");
+        buffer.clear();
+        assert!(buffer.is_empty());
+        assert!(buffer.search("a", &SearchOptions::default()).is_empty());
+
+        buffer.push("c\n");
+        let hits = buffer.search("c", &SearchOptions::default());
+        assert_eq!(hits[0].line_number, 2);
+    }
+
+    #[test]
+    fn test_long_lines_are_truncated() {
+        let mut buffer = ScrollbackBuffer::new(10);
+        buffer.push(&"x".repeat(MAX_LINE_LENGTH * 2));
+        buffer.push("\n");
+        assert_eq!(buffer.line_at(0).len(), MAX_LINE_LENGTH);
+    }
+
+    #[test]
+    fn test_search_with_context() {
+        let mut buffer = ScrollbackBuffer::new(100);
+        buffer.push("line 1\nline 2\nerror: failed\nline 4\nline 5\n");
+
+        let options = SearchOptions {
+            case_sensitive: false,
+            context_lines: 1,
+            ..SearchOptions::default()
+        };
+        let hits = buffer.search("ERROR", &options);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line_number, 2);
+        assert_eq!(hits[0].column, 0);
+        assert_eq!(hits[0].context_before, vec!["line 2"]);
+        assert_eq!(hits[0].context_after, vec!["line 4"]);
+    }
+
+    #[test]
+    fn test_search_case_sensitive() {
+        let mut buffer = ScrollbackBuffer::new(100);
+        buffer.push("Error\nerror\n");
+
+        let options = SearchOptions {
+            case_sensitive: true,
+            context_lines: 0,
+            ..SearchOptions::default()
+        };
+        let hits = buffer.search("error", &options);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line_number, 1);
+    }
+
+    #[test]
+    fn test_search_column_ignores_case_folding() {
+        let mut buffer = ScrollbackBuffer::new(100);
+        // 'İ' lowercases to two characters
+        buffer.push("İİ [a+b] Fail\n");
+
+        let hits = buffer.search("[A+B] fail", &SearchOptions::default());
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].column, 3);
+        assert_eq!(hits[0].cell_column, 3);
+    }
+
+    #[test]
+    fn test_search_column_counts_characters() {
+        let mut buffer = ScrollbackBuffer::new(100);
+        buffer.push("日本語 error\n");
+        let hits = buffer.search("error", &SearchOptions::default());
+        assert_eq!(hits[0].column, 4);
+        assert_eq!(hits[0].cell_column, 7);
+    }
+
+    #[test]
+    fn test_search_cell_column_uses_width_mode() {
+        let mut buffer = ScrollbackBuffer::new(100);
+        buffer.push("①② error\n");
+        let options = SearchOptions {
+            width_mode: UnicodeWidthMode {
+                ambiguous: cell_width::AmbiguousWidth::Wide,
+                emoji_vs16_wide: false,
+            },
+            ..SearchOptions::default()
+        };
+        let hits = buffer.search("error", &options);
+        assert_eq!(hits[0].column, 3);
+        assert_eq!(hits[0].cell_column, 5);
+    }
+
+    #[test]
+    fn test_search_includes_partial_line() {
+        let mut buffer = ScrollbackBuffer::new(100);
+        buffer.push("$ cargo build");
+        let hits = buffer.search("cargo", &SearchOptions::default());
+        assert_eq!(hits.len(), 1);
+    }
+
+    #[test]
+    fn test_search_empty_query() {
+        let mut buffer = ScrollbackBuffer::new(100);
+        buffer.push("text\n");
+        assert!(buffer.search("", &SearchOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_search_hit_limit() {
+        let mut buffer = ScrollbackBuffer::new(1000);
+        for _ in 0..MAX_HITS_PER_SESSION + 50 {
+            buffer.push("match\n");
+        }
+        let hits = buffer.search("match", &SearchOptions::default());
+        assert_eq!(hits.len(), MAX_HITS_PER_SESSION);
+    }
+
+    #[test]
+    fn test_context_lines_clamped() {
+        let mut buffer = ScrollbackBuffer::new(1000);
+        for i in 0..30 {
+            buffer.push(&format!("line {}\n", i));
+        }
+        let options = SearchOptions {
+            case_sensitive: false,
+            context_lines: 100,
+            ..SearchOptions::default()
+        };
+        let hits = buffer.search("line 15", &options);
+        assert_eq!(hits[0].context_before.len(), MAX_CONTEXT_LINES);
+        assert_eq!(hits[0].context_after.len(), MAX_CONTEXT_LINES);
+    }
+
+    #[test]
+    fn test_search_regex() {
+        let mut buffer = ScrollbackBuffer::new(100);
+        buffer.push("ok 1\nFAILED test_a (12ms)\nok 2\nfailed test_b (3ms)\n");
+
+        let regex = compile_regex(r"failed \w+ \(\d+ms\)", false).unwrap();
+        let hits = buffer.search_regex(&regex, &SearchOptions::default());
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].line, "FAILED test_a (12ms)");
+        assert_eq!(hits[0].line_number, 1);
+        assert_eq!(hits[0].column, 0);
+
+        let regex = compile_regex("failed", true).unwrap();
+        let hits = buffer.search_regex(&regex, &SearchOptions::default());
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line, "failed test_b (3ms)");
+
+        let regex = compile_regex(r"\d+ms", true).unwrap();
+        let hits = buffer.search_regex(&regex, &SearchOptions::default());
+        assert_eq!(hits[0].column, 15);
+    }
+
+    #[test]
+    fn test_compile_regex_rejects_bad_patterns() {
+        assert!(compile_regex("(unclosed", false)
+            .unwrap_err()
+            .contains("Invalid search pattern"));
+        assert!(compile_regex("a*", false).is_err());
+        assert!(compile_regex("", false).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+//! Session tags and color labels
+//!
+//! Users can tag sessions (e.g. `prod`, `db`) and give them a color label so
+//! the session switcher can group and highlight them. Labels live on the
+//! session in `PtyManager` and are set with `set_session_labels`; changes are
+//! emitted as `session-labels-changed`.
+
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of tags on a session
+pub const MAX_TAGS: usize = 8;
+
+/// Maximum length of a tag, in characters
+pub const MAX_TAG_LENGTH: usize = 32;
+
+/// Color label, matching the Finder tag colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Gray,
+}
+
+/// Tags and color of a session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionLabels {
+    pub tags: Vec<String>,
+    pub color: Option<LabelColor>,
+}
+
+/// Payload of the `session-labels-changed` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLabelsChanged {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub labels: SessionLabels,
+}
+
+impl SessionLabels {
+    /// Build labels from user input: tags are trimmed, lowercased and
+    /// deduplicated; empty tags are dropped
+    pub fn new(tags: Vec<String>, color: Option<LabelColor>) -> Result<Self, String> {
+        let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let tag = tag.trim().to_lowercase();
+            if tag.is_empty() || normalized.contains(&tag) {
+                continue;
+            }
+            if tag.chars().count() > MAX_TAG_LENGTH {
+                return Err(format!(
+                    "Tag too long: {} (max {} characters)",
+                    tag, MAX_TAG_LENGTH
+                ));
+            }
+            if tag.chars().any(char::is_control) {
+                return Err(format!("Invalid tag: {:?}", tag));
+            }
+            normalized.push(tag);
+        }
+        if normalized.len() > MAX_TAGS {
+            return Err(format!("Too many tags (max {})", MAX_TAGS));
+        }
+        Ok(Self {
+            tags: normalized,
+            color,
+        })
+    }
+
+    /// Whether the session has a tag
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.trim().to_lowercase();
+        self.tags.contains(&tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn test_tags_normalized() {
+        let labels =
+            SessionLabels::new(tags(&[" Prod ", "db", "prod", ""]), Some(LabelColor::Red)).unwrap();
+        assert_eq!(labels.tags, ["prod", "db"]);
+        assert_eq!(labels.color, Some(LabelColor::Red));
+        assert!(labels.has_tag("PROD"));
+        assert!(!labels.has_tag("staging"));
+    }
+
+    #[test]
+    fn test_invalid_tags_rejected() {
+        assert!(SessionLabels::new(tags(&[&"x".repeat(MAX_TAG_LENGTH + 1)]), None).is_err());
+        assert!(SessionLabels::new(tags(&["a\x1bb"]), None).is_err());
+        let many: Vec<String> = (0..=MAX_TAGS).map(|i| format!("tag{}", i)).collect();
+        assert!(SessionLabels::new(many, None).is_err());
+    }
+
+    #[test]
+    fn test_changed_event_serialization() {
+        let event = SessionLabelsChanged {
+            session_id: "s1".to_string(),
+            labels: SessionLabels::new(tags(&["prod"]), Some(LabelColor::Red)).unwrap(),
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"session_id":"s1","tags":["prod"],"color":"red"}"#
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Default session names
+//!
+//! Sessions nobody named still need telling apart in the session switcher,
+//! where several tabs are often titled `zsh`. `list_pty_sessions` gives each
+//! one a `name` built from its directory profile, its working directory and
+//! the local time it was created, e.g. `work ~/api 09:14`. The name follows
+//! the session's current directory and profile, so it changes with `cd`.
+
+use crate::tab_title::abbreviate_home;
+
+/// Name used when nothing else is known (the time is still added)
+const FALLBACK_NAME: &str = "Terminal";
+
+/// Local hour and minute of a time in seconds since the Unix epoch
+pub fn local_hour_minute(secs: u64) -> Option<(u8, u8)> {
+    let time = secs as libc::time_t;
+    // SAFETY: localtime_r only writes to the tm it's given
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&time, &mut tm).is_null() {
+            return None;
+        }
+        Some((tm.tm_hour as u8, tm.tm_min as u8))
+    }
+}
+
+/// Name of a session from its profile, directory and creation time
+pub fn auto_name(
+    profile: Option<&str>,
+    cwd: Option<&str>,
+    home: Option<&str>,
+    created: Option<(u8, u8)>,
+) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(profile) = profile.filter(|profile| !profile.is_empty()) {
+        parts.push(profile.to_string());
+    }
+    match cwd {
+        Some(cwd) => parts.push(abbreviate_home(cwd, home)),
+        None if parts.is_empty() => parts.push(FALLBACK_NAME.to_string()),
+        None => {}
+    }
+    if let Some((hour, minute)) = created {
+        parts.push(format!("{:02}:{:02}", hour, minute));
+    }
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOME: Option<&str> = Some("/Users/me");
+
+    #[test]
+    fn test_auto_name() {
+        assert_eq!(
+            auto_name(Some("work"), Some("/Users/me/api"), HOME, Some((9, 14))),
+            "work ~/api 09:14"
+        );
+        assert_eq!(
+            auto_name(None, Some("/tmp"), HOME, Some((23, 5))),
+            "/tmp 23:05"
+        );
+        assert_eq!(auto_name(Some(""), Some("/Users/me"), HOME, None), "~");
+        assert_eq!(auto_name(None, None, HOME, Some((0, 0))), "Terminal 00:00");
+        assert_eq!(auto_name(Some("prod"), None, HOME, None), "prod");
+    }
+
+    #[test]
+    fn test_local_hour_minute() {
+        let (hour, minute) = local_hour_minute(1_750_000_000).unwrap();
+        assert!(hour < 24 && minute < 60);
+    }
+}
@@ -0,0 +1,828 @@
+//! Persisted session metadata
+//!
+//! Records of past sessions (labels, last directory, exit code), the recent
+//! directories list and the history of commands run with `execute_command`
+//! live in a small SQLite database (`sessions.db` in the app data directory)
+//! instead of `settings.json` or one more JSON file per feature. History
+//! entries keep the full invocation (arguments, directory, environment subset)
+//! so `rerun_history_entry` can run them again as they were run, except for
+//! commands containing secrets, which are only kept redacted.
+//!
+//! The schema is versioned with `PRAGMA user_version` and upgraded by
+//! `MIGRATIONS` when the database is opened. State that earlier versions kept
+//! in JSON files (`recent-dirs.json`) is imported once and the file renamed to
+//! `*.migrated`. A database that can't be read or fails its integrity check is
+//! moved aside as `sessions.db.corrupt` and replaced with an empty one, so a
+//! damaged file costs the history but never keeps the app from starting.
+
+use crate::bookmarks::Bookmark;
+use crate::recent_dirs::RecentDir;
+use crate::redact;
+use crate::sandbox::SandboxLevel;
+use crate::session_labels::SessionLabels;
+use crate::ssh::SshHost;
+use crate::unix_time::now_secs;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Params};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{command, State};
+use tracing::{error, info, warn};
+
+/// Name of the database file in the app data directory
+pub const DATABASE_FILE: &str = "sessions.db";
+
+/// Recent directories file written by earlier versions
+const LEGACY_RECENT_DIRS_FILE: &str = "recent-dirs.json";
+
+/// Maximum number of session records kept
+const MAX_SESSION_RECORDS: usize = 500;
+
+/// Maximum number of history entries kept
+const MAX_HISTORY_ENTRIES: usize = 1000;
+
+/// Default number of entries returned by the list commands
+const DEFAULT_LIST_LIMIT: usize = 50;
+
+/// Schema upgrades; `MIGRATIONS[n]` takes the database from version n to n + 1
+const MIGRATIONS: &[&str] = &[
+    // 1: sessions, recent directories and command history
+    "CREATE TABLE sessions (
+        session_id TEXT PRIMARY KEY,
+        labels TEXT NOT NULL,
+        cwd TEXT,
+        created_at INTEGER NOT NULL,
+        closed_at INTEGER,
+        exit_code INTEGER
+    );
+    CREATE TABLE recent_dirs (
+        path TEXT PRIMARY KEY,
+        visits INTEGER NOT NULL,
+        last_visited INTEGER NOT NULL
+    );
+    CREATE TABLE command_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        command TEXT NOT NULL,
+        exit_code INTEGER NOT NULL,
+        finished_at INTEGER NOT NULL
+    );",
+    // 2: invocation snapshots for rerunning history entries
+    "ALTER TABLE command_history ADD COLUMN invocation TEXT;",
+    // 3: directory bookmarks
+    "CREATE TABLE bookmarks (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        path TEXT NOT NULL,
+        profile TEXT,
+        created_at INTEGER NOT NULL
+    );",
+    // 4: saved ssh hosts
+    "CREATE TABLE ssh_hosts (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        hostname TEXT NOT NULL,
+        user TEXT,
+        port INTEGER,
+        created_at INTEGER NOT NULL
+    );",
+];
+
+/// A session, as remembered after it was closed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub labels: SessionLabels,
+    /// Last directory reported by the shell
+    pub cwd: Option<String>,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+    /// `None` while the session is open (or if the app quit first)
+    pub closed_at: Option<u64>,
+    pub exit_code: Option<i32>,
+}
+
+/// A command run with `execute_command` or `execute_command_stream`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    /// Command line, with secrets redacted
+    pub command: String,
+    pub exit_code: i32,
+    /// Seconds since the Unix epoch
+    pub finished_at: u64,
+    /// Directory the command ran in, when its invocation was kept
+    pub cwd: Option<String>,
+    /// Directory profile matching `cwd` at the time
+    pub profile: Option<String>,
+    /// Whether `rerun_history_entry` can run it again
+    pub rerunnable: bool,
+}
+
+/// How a command was run, as needed to run it again the same way
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandInvocation {
+    pub cmd: String,
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    /// Environment variables that affect how commands behave (see
+    /// `commands::SNAPSHOT_ENV_VARS`), as they were set
+    pub env: Vec<(String, String)>,
+    /// Name of the directory profile matching `cwd`
+    pub profile: Option<String>,
+    /// How the command was sandboxed
+    #[serde(default)]
+    pub sandbox: SandboxLevel,
+}
+
+impl CommandInvocation {
+    /// Command line as shown in the history
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.cmd.as_str())
+            .chain(self.args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Bring the schema up to date
+fn migrate(conn: &Connection) -> Result<(), String> {
+    let version: usize = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read schema version: {}", e))?;
+    if version > MIGRATIONS.len() {
+        // Written by a newer version; the tables it needs are still there
+        warn!(version, "Session database is from a newer version");
+        return Ok(());
+    }
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn
+            .unchecked_transaction()
+            .map_err(|e| format!("Failed to start migration: {}", e))?;
+        tx.execute_batch(migration)
+            .and_then(|_| tx.pragma_update(None, "user_version", (index + 1) as i64))
+            .and_then(|_| tx.commit())
+            .map_err(|e| format!("Failed to migrate to version {}: {}", index + 1, e))?;
+        info!(version = index + 1, "Migrated session database");
+    }
+    Ok(())
+}
+
+/// Open a database and check that it's usable
+fn open_checked(path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(path).map_err(|e| format!("Failed to open database: {}", e))?;
+    let check: String = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to check database: {}", e))?;
+    if check != "ok" {
+        return Err(format!("Integrity check failed: {}", check));
+    }
+    migrate(&conn)?;
+    Ok(conn)
+}
+
+/// SQLite store for session metadata, recent directories and command history
+pub struct SessionStore {
+    conn: Mutex<Connection>,
+}
+
+impl SessionStore {
+    /// Open (or create) the database at `path`, starting over if it's corrupt
+    pub fn open(path: &Path) -> Self {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let conn = open_checked(path).or_else(|e| {
+            error!(error = %e, "Session database is unusable, starting a new one");
+            let corrupt = PathBuf::from(format!("{}.corrupt", path.display()));
+            if let Err(e) = fs::rename(path, &corrupt) {
+                warn!(error = %e, "Failed to move the corrupt session database aside");
+                let _ = fs::remove_file(path);
+            }
+            let _ = fs::remove_file(format!("{}-journal", path.display()));
+            open_checked(path)
+        });
+        let conn = conn.unwrap_or_else(|e| {
+            error!(error = %e, "Failed to create session database, keeping it in memory");
+            let conn = Connection::open_in_memory().expect("in-memory database");
+            if let Err(e) = migrate(&conn) {
+                error!(error = %e, "Failed to set up in-memory session database");
+            }
+            conn
+        });
+        Self {
+            conn: Mutex::new(conn),
+        }
+    }
+
+    /// Import state earlier versions kept in JSON files in `dir`
+    pub fn import_legacy_files(&self, dir: &Path) {
+        let path = dir.join(LEGACY_RECENT_DIRS_FILE);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return;
+        };
+        match serde_json::from_str::<Vec<RecentDir>>(&content) {
+            Ok(dirs) => match self.import_recent_dirs(&dirs) {
+                Ok(()) => info!(count = dirs.len(), "Imported recent directories"),
+                Err(e) => {
+                    // Keep the file to try again next launch
+                    error!(error = %e, "Failed to import recent directories");
+                    return;
+                }
+            },
+            Err(e) => warn!(error = %e, "Skipping unreadable recent directories file"),
+        }
+        if let Err(e) = fs::rename(&path, path.with_extension("json.migrated")) {
+            warn!(error = %e, "Failed to rename imported recent directories file");
+        }
+    }
+
+    fn import_recent_dirs(&self, dirs: &[RecentDir]) -> Result<(), String> {
+        let mut conn = self.conn.lock();
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start import: {}", e))?;
+        for dir in dirs {
+            tx.execute(
+                "INSERT OR IGNORE INTO recent_dirs (path, visits, last_visited)
+                 VALUES (?1, ?2, ?3)",
+                params![dir.path, dir.visits as i64, dir.last_visited as i64],
+            )
+            .map_err(|e| format!("Failed to import {}: {}", dir.path, e))?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit import: {}", e))
+    }
+
+    /// Remember a new session, forgetting the oldest beyond `MAX_SESSION_RECORDS`
+    pub fn session_created(&self, session_id: &str, cwd: Option<&str>) {
+        self.session_created_at(session_id, cwd, now_secs());
+    }
+
+    fn session_created_at(&self, session_id: &str, cwd: Option<&str>, now: u64) {
+        let labels = serde_json::to_string(&SessionLabels::default()).unwrap_or_default();
+        let conn = self.conn.lock();
+        let result = conn
+            .execute(
+                "INSERT OR REPLACE INTO sessions (session_id, labels, cwd, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![session_id, labels, cwd, now as i64],
+            )
+            .and_then(|_| {
+                conn.execute(
+                    "DELETE FROM sessions WHERE session_id NOT IN
+                     (SELECT session_id FROM sessions ORDER BY created_at DESC, rowid DESC LIMIT ?1)",
+                    params![MAX_SESSION_RECORDS as i64],
+                )
+            });
+        if let Err(e) = result {
+            error!(session_id = %session_id, error = %e, "Failed to record session");
+        }
+    }
+
+    fn update_session(&self, session_id: &str, sql: &str, params: impl Params) {
+        if let Err(e) = self.conn.lock().execute(sql, params) {
+            error!(session_id = %session_id, error = %e, "Failed to update session record");
+        }
+    }
+
+    pub fn session_cwd_changed(&self, session_id: &str, cwd: &str) {
+        self.update_session(
+            session_id,
+            "UPDATE sessions SET cwd = ?2 WHERE session_id = ?1",
+            params![session_id, cwd],
+        );
+    }
+
+    pub fn session_labels_changed(&self, session_id: &str, labels: &SessionLabels) {
+        match serde_json::to_string(labels) {
+            Ok(labels) => self.update_session(
+                session_id,
+                "UPDATE sessions SET labels = ?2 WHERE session_id = ?1",
+                params![session_id, labels],
+            ),
+            Err(e) => error!(error = %e, "Failed to serialize session labels"),
+        }
+    }
+
+    pub fn session_closed(&self, session_id: &str, exit_code: Option<i32>) {
+        self.update_session(
+            session_id,
+            "UPDATE sessions SET closed_at = ?2, exit_code = ?3 WHERE session_id = ?1",
+            params![session_id, now_secs() as i64, exit_code],
+        );
+    }
+
+    /// Session records, newest first
+    pub fn sessions(&self, limit: usize) -> Result<Vec<SessionRecord>, String> {
+        let conn = self.conn.lock();
+        let mut statement = conn
+            .prepare(
+                "SELECT session_id, labels, cwd, created_at, closed_at, exit_code FROM sessions
+                 ORDER BY created_at DESC, rowid DESC LIMIT ?1",
+            )
+            .map_err(|e| format!("Failed to query sessions: {}", e))?;
+        let rows = statement
+            .query_map(params![limit as i64], |row| {
+                let labels: String = row.get(1)?;
+                Ok(SessionRecord {
+                    session_id: row.get(0)?,
+                    labels: serde_json::from_str(&labels).unwrap_or_default(),
+                    cwd: row.get(2)?,
+                    created_at: row.get::<_, i64>(3)? as u64,
+                    closed_at: row.get::<_, Option<i64>>(4)?.map(|secs| secs as u64),
+                    exit_code: row.get(5)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query sessions: {}", e))?;
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read sessions: {}", e))
+    }
+
+    /// Labels a session was last given, if it's remembered
+    pub fn session_labels(&self, session_id: &str) -> Option<SessionLabels> {
+        self.conn
+            .lock()
+            .query_row(
+                "SELECT labels FROM sessions WHERE session_id = ?1",
+                params![session_id],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .ok()
+            .flatten()
+            .and_then(|labels| serde_json::from_str(&labels).ok())
+    }
+
+    pub fn recent_dirs(&self) -> Result<Vec<RecentDir>, String> {
+        let conn = self.conn.lock();
+        let mut statement = conn
+            .prepare("SELECT path, visits, last_visited FROM recent_dirs")
+            .map_err(|e| format!("Failed to query recent directories: {}", e))?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok(RecentDir {
+                    path: row.get(0)?,
+                    visits: row.get::<_, i64>(1)? as u64,
+                    last_visited: row.get::<_, i64>(2)? as u64,
+                })
+            })
+            .map_err(|e| format!("Failed to query recent directories: {}", e))?;
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read recent directories: {}", e))
+    }
+
+    pub fn save_recent_dir(&self, dir: &RecentDir) {
+        if let Err(e) = self.conn.lock().execute(
+            "INSERT INTO recent_dirs (path, visits, last_visited) VALUES (?1, ?2, ?3)
+             ON CONFLICT (path) DO UPDATE
+             SET visits = excluded.visits, last_visited = excluded.last_visited",
+            params![dir.path, dir.visits as i64, dir.last_visited as i64],
+        ) {
+            error!(error = %e, "Failed to save recent directory");
+        }
+    }
+
+    pub fn remove_recent_dir(&self, path: &str) {
+        if let Err(e) = self
+            .conn
+            .lock()
+            .execute("DELETE FROM recent_dirs WHERE path = ?1", params![path])
+        {
+            error!(error = %e, "Failed to remove recent directory");
+        }
+    }
+
+    /// Bookmarks in the order they were added
+    pub fn bookmarks(&self) -> Result<Vec<Bookmark>, String> {
+        let conn = self.conn.lock();
+        let mut statement = conn
+            .prepare("SELECT id, name, path, profile FROM bookmarks ORDER BY id")
+            .map_err(|e| format!("Failed to query bookmarks: {}", e))?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok(Bookmark {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    path: row.get(2)?,
+                    profile: row.get(3)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query bookmarks: {}", e))?;
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read bookmarks: {}", e))
+    }
+
+    pub fn bookmark(&self, id: i64) -> Result<Bookmark, String> {
+        self.bookmarks()?
+            .into_iter()
+            .find(|bookmark| bookmark.id == id)
+            .ok_or_else(|| format!("Bookmark not found: {}", id))
+    }
+
+    /// Add a bookmark, returning it with its ID
+    pub fn add_bookmark(
+        &self,
+        name: &str,
+        path: &str,
+        profile: Option<&str>,
+    ) -> Result<Bookmark, String> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO bookmarks (name, path, profile, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![name, path, profile, now_secs() as i64],
+        )
+        .map_err(|e| format!("Failed to save bookmark: {}", e))?;
+        Ok(Bookmark {
+            id: conn.last_insert_rowid(),
+            name: name.to_string(),
+            path: path.to_string(),
+            profile: profile.map(str::to_string),
+        })
+    }
+
+    /// Remove a bookmark, returning false if there was none with the ID
+    pub fn remove_bookmark(&self, id: i64) -> Result<bool, String> {
+        self.conn
+            .lock()
+            .execute("DELETE FROM bookmarks WHERE id = ?1", params![id])
+            .map(|removed| removed > 0)
+            .map_err(|e| format!("Failed to remove bookmark: {}", e))
+    }
+
+    /// Saved ssh hosts in the order they were added
+    pub fn ssh_hosts(&self) -> Result<Vec<SshHost>, String> {
+        let conn = self.conn.lock();
+        let mut statement = conn
+            .prepare("SELECT id, name, hostname, user, port FROM ssh_hosts ORDER BY id")
+            .map_err(|e| format!("Failed to query ssh hosts: {}", e))?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok(SshHost {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    hostname: row.get(2)?,
+                    user: row.get(3)?,
+                    port: row.get(4)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query ssh hosts: {}", e))?;
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read ssh hosts: {}", e))
+    }
+
+    /// Save an ssh host, returning it with its ID
+    pub fn add_ssh_host(
+        &self,
+        name: &str,
+        hostname: &str,
+        user: Option<&str>,
+        port: Option<u16>,
+    ) -> Result<SshHost, String> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO ssh_hosts (name, hostname, user, port, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![name, hostname, user, port, now_secs() as i64],
+        )
+        .map_err(|e| format!("Failed to save ssh host: {}", e))?;
+        Ok(SshHost {
+            id: Some(conn.last_insert_rowid()),
+            name: name.to_string(),
+            hostname: Some(hostname.to_string()),
+            user: user.map(str::to_string),
+            port,
+        })
+    }
+
+    /// Remove a saved ssh host, returning false if there was none with the ID
+    pub fn remove_ssh_host(&self, id: i64) -> Result<bool, String> {
+        self.conn
+            .lock()
+            .execute("DELETE FROM ssh_hosts WHERE id = ?1", params![id])
+            .map(|removed| removed > 0)
+            .map_err(|e| format!("Failed to remove ssh host: {}", e))
+    }
+
+    /// Add a finished command to the history. Commands containing secrets
+    /// are redacted and their invocation isn't kept.
+    pub fn record_command(&self, invocation: &CommandInvocation, exit_code: i32) {
+        self.record_invocation_at(invocation, exit_code, now_secs());
+    }
+
+    fn record_invocation_at(&self, invocation: &CommandInvocation, exit_code: i32, now: u64) {
+        let command = invocation.command_line();
+        let has_secrets = redact::redact(&command) != command
+            || invocation
+                .env
+                .iter()
+                .any(|(_, value)| redact::redact(value) != *value);
+        let snapshot = if has_secrets {
+            None
+        } else {
+            serde_json::to_string(invocation).ok()
+        };
+        self.insert_history(&command, snapshot, exit_code, now);
+    }
+
+    #[cfg(test)]
+    fn record_command_at(&self, command: &str, exit_code: i32, now: u64) {
+        self.insert_history(command, None, exit_code, now);
+    }
+
+    fn insert_history(&self, command: &str, invocation: Option<String>, exit_code: i32, now: u64) {
+        let conn = self.conn.lock();
+        let result = conn
+            .execute(
+                "INSERT INTO command_history (command, exit_code, finished_at, invocation)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![redact::redact(command), exit_code, now as i64, invocation],
+            )
+            .and_then(|_| {
+                conn.execute(
+                    "DELETE FROM command_history WHERE id <= (SELECT MAX(id) FROM command_history) - ?1",
+                    params![MAX_HISTORY_ENTRIES as i64],
+                )
+            });
+        if let Err(e) = result {
+            error!(error = %e, "Failed to record command history");
+        }
+    }
+
+    /// Command history, newest first
+    pub fn history(&self, limit: usize) -> Result<Vec<HistoryEntry>, String> {
+        let conn = self.conn.lock();
+        let mut statement = conn
+            .prepare(
+                "SELECT id, command, exit_code, finished_at, invocation FROM command_history
+                 ORDER BY id DESC LIMIT ?1",
+            )
+            .map_err(|e| format!("Failed to query history: {}", e))?;
+        let rows = statement
+            .query_map(params![limit as i64], |row| {
+                let invocation = row
+                    .get::<_, Option<String>>(4)?
+                    .and_then(|json| serde_json::from_str::<CommandInvocation>(&json).ok());
+                Ok(HistoryEntry {
+                    id: row.get(0)?,
+                    command: row.get(1)?,
+                    exit_code: row.get(2)?,
+                    finished_at: row.get::<_, i64>(3)? as u64,
+                    rerunnable: invocation.is_some(),
+                    cwd: invocation.as_ref().and_then(|i| i.cwd.clone()),
+                    profile: invocation.and_then(|i| i.profile),
+                })
+            })
+            .map_err(|e| format!("Failed to query history: {}", e))?;
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read history: {}", e))
+    }
+
+    /// Invocation of a history entry, for running it again
+    pub fn invocation(&self, id: i64) -> Result<CommandInvocation, String> {
+        let invocation: Option<String> = self
+            .conn
+            .lock()
+            .query_row(
+                "SELECT invocation FROM command_history WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query history: {}", e))?
+            .ok_or_else(|| format!("History entry not found: {}", id))?;
+        invocation
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .ok_or_else(|| format!("History entry {} can't be rerun", id))
+    }
+}
+
+/// Recent sessions, newest first
+#[command]
+pub fn list_session_records(
+    session_store: State<'_, Arc<SessionStore>>,
+    limit: Option<usize>,
+) -> Result<Vec<SessionRecord>, String> {
+    session_store.sessions(limit.unwrap_or(DEFAULT_LIST_LIMIT))
+}
+
+/// Commands run through `execute_command`, newest first
+#[command]
+pub fn list_command_history(
+    session_store: State<'_, Arc<SessionStore>>,
+    limit: Option<usize>,
+) -> Result<Vec<HistoryEntry>, String> {
+    session_store.history(limit.unwrap_or(DEFAULT_LIST_LIMIT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_labels::LabelColor;
+    use tempfile::TempDir;
+
+    const NOW: u64 = 1_750_000_000;
+
+    fn store(temp_dir: &TempDir) -> SessionStore {
+        SessionStore::open(&temp_dir.path().join(DATABASE_FILE))
+    }
+
+    #[test]
+    fn test_migrates_new_database() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = store(&temp_dir);
+        let version: usize = store
+            .conn
+            .lock()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+        // Reopening an up-to-date database is a no-op
+        drop(store);
+        assert!(self::store(&temp_dir).sessions(10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_session_records() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = store(&temp_dir);
+        store.session_created_at("a", Some("/tmp"), NOW);
+        store.session_created_at("b", None, NOW + 1);
+        let labels = SessionLabels::new(vec!["prod".to_string()], Some(LabelColor::Red)).unwrap();
+        store.session_labels_changed("a", &labels);
+        store.session_cwd_changed("a", "/srv");
+        store.session_closed("a", Some(1));
+
+        let reopened = self::store(&temp_dir);
+        let sessions = reopened.sessions(10).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].session_id, "b");
+        assert_eq!(sessions[0].closed_at, None);
+        assert_eq!(sessions[1].labels, labels);
+        assert_eq!(sessions[1].cwd.as_deref(), Some("/srv"));
+        assert!(sessions[1].closed_at.is_some());
+        assert_eq!(sessions[1].exit_code, Some(1));
+        assert_eq!(reopened.session_labels("a"), Some(labels));
+        assert_eq!(reopened.session_labels("missing"), None);
+    }
+
+    #[test]
+    fn test_session_records_are_capped() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = store(&temp_dir);
+        for i in 0..MAX_SESSION_RECORDS + 5 {
+            store.session_created_at(&format!("s{}", i), None, NOW + i as u64);
+        }
+        let sessions = store.sessions(usize::MAX).unwrap();
+        assert_eq!(sessions.len(), MAX_SESSION_RECORDS);
+        assert_eq!(
+            sessions[0].session_id,
+            format!("s{}", MAX_SESSION_RECORDS + 4)
+        );
+        assert!(store.session_labels("s4").is_none());
+    }
+
+    #[test]
+    fn test_command_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = store(&temp_dir);
+        for i in 0..MAX_HISTORY_ENTRIES + 3 {
+            store.record_command_at(&format!("git status {}", i), 0, NOW);
+        }
+        store.record_command_at("curl -H 'Authorization: Bearer abc123def456ghi789'", 6, NOW);
+
+        let history = store.history(usize::MAX).unwrap();
+        assert_eq!(history.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(history[0].exit_code, 6);
+        assert!(!history[0].command.contains("abc123def456ghi789"));
+        assert_eq!(store.history(2).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_invocations_are_kept_without_secrets() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = store(&temp_dir);
+        let invocation = CommandInvocation {
+            cmd: "cargo".to_string(),
+            args: vec!["test".to_string(), "--workspace".to_string()],
+            cwd: Some("/Users/me/code".to_string()),
+            env: vec![("RUST_LOG".to_string(), "debug".to_string())],
+            profile: Some("work".to_string()),
+            sandbox: SandboxLevel::NoNetwork,
+        };
+        store.record_invocation_at(&invocation, 0, NOW);
+        let secret = CommandInvocation {
+            cmd: "curl".to_string(),
+            args: vec![
+                "-H".to_string(),
+                "Authorization: Bearer abc123def456ghi789".to_string(),
+            ],
+            ..invocation.clone()
+        };
+        store.record_invocation_at(&secret, 0, NOW);
+        store.record_command_at("ls", 0, NOW);
+
+        let history = store.history(10).unwrap();
+        assert!(!history[0].rerunnable);
+        assert!(!history[1].rerunnable);
+        assert!(!history[1].command.contains("abc123def456ghi789"));
+        assert!(store.invocation(history[1].id).is_err());
+
+        let entry = &history[2];
+        assert_eq!(entry.command, "cargo test --workspace");
+        assert!(entry.rerunnable);
+        assert_eq!(entry.cwd.as_deref(), Some("/Users/me/code"));
+        assert_eq!(entry.profile.as_deref(), Some("work"));
+        assert_eq!(store.invocation(entry.id).unwrap(), invocation);
+        assert!(store.invocation(entry.id + 100).is_err());
+    }
+
+    #[test]
+    fn test_ssh_hosts() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = store(&temp_dir);
+        let prod = store
+            .add_ssh_host("prod", "10.0.0.5", Some("deploy"), Some(2222))
+            .unwrap();
+        let pi = store
+            .add_ssh_host("pi", "raspberrypi.local", None, None)
+            .unwrap();
+        assert_eq!(store.ssh_hosts().unwrap(), [prod.clone(), pi.clone()]);
+
+        assert!(store.remove_ssh_host(prod.id.unwrap()).unwrap());
+        assert!(!store.remove_ssh_host(prod.id.unwrap()).unwrap());
+
+        let reopened = SessionStore::open(&temp_dir.path().join(DATABASE_FILE));
+        assert_eq!(reopened.ssh_hosts().unwrap(), [pi]);
+    }
+
+    #[test]
+    fn test_bookmarks() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = store(&temp_dir);
+        let code = store
+            .add_bookmark("code", "/Users/me/code", Some("work"))
+            .unwrap();
+        let notes = store
+            .add_bookmark("notes", "/Users/me/notes", None)
+            .unwrap();
+        assert_ne!(code.id, notes.id);
+        assert_eq!(store.bookmarks().unwrap(), [code.clone(), notes.clone()]);
+        assert_eq!(
+            store.bookmark(code.id).unwrap().profile.as_deref(),
+            Some("work")
+        );
+
+        assert!(store.remove_bookmark(code.id).unwrap());
+        assert!(!store.remove_bookmark(code.id).unwrap());
+        assert!(store.bookmark(code.id).is_err());
+
+        let reopened = SessionStore::open(&temp_dir.path().join(DATABASE_FILE));
+        assert_eq!(reopened.bookmarks().unwrap(), [notes]);
+    }
+
+    #[test]
+    fn test_imports_legacy_recent_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        let legacy = temp_dir.path().join(LEGACY_RECENT_DIRS_FILE);
+        fs::write(
+            &legacy,
+            r#"[{"path": "/Users/me/code", "visits": 3, "last_visited": 1750000000}]"#,
+        )
+        .unwrap();
+
+        let store = store(&temp_dir);
+        store.import_legacy_files(temp_dir.path());
+        assert_eq!(
+            store.recent_dirs().unwrap(),
+            [RecentDir {
+                path: "/Users/me/code".to_string(),
+                visits: 3,
+                last_visited: NOW,
+            }]
+        );
+        assert!(!legacy.exists());
+        assert!(temp_dir.path().join("recent-dirs.json.migrated").exists());
+
+        // Importing again finds nothing to do
+        store.import_legacy_files(temp_dir.path());
+        assert_eq!(store.recent_dirs().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_recovers_from_corrupt_database() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(DATABASE_FILE);
+        fs::write(
+            &path,
+            b"definitely not a SQLite database, just some garbage bytes",
+        )
+        .unwrap();
+
+        let store = SessionStore::open(&path);
+        store.record_command_at("ls", 0, NOW);
+        assert_eq!(store.history(10).unwrap().len(), 1);
+        assert!(temp_dir.path().join("sessions.db.corrupt").exists());
+    }
+}
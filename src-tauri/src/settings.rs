@@ -3,9 +3,33 @@
 //! Provides centralized settings storage with type-safe validation.
 //! Settings are persisted to app data directory and survive app updates.
 
+use crate::accessibility::AccessibilityVerbosity;
+use crate::activity;
+use crate::answerback::{
+    self, DEFAULT_PRIMARY_DEVICE_ATTRIBUTES, DEFAULT_SECONDARY_DEVICE_ATTRIBUTES,
+};
+use crate::caffeinate::DEFAULT_CAFFEINATE_COMMANDS;
+use crate::cell_width::UnicodeWidthMode;
+use crate::clipboard::ClipboardAccess;
+use crate::config_file;
+use crate::i18n::Locale;
+use crate::idle::{IdleSuspendMode, DEFAULT_IDLE_SUSPEND_MINUTES};
+use crate::input::{MousePolicy, MouseReporting};
+use crate::links::{self, LinkHandler, LinkPattern};
+use crate::lock::DEFAULT_INACTIVITY_LOCK_MINUTES;
+use crate::notifications::QuietHours;
+use crate::output_batch;
+use crate::passthrough;
+use crate::presentation::{PresentationBackup, PresentationMode};
+use crate::profiles::{self, DirectoryProfile, Profile};
+use crate::status_server::DEFAULT_STATUS_SERVER_PORT;
+use crate::transcript::TranscriptFormat;
+use crate::widgets::{self, MenuWidget};
+use crate::window_presets::{self, WindowSizePreset};
+use crate::workspace::{self, Workspace};
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use tracing::{debug, error};
 
@@ -35,13 +59,339 @@ pub struct AppSettings {
     #[serde(default = "default_pin_shortcut")]
     pub pin_shortcut: String,
 
-    /// Whether onboarding has been completed
-    #[serde(default)]
-    pub onboarding_complete: bool,
+    /// Onboarding progress. Older settings files stored a single
+    /// `onboarding_complete` boolean, which is migrated on load.
+    #[serde(
+        default,
+        alias = "onboarding_complete",
+        deserialize_with = "deserialize_onboarding"
+    )]
+    pub onboarding: OnboardingState,
 
     /// Whether window is pinned (prevents auto-hide)
     #[serde(default)]
     pub pinned: bool,
+
+    /// Whether programs may post notifications via OSC 9 / OSC 777
+    /// (can be overridden per session)
+    #[serde(default = "default_true")]
+    pub terminal_notifications: bool,
+
+    /// Whether programs may enable the kitty keyboard protocol
+    #[serde(default)]
+    pub kitty_keyboard_protocol: bool,
+
+    /// Cell width of ambiguous-width characters and VS16 emoji, shared with
+    /// the frontend so backend columns match the rendered terminal
+    #[serde(default)]
+    pub unicode_width_mode: UnicodeWidthMode,
+
+    /// Whether mouse events are reported to programs that enable mouse tracking
+    #[serde(default)]
+    pub mouse_reporting: MouseReporting,
+
+    /// Whether holding Shift bypasses mouse reporting for native text selection
+    #[serde(default = "default_true")]
+    pub mouse_shift_override: bool,
+
+    /// Whether local usage statistics are collected (opt-in, never uploaded)
+    #[serde(default)]
+    pub usage_stats_enabled: bool,
+
+    /// Which terminal events are announced to VoiceOver
+    #[serde(default)]
+    pub accessibility_verbosity: AccessibilityVerbosity,
+
+    /// Reduce animations; `None` follows the system "Reduce motion" preference
+    #[serde(default)]
+    pub reduce_motion: Option<bool>,
+
+    /// Use a high-contrast theme; `None` follows the system "Increase contrast" preference
+    #[serde(default)]
+    pub increase_contrast: Option<bool>,
+
+    /// Tab title template, see `tab_title::render_title` for placeholders
+    #[serde(default = "default_title_template")]
+    pub title_template: String,
+
+    /// Named profiles (theme, environment, startup command)
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+
+    /// Directory globs mapped to profile names; the first match wins
+    #[serde(default)]
+    pub directory_profiles: Vec<DirectoryProfile>,
+
+    /// Suspend shells that sit idle while the window is hidden
+    #[serde(default)]
+    pub idle_suspend: IdleSuspendMode,
+
+    /// Minutes of inactivity before an idle shell is suspended
+    #[serde(default = "default_idle_suspend_minutes")]
+    pub idle_suspend_minutes: u32,
+
+    /// Keep the Mac awake while a command from `caffeinate_commands` runs
+    #[serde(default)]
+    pub caffeinate_during_commands: bool,
+
+    /// Commands that keep the Mac awake, e.g. `rsync` or `cargo build`
+    #[serde(default = "default_caffeinate_commands")]
+    pub caffeinate_commands: Vec<String>,
+
+    /// Lock the panel after inactivity or on wake, requiring Touch ID or the
+    /// account password to unlock
+    #[serde(default)]
+    pub inactivity_lock: bool,
+
+    /// Minutes without input before the panel locks
+    #[serde(default = "default_inactivity_lock_minutes")]
+    pub inactivity_lock_minutes: u32,
+
+    /// Redact secrets from logs and diagnostics exports
+    #[serde(default = "default_true")]
+    pub redact_secrets: bool,
+
+    /// Extra redaction regexes; a `secret` named group limits what is redacted
+    #[serde(default)]
+    pub redaction_patterns: Vec<String>,
+
+    /// Language of error messages from the backend
+    #[serde(default)]
+    pub locale: Locale,
+
+    /// Mouse reports or arrow keys sent per wheel notch (1 - 10)
+    #[serde(default = "default_wheel_scroll_multiplier")]
+    pub wheel_scroll_multiplier: u8,
+
+    /// Whether the wheel sends arrow keys on the alternate screen when the
+    /// program doesn't track the mouse (scrolls `less`, `man` and `vim`)
+    #[serde(default = "default_true")]
+    pub wheel_alternate_scroll: bool,
+
+    /// Reply to ENQ (empty sends nothing)
+    #[serde(default)]
+    pub answerback: String,
+
+    /// Parameters of the Primary Device Attributes reply (`CSI ? ... c`)
+    #[serde(default = "default_primary_device_attributes")]
+    pub primary_device_attributes: String,
+
+    /// Parameters of the Secondary Device Attributes reply (`CSI > ... c`)
+    #[serde(default = "default_secondary_device_attributes")]
+    pub secondary_device_attributes: String,
+
+    /// Serve `/healthz` and `/sessions` on localhost for monitoring scripts
+    #[serde(default)]
+    pub status_server_enabled: bool,
+
+    /// Localhost port of the status endpoint (1024 - 65535)
+    #[serde(default = "default_status_server_port")]
+    pub status_server_port: u16,
+
+    /// Window sizes `cycle_window_size` steps through
+    #[serde(default = "window_presets::default_presets")]
+    pub window_size_presets: Vec<WindowSizePreset>,
+
+    /// Show running jobs, progress and bells in a small always-on-top strip
+    #[serde(default)]
+    pub status_strip_enabled: bool,
+
+    /// How long PTY output is collected into one frame before it's sent to
+    /// the terminal, in milliseconds (0 - 50; 0 sends every read)
+    #[serde(default = "default_output_frame_interval_ms")]
+    pub output_frame_interval_ms: u64,
+
+    /// Most output sent to the terminal per second and session, in KiB (0
+    /// for no limit); the rest is dropped so a flood can't freeze the app
+    #[serde(default = "default_output_rate_limit_kib")]
+    pub output_rate_limit_kib: u64,
+
+    /// Shell started for new sessions instead of the login shell (`$SHELL`)
+    #[serde(default)]
+    pub shell: Option<ShellConfig>,
+
+    /// Environment variables set in every new session (e.g. `EDITOR`,
+    /// `HTTPS_PROXY`); directory profiles' variables take precedence
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+
+    /// Extra link patterns for `detect_links`, each with the URL to open
+    #[serde(default)]
+    pub link_patterns: Vec<LinkPattern>,
+
+    /// How `open_link` opens links, by scheme (e.g. `https` in a specific
+    /// browser); other schemes than http, https and mailto need confirmation
+    #[serde(default)]
+    pub link_handlers: BTreeMap<String, LinkHandler>,
+
+    /// Seconds without output after which a busy session is reported idle
+    /// (`pty-idle`); 0 turns activity events off
+    #[serde(default = "default_activity_idle_seconds")]
+    pub activity_idle_seconds: u32,
+
+    /// Whether the bell posts a notification while the panel is hidden
+    #[serde(default)]
+    pub bell_notifications: bool,
+
+    /// Whether closing a pane detaches its session instead of ending the
+    /// shell, to be reattached from the session switcher
+    #[serde(default)]
+    pub keep_sessions_alive: bool,
+
+    /// Whether dropping a single folder on a shell at its prompt runs `cd`
+    /// into it instead of typing the path
+    #[serde(default)]
+    pub cd_on_folder_drop: bool,
+
+    /// Whether Enter on a plain `rm` at a shell prompt is held back so the
+    /// files can be moved to the Trash instead
+    #[serde(default)]
+    pub safe_delete: bool,
+
+    /// Scripts whose output is shown in the tray menu or title
+    #[serde(default)]
+    pub menu_widgets: Vec<MenuWidget>,
+
+    /// SSH hosts, by name, that `connect_ssh` connects with mosh when it's
+    /// installed
+    #[serde(default)]
+    pub mosh_hosts: Vec<String>,
+
+    /// Daily window (local `HH:MM` times) during which bells and system
+    /// notifications are silenced
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+
+    /// Whether every new session's output is appended to a transcript file
+    #[serde(default)]
+    pub session_logging: bool,
+
+    /// How transcripts are written: raw output or plain text
+    #[serde(default)]
+    pub session_log_format: TranscriptFormat,
+
+    /// Shell command run as a job whenever the window is shown
+    #[serde(default)]
+    pub on_show_command: Option<String>,
+
+    /// Shell command run as a job whenever the window is hidden
+    #[serde(default)]
+    pub on_hide_command: Option<String>,
+
+    /// Cmd shortcuts (`cmd+k`) sent to the terminal instead of handled by the
+    /// app while one of the listed programs is in the foreground
+    #[serde(default = "passthrough::default_shortcut_passthrough")]
+    pub shortcut_passthrough: BTreeMap<String, Vec<String>>,
+
+    /// Make the window opaque while the screen is shared (`auto`) or always
+    /// (`on`)
+    #[serde(default)]
+    pub presentation_mode: PresentationMode,
+
+    /// While `toggle_presentation_mode` is on, the settings it replaced
+    #[serde(default)]
+    pub presenting: Option<PresentationBackup>,
+
+    /// Whether programs may copy to and read the clipboard with OSC 52
+    #[serde(default)]
+    pub clipboard_access: ClipboardAccess,
+
+    /// Named sets of sessions and window placement for `open_workspace`
+    #[serde(default)]
+    pub workspaces: Vec<Workspace>,
+}
+
+/// A shell to start for new sessions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShellConfig {
+    /// Absolute path of the shell
+    pub path: String,
+    /// Arguments for interactive sessions (e.g. `["-l"]`); command sessions
+    /// always run with `-l -c`
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// A step of the onboarding flow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    ShortcutSet,
+    ShellIntegrationInstalled,
+    PermissionsGranted,
+}
+
+impl OnboardingStep {
+    /// All steps, in the order the onboarding flow presents them
+    pub const ALL: [OnboardingStep; 3] = [
+        OnboardingStep::ShortcutSet,
+        OnboardingStep::ShellIntegrationInstalled,
+        OnboardingStep::PermissionsGranted,
+    ];
+}
+
+/// Onboarding progress, so the flow can resume where it left off
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnboardingState {
+    #[serde(default)]
+    pub shortcut_set: bool,
+    #[serde(default)]
+    pub shell_integration_installed: bool,
+    #[serde(default)]
+    pub permissions_granted: bool,
+    /// Set when the user finishes (or skips) the flow
+    #[serde(default)]
+    pub completed: bool,
+}
+
+impl OnboardingState {
+    pub fn is_step_done(&self, step: OnboardingStep) -> bool {
+        match step {
+            OnboardingStep::ShortcutSet => self.shortcut_set,
+            OnboardingStep::ShellIntegrationInstalled => self.shell_integration_installed,
+            OnboardingStep::PermissionsGranted => self.permissions_granted,
+        }
+    }
+
+    pub fn set_step(&mut self, step: OnboardingStep, done: bool) {
+        let field = match step {
+            OnboardingStep::ShortcutSet => &mut self.shortcut_set,
+            OnboardingStep::ShellIntegrationInstalled => &mut self.shell_integration_installed,
+            OnboardingStep::PermissionsGranted => &mut self.permissions_granted,
+        };
+        *field = done;
+    }
+
+    /// First step not done yet, or `None` when onboarding is finished
+    pub fn next_step(&self) -> Option<OnboardingStep> {
+        if self.completed {
+            return None;
+        }
+        OnboardingStep::ALL
+            .into_iter()
+            .find(|step| !self.is_step_done(*step))
+    }
+}
+
+/// Accept both the current onboarding object and the legacy boolean
+fn deserialize_onboarding<'de, D>(deserializer: D) -> Result<OnboardingState, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Onboarding {
+        Legacy(bool),
+        Current(OnboardingState),
+    }
+
+    Ok(match Onboarding::deserialize(deserializer)? {
+        Onboarding::Legacy(completed) => OnboardingState {
+            completed,
+            ..OnboardingState::default()
+        },
+        Onboarding::Current(state) => state,
+    })
 }
 
 // Default value functions
@@ -60,6 +410,44 @@ fn default_pin_shortcut() -> String {
 fn default_true() -> bool {
     true
 }
+fn default_idle_suspend_minutes() -> u32 {
+    DEFAULT_IDLE_SUSPEND_MINUTES
+}
+fn default_inactivity_lock_minutes() -> u32 {
+    DEFAULT_INACTIVITY_LOCK_MINUTES
+}
+fn default_wheel_scroll_multiplier() -> u8 {
+    1
+}
+fn default_primary_device_attributes() -> String {
+    DEFAULT_PRIMARY_DEVICE_ATTRIBUTES.to_string()
+}
+fn default_secondary_device_attributes() -> String {
+    DEFAULT_SECONDARY_DEVICE_ATTRIBUTES.to_string()
+}
+fn default_output_frame_interval_ms() -> u64 {
+    output_batch::DEFAULT_FRAME_INTERVAL_MS
+}
+fn default_output_rate_limit_kib() -> u64 {
+    output_batch::DEFAULT_RATE_LIMIT_KIB
+}
+
+fn default_activity_idle_seconds() -> u32 {
+    activity::DEFAULT_IDLE_SECONDS
+}
+
+fn default_status_server_port() -> u16 {
+    DEFAULT_STATUS_SERVER_PORT
+}
+fn default_caffeinate_commands() -> Vec<String> {
+    DEFAULT_CAFFEINATE_COMMANDS
+        .iter()
+        .map(|c| c.to_string())
+        .collect()
+}
+fn default_title_template() -> String {
+    crate::tab_title::DEFAULT_TITLE_TEMPLATE.to_string()
+}
 
 impl Default for AppSettings {
     fn default() -> Self {
@@ -70,13 +458,76 @@ impl Default for AppSettings {
             global_shortcut: default_shortcut(),
             shortcut_enabled: true,
             pin_shortcut: default_pin_shortcut(),
-            onboarding_complete: false,
+            onboarding: OnboardingState::default(),
             pinned: false,
+            terminal_notifications: true,
+            kitty_keyboard_protocol: false,
+            unicode_width_mode: UnicodeWidthMode::default(),
+            mouse_reporting: MouseReporting::Enabled,
+            mouse_shift_override: true,
+            usage_stats_enabled: false,
+            accessibility_verbosity: AccessibilityVerbosity::Minimal,
+            reduce_motion: None,
+            increase_contrast: None,
+            title_template: default_title_template(),
+            profiles: Vec::new(),
+            directory_profiles: Vec::new(),
+            idle_suspend: IdleSuspendMode::Off,
+            idle_suspend_minutes: DEFAULT_IDLE_SUSPEND_MINUTES,
+            caffeinate_during_commands: false,
+            caffeinate_commands: default_caffeinate_commands(),
+            inactivity_lock: false,
+            inactivity_lock_minutes: DEFAULT_INACTIVITY_LOCK_MINUTES,
+            redact_secrets: true,
+            redaction_patterns: Vec::new(),
+            locale: Locale::System,
+            wheel_scroll_multiplier: default_wheel_scroll_multiplier(),
+            wheel_alternate_scroll: true,
+            answerback: String::new(),
+            primary_device_attributes: default_primary_device_attributes(),
+            secondary_device_attributes: default_secondary_device_attributes(),
+            status_server_enabled: false,
+            status_server_port: default_status_server_port(),
+            window_size_presets: window_presets::default_presets(),
+            status_strip_enabled: false,
+            output_frame_interval_ms: default_output_frame_interval_ms(),
+            output_rate_limit_kib: default_output_rate_limit_kib(),
+            shell: None,
+            env: BTreeMap::new(),
+            link_patterns: Vec::new(),
+            link_handlers: BTreeMap::new(),
+            activity_idle_seconds: default_activity_idle_seconds(),
+            bell_notifications: false,
+            keep_sessions_alive: false,
+            cd_on_folder_drop: false,
+            safe_delete: false,
+            menu_widgets: Vec::new(),
+            mosh_hosts: Vec::new(),
+            quiet_hours: None,
+            session_logging: false,
+            session_log_format: TranscriptFormat::Plain,
+            on_show_command: None,
+            on_hide_command: None,
+            shortcut_passthrough: passthrough::default_shortcut_passthrough(),
+            presentation_mode: PresentationMode::default(),
+            presenting: None,
+            clipboard_access: ClipboardAccess::default(),
+            workspaces: Vec::new(),
         }
     }
 }
 
 impl AppSettings {
+    /// Mouse reporting policy for input encoding
+    pub fn mouse_policy(&self) -> MousePolicy {
+        MousePolicy {
+            reporting: self.mouse_reporting,
+            shift_overrides: self.mouse_shift_override,
+            scroll_multiplier: self.wheel_scroll_multiplier,
+            alternate_scroll: self.wheel_alternate_scroll,
+        }
+    }
+
     /// Validate and clamp settings to allowed ranges
     pub fn validate(&mut self) {
         // Clamp opacity to 0.3 - 1.0
@@ -86,6 +537,88 @@ impl AppSettings {
         self.font_size = self.font_size.clamp(10, 24);
 
         // Note: Window size validation removed - now managed per-screen by screen_config.rs
+
+        self.caffeinate_commands.retain(|c| !c.trim().is_empty());
+
+        // Clamp wheel scroll multiplier to 1 - 10
+        self.wheel_scroll_multiplier = self.wheel_scroll_multiplier.clamp(1, 10);
+
+        // Terminal identification replies must be well-formed
+        self.answerback = answerback::sanitize_answerback(&self.answerback);
+        if !answerback::is_attribute_list(&self.primary_device_attributes) {
+            self.primary_device_attributes = default_primary_device_attributes();
+        }
+        if !answerback::is_attribute_list(&self.secondary_device_attributes) {
+            self.secondary_device_attributes = default_secondary_device_attributes();
+        }
+
+        // Privileged ports can't be bound without root
+        if self.status_server_port < 1024 {
+            self.status_server_port = default_status_server_port();
+        }
+
+        window_presets::sanitize(&mut self.window_size_presets);
+
+        self.output_frame_interval_ms = self
+            .output_frame_interval_ms
+            .min(output_batch::MAX_FRAME_INTERVAL_MS);
+        if self.output_rate_limit_kib > 0 {
+            self.output_rate_limit_kib = self
+                .output_rate_limit_kib
+                .max(output_batch::MIN_RATE_LIMIT_KIB);
+        }
+
+        validate_shell(&mut self.shell);
+        for profile in &mut self.profiles {
+            validate_shell(&mut profile.shell);
+        }
+
+        self.env.retain(|name, _| profiles::is_env_name(name));
+        self.link_patterns
+            .retain(|link| !link.pattern.is_empty() && !link.url.trim().is_empty());
+
+        self.activity_idle_seconds = self.activity_idle_seconds.min(activity::MAX_IDLE_SECONDS);
+        widgets::sanitize(&mut self.menu_widgets);
+        workspace::sanitize(&mut self.workspaces);
+        passthrough::sanitize(&mut self.shortcut_passthrough);
+        self.mosh_hosts.retain(|host| !host.trim().is_empty());
+        if self
+            .quiet_hours
+            .as_ref()
+            .is_some_and(|quiet| !quiet.is_valid())
+        {
+            self.quiet_hours = None;
+        }
+        for command in [&mut self.on_show_command, &mut self.on_hide_command] {
+            *command = command
+                .take()
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty());
+        }
+
+        // Schemes are case-insensitive; `open_link` looks them up lowercased
+        self.link_handlers = std::mem::take(&mut self.link_handlers)
+            .into_iter()
+            .filter(|(scheme, handler)| {
+                links::is_scheme(scheme)
+                    && !matches!(handler, LinkHandler::Browser { app } if app.trim().is_empty())
+            })
+            .map(|(scheme, handler)| (scheme.to_ascii_lowercase(), handler))
+            .collect();
+    }
+}
+
+/// Trim a shell path and drop a relative one, which would be looked up in
+/// µTerm's PATH rather than the user's
+fn validate_shell(shell: &mut Option<ShellConfig>) {
+    if let Some(config) = shell {
+        config.path = config.path.trim().to_string();
+    }
+    if shell
+        .as_ref()
+        .is_some_and(|config| !Path::new(&config.path).is_absolute())
+    {
+        *shell = None;
     }
 }
 
@@ -106,21 +639,15 @@ impl SettingsManager {
     }
 
     /// Load settings from disk
-    fn load_settings(path: &PathBuf) -> AppSettings {
-        match fs::read_to_string(path) {
-            Ok(content) => match serde_json::from_str::<AppSettings>(&content) {
-                Ok(mut settings) => {
-                    settings.validate();
-                    debug!("Loaded settings from disk");
-                    settings
-                }
-                Err(e) => {
-                    error!("Failed to parse settings: {}, using defaults", e);
-                    AppSettings::default()
-                }
-            },
-            Err(_) => {
-                debug!("No existing settings file, using defaults");
+    fn load_settings(path: &Path) -> AppSettings {
+        match config_file::read_json::<AppSettings>(path) {
+            Some(mut settings) => {
+                settings.validate();
+                debug!("Loaded settings from disk");
+                settings
+            }
+            None => {
+                debug!("No usable settings file, using defaults");
                 AppSettings::default()
             }
         }
@@ -135,17 +662,9 @@ impl SettingsManager {
                 poisoned.into_inner()
             }
         };
-        match serde_json::to_string_pretty(&*settings) {
-            Ok(json) => {
-                if let Some(parent) = self.settings_path.parent() {
-                    let _ = fs::create_dir_all(parent);
-                }
-                match fs::write(&self.settings_path, json) {
-                    Ok(_) => debug!("Saved settings to disk"),
-                    Err(e) => error!("Failed to write settings: {}", e),
-                }
-            }
-            Err(e) => error!("Failed to serialize settings: {}", e),
+        match config_file::write_json(&self.settings_path, &*settings) {
+            Ok(()) => debug!("Saved settings to disk"),
+            Err(e) => error!("Failed to save settings: {}", e),
         }
     }
 
@@ -201,13 +720,29 @@ impl SettingsManager {
 
     pub fn set_onboarding_complete(&self, complete: bool) {
         if let Ok(mut settings) = self.settings.lock() {
-            settings.onboarding_complete = complete;
+            settings.onboarding.completed = complete;
         } else {
             error!("Failed to set onboarding complete: mutex poisoned");
         }
         self.save_settings();
     }
 
+    /// Mark an onboarding step done or not done, returning the new state
+    pub fn set_onboarding_step(&self, step: OnboardingStep, done: bool) -> OnboardingState {
+        let state = match self.settings.lock() {
+            Ok(mut settings) => {
+                settings.onboarding.set_step(step, done);
+                settings.onboarding.clone()
+            }
+            Err(_) => {
+                error!("Failed to set onboarding step: mutex poisoned");
+                return self.get().onboarding;
+            }
+        };
+        self.save_settings();
+        state
+    }
+
     pub fn get_pinned(&self) -> bool {
         self.settings
             .lock()
@@ -242,6 +777,7 @@ impl SettingsManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::session_labels::LabelColor;
     use std::fs;
     use tempfile::TempDir;
 
@@ -255,8 +791,80 @@ mod tests {
         assert_eq!(settings.global_shortcut, "CommandOrControl+Shift+T");
         assert!(settings.shortcut_enabled);
         assert_eq!(settings.pin_shortcut, "CommandOrControl+Backquote");
-        assert!(!settings.onboarding_complete);
+        assert_eq!(settings.onboarding, OnboardingState::default());
         assert!(!settings.pinned);
+        assert!(settings.terminal_notifications);
+        assert!(!settings.kitty_keyboard_protocol);
+        assert_eq!(settings.unicode_width_mode, UnicodeWidthMode::default());
+        assert_eq!(settings.mouse_policy(), MousePolicy::default());
+        assert!(!settings.usage_stats_enabled);
+        assert_eq!(
+            settings.accessibility_verbosity,
+            AccessibilityVerbosity::Minimal
+        );
+        assert_eq!(settings.reduce_motion, None);
+        assert_eq!(settings.increase_contrast, None);
+        assert_eq!(settings.title_template, "{cwd_basename}");
+        assert!(settings.profiles.is_empty());
+        assert!(settings.directory_profiles.is_empty());
+        assert_eq!(settings.idle_suspend, IdleSuspendMode::Off);
+        assert_eq!(settings.idle_suspend_minutes, 15);
+        assert!(!settings.caffeinate_during_commands);
+        assert!(settings
+            .caffeinate_commands
+            .contains(&"cargo build".to_string()));
+        assert!(!settings.inactivity_lock);
+        assert_eq!(settings.inactivity_lock_minutes, 5);
+        assert!(settings.redact_secrets);
+        assert!(settings.redaction_patterns.is_empty());
+        assert_eq!(settings.locale, Locale::System);
+        assert_eq!(settings.wheel_scroll_multiplier, 1);
+        assert!(settings.wheel_alternate_scroll);
+        assert!(settings.answerback.is_empty());
+        assert_eq!(settings.primary_device_attributes, "1;2");
+        assert_eq!(settings.secondary_device_attributes, "0;276;0");
+        assert!(!settings.status_server_enabled);
+        assert_eq!(settings.status_server_port, DEFAULT_STATUS_SERVER_PORT);
+        assert_eq!(
+            settings.window_size_presets,
+            window_presets::default_presets()
+        );
+        assert!(!settings.status_strip_enabled);
+        assert_eq!(
+            settings.output_frame_interval_ms,
+            output_batch::DEFAULT_FRAME_INTERVAL_MS
+        );
+        assert_eq!(
+            settings.output_rate_limit_kib,
+            output_batch::DEFAULT_RATE_LIMIT_KIB
+        );
+        assert_eq!(settings.shell, None);
+        assert!(settings.env.is_empty());
+        assert!(settings.link_patterns.is_empty());
+        assert!(settings.link_handlers.is_empty());
+        assert_eq!(
+            settings.activity_idle_seconds,
+            activity::DEFAULT_IDLE_SECONDS
+        );
+        assert!(!settings.bell_notifications);
+        assert!(!settings.keep_sessions_alive);
+        assert!(!settings.cd_on_folder_drop);
+        assert!(!settings.safe_delete);
+        assert!(settings.menu_widgets.is_empty());
+        assert!(settings.mosh_hosts.is_empty());
+        assert!(settings.quiet_hours.is_none());
+        assert!(!settings.session_logging);
+        assert_eq!(settings.session_log_format, TranscriptFormat::Plain);
+        assert!(settings.on_show_command.is_none());
+        assert!(settings.on_hide_command.is_none());
+        assert_eq!(
+            settings.shortcut_passthrough,
+            passthrough::default_shortcut_passthrough()
+        );
+        assert_eq!(settings.presentation_mode, PresentationMode::Off);
+        assert!(settings.presenting.is_none());
+        assert_eq!(settings.clipboard_access, ClipboardAccess::Ask);
+        assert!(settings.workspaces.is_empty());
     }
 
     #[test]
@@ -315,6 +923,236 @@ mod tests {
         assert_eq!(settings.font_size, 24);
     }
 
+    #[test]
+    fn test_app_settings_validate_caffeinate_commands() {
+        let mut settings = AppSettings::default();
+        settings.caffeinate_commands = vec!["rsync".to_string(), "  ".to_string()];
+        settings.validate();
+        assert_eq!(settings.caffeinate_commands, ["rsync"]);
+    }
+
+    #[test]
+    fn test_app_settings_validate_wheel_scroll_multiplier() {
+        let mut settings = AppSettings::default();
+        settings.wheel_scroll_multiplier = 0;
+        settings.validate();
+        assert_eq!(settings.wheel_scroll_multiplier, 1);
+
+        settings.wheel_scroll_multiplier = 50;
+        settings.validate();
+        assert_eq!(settings.wheel_scroll_multiplier, 10);
+    }
+
+    #[test]
+    fn test_app_settings_validate_terminal_id() {
+        let mut settings = AppSettings::default();
+        settings.answerback = "host\x1b[2J\r".to_string();
+        settings.primary_device_attributes = "62;22".to_string();
+        settings.secondary_device_attributes = "1;2c\x1b".to_string();
+        settings.validate();
+        assert_eq!(settings.answerback, "host[2J\r");
+        assert_eq!(settings.primary_device_attributes, "62;22");
+        assert_eq!(settings.secondary_device_attributes, "0;276;0");
+    }
+
+    #[test]
+    fn test_app_settings_validate_status_server_port() {
+        let mut settings = AppSettings::default();
+        settings.status_server_port = 80;
+        settings.validate();
+        assert_eq!(settings.status_server_port, DEFAULT_STATUS_SERVER_PORT);
+
+        settings.status_server_port = 9100;
+        settings.validate();
+        assert_eq!(settings.status_server_port, 9100);
+    }
+
+    #[test]
+    fn test_app_settings_validate_window_size_presets() {
+        let mut settings = AppSettings::default();
+        settings.window_size_presets.clear();
+        settings.validate();
+        assert_eq!(
+            settings.window_size_presets,
+            window_presets::default_presets()
+        );
+    }
+
+    #[test]
+    fn test_app_settings_validate_output_frame_interval() {
+        let mut settings = AppSettings::default();
+        settings.output_frame_interval_ms = 500;
+        settings.validate();
+        assert_eq!(
+            settings.output_frame_interval_ms,
+            output_batch::MAX_FRAME_INTERVAL_MS
+        );
+
+        settings.output_frame_interval_ms = 0;
+        settings.validate();
+        assert_eq!(settings.output_frame_interval_ms, 0);
+    }
+
+    #[test]
+    fn test_app_settings_validate_output_rate_limit() {
+        let mut settings = AppSettings::default();
+        settings.output_rate_limit_kib = 1;
+        settings.validate();
+        assert_eq!(
+            settings.output_rate_limit_kib,
+            output_batch::MIN_RATE_LIMIT_KIB
+        );
+
+        settings.output_rate_limit_kib = 0;
+        settings.validate();
+        assert_eq!(settings.output_rate_limit_kib, 0);
+    }
+
+    #[test]
+    fn test_app_settings_validate_activity_idle_seconds() {
+        let mut settings = AppSettings::default();
+        settings.activity_idle_seconds = 3600;
+        settings.validate();
+        assert_eq!(settings.activity_idle_seconds, activity::MAX_IDLE_SECONDS);
+
+        settings.activity_idle_seconds = 0;
+        settings.validate();
+        assert_eq!(settings.activity_idle_seconds, 0);
+    }
+
+    #[test]
+    fn test_app_settings_validate_shell() {
+        let mut settings = AppSettings::default();
+        settings.shell = Some(ShellConfig {
+            path: " /opt/homebrew/bin/fish ".to_string(),
+            args: vec!["-l".to_string()],
+        });
+        settings.validate();
+        assert_eq!(
+            settings.shell.as_ref().map(|shell| shell.path.as_str()),
+            Some("/opt/homebrew/bin/fish")
+        );
+
+        settings.shell = Some(ShellConfig {
+            path: "fish".to_string(),
+            args: Vec::new(),
+        });
+        settings.validate();
+        assert_eq!(settings.shell, None);
+
+        settings.profiles = vec![Profile {
+            name: "docker".to_string(),
+            shell: Some(ShellConfig {
+                path: "bash".to_string(),
+                args: Vec::new(),
+            }),
+            ..Default::default()
+        }];
+        settings.validate();
+        assert_eq!(settings.profiles[0].shell, None);
+    }
+
+    #[test]
+    fn test_app_settings_validate_env() {
+        let mut settings = AppSettings::default();
+        settings.env = BTreeMap::from([
+            ("EDITOR".to_string(), "nvim".to_string()),
+            ("HTTPS_PROXY".to_string(), "http://proxy:3128".to_string()),
+            ("NOT VALID".to_string(), "x".to_string()),
+            ("".to_string(), "x".to_string()),
+        ]);
+        settings.validate();
+        assert_eq!(
+            settings.env.keys().collect::<Vec<_>>(),
+            ["EDITOR", "HTTPS_PROXY"]
+        );
+    }
+
+    #[test]
+    fn test_app_settings_validate_link_patterns() {
+        let mut settings = AppSettings::default();
+        let issue = LinkPattern {
+            pattern: r"\bOPS-\d+\b".to_string(),
+            url: "https://jira.example.com/browse/$0".to_string(),
+        };
+        settings.link_patterns = vec![
+            issue.clone(),
+            LinkPattern {
+                pattern: String::new(),
+                url: "https://example.com".to_string(),
+            },
+            LinkPattern {
+                pattern: "x".to_string(),
+                url: " ".to_string(),
+            },
+        ];
+        settings.validate();
+        assert_eq!(settings.link_patterns, [issue]);
+    }
+
+    #[test]
+    fn test_app_settings_validate_quiet_hours() {
+        let mut settings = AppSettings::default();
+        let night = QuietHours {
+            start: "22:00".to_string(),
+            end: "8:00".to_string(),
+        };
+        settings.quiet_hours = Some(night.clone());
+        settings.validate();
+        assert_eq!(settings.quiet_hours, Some(night));
+
+        settings.quiet_hours = Some(QuietHours {
+            start: "10pm".to_string(),
+            end: "8:00".to_string(),
+        });
+        settings.validate();
+        assert_eq!(settings.quiet_hours, None);
+    }
+
+    #[test]
+    fn test_app_settings_validate_window_hooks() {
+        let mut settings = AppSettings::default();
+        settings.on_show_command = Some("  spotify pause \n".to_string());
+        settings.on_hide_command = Some("   ".to_string());
+        settings.validate();
+        assert_eq!(settings.on_show_command.as_deref(), Some("spotify pause"));
+        assert_eq!(settings.on_hide_command, None);
+    }
+
+    #[test]
+    fn test_app_settings_validate_link_handlers() {
+        let mut settings = AppSettings::default();
+        settings.link_handlers = BTreeMap::from([
+            (
+                "HTTPS".to_string(),
+                LinkHandler::Browser {
+                    app: "Firefox".to_string(),
+                },
+            ),
+            (
+                "http".to_string(),
+                LinkHandler::Browser {
+                    app: " ".to_string(),
+                },
+            ),
+            ("file".to_string(), LinkHandler::Preview),
+            ("not a scheme".to_string(), LinkHandler::Default),
+        ]);
+        settings.validate();
+        assert_eq!(
+            settings.link_handlers,
+            BTreeMap::from([
+                ("file".to_string(), LinkHandler::Preview),
+                (
+                    "https".to_string(),
+                    LinkHandler::Browser {
+                        app: "Firefox".to_string()
+                    }
+                ),
+            ])
+        );
+    }
+
     #[test]
     fn test_app_settings_serialization() {
         let settings = AppSettings {
@@ -323,8 +1161,110 @@ mod tests {
             global_shortcut: "CommandOrControl+T".to_string(),
             shortcut_enabled: false,
             pin_shortcut: "CommandOrControl+P".to_string(),
-            onboarding_complete: true,
+            onboarding: OnboardingState {
+                shortcut_set: true,
+                shell_integration_installed: false,
+                permissions_granted: true,
+                completed: false,
+            },
             pinned: true,
+            terminal_notifications: false,
+            kitty_keyboard_protocol: true,
+            unicode_width_mode: UnicodeWidthMode {
+                ambiguous: crate::cell_width::AmbiguousWidth::Wide,
+                emoji_vs16_wide: true,
+            },
+            mouse_reporting: MouseReporting::ForceOff,
+            mouse_shift_override: false,
+            usage_stats_enabled: true,
+            accessibility_verbosity: AccessibilityVerbosity::Full,
+            reduce_motion: Some(true),
+            increase_contrast: Some(false),
+            title_template: "{process} — {cwd_basename}".to_string(),
+            profiles: vec![Profile {
+                name: "work".to_string(),
+                theme: Some("solarized".to_string()),
+                cwd: Some("~/work".to_string()),
+                color: Some(LabelColor::Blue),
+                ..Default::default()
+            }],
+            directory_profiles: vec![DirectoryProfile {
+                pattern: "~/work/**".to_string(),
+                profile: "work".to_string(),
+            }],
+            idle_suspend: IdleSuspendMode::Stop,
+            idle_suspend_minutes: 30,
+            caffeinate_during_commands: true,
+            caffeinate_commands: vec!["rsync".to_string()],
+            inactivity_lock: true,
+            inactivity_lock_minutes: 2,
+            redact_secrets: false,
+            redaction_patterns: vec![r"corp-\d+".to_string()],
+            locale: Locale::Ja,
+            wheel_scroll_multiplier: 3,
+            wheel_alternate_scroll: false,
+            answerback: "microterm".to_string(),
+            primary_device_attributes: "62;22".to_string(),
+            secondary_device_attributes: "1;10;0".to_string(),
+            status_server_enabled: true,
+            status_server_port: 9100,
+            window_size_presets: vec![WindowSizePreset::Grid {
+                name: "wide".to_string(),
+                columns: 160,
+                rows: 40,
+            }],
+            status_strip_enabled: true,
+            output_frame_interval_ms: 16,
+            output_rate_limit_kib: 1024,
+            shell: Some(ShellConfig {
+                path: "/opt/homebrew/bin/fish".to_string(),
+                args: vec!["-l".to_string()],
+            }),
+            env: BTreeMap::from([("GOPATH".to_string(), "/Users/me/go".to_string())]),
+            link_patterns: vec![LinkPattern {
+                pattern: r"\bOPS-\d+\b".to_string(),
+                url: "https://jira.example.com/browse/$0".to_string(),
+            }],
+            link_handlers: BTreeMap::from([("file".to_string(), LinkHandler::Preview)]),
+            activity_idle_seconds: 30,
+            bell_notifications: true,
+            keep_sessions_alive: true,
+            cd_on_folder_drop: true,
+            safe_delete: true,
+            menu_widgets: vec![MenuWidget {
+                name: "cpu".to_string(),
+                cmd: "/usr/local/bin/cpu.sh".to_string(),
+                args: Vec::new(),
+                interval_seconds: 10,
+                placement: widgets::WidgetPlacement::Title,
+            }],
+            mosh_hosts: vec!["prod".to_string()],
+            quiet_hours: Some(QuietHours {
+                start: "22:00".to_string(),
+                end: "08:00".to_string(),
+            }),
+            session_logging: true,
+            session_log_format: TranscriptFormat::Raw,
+            on_show_command: Some("spotify pause".to_string()),
+            on_hide_command: None,
+            shortcut_passthrough: BTreeMap::from([("cmd+k".to_string(), vec!["hx".to_string()])]),
+            presentation_mode: PresentationMode::Auto,
+            presenting: Some(PresentationBackup {
+                font_size: 13,
+                increase_contrast: None,
+                terminal_notifications: true,
+                bell_notifications: false,
+                pinned: false,
+            }),
+            clipboard_access: ClipboardAccess::Allow,
+            workspaces: vec![Workspace {
+                name: "api".to_string(),
+                sessions: vec![crate::workspace::WorkspaceSession {
+                    cwd: Some("~/api".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
         };
 
         let json = serde_json::to_string(&settings).unwrap();
@@ -335,11 +1275,112 @@ mod tests {
         assert_eq!(deserialized.global_shortcut, settings.global_shortcut);
         assert_eq!(deserialized.shortcut_enabled, settings.shortcut_enabled);
         assert_eq!(deserialized.pin_shortcut, settings.pin_shortcut);
+        assert_eq!(deserialized.onboarding, settings.onboarding);
+        assert_eq!(deserialized.pinned, settings.pinned);
+        assert_eq!(deserialized.unicode_width_mode, settings.unicode_width_mode);
+        assert_eq!(deserialized.mouse_policy(), settings.mouse_policy());
         assert_eq!(
-            deserialized.onboarding_complete,
-            settings.onboarding_complete
+            deserialized.usage_stats_enabled,
+            settings.usage_stats_enabled
         );
-        assert_eq!(deserialized.pinned, settings.pinned);
+        assert_eq!(
+            deserialized.accessibility_verbosity,
+            settings.accessibility_verbosity
+        );
+        assert_eq!(deserialized.reduce_motion, settings.reduce_motion);
+        assert_eq!(deserialized.increase_contrast, settings.increase_contrast);
+        assert_eq!(deserialized.title_template, settings.title_template);
+        assert_eq!(deserialized.profiles, settings.profiles);
+        assert_eq!(deserialized.directory_profiles, settings.directory_profiles);
+        assert_eq!(deserialized.idle_suspend, settings.idle_suspend);
+        assert_eq!(
+            deserialized.idle_suspend_minutes,
+            settings.idle_suspend_minutes
+        );
+        assert_eq!(
+            deserialized.caffeinate_during_commands,
+            settings.caffeinate_during_commands
+        );
+        assert_eq!(
+            deserialized.caffeinate_commands,
+            settings.caffeinate_commands
+        );
+        assert_eq!(deserialized.inactivity_lock, settings.inactivity_lock);
+        assert_eq!(
+            deserialized.inactivity_lock_minutes,
+            settings.inactivity_lock_minutes
+        );
+        assert_eq!(deserialized.redact_secrets, settings.redact_secrets);
+        assert_eq!(deserialized.redaction_patterns, settings.redaction_patterns);
+        assert_eq!(deserialized.locale, settings.locale);
+        assert_eq!(
+            deserialized.wheel_scroll_multiplier,
+            settings.wheel_scroll_multiplier
+        );
+        assert_eq!(
+            deserialized.wheel_alternate_scroll,
+            settings.wheel_alternate_scroll
+        );
+        assert_eq!(deserialized.answerback, settings.answerback);
+        assert_eq!(
+            deserialized.primary_device_attributes,
+            settings.primary_device_attributes
+        );
+        assert_eq!(
+            deserialized.secondary_device_attributes,
+            settings.secondary_device_attributes
+        );
+        assert_eq!(
+            deserialized.status_server_enabled,
+            settings.status_server_enabled
+        );
+        assert_eq!(deserialized.status_server_port, settings.status_server_port);
+        assert_eq!(
+            deserialized.window_size_presets,
+            settings.window_size_presets
+        );
+        assert_eq!(
+            deserialized.status_strip_enabled,
+            settings.status_strip_enabled
+        );
+        assert_eq!(
+            deserialized.output_frame_interval_ms,
+            settings.output_frame_interval_ms
+        );
+        assert_eq!(
+            deserialized.output_rate_limit_kib,
+            settings.output_rate_limit_kib
+        );
+        assert_eq!(deserialized.shell, settings.shell);
+        assert_eq!(deserialized.env, settings.env);
+        assert_eq!(deserialized.link_patterns, settings.link_patterns);
+        assert_eq!(deserialized.link_handlers, settings.link_handlers);
+        assert_eq!(
+            deserialized.activity_idle_seconds,
+            settings.activity_idle_seconds
+        );
+        assert_eq!(deserialized.bell_notifications, settings.bell_notifications);
+        assert_eq!(
+            deserialized.keep_sessions_alive,
+            settings.keep_sessions_alive
+        );
+        assert_eq!(deserialized.cd_on_folder_drop, settings.cd_on_folder_drop);
+        assert_eq!(deserialized.safe_delete, settings.safe_delete);
+        assert_eq!(deserialized.menu_widgets, settings.menu_widgets);
+        assert_eq!(deserialized.mosh_hosts, settings.mosh_hosts);
+        assert_eq!(deserialized.quiet_hours, settings.quiet_hours);
+        assert_eq!(deserialized.session_logging, settings.session_logging);
+        assert_eq!(deserialized.session_log_format, settings.session_log_format);
+        assert_eq!(deserialized.on_show_command, settings.on_show_command);
+        assert_eq!(deserialized.on_hide_command, settings.on_hide_command);
+        assert_eq!(
+            deserialized.shortcut_passthrough,
+            settings.shortcut_passthrough
+        );
+        assert_eq!(deserialized.presentation_mode, settings.presentation_mode);
+        assert_eq!(deserialized.presenting, settings.presenting);
+        assert_eq!(deserialized.clipboard_access, settings.clipboard_access);
+        assert_eq!(deserialized.workspaces, settings.workspaces);
     }
 
     #[test]
@@ -464,15 +1505,59 @@ mod tests {
         let (manager, _temp_dir) = create_temp_manager();
 
         let settings = manager.get();
-        assert!(!settings.onboarding_complete);
+        assert!(!settings.onboarding.completed);
 
         manager.set_onboarding_complete(true);
         let settings = manager.get();
-        assert!(settings.onboarding_complete);
+        assert!(settings.onboarding.completed);
 
         manager.set_onboarding_complete(false);
         let settings = manager.get();
-        assert!(!settings.onboarding_complete);
+        assert!(!settings.onboarding.completed);
+    }
+
+    #[test]
+    fn test_manager_set_onboarding_step() {
+        let (manager, temp_dir) = create_temp_manager();
+
+        let state = manager.set_onboarding_step(OnboardingStep::ShortcutSet, true);
+        assert!(state.shortcut_set);
+        assert_eq!(
+            state.next_step(),
+            Some(OnboardingStep::ShellIntegrationInstalled)
+        );
+
+        // Progress survives a restart
+        let reloaded = SettingsManager::new(temp_dir.path().join("settings.json"));
+        assert!(reloaded.get().onboarding.shortcut_set);
+    }
+
+    #[test]
+    fn test_onboarding_next_step() {
+        let mut state = OnboardingState::default();
+        assert_eq!(state.next_step(), Some(OnboardingStep::ShortcutSet));
+        for step in OnboardingStep::ALL {
+            state.set_step(step, true);
+        }
+        assert_eq!(state.next_step(), None);
+
+        state.set_step(OnboardingStep::PermissionsGranted, false);
+        assert_eq!(state.next_step(), Some(OnboardingStep::PermissionsGranted));
+        state.completed = true;
+        assert_eq!(state.next_step(), None);
+    }
+
+    #[test]
+    fn test_legacy_onboarding_complete_migrated() {
+        let settings: AppSettings =
+            serde_json::from_str(r#"{"opacity": 0.9, "onboarding_complete": true}"#).unwrap();
+        assert!(settings.onboarding.completed);
+        assert!(!settings.onboarding.shortcut_set);
+
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(!json.contains("onboarding_complete"));
+        let reloaded: AppSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded.onboarding, settings.onboarding);
     }
 
     #[test]
@@ -559,6 +1644,25 @@ mod tests {
         assert_eq!(settings.font_size, 13); // default
     }
 
+    #[test]
+    fn test_manager_restores_backup_after_interrupted_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let settings_path = temp_dir.path().join("settings.json");
+        {
+            let manager = SettingsManager::new(settings_path.clone());
+            manager.set_font_size(15);
+            manager.set_opacity(0.7);
+        }
+
+        // A crash mid-write by an older version left truncated JSON
+        fs::write(&settings_path, "{\"opacity\": 0.").unwrap();
+
+        // The backup holds the settings from before the last save
+        let settings = SettingsManager::new(settings_path).get();
+        assert_eq!(settings.font_size, 15);
+        assert_eq!(settings.opacity, 0.9);
+    }
+
     #[test]
     fn test_manager_load_settings_with_invalid_values() {
         let temp_dir = TempDir::new().unwrap();
@@ -589,7 +1693,7 @@ mod tests {
         assert_eq!(settings.opacity, 0.8);
         assert_eq!(settings.font_size, 14);
         assert!(settings.pinned);
-        assert!(settings.onboarding_complete);
+        assert!(settings.onboarding.completed);
 
         // Update all at once
         let mut new_settings = AppSettings::default();
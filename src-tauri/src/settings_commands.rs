@@ -1,8 +1,16 @@
 //! Settings management commands
 
-use crate::settings::{AppSettings, SettingsManager};
+use crate::accessibility;
+use crate::events::{self, PinStateUpdated};
+use crate::i18n;
+use crate::links;
+use crate::presentation;
+use crate::pty::PtyManager;
+use crate::redact;
+use crate::settings::{AppSettings, OnboardingState, OnboardingStep, SettingsManager};
+use crate::status_server::StatusServer;
 use std::sync::Arc;
-use tauri::{command, AppHandle, Emitter, State};
+use tauri::{command, AppHandle, Manager, State};
 
 /// Get current settings
 #[command]
@@ -13,10 +21,25 @@ pub fn get_settings(settings_manager: State<Arc<SettingsManager>>) -> Result<App
 /// Update all settings
 #[command]
 pub fn update_settings(
+    app: AppHandle,
     settings_manager: State<Arc<SettingsManager>>,
+    pty_manager: State<Arc<PtyManager>>,
     settings: AppSettings,
 ) -> Result<(), String> {
+    let template_changed = settings_manager.get().title_template != settings.title_template;
+    redact::configure(&settings);
+    i18n::configure(&settings);
+    links::configure(&settings);
     settings_manager.update(settings);
+    accessibility::refresh_display_preferences(&app);
+    presentation::refresh(&app);
+    if let Some(status_server) = app.try_state::<Arc<StatusServer>>() {
+        status_server.configure(&app, &settings_manager.get());
+    }
+    crate::configure_status_strip(&app, settings_manager.get().status_strip_enabled);
+    if template_changed {
+        pty_manager.refresh_titles(&app);
+    }
     Ok(())
 }
 
@@ -71,7 +94,7 @@ pub fn set_pinned(
     }
 
     // Emit event to frontend for UI update
-    app.emit("pin-state-updated", serde_json::json!({ "pinned": pinned }))
+    events::emit(&app, PinStateUpdated { pinned })
         .map_err(|e| format!("Failed to emit pin-state-updated: {}", e))?;
 
     Ok(())
@@ -93,6 +116,24 @@ pub fn set_onboarding_complete(
     Ok(())
 }
 
+/// Get onboarding progress
+#[command]
+pub fn get_onboarding_state(
+    settings_manager: State<Arc<SettingsManager>>,
+) -> Result<OnboardingState, String> {
+    Ok(settings_manager.get().onboarding)
+}
+
+/// Mark an onboarding step done or not done
+#[command]
+pub fn set_onboarding_step(
+    settings_manager: State<Arc<SettingsManager>>,
+    step: OnboardingStep,
+    done: bool,
+) -> Result<OnboardingState, String> {
+    Ok(settings_manager.set_onboarding_step(step, done))
+}
+
 // Validation helper functions for testing
 #[cfg(test)]
 mod validation {
@@ -0,0 +1,391 @@
+//! Shell integration prompt marks (OSC 133)
+//!
+//! Turns the prompt marks sent by shells with integration scripts into
+//! `prompt-start`, `command-start` and `command-end` events, and tracks which
+//! integration sequences a session has sent for `check_shell_integration`.
+
+use crate::events;
+use crate::tab_title;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+/// An OSC 133 mark, as sent by iTerm2, VS Code, WezTerm, kitty and fish 4
+/// integration scripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptMark {
+    /// `A`
+    PromptStart,
+    /// `B`: command input starts
+    PromptEnd,
+    /// `C`: command output starts
+    CommandExecuted,
+    /// `D` with the exit code, when given
+    CommandFinished { exit_code: Option<i32> },
+}
+
+/// Parse the data of an OSC 133 sequence; options after the mark (such as
+/// `A;cl=m` or `C;cmdline=...`) are ignored
+pub fn parse_mark(data: &str) -> Option<PromptMark> {
+    let mut params = data.split(';');
+    let mark = match params.next()? {
+        "A" => PromptMark::PromptStart,
+        "B" => PromptMark::PromptEnd,
+        "C" => PromptMark::CommandExecuted,
+        "D" => PromptMark::CommandFinished {
+            exit_code: params.next().and_then(|code| code.trim().parse().ok()),
+        },
+        _ => return None,
+    };
+    Some(mark)
+}
+
+/// Command line given in the data of a `C` mark (`cmdline=` or kitty's
+/// percent-encoded `cmdline_url=`), if any
+pub fn parse_command_line(data: &str) -> Option<String> {
+    let mut params = data.split(';');
+    if params.next()? != "C" {
+        return None;
+    }
+    // The command line may itself contain semicolons, so take the rest
+    let options = data.get(2..)?;
+    let command = if let Some(url) = options.strip_prefix("cmdline_url=") {
+        tab_title::percent_decode(url)?
+    } else {
+        options.strip_prefix("cmdline=")?.to_string()
+    };
+    let command = command.trim();
+    (!command.is_empty()).then(|| command.to_string())
+}
+
+/// The last finished command of a session, for the "took 2m13s" badge and
+/// `get_last_command_stats`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandStats {
+    /// Command line (with secrets redacted), when the shell reported it
+    pub command: Option<String>,
+    /// `None` when the shell didn't report one
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    /// When the command finished, in seconds since the Unix epoch
+    pub finished_at: u64,
+}
+
+/// Shell integration sequences received from a session, so
+/// `check_shell_integration` can tell which features don't work for it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IntegrationMarks {
+    /// OSC 7
+    pub cwd: bool,
+    pub prompt_start: bool,
+    pub command_executed: bool,
+    pub command_finished: bool,
+    /// A `C` mark carried the command line
+    pub command_line: bool,
+}
+
+impl IntegrationMarks {
+    pub fn record(&mut self, mark: PromptMark) {
+        match mark {
+            PromptMark::PromptStart => self.prompt_start = true,
+            PromptMark::PromptEnd => {}
+            PromptMark::CommandExecuted => self.command_executed = true,
+            PromptMark::CommandFinished { .. } => self.command_finished = true,
+        }
+    }
+
+    /// Features that don't work with the sequences received so far
+    pub fn degraded(&self) -> Vec<DegradedFeature> {
+        let mut degraded = Vec::new();
+        let mut add = |feature, reason: &str| {
+            degraded.push(DegradedFeature {
+                feature,
+                reason: reason.to_string(),
+            })
+        };
+        if !self.cwd {
+            add(
+                IntegrationFeature::CwdTracking,
+                "No working directory reports (OSC 7); the directory is read from the shell \
+                 process instead, which misses ssh sessions",
+            );
+        }
+        if !self.prompt_start {
+            add(
+                IntegrationFeature::PromptJumping,
+                "No prompt marks (OSC 133 A), so prompts can't be jumped to",
+            );
+        }
+        if !(self.command_executed && self.command_finished) {
+            add(
+                IntegrationFeature::CommandStatus,
+                "No command marks (OSC 133 C and D), so command durations and exit codes \
+                 aren't known",
+            );
+        }
+        if !self.command_line {
+            add(
+                IntegrationFeature::CommandLines,
+                if self.command_executed {
+                    "Command marks don't include the command line (cmdline=)"
+                } else {
+                    "No command marks (OSC 133 C), so finished commands aren't named"
+                },
+            );
+        }
+        degraded
+    }
+}
+
+/// A feature that relies on shell integration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrationFeature {
+    /// Tab titles, recent directories and profiles following `cd`
+    CwdTracking,
+    PromptJumping,
+    /// Command durations, exit codes and finished-command notifications
+    CommandStatus,
+    /// Command lines in `get_last_command_stats`
+    CommandLines,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DegradedFeature {
+    pub feature: IntegrationFeature,
+    pub reason: String,
+}
+
+/// Result of `check_shell_integration`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrationHealth {
+    /// OSC 7 directory reports were received
+    pub cwd_reports: bool,
+    /// OSC 133 prompt marks were received
+    pub prompt_marks: bool,
+    /// OSC 133 command start and end marks were received
+    pub command_marks: bool,
+    pub command_lines: bool,
+    /// Empty when everything works
+    pub degraded: Vec<DegradedFeature>,
+}
+
+impl From<IntegrationMarks> for IntegrationHealth {
+    fn from(marks: IntegrationMarks) -> Self {
+        Self {
+            cwd_reports: marks.cwd,
+            prompt_marks: marks.prompt_start,
+            command_marks: marks.command_executed && marks.command_finished,
+            command_lines: marks.command_line,
+            degraded: marks.degraded(),
+        }
+    }
+}
+
+/// Payload of the `prompt-start` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptStart {
+    pub session_id: String,
+}
+
+/// Payload of the `command-start` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandStart {
+    pub session_id: String,
+}
+
+/// Payload of the `command-end` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandEnd {
+    pub session_id: String,
+    /// `None` when the shell didn't report one
+    pub exit_code: Option<i32>,
+    /// Milliseconds from `command-start`
+    pub duration_ms: u64,
+}
+
+/// A mark worth telling the frontend about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptEvent {
+    PromptStart,
+    CommandStart,
+    CommandEnd {
+        exit_code: Option<i32>,
+        duration: Duration,
+    },
+}
+
+/// Per-session mark state, owned by the reader thread
+#[derive(Debug, Default)]
+pub struct PromptTracker {
+    /// When the running command's `C` mark arrived
+    executed_at: Option<Instant>,
+}
+
+impl PromptTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Event for a mark received at `now`, if any: `B` emits nothing, and a
+    /// `D` without a preceding `C` (an empty command line, or the first
+    /// prompt) isn't reported
+    pub fn handle(&mut self, mark: PromptMark, now: Instant) -> Option<PromptEvent> {
+        match mark {
+            PromptMark::PromptStart => Some(PromptEvent::PromptStart),
+            PromptMark::PromptEnd => None,
+            PromptMark::CommandExecuted => {
+                self.executed_at = Some(now);
+                Some(PromptEvent::CommandStart)
+            }
+            PromptMark::CommandFinished { exit_code } => {
+                let executed_at = self.executed_at.take()?;
+                Some(PromptEvent::CommandEnd {
+                    exit_code,
+                    duration: now.saturating_duration_since(executed_at),
+                })
+            }
+        }
+    }
+}
+
+/// Emit a session's prompt event to the session's window; the reader thread
+/// calls this once the output the mark was found in has been emitted
+pub fn emit(app: &AppHandle, window: &str, session_id: String, event: PromptEvent) {
+    let _ = match event {
+        PromptEvent::PromptStart => events::emit_to(app, window, PromptStart { session_id }),
+        PromptEvent::CommandStart => events::emit_to(app, window, CommandStart { session_id }),
+        PromptEvent::CommandEnd {
+            exit_code,
+            duration,
+        } => events::emit_to(
+            app,
+            window,
+            CommandEnd {
+                session_id,
+                exit_code,
+                duration_ms: duration.as_millis() as u64,
+            },
+        ),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mark() {
+        assert_eq!(parse_mark("A"), Some(PromptMark::PromptStart));
+        assert_eq!(parse_mark("A;cl=m;aid=1"), Some(PromptMark::PromptStart));
+        assert_eq!(parse_mark("B"), Some(PromptMark::PromptEnd));
+        assert_eq!(
+            parse_mark("C;cmdline=ls"),
+            Some(PromptMark::CommandExecuted)
+        );
+        assert_eq!(
+            parse_mark("D;2"),
+            Some(PromptMark::CommandFinished { exit_code: Some(2) })
+        );
+        assert_eq!(
+            parse_mark("D"),
+            Some(PromptMark::CommandFinished { exit_code: None })
+        );
+        assert_eq!(parse_mark("P;k=i"), None);
+        assert_eq!(parse_mark(""), None);
+    }
+
+    #[test]
+    fn test_parse_command_line() {
+        assert_eq!(
+            parse_command_line("C;cmdline=make test; echo done"),
+            Some("make test; echo done".to_string())
+        );
+        assert_eq!(
+            parse_command_line("C;cmdline_url=git%20log%20-p"),
+            Some("git log -p".to_string())
+        );
+        assert_eq!(parse_command_line("C;cmdline_url=%zz"), None);
+        assert_eq!(parse_command_line("C;cmdline=  "), None);
+        assert_eq!(parse_command_line("C"), None);
+        assert_eq!(parse_command_line("A;cmdline=ls"), None);
+    }
+
+    #[test]
+    fn test_tracker_measures_commands() {
+        let mut tracker = PromptTracker::new();
+        let start = Instant::now();
+        let finished = PromptMark::CommandFinished { exit_code: Some(1) };
+
+        // First prompt: D without a command
+        assert_eq!(tracker.handle(finished, start), None);
+        assert_eq!(
+            tracker.handle(PromptMark::PromptStart, start),
+            Some(PromptEvent::PromptStart)
+        );
+        assert_eq!(tracker.handle(PromptMark::PromptEnd, start), None);
+        assert_eq!(
+            tracker.handle(PromptMark::CommandExecuted, start),
+            Some(PromptEvent::CommandStart)
+        );
+        assert_eq!(
+            tracker.handle(finished, start + Duration::from_millis(1500)),
+            Some(PromptEvent::CommandEnd {
+                exit_code: Some(1),
+                duration: Duration::from_millis(1500),
+            })
+        );
+        // Reported once per command
+        assert_eq!(tracker.handle(finished, start), None);
+    }
+
+    fn degraded_features(marks: IntegrationMarks) -> Vec<IntegrationFeature> {
+        IntegrationHealth::from(marks)
+            .degraded
+            .into_iter()
+            .map(|degraded| degraded.feature)
+            .collect()
+    }
+
+    #[test]
+    fn test_health_without_integration() {
+        let health = IntegrationHealth::from(IntegrationMarks::default());
+        assert!(!health.cwd_reports && !health.prompt_marks && !health.command_marks);
+        assert_eq!(
+            degraded_features(IntegrationMarks::default()),
+            [
+                IntegrationFeature::CwdTracking,
+                IntegrationFeature::PromptJumping,
+                IntegrationFeature::CommandStatus,
+                IntegrationFeature::CommandLines,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_health_with_marks() {
+        let mut marks = IntegrationMarks::default();
+        for mark in [
+            PromptMark::PromptStart,
+            PromptMark::PromptEnd,
+            PromptMark::CommandExecuted,
+            PromptMark::CommandFinished { exit_code: Some(0) },
+        ] {
+            marks.record(mark);
+        }
+        // Marks without OSC 7 or command lines, like a bare zsh precmd hook
+        assert_eq!(
+            degraded_features(marks),
+            [
+                IntegrationFeature::CwdTracking,
+                IntegrationFeature::CommandLines
+            ]
+        );
+
+        marks.cwd = true;
+        marks.command_line = true;
+        let health = IntegrationHealth::from(marks);
+        assert!(health.command_marks);
+        assert!(health.degraded.is_empty());
+    }
+}
@@ -0,0 +1,692 @@
+//! SSH connection manager
+//!
+//! `list_ssh_hosts` offers the concrete `Host` entries of `~/.ssh/config`
+//! (patterns with wildcards are skipped) followed by hosts saved with
+//! `add_ssh_host`, which are kept in the session store. `connect_ssh` starts
+//! a session running `ssh` with the chosen host and emits `ssh-connected`;
+//! `ssh-disconnected` follows when the session ends, with ssh's exit code
+//! (255 when the connection failed or dropped).
+//!
+//! Hosts named in the `mosh_hosts` setting connect with mosh instead, which
+//! survives sleep, roaming and dropped Wi-Fi, when `mosh` is on the sessions'
+//! PATH; without it they fall back to ssh. `reconnect_ssh` connects the host
+//! of an ended session again the same way.
+//!
+//! Host names, users and addresses are validated rather than trusted: one
+//! starting with `-` would be taken as an ssh option.
+
+use crate::events;
+use crate::file_drop;
+use crate::pty::{build_pty_path, PtyManager, SessionOptions};
+use crate::session_store::SessionStore;
+use crate::settings::SettingsManager;
+use crate::unix_time::now_secs;
+use crate::usage_stats::UsageStatsManager;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{command, AppHandle, Manager, State, WebviewWindow};
+use tracing::{debug, info, warn};
+
+/// PTY size until the terminal attaches and resizes the session
+const DEFAULT_COLS: u16 = 80;
+const DEFAULT_ROWS: u16 = 24;
+
+/// Ended connections kept for `reconnect_ssh`
+const MAX_ENDED: usize = 20;
+
+/// How a session connects to its host
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SshTransport {
+    #[default]
+    Ssh,
+    Mosh,
+}
+
+/// A host to connect to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SshHost {
+    /// Store ID of a saved host; `None` for hosts from `~/.ssh/config`
+    pub id: Option<i64>,
+    /// `Host` alias, or the saved host's name
+    pub name: String,
+    /// Address to connect to; `None` when the alias is the address
+    pub hostname: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+}
+
+/// An open ssh session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SshConnection {
+    pub session_id: String,
+    /// Name of the host
+    pub host: String,
+    pub transport: SshTransport,
+    /// Seconds since the Unix epoch
+    pub connected_at: u64,
+}
+
+/// Payload of the `ssh-connected` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SshConnected {
+    pub session_id: String,
+    pub host: String,
+    pub transport: SshTransport,
+}
+
+/// Payload of the `ssh-disconnected` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SshDisconnected {
+    pub session_id: String,
+    pub host: String,
+    pub transport: SshTransport,
+    /// ssh's or mosh's exit code; `None` when the session was closed
+    pub exit_code: Option<i32>,
+}
+
+/// Concrete hosts of an ssh config file, in file order. `Include` and
+/// `Match` blocks are not followed.
+pub fn parse_ssh_config(text: &str) -> Vec<SshHost> {
+    let mut hosts: Vec<SshHost> = Vec::new();
+    // Indices into `hosts` of the current `Host` block's aliases
+    let mut block = 0..0;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // Keywords are case-insensitive and may be followed by `=`
+        let (keyword, value) = match line.split_once(|c: char| c.is_whitespace() || c == '=') {
+            Some((keyword, value)) => (
+                keyword.to_ascii_lowercase(),
+                value.trim_start_matches(|c: char| c.is_whitespace() || c == '='),
+            ),
+            None => continue,
+        };
+        let value = value.trim().trim_matches('"');
+        match keyword.as_str() {
+            "host" => {
+                let start = hosts.len();
+                for alias in value.split_whitespace() {
+                    let concrete = !alias.contains(['*', '?', '!']);
+                    if concrete && !hosts.iter().any(|host| host.name == alias) {
+                        hosts.push(SshHost {
+                            id: None,
+                            name: alias.to_string(),
+                            hostname: None,
+                            user: None,
+                            port: None,
+                        });
+                    }
+                }
+                block = start..hosts.len();
+            }
+            "match" => block = 0..0,
+            // The first value of each option wins, as in ssh
+            "hostname" => {
+                for host in &mut hosts[block.clone()] {
+                    host.hostname.get_or_insert_with(|| value.to_string());
+                }
+            }
+            "user" => {
+                for host in &mut hosts[block.clone()] {
+                    host.user.get_or_insert_with(|| value.to_string());
+                }
+            }
+            "port" => {
+                if let Ok(port) = value.parse() {
+                    for host in &mut hosts[block.clone()] {
+                        host.port.get_or_insert(port);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    hosts
+}
+
+/// Hosts from `~/.ssh/config`; empty if there is none
+pub fn config_hosts() -> Vec<SshHost> {
+    let Some(home) = std::env::var_os("HOME") else {
+        return Vec::new();
+    };
+    let path = PathBuf::from(home).join(".ssh").join("config");
+    match fs::read_to_string(&path) {
+        Ok(text) => parse_ssh_config(&text),
+        Err(e) => {
+            debug!(path = %path.display(), error = %e, "No ssh config");
+            Vec::new()
+        }
+    }
+}
+
+/// Check a host name, address or user before it goes on the ssh command line
+fn validate_part(kind: &str, value: &str) -> Result<(), String> {
+    if value.is_empty() {
+        return Err(format!("SSH {} is empty", kind));
+    }
+    if value.starts_with('-') || value.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(format!("Invalid SSH {}: {}", kind, value));
+    }
+    Ok(())
+}
+
+/// Port, user and address to connect to. Config hosts connect by alias so
+/// the rest of their config applies.
+fn connection_parts(host: &SshHost) -> Result<(Option<u16>, Option<&str>, &str), String> {
+    let saved = host.id.is_some();
+    let user = host.user.as_deref().filter(|_| saved);
+    if let Some(user) = user {
+        validate_part("user", user)?;
+    }
+    let target = match (&host.hostname, saved) {
+        (Some(hostname), true) => hostname,
+        _ => &host.name,
+    };
+    validate_part("host", target)?;
+    Ok((host.port.filter(|_| saved), user, target))
+}
+
+/// Command line that connects to a host
+pub fn ssh_command(host: &SshHost) -> Result<String, String> {
+    let (port, user, target) = connection_parts(host)?;
+    let mut args = vec!["ssh".to_string()];
+    if let Some(port) = port {
+        args.push("-p".to_string());
+        args.push(port.to_string());
+    }
+    if let Some(user) = user {
+        args.push("-l".to_string());
+        args.push(file_drop::shell_quote(user).ok_or("Invalid SSH user")?);
+    }
+    args.push(file_drop::shell_quote(target).ok_or("Invalid SSH host")?);
+    Ok(args.join(" "))
+}
+
+/// Command line that connects to a host with the mosh at `mosh`. mosh's own
+/// `-p` is the UDP port, so the ssh port goes in the ssh command it runs.
+pub fn mosh_command(host: &SshHost, mosh: &Path) -> Result<String, String> {
+    let (port, user, target) = connection_parts(host)?;
+    let mosh = mosh.to_str().and_then(file_drop::shell_quote);
+    let mut args = vec![mosh.ok_or("Invalid mosh path")?];
+    if let Some(port) = port {
+        args.push(format!("--ssh='ssh -p {}'", port));
+    }
+    let target = match user {
+        Some(user) => format!("{}@{}", user, target),
+        None => target.to_string(),
+    };
+    args.push(file_drop::shell_quote(&target).ok_or("Invalid SSH host")?);
+    Ok(args.join(" "))
+}
+
+/// `mosh` in the directories of a PATH
+pub fn find_mosh(path: &str) -> Option<PathBuf> {
+    path.split(':')
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| Path::new(dir).join("mosh"))
+        .find(|candidate| candidate.is_file())
+}
+
+/// `mosh` on the PATH sessions get
+fn session_mosh() -> Option<PathBuf> {
+    let home = std::env::var("HOME").unwrap_or_default();
+    find_mosh(&build_pty_path(
+        &home,
+        std::env::var("PATH").ok().as_deref(),
+    ))
+}
+
+/// Transport and command line for a connection. `mosh` is the mosh found,
+/// if any; a host that only prefers mosh falls back to ssh without it.
+fn connect_command(
+    host: &SshHost,
+    use_mosh: bool,
+    require_mosh: bool,
+    mosh: Option<&Path>,
+) -> Result<(SshTransport, String), String> {
+    match (use_mosh, mosh) {
+        (true, Some(mosh)) => Ok((SshTransport::Mosh, mosh_command(host, mosh)?)),
+        (true, None) if require_mosh => Err("mosh is not installed".to_string()),
+        (true, None) => {
+            warn!(host = %host.name, "mosh is not installed, connecting with ssh");
+            Ok((SshTransport::Ssh, ssh_command(host)?))
+        }
+        (false, _) => Ok((SshTransport::Ssh, ssh_command(host)?)),
+    }
+}
+
+/// Config hosts followed by saved ones
+fn all_hosts(store: &SessionStore) -> Result<Vec<SshHost>, String> {
+    let mut hosts = config_hosts();
+    hosts.extend(store.ssh_hosts()?);
+    Ok(hosts)
+}
+
+/// Open ssh sessions, and recently ended ones for `reconnect_ssh`
+#[derive(Default)]
+pub struct SshManager {
+    connections: Mutex<HashMap<String, SshConnection>>,
+    ended: Mutex<VecDeque<SshConnection>>,
+}
+
+impl SshManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, connection: SshConnection) {
+        self.connections
+            .lock()
+            .insert(connection.session_id.clone(), connection);
+    }
+
+    /// Forget a session, returning its connection if it was an ssh session
+    pub fn remove(&self, session_id: &str) -> Option<SshConnection> {
+        let connection = self.connections.lock().remove(session_id)?;
+        let mut ended = self.ended.lock();
+        if ended.len() == MAX_ENDED {
+            ended.pop_front();
+        }
+        ended.push_back(connection.clone());
+        Some(connection)
+    }
+
+    /// Take an ended connection to reconnect it
+    pub fn take_ended(&self, session_id: &str) -> Option<SshConnection> {
+        let mut ended = self.ended.lock();
+        let index = ended
+            .iter()
+            .position(|connection| connection.session_id == session_id)?;
+        ended.remove(index)
+    }
+
+    /// Open connections, oldest first
+    pub fn list(&self) -> Vec<SshConnection> {
+        let mut connections: Vec<SshConnection> =
+            self.connections.lock().values().cloned().collect();
+        connections.sort_by(|a, b| {
+            a.connected_at
+                .cmp(&b.connected_at)
+                .then(a.session_id.cmp(&b.session_id))
+        });
+        connections
+    }
+}
+
+/// Report a session's exit if it was an ssh session (called by the reader thread)
+pub fn session_exited(app: &AppHandle, session_id: &str, exit_code: Option<i32>) {
+    let Some(manager) = app.try_state::<Arc<SshManager>>() else {
+        return;
+    };
+    if let Some(connection) = manager.remove(session_id) {
+        info!(session_id = %session_id, host = %connection.host, ?exit_code, "SSH session ended");
+        let _ = events::emit(
+            app,
+            SshDisconnected {
+                session_id: connection.session_id,
+                host: connection.host,
+                transport: connection.transport,
+                exit_code,
+            },
+        );
+    }
+}
+
+/// Hosts from `~/.ssh/config` followed by saved hosts
+#[command]
+pub fn list_ssh_hosts(session_store: State<'_, Arc<SessionStore>>) -> Result<Vec<SshHost>, String> {
+    all_hosts(&session_store)
+}
+
+/// Save a host that isn't in `~/.ssh/config`
+#[command]
+pub fn add_ssh_host(
+    session_store: State<'_, Arc<SessionStore>>,
+    name: String,
+    hostname: String,
+    user: Option<String>,
+    port: Option<u16>,
+) -> Result<SshHost, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("SSH host name is empty".to_string());
+    }
+    let hostname = hostname.trim();
+    validate_part("host", hostname)?;
+    let user = user
+        .as_deref()
+        .map(str::trim)
+        .filter(|user| !user.is_empty());
+    if let Some(user) = user {
+        validate_part("user", user)?;
+    }
+    session_store.add_ssh_host(name, hostname, user, port)
+}
+
+/// Remove a saved host, returning false if there was none with the ID
+#[command]
+pub fn remove_ssh_host(
+    session_store: State<'_, Arc<SessionStore>>,
+    id: i64,
+) -> Result<bool, String> {
+    session_store.remove_ssh_host(id)
+}
+
+/// Start a session connecting to a host, returning the session ID
+fn connect(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    ssh_host: SshHost,
+    mosh: Option<bool>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<String, String> {
+    let use_mosh = mosh.unwrap_or_else(|| {
+        app.try_state::<Arc<SettingsManager>>()
+            .is_some_and(|settings| settings.get().mosh_hosts.contains(&ssh_host.name))
+    });
+    let mosh_path = use_mosh.then(session_mosh).flatten();
+    let (transport, command) = connect_command(
+        &ssh_host,
+        use_mosh,
+        mosh == Some(true),
+        mosh_path.as_deref(),
+    )?;
+
+    // Register the connection first, so an ssh that fails at once is still
+    // reported after `ssh-connected`
+    let connection = SshConnection {
+        session_id: uuid::Uuid::new_v4().to_string(),
+        host: ssh_host.name,
+        transport,
+        connected_at: now_secs(),
+    };
+    app.state::<Arc<SshManager>>().insert(connection.clone());
+    let _ = events::emit(
+        app,
+        SshConnected {
+            session_id: connection.session_id.clone(),
+            host: connection.host.clone(),
+            transport,
+        },
+    );
+
+    let options = SessionOptions {
+        command: Some(command),
+        session_id: Some(connection.session_id.clone()),
+        window: Some(window.label().to_string()),
+        ..Default::default()
+    };
+    let created = app.state::<Arc<PtyManager>>().create_session(
+        app.clone(),
+        cols.unwrap_or(DEFAULT_COLS),
+        rows.unwrap_or(DEFAULT_ROWS),
+        options,
+    );
+    if let Err(e) = created {
+        session_exited(app, &connection.session_id, Some(255));
+        return Err(e);
+    }
+    info!(session_id = %connection.session_id, host = %connection.host, ?transport, "SSH session started");
+    if let Some(usage_stats) = app.try_state::<Arc<UsageStatsManager>>() {
+        usage_stats.record_feature(match transport {
+            SshTransport::Ssh => "ssh",
+            SshTransport::Mosh => "mosh",
+        })?;
+    }
+    Ok(connection.session_id)
+}
+
+/// Start a session connecting to a host from `list_ssh_hosts`, returning the
+/// session ID. `mosh` overrides whether the host is in `mosh_hosts`; asking
+/// for mosh when it isn't installed fails instead of falling back to ssh.
+#[command]
+pub async fn connect_ssh(
+    app: AppHandle,
+    window: WebviewWindow,
+    session_store: State<'_, Arc<SessionStore>>,
+    host: String,
+    mosh: Option<bool>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<String, String> {
+    let ssh_host = all_hosts(&session_store)?
+        .into_iter()
+        .find(|candidate| candidate.name == host)
+        .ok_or_else(|| format!("SSH host not found: {}", host))?;
+    connect(&app, &window, ssh_host, mosh, cols, rows)
+}
+
+/// Connect the host of an ended ssh or mosh session again with the same
+/// transport, returning the new session ID
+#[command]
+pub async fn reconnect_ssh(
+    app: AppHandle,
+    window: WebviewWindow,
+    ssh_manager: State<'_, Arc<SshManager>>,
+    session_store: State<'_, Arc<SessionStore>>,
+    session_id: String,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<String, String> {
+    let ended = ssh_manager
+        .take_ended(&session_id)
+        .ok_or_else(|| format!("No ended SSH session: {}", session_id))?;
+    let ssh_host = all_hosts(&session_store)?
+        .into_iter()
+        .find(|candidate| candidate.name == ended.host)
+        .ok_or_else(|| format!("SSH host not found: {}", ended.host))?;
+    let mosh = ended.transport == SshTransport::Mosh;
+    connect(&app, &window, ssh_host, Some(mosh), cols, rows)
+}
+
+/// Path of the mosh sessions would run, if it's installed
+#[command]
+pub fn check_mosh() -> Option<String> {
+    session_mosh().map(|path| path.to_string_lossy().into_owned())
+}
+
+/// Open ssh sessions, oldest first
+#[command]
+pub fn list_ssh_connections(
+    ssh_manager: State<'_, Arc<SshManager>>,
+) -> Result<Vec<SshConnection>, String> {
+    Ok(ssh_manager.list())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(name: &str) -> SshHost {
+        SshHost {
+            id: None,
+            name: name.to_string(),
+            hostname: None,
+            user: None,
+            port: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_ssh_config() {
+        let config = "\
+# Work
+Host prod prod-db
+    HostName 10.0.0.5
+    User deploy
+    Port 2222
+
+Host *.internal !bastion
+    User ops
+
+host=staging
+  hostname = \"staging.example.com\"
+  User first
+  User second
+
+Match host foo
+  User ignored
+
+Host prod
+  User other
+";
+        assert_eq!(
+            parse_ssh_config(config),
+            [
+                SshHost {
+                    hostname: Some("10.0.0.5".to_string()),
+                    user: Some("deploy".to_string()),
+                    port: Some(2222),
+                    ..host("prod")
+                },
+                SshHost {
+                    hostname: Some("10.0.0.5".to_string()),
+                    user: Some("deploy".to_string()),
+                    port: Some(2222),
+                    ..host("prod-db")
+                },
+                SshHost {
+                    hostname: Some("staging.example.com".to_string()),
+                    user: Some("first".to_string()),
+                    ..host("staging")
+                },
+            ]
+        );
+        assert!(parse_ssh_config("").is_empty());
+    }
+
+    #[test]
+    fn test_ssh_command() {
+        // Config hosts connect by alias
+        let config_host = SshHost {
+            hostname: Some("10.0.0.5".to_string()),
+            user: Some("deploy".to_string()),
+            port: Some(2222),
+            ..host("prod")
+        };
+        assert_eq!(ssh_command(&config_host).unwrap(), "ssh 'prod'");
+
+        let saved = SshHost {
+            id: Some(1),
+            ..config_host
+        };
+        assert_eq!(
+            ssh_command(&saved).unwrap(),
+            "ssh -p 2222 -l 'deploy' '10.0.0.5'"
+        );
+
+        assert!(ssh_command(&host("-oProxyCommand=touch /tmp/x")).is_err());
+        assert!(ssh_command(&host("a b")).is_err());
+        let bad_user = SshHost {
+            id: Some(2),
+            user: Some("-x".to_string()),
+            ..host("box")
+        };
+        assert!(ssh_command(&bad_user).is_err());
+    }
+
+    #[test]
+    fn test_connections() {
+        let manager = SshManager::new();
+        for (id, at) in [("b", 2), ("a", 1)] {
+            manager.insert(SshConnection {
+                session_id: id.to_string(),
+                host: "prod".to_string(),
+                transport: SshTransport::Ssh,
+                connected_at: at,
+            });
+        }
+        let ids: Vec<String> = manager.list().into_iter().map(|c| c.session_id).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(manager.remove("a").unwrap().host, "prod");
+        assert!(manager.remove("a").is_none());
+        assert_eq!(manager.list().len(), 1);
+
+        // Ended connections can be taken once to reconnect
+        assert!(manager.take_ended("b").is_none());
+        assert_eq!(manager.take_ended("a").unwrap().host, "prod");
+        assert!(manager.take_ended("a").is_none());
+    }
+
+    #[test]
+    fn test_ended_connections_are_capped() {
+        let manager = SshManager::new();
+        for i in 0..=MAX_ENDED {
+            manager.insert(SshConnection {
+                session_id: i.to_string(),
+                host: "prod".to_string(),
+                transport: SshTransport::Mosh,
+                connected_at: 0,
+            });
+            manager.remove(&i.to_string());
+        }
+        assert!(manager.take_ended("0").is_none());
+        let last = manager.take_ended(&MAX_ENDED.to_string()).unwrap();
+        assert_eq!(last.transport, SshTransport::Mosh);
+    }
+
+    #[test]
+    fn test_mosh_command() {
+        let mosh = Path::new("/opt/homebrew/bin/mosh");
+        assert_eq!(
+            mosh_command(&host("prod"), mosh).unwrap(),
+            "'/opt/homebrew/bin/mosh' 'prod'"
+        );
+        let saved = SshHost {
+            id: Some(1),
+            hostname: Some("10.0.0.5".to_string()),
+            user: Some("deploy".to_string()),
+            port: Some(2222),
+            ..host("prod")
+        };
+        assert_eq!(
+            mosh_command(&saved, mosh).unwrap(),
+            "'/opt/homebrew/bin/mosh' --ssh='ssh -p 2222' 'deploy@10.0.0.5'"
+        );
+        assert!(mosh_command(&host("-x"), mosh).is_err());
+    }
+
+    #[test]
+    fn test_connect_command_falls_back_to_ssh() {
+        let mosh = Path::new("/usr/bin/mosh");
+        let prod = host("prod");
+        assert_eq!(
+            connect_command(&prod, true, false, Some(mosh)).unwrap(),
+            (SshTransport::Mosh, "'/usr/bin/mosh' 'prod'".to_string())
+        );
+        assert_eq!(
+            connect_command(&prod, true, false, None).unwrap(),
+            (SshTransport::Ssh, "ssh 'prod'".to_string())
+        );
+        assert!(connect_command(&prod, true, true, None).is_err());
+        assert_eq!(
+            connect_command(&prod, false, false, Some(mosh)).unwrap().0,
+            SshTransport::Ssh
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_mosh() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let bin = dir.path().join("bin");
+        fs::create_dir(&bin).unwrap();
+        let mosh = bin.join("mosh");
+        fs::write(&mosh, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&mosh, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let path = format!("{}::{}", dir.path().display(), bin.display());
+        assert_eq!(find_mosh(&path), Some(mosh));
+        assert_eq!(find_mosh(&dir.path().display().to_string()), None);
+    }
+}
@@ -0,0 +1,365 @@
+//! Localhost status endpoint
+//!
+//! With the `status_server_enabled` setting on, a small HTTP server on
+//! `127.0.0.1:<status_server_port>` lets personal dashboards and scripts check
+//! that the app and its background jobs are alive:
+//!
+//! - `GET /healthz`: version, uptime and counts
+//! - `GET /sessions`: live sessions and jobs
+//! - `GET /metrics`: internal metrics in the Prometheus text format
+//!
+//! Every request needs an `Authorization: Bearer <token>` header. The token is
+//! generated on first start and kept in `status-token` in the app data
+//! directory, readable only by the user. While the app is locked, `/sessions`
+//! answers 423 so the endpoint doesn't reveal what the sessions are doing.
+
+use crate::jobs::{JobManager, JobStatus};
+use crate::lock::LockManager;
+use crate::metrics;
+use crate::pty::PtyManager;
+use crate::settings::AppSettings;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tracing::{debug, info, warn};
+
+/// Default value of the `status_server_port` setting
+pub const DEFAULT_STATUS_SERVER_PORT: u16 = 47615;
+
+/// File name of the token in the app data directory
+const TOKEN_FILE: &str = "status-token";
+
+/// How often the listener checks whether it should stop
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long a client may take to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Requests with a larger head are rejected
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// An HTTP response
+#[derive(Debug, PartialEq)]
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, json!({ "error": message }))
+    }
+
+    fn to_http(&self) -> String {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            423 => "Locked",
+            _ => "Internal Server Error",
+        };
+        let authenticate = if self.status == 401 {
+            "WWW-Authenticate: Bearer\r\n"
+        } else {
+            ""
+        };
+        format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            self.content_type,
+            self.body.len(),
+            authenticate,
+            self.body
+        )
+    }
+}
+
+/// The status endpoint, started and stopped as the settings change
+pub struct StatusServer {
+    token_path: PathBuf,
+    started_at: Instant,
+    /// Port and stop flag of the running listener
+    running: Mutex<Option<(u16, Arc<AtomicBool>)>>,
+}
+
+impl StatusServer {
+    pub fn new(app_data_dir: &Path) -> Self {
+        Self {
+            token_path: app_data_dir.join(TOKEN_FILE),
+            started_at: Instant::now(),
+            running: Mutex::new(None),
+        }
+    }
+
+    /// Start, stop or move the listener to match the settings
+    pub fn configure(&self, app: &AppHandle, settings: &AppSettings) {
+        let wanted = settings
+            .status_server_enabled
+            .then_some(settings.status_server_port);
+        let mut running = self.running.lock();
+        if running.as_ref().map(|(port, _)| *port) == wanted {
+            return;
+        }
+        if let Some((port, stop)) = running.take() {
+            stop.store(true, Ordering::SeqCst);
+            info!(port, "Status endpoint stopped");
+        }
+        if let Some(port) = wanted {
+            match self.start(app.clone(), port) {
+                Ok(stop) => *running = Some((port, stop)),
+                Err(e) => warn!("{}", e),
+            }
+        }
+    }
+
+    fn start(&self, app: AppHandle, port: u16) -> Result<Arc<AtomicBool>, String> {
+        let token = load_or_create_token(&self.token_path)?;
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .map_err(|e| format!("Failed to start status endpoint on port {}: {}", port, e))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure status endpoint: {}", e))?;
+        info!(port, token_file = %self.token_path.display(), "Status endpoint started");
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = stop.clone();
+        let started_at = self.started_at;
+        thread::spawn(move || {
+            while !stop_flag.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        if let Err(e) = handle_connection(&app, stream, &token, started_at) {
+                            debug!("Status request failed: {}", e);
+                        }
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                    Err(e) => {
+                        warn!("Status endpoint accept failed: {}", e);
+                        thread::sleep(POLL_INTERVAL);
+                    }
+                }
+            }
+        });
+        Ok(stop)
+    }
+}
+
+/// Read the token, creating it (mode 0600) on first use
+fn load_or_create_token(path: &Path) -> Result<String, String> {
+    if let Ok(token) = std::fs::read_to_string(path) {
+        let token = token.trim();
+        if !token.is_empty() {
+            return Ok(token.to_string());
+        }
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(format!("{}\n", token).as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(token)
+}
+
+/// Compare tokens in constant time
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Path of an authorized GET request, or the error response
+fn authorize<'a>(head: &'a str, token: &str) -> Result<&'a str, Response> {
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Err(Response::error(400, "malformed request"));
+    };
+    let authorized = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+        .and_then(|(_, value)| value.trim().strip_prefix("Bearer "))
+        .is_some_and(|given| tokens_match(given.trim(), token));
+    if !authorized {
+        return Err(Response::error(401, "missing or invalid token"));
+    }
+    if method != "GET" {
+        return Err(Response::error(405, "only GET is supported"));
+    }
+    Ok(target.split('?').next().unwrap_or(target))
+}
+
+fn route(app: &AppHandle, path: &str, started_at: Instant) -> Response {
+    let sessions = app
+        .try_state::<Arc<PtyManager>>()
+        .map(|pty_manager| pty_manager.list_sessions())
+        .unwrap_or_default();
+    let jobs = app
+        .try_state::<Arc<JobManager>>()
+        .map(|job_manager| job_manager.list())
+        .unwrap_or_default();
+    match path {
+        "/healthz" => Response::json(
+            200,
+            json!({
+                "status": "ok",
+                "version": env!("CARGO_PKG_VERSION"),
+                "uptime_secs": started_at.elapsed().as_secs(),
+                "sessions": sessions.len(),
+                "running_jobs": jobs
+                    .iter()
+                    .filter(|job| job.status == JobStatus::Running)
+                    .count(),
+            }),
+        ),
+        "/sessions" => {
+            let locked = app
+                .try_state::<Arc<LockManager>>()
+                .is_some_and(|lock_manager| lock_manager.is_locked());
+            if locked {
+                return Response::error(423, "the app is locked");
+            }
+            Response::json(200, json!({ "sessions": sessions, "jobs": jobs }))
+        }
+        "/metrics" => Response {
+            status: 200,
+            content_type: "text/plain; version=0.0.4",
+            body: metrics::snapshot(sessions.len()).to_prometheus(),
+        },
+        _ => Response::error(404, "not found"),
+    }
+}
+
+/// Read the request head (up to the blank line)
+fn read_head(stream: &mut TcpStream) -> Result<String, String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_SIZE {
+            return Err("request too large".to_string());
+        }
+        let n = stream.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+fn handle_connection(
+    app: &AppHandle,
+    mut stream: TcpStream,
+    token: &str,
+    started_at: Instant,
+) -> Result<(), String> {
+    // Accepted sockets inherit non-blocking mode on macOS
+    stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(REQUEST_TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    let response = match read_head(&mut stream) {
+        Ok(head) => match authorize(&head, token) {
+            Ok(path) => route(app, path, started_at),
+            Err(response) => response,
+        },
+        Err(e) => Response::error(400, &e),
+    };
+    stream
+        .write_all(response.to_http().as_bytes())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn request(path: &str, auth: Option<&str>) -> String {
+        let mut head = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n", path);
+        if let Some(auth) = auth {
+            head.push_str(&format!("Authorization: {}\r\n", auth));
+        }
+        head + "\r\n"
+    }
+
+    #[test]
+    fn test_authorize() {
+        let head = request("/healthz?verbose=1", Some("Bearer secret"));
+        assert_eq!(authorize(&head, "secret"), Ok("/healthz"));
+
+        let head = request("/healthz", Some("bearer-ish secret"));
+        assert_eq!(authorize(&head, "secret").unwrap_err().status, 401);
+        let head = request("/healthz", Some("Bearer secre"));
+        assert_eq!(authorize(&head, "secret").unwrap_err().status, 401);
+        let head = request("/healthz", None);
+        assert_eq!(authorize(&head, "secret").unwrap_err().status, 401);
+
+        let head = "POST /healthz HTTP/1.1\r\nauthorization: Bearer secret\r\n\r\n";
+        assert_eq!(authorize(head, "secret").unwrap_err().status, 405);
+        assert_eq!(authorize("\r\n\r\n", "secret").unwrap_err().status, 400);
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("abc", "abc"));
+        assert!(!tokens_match("abd", "abc"));
+        assert!(!tokens_match("ab", "abc"));
+        assert!(!tokens_match("", "abc"));
+    }
+
+    #[test]
+    fn test_load_or_create_token() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested").join(TOKEN_FILE);
+        let token = load_or_create_token(&path).unwrap();
+        assert_eq!(token.len(), 32);
+        assert_eq!(load_or_create_token(&path).unwrap(), token);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_response_to_http() {
+        let http = Response::error(401, "missing or invalid token").to_http();
+        assert!(http.starts_with("HTTP/1.1 401 Unauthorized\r\n"));
+        assert!(http.contains("WWW-Authenticate: Bearer\r\n"));
+        assert!(http.ends_with("\r\n\r\n{\"error\":\"missing or invalid token\"}"));
+    }
+}
@@ -0,0 +1,187 @@
+//! Status strip window
+//!
+//! With `status_strip_enabled`, a tiny always-on-top window (`status-strip`)
+//! sits in the top-right corner of the primary screen and shows running jobs,
+//! the combined progress and the sessions that rang the bell, so they're
+//! visible while the panel is hidden. The window is created in `lib.rs`; this
+//! module keeps what it shows and emits `status-strip-updated`.
+//!
+//! Bells are only collected while the panel is hidden and are cleared when it
+//! is shown again.
+
+use crate::events;
+use crate::jobs::{JobManager, JobStatus};
+use crate::progress::{ProgressTracker, ProgressUpdate};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::{command, AppHandle, Manager};
+
+/// Label of the status strip window
+pub const STATUS_STRIP_LABEL: &str = "status-strip";
+
+/// Size of the status strip window in logical pixels
+pub const STATUS_STRIP_WIDTH: f64 = 240.0;
+pub const STATUS_STRIP_HEIGHT: f64 = 28.0;
+
+/// Gap between the strip and the screen edges
+pub const STATUS_STRIP_MARGIN: f64 = 8.0;
+
+/// What the status strip shows; payload of `status-strip-updated`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusSnapshot {
+    /// Task names of the running jobs, newest first
+    pub running_jobs: Vec<String>,
+    /// Combined progress of all sessions
+    pub progress: Option<ProgressUpdate>,
+    /// Sessions that rang the bell while the panel was hidden, oldest first
+    pub bells: Vec<String>,
+}
+
+/// Bells the status strip hasn't cleared yet
+#[derive(Default)]
+pub struct StatusStrip {
+    bells: Mutex<Vec<String>>,
+}
+
+impl StatusStrip {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a bell, returning false if the session already rang
+    pub fn ring(&self, session_id: &str) -> bool {
+        let mut bells = self.bells.lock();
+        if bells.iter().any(|id| id == session_id) {
+            return false;
+        }
+        bells.push(session_id.to_string());
+        true
+    }
+
+    /// Forget a session's bell (e.g. when it exits)
+    pub fn remove(&self, session_id: &str) -> bool {
+        let mut bells = self.bells.lock();
+        let len = bells.len();
+        bells.retain(|id| id != session_id);
+        bells.len() != len
+    }
+
+    /// Forget all bells, returning true if there were any
+    pub fn clear(&self) -> bool {
+        !std::mem::take(&mut *self.bells.lock()).is_empty()
+    }
+
+    pub fn bells(&self) -> Vec<String> {
+        self.bells.lock().clone()
+    }
+}
+
+fn panel_visible(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false)
+}
+
+/// Current state of jobs, progress and bells
+pub fn snapshot(app: &AppHandle) -> StatusSnapshot {
+    let running_jobs = app
+        .try_state::<Arc<JobManager>>()
+        .map(|jobs| {
+            jobs.list()
+                .into_iter()
+                .filter(|job| job.status == JobStatus::Running)
+                .map(|job| job.task)
+                .collect()
+        })
+        .unwrap_or_default();
+    let progress = app
+        .try_state::<Arc<ProgressTracker>>()
+        .and_then(|tracker| tracker.aggregate());
+    let bells = app
+        .try_state::<Arc<StatusStrip>>()
+        .map(|strip| strip.bells())
+        .unwrap_or_default();
+    StatusSnapshot {
+        running_jobs,
+        progress,
+        bells,
+    }
+}
+
+/// Send the current snapshot to the status strip, if it's open
+pub fn refresh(app: &AppHandle) {
+    if app.get_webview_window(STATUS_STRIP_LABEL).is_some() {
+        let _ = events::emit(app, snapshot(app));
+    }
+}
+
+/// Handle a bell from a session; only bells rung while the panel is hidden
+/// are shown
+pub fn bell(app: &AppHandle, session_id: &str) {
+    if panel_visible(app) {
+        return;
+    }
+    let rang = app
+        .try_state::<Arc<StatusStrip>>()
+        .is_some_and(|strip| strip.ring(session_id));
+    if rang {
+        refresh(app);
+    }
+}
+
+/// Forget a session's bell when it exits
+pub fn clear_session(app: &AppHandle, session_id: &str) {
+    let removed = app
+        .try_state::<Arc<StatusStrip>>()
+        .is_some_and(|strip| strip.remove(session_id));
+    if removed {
+        refresh(app);
+    }
+}
+
+/// Clear the bells once the panel is shown
+pub fn panel_shown(app: &AppHandle) {
+    let cleared = app
+        .try_state::<Arc<StatusStrip>>()
+        .is_some_and(|strip| strip.clear());
+    if cleared {
+        refresh(app);
+    }
+}
+
+#[command]
+pub fn get_status_snapshot(app: AppHandle) -> Result<StatusSnapshot, String> {
+    Ok(snapshot(&app))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bells_are_deduplicated_and_cleared() {
+        let strip = StatusStrip::new();
+        assert!(strip.ring("a"));
+        assert!(strip.ring("b"));
+        assert!(!strip.ring("a"));
+        assert_eq!(strip.bells(), ["a", "b"]);
+
+        assert!(strip.remove("a"));
+        assert!(!strip.remove("a"));
+        assert_eq!(strip.bells(), ["b"]);
+
+        assert!(strip.clear());
+        assert!(!strip.clear());
+        assert!(strip.bells().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_json() {
+        let json = serde_json::to_value(StatusSnapshot::default()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"running_jobs": [], "progress": null, "bells": []})
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! Tab titles rendered from the `title_template` setting
+//!
+//! Each session tracks the inputs a title can use: the working directory
+//! (reported with OSC 7, or read from the shell process), the title set by
+//! the program (OSC 0/2) and the foreground process name. Whenever the
+//! rendered title changes it is emitted to the frontend as
+//! `tab-title-changed`.
+
+use crate::events;
+use crate::settings::SettingsManager;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+/// Default template, matching the original cwd-based tab names
+pub const DEFAULT_TITLE_TEMPLATE: &str = "{cwd_basename}";
+
+/// Title used when a template renders to nothing
+const FALLBACK_TITLE: &str = "Terminal";
+
+/// Titles set by programs are truncated to this many characters
+const MAX_PROGRAM_TITLE_LENGTH: usize = 256;
+
+/// Minimum time between polls of the session's process and cwd
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Characters trimmed from the ends of a rendered title, so separators
+/// around empty placeholders don't linger
+const TRIM_CHARS: &[char] = &[' ', '-', '—', '–', ':', '|', '·'];
+
+/// Values available to a title template
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TitleInputs {
+    pub cwd: Option<String>,
+    /// Title set by the program with OSC 0 or OSC 2
+    pub title: Option<String>,
+    /// Name of the foreground process
+    pub process: Option<String>,
+}
+
+/// Payload of the `tab-title-changed` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabTitle {
+    pub session_id: String,
+    pub title: String,
+}
+
+/// Abbreviate the home directory as `~`
+pub(crate) fn abbreviate_home(path: &str, home: Option<&str>) -> String {
+    match home.filter(|home| !home.is_empty() && *home != "/") {
+        Some(home) if path == home => "~".to_string(),
+        Some(home) => match path.strip_prefix(home) {
+            Some(rest) if rest.starts_with('/') => format!("~{}", rest),
+            _ => path.to_string(),
+        },
+        None => path.to_string(),
+    }
+}
+
+/// Last component of a path (`/` for the root)
+fn basename(path: &str) -> &str {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return "/";
+    }
+    trimmed.rsplit('/').next().unwrap_or(trimmed)
+}
+
+/// Render a title template.
+///
+/// Supported placeholders are `{cwd}` (home abbreviated as `~`),
+/// `{cwd_basename}`, `{title}` and `{process}`; missing values render as
+/// nothing and unknown placeholders are kept as written.
+pub fn render_title(template: &str, inputs: &TitleInputs, home: Option<&str>) -> String {
+    let cwd = inputs.cwd.as_deref();
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start..];
+        let Some(end) = after.find('}') else {
+            rest = after;
+            break;
+        };
+        let value = match &after[1..end] {
+            "cwd" => cwd.map(|cwd| abbreviate_home(cwd, home)),
+            "cwd_basename" => cwd.map(|cwd| basename(cwd).to_string()),
+            "title" => inputs.title.clone(),
+            "process" => inputs.process.clone(),
+            _ => Some(after[..=end].to_string()),
+        };
+        rendered.push_str(&value.unwrap_or_default());
+        rest = &after[end + 1..];
+    }
+    rendered.push_str(rest);
+
+    let rendered = rendered.trim_matches(TRIM_CHARS);
+    if rendered.is_empty() {
+        FALLBACK_TITLE.to_string()
+    } else {
+        rendered.to_string()
+    }
+}
+
+/// Decode `%XX` escapes in a file URL path
+/// Decode `%XX` escapes; `None` if an escape or the result is invalid
+pub fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Parse the data of an OSC 7 sequence (`file://host/path`) into a path
+pub fn parse_osc7(data: &str) -> Option<String> {
+    let rest = data.strip_prefix("file://")?;
+    let path = &rest[rest.find('/')?..];
+    percent_decode(path).filter(|path| !path.is_empty())
+}
+
+/// Tracked title inputs for one session
+#[derive(Debug, Default)]
+pub struct SessionTitle {
+    inputs: TitleInputs,
+    /// Whether the shell reports its cwd with OSC 7 (then polling is skipped)
+    cwd_reported: bool,
+    last_poll: Option<Instant>,
+    /// Last emitted title
+    rendered: Option<String>,
+}
+
+impl SessionTitle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Title set by the program (OSC 0/2); an empty title clears it
+    pub fn set_program_title(&mut self, title: &str) {
+        let title: String = title
+            .chars()
+            .filter(|c| !c.is_control())
+            .take(MAX_PROGRAM_TITLE_LENGTH)
+            .collect();
+        self.inputs.title = (!title.is_empty()).then_some(title);
+    }
+
+    /// Working directory reported by the shell (OSC 7).
+    /// Returns true if it differs from the previously reported one.
+    pub fn set_reported_cwd(&mut self, cwd: String) -> bool {
+        let changed = !self.cwd_reported || self.inputs.cwd.as_deref() != Some(cwd.as_str());
+        self.cwd_reported = true;
+        self.inputs.cwd = Some(cwd);
+        changed
+    }
+
+    /// Working directory last reported with OSC 7, if the shell reports it
+    pub fn reported_cwd(&self) -> Option<&str> {
+        self.cwd_reported.then(|| self.cwd()).flatten()
+    }
+
+    /// Whether the process and cwd should be polled again
+    pub fn poll_due(&self) -> bool {
+        self.last_poll
+            .is_none_or(|last| last.elapsed() >= POLL_INTERVAL)
+    }
+
+    /// Current working directory, if known
+    pub fn cwd(&self) -> Option<&str> {
+        self.inputs.cwd.as_deref()
+    }
+
+    /// Store polled values; the polled cwd is ignored once OSC 7 was seen
+    pub fn set_polled(&mut self, process: Option<String>, cwd: Option<String>) {
+        self.last_poll = Some(Instant::now());
+        self.inputs.process = process;
+        if !self.cwd_reported && cwd.is_some() {
+            self.inputs.cwd = cwd;
+        }
+    }
+
+    /// Render the title, returning it only if it changed since the last call
+    pub fn render(&mut self, template: &str, home: Option<&str>) -> Option<String> {
+        let title = render_title(template, &self.inputs, home);
+        if self.rendered.as_deref() == Some(title.as_str()) {
+            return None;
+        }
+        self.rendered = Some(title.clone());
+        Some(title)
+    }
+
+    /// Last emitted title
+    pub fn rendered(&self) -> Option<&str> {
+        self.rendered.as_deref()
+    }
+
+    /// Forget the last emitted title so the next render is emitted
+    pub fn invalidate(&mut self) {
+        self.rendered = None;
+    }
+}
+
+/// Render a session's title and emit `tab-title-changed` if it changed
+pub fn update_title(app: &AppHandle, session_id: &str, title: &mut SessionTitle) {
+    let template = app
+        .try_state::<Arc<SettingsManager>>()
+        .map(|settings| settings.get().title_template)
+        .unwrap_or_else(|| DEFAULT_TITLE_TEMPLATE.to_string());
+    let home = std::env::var("HOME").ok();
+    if let Some(title) = title.render(&template, home.as_deref()) {
+        let _ = events::emit(
+            app,
+            TabTitle {
+                session_id: session_id.to_string(),
+                title,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_inputs(cwd: Option<&str>, title: Option<&str>, process: Option<&str>) -> TitleInputs {
+        TitleInputs {
+            cwd: cwd.map(String::from),
+            title: title.map(String::from),
+            process: process.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_render_title() {
+        let inputs = make_inputs(Some("/Users/me/code/microterm"), Some("vim"), Some("nvim"));
+        let home = Some("/Users/me");
+        assert_eq!(render_title("{cwd_basename}", &inputs, home), "microterm");
+        assert_eq!(render_title("{cwd}", &inputs, home), "~/code/microterm");
+        assert_eq!(
+            render_title("{process} — {cwd_basename}", &inputs, home),
+            "nvim — microterm"
+        );
+        assert_eq!(render_title("{title}", &inputs, home), "vim");
+        assert_eq!(render_title("{unknown} {", &inputs, home), "{unknown} {");
+    }
+
+    #[test]
+    fn test_render_title_missing_values() {
+        let inputs = make_inputs(Some("/Users/me"), None, None);
+        let home = Some("/Users/me");
+        assert_eq!(render_title("{cwd}", &inputs, home), "~");
+        assert_eq!(
+            render_title("{process} — {cwd_basename}", &inputs, home),
+            "me"
+        );
+        assert_eq!(render_title("{title}", &inputs, home), FALLBACK_TITLE);
+        assert_eq!(
+            render_title("{cwd_basename}", &make_inputs(Some("/"), None, None), home),
+            "/"
+        );
+    }
+
+    #[test]
+    fn test_abbreviate_home_requires_path_boundary() {
+        assert_eq!(
+            abbreviate_home("/Users/meg", Some("/Users/me")),
+            "/Users/meg"
+        );
+        assert_eq!(abbreviate_home("/tmp", None), "/tmp");
+    }
+
+    #[test]
+    fn test_parse_osc7() {
+        assert_eq!(
+            parse_osc7("file://host.local/Users/me/My%20Project").as_deref(),
+            Some("/Users/me/My Project")
+        );
+        assert_eq!(parse_osc7("file:///tmp").as_deref(), Some("/tmp"));
+        assert_eq!(parse_osc7("http://host/tmp"), None);
+        assert_eq!(parse_osc7("file://host/bad%zz"), None);
+    }
+
+    #[test]
+    fn test_session_title_change_detection() {
+        let mut title = SessionTitle::new();
+        title.set_polled(Some("zsh".to_string()), Some("/tmp".to_string()));
+        assert_eq!(
+            title.render("{process}: {cwd}", None).as_deref(),
+            Some("zsh: /tmp")
+        );
+        assert_eq!(title.render("{process}: {cwd}", None), None);
+
+        assert_eq!(title.reported_cwd(), None);
+
+        // OSC 7 wins over the polled cwd
+        assert!(title.set_reported_cwd("/var".to_string()));
+        assert_eq!(title.reported_cwd(), Some("/var"));
+        assert!(!title.set_reported_cwd("/var".to_string()));
+        title.set_polled(Some("zsh".to_string()), Some("/tmp".to_string()));
+        assert_eq!(
+            title.render("{process}: {cwd}", None).as_deref(),
+            Some("zsh: /var")
+        );
+
+        title.invalidate();
+        assert!(title.render("{process}: {cwd}", None).is_some());
+    }
+
+    #[test]
+    fn test_program_title_sanitized() {
+        let mut title = SessionTitle::new();
+        title.set_program_title("build\x07 log");
+        assert_eq!(title.inputs.title.as_deref(), Some("build log"));
+        title.set_program_title("");
+        assert_eq!(title.inputs.title, None);
+    }
+}
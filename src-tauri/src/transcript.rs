@@ -0,0 +1,276 @@
+//! Session transcripts
+//!
+//! `start_session_log` appends a session's output to a file in the
+//! `transcripts` directory of the app data directory, either raw (escape
+//! sequences included, so `cat` replays it) or as plain text. With
+//! `session_logging`, every new session is logged from its start in
+//! `session_log_format`. Files are only readable by the user, and output is
+//! written a line at a time through `redact`, since it can contain secrets
+//! (a token split across PTY reads is still one line). A file reaching `MAX_LOG_BYTES` is rotated: `<name>.log`
+//! becomes `<name>.1.log`, `<name>.1.log` becomes `<name>.2.log` and so on,
+//! keeping `MAX_ROTATED_FILES`.
+
+use crate::redact;
+use crate::scrollback::AnsiStripper;
+use crate::unix_time;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Directory under the app data directory transcripts are written to
+pub const TRANSCRIPTS_DIR: &str = "transcripts";
+
+/// Size at which a transcript file is rotated
+pub const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Rotated files kept per transcript
+pub const MAX_ROTATED_FILES: usize = 5;
+
+/// Output without a newline is written once this much has built up
+const MAX_PENDING_BYTES: usize = 16 * 1024;
+
+/// How output is written to a transcript
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptFormat {
+    /// Output as the terminal received it
+    Raw,
+    /// Escape sequences and control characters other than newlines and tabs
+    /// removed
+    #[default]
+    Plain,
+}
+
+/// File name of a session's transcript: the start time, so files sort by
+/// age, and the session ID with anything unusual for a file name replaced
+fn file_name(session_id: &str, started_at: u64) -> String {
+    let id: String = session_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}-{}.log", started_at, id)
+}
+
+/// Path of the `index`th rotated file of a transcript
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    path.with_extension(format!("{}.log", index))
+}
+
+fn open_append(path: &Path) -> Result<File, String> {
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .map_err(|e| format!("Failed to open transcript {}: {}", path.display(), e))
+}
+
+/// An open transcript of a session's output
+pub struct TranscriptLog {
+    path: PathBuf,
+    file: File,
+    format: TranscriptFormat,
+    written: u64,
+    max_bytes: u64,
+    stripper: AnsiStripper,
+    /// Output after the last newline, not redacted and written yet
+    pending: String,
+}
+
+impl TranscriptLog {
+    /// Start a transcript for a session in `dir`
+    pub fn start(dir: &Path, session_id: &str, format: TranscriptFormat) -> Result<Self, String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let started_at = unix_time::now_secs();
+        let path = dir.join(file_name(session_id, started_at));
+        let log = Self::open(path, format, MAX_LOG_BYTES)?;
+        info!(session_id = %session_id, path = %log.path.display(), ?format, "Transcript started");
+        Ok(log)
+    }
+
+    fn open(path: PathBuf, format: TranscriptFormat, max_bytes: u64) -> Result<Self, String> {
+        let file = open_append(&path)?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path,
+            file,
+            format,
+            written,
+            max_bytes,
+            stripper: AnsiStripper::new(),
+            pending: String::new(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn format(&self) -> TranscriptFormat {
+        self.format
+    }
+
+    /// Append a chunk of output; complete lines are written
+    pub fn push(&mut self, data: &str) {
+        match self.format {
+            TranscriptFormat::Raw => self.pending.push_str(data),
+            TranscriptFormat::Plain => self.pending.extend(
+                self.stripper
+                    .strip(data)
+                    .chars()
+                    .filter(|c| !c.is_control() || *c == '\n' || *c == '\t'),
+            ),
+        }
+        let end = match self.pending.rfind('\n') {
+            Some(newline) => newline + 1,
+            None if self.pending.len() >= MAX_PENDING_BYTES => self.pending.len(),
+            None => return,
+        };
+        let lines: String = self.pending.drain(..end).collect();
+        self.write(&lines);
+    }
+
+    /// Write redacted output, rotating the file when it's full
+    fn write(&mut self, text: &str) {
+        let text = redact::redact(text);
+        if text.is_empty() {
+            return;
+        }
+        if let Err(e) = self.file.write_all(text.as_bytes()) {
+            warn!(path = %self.path.display(), error = %e, "Failed to write transcript");
+            return;
+        }
+        self.written += text.len() as u64;
+        if self.written >= self.max_bytes {
+            self.rotate();
+        }
+    }
+
+    /// Move the full file aside and start an empty one
+    fn rotate(&mut self) {
+        for index in (1..MAX_ROTATED_FILES).rev() {
+            let from = rotated_path(&self.path, index);
+            if from.exists() {
+                let _ = fs::rename(&from, rotated_path(&self.path, index + 1));
+            }
+        }
+        if let Err(e) = fs::rename(&self.path, rotated_path(&self.path, 1)) {
+            warn!(path = %self.path.display(), error = %e, "Failed to rotate transcript");
+            return;
+        }
+        match open_append(&self.path) {
+            Ok(file) => {
+                self.file = file;
+                self.written = 0;
+            }
+            Err(e) => warn!("{}", e),
+        }
+    }
+}
+
+impl Drop for TranscriptLog {
+    fn drop(&mut self) {
+        let rest = std::mem::take(&mut self.pending);
+        self.write(&rest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name("3f2a-b1", 1700000000), "1700000000-3f2a-b1.log");
+        assert_eq!(file_name("../x y", 1), "1-___x_y.log");
+    }
+
+    #[test]
+    fn test_plain_strips_escapes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = TranscriptLog::start(dir.path(), "s1", TranscriptFormat::Plain).unwrap();
+        log.push("\x1b[1;32m$\x1b[0m ls\r\n");
+        log.push("a\tb\x07\x1b]0;ti");
+        log.push("tle\x07\r\n");
+        let text = fs::read_to_string(log.path()).unwrap();
+        assert_eq!(text, "$ ls\na\tb\n");
+    }
+
+    #[test]
+    fn test_raw_keeps_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = TranscriptLog::start(dir.path(), "s1", TranscriptFormat::Raw).unwrap();
+        log.push("\x1b[1mbold\x1b[0m\r\n");
+        assert_eq!(
+            fs::read_to_string(log.path()).unwrap(),
+            "\x1b[1mbold\x1b[0m\r\n"
+        );
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("1-s1.log");
+        let mut log = TranscriptLog::open(path.clone(), TranscriptFormat::Raw, 4).unwrap();
+        for i in 0..=MAX_ROTATED_FILES + 1 {
+            log.push(&format!("{}{}{}\n", i, i, i));
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        let newest = MAX_ROTATED_FILES + 1;
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            format!("{}\n", newest.to_string().repeat(3))
+        );
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, MAX_ROTATED_FILES)).unwrap(),
+            format!(
+                "{}\n",
+                (newest + 1 - MAX_ROTATED_FILES).to_string().repeat(3)
+            )
+        );
+        assert!(!rotated_path(&path, MAX_ROTATED_FILES + 1).exists());
+    }
+
+    #[test]
+    fn test_secrets_are_redacted() {
+        let token = format!("ghp_{}", "a1B2".repeat(9));
+        for format in [TranscriptFormat::Raw, TranscriptFormat::Plain] {
+            let dir = tempfile::tempdir().unwrap();
+            let mut log = TranscriptLog::start(dir.path(), "s1", format).unwrap();
+            let path = log.path().to_path_buf();
+            // Split across reads, and a last line without a newline
+            log.push(&format!("$ echo {}", &token[..10]));
+            log.push(&format!("{}\r\n$ export T={}", &token[10..], token));
+            assert!(!fs::read_to_string(&path).unwrap().contains("ghp_"));
+            drop(log);
+
+            let text = fs::read_to_string(&path).unwrap();
+            assert!(!text.contains("ghp_"), "{:?}", text);
+            assert_eq!(text.matches(redact::REDACTED).count(), 2);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_only_readable_by_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let log = TranscriptLog::start(dir.path(), "s1", TranscriptFormat::Raw).unwrap();
+        let mode = fs::metadata(log.path()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,415 @@
+//! Moving files to the Trash instead of deleting them
+//!
+//! `move_to_trash` moves files with `NSFileManager`, so they can be put back
+//! from the Finder. With `safe_delete`, `write_to_pty` also tracks the line
+//! typed at each session's prompt: when Enter would run a plain `rm` of
+//! named files, it's held back and `rm-intercepted` lets the frontend offer
+//! the Trash instead. `resolve_rm_intercept` then trashes the files and
+//! clears the line, runs `rm` after all, or leaves the line for editing.
+//!
+//! Only lines whose every key is known are considered: history recall, tab
+//! completion and other escape sequences make the line unknown until the
+//! next Enter. Commands with globs, variables, `~`, redirections or several
+//! commands aren't intercepted, since the shell would expand them
+//! differently than we can.
+
+use crate::events;
+use crate::lock::LockManager;
+use crate::pty::{PtyManager, WriteError};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{command, AppHandle, State};
+use tracing::{info, warn};
+
+/// Characters the shell would treat specially outside quotes
+const SPECIAL_CHARS: &str = ";&|<>()$`*?[]{}~!#";
+
+/// `rm` options that don't change which files are removed
+const RM_FLAGS: &str = "rRfidv";
+
+/// Kill-line (Ctrl-U), clearing the shell's input line
+const CLEAR_LINE: &str = "\x15";
+
+/// Payload of the `rm-intercepted` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RmIntercepted {
+    pub session_id: String,
+    /// The held back command line
+    pub command: String,
+    /// Absolute paths it would remove
+    pub paths: Vec<String>,
+}
+
+/// What to do with an intercepted `rm`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RmAction {
+    /// Move the files to the Trash and clear the line
+    Trash,
+    /// Run the `rm` after all
+    Run,
+    /// Leave the line as typed
+    Cancel,
+}
+
+/// Split a command line into words, or `None` if the shell would expand
+/// anything in it
+fn split_words(line: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => word.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '$' | '`' | '\\' | '!' => return None,
+                        c => word.push(c),
+                    }
+                }
+            }
+            '\\' => word.get_or_insert_with(String::new).push(chars.next()?),
+            c if c.is_whitespace() => words.extend(word.take()),
+            c if SPECIAL_CHARS.contains(c) => return None,
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Some(words)
+}
+
+/// Files a plain `rm` command line removes, as written
+pub fn parse_rm(line: &str) -> Option<Vec<String>> {
+    let words = split_words(line)?;
+    let (program, args) = words.split_first()?;
+    if program != "rm" {
+        return None;
+    }
+    let mut paths = Vec::new();
+    let mut options_ended = false;
+    for arg in args {
+        if options_ended || !arg.starts_with('-') || arg == "-" {
+            paths.push(arg.clone());
+        } else if arg == "--" {
+            options_ended = true;
+        } else if !arg[1..].chars().all(|flag| RM_FLAGS.contains(flag)) {
+            return None;
+        }
+    }
+    (!paths.is_empty()).then_some(paths)
+}
+
+/// Resolve `rm` arguments against the shell's directory
+fn resolve_paths(paths: &[String], cwd: Option<&str>) -> Option<Vec<String>> {
+    paths
+        .iter()
+        .map(|path| {
+            let path = Path::new(path);
+            let path = if path.is_absolute() {
+                path.to_path_buf()
+            } else {
+                Path::new(cwd?).join(path)
+            };
+            Some(path.to_string_lossy().into_owned())
+        })
+        .collect()
+}
+
+/// Apply typed input to a line; `None` is a line that isn't known exactly
+fn feed(line: &mut Option<String>, c: char) {
+    match c {
+        '\r' | '\n' | '\x03' | '\x15' => *line = Some(String::new()),
+        '\x7f' | '\x08' => {
+            if let Some(line) = line {
+                line.pop();
+            }
+        }
+        c if c.is_control() => *line = None,
+        c => {
+            if let Some(line) = line {
+                line.push(c);
+            }
+        }
+    }
+}
+
+/// Typed lines and held back `rm` commands of each session
+#[derive(Default)]
+pub struct SafeDelete {
+    lines: Mutex<HashMap<String, Option<String>>>,
+    pending: Mutex<HashMap<String, RmIntercepted>>,
+}
+
+impl SafeDelete {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track input written to a session, returning the line a trailing
+    /// Enter submits if it is known
+    pub fn input(&self, session_id: &str, data: &str) -> Option<String> {
+        let mut lines = self.lines.lock();
+        let line = lines
+            .entry(session_id.to_string())
+            .or_insert_with(|| Some(String::new()));
+        let (typed, enter) = match data.strip_suffix('\r') {
+            Some(typed) => (typed, true),
+            None => (data, false),
+        };
+        typed.chars().for_each(|c| feed(line, c));
+        if !enter {
+            return None;
+        }
+        line.replace(String::new())
+    }
+
+    /// Hold back an `rm`; the session's line is still the command
+    fn hold(&self, intercepted: RmIntercepted) {
+        self.lines.lock().insert(
+            intercepted.session_id.clone(),
+            Some(intercepted.command.clone()),
+        );
+        self.pending
+            .lock()
+            .insert(intercepted.session_id.clone(), intercepted);
+    }
+
+    fn take(&self, session_id: &str) -> Option<RmIntercepted> {
+        self.pending.lock().remove(session_id)
+    }
+
+    /// Forget a line, after it was run or cleared
+    fn reset(&self, session_id: &str) {
+        self.lines
+            .lock()
+            .insert(session_id.to_string(), Some(String::new()));
+    }
+
+    /// Forget a session's state (e.g. when it exits)
+    pub fn remove_session(&self, session_id: &str) {
+        self.lines.lock().remove(session_id);
+        self.pending.lock().remove(session_id);
+    }
+}
+
+/// Hold back Enter if it would run a plain `rm` at a session's prompt.
+/// Returns the input to write instead, or `None` to write it unchanged.
+pub fn intercept(
+    app: &AppHandle,
+    pty_manager: &PtyManager,
+    safe_delete: &SafeDelete,
+    session_id: &str,
+    data: &str,
+) -> Option<String> {
+    let command = safe_delete.input(session_id, data)?;
+    let paths = parse_rm(&command)?;
+    if !pty_manager.is_at_prompt(session_id).unwrap_or(false) {
+        return None;
+    }
+    let cwd = pty_manager.get_session_cwd(session_id).ok().flatten();
+    let paths = resolve_paths(&paths, cwd.as_deref())?;
+
+    info!(session_id = %session_id, count = paths.len(), "Held back rm");
+    let intercepted = RmIntercepted {
+        session_id: session_id.to_string(),
+        command,
+        paths,
+    };
+    safe_delete.hold(intercepted.clone());
+    let _ = events::emit(app, intercepted);
+    Some(data[..data.len() - 1].to_string())
+}
+
+/// Move a file to the Trash, returning where it ended up
+#[cfg(target_os = "macos")]
+fn trash_item(path: &Path) -> Result<PathBuf, String> {
+    use objc2_foundation::{NSFileManager, NSString, NSURL};
+
+    let url = NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy()));
+    let mut resulting = None;
+    NSFileManager::defaultManager()
+        .trashItemAtURL_resultingItemURL_error(&url, Some(&mut resulting))
+        .map_err(|e| e.localizedDescription().to_string())?;
+    Ok(resulting
+        .and_then(|url| url.path())
+        .map(|path| PathBuf::from(path.to_string()))
+        .unwrap_or_default())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn trash_item(_path: &Path) -> Result<PathBuf, String> {
+    Err("Moving to the Trash is only supported on macOS".to_string())
+}
+
+/// Move files to the Trash, returning where each ended up. Every path is
+/// tried; the error names those that failed.
+pub fn trash(paths: &[String]) -> Result<Vec<String>, String> {
+    let mut trashed = Vec::new();
+    let mut failed = Vec::new();
+    for path in paths {
+        let path = Path::new(path);
+        let result = if !path.is_absolute() {
+            Err("not an absolute path".to_string())
+        } else if path.symlink_metadata().is_err() {
+            Err("no such file".to_string())
+        } else {
+            trash_item(path)
+        };
+        match result {
+            Ok(location) => trashed.push(location.to_string_lossy().into_owned()),
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Failed to move to the Trash");
+                failed.push(format!("{}: {}", path.display(), e));
+            }
+        }
+    }
+    if failed.is_empty() {
+        Ok(trashed)
+    } else {
+        Err(format!(
+            "Failed to move to the Trash: {}",
+            failed.join("; ")
+        ))
+    }
+}
+
+/// Move files to the Trash, returning where each ended up
+#[command]
+pub async fn move_to_trash(paths: Vec<String>) -> Result<Vec<String>, String> {
+    trash(&paths)
+}
+
+/// Act on the `rm` held back in a session
+#[command]
+pub async fn resolve_rm_intercept(
+    pty_manager: State<'_, Arc<PtyManager>>,
+    safe_delete: State<'_, Arc<SafeDelete>>,
+    lock_manager: State<'_, Arc<LockManager>>,
+    session_id: String,
+    action: RmAction,
+) -> Result<(), String> {
+    lock_manager.ensure_unlocked()?;
+    lock_manager.record_activity();
+    let intercepted = safe_delete
+        .take(&session_id)
+        .ok_or_else(|| format!("No rm held back in session {}", session_id))?;
+    let write = |data: &str| {
+        pty_manager
+            .write_to_session(&session_id, data)
+            .map_err(|e: WriteError| e.to_string())
+    };
+    match action {
+        RmAction::Trash => {
+            let result = trash(&intercepted.paths);
+            // Clear the line even if some files couldn't be trashed, so the
+            // rm isn't run by the next Enter
+            write(CLEAR_LINE)?;
+            safe_delete.reset(&session_id);
+            result?;
+        }
+        RmAction::Run => {
+            write("\r")?;
+            safe_delete.reset(&session_id);
+        }
+        RmAction::Cancel => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_rm() {
+        assert_eq!(
+            parse_rm("rm -rf build 'My Files' \"a b\" c\\ d"),
+            Some(vec![
+                "build".to_string(),
+                "My Files".to_string(),
+                "a b".to_string(),
+                "c d".to_string(),
+            ])
+        );
+        assert_eq!(parse_rm("rm -- -weird"), Some(vec!["-weird".to_string()]));
+        assert_eq!(parse_rm("rm -rf"), None);
+        assert_eq!(parse_rm("rm *.log"), None);
+        assert_eq!(parse_rm("rm ~/notes"), None);
+        assert_eq!(parse_rm("rm \"$FILE\""), None);
+        assert_eq!(parse_rm("rm a; ls"), None);
+        assert_eq!(parse_rm("rm -P secret"), None);
+        assert_eq!(parse_rm("rm 'unterminated"), None);
+        assert_eq!(parse_rm("rmdir build"), None);
+        assert_eq!(parse_rm("sudo rm build"), None);
+    }
+
+    #[test]
+    fn test_resolve_paths() {
+        let paths = vec!["build".to_string(), "/tmp/x".to_string()];
+        assert_eq!(
+            resolve_paths(&paths, Some("/Users/me/app")),
+            Some(vec![
+                "/Users/me/app/build".to_string(),
+                "/tmp/x".to_string()
+            ])
+        );
+        assert_eq!(resolve_paths(&paths, None), None);
+        assert_eq!(
+            resolve_paths(&paths[1..], None),
+            Some(vec!["/tmp/x".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_tracks_typed_lines() {
+        let safe_delete = SafeDelete::new();
+        assert_eq!(safe_delete.input("a", "rm buil"), None);
+        assert_eq!(safe_delete.input("a", "x\x7fd"), None);
+        assert_eq!(safe_delete.input("a", "\r"), Some("rm build".to_string()));
+        assert_eq!(safe_delete.input("a", "ls\r"), Some("ls".to_string()));
+
+        // History recall makes the line unknown until the next Enter
+        assert_eq!(safe_delete.input("a", "\x1b[A"), None);
+        assert_eq!(safe_delete.input("a", "\r"), None);
+        assert_eq!(
+            safe_delete.input("a", "junk\x15rm x\r"),
+            Some("rm x".to_string())
+        );
+        assert_eq!(safe_delete.input("b", "rm y\r"), Some("rm y".to_string()));
+
+        safe_delete.hold(RmIntercepted {
+            session_id: "a".to_string(),
+            command: "rm x".to_string(),
+            paths: vec!["/tmp/x".to_string()],
+        });
+        assert_eq!(safe_delete.input("a", "\r"), Some("rm x".to_string()));
+        assert!(safe_delete.take("a").is_some());
+        assert!(safe_delete.take("a").is_none());
+    }
+
+    #[test]
+    fn test_trash_rejects_missing_and_relative_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing");
+        let err = trash(&[missing.to_string_lossy().into_owned()]).unwrap_err();
+        assert!(err.contains("no such file"));
+        let err = trash(&["relative".to_string()]).unwrap_err();
+        assert!(err.contains("not an absolute path"));
+        assert_eq!(trash(&[]), Ok(Vec::new()));
+    }
+}
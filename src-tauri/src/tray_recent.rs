@@ -0,0 +1,359 @@
+//! Recent sessions and commands in the tray menu
+//!
+//! The tray menu's Recent Sessions submenu lists the open sessions, most
+//! recently active first, by tab title and directory. Choosing one shows the
+//! session's window and emits `session-focus-requested` to it so the frontend
+//! selects the tab. Recent Commands lists the latest distinct commands from the
+//! history that can be rerun; choosing one starts it in a new session in the
+//! directory, profile and environment it ran with, like a launch command. The
+//! tray menu is rebuilt when the pointer enters the tray icon, so both lists are
+//! current when it opens.
+
+use crate::commands::{validate_args, validate_command};
+use crate::events;
+use crate::file_drop::shell_quote;
+use crate::launch_command;
+use crate::pty::{PtyManager, SessionOptions, SessionSummary};
+use crate::sandbox;
+use crate::session_store::{CommandInvocation, HistoryEntry, SessionStore};
+use crate::tab_title::abbreviate_home;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+/// Items listed in each submenu
+pub const MAX_ITEMS: usize = 10;
+
+/// History entries looked at for distinct commands
+const HISTORY_SCAN: usize = 100;
+
+/// Menu item labels are cut to this many characters
+const MAX_LABEL_CHARS: usize = 60;
+
+/// Prefix of the tray menu item IDs of sessions
+const SESSION_MENU_ID_PREFIX: &str = "recent-session:";
+
+/// Prefix of the tray menu item IDs of history entries
+const COMMAND_MENU_ID_PREFIX: &str = "recent-command:";
+
+/// Payload of the `session-focus-requested` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionFocusRequested {
+    pub session_id: String,
+}
+
+/// A tray menu item: its ID and label
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentItem {
+    pub menu_id: String,
+    pub label: String,
+}
+
+/// What choosing a recent item does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecentAction {
+    /// Show and select an open session
+    FocusSession(String),
+    /// Run a history entry again in a new session
+    Rerun(i64),
+}
+
+/// Action of a tray menu item, if it's a recent item
+pub fn action_from_menu_id(menu_id: &str) -> Option<RecentAction> {
+    if let Some(session_id) = menu_id.strip_prefix(SESSION_MENU_ID_PREFIX) {
+        return Some(RecentAction::FocusSession(session_id.to_string()));
+    }
+    let id = menu_id.strip_prefix(COMMAND_MENU_ID_PREFIX)?.parse().ok()?;
+    Some(RecentAction::Rerun(id))
+}
+
+fn truncate_label(label: &str) -> String {
+    if label.chars().count() <= MAX_LABEL_CHARS {
+        return label.to_string();
+    }
+    let mut truncated: String = label.chars().take(MAX_LABEL_CHARS - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Label of a session: its tab title and directory
+fn session_label(title: Option<&str>, cwd: Option<&str>, home: Option<&str>) -> String {
+    let cwd = cwd.map(|cwd| abbreviate_home(cwd, home));
+    let label = match (title.filter(|title| !title.is_empty()), cwd) {
+        (Some(title), Some(cwd)) if title != cwd => format!("{} — {}", title, cwd),
+        (Some(title), _) => title.to_string(),
+        (None, Some(cwd)) => cwd,
+        (None, None) => "Terminal".to_string(),
+    };
+    truncate_label(&label)
+}
+
+/// Session items, most recently active first
+fn session_items(mut sessions: Vec<SessionSummary>, home: Option<&str>) -> Vec<RecentItem> {
+    sessions.sort_by_key(|session| session.idle_secs);
+    sessions
+        .iter()
+        .take(MAX_ITEMS)
+        .map(|session| RecentItem {
+            menu_id: format!("{}{}", SESSION_MENU_ID_PREFIX, session.session_id),
+            label: session_label(session.title.as_deref(), session.cwd.as_deref(), home),
+        })
+        .collect()
+}
+
+/// Command items: the newest entry of each distinct rerunnable command
+fn command_items(history: &[HistoryEntry]) -> Vec<RecentItem> {
+    let mut seen: Vec<&str> = Vec::new();
+    history
+        .iter()
+        .filter(|entry| entry.rerunnable)
+        .filter(|entry| {
+            let new = !seen.contains(&entry.command.as_str());
+            if new {
+                seen.push(&entry.command);
+            }
+            new
+        })
+        .take(MAX_ITEMS)
+        .map(|entry| RecentItem {
+            menu_id: format!("{}{}", COMMAND_MENU_ID_PREFIX, entry.id),
+            label: truncate_label(&entry.command),
+        })
+        .collect()
+}
+
+/// Open sessions for the tray menu
+pub fn sessions(app: &AppHandle) -> Vec<RecentItem> {
+    let sessions = app
+        .try_state::<Arc<PtyManager>>()
+        .map(|pty_manager| pty_manager.list_sessions())
+        .unwrap_or_default();
+    let home = std::env::var("HOME").ok();
+    session_items(sessions, home.as_deref())
+}
+
+/// Recent commands for the tray menu; empty when the store isn't available
+pub fn commands(app: &AppHandle) -> Vec<RecentItem> {
+    app.try_state::<Arc<SessionStore>>()
+        .and_then(|store| store.history(HISTORY_SCAN).ok())
+        .map(|history| command_items(&history))
+        .unwrap_or_default()
+}
+
+/// Ask the session's window to select it, returning the window's label
+pub fn focus_session(app: &AppHandle, session_id: &str) -> Result<String, String> {
+    let pty_manager = app
+        .try_state::<Arc<PtyManager>>()
+        .ok_or("PTY manager not available")?;
+    let window = pty_manager.session_window(session_id)?;
+    events::emit_to(
+        app,
+        &window,
+        SessionFocusRequested {
+            session_id: session_id.to_string(),
+        },
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(window)
+}
+
+/// Command line to run an invocation with `<shell> -c`, sandboxed as it was
+fn shell_command(invocation: &CommandInvocation) -> Option<String> {
+    let quote = |word: &str| {
+        let plain = !word.is_empty()
+            && word
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_-./=:,@%+".contains(c));
+        if plain {
+            Some(word.to_string())
+        } else {
+            shell_quote(word)
+        }
+    };
+    let home = std::env::var("HOME").ok();
+    let (cmd, args) = sandbox::wrap(
+        invocation.sandbox,
+        &invocation.cmd,
+        &invocation.args,
+        &invocation.env,
+        home.as_deref(),
+    )
+    .ok()?;
+    std::iter::once(cmd.as_str())
+        .chain(args.iter().map(String::as_str))
+        .map(quote)
+        .collect::<Option<Vec<_>>>()
+        .map(|words| words.join(" "))
+}
+
+/// Run a history entry again in a new session, returning the session ID
+pub fn rerun(app: &AppHandle, id: i64) -> Result<String, String> {
+    let store = app
+        .try_state::<Arc<SessionStore>>()
+        .ok_or("Session store not available")?;
+    let invocation = store.invocation(id)?;
+    validate_command(&invocation.cmd)?;
+    validate_args(&invocation.args)?;
+    let cwd = invocation.cwd.as_ref().map(PathBuf::from);
+    if let Some(cwd) = &cwd {
+        if !cwd.is_dir() {
+            return Err(format!("Directory no longer exists: {}", cwd.display()));
+        }
+    }
+    let command =
+        shell_command(&invocation).ok_or_else(|| format!("History entry {} can't be rerun", id))?;
+
+    let title = invocation.command_line();
+    let options = SessionOptions {
+        cwd,
+        command: Some(command),
+        profile: invocation.profile,
+        env: invocation.env,
+        ..Default::default()
+    };
+    let session_id = launch_command::start(app, options, title)?;
+    info!(session_id = %session_id, history_id = id, "Reran command from the tray");
+    Ok(session_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::SandboxLevel;
+    use crate::session_labels::SessionLabels;
+
+    fn summary(session_id: &str, title: Option<&str>, idle_secs: u64) -> SessionSummary {
+        SessionSummary {
+            session_id: session_id.to_string(),
+            cwd: Some("/Users/me/src/app".to_string()),
+            title: title.map(String::from),
+            name: "~/src/app 09:14".to_string(),
+            running_command: None,
+            idle_secs,
+            last_input_at: None,
+            last_output_at: None,
+            suspended: false,
+            protected: false,
+            read_only: false,
+            labels: SessionLabels::default(),
+            window: "main".to_string(),
+            attached: true,
+            alternate_screen: false,
+        }
+    }
+
+    fn entry(id: i64, command: &str, rerunnable: bool) -> HistoryEntry {
+        HistoryEntry {
+            id,
+            command: command.to_string(),
+            exit_code: 0,
+            finished_at: 0,
+            cwd: None,
+            profile: None,
+            rerunnable,
+        }
+    }
+
+    #[test]
+    fn test_menu_ids_round_trip() {
+        let items = session_items(vec![summary("3f2a", None, 0)], None);
+        assert_eq!(
+            action_from_menu_id(&items[0].menu_id),
+            Some(RecentAction::FocusSession("3f2a".to_string()))
+        );
+        let items = command_items(&[entry(42, "ls", true)]);
+        assert_eq!(
+            action_from_menu_id(&items[0].menu_id),
+            Some(RecentAction::Rerun(42))
+        );
+        assert_eq!(action_from_menu_id("recent-command:x"), None);
+        assert_eq!(action_from_menu_id("bookmark:1"), None);
+    }
+
+    #[test]
+    fn test_session_label() {
+        let home = Some("/Users/me");
+        assert_eq!(
+            session_label(Some("vim"), Some("/Users/me/src"), home),
+            "vim — ~/src"
+        );
+        assert_eq!(
+            session_label(Some("~/src"), Some("/Users/me/src"), home),
+            "~/src"
+        );
+        assert_eq!(session_label(None, Some("/tmp"), home), "/tmp");
+        assert_eq!(session_label(Some(""), None, home), "Terminal");
+        let long = "x".repeat(100);
+        let label = session_label(Some(&long), None, home);
+        assert_eq!(label.chars().count(), MAX_LABEL_CHARS);
+        assert!(label.ends_with('…'));
+    }
+
+    #[test]
+    fn test_sessions_most_recent_first() {
+        let sessions = (0..MAX_ITEMS as u64 + 2)
+            .map(|i| summary(&format!("s{}", i), Some("zsh"), 100 - i))
+            .collect();
+        let items = session_items(sessions, Some("/Users/me"));
+        assert_eq!(items.len(), MAX_ITEMS);
+        assert_eq!(items[0].menu_id, "recent-session:s11");
+        assert_eq!(items[0].label, "zsh — ~/src/app");
+    }
+
+    #[test]
+    fn test_commands_are_distinct_and_rerunnable() {
+        let history = [
+            entry(5, "make test", true),
+            entry(4, "cat ***", false),
+            entry(3, "make test", true),
+            entry(2, "git status", true),
+        ];
+        let items = command_items(&history);
+        let ids: Vec<&str> = items.iter().map(|item| item.menu_id.as_str()).collect();
+        assert_eq!(ids, ["recent-command:5", "recent-command:2"]);
+        assert_eq!(items[1].label, "git status");
+    }
+
+    #[test]
+    fn test_shell_command_quotes_arguments() {
+        let invocation = CommandInvocation {
+            cmd: "git".to_string(),
+            args: vec![
+                "commit".to_string(),
+                "-m".to_string(),
+                "it's done".to_string(),
+                "--author=me@example.com".to_string(),
+            ],
+            cwd: None,
+            env: Vec::new(),
+            profile: None,
+            sandbox: SandboxLevel::Off,
+        };
+        assert_eq!(
+            shell_command(&invocation).as_deref(),
+            Some(r"git commit -m 'it'\''s done' --author=me@example.com")
+        );
+        let invocation = CommandInvocation {
+            args: vec!["a\nb".to_string()],
+            ..invocation
+        };
+        assert_eq!(shell_command(&invocation), None);
+    }
+
+    #[test]
+    fn test_shell_command_keeps_sandbox() {
+        let invocation = CommandInvocation {
+            cmd: "ls".to_string(),
+            args: Vec::new(),
+            cwd: None,
+            env: vec![("LANG".to_string(), "en_US.UTF-8".to_string())],
+            profile: None,
+            sandbox: SandboxLevel::Env,
+        };
+        let command = shell_command(&invocation).unwrap();
+        assert!(command.starts_with("/usr/bin/env -i LANG=en_US.UTF-8 "));
+        assert!(command.ends_with(" ls"));
+    }
+}
@@ -0,0 +1,14 @@
+//! Wall-clock timestamps
+//!
+//! Stored and reported times (job history, recent directories, session
+//! records) are whole seconds since the Unix epoch.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds since the Unix epoch, or 0 if the clock is before it
+pub(crate) fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,341 @@
+//! Opt-in, local-only usage statistics
+//!
+//! When the `usage_stats_enabled` setting is on, µTerm counts sessions
+//! created, commands submitted and which features are used. Only counts are
+//! kept (never command text, paths or output), they stay in the app data
+//! directory, and nothing is sent anywhere: `export_usage_stats` returns a
+//! JSON document the user can choose to share.
+
+use crate::settings::SettingsManager;
+use crate::unix_time::now_secs;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{command, State};
+use tracing::{debug, error, warn};
+
+/// Minimum time between writes of the stats file while counting
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Maximum length of a feature name
+const MAX_FEATURE_NAME_LENGTH: usize = 48;
+
+/// Maximum number of distinct features tracked
+const MAX_FEATURES: usize = 128;
+
+/// Version of the exported document format
+const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Accumulated usage counts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// When counting started, in seconds since the Unix epoch
+    #[serde(default)]
+    pub since: u64,
+    /// When the last count was recorded, in seconds since the Unix epoch
+    #[serde(default)]
+    pub last_recorded_at: u64,
+    #[serde(default)]
+    pub sessions_created: u64,
+    /// Command lines submitted to shells (Enter presses) and one-off commands
+    #[serde(default)]
+    pub commands_run: u64,
+    /// Uses per feature, keyed by feature name
+    #[serde(default)]
+    pub features: BTreeMap<String, u64>,
+}
+
+/// Document returned by `export_usage_stats`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageStatsExport {
+    pub format_version: u32,
+    pub app_version: String,
+    pub exported_at: u64,
+    pub stats: UsageStats,
+}
+
+/// Validate a feature name (`snake_case` identifier)
+fn validate_feature_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_FEATURE_NAME_LENGTH {
+        return Err(format!(
+            "Feature name must be 1-{} characters",
+            MAX_FEATURE_NAME_LENGTH
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(format!("Invalid feature name: {}", name));
+    }
+    Ok(())
+}
+
+/// Number of command lines submitted by a write to a PTY
+pub fn count_submitted_lines(data: &str) -> u64 {
+    data.matches('\r').count() as u64
+}
+
+/// Usage counter, gated by the `usage_stats_enabled` setting
+pub struct UsageStatsManager {
+    stats: Mutex<UsageStats>,
+    /// Last time the stats file was written
+    last_saved: Mutex<Option<Instant>>,
+    stats_path: PathBuf,
+    settings: Arc<SettingsManager>,
+}
+
+impl UsageStatsManager {
+    /// Create a new stats manager backed by the given file
+    pub fn new(stats_path: PathBuf, settings: Arc<SettingsManager>) -> Self {
+        let stats = Self::load_stats(&stats_path);
+        Self {
+            stats: Mutex::new(stats),
+            last_saved: Mutex::new(None),
+            stats_path,
+            settings,
+        }
+    }
+
+    /// Load stats from disk
+    fn load_stats(path: &PathBuf) -> UsageStats {
+        match fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str(&content) {
+                Ok(stats) => stats,
+                Err(e) => {
+                    error!("Failed to parse usage stats: {}", e);
+                    UsageStats::default()
+                }
+            },
+            Err(_) => {
+                debug!("No existing usage stats file, starting fresh");
+                UsageStats::default()
+            }
+        }
+    }
+
+    /// Save stats to disk
+    fn save_stats(&self) {
+        let json = match serde_json::to_string_pretty(&*self.stats.lock()) {
+            Ok(json) => json,
+            Err(e) => {
+                error!("Failed to serialize usage stats: {}", e);
+                return;
+            }
+        };
+        if let Some(parent) = self.stats_path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        match fs::write(&self.stats_path, json) {
+            Ok(_) => debug!("Saved usage stats"),
+            Err(e) => error!("Failed to write usage stats: {}", e),
+        }
+        *self.last_saved.lock() = Some(Instant::now());
+    }
+
+    /// Save unless the file was written recently
+    fn save_throttled(&self) {
+        let due = self
+            .last_saved
+            .lock()
+            .is_none_or(|last| last.elapsed() >= SAVE_INTERVAL);
+        if due {
+            self.save_stats();
+        }
+    }
+
+    /// Whether the user opted in
+    pub fn is_enabled(&self) -> bool {
+        self.settings.get().usage_stats_enabled
+    }
+
+    /// Apply a change to the stats if enabled
+    fn update(&self, change: impl FnOnce(&mut UsageStats)) {
+        if !self.is_enabled() {
+            return;
+        }
+        {
+            let mut stats = self.stats.lock();
+            let now = now_secs();
+            if stats.since == 0 {
+                stats.since = now;
+            }
+            stats.last_recorded_at = now;
+            change(&mut stats);
+        }
+        self.save_throttled();
+    }
+
+    pub fn record_session_created(&self) {
+        self.update(|stats| stats.sessions_created += 1);
+    }
+
+    pub fn record_commands(&self, count: u64) {
+        if count > 0 {
+            self.update(|stats| stats.commands_run += count);
+        }
+    }
+
+    /// Count a use of a feature
+    pub fn record_feature(&self, name: &str) -> Result<(), String> {
+        validate_feature_name(name)?;
+        self.update(|stats| {
+            if let Some(count) = stats.features.get_mut(name) {
+                *count += 1;
+            } else if stats.features.len() < MAX_FEATURES {
+                stats.features.insert(name.to_string(), 1);
+            } else {
+                warn!(feature = %name, "Usage stats feature limit reached");
+            }
+        });
+        Ok(())
+    }
+
+    /// Current stats
+    pub fn get(&self) -> UsageStats {
+        self.stats.lock().clone()
+    }
+
+    /// Erase all stats
+    pub fn reset(&self) {
+        *self.stats.lock() = UsageStats::default();
+        self.save_stats();
+    }
+
+    /// Write pending counts to disk
+    pub fn flush(&self) {
+        if self.last_saved.lock().is_some() {
+            self.save_stats();
+        }
+    }
+}
+
+/// Current usage statistics
+#[command]
+pub fn get_usage_stats(
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
+) -> Result<UsageStats, String> {
+    usage_stats.flush();
+    Ok(usage_stats.get())
+}
+
+/// Usage statistics as a JSON document the user can save or share
+#[command]
+pub fn export_usage_stats(
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
+) -> Result<String, String> {
+    usage_stats.flush();
+    let export = UsageStatsExport {
+        format_version: EXPORT_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: now_secs(),
+        stats: usage_stats.get(),
+    };
+    serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to export usage stats: {}", e))
+}
+
+/// Erase all usage statistics
+#[command]
+pub fn reset_usage_stats(usage_stats: State<'_, Arc<UsageStatsManager>>) -> Result<(), String> {
+    usage_stats.reset();
+    Ok(())
+}
+
+/// Count a use of a frontend feature (no-op unless stats are enabled)
+#[command]
+pub fn record_feature_usage(
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
+    feature: String,
+) -> Result<(), String> {
+    usage_stats.record_feature(&feature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn manager(temp_dir: &TempDir, enabled: bool) -> UsageStatsManager {
+        let settings = Arc::new(SettingsManager::new(temp_dir.path().join("settings.json")));
+        let mut updated = settings.get();
+        updated.usage_stats_enabled = enabled;
+        settings.update(updated);
+        UsageStatsManager::new(temp_dir.path().join("usage-stats.json"), settings)
+    }
+
+    #[test]
+    fn test_disabled_records_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir, false);
+        manager.record_session_created();
+        manager.record_commands(3);
+        manager.record_feature("split_pane").unwrap();
+        assert_eq!(manager.get(), UsageStats::default());
+    }
+
+    #[test]
+    fn test_records_counts() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir, true);
+        manager.record_session_created();
+        manager.record_commands(2);
+        manager.record_commands(0);
+        manager.record_feature("split_pane").unwrap();
+        manager.record_feature("split_pane").unwrap();
+
+        let stats = manager.get();
+        assert_eq!(stats.sessions_created, 1);
+        assert_eq!(stats.commands_run, 2);
+        assert_eq!(stats.features.get("split_pane"), Some(&2));
+        assert!(stats.since > 0);
+    }
+
+    #[test]
+    fn test_feature_name_validation() {
+        assert!(validate_feature_name("scrollback_search").is_ok());
+        assert!(validate_feature_name("").is_err());
+        assert!(validate_feature_name("Split Pane").is_err());
+        assert!(validate_feature_name("/etc/passwd").is_err());
+        assert!(validate_feature_name(&"a".repeat(MAX_FEATURE_NAME_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_feature_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir, true);
+        for i in 0..MAX_FEATURES + 10 {
+            manager.record_feature(&format!("feature_{}", i)).unwrap();
+        }
+        assert_eq!(manager.get().features.len(), MAX_FEATURES);
+    }
+
+    #[test]
+    fn test_persistence_and_reset() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(&temp_dir, true);
+        manager.record_session_created();
+        manager.record_commands(5);
+        manager.flush();
+
+        let settings = Arc::new(SettingsManager::new(temp_dir.path().join("settings.json")));
+        let reloaded =
+            UsageStatsManager::new(temp_dir.path().join("usage-stats.json"), settings.clone());
+        assert_eq!(reloaded.get().commands_run, 5);
+
+        reloaded.reset();
+        let reloaded = UsageStatsManager::new(temp_dir.path().join("usage-stats.json"), settings);
+        assert_eq!(reloaded.get(), UsageStats::default());
+    }
+
+    #[test]
+    fn test_count_submitted_lines() {
+        assert_eq!(count_submitted_lines("ls -la\r"), 1);
+        assert_eq!(count_submitted_lines("abc"), 0);
+        assert_eq!(count_submitted_lines("a\rb\r"), 2);
+    }
+}
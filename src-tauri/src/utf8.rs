@@ -0,0 +1,112 @@
+//! Incremental UTF-8 decoding of chunked output
+//!
+//! Reads from a PTY or a child's pipe end wherever the buffer fills, which is
+//! often in the middle of a multi-byte character (CJK text, emoji). Decoding
+//! each read on its own would turn both halves into replacement characters, so
+//! `Utf8Decoder` holds an incomplete sequence at the end of a chunk back until
+//! the next one completes it. Bytes that can never be valid UTF-8 are replaced
+//! with U+FFFD as usual, without affecting the text around them.
+
+/// Decodes a byte stream that arrives in arbitrary chunks
+#[derive(Debug, Default)]
+pub struct Utf8Decoder {
+    /// Start of a character cut off at the end of the last chunk (at most 3 bytes)
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode a chunk, holding back an incomplete character at its end
+    pub fn decode(&mut self, bytes: &[u8]) -> String {
+        let mut joined: Vec<u8>;
+        let mut rest: &[u8] = if self.pending.is_empty() {
+            bytes
+        } else {
+            joined = std::mem::take(&mut self.pending);
+            joined.extend_from_slice(bytes);
+            &joined
+        };
+
+        let mut text = String::with_capacity(rest.len());
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    text.push_str(&String::from_utf8_lossy(valid));
+                    match e.error_len() {
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        // Only the end of the chunk is missing
+                        None => {
+                            self.pending.extend_from_slice(after);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        text
+    }
+
+    /// Decode what's held back once the stream has ended
+    pub fn finish(&mut self) -> String {
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_characters_are_joined() {
+        let text = "日本語 👋 é";
+        let bytes = text.as_bytes();
+        for split in 0..=bytes.len() {
+            let mut decoder = Utf8Decoder::new();
+            let mut decoded = decoder.decode(&bytes[..split]);
+            decoded.push_str(&decoder.decode(&bytes[split..]));
+            assert_eq!(decoded, text, "split at {}", split);
+            assert_eq!(decoder.finish(), "");
+        }
+    }
+
+    #[test]
+    fn test_byte_by_byte() {
+        let text = "😀a漢";
+        let mut decoder = Utf8Decoder::new();
+        let decoded: String = text
+            .as_bytes()
+            .iter()
+            .map(|byte| decoder.decode(std::slice::from_ref(byte)))
+            .collect();
+        assert_eq!(decoded, text);
+    }
+
+    #[test]
+    fn test_invalid_bytes_are_replaced() {
+        let mut decoder = Utf8Decoder::new();
+        assert_eq!(decoder.decode(b"a\xffb\xc3"), "a\u{fffd}b");
+        assert_eq!(decoder.decode(b"\xa9\xe2\x82x"), "é\u{fffd}x");
+        assert_eq!(decoder.finish(), "");
+    }
+
+    #[test]
+    fn test_finish_flushes_truncated_character() {
+        let mut decoder = Utf8Decoder::new();
+        assert_eq!(decoder.decode(b"ok\xf0\x9f"), "ok");
+        assert_eq!(decoder.finish(), "\u{fffd}");
+        assert_eq!(decoder.finish(), "");
+    }
+}
@@ -0,0 +1,425 @@
+//! Escape sequence scanner for PTY output
+//!
+//! xterm.js does the actual terminal emulation; this scanner only picks out the
+//! sequences the backend reacts to (OSC notifications, progress, private-mode
+//! CSI sequences, terminal identification queries, etc.). Output is never
+//! modified. State is kept between chunks, so sequences split across PTY reads
+//! are still recognized.
+
+/// OSC/APC payloads longer than this are discarded (inline images are large)
+const MAX_STRING_LENGTH: usize = 12 * 1024 * 1024;
+
+/// CSI sequences longer than this are ignored
+const MAX_CSI_LENGTH: usize = 256;
+
+/// Private parameter markers (`CSI ? ...`, `CSI > ...`, etc.)
+const PRIVATE_MARKERS: [char; 4] = ['<', '=', '>', '?'];
+
+/// A sequence recognized by `VtScanner`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VtEvent {
+    /// BEL outside of a control string
+    Bell,
+    /// ENQ, asking for the answerback message
+    Enq,
+    /// Primary Device Attributes request (`CSI c` / `CSI 0 c`)
+    PrimaryDeviceAttributes,
+    /// Operating System Command: `OSC command ; data ST`
+    Osc { command: String, data: String },
+    /// Application Program Command: `APC data ST` (kitty graphics)
+    Apc { data: String },
+    /// Control Sequence with a private marker: `CSI marker params intermediates final`.
+    /// Plain CSI sequences (SGR, cursor movement) are not reported.
+    Csi {
+        marker: char,
+        params: String,
+        intermediates: String,
+        final_char: char,
+    },
+}
+
+/// Kind of control string being collected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StringKind {
+    Osc,
+    Apc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    Ground,
+    Escape,
+    Csi,
+    /// Oversized or malformed CSI: skip until its final byte
+    CsiIgnore,
+    String(StringKind),
+    /// ESC seen inside a control string (possible ST)
+    StringEscape(StringKind),
+    /// Oversized control string: skip until its terminator
+    StringIgnore,
+}
+
+/// Stateful scanner that extracts `VtEvent`s from a stream of output chunks
+#[derive(Debug, Clone)]
+pub struct VtScanner {
+    state: ScanState,
+    buffer: String,
+    /// Whether OSC and APC payloads are collected
+    strings: bool,
+}
+
+impl Default for VtScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VtScanner {
+    pub fn new() -> Self {
+        Self {
+            state: ScanState::Ground,
+            buffer: String::new(),
+            strings: true,
+        }
+    }
+
+    /// Scanner for telling where sequences start and end; OSC and APC payloads
+    /// are skipped rather than collected
+    pub fn boundaries_only() -> Self {
+        Self {
+            strings: false,
+            ..Self::new()
+        }
+    }
+
+    /// Whether the scanner is outside of any escape sequence
+    pub fn at_ground(&self) -> bool {
+        self.state == ScanState::Ground
+    }
+
+    /// Scan a chunk of output, returning any sequences completed within it
+    pub fn scan(&mut self, input: &str) -> Vec<VtEvent> {
+        input.chars().filter_map(|c| self.advance(c)).collect()
+    }
+
+    /// Scan one character, returning the sequence it completes
+    pub fn advance(&mut self, c: char) -> Option<VtEvent> {
+        match self.state {
+            ScanState::Ground => match c {
+                '\x07' => return Some(VtEvent::Bell),
+                '\x05' => return Some(VtEvent::Enq),
+                '\x1b' => self.state = ScanState::Escape,
+                '\u{9b}' => self.start_csi(),
+                '\u{9d}' => self.start_string(StringKind::Osc),
+                '\u{9f}' => self.start_string(StringKind::Apc),
+                _ => {}
+            },
+            ScanState::Escape => match c {
+                '[' => self.start_csi(),
+                ']' => self.start_string(StringKind::Osc),
+                '_' => self.start_string(StringKind::Apc),
+                '\x1b' => {}
+                _ => self.state = ScanState::Ground,
+            },
+            ScanState::Csi => match c {
+                '@'..='~' => return self.finish_csi(c),
+                '\x1b' => self.state = ScanState::Escape,
+                '\x18' | '\x1a' => self.state = ScanState::Ground,
+                // C0 controls inside CSI are executed, not part of the sequence
+                '\0'..='\x1f' => {}
+                ' '..='?' if self.buffer.len() < MAX_CSI_LENGTH => self.buffer.push(c),
+                _ => self.state = ScanState::CsiIgnore,
+            },
+            ScanState::CsiIgnore => match c {
+                '@'..='~' | '\x18' | '\x1a' => self.state = ScanState::Ground,
+                '\x1b' => self.state = ScanState::Escape,
+                _ => {}
+            },
+            ScanState::String(kind) => match c {
+                // BEL only terminates OSC (xterm convention)
+                '\x07' if kind == StringKind::Osc => return self.finish_string(kind),
+                '\u{9c}' => return self.finish_string(kind),
+                '\x1b' => self.state = ScanState::StringEscape(kind),
+                // CAN and SUB abort the sequence
+                '\x18' | '\x1a' => self.state = ScanState::Ground,
+                _ if !self.strings => {}
+                _ => {
+                    if self.buffer.len() + c.len_utf8() > MAX_STRING_LENGTH {
+                        self.buffer.clear();
+                        self.state = ScanState::StringIgnore;
+                    } else {
+                        self.buffer.push(c);
+                    }
+                }
+            },
+            ScanState::StringEscape(kind) => match c {
+                '\\' => return self.finish_string(kind),
+                // Unterminated string followed by a new one
+                ']' => self.start_string(StringKind::Osc),
+                '_' => self.start_string(StringKind::Apc),
+                _ => {
+                    self.buffer.clear();
+                    self.state = ScanState::Ground;
+                }
+            },
+            ScanState::StringIgnore => match c {
+                '\x07' | '\u{9c}' | '\x18' | '\x1a' => self.state = ScanState::Ground,
+                '\x1b' => self.state = ScanState::Escape,
+                _ => {}
+            },
+        }
+        None
+    }
+
+    fn start_csi(&mut self) {
+        self.buffer.clear();
+        self.state = ScanState::Csi;
+    }
+
+    fn finish_csi(&mut self, final_char: char) -> Option<VtEvent> {
+        self.state = ScanState::Ground;
+        let body = std::mem::take(&mut self.buffer);
+        if final_char == 'c' && (body.is_empty() || body == "0") {
+            return Some(VtEvent::PrimaryDeviceAttributes);
+        }
+        let marker = body
+            .chars()
+            .next()
+            .filter(|c| PRIVATE_MARKERS.contains(c))?;
+        let rest = &body[marker.len_utf8()..];
+        // Parameter bytes (0x30-0x3F) come before intermediates (0x20-0x2F)
+        let split = rest
+            .find(|c: char| (' '..='/').contains(&c))
+            .unwrap_or(rest.len());
+        let (params, intermediates) = rest.split_at(split);
+        if params.contains(PRIVATE_MARKERS)
+            || intermediates.contains(|c: char| !(' '..='/').contains(&c))
+        {
+            return None;
+        }
+        Some(VtEvent::Csi {
+            marker,
+            params: params.to_string(),
+            intermediates: intermediates.to_string(),
+            final_char,
+        })
+    }
+
+    fn start_string(&mut self, kind: StringKind) {
+        self.buffer.clear();
+        self.state = ScanState::String(kind);
+    }
+
+    fn finish_string(&mut self, kind: StringKind) -> Option<VtEvent> {
+        self.state = ScanState::Ground;
+        let payload = std::mem::take(&mut self.buffer);
+        if kind == StringKind::Apc {
+            return Some(VtEvent::Apc { data: payload });
+        }
+        let (command, data) = match payload.split_once(';') {
+            Some((command, data)) => (command.to_string(), data.to_string()),
+            None => (payload, String::new()),
+        };
+        if command.is_empty() {
+            return None;
+        }
+        Some(VtEvent::Osc { command, data })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn osc(command: &str, data: &str) -> VtEvent {
+        VtEvent::Osc {
+            command: command.to_string(),
+            data: data.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_osc_with_bel() {
+        let mut scanner = VtScanner::new();
+        let events = scanner.scan("before\x1b]9;4;1;50\x07after");
+        assert_eq!(events, vec![osc("9", "4;1;50")]);
+    }
+
+    #[test]
+    fn test_osc_with_st() {
+        let mut scanner = VtScanner::new();
+        let events = scanner.scan("\x1b]0;title\x1b\\");
+        assert_eq!(events, vec![osc("0", "title")]);
+    }
+
+    #[test]
+    fn test_c1_osc() {
+        let mut scanner = VtScanner::new();
+        let events = scanner.scan("\u{9d}2;title\u{9c}");
+        assert_eq!(events, vec![osc("2", "title")]);
+    }
+
+    #[test]
+    fn test_osc_without_data() {
+        let mut scanner = VtScanner::new();
+        assert_eq!(scanner.scan("\x1b]104\x07"), vec![osc("104", "")]);
+    }
+
+    #[test]
+    fn test_osc_split_across_chunks() {
+        let mut scanner = VtScanner::new();
+        assert!(scanner.scan("text\x1b").is_empty());
+        assert!(scanner.scan("]9;4;").is_empty());
+        assert!(scanner.scan("1;75\x1b").is_empty());
+        assert_eq!(scanner.scan("\\more"), vec![osc("9", "4;1;75")]);
+    }
+
+    #[test]
+    fn test_multiple_sequences_in_one_chunk() {
+        let mut scanner = VtScanner::new();
+        let events = scanner.scan("\x1b[31mred\x1b]0;a\x07\x1b]0;b\x07");
+        assert_eq!(events, vec![osc("0", "a"), osc("0", "b")]);
+    }
+
+    fn csi(marker: char, params: &str, final_char: char) -> VtEvent {
+        VtEvent::Csi {
+            marker,
+            params: params.to_string(),
+            intermediates: String::new(),
+            final_char,
+        }
+    }
+
+    #[test]
+    fn test_plain_csi_is_ignored() {
+        let mut scanner = VtScanner::new();
+        assert!(scanner
+            .scan("\x1b[1;31mhello\x1b[0m\x1b[2J\x1b[H")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_private_csi() {
+        let mut scanner = VtScanner::new();
+        let events = scanner.scan("\x1b[?2004h\x1b[>1u\x1b[?u\x1b[<u");
+        assert_eq!(
+            events,
+            vec![
+                csi('?', "2004", 'h'),
+                csi('>', "1", 'u'),
+                csi('?', "", 'u'),
+                csi('<', "", 'u'),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_device_attributes_and_enq() {
+        let mut scanner = VtScanner::new();
+        let events = scanner.scan("\x1b[c\x1b[0c\x1b[>c\x05\x1b[1c");
+        assert_eq!(
+            events,
+            vec![
+                VtEvent::PrimaryDeviceAttributes,
+                VtEvent::PrimaryDeviceAttributes,
+                csi('>', "", 'c'),
+                VtEvent::Enq,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_csi_split_across_chunks() {
+        let mut scanner = VtScanner::new();
+        assert!(scanner.scan("\x1b[?10").is_empty());
+        assert_eq!(scanner.scan("49h"), vec![csi('?', "1049", 'h')]);
+    }
+
+    #[test]
+    fn test_csi_intermediates() {
+        let mut scanner = VtScanner::new();
+        assert_eq!(
+            scanner.scan("\x1b[?1$p"),
+            vec![VtEvent::Csi {
+                marker: '?',
+                params: "1".to_string(),
+                intermediates: "$".to_string(),
+                final_char: 'p',
+            }]
+        );
+    }
+
+    #[test]
+    fn test_csi_cancelled_by_escape() {
+        let mut scanner = VtScanner::new();
+        let events = scanner.scan("\x1b[?20\x1b]0;t\x07");
+        assert_eq!(events, vec![osc("0", "t")]);
+    }
+
+    #[test]
+    fn test_bell() {
+        let mut scanner = VtScanner::new();
+        // BEL terminating an OSC is not a bell
+        let events = scanner.scan("a\x07b\x1b]0;t\x07");
+        assert_eq!(events, vec![VtEvent::Bell, osc("0", "t")]);
+    }
+
+    #[test]
+    fn test_cancelled_osc() {
+        let mut scanner = VtScanner::new();
+        // The BEL after the cancelled sequence is an ordinary bell
+        assert_eq!(scanner.scan("\x1b]0;oops\x18text\x07"), vec![VtEvent::Bell]);
+    }
+
+    #[test]
+    fn test_unterminated_osc_replaced_by_new_one() {
+        let mut scanner = VtScanner::new();
+        let events = scanner.scan("\x1b]0;lost\x1b]0;kept\x07");
+        assert_eq!(events, vec![osc("0", "kept")]);
+    }
+
+    #[test]
+    fn test_apc() {
+        let mut scanner = VtScanner::new();
+        let events = scanner.scan("\x1b_Ga=T,f=100;AAAA\x1b\\");
+        assert_eq!(
+            events,
+            vec![VtEvent::Apc {
+                data: "Ga=T,f=100;AAAA".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_bel_does_not_end_apc() {
+        let mut scanner = VtScanner::new();
+        let events = scanner.scan("\x1b_a\x07b\x1b\\");
+        assert_eq!(
+            events,
+            vec![VtEvent::Apc {
+                data: "a\x07b".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_oversized_osc_discarded() {
+        let mut scanner = VtScanner::new();
+        let huge = "x".repeat(MAX_STRING_LENGTH + 1);
+        let input = format!("\x1b]1337;{}\x07\x1b]0;ok\x07", huge);
+        assert_eq!(scanner.scan(&input), vec![osc("0", "ok")]);
+    }
+
+    #[test]
+    fn test_boundaries_only() {
+        let mut scanner = VtScanner::boundaries_only();
+        assert!(scanner.at_ground());
+        assert!(scanner.scan("ok\x1b]1337;File=").is_empty());
+        assert!(!scanner.at_ground());
+        assert!(scanner.buffer.is_empty());
+        scanner.scan("AAAA\x07\x1b[?25");
+        assert!(!scanner.at_ground());
+        assert_eq!(scanner.scan("l"), vec![csi('?', "25", 'l')]);
+        assert!(scanner.at_ground());
+    }
+}
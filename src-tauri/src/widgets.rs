@@ -0,0 +1,481 @@
+//! Menu bar script widgets
+//!
+//! `menu_widgets` in the settings lists small scripts whose output is shown
+//! in the tray, like xbar or SwiftBar plugins. Each script runs every
+//! `interval_seconds`, with the same validation and environment as
+//! `execute_command` but without being added to the history. Output follows
+//! the xbar layout: the first line is the widget's title and lines after a
+//! `---` line are its menu items, with further `---` lines as separators.
+//! xbar parameters after ` | ` are dropped. A widget placed in the title has
+//! its title shown next to the tray icon while no progress is reported there.
+
+use crate::askpass;
+use crate::commands;
+use crate::progress;
+use crate::settings::SettingsManager;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{command, AppHandle, Manager, State};
+use tracing::{debug, warn};
+
+/// How often widgets are checked for being due
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a script may run before it's killed
+const RUN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Output read from a script beyond this is ignored
+const MAX_OUTPUT_BYTES: u64 = 64 * 1024;
+
+/// Menu items kept per widget
+const MAX_ITEMS: usize = 30;
+
+/// Characters kept of a title or menu item
+const MAX_TEXT_CHARS: usize = 80;
+
+/// Refresh interval bounds, in seconds
+pub const MIN_INTERVAL_SECONDS: u64 = 5;
+pub const MAX_INTERVAL_SECONDS: u64 = 24 * 60 * 60;
+
+/// Menu ID of widget items; clicking one does nothing
+pub const MENU_ID: &str = "widget";
+
+fn default_interval_seconds() -> u64 {
+    60
+}
+
+/// Where a widget's title is shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WidgetPlacement {
+    /// A tray menu entry, with the items in a submenu
+    #[default]
+    Menu,
+    /// Next to the tray icon
+    Title,
+}
+
+/// A `menu_widgets` entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MenuWidget {
+    pub name: String,
+    /// Script or program to run, with `args`
+    pub cmd: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Seconds between runs (5 - 86400)
+    #[serde(default = "default_interval_seconds")]
+    pub interval_seconds: u64,
+    #[serde(default)]
+    pub placement: WidgetPlacement,
+}
+
+/// Drop widgets without a name or command, or named like an earlier one, and
+/// clamp their intervals
+pub fn sanitize(widgets: &mut Vec<MenuWidget>) {
+    let mut seen = Vec::new();
+    widgets.retain(|widget| {
+        let name = widget.name.trim().to_string();
+        if name.is_empty() || widget.cmd.trim().is_empty() || seen.contains(&name) {
+            return false;
+        }
+        seen.push(name);
+        true
+    });
+    for widget in widgets.iter_mut() {
+        widget.name = widget.name.trim().to_string();
+        widget.interval_seconds = widget
+            .interval_seconds
+            .clamp(MIN_INTERVAL_SECONDS, MAX_INTERVAL_SECONDS);
+    }
+}
+
+/// The latest run of a widget
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WidgetOutput {
+    pub name: String,
+    pub placement: WidgetPlacement,
+    /// First line of the output; the widget's name if the run failed or
+    /// printed nothing
+    pub title: String,
+    /// Menu items, grouped between separators
+    pub sections: Vec<Vec<String>>,
+    /// Why the last run failed
+    pub error: Option<String>,
+    /// Unix timestamp of the run, in milliseconds
+    pub updated_at: u64,
+}
+
+/// Text of an xbar output line: parameters dropped, control characters
+/// removed, shortened to `MAX_TEXT_CHARS`
+fn line_text(line: &str) -> String {
+    let text = line.split(" | ").next().unwrap_or_default();
+    let text: String = text.chars().filter(|c| !c.is_control()).collect();
+    let text = text.trim();
+    if text.chars().count() > MAX_TEXT_CHARS {
+        let mut short: String = text.chars().take(MAX_TEXT_CHARS - 1).collect();
+        short.push('…');
+        short
+    } else {
+        text.to_string()
+    }
+}
+
+/// Split xbar output into its title and menu sections
+pub fn parse_output(output: &str) -> (String, Vec<Vec<String>>) {
+    let mut lines = output.lines();
+    let title = lines
+        .by_ref()
+        .take_while(|line| line.trim() != "---")
+        .map(line_text)
+        .find(|text| !text.is_empty())
+        .unwrap_or_default();
+
+    let mut sections = vec![Vec::new()];
+    let mut count = 0;
+    for line in lines {
+        if line.trim() == "---" {
+            sections.push(Vec::new());
+            continue;
+        }
+        let text = line_text(line);
+        if text.is_empty() || count == MAX_ITEMS {
+            continue;
+        }
+        count += 1;
+        if let Some(section) = sections.last_mut() {
+            section.push(text);
+        }
+    }
+    sections.retain(|section| !section.is_empty());
+    (title, sections)
+}
+
+/// Run a widget's script, returning its standard output
+fn run(widget: &MenuWidget) -> Result<String, String> {
+    commands::validate_command(&widget.cmd)?;
+    commands::validate_args(&widget.args)?;
+
+    let mut child = Command::new(&widget.cmd)
+        .args(&widget.args)
+        .envs(askpass::helper_path().map(|path| (askpass::ASKPASS_ENV_VAR, path)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| commands::spawn_error(&widget.cmd, &e))?;
+
+    // Read on another thread so a script that never exits can be killed
+    let stdout = child.stdout.take();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(stdout) = stdout {
+            let _ = stdout.take(MAX_OUTPUT_BYTES).read_to_end(&mut output);
+        }
+        let _ = sender.send(output);
+    });
+
+    let output = match receiver.recv_timeout(RUN_TIMEOUT) {
+        Ok(output) => output,
+        Err(_) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("Did not finish within {}s", RUN_TIMEOUT.as_secs()));
+        }
+    };
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to wait for command: {}", e))?;
+    if !status.success() {
+        return Err(format!("Exited with {}", status));
+    }
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Output of a widget run
+fn widget_output(widget: &MenuWidget, result: Result<String, String>) -> WidgetOutput {
+    let (title, sections, error) = match result {
+        Ok(stdout) => {
+            let (title, sections) = parse_output(&stdout);
+            (title, sections, None)
+        }
+        Err(e) => (String::new(), Vec::new(), Some(e)),
+    };
+    WidgetOutput {
+        name: widget.name.clone(),
+        placement: widget.placement,
+        title: if title.is_empty() {
+            widget.name.clone()
+        } else {
+            title
+        },
+        sections,
+        error,
+        updated_at: now_millis(),
+    }
+}
+
+struct WidgetState {
+    widget: MenuWidget,
+    output: WidgetOutput,
+    last_run: Instant,
+}
+
+/// Latest output of each configured widget
+#[derive(Default)]
+pub struct WidgetManager {
+    states: Mutex<HashMap<String, WidgetState>>,
+}
+
+impl WidgetManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Widgets that are new, changed or due for a run
+    fn due(&self, widgets: &[MenuWidget]) -> Vec<MenuWidget> {
+        let states = self.states.lock();
+        widgets
+            .iter()
+            .filter(|widget| match states.get(&widget.name) {
+                Some(state) => {
+                    state.widget != **widget
+                        || state.last_run.elapsed() >= Duration::from_secs(widget.interval_seconds)
+                }
+                None => true,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Store a run's output, returning whether what the tray shows changed
+    fn record(&self, widget: MenuWidget, output: WidgetOutput) -> bool {
+        let mut states = self.states.lock();
+        let changed = states.get(&widget.name).is_none_or(|state| {
+            let old = &state.output;
+            (old.placement, &old.title, &old.sections, &old.error)
+                != (
+                    output.placement,
+                    &output.title,
+                    &output.sections,
+                    &output.error,
+                )
+        });
+        states.insert(
+            widget.name.clone(),
+            WidgetState {
+                widget,
+                output,
+                last_run: Instant::now(),
+            },
+        );
+        changed
+    }
+
+    /// Forget widgets no longer configured, returning whether any were
+    fn retain(&self, widgets: &[MenuWidget]) -> bool {
+        let mut states = self.states.lock();
+        let before = states.len();
+        states.retain(|name, _| widgets.iter().any(|widget| widget.name == *name));
+        states.len() != before
+    }
+
+    /// Outputs of the configured widgets that have run, in settings order
+    pub fn outputs(&self, widgets: &[MenuWidget]) -> Vec<WidgetOutput> {
+        let states = self.states.lock();
+        widgets
+            .iter()
+            .filter_map(|widget| states.get(&widget.name))
+            .map(|state| state.output.clone())
+            .collect()
+    }
+}
+
+/// Configured widgets' outputs; empty when widgets aren't available
+pub fn list(app: &AppHandle) -> Vec<WidgetOutput> {
+    let (Some(manager), Some(settings_manager)) = (
+        app.try_state::<Arc<WidgetManager>>(),
+        app.try_state::<Arc<SettingsManager>>(),
+    ) else {
+        return Vec::new();
+    };
+    manager.outputs(&settings_manager.get().menu_widgets)
+}
+
+/// Titles of widgets placed in the tray title, joined for display
+pub fn tray_title(app: &AppHandle) -> Option<String> {
+    let titles: Vec<String> = list(app)
+        .into_iter()
+        .filter(|output| output.placement == WidgetPlacement::Title)
+        .map(|output| output.title)
+        .collect();
+    (!titles.is_empty()).then(|| titles.join("  "))
+}
+
+/// Start running widgets in the background
+pub fn start(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(CHECK_INTERVAL);
+        refresh(&app);
+    });
+}
+
+fn refresh(app: &AppHandle) {
+    let (Some(manager), Some(settings_manager)) = (
+        app.try_state::<Arc<WidgetManager>>(),
+        app.try_state::<Arc<SettingsManager>>(),
+    ) else {
+        return;
+    };
+    let widgets = settings_manager.get().menu_widgets;
+    let mut changed = manager.retain(&widgets);
+    for widget in manager.due(&widgets) {
+        let result = run(&widget);
+        if let Err(e) = &result {
+            warn!(widget = %widget.name, "Widget script failed: {}", e);
+        }
+        let output = widget_output(&widget, result);
+        changed |= manager.record(widget, output);
+    }
+    if changed {
+        debug!("Menu widgets changed");
+        if let Err(e) = crate::refresh_tray_menu(app) {
+            warn!("Failed to update tray menu: {}", e);
+        }
+        progress::refresh_indicators(app);
+    }
+}
+
+/// Latest output of the configured widgets
+#[command]
+pub fn get_menu_widgets(
+    manager: State<'_, Arc<WidgetManager>>,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+) -> Vec<WidgetOutput> {
+    manager.outputs(&settings_manager.get().menu_widgets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widget(name: &str, cmd: &str) -> MenuWidget {
+        MenuWidget {
+            name: name.to_string(),
+            cmd: cmd.to_string(),
+            args: Vec::new(),
+            interval_seconds: default_interval_seconds(),
+            placement: WidgetPlacement::Menu,
+        }
+    }
+
+    #[test]
+    fn test_parse_output() {
+        let output =
+            "☀ 21°C | color=orange\n---\nHumidity 40%\nWind 5 km/h\n---\nRefresh | refresh=true\n";
+        let (title, sections) = parse_output(output);
+        assert_eq!(title, "☀ 21°C");
+        assert_eq!(
+            sections,
+            vec![
+                vec!["Humidity 40%".to_string(), "Wind 5 km/h".to_string()],
+                vec!["Refresh".to_string()],
+            ]
+        );
+
+        assert_eq!(parse_output("3 PRs\n"), ("3 PRs".to_string(), Vec::new()));
+        assert_eq!(parse_output(""), (String::new(), Vec::new()));
+        // Only the first header line is the title
+        assert_eq!(parse_output("\nA\nB\n").0, "A");
+    }
+
+    #[test]
+    fn test_parse_output_limits() {
+        let long = "x".repeat(200);
+        let (title, _) = parse_output(&format!("{}\x1b[0m\n", long));
+        assert_eq!(title.chars().count(), MAX_TEXT_CHARS);
+        assert!(title.ends_with('…'));
+
+        let items: String = (0..100).map(|i| format!("{}\n", i)).collect();
+        let (_, sections) = parse_output(&format!("T\n---\n{}", items));
+        assert_eq!(sections[0].len(), MAX_ITEMS);
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let mut widgets = vec![
+            widget(" cpu ", "/usr/local/bin/cpu.sh"),
+            widget("", "/bin/date"),
+            widget("date", " "),
+            widget("cpu", "/bin/date"),
+            MenuWidget {
+                interval_seconds: 1,
+                ..widget("fast", "/bin/date")
+            },
+        ];
+        sanitize(&mut widgets);
+        let names: Vec<&str> = widgets.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, vec!["cpu", "fast"]);
+        assert_eq!(widgets[1].interval_seconds, MIN_INTERVAL_SECONDS);
+    }
+
+    #[test]
+    fn test_due_and_record() {
+        let manager = WidgetManager::new();
+        let date = widget("date", "/bin/date");
+        assert_eq!(manager.due(std::slice::from_ref(&date)).len(), 1);
+
+        let output = widget_output(&date, Ok("Mon\n".to_string()));
+        assert!(manager.record(date.clone(), output.clone()));
+        assert!(manager.due(std::slice::from_ref(&date)).is_empty());
+        // The same output again doesn't change the tray
+        assert!(!manager.record(date.clone(), output));
+
+        // A changed widget runs again right away
+        let changed = MenuWidget {
+            placement: WidgetPlacement::Title,
+            ..date.clone()
+        };
+        assert_eq!(manager.due(std::slice::from_ref(&changed)).len(), 1);
+
+        assert_eq!(manager.outputs(std::slice::from_ref(&date))[0].title, "Mon");
+        assert!(!manager.retain(std::slice::from_ref(&date)));
+        assert!(manager.retain(&[]));
+        assert!(manager.outputs(&[date]).is_empty());
+    }
+
+    #[test]
+    fn test_failed_run_shows_name() {
+        let date = widget("date", "/bin/date");
+        let output = widget_output(&date, Err("Exited with 1".to_string()));
+        assert_eq!(output.title, "date");
+        assert_eq!(output.error.as_deref(), Some("Exited with 1"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run() {
+        let echo = MenuWidget {
+            args: vec!["hi".to_string()],
+            ..widget("echo", "echo")
+        };
+        assert_eq!(run(&echo).unwrap(), "hi\n");
+        assert!(run(&widget("false", "false")).is_err());
+        assert!(run(&widget("bad", "echo; rm")).is_err());
+    }
+}
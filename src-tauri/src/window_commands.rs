@@ -2,10 +2,126 @@
 //!
 //! Provides commands to query screen dimensions and adjust window size
 //! to ensure the window fits on small screens.
+//!
+//! All math is done in logical pixels. Monitor bounds are converted with that
+//! monitor's scale factor and window bounds with the window's, since Tauri
+//! reports both in physical pixels of their own display; mixing them
+//! misplaces the window on scaled (e.g. 1.25x or 1.5x) external displays.
+//! Sizes and positions are set as logical values for the same reason.
 
-use tauri::{command, AppHandle, PhysicalSize, Runtime, WebviewWindow};
+use crate::settings::SettingsManager;
+use crate::window_presets;
+use std::sync::Arc;
+use tauri::{
+    command, AppHandle, LogicalPosition, LogicalSize, Monitor, Runtime, State, WebviewWindow,
+};
 use tracing::debug;
 
+/// A rectangle in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogicalRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl LogicalRect {
+    /// Bounds of a monitor, converted with the monitor's own scale factor
+    pub fn of_monitor(monitor: &Monitor) -> Self {
+        let scale = monitor.scale_factor();
+        let position = monitor.position();
+        let size = monitor.size();
+        Self {
+            x: position.x as f64 / scale,
+            y: position.y as f64 / scale,
+            width: size.width as f64 / scale,
+            height: size.height as f64 / scale,
+        }
+    }
+
+    /// Bounds of a window, converted with the window's scale factor
+    pub fn of_window<R: Runtime>(window: &WebviewWindow<R>) -> Result<Self, String> {
+        let scale = window
+            .scale_factor()
+            .map_err(|e| format!("Failed to get window scale factor: {}", e))?;
+        let position = window
+            .outer_position()
+            .map_err(|e| format!("Failed to get window position: {}", e))?;
+        let size = window
+            .outer_size()
+            .map_err(|e| format!("Failed to get window size: {}", e))?;
+        Ok(Self {
+            x: position.x as f64 / scale,
+            y: position.y as f64 / scale,
+            width: size.width as f64 / scale,
+            height: size.height as f64 / scale,
+        })
+    }
+
+    /// Top-left corner moving `window` the least to fit inside this rectangle;
+    /// a window larger than it is aligned to the left and top edges
+    pub fn clamp(&self, window: LogicalRect) -> (f64, f64) {
+        let x = window.x.min(self.x + self.width - window.width).max(self.x);
+        let y = window
+            .y
+            .min(self.y + self.height - window.height)
+            .max(self.y);
+        (x, y)
+    }
+
+    fn area(&self) -> f64 {
+        self.width.max(0.0) * self.height.max(0.0)
+    }
+
+    fn intersection_area(&self, other: &LogicalRect) -> f64 {
+        let width = (self.x + self.width).min(other.x + other.width) - self.x.max(other.x);
+        let height = (self.y + self.height).min(other.y + other.height) - self.y.max(other.y);
+        width.max(0.0) * height.max(0.0)
+    }
+
+    /// Distance from the rectangle to a point (0 inside it)
+    fn distance_to(&self, x: f64, y: f64) -> f64 {
+        let dx = (self.x - x).max(x - (self.x + self.width)).max(0.0);
+        let dy = (self.y - y).max(y - (self.y + self.height)).max(0.0);
+        dx.hypot(dy)
+    }
+}
+
+/// Where to move a window so it's on screen, or `None` if it already is.
+///
+/// A window inside the union of the screens is left alone, even when it
+/// spans two of them. Otherwise it's clamped into the screen it overlaps
+/// most, or the one nearest to its center when it's off every screen.
+pub fn visible_position(window: LogicalRect, screens: &[LogicalRect]) -> Option<(f64, f64)> {
+    // Screens don't overlap, so the covered area adds up
+    let covered: f64 = screens
+        .iter()
+        .map(|screen| screen.intersection_area(&window))
+        .sum();
+    if covered >= window.area() - 1.0 {
+        return None;
+    }
+
+    let center_x = window.x + window.width / 2.0;
+    let center_y = window.y + window.height / 2.0;
+    let nearest = screens.iter().max_by(|a, b| {
+        let key = |screen: &LogicalRect| {
+            (
+                screen.intersection_area(&window),
+                -screen.distance_to(center_x, center_y),
+            )
+        };
+        key(a)
+            .partial_cmp(&key(b))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    })?;
+
+    let (x, y) = nearest.clamp(window);
+    // Ignore sub-pixel differences from rounding
+    ((x - window.x).abs() >= 0.5 || (y - window.y).abs() >= 0.5).then_some((x, y))
+}
+
 /// Screen size information in logical pixels
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ScreenInfo {
@@ -19,6 +135,45 @@ pub struct ScreenInfo {
     pub available_width: f64,
     /// Available height (excluding menubar/dock)
     pub available_height: f64,
+    /// Left edge of the screen in logical pixels
+    pub x: f64,
+    /// Top edge of the screen in logical pixels
+    pub y: f64,
+}
+
+impl ScreenInfo {
+    fn from_monitor(monitor: &Monitor) -> Self {
+        let bounds = LogicalRect::of_monitor(monitor);
+
+        // On macOS, account for menubar and dock
+        // Menubar is typically 25px, dock can be 50-100px depending on size
+        // Use conservative estimate: remove 25px from height for menubar
+        #[cfg(target_os = "macos")]
+        let available_height = (bounds.height - 25.0).max(bounds.height * 0.9);
+        // On other platforms, assume full screen is available
+        #[cfg(not(target_os = "macos"))]
+        let available_height = bounds.height;
+
+        Self {
+            width: bounds.width,
+            height: bounds.height,
+            scale_factor: monitor.scale_factor(),
+            available_width: bounds.width,
+            available_height,
+            x: bounds.x,
+            y: bounds.y,
+        }
+    }
+
+    /// Area the window may occupy
+    pub fn available_rect(&self) -> LogicalRect {
+        LogicalRect {
+            x: self.x,
+            y: self.y,
+            width: self.available_width,
+            height: self.available_height,
+        }
+    }
 }
 
 /// Get the screen information for the screen where the window is located
@@ -33,55 +188,18 @@ pub fn get_screen_info<R: Runtime>(
         .map_err(|e| format!("Failed to get current monitor: {}", e))?
         .ok_or("No monitor found")?;
 
-    let scale = current_monitor.scale_factor();
-    let size = current_monitor.size();
-    let position = current_monitor.position();
-
-    // Calculate logical dimensions
-    let width = size.width as f64 / scale;
-    let height = size.height as f64 / scale;
-
-    #[cfg(target_os = "macos")]
-    {
-        // On macOS, account for menubar and dock
-        // Menubar is typically 25px, dock can be 50-100px depending on size
-        // Use conservative estimate: remove 25px from height for menubar
-        let available_width = width;
-        let available_height = (height - 25.0).max(height * 0.9);
-
-        debug!(
-            "Screen info: {}x{} (available: {}x{}) at ({}, {}), scale: {}",
-            width, height, available_width, available_height, position.x, position.y, scale
-        );
-
-        Ok(ScreenInfo {
-            width,
-            height,
-            scale_factor: scale,
-            available_width,
-            available_height,
-        })
-    }
-
-    #[cfg(not(target_os = "macos"))]
-    {
-        let current_monitor = window
-            .current_monitor()
-            .map_err(|e| format!("Failed to get current monitor: {}", e))?
-            .ok_or("No monitor found")?;
-
-        let scale = current_monitor.scale_factor();
-        let size = current_monitor.size();
-
-        // On other platforms, assume full screen is available
-        Ok(ScreenInfo {
-            width: size.width as f64 / scale,
-            height: size.height as f64 / scale,
-            scale_factor: scale,
-            available_width: size.width as f64 / scale,
-            available_height: size.height as f64 / scale,
-        })
-    }
+    let info = ScreenInfo::from_monitor(&current_monitor);
+    debug!(
+        "Screen info: {}x{} (available: {}x{}) at ({}, {}), scale: {}",
+        info.width,
+        info.height,
+        info.available_width,
+        info.available_height,
+        info.x,
+        info.y,
+        info.scale_factor
+    );
+    Ok(info)
 }
 
 /// Adjust window size to fit within screen bounds
@@ -105,9 +223,9 @@ pub fn adjust_window_size<R: Runtime>(
     let new_width = max_width.min(safe_max_width);
     let new_height = max_height.min(safe_max_height);
 
-    // Convert to physical pixels
-    let physical_width = (new_width * screen_info.scale_factor) as u32;
-    let physical_height = (new_height * screen_info.scale_factor) as u32;
+    // Physical size on this screen, for the caller
+    let physical_width = (new_width * screen_info.scale_factor).round() as u32;
+    let physical_height = (new_height * screen_info.scale_factor).round() as u32;
 
     debug!(
         "Adjusting window size: logical={}x{}, physical={}x{}",
@@ -116,77 +234,232 @@ pub fn adjust_window_size<R: Runtime>(
 
     // Set window size
     window
-        .set_size(PhysicalSize::new(physical_width, physical_height))
+        .set_size(LogicalSize::new(new_width, new_height))
         .map_err(|e| format!("Failed to set window size: {}", e))?;
 
     Ok((physical_width, physical_height))
 }
 
-/// Ensure window is positioned within visible screen bounds
+/// Ensure window is positioned within visible screen bounds, considering all
+/// displays (see `visible_position`)
 #[command]
 pub fn ensure_window_visible<R: Runtime>(
     _app: AppHandle<R>,
     window: WebviewWindow<R>,
 ) -> Result<(), String> {
-    let screen_info = get_screen_info(_app.clone(), window.clone())?;
-    let outer_position = window
-        .outer_position()
-        .map_err(|e| format!("Failed to get window position: {}", e))?;
-    let outer_size = window
-        .outer_size()
-        .map_err(|e| format!("Failed to get window size: {}", e))?;
+    let monitors = window
+        .available_monitors()
+        .map_err(|e| format!("Failed to get monitors: {}", e))?;
+    // Available areas (below the menubar) of every display
+    let mut screens: Vec<LogicalRect> = monitors
+        .iter()
+        .map(|monitor| ScreenInfo::from_monitor(monitor).available_rect())
+        .collect();
+    if screens.is_empty() {
+        screens.push(get_screen_info(_app.clone(), window.clone())?.available_rect());
+    }
+    let bounds = LogicalRect::of_window(&window)?;
+
+    if let Some((new_x, new_y)) = visible_position(bounds, &screens) {
+        debug!(
+            "Adjusting window position: from ({}, {}) to ({}, {})",
+            bounds.x, bounds.y, new_x, new_y
+        );
+        window
+            .set_position(LogicalPosition::new(new_x, new_y))
+            .map_err(|e| format!("Failed to set window position: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Move and resize the window to `frame`; synchronous on macOS, so the new
+/// frame can be saved right away
+fn set_window_frame(window: &WebviewWindow, frame: LogicalRect) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2_app_kit::{NSScreen, NSWindow};
+        use objc2_foundation::{MainThreadMarker, NSPoint, NSRect, NSSize};
+
+        let mtm = MainThreadMarker::new().ok_or("MainThreadMarker not available")?;
+        // Logical coordinates are points with the origin at the top-left of the
+        // primary screen; NSWindow frames start at its bottom-left
+        let primary_height = NSScreen::screens(mtm)
+            .iter()
+            .next()
+            .map(|screen| screen.frame().size.height)
+            .ok_or("No screen found")?;
+        let ns_window = window
+            .ns_window()
+            .map_err(|e| format!("Failed to get NSWindow: {}", e))?
+            as *mut NSWindow;
+        let ns_window =
+            std::ptr::NonNull::new(ns_window).ok_or("NSWindow pointer is null".to_string())?;
+        let rect = NSRect::new(
+            NSPoint::new(frame.x, primary_height - frame.y - frame.height),
+            NSSize::new(frame.width, frame.height),
+        );
+        // SAFETY: the pointer comes from Tauri's live window and we're on the main thread
+        unsafe { ns_window.as_ref().setFrame_display(rect, true) };
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        window
+            .set_size(LogicalSize::new(frame.width, frame.height))
+            .map_err(|e| format!("Failed to set window size: {}", e))?;
+        window
+            .set_position(LogicalPosition::new(frame.x, frame.y))
+            .map_err(|e| format!("Failed to set window position: {}", e))
+    }
+}
+
+/// Move and resize the window to `frame`, kept on screen, and save it for
+/// the screen
+pub fn place_window(window: &WebviewWindow, frame: LogicalRect) -> Result<(), String> {
+    set_window_frame(window, frame)?;
+    ensure_window_visible(window.app_handle().clone(), window.clone())?;
+    crate::save_window_config(window)
+}
 
-    let scale = screen_info.scale_factor;
+/// Resize the window to the next size preset, keeping its top-left corner
+/// where possible, and save the size for the screen. Returns the preset name.
+#[command]
+pub fn cycle_window_size(
+    app: AppHandle,
+    window: WebviewWindow,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+) -> Result<String, String> {
+    let settings = settings_manager.get();
+    let screen_info = get_screen_info(app, window.clone())?;
+    let bounds = LogicalRect::of_window(&window)?;
 
-    // Convert to logical coordinates
-    let x = outer_position.x as f64 / scale;
-    let y = outer_position.y as f64 / scale;
-    let width = outer_size.width as f64 / scale;
-    let height = outer_size.height as f64 / scale;
+    let (preset, (width, height)) = window_presets::next_preset(
+        &settings.window_size_presets,
+        (bounds.width, bounds.height),
+        (screen_info.available_width, screen_info.available_height),
+        settings.font_size,
+    )
+    .ok_or("No window size presets")?;
 
-    // Check if window is outside visible bounds
-    let mut new_x = x;
-    let mut new_y = y;
-    let mut adjusted = false;
+    let resized = LogicalRect {
+        width,
+        height,
+        ..bounds
+    };
+    let (x, y) = screen_info.available_rect().clamp(resized);
+    debug!(
+        "Applying window size preset '{}': {}x{} at ({}, {})",
+        preset.name(),
+        width,
+        height,
+        x,
+        y
+    );
+    set_window_frame(&window, LogicalRect { x, y, ..resized })?;
+    crate::save_window_config(&window)?;
+    Ok(preset.name().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> LogicalRect {
+        LogicalRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_clamp_keeps_visible_window() {
+        let screen = rect(0.0, 0.0, 1440.0, 875.0);
+        assert_eq!(screen.clamp(rect(100.0, 30.0, 800.0, 600.0)), (100.0, 30.0));
+    }
 
-    // Ensure window doesn't go off right edge
-    if x + width > screen_info.available_width {
-        new_x = screen_info.available_width - width;
-        adjusted = true;
+    #[test]
+    fn test_clamp_on_offset_screen() {
+        // External display right of a 2560-point-wide primary, 2560x1440 at 1.5x
+        // (3840x2160 physical pixels at physical x 3840)
+        let screen = rect(2560.0, 0.0, 2560.0, 1415.0);
+        // Must not be snapped back onto the primary display
+        assert_eq!(
+            screen.clamp(rect(3000.0, 100.0, 800.0, 600.0)),
+            (3000.0, 100.0)
+        );
+        // Off the right and bottom edges
+        assert_eq!(
+            screen.clamp(rect(4900.0, 1300.0, 800.0, 600.0)),
+            (4320.0, 815.0)
+        );
+        // Off the left edge
+        assert_eq!(
+            screen.clamp(rect(2400.0, 100.0, 800.0, 600.0)),
+            (2560.0, 100.0)
+        );
     }
 
-    // Ensure window doesn't go off left edge
-    if new_x < 0.0 {
-        new_x = 0.0;
-        adjusted = true;
+    #[test]
+    fn test_clamp_window_larger_than_screen() {
+        let screen = rect(-1280.0, -200.0, 1280.0, 800.0);
+        assert_eq!(
+            screen.clamp(rect(-1000.0, 0.0, 1600.0, 900.0)),
+            (-1280.0, -200.0)
+        );
     }
 
-    // Ensure window doesn't go off bottom edge
-    if y + height > screen_info.available_height {
-        new_y = screen_info.available_height - height;
-        adjusted = true;
+    fn screens() -> Vec<LogicalRect> {
+        // Laptop screen, and a 1.5x external display to its right, lower down
+        vec![
+            rect(0.0, 25.0, 1440.0, 875.0),
+            rect(1440.0, 200.0, 2560.0, 1415.0),
+        ]
     }
 
-    // Ensure window doesn't go off top edge (account for menubar)
-    if new_y < 0.0 {
-        new_y = 0.0;
-        adjusted = true;
+    #[test]
+    fn test_visible_position_across_displays() {
+        // Entirely on the external display
+        assert_eq!(
+            visible_position(rect(2000.0, 400.0, 800.0, 600.0), &screens()),
+            None
+        );
+        // Straddling the boundary, but fully on screen
+        assert_eq!(
+            visible_position(rect(1000.0, 250.0, 800.0, 600.0), &screens()),
+            None
+        );
     }
 
-    if adjusted {
-        debug!(
-            "Adjusting window position: from ({}, {}) to ({}, {})",
-            x, y, new_x, new_y
+    #[test]
+    fn test_visible_position_uses_most_overlapping_display() {
+        // Mostly on the external display, partly below it
+        assert_eq!(
+            visible_position(rect(2000.0, 1300.0, 800.0, 600.0), &screens()),
+            Some((2000.0, 1015.0))
+        );
+        // Mostly on the laptop, hanging into the gap above the external display
+        assert_eq!(
+            visible_position(rect(1000.0, 25.0, 800.0, 600.0), &screens()),
+            Some((640.0, 25.0))
         );
-        let physical_x = (new_x * scale) as i32;
-        let physical_y = (new_y * scale) as i32;
-        window
-            .set_position(tauri::Position::Physical(tauri::PhysicalPosition {
-                x: physical_x,
-                y: physical_y,
-            }))
-            .map_err(|e| format!("Failed to set window position: {}", e))?;
     }
 
-    Ok(())
+    #[test]
+    fn test_visible_position_off_every_display() {
+        // Far right of everything: nearest is the external display
+        assert_eq!(
+            visible_position(rect(5000.0, 500.0, 800.0, 600.0), &screens()),
+            Some((3200.0, 500.0))
+        );
+        // Above the laptop screen
+        assert_eq!(
+            visible_position(rect(100.0, -900.0, 800.0, 600.0), &screens()),
+            Some((100.0, 25.0))
+        );
+        assert_eq!(visible_position(rect(0.0, 0.0, 10.0, 10.0), &[]), None);
+    }
 }
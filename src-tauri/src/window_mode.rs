@@ -0,0 +1,206 @@
+//! Window modes
+//!
+//! Besides the terminal panel, the window can be a one-line command bar: an
+//! input row backed by `execute_command_stream` and the command history. The
+//! bar expands to the panel's size when the command prints output and
+//! collapses back afterwards.
+//!
+//! Each mode keeps its own per-screen window config (see `screen_id`), so
+//! resizing the command bar doesn't change the panel. The bar's height is
+//! fixed; only its width and position are remembered.
+
+use crate::events;
+use crate::screen_config::{ScreenId, WindowConfig};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::{command, AppHandle, Manager, State, WebviewWindow};
+use tracing::{debug, warn};
+
+/// Height of the command bar in logical pixels
+pub const COMMAND_BAR_HEIGHT: f64 = 56.0;
+
+/// What the window is showing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowMode {
+    /// The terminal panel with tabs
+    #[default]
+    Panel,
+    /// Just the command input row
+    CommandBar,
+    /// The command bar grown to show a command's output
+    CommandBarExpanded,
+}
+
+/// Something that moves the window between modes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModeTransition {
+    OpenCommandBar,
+    /// A command run from the command bar printed output
+    Output,
+    /// Back to the input row after reading the output
+    Collapse,
+    OpenPanel,
+}
+
+impl WindowMode {
+    /// Mode after `transition`; transitions that don't apply leave it unchanged
+    pub fn next(self, transition: ModeTransition) -> WindowMode {
+        match (self, transition) {
+            (_, ModeTransition::OpenCommandBar) => WindowMode::CommandBar,
+            (_, ModeTransition::OpenPanel) => WindowMode::Panel,
+            (WindowMode::CommandBar, ModeTransition::Output) => WindowMode::CommandBarExpanded,
+            (WindowMode::CommandBarExpanded, ModeTransition::Collapse) => WindowMode::CommandBar,
+            (mode, _) => mode,
+        }
+    }
+
+    /// Key of this mode's window config for a screen; the panel keeps the
+    /// plain screen ID so existing configs still apply
+    pub fn screen_id(self, screen_id: ScreenId) -> ScreenId {
+        match self {
+            WindowMode::Panel => screen_id,
+            WindowMode::CommandBar => screen_id.with_suffix("command-bar"),
+            WindowMode::CommandBarExpanded => screen_id.with_suffix("command-bar-expanded"),
+        }
+    }
+
+    /// Apply the mode's fixed dimensions to a window config
+    pub fn adjust_config(self, config: &mut WindowConfig) {
+        if self == WindowMode::CommandBar {
+            config.height = COMMAND_BAR_HEIGHT;
+        }
+    }
+}
+
+/// Payload of the `window-mode-changed` event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowModeChanged {
+    pub mode: WindowMode,
+}
+
+/// Current window mode
+#[derive(Default)]
+pub struct WindowModeManager {
+    mode: Mutex<WindowMode>,
+}
+
+impl WindowModeManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self) -> WindowMode {
+        *self.mode.lock()
+    }
+
+    fn set(&self, mode: WindowMode) {
+        *self.mode.lock() = mode;
+    }
+}
+
+/// Current mode, or the panel when the manager isn't set up
+pub fn current(app: &AppHandle) -> WindowMode {
+    app.try_state::<Arc<WindowModeManager>>()
+        .map(|manager| manager.get())
+        .unwrap_or_default()
+}
+
+#[command]
+pub fn get_window_mode(
+    mode_manager: State<'_, Arc<WindowModeManager>>,
+) -> Result<WindowMode, String> {
+    Ok(mode_manager.get())
+}
+
+/// Move the window to another mode, saving the current mode's size and
+/// position and applying the new mode's. Returns the resulting mode.
+#[command]
+pub fn transition_window_mode(
+    window: WebviewWindow,
+    mode_manager: State<'_, Arc<WindowModeManager>>,
+    transition: ModeTransition,
+) -> Result<WindowMode, String> {
+    let mode = mode_manager.get();
+    let next = mode.next(transition);
+    if next == mode {
+        return Ok(mode);
+    }
+    debug!(from = ?mode, to = ?next, "Window mode transition");
+
+    let visible = window.is_visible().unwrap_or(false);
+    if visible {
+        if let Err(e) = crate::save_window_config(&window) {
+            warn!("Failed to save window config: {}", e);
+        }
+    }
+    mode_manager.set(next);
+    if visible {
+        crate::apply_window_config(&window)?;
+    }
+    let _ = events::emit(&window, WindowModeChanged { mode: next });
+    Ok(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transitions() {
+        use ModeTransition::*;
+        use WindowMode::*;
+
+        assert_eq!(Panel.next(OpenCommandBar), CommandBar);
+        assert_eq!(CommandBar.next(Output), CommandBarExpanded);
+        assert_eq!(CommandBarExpanded.next(Output), CommandBarExpanded);
+        assert_eq!(CommandBarExpanded.next(Collapse), CommandBar);
+        assert_eq!(CommandBarExpanded.next(OpenCommandBar), CommandBar);
+        assert_eq!(CommandBarExpanded.next(OpenPanel), Panel);
+
+        // Command bar transitions don't affect the panel
+        assert_eq!(Panel.next(Output), Panel);
+        assert_eq!(Panel.next(Collapse), Panel);
+        assert_eq!(CommandBar.next(Collapse), CommandBar);
+    }
+
+    #[test]
+    fn test_per_mode_configs() {
+        let screen_id = ScreenId::from_dimensions(1440.0, 900.0);
+        assert_eq!(WindowMode::Panel.screen_id(screen_id.clone()), screen_id);
+        assert_eq!(
+            WindowMode::CommandBar.screen_id(screen_id.clone()).as_str(),
+            "1440x900#command-bar"
+        );
+        assert_eq!(
+            WindowMode::CommandBarExpanded.screen_id(screen_id).as_str(),
+            "1440x900#command-bar-expanded"
+        );
+
+        let mut config = WindowConfig {
+            width: 900.0,
+            height: 600.0,
+            x: Some(10.0),
+            y: None,
+        };
+        WindowMode::CommandBarExpanded.adjust_config(&mut config);
+        assert_eq!(config.height, 600.0);
+        WindowMode::CommandBar.adjust_config(&mut config);
+        assert_eq!(config.height, COMMAND_BAR_HEIGHT);
+        assert_eq!(config.width, 900.0);
+    }
+
+    #[test]
+    fn test_mode_json() {
+        assert_eq!(
+            serde_json::to_string(&WindowMode::CommandBarExpanded).unwrap(),
+            "\"command_bar_expanded\""
+        );
+        assert_eq!(
+            serde_json::from_str::<ModeTransition>("\"open_command_bar\"").unwrap(),
+            ModeTransition::OpenCommandBar
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! Window size presets
+//!
+//! `cycle_window_size` steps through `window_size_presets` from the settings,
+//! so the window can switch between a small command box and a full work
+//! surface. A preset is either a terminal grid, sized with the font size, or a
+//! share of the screen's available area. The resulting size is saved to the
+//! screen's config like a manual resize.
+
+use serde::{Deserialize, Serialize};
+
+/// Height of the tab bar above the terminal, in logical pixels
+const TAB_BAR_HEIGHT: f64 = 40.0;
+
+/// Horizontal and vertical padding around the terminal, in logical pixels
+const TERMINAL_PADDING: f64 = 16.0;
+
+/// Approximate cell size relative to the font size (xterm.js with line height 1.2)
+const CELL_WIDTH_RATIO: f64 = 0.6;
+const CELL_HEIGHT_RATIO: f64 = 1.2;
+
+/// Sizes closer than this (in logical pixels) count as the same preset
+const MATCH_TOLERANCE: f64 = 4.0;
+
+/// A window size to cycle through
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WindowSizePreset {
+    /// Room for a terminal grid at the current font size
+    Grid {
+        name: String,
+        columns: u16,
+        rows: u16,
+    },
+    /// Percentages of the screen's available area (10 - 100)
+    Screen {
+        name: String,
+        width_percent: u8,
+        height_percent: u8,
+    },
+}
+
+impl WindowSizePreset {
+    pub fn name(&self) -> &str {
+        match self {
+            WindowSizePreset::Grid { name, .. } | WindowSizePreset::Screen { name, .. } => name,
+        }
+    }
+
+    /// Window size in logical pixels, never larger than the available area
+    pub fn size(&self, available_width: f64, available_height: f64, font_size: u8) -> (f64, f64) {
+        let (width, height) = match *self {
+            WindowSizePreset::Grid { columns, rows, .. } => {
+                let font_size = f64::from(font_size);
+                (
+                    f64::from(columns) * font_size * CELL_WIDTH_RATIO + TERMINAL_PADDING,
+                    f64::from(rows) * font_size * CELL_HEIGHT_RATIO
+                        + TERMINAL_PADDING
+                        + TAB_BAR_HEIGHT,
+                )
+            }
+            WindowSizePreset::Screen {
+                width_percent,
+                height_percent,
+                ..
+            } => (
+                available_width * f64::from(width_percent) / 100.0,
+                available_height * f64::from(height_percent) / 100.0,
+            ),
+        };
+        (
+            width.min(available_width).round(),
+            height.min(available_height).round(),
+        )
+    }
+
+    /// Clamp the preset's numbers to sensible ranges
+    fn clamp(&mut self) {
+        match self {
+            WindowSizePreset::Grid { columns, rows, .. } => {
+                *columns = (*columns).clamp(20, 500);
+                *rows = (*rows).clamp(5, 200);
+            }
+            WindowSizePreset::Screen {
+                width_percent,
+                height_percent,
+                ..
+            } => {
+                *width_percent = (*width_percent).clamp(10, 100);
+                *height_percent = (*height_percent).clamp(10, 100);
+            }
+        }
+    }
+}
+
+/// Compact, half screen and 95% of the screen
+pub fn default_presets() -> Vec<WindowSizePreset> {
+    vec![
+        WindowSizePreset::Grid {
+            name: "compact".to_string(),
+            columns: 80,
+            rows: 24,
+        },
+        WindowSizePreset::Screen {
+            name: "half".to_string(),
+            width_percent: 50,
+            height_percent: 50,
+        },
+        WindowSizePreset::Screen {
+            name: "large".to_string(),
+            width_percent: 95,
+            height_percent: 95,
+        },
+    ]
+}
+
+/// Drop unnamed presets and clamp the rest; an empty list gets the defaults
+pub fn sanitize(presets: &mut Vec<WindowSizePreset>) {
+    presets.retain(|preset| !preset.name().trim().is_empty());
+    for preset in presets.iter_mut() {
+        preset.clamp();
+    }
+    if presets.is_empty() {
+        *presets = default_presets();
+    }
+}
+
+/// The preset after the one matching the current window size (the first
+/// preset when none matches), with its size
+pub fn next_preset(
+    presets: &[WindowSizePreset],
+    current: (f64, f64),
+    available: (f64, f64),
+    font_size: u8,
+) -> Option<(&WindowSizePreset, (f64, f64))> {
+    let size = |preset: &WindowSizePreset| preset.size(available.0, available.1, font_size);
+    let next = presets
+        .iter()
+        .position(|preset| {
+            let (width, height) = size(preset);
+            (width - current.0).abs() <= MATCH_TOLERANCE
+                && (height - current.1).abs() <= MATCH_TOLERANCE
+        })
+        .map_or(0, |index| (index + 1) % presets.len());
+    let preset = presets.get(next)?;
+    Some((preset, size(preset)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AVAILABLE: (f64, f64) = (1440.0, 875.0);
+
+    #[test]
+    fn test_preset_sizes() {
+        let presets = default_presets();
+        assert_eq!(
+            presets[0].size(AVAILABLE.0, AVAILABLE.1, 13),
+            (640.0, 430.0)
+        );
+        assert_eq!(
+            presets[1].size(AVAILABLE.0, AVAILABLE.1, 13),
+            (720.0, 438.0)
+        );
+        assert_eq!(
+            presets[2].size(AVAILABLE.0, AVAILABLE.1, 13),
+            (1368.0, 831.0)
+        );
+        // A grid never exceeds the screen
+        assert_eq!(presets[0].size(600.0, 400.0, 24), (600.0, 400.0));
+    }
+
+    #[test]
+    fn test_next_preset_cycles() {
+        let presets = default_presets();
+        let next = |current| {
+            next_preset(&presets, current, AVAILABLE, 13)
+                .map(|(preset, size)| (preset.name().to_string(), size))
+                .unwrap()
+        };
+        // A manually sized window starts at the first preset
+        assert_eq!(
+            next((900.0, 600.0)),
+            ("compact".to_string(), (640.0, 430.0))
+        );
+        assert_eq!(next((641.0, 428.0)), ("half".to_string(), (720.0, 438.0)));
+        assert_eq!(next((720.0, 438.0)), ("large".to_string(), (1368.0, 831.0)));
+        assert_eq!(
+            next((1368.0, 831.0)),
+            ("compact".to_string(), (640.0, 430.0))
+        );
+        assert!(next_preset(&[], (0.0, 0.0), AVAILABLE, 13).is_none());
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let mut presets = vec![
+            WindowSizePreset::Grid {
+                name: " ".to_string(),
+                columns: 80,
+                rows: 24,
+            },
+            WindowSizePreset::Screen {
+                name: "full".to_string(),
+                width_percent: 150,
+                height_percent: 0,
+            },
+        ];
+        sanitize(&mut presets);
+        assert_eq!(
+            presets,
+            vec![WindowSizePreset::Screen {
+                name: "full".to_string(),
+                width_percent: 100,
+                height_percent: 10,
+            }]
+        );
+
+        let mut presets = Vec::new();
+        sanitize(&mut presets);
+        assert_eq!(presets, default_presets());
+    }
+
+    #[test]
+    fn test_preset_json() {
+        let json = r#"[{"kind": "grid", "name": "small", "columns": 100, "rows": 30},
+            {"kind": "screen", "name": "tall", "width_percent": 40, "height_percent": 90}]"#;
+        let presets: Vec<WindowSizePreset> = serde_json::from_str(json).unwrap();
+        assert_eq!(presets[0].name(), "small");
+        assert_eq!(
+            presets[1],
+            WindowSizePreset::Screen {
+                name: "tall".to_string(),
+                width_percent: 40,
+                height_percent: 90,
+            }
+        );
+    }
+}
@@ -0,0 +1,492 @@
+//! Workspaces
+//!
+//! A workspace is a named project setup: the sessions to open (each with a
+//! directory, profile, startup command and environment), variables shared by
+//! all of them and, optionally, where the window goes. Workspaces are kept in
+//! the settings and edited with `save_workspace` / `delete_workspace`;
+//! `save_workspace` can record the window's current frame. `open_workspace`
+//! starts every session in the calling window, in order, and queues them for
+//! the frontend like bookmarks (`session-opened`), so they open as tabs; then
+//! it moves the window into place. Directories are checked before anything
+//! starts, and if a session fails to start the ones already started are
+//! closed, so a workspace opens completely or not at all.
+
+use crate::launch_command;
+use crate::profiles;
+use crate::pty::{self, PtyManager, SessionOptions};
+use crate::settings::{AppSettings, SettingsManager};
+use crate::usage_stats::UsageStatsManager;
+use crate::window_commands::{self, LogicalRect};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tauri::{command, AppHandle, Manager, State, WebviewWindow};
+use tracing::{info, warn};
+
+/// Sessions a workspace opens at most
+const MAX_WORKSPACE_SESSIONS: usize = 16;
+
+/// A session opened by a workspace
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceSession {
+    /// Tab title (defaults to the workspace name)
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Starting directory (a leading `~` is the home directory)
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Profile to start with instead of the directory's
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Command typed into the shell once it starts, e.g. `npm run dev`
+    #[serde(default)]
+    pub startup_command: Option<String>,
+    /// Environment variables, set after the workspace's
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// Window frame in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceWindow {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl WorkspaceWindow {
+    fn is_valid(&self) -> bool {
+        [self.x, self.y, self.width, self.height]
+            .iter()
+            .all(|n| n.is_finite())
+            && self.width > 0.0
+            && self.height > 0.0
+    }
+}
+
+impl From<LogicalRect> for WorkspaceWindow {
+    fn from(rect: LogicalRect) -> Self {
+        Self {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+        }
+    }
+}
+
+impl From<WorkspaceWindow> for LogicalRect {
+    fn from(window: WorkspaceWindow) -> Self {
+        Self {
+            x: window.x,
+            y: window.y,
+            width: window.width,
+            height: window.height,
+        }
+    }
+}
+
+/// Named set of sessions and window placement
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Workspace {
+    pub name: String,
+    pub sessions: Vec<WorkspaceSession>,
+    /// Environment variables for all of its sessions
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Where to put the window; `None` leaves it where it is
+    #[serde(default)]
+    pub window: Option<WorkspaceWindow>,
+}
+
+/// Check a workspace before saving it, trimming its name
+pub fn validate(workspace: Workspace, settings: &AppSettings) -> Result<Workspace, String> {
+    let name = workspace.name.trim();
+    if name.is_empty() {
+        return Err("Workspace name is empty".to_string());
+    }
+    if workspace.sessions.is_empty() {
+        return Err("Workspace has no sessions".to_string());
+    }
+    if workspace.sessions.len() > MAX_WORKSPACE_SESSIONS {
+        return Err(format!(
+            "Too many sessions: {} (max {})",
+            workspace.sessions.len(),
+            MAX_WORKSPACE_SESSIONS
+        ));
+    }
+    let env_names = workspace
+        .env
+        .keys()
+        .chain(workspace.sessions.iter().flat_map(|s| s.env.keys()));
+    for env_name in env_names {
+        if !profiles::is_env_name(env_name) {
+            return Err(format!("Invalid environment variable name: {}", env_name));
+        }
+    }
+    for session in &workspace.sessions {
+        if let Some(profile) = &session.profile {
+            if profiles::find(settings, profile).is_none() {
+                return Err(format!("Profile not found: {}", profile));
+            }
+        }
+        if let Some(command) = &session.startup_command {
+            pty::validate_startup_command(command)?;
+        }
+    }
+    if workspace.window.is_some_and(|window| !window.is_valid()) {
+        return Err("Invalid window frame".to_string());
+    }
+    Ok(Workspace {
+        name: name.to_string(),
+        ..workspace
+    })
+}
+
+/// Drop unnamed workspaces, invalid variables and frames (e.g. from a
+/// hand-edited settings file)
+pub fn sanitize(workspaces: &mut Vec<Workspace>) {
+    workspaces.retain(|workspace| !workspace.name.trim().is_empty());
+    for workspace in workspaces.iter_mut() {
+        workspace.env.retain(|name, _| profiles::is_env_name(name));
+        for session in &mut workspace.sessions {
+            session.env.retain(|name, _| profiles::is_env_name(name));
+        }
+        if workspace.window.is_some_and(|window| !window.is_valid()) {
+            workspace.window = None;
+        }
+    }
+}
+
+/// Options and tab title of each session of a workspace, for the window
+/// labelled `window`; fails if a directory is missing
+pub fn session_options(
+    workspace: &Workspace,
+    window: &str,
+    home: &str,
+) -> Result<Vec<(SessionOptions, String)>, String> {
+    workspace
+        .sessions
+        .iter()
+        .map(|session| {
+            let cwd = match &session.cwd {
+                Some(cwd) => {
+                    let dir = profiles::expand_home(cwd, home);
+                    if !dir.is_absolute() || !dir.is_dir() {
+                        return Err(format!("Not a directory: {}", cwd));
+                    }
+                    Some(dir)
+                }
+                None => None,
+            };
+            let options = SessionOptions {
+                cwd,
+                window: Some(window.to_string()),
+                profile: session.profile.clone(),
+                startup_command: session
+                    .startup_command
+                    .as_deref()
+                    .map(pty::validate_startup_command)
+                    .transpose()?,
+                env: workspace
+                    .env
+                    .iter()
+                    .chain(&session.env)
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect(),
+                ..Default::default()
+            };
+            let title = session
+                .title
+                .clone()
+                .unwrap_or_else(|| workspace.name.clone());
+            Ok((options, title))
+        })
+        .collect()
+}
+
+/// Start a workspace's sessions in `window` and move the window into place,
+/// returning the session IDs
+pub fn open(
+    app: &AppHandle,
+    window: &WebviewWindow,
+    workspace: &Workspace,
+) -> Result<Vec<String>, String> {
+    let home = std::env::var("HOME").map_err(|_| "HOME is not set".to_string())?;
+    let sessions = session_options(workspace, window.label(), &home)?;
+
+    let mut session_ids = Vec::with_capacity(sessions.len());
+    for (options, title) in sessions {
+        match launch_command::start(app, options, title) {
+            Ok(session_id) => session_ids.push(session_id),
+            Err(e) => {
+                if let Some(pty_manager) = app.try_state::<Arc<PtyManager>>() {
+                    for session_id in &session_ids {
+                        let _ = pty_manager.close_session(session_id, true);
+                    }
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    if let Some(frame) = workspace.window {
+        // The sessions are open either way
+        if let Err(e) = window_commands::place_window(window, frame.into()) {
+            warn!(workspace = %workspace.name, "Failed to place the window: {}", e);
+        }
+    }
+    info!(workspace = %workspace.name, sessions = session_ids.len(), "Opened workspace");
+    Ok(session_ids)
+}
+
+#[command]
+pub fn list_workspaces(
+    settings_manager: State<'_, Arc<SettingsManager>>,
+) -> Result<Vec<Workspace>, String> {
+    Ok(settings_manager.get().workspaces)
+}
+
+/// Add a workspace, or replace the one with the same name; with
+/// `capture_window`, the window's current frame becomes its placement
+#[command]
+pub fn save_workspace(
+    window: WebviewWindow,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    workspace: Workspace,
+    capture_window: Option<bool>,
+) -> Result<Workspace, String> {
+    let mut settings = settings_manager.get();
+    let mut workspace = validate(workspace, &settings)?;
+    if capture_window.unwrap_or(false) {
+        workspace.window = Some(LogicalRect::of_window(&window)?.into());
+    }
+    match settings
+        .workspaces
+        .iter_mut()
+        .find(|w| w.name == workspace.name)
+    {
+        Some(existing) => *existing = workspace.clone(),
+        None => settings.workspaces.push(workspace.clone()),
+    }
+    settings_manager.update(settings);
+    Ok(workspace)
+}
+
+/// Remove a workspace, returning false if there was none with the name
+#[command]
+pub fn delete_workspace(
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    name: String,
+) -> Result<bool, String> {
+    let mut settings = settings_manager.get();
+    let len = settings.workspaces.len();
+    settings.workspaces.retain(|w| w.name != name);
+    let removed = settings.workspaces.len() != len;
+    if removed {
+        settings_manager.update(settings);
+    }
+    Ok(removed)
+}
+
+/// Open a workspace in the calling window, returning the IDs of its sessions
+/// in order
+#[command]
+pub fn open_workspace(
+    app: AppHandle,
+    window: WebviewWindow,
+    settings_manager: State<'_, Arc<SettingsManager>>,
+    usage_stats: State<'_, Arc<UsageStatsManager>>,
+    name: String,
+) -> Result<Vec<String>, String> {
+    let workspace = settings_manager
+        .get()
+        .workspaces
+        .into_iter()
+        .find(|w| w.name == name)
+        .ok_or_else(|| format!("Workspace not found: {}", name))?;
+    let session_ids = open(&app, &window, &workspace)?;
+    usage_stats.record_feature("workspaces")?;
+    Ok(session_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::Profile;
+    use tempfile::TempDir;
+
+    fn session(cwd: &str) -> WorkspaceSession {
+        WorkspaceSession {
+            cwd: Some(cwd.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn workspace(sessions: Vec<WorkspaceSession>) -> Workspace {
+        Workspace {
+            name: "api".to_string(),
+            sessions,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_session_options() {
+        let home = TempDir::new().unwrap();
+        std::fs::create_dir(home.path().join("api")).unwrap();
+        let home_str = home.path().to_string_lossy();
+        let mut workspace = workspace(vec![
+            WorkspaceSession {
+                title: Some("server".to_string()),
+                startup_command: Some(" npm run dev ".to_string()),
+                env: BTreeMap::from([("PORT".to_string(), "3001".to_string())]),
+                ..session("~/api")
+            },
+            WorkspaceSession::default(),
+        ]);
+        workspace.env = BTreeMap::from([
+            ("NODE_ENV".to_string(), "development".to_string()),
+            ("PORT".to_string(), "3000".to_string()),
+        ]);
+
+        let sessions = session_options(&workspace, "main", &home_str).unwrap();
+        assert_eq!(sessions.len(), 2);
+        let (options, title) = &sessions[0];
+        assert_eq!(title, "server");
+        assert_eq!(options.cwd, Some(home.path().join("api")));
+        assert_eq!(options.window.as_deref(), Some("main"));
+        assert_eq!(options.startup_command.as_deref(), Some("npm run dev"));
+        // The session's variables come last, so they win
+        assert_eq!(
+            options.env,
+            [
+                ("NODE_ENV".to_string(), "development".to_string()),
+                ("PORT".to_string(), "3000".to_string()),
+                ("PORT".to_string(), "3001".to_string()),
+            ]
+        );
+        let (options, title) = &sessions[1];
+        assert_eq!(title, "api");
+        assert_eq!(options.cwd, None);
+    }
+
+    #[test]
+    fn test_session_options_missing_directory() {
+        let home = TempDir::new().unwrap();
+        let home_str = home.path().to_string_lossy();
+        let workspace = workspace(vec![WorkspaceSession::default(), session("~/missing")]);
+        assert_eq!(
+            session_options(&workspace, "main", &home_str).unwrap_err(),
+            "Not a directory: ~/missing"
+        );
+        let workspace = self::workspace(vec![session("relative")]);
+        assert!(session_options(&workspace, "main", &home_str).is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut settings = AppSettings::default();
+        settings.profiles = vec![Profile {
+            name: "work".to_string(),
+            ..Default::default()
+        }];
+        let valid = Workspace {
+            name: " api ".to_string(),
+            sessions: vec![WorkspaceSession {
+                profile: Some("work".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert_eq!(validate(valid.clone(), &settings).unwrap().name, "api");
+
+        let invalid = |change: fn(&mut Workspace)| {
+            let mut workspace = valid.clone();
+            change(&mut workspace);
+            validate(workspace, &settings).is_err()
+        };
+        assert!(invalid(|w| w.name = " ".to_string()));
+        assert!(invalid(|w| w.sessions.clear()));
+        assert!(invalid(
+            |w| w.sessions = vec![WorkspaceSession::default(); MAX_WORKSPACE_SESSIONS + 1]
+        ));
+        assert!(invalid(|w| {
+            w.env.insert("NOT-A-NAME".to_string(), "x".to_string());
+        }));
+        assert!(invalid(|w| {
+            w.sessions[0].env.insert("1X".to_string(), "x".to_string());
+        }));
+        assert!(invalid(
+            |w| w.sessions[0].profile = Some("missing".to_string())
+        ));
+        assert!(invalid(
+            |w| w.sessions[0].startup_command = Some("a\nb".to_string())
+        ));
+        assert!(invalid(|w| {
+            w.window = Some(WorkspaceWindow {
+                x: 0.0,
+                y: 0.0,
+                width: 0.0,
+                height: 600.0,
+            })
+        }));
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let mut workspaces = vec![
+            Workspace {
+                name: " ".to_string(),
+                ..Default::default()
+            },
+            Workspace {
+                name: "api".to_string(),
+                sessions: vec![WorkspaceSession {
+                    env: BTreeMap::from([("A=B".to_string(), "x".to_string())]),
+                    ..Default::default()
+                }],
+                env: BTreeMap::from([
+                    ("".to_string(), "x".to_string()),
+                    ("PORT".to_string(), "3000".to_string()),
+                ]),
+                window: Some(WorkspaceWindow {
+                    x: f64::NAN,
+                    y: 0.0,
+                    width: 800.0,
+                    height: 600.0,
+                }),
+            },
+        ];
+        sanitize(&mut workspaces);
+        assert_eq!(
+            workspaces,
+            vec![Workspace {
+                name: "api".to_string(),
+                sessions: vec![WorkspaceSession::default()],
+                env: BTreeMap::from([("PORT".to_string(), "3000".to_string())]),
+                window: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_workspace_json() {
+        let json = r#"{"name": "api", "sessions": [{"cwd": "~/api"}, {}],
+            "window": {"x": 10, "y": 40, "width": 900, "height": 600}}"#;
+        let workspace: Workspace = serde_json::from_str(json).unwrap();
+        assert_eq!(workspace.sessions[0], session("~/api"));
+        assert_eq!(workspace.sessions[1], WorkspaceSession::default());
+        assert!(workspace.env.is_empty());
+        assert_eq!(
+            workspace.window,
+            Some(WorkspaceWindow {
+                x: 10.0,
+                y: 40.0,
+                width: 900.0,
+                height: 600.0,
+            })
+        );
+    }
+}
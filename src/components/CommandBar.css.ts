@@ -0,0 +1,63 @@
+import { globalStyle, style } from "@vanilla-extract/css";
+
+export const commandBar = style({
+  position: "absolute",
+  inset: 0,
+  zIndex: 200,
+  display: "flex",
+  flexDirection: "column",
+  background: "#1e1e1e",
+  borderRadius: "10px",
+  overflow: "hidden",
+});
+
+export const inputRow = style({
+  display: "flex",
+  alignItems: "center",
+  gap: "8px",
+  height: "56px",
+  flexShrink: 0,
+  padding: "0 16px",
+  borderBottom: "1px solid rgba(255, 255, 255, 0.08)",
+});
+
+export const prompt = style({
+  color: "rgba(168, 85, 247, 0.9)",
+  fontFamily: "monospace",
+  fontSize: "15px",
+});
+
+export const input = style({
+  flex: 1,
+  background: "transparent",
+  border: "none",
+  outline: "none",
+  color: "rgba(255, 255, 255, 0.9)",
+  fontSize: "15px",
+  fontFamily: "monospace",
+});
+
+globalStyle(`${input}::placeholder`, {
+  color: "rgba(255, 255, 255, 0.35)",
+});
+
+export const status = style({
+  color: "rgba(255, 255, 255, 0.45)",
+  fontSize: "12px",
+});
+
+export const output = style({
+  flex: 1,
+  margin: 0,
+  padding: "12px 16px",
+  overflow: "auto",
+  color: "rgba(255, 255, 255, 0.85)",
+  fontFamily: "monospace",
+  fontSize: "13px",
+  whiteSpace: "pre-wrap",
+  wordBreak: "break-all",
+});
+
+export const stderr = style({
+  color: "#f87171",
+});
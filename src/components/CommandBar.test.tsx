@@ -0,0 +1,109 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { render, screen, fireEvent, waitFor } from "@testing-library/react";
+import CommandBar from "./CommandBar";
+
+// Mock CSS modules
+vi.mock("./CommandBar.css", () => ({
+  commandBar: "command-bar",
+  inputRow: "input-row",
+  prompt: "prompt",
+  input: "input",
+  status: "status",
+  output: "output",
+  stderr: "stderr",
+}));
+
+const mockExecuteCommandStream = vi.fn();
+const mockListCommandHistory = vi.fn();
+
+vi.mock("@/lib/tauri/commands", () => ({
+  executeCommandStream: (...args: any[]) => mockExecuteCommandStream(...args),
+  listCommandHistory: (...args: any[]) => mockListCommandHistory(...args),
+}));
+
+describe("CommandBar", () => {
+  const mockOnTransition = vi.fn();
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    mockListCommandHistory.mockResolvedValue([
+      { command: "git status", exit_code: 0, finished_at: 2 },
+      { command: "ls -la", exit_code: 0, finished_at: 1 },
+    ]);
+    mockExecuteCommandStream.mockResolvedValue(undefined);
+  });
+
+  function typeCommand(command: string) {
+    const input = screen.getByLabelText("Command");
+    fireEvent.change(input, { target: { value: command } });
+    fireEvent.keyDown(input, { key: "Enter" });
+  }
+
+  it("should run the command and expand on output", async () => {
+    mockExecuteCommandStream.mockImplementation(
+      async (_cmd: string, _args: string[], onStdout: (chunk: string) => void) => {
+        onStdout("hello\n");
+      }
+    );
+    render(<CommandBar mode="command_bar" onTransition={mockOnTransition} />);
+
+    typeCommand("echo  hello");
+
+    await waitFor(() => {
+      expect(mockOnTransition).toHaveBeenCalledWith("output");
+    });
+    expect(mockExecuteCommandStream.mock.calls[0].slice(0, 2)).toEqual(["echo", ["hello"]]);
+    // stderr is shown where it was printed, not after all of stdout
+    expect(mockExecuteCommandStream.mock.calls[0][5]).toEqual({ merged: true });
+    expect(mockOnTransition).toHaveBeenCalledTimes(1);
+  });
+
+  it("should not expand for a command without output", async () => {
+    mockExecuteCommandStream.mockImplementation(
+      async (_cmd: string, _args: string[], _out: unknown, _err: unknown, onComplete: (code: number) => void) => {
+        onComplete(0);
+      }
+    );
+    render(<CommandBar mode="command_bar" onTransition={mockOnTransition} />);
+
+    typeCommand("true");
+
+    expect(await screen.findByText("Exit 0")).toBeInTheDocument();
+    expect(mockOnTransition).not.toHaveBeenCalled();
+  });
+
+  it("should show output when expanded", () => {
+    render(<CommandBar mode="command_bar_expanded" onTransition={mockOnTransition} />);
+    expect(screen.getByLabelText("Command output")).toBeInTheDocument();
+  });
+
+  it("should step through history with the arrow keys", async () => {
+    render(<CommandBar mode="command_bar" onTransition={mockOnTransition} />);
+    const input = screen.getByLabelText("Command") as HTMLInputElement;
+    await waitFor(() => {
+      expect(mockListCommandHistory).toHaveBeenCalled();
+    });
+
+    await waitFor(() => {
+      fireEvent.keyDown(input, { key: "ArrowUp" });
+      expect(input.value).toBe("git status");
+    });
+    fireEvent.keyDown(input, { key: "ArrowUp" });
+    expect(input.value).toBe("ls -la");
+    fireEvent.keyDown(input, { key: "ArrowDown" });
+    fireEvent.keyDown(input, { key: "ArrowDown" });
+    expect(input.value).toBe("");
+  });
+
+  it("should collapse, then return to the panel on Escape", () => {
+    const { rerender } = render(
+      <CommandBar mode="command_bar_expanded" onTransition={mockOnTransition} />
+    );
+    fireEvent.keyDown(screen.getByLabelText("Command"), { key: "Escape" });
+    expect(mockOnTransition).toHaveBeenLastCalledWith("collapse");
+
+    rerender(<CommandBar mode="command_bar" onTransition={mockOnTransition} />);
+    fireEvent.keyDown(screen.getByLabelText("Command"), { key: "Escape" });
+    expect(mockOnTransition).toHaveBeenLastCalledWith("open_panel");
+  });
+});
@@ -0,0 +1,151 @@
+import { executeCommandStream, listCommandHistory } from "@/lib/tauri/commands";
+import type { ModeTransition, WindowMode } from "@/lib/tauri/window";
+import { useCallback, useEffect, useRef, useState } from "react";
+import * as styles from "./CommandBar.css";
+
+const HISTORY_LIMIT = 100;
+
+interface CommandBarProps {
+  mode: WindowMode;
+  onTransition: (transition: ModeTransition) => void;
+}
+
+interface OutputChunk {
+  text: string;
+  isStderr: boolean;
+}
+
+/**
+ * One-line launcher shown in command bar mode.
+ * Runs commands with execute_command_stream (no shell), steps through the command
+ * history with the arrow keys, and asks the backend to expand the window when the
+ * command prints output. Escape collapses the output, then returns to the panel.
+ */
+export default function CommandBar({ mode, onTransition }: CommandBarProps) {
+  const inputRef = useRef<HTMLInputElement>(null);
+  const [input, setInput] = useState("");
+  const [history, setHistory] = useState<string[]>([]);
+  const [historyIndex, setHistoryIndex] = useState(-1);
+  const [output, setOutput] = useState<OutputChunk[]>([]);
+  const [running, setRunning] = useState(false);
+  const [exitCode, setExitCode] = useState<number | null>(null);
+  const expandedRef = useRef(false);
+  const expanded = mode === "command_bar_expanded";
+
+  const loadHistory = useCallback(async () => {
+    const entries = await listCommandHistory(HISTORY_LIMIT);
+    setHistory(entries.map((entry) => entry.command));
+  }, []);
+
+  useEffect(() => {
+    loadHistory();
+    inputRef.current?.focus();
+  }, [loadHistory]);
+
+  useEffect(() => {
+    expandedRef.current = expanded;
+  }, [expanded]);
+
+  const appendOutput = useCallback(
+    (text: string, isStderr: boolean) => {
+      setOutput((prev) => [...prev, { text, isStderr }]);
+      if (!expandedRef.current) {
+        expandedRef.current = true;
+        onTransition("output");
+      }
+    },
+    [onTransition]
+  );
+
+  const runCommand = useCallback(
+    async (line: string) => {
+      const [cmd, ...args] = line.trim().split(/\s+/);
+      if (!cmd) return;
+
+      setOutput([]);
+      setExitCode(null);
+      setRunning(true);
+      setHistoryIndex(-1);
+      try {
+        await executeCommandStream(
+          cmd,
+          args,
+          (chunk) => appendOutput(chunk, false),
+          (chunk) => appendOutput(chunk, true),
+          (code) => {
+            setRunning(false);
+            setExitCode(code);
+            loadHistory();
+          },
+          { merged: true }
+        );
+      } catch (error) {
+        setRunning(false);
+        appendOutput(`${error}\n`, true);
+      }
+    },
+    [appendOutput, loadHistory]
+  );
+
+  const handleKeyDown = useCallback(
+    (e: React.KeyboardEvent<HTMLInputElement>) => {
+      if (e.key === "Enter") {
+        e.preventDefault();
+        if (!running) runCommand(input);
+      } else if (e.key === "Escape") {
+        e.preventDefault();
+        if (expanded) {
+          setOutput([]);
+          setExitCode(null);
+          onTransition("collapse");
+        } else {
+          onTransition("open_panel");
+        }
+      } else if (e.key === "ArrowUp" || e.key === "ArrowDown") {
+        if (history.length === 0) return;
+        e.preventDefault();
+        const next =
+          e.key === "ArrowUp"
+            ? Math.min(historyIndex + 1, history.length - 1)
+            : Math.max(historyIndex - 1, -1);
+        setHistoryIndex(next);
+        setInput(next === -1 ? "" : history[next]);
+      }
+    },
+    [expanded, history, historyIndex, input, onTransition, runCommand, running]
+  );
+
+  return (
+    <div className={styles.commandBar} role="dialog" aria-label="Command bar">
+      <div className={styles.inputRow}>
+        <span className={styles.prompt}>❯</span>
+        <input
+          ref={inputRef}
+          className={styles.input}
+          value={input}
+          onChange={(e) => {
+            setInput(e.target.value);
+            setHistoryIndex(-1);
+          }}
+          onKeyDown={handleKeyDown}
+          placeholder="Run a command"
+          spellCheck={false}
+          aria-label="Command"
+        />
+        {running && <span className={styles.status}>Running…</span>}
+        {!running && exitCode !== null && (
+          <span className={styles.status}>Exit {exitCode}</span>
+        )}
+      </div>
+      {expanded && (
+        <pre className={styles.output} aria-label="Command output">
+          {output.map((chunk, index) => (
+            <span key={index} className={chunk.isStderr ? styles.stderr : undefined}>
+              {chunk.text}
+            </span>
+          ))}
+        </pre>
+      )}
+    </div>
+  );
+}
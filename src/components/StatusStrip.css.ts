@@ -0,0 +1,37 @@
+import { style } from "@vanilla-extract/css";
+
+export const strip = style({
+  display: "flex",
+  alignItems: "center",
+  gap: "10px",
+  width: "100vw",
+  height: "100vh",
+  padding: "0 10px",
+  background: "rgba(30, 30, 30, 0.85)",
+  border: "none",
+  borderRadius: "8px",
+  color: "rgba(255, 255, 255, 0.85)",
+  fontFamily: "monospace",
+  fontSize: "12px",
+  whiteSpace: "nowrap",
+  overflow: "hidden",
+  cursor: "pointer",
+});
+
+export const item = style({
+  overflow: "hidden",
+  textOverflow: "ellipsis",
+});
+
+export const idle = style({
+  color: "rgba(255, 255, 255, 0.4)",
+});
+
+export const error = style({
+  color: "#f87171",
+});
+
+export const bell = style({
+  marginLeft: "auto",
+  color: "#fbbf24",
+});
@@ -0,0 +1,81 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { render, screen, fireEvent, act } from "@testing-library/react";
+import StatusStrip from "./StatusStrip";
+
+// Mock CSS modules
+vi.mock("./StatusStrip.css", () => ({
+  strip: "strip",
+  item: "item",
+  idle: "idle",
+  error: "error",
+  bell: "bell",
+}));
+
+const mockGetStatusSnapshot = vi.fn();
+const mockEmit = vi.fn();
+let statusListener: ((event: { payload: unknown }) => void) | null = null;
+
+vi.mock("@/lib/tauri/window", () => ({
+  getStatusSnapshot: () => mockGetStatusSnapshot(),
+}));
+
+vi.mock("@/lib/tauri/preload", () => ({
+  emit: (...args: any[]) => mockEmit(...args),
+  listen: vi.fn(async (_event: string, handler: (event: { payload: unknown }) => void) => {
+    statusListener = handler;
+    return () => {
+      statusListener = null;
+    };
+  }),
+}));
+
+describe("StatusStrip", () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    statusListener = null;
+    mockGetStatusSnapshot.mockResolvedValue({ running_jobs: [], progress: null, bells: [] });
+  });
+
+  it("should show the app name when nothing is happening", async () => {
+    render(<StatusStrip />);
+    expect(await screen.findByText("µTerm")).toBeInTheDocument();
+  });
+
+  it("should show jobs, progress and bells from the snapshot", async () => {
+    mockGetStatusSnapshot.mockResolvedValue({
+      running_jobs: ["build"],
+      progress: { state: "normal", progress: 42 },
+      bells: ["session-1", "session-2"],
+    });
+    render(<StatusStrip />);
+
+    expect(await screen.findByText("▶ build")).toBeInTheDocument();
+    expect(screen.getByRole("progressbar")).toHaveTextContent("42%");
+    expect(screen.getByLabelText("2 bells")).toBeInTheDocument();
+    expect(screen.queryByText("µTerm")).not.toBeInTheDocument();
+  });
+
+  it("should update on status-strip-updated", async () => {
+    render(<StatusStrip />);
+    await screen.findByText("µTerm");
+
+    act(() => {
+      statusListener?.({
+        payload: {
+          running_jobs: ["test", "lint"],
+          progress: { state: "indeterminate", progress: 0 },
+          bells: [],
+        },
+      });
+    });
+
+    expect(screen.getByText("▶ 2 jobs")).toBeInTheDocument();
+    expect(screen.getByRole("progressbar")).toHaveTextContent("…");
+  });
+
+  it("should toggle the panel when clicked", async () => {
+    render(<StatusStrip />);
+    fireEvent.click(await screen.findByRole("button"));
+    expect(mockEmit).toHaveBeenCalledWith("toggle-window", {});
+  });
+});
@@ -0,0 +1,80 @@
+import { emit, listen } from "@/lib/tauri/preload";
+import { getStatusSnapshot, type StatusSnapshot } from "@/lib/tauri/window";
+import { useEffect, useState } from "react";
+import * as styles from "./StatusStrip.css";
+
+const EMPTY: StatusSnapshot = { running_jobs: [], progress: null, bells: [] };
+
+/**
+ * Contents of the always-on-top status strip window.
+ * Shows running jobs, the combined progress and sessions that rang the bell while
+ * the panel was hidden; clicking it toggles the panel like the tray icon.
+ */
+export default function StatusStrip() {
+  const [snapshot, setSnapshot] = useState<StatusSnapshot>(EMPTY);
+
+  useEffect(() => {
+    let unlistenFn: (() => void) | null = null;
+    let cancelled = false;
+
+    getStatusSnapshot().then((current) => {
+      if (!cancelled && current) setSnapshot(current);
+    });
+
+    const setupListener = async () => {
+      try {
+        const unlisten = await listen<StatusSnapshot>("status-strip-updated", (event) => {
+          setSnapshot(event.payload);
+        });
+        if (cancelled) {
+          unlisten();
+          return;
+        }
+        unlistenFn = unlisten;
+      } catch (error) {
+        console.error("[StatusStrip] Failed to setup status-strip-updated listener:", error);
+      }
+    };
+
+    setupListener();
+
+    return () => {
+      cancelled = true;
+      unlistenFn?.();
+    };
+  }, []);
+
+  const { running_jobs: jobs, progress, bells } = snapshot;
+  const idle = jobs.length === 0 && !progress && bells.length === 0;
+  const showPercent = progress && progress.state !== "indeterminate";
+
+  return (
+    <button
+      type="button"
+      className={styles.strip}
+      onClick={() => emit("toggle-window", {})}
+      title={jobs.length > 0 ? jobs.join(", ") : undefined}
+    >
+      {idle && <span className={styles.idle}>µTerm</span>}
+      {jobs.length > 0 && (
+        <span className={styles.item}>
+          ▶ {jobs.length === 1 ? jobs[0] : `${jobs.length} jobs`}
+        </span>
+      )}
+      {progress && (
+        <span
+          className={progress.state === "error" ? styles.error : styles.item}
+          role="progressbar"
+          aria-valuenow={showPercent ? progress.progress : undefined}
+        >
+          {showPercent ? `${progress.progress}%` : "…"}
+        </span>
+      )}
+      {bells.length > 0 && (
+        <span className={styles.bell} aria-label={`${bells.length} bells`}>
+          🔔 {bells.length}
+        </span>
+      )}
+    </button>
+  );
+}
@@ -1,3 +1,4 @@
+import CommandBar from "@/components/CommandBar";
 import { GearIcon } from "@/components/icons";
 import Onboarding from "@/components/Onboarding";
 import SearchBar from "@/components/SearchBar";
@@ -9,12 +10,16 @@ import { type XTerminalHandle } from "@/components/XTerminal";
 import { PaneProvider, usePaneContext } from "@/contexts/PaneContext";
 import { useTabContext } from "@/contexts/TabContext";
 import {
+  type OpenedSession,
   useFontSizeShortcuts,
+  useOpenedSessions,
   usePaneShortcuts,
   useSettings,
   useTabShortcuts,
   useTerminalSearch,
   useToast,
+  useWindowMode,
+  useWindowSizeShortcut,
 } from "@/hooks";
 import { useCallback, useEffect, useMemo, useRef, useState } from "react";
 import * as styles from "./styles.css";
@@ -23,7 +28,7 @@ function TerminalViewInner() {
   const [settingsOpen, setSettingsOpen] = useState(false);
   // Map<tabId, Map<paneId, XTerminalHandle>>
   const terminalRefs = useRef<Map<string, Map<string, XTerminalHandle>>>(new Map());
-  const { tabs, activeTabId, updateTabTitle } = useTabContext();
+  const { tabs, activeTabId, createTab, updateTabTitle } = useTabContext();
   const {
     getPaneTree,
     getActivePaneId,
@@ -65,6 +70,19 @@ function TerminalViewInner() {
     registerLayoutController(controller);
   }, [registerLayoutController]);
 
+  // Show sessions started from launch commands (`run <command>`) and bookmarks in new tabs
+  useOpenedSessions(
+    useCallback(
+      (session: OpenedSession) => {
+        const tabId = createTab();
+        const paneId = initializeTabPanes(tabId);
+        updatePaneSessionId(tabId, paneId, session.session_id);
+        updateTabTitle(tabId, session.title);
+      },
+      [createTab, initializeTabPanes, updatePaneSessionId, updateTabTitle]
+    )
+  );
+
   // Initialize pane state for each tab
   useEffect(() => {
     for (const tab of tabs) {
@@ -188,6 +206,14 @@ function TerminalViewInner() {
     onSettingsChange: handleSettingsChange,
   });
 
+  // Cmd+Shift+Enter cycles through the window size presets
+  useWindowSizeShortcut({ disabled: settingsOpen || searchOpen });
+
+  // Command bar mode (Cmd+Shift+L); the panel stays mounted underneath
+  const { mode: windowMode, transition: transitionWindowMode } = useWindowMode(
+    settingsOpen || searchOpen
+  );
+
   // NOTE: Multi-screen window size management is now handled by Rust backend
   // in src-tauri/src/lib.rs (toggle_window, apply_window_config, save_window_config)
   // This provides better performance (~2-4ms vs ~23-80ms) and eliminates visual flash
@@ -304,6 +330,9 @@ function TerminalViewInner() {
         onSettingsChange={handleSettingsChange}
       />
       {showOnboarding && <Onboarding onComplete={handleOnboardingComplete} />}
+      {windowMode !== "panel" && (
+        <CommandBar mode={windowMode} onTransition={transitionWindowMode} />
+      )}
       <ToastContainer toasts={toasts} onRemove={removeToast} />
     </main>
   );
@@ -1,4 +1,7 @@
+export { useDisplayPreferences } from "./useDisplayPreferences";
 export { useFontSizeShortcuts } from "./useFontSizeShortcuts";
+export { useOpenedSessions } from "./useOpenedSessions";
+export type { OpenedSession } from "./useOpenedSessions";
 export { usePaneShortcuts } from "./usePaneShortcuts";
 export { useScreenSize } from "./useScreenSize";
 export type { ScreenSize } from "./useScreenSize";
@@ -6,6 +9,8 @@ export { useSettings } from "./useSettings";
 export { useTabShortcuts } from "./useTabShortcuts";
 export { useTerminalSearch } from "./useTerminalSearch";
 export { useToast, type ToastItem } from "./useToast";
+export { useWindowMode } from "./useWindowMode";
+export { useWindowSizeShortcut } from "./useWindowSizeShortcut";
 // NOTE: Window sizing hooks are deprecated - all window management now done in Rust backend
 // - useInitialWindowSize: removed, Rust handles initial sizing via apply_window_config()
 // - useMultiScreenWindowSize: removed, Rust persists per-screen config via screen_config.rs
@@ -0,0 +1,13 @@
+import { useSyncExternalStore } from "react";
+import {
+  getDisplayPreferencesSnapshot,
+  subscribeDisplayPreferences,
+} from "@/lib/displayPreferences";
+
+/**
+ * Effective Reduce Motion and Increase Contrast preferences (system values with the settings
+ * overrides applied), updated on `display-preferences-changed`
+ */
+export function useDisplayPreferences() {
+  return useSyncExternalStore(subscribeDisplayPreferences, getDisplayPreferencesSnapshot);
+}
@@ -0,0 +1,81 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { renderHook, waitFor } from "@testing-library/react";
+import { useOpenedSessions } from "./useOpenedSessions";
+
+// Mock Tauri APIs
+const mockInvoke = vi.fn();
+const mockListen = vi.fn();
+const mockUnlisten = vi.fn();
+
+vi.mock("@tauri-apps/api/core", () => ({
+  invoke: (...args: any[]) => mockInvoke(...args),
+}));
+
+vi.mock("@tauri-apps/api/event", () => ({
+  listen: (...args: any[]) => mockListen(...args),
+}));
+
+describe("useOpenedSessions", () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    mockListen.mockResolvedValue(mockUnlisten);
+    mockInvoke.mockResolvedValue([]);
+  });
+
+  it("should show sessions started before mount", async () => {
+    mockInvoke.mockResolvedValueOnce([
+      { session_id: "s1", title: "make test", command: "make test" },
+    ]);
+    const onSessionOpened = vi.fn();
+
+    renderHook(() => useOpenedSessions(onSessionOpened));
+
+    await waitFor(() => {
+      expect(onSessionOpened).toHaveBeenCalledWith({
+        session_id: "s1",
+        title: "make test",
+        command: "make test",
+      });
+    });
+    expect(mockInvoke).toHaveBeenCalledWith("take_opened_sessions");
+  });
+
+  it("should take sessions when session-opened arrives", async () => {
+    const onSessionOpened = vi.fn();
+    renderHook(() => useOpenedSessions(onSessionOpened));
+
+    await waitFor(() => {
+      expect(mockListen).toHaveBeenCalledWith("session-opened", expect.any(Function));
+    });
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledTimes(1);
+    });
+
+    mockInvoke.mockResolvedValueOnce([{ session_id: "s2", title: "code", command: null }]);
+    const handler = mockListen.mock.calls[0][1];
+    handler({ payload: { version: 3, session_id: "s2", title: "code", command: null } });
+
+    await waitFor(() => {
+      expect(onSessionOpened).toHaveBeenCalledWith({
+        session_id: "s2",
+        title: "code",
+        command: null,
+      });
+    });
+    expect(onSessionOpened).toHaveBeenCalledTimes(1);
+  });
+
+  it("should unlisten on unmount", async () => {
+    const { unmount } = renderHook(() => useOpenedSessions(vi.fn()));
+
+    await waitFor(() => {
+      expect(mockListen).toHaveBeenCalled();
+    });
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalled();
+    });
+    unmount();
+
+    expect(mockUnlisten).toHaveBeenCalled();
+  });
+});
@@ -0,0 +1,64 @@
+import { useEffect, useRef } from "react";
+
+/** A session started by a launch command (`run <command>`) or a bookmark */
+export interface OpenedSession {
+  session_id: string;
+  /** Tab title: the command, or the bookmark's name */
+  title: string;
+  /** Command the session runs; null for shells */
+  command: string | null;
+}
+
+/**
+ * Shows sessions started from launch commands and bookmarks.
+ * The backend queues them until they are taken, so sessions started before the
+ * frontend was listening (the first launch) are picked up on mount and later
+ * ones when "session-opened" arrives.
+ */
+export function useOpenedSessions(onSessionOpened: (session: OpenedSession) => void) {
+  const callbackRef = useRef(onSessionOpened);
+  callbackRef.current = onSessionOpened;
+
+  useEffect(() => {
+    let unlistenFn: (() => void) | null = null;
+    let cancelled = false;
+
+    const takeSessions = async () => {
+      try {
+        const { invoke } = await import("@tauri-apps/api/core");
+        const sessions = await invoke<OpenedSession[]>("take_opened_sessions");
+        if (cancelled) return;
+        for (const session of sessions) {
+          callbackRef.current(session);
+        }
+      } catch (error) {
+        console.error("[Launch] Failed to take opened sessions:", error);
+      }
+    };
+
+    const setupListener = async () => {
+      try {
+        const { listen } = await import("@tauri-apps/api/event");
+        const unlisten = await listen("session-opened", () => {
+          takeSessions();
+        });
+        if (cancelled) {
+          unlisten();
+          return;
+        }
+        unlistenFn = unlisten;
+      } catch (error) {
+        console.error("[Launch] Failed to setup session-opened listener:", error);
+      }
+      // Sessions started before the listener was ready
+      takeSessions();
+    };
+
+    setupListener();
+
+    return () => {
+      cancelled = true;
+      unlistenFn?.();
+    };
+  }, []);
+}
@@ -5,7 +5,7 @@ import {
   PTY_RESTART_DELAY_MS,
   PTY_RETRY_DELAY_MS,
 } from "@/lib/constants";
-import { ensureValidDimensions } from "@/lib/ptyUtils";
+import { ensureValidDimensions, exitMessage, isRejectedWriteError } from "@/lib/ptyUtils";
 
 interface PtyOutput {
   session_id: string;
@@ -15,6 +15,8 @@ interface PtyOutput {
 interface PtyExit {
   session_id: string;
   exit_code: number | null;
+  /** Signal that killed the shell, such as SIGKILL */
+  signal?: string | null;
 }
 
 interface UsePtySessionOptions {
@@ -112,6 +114,8 @@ export function usePtySession({
         data,
       });
     } catch (error) {
+      // Read-only sessions and the lock reject input; the session itself is fine
+      if (isRejectedWriteError(error)) return;
       console.error("[PTY] Write failed:", error);
       terminal.write("\r\n\x1b[31m[Connection lost. Attempting to reconnect...]\x1b[0m\r\n");
 
@@ -183,7 +187,8 @@ export function usePtySession({
     // Listen for PTY exit
     const unlistenExit = await listen<PtyExit>("pty-exit", (event) => {
       if (event.payload.session_id === sessionIdRef.current) {
-        terminal.write("\r\n\x1b[33m[Process exited]\x1b[0m\r\n");
+        const message = exitMessage(event.payload.exit_code, event.payload.signal);
+        terminal.write(`\r\n\x1b[33m${message}\x1b[0m\r\n`);
         sessionIdRef.current = null;
         // Restart the session after a delay
         setTimeout(() => {
@@ -99,6 +99,27 @@ describe("useTerminalInput", () => {
     });
   });
 
+  it("should drop focus reports from xterm.js", async () => {
+    renderHook(() =>
+      useTerminalInput({
+        terminal: mockTerminal,
+        ptyManager: mockPtyManager,
+        isPtyReady: true,
+      })
+    );
+
+    act(() => {
+      mockOnData("\x1b[I");
+      mockOnData("\x1b[O");
+      mockOnData("after");
+    });
+
+    await waitFor(() => {
+      expect(mockPtyManager.write).toHaveBeenCalledWith("after");
+    });
+    expect(mockPtyManager.write).toHaveBeenCalledTimes(1);
+  });
+
   it("should buffer input when PTY is not ready", async () => {
     renderHook(() =>
       useTerminalInput({
@@ -1,7 +1,7 @@
 import { useCallback, useEffect, useRef } from "react";
 import { Terminal } from "@xterm/xterm";
 import { PtyManager } from "@/lib/ptyManager";
-import { DOUBLE_ESC_INTERVAL_MS, ESC_KEY } from "@/lib/constants";
+import { DOUBLE_ESC_INTERVAL_MS, ESC_KEY, FOCUS_REPORTS } from "@/lib/constants";
 import { loadSettings } from "@/lib/settings";
 import { isPinStatePayload } from "@/lib/guards";
 
@@ -69,6 +69,11 @@ export function useTerminalInput({
   // Handle terminal data with double-ESC detection
   const handleData = useCallback(
     (data: string) => {
+      // Focus reports follow the window's key state and come from the backend
+      if (FOCUS_REPORTS.includes(data)) {
+        return;
+      }
+
       // Double-ESC detection
       if (data === ESC_KEY) {
         const now = Date.now();
@@ -32,6 +32,10 @@ vi.mock("@xterm/xterm", () => ({
   },
 }));
 
+vi.mock("@/lib/terminal/deviceReports", () => ({
+  suppressDeviceAttributesReplies: vi.fn(),
+}));
+
 vi.mock("@/lib/terminal/theme", () => ({
   getTerminalTheme: vi.fn((opacity) => ({
     background: `rgba(0, 0, 0, ${opacity})`,
@@ -48,6 +52,30 @@ vi.mock("@/lib/terminalAddons", () => ({
   })),
 }));
 
+const displayPreferences = vi.hoisted(() => ({
+  current: { reduce_motion: false, increase_contrast: false },
+}));
+
+vi.mock("./useDisplayPreferences", () => ({
+  useDisplayPreferences: () => displayPreferences.current,
+}));
+
+vi.mock("@/lib/displayPreferences", async (importOriginal) => ({
+  ...(await importOriginal<typeof import("@/lib/displayPreferences")>()),
+  getDisplayPreferencesSnapshot: () => displayPreferences.current,
+}));
+
+vi.mock("@/lib/terminal/unicodeWidth", () => ({
+  applyUnicodeWidthMode: vi.fn(),
+}));
+
+const backendSettings = vi.hoisted(() => ({ current: null as unknown }));
+
+vi.mock("@/lib/tauri/settings", () => ({
+  DEFAULT_UNICODE_WIDTH_MODE: { ambiguous: "narrow", emoji_vs16_wide: false },
+  getSettings: vi.fn(() => Promise.resolve(backendSettings.current)),
+}));
+
 vi.mock("@/lib/settings", () => ({
   loadSettings: vi.fn(() => ({
     opacity: 0.96,
@@ -56,6 +84,7 @@ vi.mock("@/lib/settings", () => ({
 }));
 
 import { getTerminalTheme } from "@/lib/terminal/theme";
+import { applyUnicodeWidthMode } from "@/lib/terminal/unicodeWidth";
 import { setupTerminalAddons } from "@/lib/terminalAddons";
 
 describe("useTerminalInstance", () => {
@@ -63,6 +92,8 @@ describe("useTerminalInstance", () => {
 
   beforeEach(() => {
     vi.clearAllMocks();
+    displayPreferences.current = { reduce_motion: false, increase_contrast: false };
+    backendSettings.current = null;
 
     // Create a real DOM element for the container
     const div = document.createElement("div");
@@ -101,7 +132,7 @@ describe("useTerminalInstance", () => {
       })
     );
 
-    expect(getTerminalTheme).toHaveBeenCalledWith(0.85);
+    expect(getTerminalTheme).toHaveBeenCalledWith(0.85, false);
   });
 
   it("should update theme when opacity changes", () => {
@@ -119,7 +150,41 @@ describe("useTerminalInstance", () => {
     // Change opacity
     rerender({ opacity: 0.8 });
 
-    expect(getTerminalTheme).toHaveBeenCalledWith(0.8);
+    expect(getTerminalTheme).toHaveBeenCalledWith(0.8, false);
+  });
+
+  it("should follow Reduce Motion and Increase Contrast", () => {
+    displayPreferences.current = { reduce_motion: true, increase_contrast: true };
+
+    const { result } = renderHook(() =>
+      useTerminalInstance({
+        containerRef,
+        opacity: 0.9,
+      })
+    );
+
+    expect(getTerminalTheme).toHaveBeenCalledWith(0.9, true);
+    expect(result.current?.terminal.options).toMatchObject({
+      cursorBlink: false,
+      smoothScrollDuration: 0,
+    });
+  });
+
+  it("should apply the backend's unicode_width_mode", async () => {
+    const mode = { ambiguous: "wide", emoji_vs16_wide: true };
+    backendSettings.current = { unicode_width_mode: mode };
+
+    const { result } = renderHook(() => useTerminalInstance({ containerRef }));
+    const terminal = result.current?.terminal;
+
+    expect(terminal?.options).toMatchObject({ allowProposedApi: true });
+    expect(applyUnicodeWidthMode).toHaveBeenCalledWith(terminal, {
+      ambiguous: "narrow",
+      emoji_vs16_wide: false,
+    });
+    await vi.waitFor(() => {
+      expect(applyUnicodeWidthMode).toHaveBeenLastCalledWith(terminal, mode);
+    });
   });
 
   it("should register onData handler if provided", () => {
@@ -1,8 +1,13 @@
 import { useCallback, useEffect, useRef, useState } from "react";
 import { Terminal } from "@xterm/xterm";
+import { getDisplayPreferencesSnapshot, terminalMotionOptions } from "@/lib/displayPreferences";
+import { DEFAULT_UNICODE_WIDTH_MODE, getSettings } from "@/lib/tauri/settings";
+import { suppressDeviceAttributesReplies } from "@/lib/terminal/deviceReports";
 import { getTerminalTheme } from "@/lib/terminal/theme";
+import { applyUnicodeWidthMode } from "@/lib/terminal/unicodeWidth";
 import { setupTerminalAddons } from "@/lib/terminalAddons";
 import { loadSettings } from "@/lib/settings";
+import { useDisplayPreferences } from "./useDisplayPreferences";
 
 export interface UseTerminalInstanceOptions {
   containerRef: React.RefObject<HTMLDivElement | null>;
@@ -77,6 +82,7 @@ export function useTerminalInstance({
 }: UseTerminalInstanceOptions) {
   const [instance, setInstance] = useState<TerminalInstance | null>(null);
   const initializedRef = useRef(false);
+  const displayPreferences = useDisplayPreferences();
 
   // Capture initial values to avoid re-initialization on prop changes
   const initialOpacityRef = useRef(propOpacity);
@@ -126,10 +132,11 @@ export function useTerminalInstance({
     const initialFontSize = initialFontSizeRef.current ?? settings.fontSize ?? 13;
 
     // Create terminal with options
-    const theme = getTerminalTheme(initialOpacity);
+    const preferences = getDisplayPreferencesSnapshot();
+    const theme = getTerminalTheme(initialOpacity, preferences.increase_contrast);
 
     const terminal = new Terminal({
-      cursorBlink: true,
+      ...terminalMotionOptions(preferences),
       cursorStyle: "block",
       fontSize: initialFontSize,
       fontFamily: '"SF Mono", Menlo, Monaco, "Courier New", monospace',
@@ -141,16 +148,32 @@ export function useTerminalInstance({
       scrollOnUserInput: false,
       scrollOnEraseInDisplay: false,
       fastScrollSensitivity: 10,
-      smoothScrollDuration: 125,
+      // Needed for custom cell widths (terminal.unicode)
+      allowProposedApi: true,
     });
 
     // Setup addons
     const { fitAddon, searchAddon, webglAddon } = setupTerminalAddons(terminal);
 
+    // The backend answers Device Attributes queries
+    suppressDeviceAttributesReplies(terminal);
+
     // Open terminal in container
     terminal.open(containerRef.current);
     terminal.focus();
 
+    // Measure cells like the backend's scrollback, per unicode_width_mode
+    applyUnicodeWidthMode(terminal, DEFAULT_UNICODE_WIDTH_MODE);
+    getSettings()
+      .then((backendSettings) => {
+        if (backendSettings?.unicode_width_mode) {
+          applyUnicodeWidthMode(terminal, backendSettings.unicode_width_mode);
+        }
+      })
+      .catch((error) => {
+        console.warn("[useTerminalInstance] Failed to apply unicode_width_mode", error);
+      });
+
     // Register data handler if provided
     if (onData) {
       terminal.onData(onData);
@@ -189,14 +212,26 @@ export function useTerminalInstance({
     return cleanup;
   }, [initTerminal]);
 
-  // Update opacity when prop changes
+  // Update the theme when opacity or Increase Contrast changes
   useEffect(() => {
-    if (instance && propOpacity !== undefined) {
-      // eslint-disable-next-line react-hooks/immutability
-      instance.terminal.options.theme = getTerminalTheme(propOpacity);
-      instance.terminal.refresh(0, instance.terminal.rows - 1);
-    }
-  }, [instance, propOpacity]);
+    if (!instance) return;
+    const opacity = propOpacity ?? initialOpacityRef.current ?? loadSettings().opacity;
+    // eslint-disable-next-line react-hooks/immutability
+    instance.terminal.options.theme = getTerminalTheme(
+      opacity,
+      displayPreferences.increase_contrast
+    );
+    instance.terminal.refresh(0, instance.terminal.rows - 1);
+  }, [instance, propOpacity, displayPreferences.increase_contrast]);
+
+  // Stop blinking and smooth scrolling with Reduce Motion
+  useEffect(() => {
+    if (!instance) return;
+    const { cursorBlink, smoothScrollDuration } = terminalMotionOptions(displayPreferences);
+    // eslint-disable-next-line react-hooks/immutability
+    instance.terminal.options.cursorBlink = cursorBlink;
+    instance.terminal.options.smoothScrollDuration = smoothScrollDuration;
+  }, [instance, displayPreferences]);
 
   // Update font size when prop changes
   useEffect(() => {
@@ -41,8 +41,8 @@ export function useTerminalResize({
     let unlisten: (() => void) | undefined;
     const setupListener = async () => {
       try {
-        unlisten = await listen<boolean>("window-visibility", (event) => {
-          setWindowVisible(event.payload);
+        unlisten = await listen<{ visible: boolean }>("window-visibility", (event) => {
+          setWindowVisible(event.payload.visible);
         });
       } catch (error) {
         console.error("Failed to listen for window visibility:", error);
@@ -0,0 +1,69 @@
+import { useCallback, useEffect, useState } from "react";
+import {
+  getWindowMode,
+  transitionWindowMode,
+  type ModeTransition,
+  type WindowMode,
+} from "@/lib/tauri/window";
+
+/**
+ * Tracks the window mode (panel or command bar) kept by the Rust backend.
+ * Cmd+Shift+L opens the command bar.
+ */
+export function useWindowMode(disabled = false) {
+  const [mode, setMode] = useState<WindowMode>("panel");
+
+  useEffect(() => {
+    let unlistenFn: (() => void) | null = null;
+    let cancelled = false;
+
+    getWindowMode().then((current) => {
+      if (!cancelled) setMode(current);
+    });
+
+    const setupListener = async () => {
+      try {
+        const { listen } = await import("@tauri-apps/api/event");
+        const unlisten = await listen<{ mode: WindowMode }>("window-mode-changed", (event) => {
+          setMode(event.payload.mode);
+        });
+        if (cancelled) {
+          unlisten();
+          return;
+        }
+        unlistenFn = unlisten;
+      } catch (error) {
+        console.error("[WindowMode] Failed to setup window-mode-changed listener:", error);
+      }
+    };
+
+    setupListener();
+
+    return () => {
+      cancelled = true;
+      unlistenFn?.();
+    };
+  }, []);
+
+  const transition = useCallback(async (next: ModeTransition) => {
+    const result = await transitionWindowMode(next);
+    if (result) setMode(result);
+  }, []);
+
+  useEffect(() => {
+    if (disabled) return;
+
+    const handleKeyDown = (e: KeyboardEvent) => {
+      const isCmdOrCtrl = e.metaKey || e.ctrlKey;
+      if (isCmdOrCtrl && e.shiftKey && e.key.toLowerCase() === "l") {
+        e.preventDefault();
+        transition("open_command_bar");
+      }
+    };
+
+    window.addEventListener("keydown", handleKeyDown);
+    return () => window.removeEventListener("keydown", handleKeyDown);
+  }, [disabled, transition]);
+
+  return { mode, transition };
+}
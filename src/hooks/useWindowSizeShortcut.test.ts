@@ -0,0 +1,49 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { renderHook, waitFor } from "@testing-library/react";
+import { useWindowSizeShortcut } from "./useWindowSizeShortcut";
+
+const mockInvoke = vi.fn();
+
+vi.mock("@tauri-apps/api/core", () => ({
+  invoke: (...args: any[]) => mockInvoke(...args),
+}));
+
+function pressKey(init: KeyboardEventInit) {
+  window.dispatchEvent(new KeyboardEvent("keydown", { bubbles: true, ...init }));
+}
+
+describe("useWindowSizeShortcut", () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    mockInvoke.mockResolvedValue("half");
+  });
+
+  it("should cycle the window size with Cmd+Shift+Enter", async () => {
+    renderHook(() => useWindowSizeShortcut());
+
+    pressKey({ key: "Enter", metaKey: true, shiftKey: true });
+
+    await waitFor(() => {
+      expect(mockInvoke).toHaveBeenCalledWith("cycle_window_size");
+    });
+  });
+
+  it("should ignore Enter without the modifiers", async () => {
+    renderHook(() => useWindowSizeShortcut());
+
+    pressKey({ key: "Enter", metaKey: true });
+    pressKey({ key: "Enter", shiftKey: true });
+
+    await new Promise((resolve) => setTimeout(resolve, 0));
+    expect(mockInvoke).not.toHaveBeenCalled();
+  });
+
+  it("should do nothing when disabled", async () => {
+    renderHook(() => useWindowSizeShortcut({ disabled: true }));
+
+    pressKey({ key: "Enter", metaKey: true, shiftKey: true });
+
+    await new Promise((resolve) => setTimeout(resolve, 0));
+    expect(mockInvoke).not.toHaveBeenCalled();
+  });
+});
@@ -0,0 +1,35 @@
+import { useEffect } from "react";
+
+interface UseWindowSizeShortcutOptions {
+  disabled?: boolean;
+}
+
+/**
+ * Hook to cycle the window through the size presets from the settings
+ * Cmd+Shift+Enter switches to the next preset (compact, half screen, large by default)
+ */
+export function useWindowSizeShortcut({ disabled }: UseWindowSizeShortcutOptions = {}) {
+  useEffect(() => {
+    if (disabled) return;
+
+    const handleKeyDown = (e: KeyboardEvent) => {
+      // Check for Cmd (macOS) or Ctrl (other platforms)
+      const isCmdOrCtrl = e.metaKey || e.ctrlKey;
+      if (!isCmdOrCtrl || !e.shiftKey || e.key !== "Enter") return;
+
+      e.preventDefault();
+      const cycle = async () => {
+        try {
+          const { invoke } = await import("@tauri-apps/api/core");
+          await invoke<string>("cycle_window_size");
+        } catch (error) {
+          console.error("[Window] Failed to cycle window size:", error);
+        }
+      };
+      cycle();
+    };
+
+    window.addEventListener("keydown", handleKeyDown);
+    return () => window.removeEventListener("keydown", handleKeyDown);
+  }, [disabled]);
+}
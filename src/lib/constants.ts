@@ -38,6 +38,9 @@ export const CWD_POLL_INTERVAL_MS = 1000;
 /** ESC key escape sequence */
 export const ESC_KEY = "\x1b";
 
+/** Focus reports xterm.js sends in focus reporting mode (the backend sends these instead) */
+export const FOCUS_REPORTS = ["\x1b[I", "\x1b[O"];
+
 // ============== Pane Split Constants ==============
 
 /** Minimum pane size as ratio (0.0-1.0) */
@@ -0,0 +1,76 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+
+const mockGetDisplayPreferences = vi.fn();
+vi.mock("./tauri/window", () => ({
+  getDisplayPreferences: () => mockGetDisplayPreferences(),
+}));
+
+type Listener = (event: { payload: unknown }) => void;
+const listeners = new Map<string, Listener>();
+vi.mock("@tauri-apps/api/event", () => ({
+  listen: vi.fn(async (name: string, listener: Listener) => {
+    listeners.set(name, listener);
+    return () => listeners.delete(name);
+  }),
+}));
+
+// Import after mocks
+import {
+  getDisplayPreferencesSnapshot,
+  setDisplayPreferences,
+  subscribeDisplayPreferences,
+  terminalMotionOptions,
+} from "./displayPreferences";
+
+const report = (reduce_motion: boolean, increase_contrast: boolean) => ({
+  reduce_motion,
+  increase_contrast,
+  system: { reduce_motion: false, increase_contrast: false },
+});
+
+describe("displayPreferences.ts", () => {
+  beforeEach(() => {
+    setDisplayPreferences({ reduce_motion: false, increase_contrast: false });
+  });
+
+  it("should load the preferences and follow changes", async () => {
+    mockGetDisplayPreferences.mockResolvedValue(report(true, false));
+    const notify = vi.fn();
+    const unsubscribe = subscribeDisplayPreferences(notify);
+
+    await vi.waitFor(() => expect(listeners.has("display-preferences-changed")).toBe(true));
+    expect(getDisplayPreferencesSnapshot()).toEqual({
+      reduce_motion: true,
+      increase_contrast: false,
+    });
+    expect(document.documentElement.hasAttribute("data-reduce-motion")).toBe(true);
+
+    listeners.get("display-preferences-changed")!({ payload: report(false, true) });
+    expect(getDisplayPreferencesSnapshot()).toEqual({
+      reduce_motion: false,
+      increase_contrast: true,
+    });
+    expect(document.documentElement.hasAttribute("data-reduce-motion")).toBe(false);
+    expect(document.documentElement.hasAttribute("data-increase-contrast")).toBe(true);
+    expect(notify).toHaveBeenCalledTimes(2);
+
+    unsubscribe();
+  });
+
+  it("should keep the snapshot when nothing changed", () => {
+    const before = getDisplayPreferencesSnapshot();
+    setDisplayPreferences({ reduce_motion: false, increase_contrast: false });
+    expect(getDisplayPreferencesSnapshot()).toBe(before);
+  });
+
+  it("should stop terminal motion with Reduce Motion", () => {
+    expect(terminalMotionOptions({ reduce_motion: true, increase_contrast: false })).toEqual({
+      cursorBlink: false,
+      smoothScrollDuration: 0,
+    });
+    expect(terminalMotionOptions({ reduce_motion: false, increase_contrast: false })).toEqual({
+      cursorBlink: true,
+      smoothScrollDuration: 125,
+    });
+  });
+});
@@ -0,0 +1,88 @@
+// Reduce Motion / Increase Contrast, as the Rust backend reports them
+import {
+  getDisplayPreferences,
+  type DisplayPreferences,
+  type DisplayPreferencesReport,
+} from "./tauri/window";
+
+const DEFAULT_PREFERENCES: DisplayPreferences = {
+  reduce_motion: false,
+  increase_contrast: false,
+};
+
+let current = DEFAULT_PREFERENCES;
+let started = false;
+const subscribers = new Set<() => void>();
+
+/**
+ * Publish new preferences: mark the document root (global styles turn animations off and
+ * strengthen borders from these attributes) and notify subscribers
+ */
+export function setDisplayPreferences(preferences: DisplayPreferences): void {
+  if (
+    preferences.reduce_motion === current.reduce_motion &&
+    preferences.increase_contrast === current.increase_contrast
+  ) {
+    return;
+  }
+  current = {
+    reduce_motion: preferences.reduce_motion,
+    increase_contrast: preferences.increase_contrast,
+  };
+  const root = document.documentElement;
+  root.toggleAttribute("data-reduce-motion", current.reduce_motion);
+  root.toggleAttribute("data-increase-contrast", current.increase_contrast);
+  subscribers.forEach((notify) => notify());
+}
+
+/**
+ * Load the preferences once and follow `display-preferences-changed` (sent when the window
+ * is shown or the settings overrides change)
+ */
+async function start(): Promise<void> {
+  const report = await getDisplayPreferences();
+  if (report) setDisplayPreferences(report);
+
+  try {
+    const { listen } = await import("@tauri-apps/api/event");
+    await listen<DisplayPreferencesReport>("display-preferences-changed", (event) => {
+      setDisplayPreferences(event.payload);
+    });
+  } catch (error) {
+    console.error("[DisplayPreferences] Failed to setup listener:", error);
+  }
+}
+
+/**
+ * Subscribe to preference changes; the first subscriber starts tracking them
+ * @returns Unsubscribe function
+ */
+export function subscribeDisplayPreferences(notify: () => void): () => void {
+  subscribers.add(notify);
+  if (!started) {
+    started = true;
+    start();
+  }
+  return () => {
+    subscribers.delete(notify);
+  };
+}
+
+/**
+ * Current preferences (defaults until the backend answered)
+ */
+export function getDisplayPreferencesSnapshot(): DisplayPreferences {
+  return current;
+}
+
+/**
+ * xterm.js options that move: no cursor blinking or smooth scrolling with Reduce Motion
+ */
+export function terminalMotionOptions(preferences: DisplayPreferences): {
+  cursorBlink: boolean;
+  smoothScrollDuration: number;
+} {
+  return preferences.reduce_motion
+    ? { cursorBlink: false, smoothScrollDuration: 0 }
+    : { cursorBlink: true, smoothScrollDuration: 125 };
+}
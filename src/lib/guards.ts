@@ -47,6 +47,8 @@ export function isPtyOutput(payload: unknown): payload is PtyOutput {
 export interface PtyExit {
   session_id: string;
   exit_code: number | null;
+  /** Signal that killed the shell, such as SIGKILL */
+  signal?: string | null;
 }
 
 /**
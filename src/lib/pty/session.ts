@@ -12,7 +12,7 @@ import {
   PTY_RESTART_DELAY_MS,
   PTY_RETRY_DELAY_MS,
 } from "@/lib/constants";
-import { ensureValidDimensions } from "@/lib/ptyUtils";
+import { ensureValidDimensions, exitMessage } from "@/lib/ptyUtils";
 import { createPtySession, writeToPty, resizePty, closePtySession } from "@/lib/tauri/pty";
 import { getListen } from "@/lib/tauri/preload";
 import type { Terminal } from "@xterm/xterm";
@@ -223,7 +223,8 @@ export class PtySession {
       if (event.payload.session_id === this.sessionId) {
         // Check if terminal is destroyed before writing
         if (!this.isDestroyed) {
-          this.terminal.write("\r\n\x1b[33m[Process exited]\x1b[0m\r\n");
+          const message = exitMessage(event.payload.exit_code, event.payload.signal);
+          this.terminal.write(`\r\n\x1b[33m${message}\x1b[0m\r\n`);
         }
         this.sessionId = null;
 
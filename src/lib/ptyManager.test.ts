@@ -225,10 +225,12 @@ describe("PtyManager", () => {
 
     const mockUnlistenOutput = vi.fn();
     const mockUnlistenExit = vi.fn();
+    const mockUnlistenCleared = vi.fn();
     const mockListen = vi
       .fn()
       .mockResolvedValueOnce(mockUnlistenOutput)
-      .mockResolvedValueOnce(mockUnlistenExit);
+      .mockResolvedValueOnce(mockUnlistenExit)
+      .mockResolvedValueOnce(mockUnlistenCleared);
 
     mockGetListen.mockResolvedValue(mockListen);
 
@@ -236,11 +238,13 @@ describe("PtyManager", () => {
 
     expect(mockListen).toHaveBeenCalledWith("pty-output", expect.any(Function));
     expect(mockListen).toHaveBeenCalledWith("pty-exit", expect.any(Function));
+    expect(mockListen).toHaveBeenCalledWith("session-cleared", expect.any(Function));
     expect(cleanup).toBeDefined();
 
     cleanup();
     expect(mockUnlistenOutput).toHaveBeenCalled();
     expect(mockUnlistenExit).toHaveBeenCalled();
+    expect(mockUnlistenCleared).toHaveBeenCalled();
   });
 
   it("should buffer and flush data", async () => {
@@ -327,6 +331,59 @@ describe("PtyManager", () => {
     }
   });
 
+  it("should clear the terminal on session-cleared", async () => {
+    const terminal = {
+      ...mockTerminal,
+      clear: vi.fn(),
+      reset: vi.fn(),
+    } as unknown as Terminal;
+    const manager = new PtyManager({ terminal, onSessionCreated: mockOnSessionCreated });
+
+    await manager.ensureReady();
+    mockInvoke.mockResolvedValue("test-session-id");
+    await manager.createSession(80, 24);
+
+    const mockListen = vi.fn().mockResolvedValue(vi.fn());
+    mockGetListen.mockResolvedValue(mockListen);
+    await manager.setupListeners();
+
+    const clearedCallback = mockListen.mock.calls.find(
+      (call) => call[0] === "session-cleared"
+    )?.[1];
+
+    clearedCallback({ payload: { session_id: "other-session", reset: false } });
+    expect(terminal.clear).not.toHaveBeenCalled();
+
+    clearedCallback({ payload: { session_id: "test-session-id", reset: false } });
+    expect(terminal.clear).toHaveBeenCalledTimes(1);
+
+    clearedCallback({ payload: { session_id: "test-session-id", reset: true } });
+    expect(terminal.reset).toHaveBeenCalledTimes(1);
+  });
+
+  it("should invoke clear and reset commands", async () => {
+    const manager = new PtyManager({
+      terminal: mockTerminal,
+      onSessionCreated: mockOnSessionCreated,
+    });
+
+    await manager.ensureReady();
+    mockInvoke.mockResolvedValue("test-session-id");
+    await manager.createSession(80, 24);
+    mockInvoke.mockClear();
+    mockInvoke.mockResolvedValue(undefined);
+
+    await manager.clear();
+    expect(mockInvoke).toHaveBeenCalledWith("clear_session_scrollback", {
+      sessionId: "test-session-id",
+    });
+
+    await manager.clear(true);
+    expect(mockInvoke).toHaveBeenCalledWith("reset_session", {
+      sessionId: "test-session-id",
+    });
+  });
+
   it("should close session", async () => {
     const manager = new PtyManager({
       terminal: mockTerminal,
@@ -343,10 +400,12 @@ describe("PtyManager", () => {
     // Setup listeners first
     const mockUnlistenOutput = vi.fn();
     const mockUnlistenExit = vi.fn();
+    const mockUnlistenCleared = vi.fn();
     const mockListen = vi
       .fn()
       .mockResolvedValueOnce(mockUnlistenOutput)
-      .mockResolvedValueOnce(mockUnlistenExit);
+      .mockResolvedValueOnce(mockUnlistenExit)
+      .mockResolvedValueOnce(mockUnlistenCleared);
     mockGetListen.mockResolvedValue(mockListen);
     await manager.setupListeners();
 
@@ -357,6 +416,7 @@ describe("PtyManager", () => {
     });
     expect(mockUnlistenOutput).toHaveBeenCalled();
     expect(mockUnlistenExit).toHaveBeenCalled();
+    expect(mockUnlistenCleared).toHaveBeenCalled();
     expect(manager.getSessionId()).toBeNull();
   });
 });
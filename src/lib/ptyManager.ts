@@ -4,7 +4,7 @@
  */
 
 import { MAX_PTY_RETRIES, PTY_RESTART_DELAY_MS, PTY_RETRY_DELAY_MS } from "@/lib/constants";
-import { ensureValidDimensions } from "@/lib/ptyUtils";
+import { ensureValidDimensions, exitMessage, isRejectedWriteError } from "@/lib/ptyUtils";
 import type { Terminal } from "@xterm/xterm";
 
 interface PtyOutput {
@@ -15,6 +15,13 @@ interface PtyOutput {
 interface PtyExit {
   session_id: string;
   exit_code: number | null;
+  /** Signal that killed the shell, such as SIGKILL */
+  signal?: string | null;
+}
+
+interface SessionCleared {
+  session_id: string;
+  reset: boolean;
 }
 
 // Type guards for runtime validation of IPC payloads
@@ -40,6 +47,17 @@ function isPtyExit(payload: unknown): payload is PtyExit {
   );
 }
 
+function isSessionCleared(payload: unknown): payload is SessionCleared {
+  return (
+    typeof payload === "object" &&
+    payload !== null &&
+    "session_id" in payload &&
+    "reset" in payload &&
+    typeof (payload as SessionCleared).session_id === "string" &&
+    typeof (payload as SessionCleared).reset === "boolean"
+  );
+}
+
 export interface PtyManagerOptions {
   terminal: Terminal;
   onSessionCreated?: (sessionId: string) => void;
@@ -53,6 +71,7 @@ export class PtyManager {
   private sessionId: string | null = null;
   private unlistenOutput: (() => void) | null = null;
   private unlistenExit: (() => void) | null = null;
+  private unlistenCleared: (() => void) | null = null;
   private onSessionCreated?: (sessionId: string) => void;
   private isReconnecting = false;
   private isDestroyed = false; // Prevent write-after-dispose race condition
@@ -217,6 +236,8 @@ export class PtyManager {
         data,
       });
     } catch (error) {
+      // Read-only sessions and the lock reject input; the session itself is fine
+      if (isRejectedWriteError(error)) return;
       console.error("[PTY] Write failed:", error);
 
       // Prevent concurrent reconnection attempts
@@ -303,7 +324,8 @@ export class PtyManager {
       if (event.payload.session_id === this.sessionId) {
         // Check if terminal is destroyed before writing
         if (!this.isDestroyed) {
-          this.terminal.write("\r\n\x1b[33m[Process exited]\x1b[0m\r\n");
+          const message = exitMessage(event.payload.exit_code, event.payload.signal);
+          this.terminal.write(`\r\n\x1b[33m${message}\x1b[0m\r\n`);
         }
         this.sessionId = null;
         // Only restart if not destroyed
@@ -318,9 +340,27 @@ export class PtyManager {
     });
     this.unlistenExit = unlistenExit;
 
+    const unlistenCleared = await listen("session-cleared", (event) => {
+      if (!isSessionCleared(event.payload)) {
+        console.error("[PTY] Invalid session-cleared payload:", event.payload);
+        return;
+      }
+      if (event.payload.session_id === this.sessionId && !this.isDestroyed) {
+        // Drop output buffered from before the clear
+        this.dataBuffer = [];
+        if (event.payload.reset) {
+          this.terminal.reset();
+        } else {
+          this.terminal.clear();
+        }
+      }
+    });
+    this.unlistenCleared = unlistenCleared;
+
     return () => {
       unlistenOutput();
       unlistenExit();
+      unlistenCleared();
     };
   }
 
@@ -341,6 +381,8 @@ export class PtyManager {
     this.unlistenOutput = null;
     this.unlistenExit?.();
     this.unlistenExit = null;
+    this.unlistenCleared?.();
+    this.unlistenCleared = null;
 
     if (this.sessionId) {
       try {
@@ -358,6 +400,27 @@ export class PtyManager {
     }
   }
 
+  /**
+   * Clear the session (Cmd+K), including the backend scrollback.
+   * With `reset`, the terminal is fully reset as well.
+   */
+  async clear(reset = false): Promise<void> {
+    if (!this.sessionId) return;
+    try {
+      let invoke = this.cachedInvoke;
+      if (!invoke) {
+        const { getInvoke } = await import("@/lib/tauri");
+        invoke = await getInvoke();
+        this.cachedInvoke = invoke;
+      }
+      await invoke(reset ? "reset_session" : "clear_session_scrollback", {
+        sessionId: this.sessionId,
+      });
+    } catch (error) {
+      console.error("[PTY] Clear failed:", error);
+    }
+  }
+
   getSessionId(): string | null {
     return this.sessionId;
   }
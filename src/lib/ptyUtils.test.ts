@@ -1,6 +1,8 @@
 import { describe, it, expect, vi, beforeEach, afterEach } from "vitest";
 import {
   ensureValidDimensions,
+  exitMessage,
+  isRejectedWriteError,
   MIN_PTY_COLS,
   MIN_PTY_ROWS,
   type PtyDimensions,
@@ -248,4 +250,26 @@ describe("ptyUtils.ts", () => {
       });
     });
   });
+
+  describe("isRejectedWriteError", () => {
+    it("should detect rejected writes", () => {
+      expect(isRejectedWriteError({ kind: "read_only", session_id: "s1" })).toBe(true);
+      expect(isRejectedWriteError({ kind: "locked" })).toBe(true);
+    });
+
+    it("should not match other errors", () => {
+      expect(isRejectedWriteError({ kind: "io", message: "Failed to write to PTY" })).toBe(false);
+      expect(isRejectedWriteError("Session not found: s1")).toBe(false);
+      expect(isRejectedWriteError(null)).toBe(false);
+    });
+  });
+
+  describe("exitMessage", () => {
+    it("should tell exit codes from signals", () => {
+      expect(exitMessage(0)).toBe("[Process exited]");
+      expect(exitMessage(null, null)).toBe("[Process exited]");
+      expect(exitMessage(2, null)).toBe("[Process exited with code 2]");
+      expect(exitMessage(137, "SIGKILL")).toBe("[Process killed by SIGKILL]");
+    });
+  });
 });
@@ -28,3 +28,25 @@ export function ensureValidDimensions(cols: number, rows: number): PtyDimensions
 
   return { cols: validCols, rows: validRows };
 }
+
+/**
+ * Whether a `write_to_pty` error means the input was rejected on purpose
+ * (read-only session or locked app) rather than the session being gone
+ * @param error - Error thrown by invoke
+ */
+export function isRejectedWriteError(error: unknown): boolean {
+  if (typeof error !== "object" || error === null) return false;
+  const kind = (error as { kind?: unknown }).kind;
+  return kind === "read_only" || kind === "locked";
+}
+
+/**
+ * Line shown in the terminal when its shell exits
+ * @param exitCode - `exit_code` of the pty-exit event
+ * @param signal - `signal` of the pty-exit event, set when the shell was killed by one
+ */
+export function exitMessage(exitCode: number | null, signal?: string | null): string {
+  if (signal) return `[Process killed by ${signal}]`;
+  if (exitCode) return `[Process exited with code ${exitCode}]`;
+  return "[Process exited]";
+}
@@ -0,0 +1,66 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import {
+  listBookmarks,
+  addBookmark,
+  removeBookmark,
+  openBookmark,
+  type Bookmark,
+} from "./bookmarks";
+
+// Mock preload module
+vi.mock("./preload", () => ({
+  invoke: vi.fn(),
+  checkTauriAvailable: vi.fn(() => true),
+}));
+
+import { invoke, checkTauriAvailable } from "./preload";
+
+describe("bookmarks.ts", () => {
+  const bookmark: Bookmark = { id: 1, name: "code", path: "/Users/me/code", profile: "work" };
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    vi.mocked(checkTauriAvailable).mockReturnValue(true);
+  });
+
+  it("should list bookmarks", async () => {
+    vi.mocked(invoke).mockResolvedValue([bookmark]);
+    expect(await listBookmarks()).toEqual([bookmark]);
+    expect(invoke).toHaveBeenCalledWith("list_bookmarks");
+  });
+
+  it("should return no bookmarks outside Tauri or on errors", async () => {
+    vi.mocked(checkTauriAvailable).mockReturnValue(false);
+    expect(await listBookmarks()).toEqual([]);
+    expect(invoke).not.toHaveBeenCalled();
+
+    vi.mocked(checkTauriAvailable).mockReturnValue(true);
+    vi.mocked(invoke).mockRejectedValue(new Error("no store"));
+    const consoleSpy = vi.spyOn(console, "error").mockImplementation(() => {});
+    expect(await listBookmarks()).toEqual([]);
+    consoleSpy.mockRestore();
+  });
+
+  it("should add a bookmark without a profile", async () => {
+    vi.mocked(invoke).mockResolvedValue({ ...bookmark, profile: null });
+    await addBookmark("code", "/Users/me/code");
+    expect(invoke).toHaveBeenCalledWith("add_bookmark", {
+      name: "code",
+      path: "/Users/me/code",
+      profile: null,
+    });
+  });
+
+  it("should propagate errors when adding", async () => {
+    vi.mocked(invoke).mockRejectedValue("Not a directory: /missing");
+    await expect(addBookmark("missing", "/missing")).rejects.toBe("Not a directory: /missing");
+  });
+
+  it("should remove and open bookmarks by id", async () => {
+    vi.mocked(invoke).mockResolvedValueOnce(true).mockResolvedValueOnce("session-1");
+    expect(await removeBookmark(1)).toBe(true);
+    expect(invoke).toHaveBeenCalledWith("remove_bookmark", { id: 1 });
+    expect(await openBookmark(2)).toBe("session-1");
+    expect(invoke).toHaveBeenCalledWith("open_bookmark", { id: 2 });
+  });
+});
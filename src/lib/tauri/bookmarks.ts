@@ -0,0 +1,59 @@
+/**
+ * Directory bookmarks (Rust-backed)
+ *
+ * Opened bookmarks show up through "session-opened", like launch commands,
+ * whether they were opened here, from the tray menu or a microterm://bookmark/<id> URL.
+ */
+
+import { checkTauriAvailable, invoke } from "./preload";
+
+export interface Bookmark {
+  id: number;
+  name: string;
+  path: string;
+  /** Profile to start with instead of the directory's */
+  profile: string | null;
+}
+
+/**
+ * Get the bookmarks in the order they were added
+ */
+export async function listBookmarks(): Promise<Bookmark[]> {
+  if (!checkTauriAvailable()) return [];
+
+  try {
+    return await invoke<Bookmark[]>("list_bookmarks");
+  } catch (error) {
+    console.error("Failed to list bookmarks:", error);
+    return [];
+  }
+}
+
+/**
+ * Bookmark a directory
+ * @param profile - Profile to start with, instead of the one matching the directory
+ * @throws When the name is empty or the path isn't a directory
+ */
+export async function addBookmark(
+  name: string,
+  path: string,
+  profile?: string | null
+): Promise<Bookmark> {
+  return await invoke<Bookmark>("add_bookmark", { name, path, profile: profile ?? null });
+}
+
+/**
+ * Remove a bookmark
+ * @returns false if there was no bookmark with the ID
+ */
+export async function removeBookmark(id: number): Promise<boolean> {
+  return await invoke<boolean>("remove_bookmark", { id });
+}
+
+/**
+ * Start a shell in a bookmark's directory
+ * @returns The session ID; the session is also delivered through "session-opened"
+ */
+export async function openBookmark(id: number): Promise<string> {
+  return await invoke<string>("open_bookmark", { id });
+}
@@ -1,5 +1,10 @@
 import { describe, it, expect, vi, beforeEach } from "vitest";
-import { executeCommand, executeCommandStream, completeCommand } from "./commands";
+import {
+  executeCommand,
+  executeCommandStream,
+  completeCommand,
+  rerunHistoryEntry,
+} from "./commands";
 
 // Mock preload module
 const mockInvoke = vi.fn();
@@ -50,6 +55,18 @@ describe("commands.ts", () => {
       expect(result).toEqual(mockResult);
     });
 
+    it("should pass a sandbox level", async () => {
+      mockInvoke.mockResolvedValue({ stdout: "", stderr: "", exit_code: 0 });
+
+      await executeCommand("curl", ["example.com"], "no_network");
+
+      expect(mockInvoke).toHaveBeenCalledWith("execute_command", {
+        cmd: "curl",
+        args: ["example.com"],
+        sandbox: "no_network",
+      });
+    });
+
     it("should handle command errors", async () => {
       const mockResult = {
         stdout: "",
@@ -72,6 +89,12 @@ describe("commands.ts", () => {
   });
 
   describe("executeCommandStream", () => {
+    const executionId = "00000000-0000-4000-8000-000000000001";
+
+    beforeEach(() => {
+      vi.spyOn(crypto, "randomUUID").mockReturnValue(executionId);
+    });
+
     it("should stream stdout chunks", async () => {
       const onStdout = vi.fn();
       const onStderr = vi.fn();
@@ -86,16 +109,20 @@ describe("commands.ts", () => {
         if (eventName === "command-stdout") {
           // Simulate stdout events
           setTimeout(() => {
-            callback({ payload: { chunk: "line 1\n", is_stderr: false } });
-            callback({ payload: { chunk: "line 2\n", is_stderr: false } });
+            callback({
+              payload: { execution_id: executionId, chunk: "line 1\n", is_stderr: false },
+            });
+            callback({
+              payload: { execution_id: executionId, chunk: "line 2\n", is_stderr: false },
+            });
           }, 10);
           return Promise.resolve(unlistenStdout);
         } else if (eventName === "command-stderr") {
           return Promise.resolve(unlistenStderr);
-        } else if (eventName === "command-complete") {
+        } else if (eventName === `command-complete:${executionId}`) {
           // Simulate completion event
           setTimeout(() => {
-            callback({ payload: 0 });
+            callback({ payload: { execution_id: executionId, exit_code: 0 } });
           }, 20);
           return Promise.resolve(unlistenComplete);
         }
@@ -111,7 +138,7 @@ describe("commands.ts", () => {
 
       expect(onStdout).toHaveBeenCalledWith("line 1\n");
       expect(onStdout).toHaveBeenCalledWith("line 2\n");
-      expect(onComplete).toHaveBeenCalledWith(0);
+      expect(onComplete).toHaveBeenCalledWith(0, expect.objectContaining({ exit_code: 0 }));
     });
 
     it("should stream stderr chunks", async () => {
@@ -129,12 +156,14 @@ describe("commands.ts", () => {
         } else if (eventName === "command-stderr") {
           // Simulate stderr events
           setTimeout(() => {
-            callback({ payload: { chunk: "error message\n", is_stderr: true } });
+            callback({
+              payload: { execution_id: executionId, chunk: "error message\n", is_stderr: true },
+            });
           }, 10);
           return Promise.resolve(unlistenStderr);
-        } else if (eventName === "command-complete") {
+        } else if (eventName === `command-complete:${executionId}`) {
           setTimeout(() => {
-            callback({ payload: 1 });
+            callback({ payload: { execution_id: executionId, exit_code: 1 } });
           }, 20);
           return Promise.resolve(unlistenComplete);
         }
@@ -148,7 +177,7 @@ describe("commands.ts", () => {
       await new Promise((resolve) => setTimeout(resolve, 30));
 
       expect(onStderr).toHaveBeenCalledWith("error message\n");
-      expect(onComplete).toHaveBeenCalledWith(1);
+      expect(onComplete).toHaveBeenCalledWith(1, expect.objectContaining({ exit_code: 1 }));
     });
 
     it("should cleanup listeners on completion", async () => {
@@ -165,9 +194,9 @@ describe("commands.ts", () => {
           return Promise.resolve(unlistenStdout);
         } else if (eventName === "command-stderr") {
           return Promise.resolve(unlistenStderr);
-        } else if (eventName === "command-complete") {
+        } else if (eventName === `command-complete:${executionId}`) {
           setTimeout(() => {
-            callback({ payload: 0 });
+            callback({ payload: { execution_id: executionId, exit_code: 0 } });
           }, 10);
           return Promise.resolve(unlistenComplete);
         }
@@ -199,8 +228,90 @@ describe("commands.ts", () => {
       expect(mockInvoke).toHaveBeenCalledWith("execute_command_stream", {
         cmd: "git",
         args: ["status"],
+        executionId,
       });
     });
+
+    it("should deliver merged chunks in read order", async () => {
+      const delivered: string[] = [];
+      const onComplete = vi.fn();
+
+      mockListen.mockImplementation((eventName: string, callback: (event: any) => void) => {
+        const chunk = (seq: number, text: string, isStderr: boolean) => ({
+          payload: { execution_id: executionId, chunk: text, is_stderr: isStderr, seq },
+        });
+        if (eventName === "command-stdout") {
+          setTimeout(() => {
+            callback(chunk(2, "out 2\n", false));
+            callback(chunk(0, "out 0\n", false));
+          }, 10);
+        } else if (eventName === "command-stderr") {
+          setTimeout(() => callback(chunk(1, "err 1\n", true)), 15);
+        } else if (eventName === `command-complete:${executionId}`) {
+          setTimeout(() => callback({ payload: { execution_id: executionId, exit_code: 0 } }), 20);
+        }
+        return Promise.resolve(vi.fn());
+      });
+      mockInvoke.mockResolvedValue(undefined);
+
+      await executeCommandStream(
+        "make",
+        [],
+        (chunk) => delivered.push(chunk),
+        (chunk) => delivered.push(`stderr: ${chunk}`),
+        onComplete,
+        { merged: true }
+      );
+      await new Promise((resolve) => setTimeout(resolve, 30));
+
+      expect(delivered).toEqual(["out 0\n", "stderr: err 1\n", "out 2\n"]);
+      expect(onComplete).toHaveBeenCalled();
+      expect(mockInvoke).toHaveBeenCalledWith("execute_command_stream", {
+        cmd: "make",
+        args: [],
+        executionId,
+        merged: true,
+      });
+    });
+
+    it("should ignore output of other executions", async () => {
+      const onStdout = vi.fn();
+
+      mockListen.mockImplementation((eventName: string, callback: (event: any) => void) => {
+        if (eventName === "command-stdout") {
+          setTimeout(() => {
+            callback({ payload: { execution_id: "other", chunk: "theirs\n", is_stderr: false } });
+            callback({ payload: { execution_id: executionId, chunk: "ours\n", is_stderr: false } });
+          }, 10);
+        }
+        return Promise.resolve(vi.fn());
+      });
+      mockInvoke.mockResolvedValue(undefined);
+
+      await executeCommandStream("ls", [], onStdout, vi.fn(), vi.fn());
+      await new Promise((resolve) => setTimeout(resolve, 20));
+
+      expect(onStdout).toHaveBeenCalledTimes(1);
+      expect(onStdout).toHaveBeenCalledWith("ours\n");
+    });
+  });
+
+  describe("rerunHistoryEntry", () => {
+    it("should rerun the entry by id", async () => {
+      const mockResult = { stdout: "ok\n", stderr: "", exit_code: 0 };
+      mockInvoke.mockResolvedValue(mockResult);
+
+      const result = await rerunHistoryEntry(42);
+
+      expect(mockInvoke).toHaveBeenCalledWith("rerun_history_entry", { id: 42 });
+      expect(result).toEqual(mockResult);
+    });
+
+    it("should propagate errors for entries that can't be rerun", async () => {
+      mockInvoke.mockRejectedValue("History entry 7 can't be rerun");
+
+      await expect(rerunHistoryEntry(7)).rejects.toBe("History entry 7 can't be rerun");
+    });
   });
 
   describe("completeCommand", () => {
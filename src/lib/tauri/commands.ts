@@ -13,21 +13,62 @@ export interface CommandResult {
 }
 
 export interface StreamChunk {
+  execution_id: string;
   chunk: string;
   is_stderr: boolean;
+  /** Position among both streams' chunks, in merged mode */
+  seq: number | null;
+}
+
+/**
+ * How much a command is restricted: `env` clears its environment, `no_network` also denies
+ * network access and `strict` also denies writes outside temporary directories (both macOS only)
+ */
+export type SandboxLevel = "off" | "env" | "no_network" | "strict";
+
+export interface CommandStreamOptions {
+  /** Deliver stdout and stderr chunks in the order they were read */
+  merged?: boolean;
+  sandbox?: SandboxLevel;
+}
+
+/** Payload of `command-complete:{execution_id}` */
+export interface CommandCompletion {
+  execution_id: string;
+  exit_code: number;
+  duration_ms: number;
+  stdout_bytes: number;
+  stderr_bytes: number;
+}
+
+export interface HistoryEntry {
+  id: number;
+  command: string;
+  exit_code: number;
+  finished_at: number;
+  cwd: string | null;
+  profile: string | null;
+  /** Whether `rerunHistoryEntry` can run it again (false when it contained secrets) */
+  rerunnable: boolean;
 }
 
 /**
  * Execute a command and wait for result
  * @param cmd - Command to execute
  * @param args - Command arguments
+ * @param sandbox - Run with reduced access, e.g. for a command pasted from the internet
  * @returns Command result with stdout, stderr, and exit code
  */
-export async function executeCommand(cmd: string, args: string[] = []): Promise<CommandResult> {
+export async function executeCommand(
+  cmd: string,
+  args: string[] = [],
+  sandbox?: SandboxLevel
+): Promise<CommandResult> {
   const invoke = await getInvoke();
   const result = await invoke<CommandResult>("execute_command", {
     cmd,
     args,
+    sandbox,
   });
   return result;
 }
@@ -39,41 +80,100 @@ export async function executeCommand(cmd: string, args: string[] = []): Promise<
  * @param onStdout - Callback for stdout chunks
  * @param onStderr - Callback for stderr chunks
  * @param onComplete - Callback when command completes
+ * @param options - `merged` delivers chunks of both streams in the order they were read;
+ *   `sandbox` runs the command with reduced access
  */
 export async function executeCommandStream(
   cmd: string,
   args: string[] = [],
   onStdout: (chunk: string) => void,
   onStderr: (chunk: string) => void,
-  onComplete: (exitCode: number) => void
+  onComplete: (exitCode: number, completion: CommandCompletion) => void,
+  options: CommandStreamOptions = {}
 ): Promise<void> {
   const invoke = await getInvoke();
   const listen = await getListen();
+  // Tells this invocation's events apart from concurrent ones
+  const executionId = crypto.randomUUID();
+
+  // Merged mode: chunks that arrive ahead of their turn wait for the ones before them
+  const pending = new Map<number, StreamChunk>();
+  let nextSeq = 0;
+  const dispatch = (chunk: StreamChunk) => {
+    if (chunk.is_stderr) onStderr(chunk.chunk);
+    else onStdout(chunk.chunk);
+  };
+  const handleChunk = (chunk: StreamChunk) => {
+    if (chunk.execution_id !== executionId) return;
+    if (chunk.seq === null || chunk.seq === undefined) {
+      dispatch(chunk);
+      return;
+    }
+    pending.set(chunk.seq, chunk);
+    for (let next = pending.get(nextSeq); next; next = pending.get(nextSeq)) {
+      pending.delete(nextSeq);
+      nextSeq += 1;
+      dispatch(next);
+    }
+  };
 
   // Set up event listeners
   const unlistenStdout = await listen<StreamChunk>("command-stdout", (event) => {
-    onStdout(event.payload.chunk);
+    handleChunk(event.payload);
   });
 
   const unlistenStderr = await listen<StreamChunk>("command-stderr", (event) => {
-    onStderr(event.payload.chunk);
+    handleChunk(event.payload);
   });
 
-  const unlistenComplete = await listen<number>("command-complete", (event) => {
-    onComplete(event.payload);
-    // Clean up listeners
-    unlistenStdout();
-    unlistenStderr();
-    unlistenComplete();
-  });
+  const unlistenComplete = await listen<CommandCompletion>(
+    `command-complete:${executionId}`,
+    (event) => {
+      // Deliver anything still held back (e.g. after a dropped chunk)
+      [...pending.keys()].sort((a, b) => a - b).forEach((seq) => dispatch(pending.get(seq)!));
+      pending.clear();
+      onComplete(event.payload.exit_code, event.payload);
+      // Clean up listeners
+      unlistenStdout();
+      unlistenStderr();
+      unlistenComplete();
+    }
+  );
 
   // Execute command
   await invoke<number>("execute_command_stream", {
     cmd,
     args,
+    executionId,
+    merged: options.merged,
+    sandbox: options.sandbox,
   });
 }
 
+/**
+ * Get commands run through execute_command, newest first
+ * @param limit - Maximum number of entries
+ */
+export async function listCommandHistory(limit?: number): Promise<HistoryEntry[]> {
+  try {
+    const invoke = await getInvoke();
+    return await invoke<HistoryEntry[]>("list_command_history", { limit });
+  } catch (error) {
+    console.error("Command history error:", error);
+    return [];
+  }
+}
+
+/**
+ * Run a history entry again with the arguments, directory and environment it was run with
+ * @param id - ID of the history entry
+ * @returns Command result of the new run
+ */
+export async function rerunHistoryEntry(id: number): Promise<CommandResult> {
+  const invoke = await getInvoke();
+  return await invoke<CommandResult>("rerun_history_entry", { id });
+}
+
 /**
  * Get command completions
  * @param prefix - Command prefix to complete
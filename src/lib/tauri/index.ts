@@ -19,12 +19,73 @@ export {
 } from "./preload";
 
 // PTY operations
-export type { PtyOutput, PtyExit } from "./pty";
-export { createPtySession, writeToPty, resizePty, closePtySession } from "./pty";
+export type {
+  PtyOutput,
+  PtyOutputTruncated,
+  PtyExit,
+  ScrollbackHit,
+  ScrollbackSearchOptions,
+  PromptMarkEvent,
+  CommandEndEvent,
+  CommandStats,
+  IntegrationFeature,
+  TerminalKeyEvent,
+  ForegroundProcess,
+  ChildProcess,
+  RunningProcesses,
+  IntegrationHealth,
+  PtyActivityEvent,
+  PtyIdleEvent,
+  AlternateScreenEvent,
+  PtyBellEvent,
+  PtyOrphanedEvent,
+  SessionMoved,
+  SessionFocusRequested,
+  PasteResult,
+  PasteConfirmationRequested,
+  ClipboardAccess,
+  ClipboardAccessRequested,
+  SessionSummary,
+  TranscriptFormat,
+} from "./pty";
+export {
+  createPtySession,
+  writeToPty,
+  pasteToPty,
+  answerClipboardRequest,
+  resizePty,
+  closePtySession,
+  dropPaths,
+  searchScrollback,
+  moveSessionToWindow,
+  listPtySessions,
+  listDetachedSessions,
+  reattachPtySession,
+  getLastCommandStats,
+  checkShellIntegration,
+  passShortcut,
+  getForegroundProcess,
+  sessionHasRunningProcess,
+  startSessionLog,
+  stopSessionLog,
+} from "./pty";
 
 // Command execution
-export type { CommandResult, StreamChunk } from "./commands";
-export { executeCommand, executeCommandStream, completeCommand } from "./commands";
+export type {
+  CommandResult,
+  StreamChunk,
+  CommandStreamOptions,
+  SandboxLevel,
+  CommandCompletion,
+  HistoryEntry,
+} from "./commands";
+export {
+  executeCommand,
+  executeCommandStream,
+  completeCommand,
+  listCommandHistory,
+  rerunHistoryEntry,
+} from "./commands";
 
 // Global shortcuts
 export {
@@ -40,12 +101,35 @@ export {
 export { openUrl } from "./shell";
 
 // Window management
-export type { ScreenInfo } from "./window";
-export { getScreenInfo, adjustWindowSize, ensureWindowVisible } from "./window";
+export type {
+  ScreenInfo,
+  WindowMode,
+  ModeTransition,
+  StatusSnapshot,
+  PresentationMode,
+  PresentationStatus,
+  DisplayPreferences,
+  DisplayPreferencesReport,
+} from "./window";
+export {
+  getScreenInfo,
+  adjustWindowSize,
+  ensureWindowVisible,
+  getWindowMode,
+  transitionWindowMode,
+  STATUS_STRIP_LABEL,
+  isStatusStripWindow,
+  getStatusSnapshot,
+  getPresentationMode,
+  setPresentationMode,
+  togglePresentationMode,
+  getDisplayPreferences,
+} from "./window";
 
 // Settings management
-export type { AppSettings } from "./settings";
+export type { AppSettings, UnicodeWidthMode } from "./settings";
 export {
+  DEFAULT_UNICODE_WIDTH_MODE,
   getSettings,
   updateSettings,
   setOpacity,
@@ -54,3 +138,54 @@ export {
   getPinned,
   setOnboardingComplete,
 } from "./settings";
+
+// Permissions (onboarding)
+export type { Permission, PermissionStatus, PermissionInfo, PermissionReport } from "./permissions";
+export { getPermissionStatus, requestPermission, openPermissionSettings } from "./permissions";
+
+// Profiles
+export type { Profile, ProfileColor } from "./profiles";
+export {
+  listProfiles,
+  saveProfile,
+  deleteProfile,
+  createPtySessionWithProfile,
+} from "./profiles";
+
+// Workspaces
+export type { Workspace, WorkspaceSession, WorkspaceWindow } from "./workspaces";
+export { listWorkspaces, saveWorkspace, deleteWorkspace, openWorkspace } from "./workspaces";
+
+// Link detection
+export type { Link, LinkKind, OpenLinkResult } from "./links";
+export { detectLinks, openLink } from "./links";
+
+// Directory bookmarks
+export type { Bookmark } from "./bookmarks";
+export { listBookmarks, addBookmark, removeBookmark, openBookmark } from "./bookmarks";
+
+// SSH connection manager
+export type {
+  SshHost,
+  SshTransport,
+  SshConnection,
+  SshConnectedEvent,
+  SshDisconnectedEvent,
+} from "./ssh";
+export {
+  listSshHosts,
+  addSshHost,
+  removeSshHost,
+  connectSsh,
+  reconnectSsh,
+  checkMosh,
+  listSshConnections,
+} from "./ssh";
+
+// Trash-aware deletes
+export type { RmInterceptedEvent, RmAction } from "./trash";
+export { moveToTrash, resolveRmIntercept } from "./trash";
+
+// Menu bar script widgets
+export type { MenuWidget, WidgetOutput, WidgetPlacement } from "./widgets";
+export { getMenuWidgets } from "./widgets";
@@ -0,0 +1,55 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { detectLinks, openLink, type Link } from "./links";
+
+// Mock preload module
+vi.mock("./preload", () => ({
+  invoke: vi.fn(),
+  checkTauriAvailable: vi.fn(() => true),
+}));
+
+import { invoke, checkTauriAvailable } from "./preload";
+
+describe("links.ts", () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+    vi.mocked(checkTauriAvailable).mockReturnValue(true);
+  });
+
+  it("should detect links", async () => {
+    const link: Link = {
+      kind: "path",
+      text: "src/main.rs",
+      start: 9,
+      end: 25,
+      url: null,
+      line: 10,
+      column: 5,
+    };
+    vi.mocked(invoke).mockResolvedValue([link]);
+
+    expect(await detectLinks("error at src/main.rs:10:5")).toEqual([link]);
+    expect(invoke).toHaveBeenCalledWith("detect_links", { text: "error at src/main.rs:10:5" });
+  });
+
+  it("should return no links outside Tauri or on errors", async () => {
+    vi.mocked(checkTauriAvailable).mockReturnValue(false);
+    expect(await detectLinks("https://example.com")).toEqual([]);
+    expect(invoke).not.toHaveBeenCalled();
+
+    vi.mocked(checkTauriAvailable).mockReturnValue(true);
+    vi.mocked(invoke).mockRejectedValue("Text too long for link detection");
+    const consoleSpy = vi.spyOn(console, "error").mockImplementation(() => {});
+    expect(await detectLinks("x")).toEqual([]);
+    consoleSpy.mockRestore();
+  });
+
+  it("should open links, asking for confirmation when told to", async () => {
+    vi.mocked(invoke).mockResolvedValue({ action: "confirm", scheme: "ssh" });
+    expect(await openLink("ssh://prod-db")).toEqual({ action: "confirm", scheme: "ssh" });
+    expect(invoke).toHaveBeenCalledWith("open_link", { uri: "ssh://prod-db", confirmed: false });
+
+    vi.mocked(invoke).mockResolvedValue({ action: "opened" });
+    expect(await openLink("ssh://prod-db", true)).toEqual({ action: "opened" });
+    expect(invoke).toHaveBeenCalledWith("open_link", { uri: "ssh://prod-db", confirmed: true });
+  });
+});
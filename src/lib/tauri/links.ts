@@ -0,0 +1,51 @@
+/**
+ * Link detection and opening for terminal text (Rust-backed)
+ */
+
+import { checkTauriAvailable, invoke } from "./preload";
+
+export type LinkKind = "url" | "path" | "ip" | "git_sha" | "custom";
+
+export interface Link {
+  kind: LinkKind;
+  /** The link; for paths without the :line:column suffix */
+  text: string;
+  /** Offsets into the text (UTF-16, like JavaScript string indices) */
+  start: number;
+  end: number;
+  /** URL to open, for URLs and link_patterns matches */
+  url: string | null;
+  line: number | null;
+  column: number | null;
+}
+
+export type OpenLinkResult =
+  | { action: "opened" }
+  /** The link_handlers setting sends the scheme to the preview */
+  | { action: "preview"; uri: string }
+  /** Unusual scheme: ask the user, then call openLink again with confirmed */
+  | { action: "confirm"; scheme: string };
+
+/**
+ * Find URLs, file paths, IP addresses, commit hashes and link_patterns matches in a line of text
+ * @returns The links in order, or none if detection failed
+ */
+export async function detectLinks(text: string): Promise<Link[]> {
+  if (!checkTauriAvailable()) return [];
+
+  try {
+    return await invoke<Link[]>("detect_links", { text });
+  } catch (error) {
+    console.error("Failed to detect links:", error);
+    return [];
+  }
+}
+
+/**
+ * Open a clicked link with the handler the link_handlers setting has for its scheme
+ * @param confirmed - The user agreed to open a link with an unusual scheme
+ * @throws When the link is invalid, its scheme is blocked or opening failed
+ */
+export async function openLink(uri: string, confirmed = false): Promise<OpenLinkResult> {
+  return await invoke<OpenLinkResult>("open_link", { uri, confirmed });
+}
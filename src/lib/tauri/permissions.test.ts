@@ -0,0 +1,64 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import {
+  getPermissionStatus,
+  requestPermission,
+  openPermissionSettings,
+  type PermissionReport,
+} from "./permissions";
+
+// Mock preload module
+vi.mock("./preload", () => ({
+  invoke: vi.fn(),
+  checkTauriAvailable: vi.fn(() => true),
+}));
+
+import { invoke, checkTauriAvailable } from "./preload";
+
+describe("permissions.ts", () => {
+  const mockReport: PermissionReport = {
+    permissions: [
+      {
+        permission: "accessibility",
+        status: "denied",
+        purpose: "Global shortcuts while other apps are focused",
+        settings_url:
+          "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility",
+      },
+    ],
+    quarantine: { quarantined: true, translocated: true, bundle_path: "/tmp/µTerm.app" },
+  };
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    vi.mocked(checkTauriAvailable).mockReturnValue(true);
+  });
+
+  it("should get the permission report", async () => {
+    vi.mocked(invoke).mockResolvedValue(mockReport);
+    expect(await getPermissionStatus()).toEqual(mockReport);
+    expect(invoke).toHaveBeenCalledWith("get_permission_status");
+  });
+
+  it("should return null outside Tauri", async () => {
+    vi.mocked(checkTauriAvailable).mockReturnValue(false);
+    expect(await getPermissionStatus()).toBeNull();
+    expect(await requestPermission("accessibility")).toBeNull();
+    expect(await openPermissionSettings("accessibility")).toBe(false);
+    expect(invoke).not.toHaveBeenCalled();
+  });
+
+  it("should request a permission", async () => {
+    vi.mocked(invoke).mockResolvedValue("granted");
+    expect(await requestPermission("input_monitoring")).toBe("granted");
+    expect(invoke).toHaveBeenCalledWith("request_permission", {
+      permission: "input_monitoring",
+    });
+  });
+
+  it("should report failures to open System Settings", async () => {
+    vi.mocked(invoke).mockRejectedValue(new Error("open failed"));
+    const consoleSpy = vi.spyOn(console, "error").mockImplementation(() => {});
+    expect(await openPermissionSettings("full_disk_access")).toBe(false);
+    consoleSpy.mockRestore();
+  });
+});
@@ -0,0 +1,71 @@
+/**
+ * macOS privacy permission checks for onboarding (Rust-backed)
+ */
+
+import { checkTauriAvailable, invoke } from "./preload";
+
+export type Permission = "input_monitoring" | "accessibility" | "full_disk_access";
+
+export type PermissionStatus = "granted" | "denied" | "not_determined" | "unknown";
+
+export interface PermissionInfo {
+  permission: Permission;
+  status: PermissionStatus;
+  /** What stops working without the permission */
+  purpose: string;
+  settings_url: string;
+}
+
+export interface PermissionReport {
+  permissions: PermissionInfo[];
+  quarantine: {
+    quarantined: boolean;
+    /** Running from a randomized copy; grants are lost until the app is moved to Applications */
+    translocated: boolean;
+    bundle_path: string | null;
+  };
+}
+
+/**
+ * Get the status of each permission and whether the app runs quarantined
+ */
+export async function getPermissionStatus(): Promise<PermissionReport | null> {
+  if (!checkTauriAvailable()) return null;
+
+  try {
+    return await invoke<PermissionReport>("get_permission_status");
+  } catch (error) {
+    console.error("Failed to get permission status:", error);
+    return null;
+  }
+}
+
+/**
+ * Ask for a permission (system prompt, or System Settings for Full Disk Access)
+ * @returns The status afterwards, or null if the request failed
+ */
+export async function requestPermission(permission: Permission): Promise<PermissionStatus | null> {
+  if (!checkTauriAvailable()) return null;
+
+  try {
+    return await invoke<PermissionStatus>("request_permission", { permission });
+  } catch (error) {
+    console.error("Failed to request permission:", error);
+    return null;
+  }
+}
+
+/**
+ * Open the System Settings pane where the permission is granted
+ */
+export async function openPermissionSettings(permission: Permission): Promise<boolean> {
+  if (!checkTauriAvailable()) return false;
+
+  try {
+    await invoke("open_permission_settings", { permission });
+    return true;
+  } catch (error) {
+    console.error("Failed to open permission settings:", error);
+    return false;
+  }
+}
@@ -0,0 +1,62 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import {
+  listProfiles,
+  saveProfile,
+  deleteProfile,
+  createPtySessionWithProfile,
+  type Profile,
+} from "./profiles";
+
+// Mock preload module
+vi.mock("./preload", () => ({
+  getInvoke: vi.fn(),
+}));
+
+import { getInvoke } from "./preload";
+
+describe("profiles.ts", () => {
+  const mockInvoke = vi.fn();
+  const docker: Profile = {
+    name: "docker",
+    shell: { path: "/bin/bash", args: ["-l"] },
+    cwd: "~/containers",
+    env: { DOCKER_HOST: "unix:///var/run/docker.sock" },
+    color: "blue",
+  };
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    vi.mocked(getInvoke).mockResolvedValue(mockInvoke);
+  });
+
+  it("should list profiles", async () => {
+    mockInvoke.mockResolvedValue([docker]);
+    expect(await listProfiles()).toEqual([docker]);
+    expect(mockInvoke).toHaveBeenCalledWith("list_profiles");
+  });
+
+  it("should save and delete profiles", async () => {
+    mockInvoke.mockResolvedValueOnce(undefined).mockResolvedValueOnce(true);
+    await saveProfile(docker);
+    expect(mockInvoke).toHaveBeenCalledWith("save_profile", { profile: docker });
+    expect(await deleteProfile("docker")).toBe(true);
+    expect(mockInvoke).toHaveBeenCalledWith("delete_profile", { name: "docker" });
+  });
+
+  it("should create a session with a profile", async () => {
+    mockInvoke.mockResolvedValue("session-123");
+    expect(await createPtySessionWithProfile("docker", 80, 24)).toBe("session-123");
+    expect(mockInvoke).toHaveBeenCalledWith("create_pty_session_with_profile", {
+      profile: "docker",
+      cols: 80,
+      rows: 24,
+    });
+  });
+
+  it("should propagate unknown profiles", async () => {
+    mockInvoke.mockRejectedValue("Profile not found: missing");
+    await expect(createPtySessionWithProfile("missing", 80, 24)).rejects.toBe(
+      "Profile not found: missing"
+    );
+  });
+});
@@ -0,0 +1,59 @@
+/**
+ * Named profiles: shell, starting directory, environment, theme and tab color (Rust-backed)
+ */
+
+import { getInvoke } from "./preload";
+
+export type ProfileColor = "red" | "orange" | "yellow" | "green" | "blue" | "purple" | "gray";
+
+export interface Profile {
+  name: string;
+  theme?: string | null;
+  env?: Record<string, string>;
+  /** Keychain items exported as environment variables */
+  keychain_env?: { env: string; service: string; account?: string | null }[];
+  startup_command?: string | null;
+  /** Shell started instead of the shell setting */
+  shell?: { path: string; args: string[] } | null;
+  /** Starting directory; a leading ~ is the home directory */
+  cwd?: string | null;
+  color?: ProfileColor | null;
+}
+
+/**
+ * Get the profiles from the settings
+ */
+export async function listProfiles(): Promise<Profile[]> {
+  const invoke = await getInvoke();
+  return invoke<Profile[]>("list_profiles");
+}
+
+/**
+ * Add a profile, or replace the one with the same name
+ */
+export async function saveProfile(profile: Profile): Promise<void> {
+  const invoke = await getInvoke();
+  await invoke("save_profile", { profile });
+}
+
+/**
+ * Remove a profile
+ * @returns false if there was no profile with the name
+ */
+export async function deleteProfile(name: string): Promise<boolean> {
+  const invoke = await getInvoke();
+  return invoke<boolean>("delete_profile", { name });
+}
+
+/**
+ * Create a PTY session with a profile's shell, directory, environment and startup command
+ * @returns Session ID
+ */
+export async function createPtySessionWithProfile(
+  profile: string,
+  cols: number,
+  rows: number
+): Promise<string> {
+  const invoke = await getInvoke();
+  return invoke<string>("create_pty_session_with_profile", { profile, cols, rows });
+}
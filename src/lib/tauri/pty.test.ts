@@ -1,5 +1,25 @@
 import { describe, it, expect, vi, beforeEach } from "vitest";
-import { createPtySession, writeToPty, resizePty, closePtySession } from "./pty";
+import {
+  createPtySession,
+  writeToPty,
+  pasteToPty,
+  answerClipboardRequest,
+  resizePty,
+  closePtySession,
+  searchScrollback,
+  moveSessionToWindow,
+  listPtySessions,
+  listDetachedSessions,
+  reattachPtySession,
+  getLastCommandStats,
+  checkShellIntegration,
+  passShortcut,
+  getForegroundProcess,
+  sessionHasRunningProcess,
+  dropPaths,
+  startSessionLog,
+  stopSessionLog,
+} from "./pty";
 
 // Mock preload module
 vi.mock("./preload", () => ({
@@ -30,6 +50,43 @@ describe("PTY Tauri Commands", () => {
       expect(sessionId).toBe("session-123");
     });
 
+    it("should pass a startup command", async () => {
+      mockInvoke.mockResolvedValue("session-123");
+
+      await createPtySession(80, 24, "ssh prod");
+
+      expect(mockInvoke).toHaveBeenCalledWith("create_pty_session", {
+        cols: 80,
+        rows: 24,
+        startupCommand: "ssh prod",
+      });
+    });
+
+    it("should pass where the session starts", async () => {
+      mockInvoke.mockResolvedValue("session-123");
+
+      await createPtySession(80, 24, undefined, { cwdFromSession: "session-1" });
+
+      expect(mockInvoke).toHaveBeenCalledWith("create_pty_session", {
+        cols: 80,
+        rows: 24,
+        cwdFromSession: "session-1",
+      });
+    });
+
+    it("should pass a program to run instead of a shell", async () => {
+      mockInvoke.mockResolvedValue("session-123");
+
+      await createPtySession(80, 24, undefined, { program: "htop", args: ["-d", "10"] });
+
+      expect(mockInvoke).toHaveBeenCalledWith("create_pty_session", {
+        cols: 80,
+        rows: 24,
+        program: "htop",
+        args: ["-d", "10"],
+      });
+    });
+
     it("should handle large dimensions", async () => {
       mockInvoke.mockResolvedValue("session-123");
 
@@ -59,6 +116,45 @@ describe("PTY Tauri Commands", () => {
     });
   });
 
+  describe("pasteToPty", () => {
+    it("should paste text into a PTY session", async () => {
+      const result = { written: false, bracketed: false, removed_control_chars: 0 };
+      mockInvoke.mockResolvedValue(result);
+
+      expect(await pasteToPty("session-123", "cd /tmp\nls")).toEqual(result);
+      expect(mockInvoke).toHaveBeenCalledWith("paste_to_pty", {
+        sessionId: "session-123",
+        text: "cd /tmp\nls",
+      });
+    });
+
+    it("should pass a confirmation", async () => {
+      mockInvoke.mockResolvedValue({ written: true, bracketed: true, removed_control_chars: 0 });
+
+      await pasteToPty("session-123", "cd /tmp\nls", true);
+
+      expect(mockInvoke).toHaveBeenCalledWith("paste_to_pty", {
+        sessionId: "session-123",
+        text: "cd /tmp\nls",
+        confirmed: true,
+      });
+    });
+  });
+
+  describe("answerClipboardRequest", () => {
+    it("should answer a clipboard request", async () => {
+      mockInvoke.mockResolvedValue(undefined);
+
+      await answerClipboardRequest(7, true, true);
+
+      expect(mockInvoke).toHaveBeenCalledWith("answer_clipboard_request", {
+        requestId: 7,
+        allow: true,
+        remember: true,
+      });
+    });
+  });
+
   describe("writeToPty", () => {
     it("should write data to PTY session", async () => {
       mockInvoke.mockResolvedValue(undefined);
@@ -242,4 +338,227 @@ describe("PTY Tauri Commands", () => {
       expect(mockInvoke).toHaveBeenCalledTimes(4);
     });
   });
+
+  describe("searchScrollback", () => {
+    it("should search as plain text by default", async () => {
+      mockInvoke.mockResolvedValue([]);
+
+      await searchScrollback("session-123", "error");
+
+      expect(mockInvoke).toHaveBeenCalledWith("search_scrollback", {
+        sessionId: "session-123",
+        query: "error",
+        regex: false,
+        caseSensitive: undefined,
+        contextLines: undefined,
+      });
+    });
+
+    it("should pass regex and search options", async () => {
+      const hits = [
+        {
+          line_number: 12,
+          column: 0,
+          cell_column: 0,
+          line: "FAILED test_a",
+          context_before: [],
+          context_after: [],
+        },
+      ];
+      mockInvoke.mockResolvedValue(hits);
+
+      const result = await searchScrollback("session-123", "FAILED \\w+", {
+        regex: true,
+        caseSensitive: true,
+        contextLines: 0,
+      });
+
+      expect(result).toEqual(hits);
+      expect(mockInvoke).toHaveBeenCalledWith("search_scrollback", {
+        sessionId: "session-123",
+        query: "FAILED \\w+",
+        regex: true,
+        caseSensitive: true,
+        contextLines: 0,
+      });
+    });
+  });
+
+  describe("moveSessionToWindow", () => {
+    it("should move the session to the window", async () => {
+      mockInvoke.mockResolvedValue(undefined);
+
+      await moveSessionToWindow("session-123", "detached-1");
+
+      expect(mockInvoke).toHaveBeenCalledWith("move_session_to_window", {
+        sessionId: "session-123",
+        windowLabel: "detached-1",
+      });
+    });
+
+    it("should propagate errors", async () => {
+      mockInvoke.mockRejectedValue(new Error("Window not found: detached-1"));
+
+      await expect(moveSessionToWindow("session-123", "detached-1")).rejects.toThrow(
+        "Window not found"
+      );
+    });
+  });
+
+  describe("listPtySessions", () => {
+    it("should list sessions with their idle times", async () => {
+      const sessions = [
+        {
+          session_id: "session-123",
+          cwd: "/Users/me/src",
+          title: "src",
+          name: "~/src 09:14",
+          running_command: null,
+          idle_secs: 7200,
+          last_input_at: 1760000000,
+          last_output_at: 1760000100,
+          suspended: false,
+          protected: false,
+          read_only: false,
+          labels: { tags: [], color: null },
+          window: "main",
+          attached: true,
+          alternate_screen: false,
+        },
+      ];
+      mockInvoke.mockResolvedValue(sessions);
+
+      expect(await listPtySessions()).toEqual(sessions);
+      expect(mockInvoke).toHaveBeenCalledWith("list_pty_sessions");
+    });
+  });
+
+  describe("detached sessions", () => {
+    it("should list detached sessions", async () => {
+      mockInvoke.mockResolvedValue([]);
+
+      expect(await listDetachedSessions()).toEqual([]);
+      expect(mockInvoke).toHaveBeenCalledWith("list_detached_sessions");
+    });
+
+    it("should return the replayed output when reattaching", async () => {
+      mockInvoke.mockResolvedValue("\x1b[1m$\x1b[0m ");
+
+      expect(await reattachPtySession("session-123")).toBe("\x1b[1m$\x1b[0m ");
+      expect(mockInvoke).toHaveBeenCalledWith("reattach_pty_session", { sessionId: "session-123" });
+    });
+  });
+
+  describe("dropPaths", () => {
+    it("should return the text typed for the dropped paths", async () => {
+      mockInvoke.mockResolvedValue("cd '/Users/me/My Files'\r");
+
+      expect(await dropPaths("session-123", ["/Users/me/My Files"])).toBe(
+        "cd '/Users/me/My Files'\r"
+      );
+      expect(mockInvoke).toHaveBeenCalledWith("drop_paths", {
+        sessionId: "session-123",
+        paths: ["/Users/me/My Files"],
+      });
+    });
+  });
+
+  describe("getLastCommandStats", () => {
+    it("should return the last finished command", async () => {
+      const stats = {
+        command: "cargo build",
+        exit_code: 0,
+        duration_ms: 133000,
+        finished_at: 1760000000,
+      };
+      mockInvoke.mockResolvedValue(stats);
+
+      expect(await getLastCommandStats("session-123")).toEqual(stats);
+      expect(mockInvoke).toHaveBeenCalledWith("get_last_command_stats", {
+        sessionId: "session-123",
+      });
+    });
+  });
+
+  describe("checkShellIntegration", () => {
+    it("should return the session's integration health", async () => {
+      const health = {
+        cwd_reports: false,
+        prompt_marks: true,
+        command_marks: true,
+        command_lines: true,
+        degraded: [{ feature: "cwd_tracking", reason: "No working directory reports (OSC 7)" }],
+      };
+      mockInvoke.mockResolvedValue(health);
+
+      expect(await checkShellIntegration("session-123")).toEqual(health);
+      expect(mockInvoke).toHaveBeenCalledWith("check_shell_integration", {
+        sessionId: "session-123",
+      });
+    });
+  });
+
+  describe("getForegroundProcess", () => {
+    it("should return the foreground process", async () => {
+      const process = { pid: 4242, name: "vim", at_prompt: false };
+      mockInvoke.mockResolvedValue(process);
+
+      expect(await getForegroundProcess("session-123")).toEqual(process);
+      expect(mockInvoke).toHaveBeenCalledWith("get_foreground_process", {
+        sessionId: "session-123",
+      });
+    });
+  });
+
+  describe("sessionHasRunningProcess", () => {
+    it("should return the processes closing the session would kill", async () => {
+      const report = { running: true, processes: [{ pid: 4242, name: "rsync" }] };
+      mockInvoke.mockResolvedValue(report);
+
+      expect(await sessionHasRunningProcess("session-123")).toEqual(report);
+      expect(mockInvoke).toHaveBeenCalledWith("session_has_running_process", {
+        sessionId: "session-123",
+      });
+    });
+  });
+
+  describe("passShortcut", () => {
+    it("should report whether the shortcut went to the terminal", async () => {
+      mockInvoke.mockResolvedValue(true);
+      const event = { key: "k", meta: true };
+
+      expect(await passShortcut("session-123", event)).toBe(true);
+      expect(mockInvoke).toHaveBeenCalledWith("pass_shortcut", {
+        sessionId: "session-123",
+        event,
+      });
+    });
+  });
+
+  describe("session logs", () => {
+    it("should start a log in the given or default format", async () => {
+      mockInvoke.mockResolvedValue("/data/transcripts/1760000000-session-123.log");
+
+      expect(await startSessionLog("session-123", "raw")).toBe(
+        "/data/transcripts/1760000000-session-123.log"
+      );
+      expect(mockInvoke).toHaveBeenCalledWith("start_session_log", {
+        sessionId: "session-123",
+        format: "raw",
+      });
+
+      await startSessionLog("session-123");
+      expect(mockInvoke).toHaveBeenCalledWith("start_session_log", {
+        sessionId: "session-123",
+        format: null,
+      });
+    });
+
+    it("should stop a log", async () => {
+      mockInvoke.mockResolvedValue(null);
+
+      expect(await stopSessionLog("session-123")).toBeNull();
+      expect(mockInvoke).toHaveBeenCalledWith("stop_session_log", { sessionId: "session-123" });
+    });
+  });
 });
@@ -5,26 +5,55 @@
  */
 
 import { getInvoke } from "./preload";
+import type { ProfileColor } from "./profiles";
 
 export interface PtyOutput {
   session_id: string;
   data: string;
 }
 
+/**
+ * Payload of `pty-output-truncated`: output over the `output_rate_limit_kib` setting was
+ * dropped (sent at most once a second while it lasts)
+ */
+export interface PtyOutputTruncated {
+  session_id: string;
+  dropped_bytes: number;
+}
+
 export interface PtyExit {
   session_id: string;
   exit_code: number | null;
+  /** Signal that killed the shell, such as SIGKILL */
+  signal?: string | null;
 }
 
 /**
  * Create a new PTY session
  * @param cols - Terminal columns
  * @param rows - Terminal rows
+ * @param startupCommand - Command typed into the shell once it starts (e.g. `ssh prod`)
+ * @param options - Where to start: `cwd`, else the directory of session `cwdFromSession`
+ *   (e.g. the focused pane when splitting), else the home directory; and a `program` with
+ *   `args` to run instead of a shell (e.g. `htop` for a quick action)
  * @returns Session ID for the created PTY
  */
-export async function createPtySession(cols: number, rows: number): Promise<string> {
+export async function createPtySession(
+  cols: number,
+  rows: number,
+  startupCommand?: string,
+  options?: { cwd?: string; cwdFromSession?: string; program?: string; args?: string[] }
+): Promise<string> {
   const invoke = await getInvoke();
-  return invoke<string>("create_pty_session", { cols, rows });
+  return invoke<string>("create_pty_session", {
+    cols,
+    rows,
+    startupCommand,
+    cwd: options?.cwd,
+    cwdFromSession: options?.cwdFromSession,
+    program: options?.program,
+    args: options?.args,
+  });
 }
 
 /**
@@ -37,6 +66,66 @@ export async function writeToPty(sessionId: string, data: string): Promise<void>
   await invoke("write_to_pty", { sessionId, data });
 }
 
+/** Result of `pasteToPty` */
+export interface PasteResult {
+  /** False when the paste awaits confirmation (see `PasteConfirmationRequested`) */
+  written: boolean;
+  /** Whether it was sent in bracketed-paste markers */
+  bracketed: boolean;
+  removed_control_chars: number;
+}
+
+/** Payload of `paste-confirmation-requested`: a paste was held back until confirmed */
+export interface PasteConfirmationRequested {
+  session_id: string;
+  line_count: number;
+  is_multiline: boolean;
+  contains_sudo: boolean;
+}
+
+/**
+ * Paste clipboard text into a session, sanitized and bracketed when the program asked for it.
+ * A multi-line or sudo paste is held back until sent again with `confirmed`.
+ * @param sessionId - PTY session ID
+ * @param text - Clipboard text
+ * @param confirmed - Whether the user already confirmed the paste
+ */
+export async function pasteToPty(
+  sessionId: string,
+  text: string,
+  confirmed?: boolean
+): Promise<PasteResult> {
+  const invoke = await getInvoke();
+  return invoke<PasteResult>("paste_to_pty", { sessionId, text, confirmed });
+}
+
+/** The `clipboard_access` setting: what programs may do with OSC 52 */
+export type ClipboardAccess = "allow" | "deny" | "ask";
+
+/** Payload of `clipboard-access-requested`: an OSC 52 request awaits an answer */
+export interface ClipboardAccessRequested {
+  request_id: number;
+  session_id: string;
+  operation: "write" | "read";
+  /** Characters the program wants to copy; 0 for reads */
+  length: number;
+}
+
+/**
+ * Answer a `clipboard-access-requested` event
+ * @param requestId - ID from the event
+ * @param allow - Whether to carry out the request
+ * @param remember - Give the session's later requests the same answer
+ */
+export async function answerClipboardRequest(
+  requestId: number,
+  allow: boolean,
+  remember?: boolean
+): Promise<void> {
+  const invoke = await getInvoke();
+  await invoke("answer_clipboard_request", { requestId, allow, remember });
+}
+
 /**
  * Resize a PTY session
  * @param sessionId - PTY session ID
@@ -48,6 +137,16 @@ export async function resizePty(sessionId: string, cols: number, rows: number):
   await invoke("resize_pty", { sessionId, cols, rows });
 }
 
+/**
+ * Type paths dropped on a terminal into its session, shell-quoted; with
+ * cd_on_folder_drop a single folder runs `cd` into it instead
+ * @returns The text typed, empty if none of the paths could be
+ */
+export async function dropPaths(sessionId: string, paths: string[]): Promise<string> {
+  const invoke = await getInvoke();
+  return invoke<string>("drop_paths", { sessionId, paths });
+}
+
 /**
  * Close a PTY session
  * @param sessionId - PTY session ID
@@ -56,3 +155,304 @@ export async function closePtySession(sessionId: string): Promise<void> {
   const invoke = await getInvoke();
   await invoke("close_pty_session", { sessionId });
 }
+
+/** Payload of `session-moved` */
+export interface SessionMoved {
+  session_id: string;
+  from_window: string;
+  to_window: string;
+}
+
+/** Payload of `session-focus-requested`: the session was chosen in the tray menu */
+export interface SessionFocusRequested {
+  session_id: string;
+}
+
+/**
+ * Hand a session over to another window without restarting its shell
+ * @param sessionId - PTY session ID
+ * @param windowLabel - Label of the window that takes the session
+ */
+export async function moveSessionToWindow(sessionId: string, windowLabel: string): Promise<void> {
+  const invoke = await getInvoke();
+  await invoke("move_session_to_window", { sessionId, windowLabel });
+}
+
+/** A live session, as listed by `list_pty_sessions` */
+export interface SessionSummary {
+  session_id: string;
+  cwd: string | null;
+  /** Tab title last emitted with `tab-title-changed` */
+  title: string | null;
+  /** Default name from the profile, cwd and creation time, e.g. "work ~/api 09:14" */
+  name: string;
+  /** Running foreground command (secrets redacted), or null at the prompt */
+  running_command: string | null;
+  /** Seconds since the last input or output */
+  idle_secs: number;
+  /** Seconds since the Unix epoch; null before the first input or output */
+  last_input_at: number | null;
+  last_output_at: number | null;
+  suspended: boolean;
+  protected: boolean;
+  read_only: boolean;
+  labels: { tags: string[]; color: ProfileColor | null };
+  /** Label of the window the session belongs to */
+  window: string;
+  /** Whether a terminal pane shows the session */
+  attached: boolean;
+  /** Whether a full-screen program (vim, less, htop) is on the alternate screen */
+  alternate_screen: boolean;
+}
+
+/**
+ * List every live session with its idle times, e.g. for "idle 2h" in the
+ * session switcher
+ */
+export async function listPtySessions(): Promise<SessionSummary[]> {
+  const invoke = await getInvoke();
+  return invoke<SessionSummary[]>("list_pty_sessions");
+}
+
+/** List the sessions kept alive without a pane (with `keep_sessions_alive`) */
+export async function listDetachedSessions(): Promise<SessionSummary[]> {
+  const invoke = await getInvoke();
+  return invoke<SessionSummary[]>("list_detached_sessions");
+}
+
+/**
+ * Show a detached session in this window's terminal
+ * @param sessionId - Detached session ID
+ * @returns Its recent output, to write into the terminal before resizing the session
+ */
+export async function reattachPtySession(sessionId: string): Promise<string> {
+  const invoke = await getInvoke();
+  return invoke<string>("reattach_pty_session", { sessionId });
+}
+
+export interface ScrollbackHit {
+  line_number: number;
+  /** Character offset of the match */
+  column: number;
+  /** Terminal cell offset of the match */
+  cell_column: number;
+  line: string;
+  context_before: string[];
+  context_after: string[];
+}
+
+export interface ScrollbackSearchOptions {
+  /** Treat the query as a regular expression */
+  regex?: boolean;
+  caseSensitive?: boolean;
+  contextLines?: number;
+}
+
+/**
+ * Search a session's scrollback kept by the backend, including output xterm.js has dropped
+ * @param sessionId - PTY session ID
+ * @param query - Text or regular expression to find
+ * @returns Matching lines with surrounding context, oldest first
+ */
+export async function searchScrollback(
+  sessionId: string,
+  query: string,
+  options: ScrollbackSearchOptions = {}
+): Promise<ScrollbackHit[]> {
+  const invoke = await getInvoke();
+  return invoke<ScrollbackHit[]>("search_scrollback", {
+    sessionId,
+    query,
+    regex: options.regex ?? false,
+    caseSensitive: options.caseSensitive,
+    contextLines: options.contextLines,
+  });
+}
+
+/** Payload of `prompt-start` (OSC 133 A) and `command-start` (OSC 133 C) */
+export interface PromptMarkEvent {
+  session_id: string;
+}
+
+/** Payload of `command-end` (OSC 133 D) */
+export interface CommandEndEvent {
+  session_id: string;
+  exit_code: number | null;
+  /** Milliseconds since the matching `command-start` */
+  duration_ms: number;
+}
+
+/** The last command a session finished, as returned by `get_last_command_stats` */
+export interface CommandStats {
+  /** Command line (secrets redacted), when the shell reported it */
+  command: string | null;
+  exit_code: number | null;
+  duration_ms: number;
+  /** Seconds since the Unix epoch */
+  finished_at: number;
+}
+
+/**
+ * Get the last command a session finished, for a "took 2m13s" badge
+ * @returns null until a command finishes, or if the shell doesn't send OSC 133 marks
+ */
+export async function getLastCommandStats(sessionId: string): Promise<CommandStats | null> {
+  const invoke = await getInvoke();
+  return invoke<CommandStats | null>("get_last_command_stats", { sessionId });
+}
+
+/** The process in the foreground of a session's terminal */
+export interface ForegroundProcess {
+  pid: number;
+  /** e.g. "vim"; null if it already exited */
+  name: string | null;
+  /** Whether it's the session's shell waiting at its prompt */
+  at_prompt: boolean;
+}
+
+/**
+ * Get the process in the foreground of a session's terminal, e.g. to confirm closing a
+ * session that's running vim
+ * @returns null when it can't be determined (remote sessions)
+ */
+export async function getForegroundProcess(sessionId: string): Promise<ForegroundProcess | null> {
+  const invoke = await getInvoke();
+  return invoke<ForegroundProcess | null>("get_foreground_process", { sessionId });
+}
+
+/** A process started from a session's shell */
+export interface ChildProcess {
+  pid: number;
+  /** e.g. "rsync"; null if it already exited */
+  name: string | null;
+}
+
+/** Processes that closing a session would kill */
+export interface RunningProcesses {
+  /** Whether anything besides an idle shell is running */
+  running: boolean;
+  /** Closest to the shell first */
+  processes: ChildProcess[];
+}
+
+/**
+ * Check whether a session's shell has started processes (a command, background jobs) that
+ * closing it would kill, e.g. to confirm closing a pane in the middle of an rsync
+ */
+export async function sessionHasRunningProcess(sessionId: string): Promise<RunningProcesses> {
+  const invoke = await getInvoke();
+  return invoke<RunningProcesses>("session_has_running_process", { sessionId });
+}
+
+/** A key event as the backend encodes it, using DOM `KeyboardEvent.key` names */
+export interface TerminalKeyEvent {
+  key: string;
+  shift?: boolean;
+  alt?: boolean;
+  ctrl?: boolean;
+  /** Cmd */
+  meta?: boolean;
+  event_type?: "press" | "repeat" | "release";
+}
+
+/**
+ * Offer a Cmd shortcut (e.g. Cmd+K) to the session before acting on it: when the session's
+ * foreground program is listed for it in the `shortcut_passthrough` setting (vim, tmux, ...),
+ * the key is sent to the terminal instead
+ * @returns true when the shortcut was passed through and the app action should be skipped
+ */
+export async function passShortcut(sessionId: string, event: TerminalKeyEvent): Promise<boolean> {
+  const invoke = await getInvoke();
+  return invoke<boolean>("pass_shortcut", { sessionId, event });
+}
+
+/** A feature that relies on shell integration */
+export type IntegrationFeature =
+  | "cwd_tracking"
+  | "prompt_jumping"
+  | "command_status"
+  | "command_lines";
+
+/** Shell integration sequences a session has sent, as returned by `check_shell_integration` */
+export interface IntegrationHealth {
+  /** OSC 7 directory reports */
+  cwd_reports: boolean;
+  /** OSC 133 A */
+  prompt_marks: boolean;
+  /** OSC 133 C and D */
+  command_marks: boolean;
+  command_lines: boolean;
+  /** Features that don't work, each with why; empty when everything works */
+  degraded: { feature: IntegrationFeature; reason: string }[];
+}
+
+/**
+ * Check which shell integration features work for a session, so users can see why cwd
+ * tracking or prompt jumping doesn't
+ */
+export async function checkShellIntegration(sessionId: string): Promise<IntegrationHealth> {
+  const invoke = await getInvoke();
+  return invoke<IntegrationHealth>("check_shell_integration", { sessionId });
+}
+
+/** How a transcript is written: output as received, or with escape sequences removed */
+export type TranscriptFormat = "raw" | "plain";
+
+/**
+ * Start appending a session's output to a transcript file under the app data directory
+ * @param format - Defaults to the session_log_format setting
+ * @returns The transcript's path
+ */
+export async function startSessionLog(
+  sessionId: string,
+  format?: TranscriptFormat
+): Promise<string> {
+  const invoke = await getInvoke();
+  return invoke<string>("start_session_log", { sessionId, format: format ?? null });
+}
+
+/**
+ * Stop logging a session
+ * @returns The transcript's path, or null if the session wasn't being logged
+ */
+export async function stopSessionLog(sessionId: string): Promise<string | null> {
+  const invoke = await getInvoke();
+  return invoke<string | null>("stop_session_log", { sessionId });
+}
+
+/** Payload of `pty-activity`: a quiet session started producing output */
+export interface PtyActivityEvent {
+  session_id: string;
+}
+
+/**
+ * Payload of `pty-alternate-screen`: a full-screen program entered or left the alternate
+ * screen; sent after the output it was found in
+ */
+export interface AlternateScreenEvent {
+  session_id: string;
+  active: boolean;
+}
+
+/** Payload of `pty-idle`: a busy session has been quiet for activity_idle_seconds */
+export interface PtyIdleEvent {
+  session_id: string;
+  /** Milliseconds from the first to the last output of the busy spell */
+  active_ms: number;
+}
+
+/** Payload of `pty-bell`: a session rang the bell (at most once a second) */
+export interface PtyBellEvent {
+  session_id: string;
+}
+
+/**
+ * Payload of `pty-orphaned`: a session left without a pane by a page reload has exited.
+ * It is removed a minute later unless a terminal resizes it.
+ */
+export interface PtyOrphanedEvent {
+  session_id: string;
+  window: string;
+  exit_code: number;
+  signal: string | null;
+}
@@ -6,6 +6,19 @@ import { checkTauriAvailable, invoke } from "./preload";
 
 // Note: WindowSize removed - window sizing now managed per-screen by Rust backend (screen_config.rs)
 
+/** Cell width convention for ambiguous characters and emoji */
+export interface UnicodeWidthMode {
+  /** East Asian ambiguous characters: one cell (Western) or two (CJK) */
+  ambiguous: "narrow" | "wide";
+  /** Whether a character followed by VS16 takes two cells */
+  emoji_vs16_wide: boolean;
+}
+
+export const DEFAULT_UNICODE_WIDTH_MODE: UnicodeWidthMode = {
+  ambiguous: "narrow",
+  emoji_vs16_wide: false,
+};
+
 export interface AppSettings {
   opacity: number;
   fontSize: number;
@@ -14,6 +27,8 @@ export interface AppSettings {
   pinShortcut: string;
   onboardingComplete: boolean;
   pinned: boolean;
+  /** Sent by the backend under its own name */
+  unicode_width_mode?: UnicodeWidthMode;
 }
 
 /**
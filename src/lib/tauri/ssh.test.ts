@@ -0,0 +1,108 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import {
+  listSshHosts,
+  addSshHost,
+  removeSshHost,
+  connectSsh,
+  reconnectSsh,
+  checkMosh,
+  listSshConnections,
+  type SshHost,
+} from "./ssh";
+
+// Mock preload module
+vi.mock("./preload", () => ({
+  invoke: vi.fn(),
+  checkTauriAvailable: vi.fn(() => true),
+}));
+
+import { invoke, checkTauriAvailable } from "./preload";
+
+describe("ssh.ts", () => {
+  const host: SshHost = { id: null, name: "prod", hostname: "10.0.0.5", user: "deploy", port: 22 };
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    vi.mocked(checkTauriAvailable).mockReturnValue(true);
+  });
+
+  it("should list hosts", async () => {
+    vi.mocked(invoke).mockResolvedValue([host]);
+    expect(await listSshHosts()).toEqual([host]);
+    expect(invoke).toHaveBeenCalledWith("list_ssh_hosts");
+  });
+
+  it("should return no hosts outside Tauri or on errors", async () => {
+    vi.mocked(checkTauriAvailable).mockReturnValue(false);
+    expect(await listSshHosts()).toEqual([]);
+    expect(invoke).not.toHaveBeenCalled();
+
+    vi.mocked(checkTauriAvailable).mockReturnValue(true);
+    vi.mocked(invoke).mockRejectedValue(new Error("no store"));
+    const consoleSpy = vi.spyOn(console, "error").mockImplementation(() => {});
+    expect(await listSshHosts()).toEqual([]);
+    consoleSpy.mockRestore();
+  });
+
+  it("should add a host without a user or port", async () => {
+    vi.mocked(invoke).mockResolvedValue({ ...host, id: 1, user: null, port: null });
+    await addSshHost("pi", "raspberrypi.local");
+    expect(invoke).toHaveBeenCalledWith("add_ssh_host", {
+      name: "pi",
+      hostname: "raspberrypi.local",
+      user: null,
+      port: null,
+    });
+  });
+
+  it("should remove hosts and connect", async () => {
+    vi.mocked(invoke).mockResolvedValueOnce(true).mockResolvedValueOnce("session-1");
+    expect(await removeSshHost(1)).toBe(true);
+    expect(invoke).toHaveBeenCalledWith("remove_ssh_host", { id: 1 });
+    expect(await connectSsh("prod", 120, 40)).toBe("session-1");
+    expect(invoke).toHaveBeenCalledWith("connect_ssh", {
+      host: "prod",
+      mosh: null,
+      cols: 120,
+      rows: 40,
+    });
+  });
+
+  it("should connect with mosh and reconnect", async () => {
+    vi.mocked(invoke).mockResolvedValueOnce("session-1").mockResolvedValueOnce("session-2");
+    await connectSsh("prod", undefined, undefined, true);
+    expect(invoke).toHaveBeenCalledWith("connect_ssh", {
+      host: "prod",
+      mosh: true,
+      cols: null,
+      rows: null,
+    });
+    expect(await reconnectSsh("session-1", 80, 24)).toBe("session-2");
+    expect(invoke).toHaveBeenCalledWith("reconnect_ssh", {
+      sessionId: "session-1",
+      cols: 80,
+      rows: 24,
+    });
+  });
+
+  it("should check for mosh", async () => {
+    vi.mocked(invoke).mockResolvedValue("/opt/homebrew/bin/mosh");
+    expect(await checkMosh()).toBe("/opt/homebrew/bin/mosh");
+    expect(invoke).toHaveBeenCalledWith("check_mosh");
+
+    vi.mocked(checkTauriAvailable).mockReturnValue(false);
+    expect(await checkMosh()).toBeNull();
+  });
+
+  it("should list connections", async () => {
+    const connection = {
+      session_id: "session-1",
+      host: "prod",
+      transport: "mosh",
+      connected_at: 1760000000,
+    };
+    vi.mocked(invoke).mockResolvedValue([connection]);
+    expect(await listSshConnections()).toEqual([connection]);
+    expect(invoke).toHaveBeenCalledWith("list_ssh_connections");
+  });
+});
@@ -0,0 +1,142 @@
+/**
+ * SSH connection manager (Rust-backed)
+ *
+ * Hosts come from ~/.ssh/config and from hosts saved here. Hosts in the
+ * mosh_hosts setting connect with mosh when it's installed. Connections are
+ * reported with "ssh-connected" and "ssh-disconnected".
+ */
+
+import { checkTauriAvailable, invoke } from "./preload";
+
+export interface SshHost {
+  /** ID of a saved host; null for hosts from ~/.ssh/config */
+  id: number | null;
+  name: string;
+  /** Address to connect to; null when the name is the address */
+  hostname: string | null;
+  user: string | null;
+  port: number | null;
+}
+
+export type SshTransport = "ssh" | "mosh";
+
+export interface SshConnection {
+  session_id: string;
+  host: string;
+  transport: SshTransport;
+  /** Seconds since the Unix epoch */
+  connected_at: number;
+}
+
+/** Payload of "ssh-connected" */
+export interface SshConnectedEvent {
+  session_id: string;
+  host: string;
+  transport: SshTransport;
+}
+
+/** Payload of "ssh-disconnected"; ssh's exit code 255 means the connection failed or dropped */
+export interface SshDisconnectedEvent {
+  session_id: string;
+  host: string;
+  transport: SshTransport;
+  exit_code: number | null;
+}
+
+/**
+ * Get the hosts from ~/.ssh/config followed by the saved hosts
+ */
+export async function listSshHosts(): Promise<SshHost[]> {
+  if (!checkTauriAvailable()) return [];
+
+  try {
+    return await invoke<SshHost[]>("list_ssh_hosts");
+  } catch (error) {
+    console.error("Failed to list ssh hosts:", error);
+    return [];
+  }
+}
+
+/**
+ * Save a host that isn't in ~/.ssh/config
+ * @throws When the name is empty or the address or user is invalid
+ */
+export async function addSshHost(
+  name: string,
+  hostname: string,
+  user?: string | null,
+  port?: number | null
+): Promise<SshHost> {
+  return await invoke<SshHost>("add_ssh_host", {
+    name,
+    hostname,
+    user: user ?? null,
+    port: port ?? null,
+  });
+}
+
+/**
+ * Remove a saved host
+ * @returns false if there was no saved host with the ID
+ */
+export async function removeSshHost(id: number): Promise<boolean> {
+  return await invoke<boolean>("remove_ssh_host", { id });
+}
+
+/**
+ * Start a session connecting to a host from listSshHosts
+ * @param mosh - Whether to use mosh, instead of whether the host is in mosh_hosts
+ * @returns The session ID
+ * @throws When mosh is asked for but not installed
+ */
+export async function connectSsh(
+  host: string,
+  cols?: number,
+  rows?: number,
+  mosh?: boolean
+): Promise<string> {
+  return await invoke<string>("connect_ssh", {
+    host,
+    mosh: mosh ?? null,
+    cols: cols ?? null,
+    rows: rows ?? null,
+  });
+}
+
+/**
+ * Connect the host of an ended ssh or mosh session again the same way
+ * @returns The new session ID
+ */
+export async function reconnectSsh(
+  sessionId: string,
+  cols?: number,
+  rows?: number
+): Promise<string> {
+  return await invoke<string>("reconnect_ssh", {
+    sessionId,
+    cols: cols ?? null,
+    rows: rows ?? null,
+  });
+}
+
+/**
+ * Get the path of the mosh sessions would run
+ * @returns null when mosh isn't installed
+ */
+export async function checkMosh(): Promise<string | null> {
+  if (!checkTauriAvailable()) return null;
+
+  try {
+    return await invoke<string | null>("check_mosh");
+  } catch (error) {
+    console.error("Failed to check for mosh:", error);
+    return null;
+  }
+}
+
+/**
+ * Get the open ssh sessions, oldest first
+ */
+export async function listSshConnections(): Promise<SshConnection[]> {
+  return await invoke<SshConnection[]>("list_ssh_connections");
+}
@@ -0,0 +1,37 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { moveToTrash, resolveRmIntercept } from "./trash";
+
+// Mock preload module
+vi.mock("./preload", () => ({
+  invoke: vi.fn(),
+}));
+
+import { invoke } from "./preload";
+
+describe("trash.ts", () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  it("should move files to the Trash", async () => {
+    vi.mocked(invoke).mockResolvedValue(["/Users/me/.Trash/build"]);
+    expect(await moveToTrash(["/Users/me/app/build"])).toEqual(["/Users/me/.Trash/build"]);
+    expect(invoke).toHaveBeenCalledWith("move_to_trash", { paths: ["/Users/me/app/build"] });
+  });
+
+  it("should resolve an intercepted rm", async () => {
+    vi.mocked(invoke).mockResolvedValue(undefined);
+    await resolveRmIntercept("session-1", "trash");
+    expect(invoke).toHaveBeenCalledWith("resolve_rm_intercept", {
+      sessionId: "session-1",
+      action: "trash",
+    });
+  });
+
+  it("should propagate errors", async () => {
+    vi.mocked(invoke).mockRejectedValue("No rm held back in session session-1");
+    await expect(resolveRmIntercept("session-1", "run")).rejects.toBe(
+      "No rm held back in session session-1"
+    );
+  });
+});
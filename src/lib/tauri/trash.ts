@@ -0,0 +1,38 @@
+/**
+ * Trash-aware deletes (Rust-backed)
+ *
+ * With the safe_delete setting, Enter on a plain `rm` at a shell prompt is held
+ * back and reported with "rm-intercepted"; answer it with resolveRmIntercept.
+ */
+
+import { invoke } from "./preload";
+
+/** Payload of "rm-intercepted" */
+export interface RmInterceptedEvent {
+  session_id: string;
+  /** The held back command line */
+  command: string;
+  /** Absolute paths it would remove */
+  paths: string[];
+}
+
+/** "trash" moves the files to the Trash, "run" runs the rm, "cancel" leaves the line */
+export type RmAction = "trash" | "run" | "cancel";
+
+/**
+ * Move files to the Trash
+ * @param paths - Absolute paths
+ * @returns Where each file ended up
+ * @throws Naming the paths that couldn't be moved
+ */
+export async function moveToTrash(paths: string[]): Promise<string[]> {
+  return await invoke<string[]>("move_to_trash", { paths });
+}
+
+/**
+ * Act on the rm held back in a session
+ * @throws When no rm is held back in the session
+ */
+export async function resolveRmIntercept(sessionId: string, action: RmAction): Promise<void> {
+  await invoke("resolve_rm_intercept", { sessionId, action });
+}
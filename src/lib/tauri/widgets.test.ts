@@ -0,0 +1,44 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import { getMenuWidgets, type WidgetOutput } from "./widgets";
+
+// Mock preload module
+vi.mock("./preload", () => ({
+  invoke: vi.fn(),
+  checkTauriAvailable: vi.fn(() => true),
+}));
+
+import { invoke, checkTauriAvailable } from "./preload";
+
+describe("widgets.ts", () => {
+  const output: WidgetOutput = {
+    name: "weather",
+    placement: "title",
+    title: "☀ 21°C",
+    sections: [["Humidity 40%"]],
+    error: null,
+    updated_at: 1700000000000,
+  };
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    vi.mocked(checkTauriAvailable).mockReturnValue(true);
+  });
+
+  it("should get widget outputs", async () => {
+    vi.mocked(invoke).mockResolvedValue([output]);
+    expect(await getMenuWidgets()).toEqual([output]);
+    expect(invoke).toHaveBeenCalledWith("get_menu_widgets");
+  });
+
+  it("should return no outputs outside Tauri or on errors", async () => {
+    vi.mocked(checkTauriAvailable).mockReturnValue(false);
+    expect(await getMenuWidgets()).toEqual([]);
+    expect(invoke).not.toHaveBeenCalled();
+
+    vi.mocked(checkTauriAvailable).mockReturnValue(true);
+    vi.mocked(invoke).mockRejectedValue(new Error("not managed"));
+    const consoleSpy = vi.spyOn(console, "error").mockImplementation(() => {});
+    expect(await getMenuWidgets()).toEqual([]);
+    consoleSpy.mockRestore();
+  });
+});
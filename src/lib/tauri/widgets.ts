@@ -0,0 +1,47 @@
+/**
+ * Menu bar script widgets (Rust-backed)
+ *
+ * Scripts in the menu_widgets setting run on an interval and their xbar-style
+ * output is shown in the tray menu or title.
+ */
+
+import { checkTauriAvailable, invoke } from "./preload";
+
+export type WidgetPlacement = "menu" | "title";
+
+export interface MenuWidget {
+  name: string;
+  /** Script or program to run */
+  cmd: string;
+  args: string[];
+  /** Seconds between runs (5 - 86400) */
+  interval_seconds: number;
+  placement: WidgetPlacement;
+}
+
+export interface WidgetOutput {
+  name: string;
+  placement: WidgetPlacement;
+  /** First line of the output; the widget's name if the run failed or printed nothing */
+  title: string;
+  /** Menu items, grouped between separators */
+  sections: string[][];
+  /** Why the last run failed */
+  error: string | null;
+  /** Unix timestamp of the run, in milliseconds */
+  updated_at: number;
+}
+
+/**
+ * Get the latest output of the configured widgets that have run
+ */
+export async function getMenuWidgets(): Promise<WidgetOutput[]> {
+  if (!checkTauriAvailable()) return [];
+
+  try {
+    return await invoke<WidgetOutput[]>("get_menu_widgets");
+  } catch (error) {
+    console.error("Failed to get menu widgets:", error);
+    return [];
+  }
+}
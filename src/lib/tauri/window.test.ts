@@ -1,5 +1,13 @@
 import { describe, it, expect, vi, beforeEach, afterEach } from "vitest";
-import { getScreenInfo, adjustWindowSize, ensureWindowVisible } from "./window";
+import {
+  getScreenInfo,
+  adjustWindowSize,
+  ensureWindowVisible,
+  getPresentationMode,
+  setPresentationMode,
+  togglePresentationMode,
+  getDisplayPreferences,
+} from "./window";
 
 // Mock the preload module
 vi.mock("./preload", () => ({
@@ -268,6 +276,72 @@ describe("window.ts", () => {
     });
   });
 
+  describe("presentation mode", () => {
+    it("should get the presentation status", async () => {
+      const status = { mode: "auto", capture: "zoom.us", presenting: false, active: true };
+      vi.mocked(checkTauriAvailable).mockReturnValue(true);
+      vi.mocked(invoke).mockResolvedValue(status);
+
+      expect(await getPresentationMode()).toEqual(status);
+      expect(invoke).toHaveBeenCalledWith("get_presentation_mode");
+    });
+
+    it("should set the presentation mode", async () => {
+      const status = { mode: "on", capture: null, presenting: false, active: true };
+      vi.mocked(checkTauriAvailable).mockReturnValue(true);
+      vi.mocked(invoke).mockResolvedValue(status);
+
+      expect(await setPresentationMode("on")).toEqual(status);
+      expect(invoke).toHaveBeenCalledWith("set_presentation_mode", { mode: "on" });
+    });
+
+    it("should return null when Tauri is not available", async () => {
+      vi.mocked(checkTauriAvailable).mockReturnValue(false);
+
+      expect(await setPresentationMode("auto")).toBeNull();
+      expect(invoke).not.toHaveBeenCalled();
+    });
+
+    it("should toggle presenting", async () => {
+      const settings = { fontSize: 17, pinned: true };
+      vi.mocked(checkTauriAvailable).mockReturnValue(true);
+      vi.mocked(invoke).mockResolvedValue(settings);
+
+      expect(await togglePresentationMode()).toEqual(settings);
+      expect(invoke).toHaveBeenCalledWith("toggle_presentation_mode");
+    });
+
+    it("should return null when toggling fails", async () => {
+      vi.mocked(checkTauriAvailable).mockReturnValue(true);
+      vi.mocked(invoke).mockRejectedValue(new Error("failed"));
+      vi.spyOn(console, "error").mockImplementation(() => {});
+
+      expect(await togglePresentationMode()).toBeNull();
+    });
+  });
+
+  describe("getDisplayPreferences", () => {
+    it("should get the display preferences", async () => {
+      const report = {
+        reduce_motion: true,
+        increase_contrast: false,
+        system: { reduce_motion: false, increase_contrast: false },
+      };
+      vi.mocked(checkTauriAvailable).mockReturnValue(true);
+      vi.mocked(invoke).mockResolvedValue(report);
+
+      expect(await getDisplayPreferences()).toEqual(report);
+      expect(invoke).toHaveBeenCalledWith("get_display_preferences");
+    });
+
+    it("should return null when Tauri is not available", async () => {
+      vi.mocked(checkTauriAvailable).mockReturnValue(false);
+
+      expect(await getDisplayPreferences()).toBeNull();
+      expect(invoke).not.toHaveBeenCalled();
+    });
+  });
+
   describe("Integration scenarios", () => {
     it("should handle sequential calls to different functions", async () => {
       vi.mocked(checkTauriAvailable).mockReturnValue(true);
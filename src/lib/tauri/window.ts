@@ -2,7 +2,9 @@
  * Window management commands for screen size adaptation
  */
 
+import { getCurrentWindow } from "@tauri-apps/api/window";
 import { checkTauriAvailable, invoke } from "./preload";
+import type { AppSettings } from "./settings";
 
 export interface ScreenInfo {
   width: number;
@@ -10,6 +12,10 @@ export interface ScreenInfo {
   scale_factor: number;
   available_width: number;
   available_height: number;
+  /** Left edge of the screen in logical pixels */
+  x: number;
+  /** Top edge of the screen in logical pixels */
+  y: number;
 }
 
 /**
@@ -65,3 +71,165 @@ export async function ensureWindowVisible(): Promise<boolean> {
     return false;
   }
 }
+
+/** What the window is showing: the terminal panel or the command bar */
+export type WindowMode = "panel" | "command_bar" | "command_bar_expanded";
+
+/** Something that moves the window between modes */
+export type ModeTransition = "open_command_bar" | "output" | "collapse" | "open_panel";
+
+/**
+ * Get the current window mode
+ */
+export async function getWindowMode(): Promise<WindowMode> {
+  if (!checkTauriAvailable()) return "panel";
+
+  try {
+    return await invoke<WindowMode>("get_window_mode");
+  } catch (error) {
+    console.error("Failed to get window mode:", error);
+    return "panel";
+  }
+}
+
+/**
+ * Move the window to another mode; the backend resizes it with that mode's saved config
+ * @returns The resulting mode, or null if the transition failed
+ */
+export async function transitionWindowMode(transition: ModeTransition): Promise<WindowMode | null> {
+  if (!checkTauriAvailable()) return null;
+
+  try {
+    return await invoke<WindowMode>("transition_window_mode", { transition });
+  } catch (error) {
+    console.error("Failed to change window mode:", error);
+    return null;
+  }
+}
+
+/** Label of the status strip window */
+export const STATUS_STRIP_LABEL = "status-strip";
+
+export interface StatusSnapshot {
+  /** Task names of the running jobs, newest first */
+  running_jobs: string[];
+  /** Combined progress of all sessions */
+  progress: {
+    state: "hidden" | "normal" | "error" | "indeterminate" | "paused";
+    progress: number;
+  } | null;
+  /** Sessions that rang the bell while the panel was hidden */
+  bells: string[];
+}
+
+/**
+ * Whether this webview is the status strip rather than the main window
+ */
+export function isStatusStripWindow(): boolean {
+  if (!checkTauriAvailable()) return false;
+  return getCurrentWindow().label === STATUS_STRIP_LABEL;
+}
+
+/**
+ * Get the running jobs, progress and bells shown in the status strip
+ */
+export async function getStatusSnapshot(): Promise<StatusSnapshot | null> {
+  if (!checkTauriAvailable()) return null;
+
+  try {
+    return await invoke<StatusSnapshot>("get_status_snapshot");
+  } catch (error) {
+    console.error("Failed to get status snapshot:", error);
+    return null;
+  }
+}
+
+/** The `presentation_mode` setting: make the window opaque while sharing the screen, or always */
+export type PresentationMode = "off" | "auto" | "on";
+
+/** Payload of `presentation-mode-changed` */
+export interface PresentationStatus {
+  mode: PresentationMode;
+  /** What gave a screen capture away ("Screen Sharing", "zoom.us"), in auto mode */
+  capture: string | null;
+  /** Whether `togglePresentationMode` is on; the window is opaque while it is */
+  presenting: boolean;
+  /** Whether the window is forced opaque; render at full opacity without blur while set */
+  active: boolean;
+}
+
+/**
+ * Get the presentation mode and whether the window is currently forced opaque
+ */
+export async function getPresentationMode(): Promise<PresentationStatus | null> {
+  if (!checkTauriAvailable()) return null;
+
+  try {
+    return await invoke<PresentationStatus>("get_presentation_mode");
+  } catch (error) {
+    console.error("Failed to get presentation mode:", error);
+    return null;
+  }
+}
+
+/**
+ * Change the presentation mode setting
+ * @returns The resulting status, or null if it couldn't be changed
+ */
+export async function setPresentationMode(
+  mode: PresentationMode
+): Promise<PresentationStatus | null> {
+  if (!checkTauriAvailable()) return null;
+
+  try {
+    return await invoke<PresentationStatus>("set_presentation_mode", { mode });
+  } catch (error) {
+    console.error("Failed to set presentation mode:", error);
+    return null;
+  }
+}
+
+/**
+ * Start presenting (larger font, high contrast, no notifications, pinned window), or restore
+ * the settings from before
+ * @returns The new settings, or null if presenting couldn't be toggled
+ */
+export async function togglePresentationMode(): Promise<AppSettings | null> {
+  if (!checkTauriAvailable()) return null;
+
+  try {
+    return await invoke<AppSettings>("toggle_presentation_mode");
+  } catch (error) {
+    console.error("Failed to toggle presentation mode:", error);
+    return null;
+  }
+}
+
+/** Motion and contrast preferences */
+export interface DisplayPreferences {
+  reduce_motion: boolean;
+  increase_contrast: boolean;
+}
+
+/**
+ * Result of `getDisplayPreferences` and payload of `display-preferences-changed`: the values
+ * to follow (system preferences with the `reduce_motion` / `increase_contrast` overrides
+ * applied), plus what macOS reports
+ */
+export interface DisplayPreferencesReport extends DisplayPreferences {
+  system: DisplayPreferences;
+}
+
+/**
+ * Get the effective Reduce Motion and Increase Contrast preferences
+ */
+export async function getDisplayPreferences(): Promise<DisplayPreferencesReport | null> {
+  if (!checkTauriAvailable()) return null;
+
+  try {
+    return await invoke<DisplayPreferencesReport>("get_display_preferences");
+  } catch (error) {
+    console.error("Failed to get display preferences:", error);
+    return null;
+  }
+}
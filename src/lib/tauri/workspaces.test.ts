@@ -0,0 +1,61 @@
+import { describe, it, expect, vi, beforeEach } from "vitest";
+import {
+  listWorkspaces,
+  saveWorkspace,
+  deleteWorkspace,
+  openWorkspace,
+  type Workspace,
+} from "./workspaces";
+
+// Mock preload module
+vi.mock("./preload", () => ({
+  getInvoke: vi.fn(),
+}));
+
+import { getInvoke } from "./preload";
+
+describe("workspaces.ts", () => {
+  const mockInvoke = vi.fn();
+  const api: Workspace = {
+    name: "api",
+    sessions: [
+      { title: "server", cwd: "~/api", startup_command: "npm run dev" },
+      { cwd: "~/api", profile: "work" },
+    ],
+    env: { NODE_ENV: "development" },
+    window: { x: 0, y: 25, width: 1200, height: 800 },
+  };
+
+  beforeEach(() => {
+    vi.clearAllMocks();
+    vi.mocked(getInvoke).mockResolvedValue(mockInvoke);
+  });
+
+  it("should list workspaces", async () => {
+    mockInvoke.mockResolvedValue([api]);
+    expect(await listWorkspaces()).toEqual([api]);
+    expect(mockInvoke).toHaveBeenCalledWith("list_workspaces");
+  });
+
+  it("should save and delete workspaces", async () => {
+    mockInvoke.mockResolvedValueOnce(api).mockResolvedValueOnce(true);
+    expect(await saveWorkspace(api, true)).toEqual(api);
+    expect(mockInvoke).toHaveBeenCalledWith("save_workspace", {
+      workspace: api,
+      captureWindow: true,
+    });
+    expect(await deleteWorkspace("api")).toBe(true);
+    expect(mockInvoke).toHaveBeenCalledWith("delete_workspace", { name: "api" });
+  });
+
+  it("should open a workspace", async () => {
+    mockInvoke.mockResolvedValue(["session-1", "session-2"]);
+    expect(await openWorkspace("api")).toEqual(["session-1", "session-2"]);
+    expect(mockInvoke).toHaveBeenCalledWith("open_workspace", { name: "api" });
+  });
+
+  it("should propagate unknown workspaces", async () => {
+    mockInvoke.mockRejectedValue("Workspace not found: missing");
+    await expect(openWorkspace("missing")).rejects.toBe("Workspace not found: missing");
+  });
+});
@@ -0,0 +1,74 @@
+/**
+ * Workspaces: named sets of sessions and window placement, opened in one go (Rust-backed)
+ */
+
+import { getInvoke } from "./preload";
+
+export interface WorkspaceSession {
+  /** Tab title; defaults to the workspace name */
+  title?: string | null;
+  /** Starting directory; a leading ~ is the home directory */
+  cwd?: string | null;
+  /** Profile to start with instead of the directory's */
+  profile?: string | null;
+  startup_command?: string | null;
+  /** Set after the workspace's variables */
+  env?: Record<string, string>;
+}
+
+/** Window frame in logical pixels */
+export interface WorkspaceWindow {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+export interface Workspace {
+  name: string;
+  sessions: WorkspaceSession[];
+  /** Environment variables for all of its sessions */
+  env?: Record<string, string>;
+  /** Where to put the window; null leaves it where it is */
+  window?: WorkspaceWindow | null;
+}
+
+/**
+ * Get the workspaces from the settings
+ */
+export async function listWorkspaces(): Promise<Workspace[]> {
+  const invoke = await getInvoke();
+  return invoke<Workspace[]>("list_workspaces");
+}
+
+/**
+ * Add a workspace, or replace the one with the same name
+ * @param captureWindow - Record the window's current frame as its placement
+ * @returns The workspace as saved
+ */
+export async function saveWorkspace(
+  workspace: Workspace,
+  captureWindow?: boolean
+): Promise<Workspace> {
+  const invoke = await getInvoke();
+  return invoke<Workspace>("save_workspace", { workspace, captureWindow });
+}
+
+/**
+ * Remove a workspace
+ * @returns false if there was no workspace with the name
+ */
+export async function deleteWorkspace(name: string): Promise<boolean> {
+  const invoke = await getInvoke();
+  return invoke<boolean>("delete_workspace", { name });
+}
+
+/**
+ * Open a workspace's sessions in this window (they arrive through `session-opened`)
+ * and move the window into place
+ * @returns Session IDs, in the workspace's order
+ */
+export async function openWorkspace(name: string): Promise<string[]> {
+  const invoke = await getInvoke();
+  return invoke<string[]>("open_workspace", { name });
+}
@@ -0,0 +1,25 @@
+import { describe, it, expect, vi } from "vitest";
+import type { Terminal } from "@xterm/xterm";
+import { isDeviceAttributesQuery, suppressDeviceAttributesReplies } from "./deviceReports";
+
+describe("deviceReports", () => {
+  it("should recognize plain Device Attributes queries", () => {
+    expect(isDeviceAttributesQuery([])).toBe(true);
+    expect(isDeviceAttributesQuery([0])).toBe(true);
+    expect(isDeviceAttributesQuery([1])).toBe(false);
+    expect(isDeviceAttributesQuery([0, 1])).toBe(false);
+  });
+
+  it("should register primary and secondary DA handlers", () => {
+    const registerCsiHandler = vi.fn();
+    const terminal = { parser: { registerCsiHandler } } as unknown as Terminal;
+
+    suppressDeviceAttributesReplies(terminal);
+
+    expect(registerCsiHandler).toHaveBeenCalledWith({ final: "c" }, isDeviceAttributesQuery);
+    expect(registerCsiHandler).toHaveBeenCalledWith(
+      { prefix: ">", final: "c" },
+      isDeviceAttributesQuery
+    );
+  });
+});
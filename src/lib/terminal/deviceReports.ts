@@ -0,0 +1,26 @@
+/**
+ * Terminal identification replies
+ *
+ * The backend answers Device Attributes queries (`CSI c`, `CSI > c`) from the
+ * user's terminal ID settings. xterm.js would answer them too, so its built-in
+ * replies are suppressed here to keep programs from seeing two.
+ */
+
+import type { Terminal } from "@xterm/xterm";
+
+type CsiParams = (number | number[])[];
+
+/** Whether a DA request is the plain query (no parameter or 0) */
+export function isDeviceAttributesQuery(params: CsiParams): boolean {
+  return params.length === 0 || (params.length === 1 && params[0] === 0);
+}
+
+/**
+ * Stop xterm.js from replying to Device Attributes queries
+ *
+ * @param terminal - xterm.js Terminal instance
+ */
+export function suppressDeviceAttributesReplies(terminal: Terminal): void {
+  terminal.parser.registerCsiHandler({ final: "c" }, isDeviceAttributesQuery);
+  terminal.parser.registerCsiHandler({ prefix: ">", final: "c" }, isDeviceAttributesQuery);
+}
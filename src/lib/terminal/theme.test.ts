@@ -215,6 +215,21 @@ describe("getTerminalTheme", () => {
     });
   });
 
+  describe("High Contrast", () => {
+    it("should use brighter colors on an opaque background", () => {
+      const theme = getTerminalTheme(0.8, true);
+
+      expect(theme.foreground).toBe("#ffffff");
+      expect(theme.background).toBe("rgba(0, 0, 0, 1)");
+      expect(theme.red).toBe(getTerminalTheme(0.8).red);
+    });
+
+    it("should cache high contrast themes separately", () => {
+      expect(getTerminalTheme(1, true)).toBe(getTerminalTheme(0.5, true));
+      expect(getTerminalTheme(1, true)).not.toBe(getTerminalTheme(1));
+    });
+  });
+
   describe("Type Safety", () => {
     it("should return TerminalTheme type with all required fields", () => {
       const theme = getTerminalTheme(0.95);
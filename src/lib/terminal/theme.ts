@@ -25,27 +25,36 @@ const BASE_THEME = {
   brightWhite: "#e6e6e6",
 } as const;
 
-export type TerminalTheme = typeof BASE_THEME & { background: string };
+// Brighter text, dim colors and selection for Increase Contrast
+const HIGH_CONTRAST_COLORS = {
+  foreground: "#ffffff",
+  selectionBackground: "#ffffff60",
+  brightBlack: "#8b93a5",
+} as const;
+
+export type TerminalTheme = Record<keyof typeof BASE_THEME | "background", string>;
 
 // Theme cache to avoid recreating objects
-const themeCache = new Map<number, TerminalTheme>();
+const themeCache = new Map<string, TerminalTheme>();
 
 /**
  * Get theme with specified opacity
  * The terminal background has the opacity - container should be transparent
  * @param opacity - Background opacity (0-1), defaults to 0.95 if not provided
+ * @param highContrast - Follow Increase Contrast: brighter colors on an opaque background
  * @returns Terminal theme object with background color
  */
-export function getTerminalTheme(opacity?: number): TerminalTheme {
+export function getTerminalTheme(opacity?: number, highContrast = false): TerminalTheme {
   // Default to 0.95 if undefined to prevent rgba(0, 0, 0, NaN)
-  const validOpacity = opacity ?? 0.95;
+  const validOpacity = highContrast ? 1 : (opacity ?? 0.95);
   const clampedOpacity = Math.max(0, Math.min(1, validOpacity));
-  const cacheKey = Math.round(clampedOpacity * 100);
+  const cacheKey = `${highContrast ? "hc" : ""}${Math.round(clampedOpacity * 100)}`;
 
   let theme = themeCache.get(cacheKey);
   if (!theme) {
     theme = {
       ...BASE_THEME,
+      ...(highContrast ? HIGH_CONTRAST_COLORS : {}),
       background: `rgba(0, 0, 0, ${clampedOpacity})`,
     };
     themeCache.set(cacheKey, theme);
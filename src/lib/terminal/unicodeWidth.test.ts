@@ -0,0 +1,78 @@
+import type { IUnicodeVersionProvider, Terminal } from "@xterm/xterm";
+import { describe, expect, it, vi } from "vitest";
+import {
+  applyUnicodeWidthMode,
+  createUnicodeWidthProvider,
+  isAmbiguous,
+  unicodeWidthVersion,
+} from "./unicodeWidth";
+
+const VS16 = 0xfe0f;
+
+/** Narrow everywhere except CJK; joins zero-width characters like xterm.js */
+const base: IUnicodeVersionProvider = {
+  version: "base",
+  wcwidth: (codepoint) => (codepoint === VS16 ? 0 : codepoint >= 0x4e00 ? 2 : 1),
+  charProperties(this: IUnicodeVersionProvider, codepoint, preceding) {
+    const width = this.wcwidth(codepoint);
+    const join = width === 0 && preceding !== 0 ? 1 : 0;
+    return (width << 1) | join;
+  },
+};
+
+const widthOf = (properties: number) => (properties >> 1) & 0b11;
+
+describe("unicodeWidth", () => {
+  it("should classify East Asian Ambiguous code points", () => {
+    expect(isAmbiguous(0x2500)).toBe(true); // ─
+    expect(isAmbiguous(0x00b1)).toBe(true); // ±
+    expect(isAmbiguous(0x41)).toBe(false); // A
+    expect(isAmbiguous(0x4e00)).toBe(false); // 一
+  });
+
+  it("should widen ambiguous characters only in wide mode", () => {
+    const wide = createUnicodeWidthProvider({ ambiguous: "wide", emoji_vs16_wide: false }, base);
+    const narrow = createUnicodeWidthProvider(
+      { ambiguous: "narrow", emoji_vs16_wide: false },
+      base
+    );
+
+    expect(wide.wcwidth(0x2500)).toBe(2);
+    expect(widthOf(wide.charProperties(0x2500, 0))).toBe(2);
+    expect(wide.wcwidth(0x41)).toBe(1);
+    expect(narrow.wcwidth(0x2500)).toBe(1);
+    expect(widthOf(narrow.charProperties(0x2500, 0))).toBe(1);
+  });
+
+  it("should make VS16 sequences two cells wide when enabled", () => {
+    const vs16 = createUnicodeWidthProvider({ ambiguous: "narrow", emoji_vs16_wide: true }, base);
+    const plain = createUnicodeWidthProvider(
+      { ambiguous: "narrow", emoji_vs16_wide: false },
+      base
+    );
+    const preceding = plain.charProperties(0x2764, 0); // ❤
+
+    expect(widthOf(vs16.charProperties(VS16, preceding))).toBe(2);
+    expect(vs16.charProperties(VS16, preceding) & 1).toBe(1);
+    expect(widthOf(plain.charProperties(VS16, preceding))).toBe(0);
+  });
+
+  it("should register each mode once and activate it", () => {
+    const mode = { ambiguous: "wide", emoji_vs16_wide: true } as const;
+    const unicode = {
+      versions: [] as string[],
+      activeVersion: "",
+      register: vi.fn((provider: IUnicodeVersionProvider) => {
+        unicode.versions.push(provider.version);
+      }),
+    };
+    const terminal = { unicode } as unknown as Terminal;
+
+    applyUnicodeWidthMode(terminal, mode, base);
+    applyUnicodeWidthMode(terminal, mode, base);
+
+    expect(unicode.register).toHaveBeenCalledTimes(1);
+    expect(unicode.activeVersion).toBe(unicodeWidthVersion(mode));
+    expect(unicode.activeVersion).toBe("microterm-wide-vs16");
+  });
+});
@@ -1,10 +1,10 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import StatusStrip from "./components/StatusStrip";
+import { isStatusStripWindow } from "./lib/tauri/window";
 import "./styles/global.css";
 
 ReactDOM.createRoot(document.getElementById("root")!).render(
-  <React.StrictMode>
-    <App />
-  </React.StrictMode>
+  <React.StrictMode>{isStatusStripWindow() ? <StatusStrip /> : <App />}</React.StrictMode>
 );
@@ -24,3 +24,21 @@ globalStyle("#root", {
   height: "100%",
   overflow: "hidden",
 });
+
+// Reduce Motion (see lib/displayPreferences): no animations or transitions
+globalStyle(
+  [
+    "html[data-reduce-motion] *",
+    "html[data-reduce-motion] *::before",
+    "html[data-reduce-motion] *::after",
+  ].join(", "),
+  {
+    animation: "none !important",
+    transition: "none !important",
+  }
+);
+
+// Increase Contrast: brighter default text
+globalStyle("html[data-increase-contrast] body", {
+  color: "#ffffff",
+});